@@ -0,0 +1,87 @@
+//! Benchmarks of the loading of registries from bundles, compared to directories of JSON files.
+//!
+//! ```sh
+//! cargo bench --bench bundle --features zstd
+//! ```
+//!
+//! Median times measured with `--warm-up-time 1 --measurement-time 3`, on a single core of a
+//! Linux x86-64 virtual machine, with Rust 1.95, each type definition being in its own file:
+//!
+//! | Type definitions | `bundle` | `directory` |
+//! | ---------------- | -------- | ----------- |
+//! | 100              | 166 µs   | 580 µs      |
+//! | 1000             | 1.44 ms  | 7.35 ms     |
+//! | 10000            | 20.0 ms  | 140 ms      |
+//!
+//! More than half of the time of the bundles is spent registering the type definitions again,
+//! which the directories do as well (see the `registry` benchmark): a binary payload would only
+//! save the JSON parsing.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use gameson::{TypeAttributes, TypeDefinition, TypeDefinitionRegistry};
+
+/// The sizes of the benchmarked registries.
+const SIZES: [u32; 3] = [100, 1_000, 10_000];
+
+/// Build the type definitions of a registry of the specified size.
+fn type_definitions(size: u32) -> Vec<TypeDefinition<u32, String>> {
+    (0..size)
+        .map(|id| TypeDefinition {
+            id,
+            name: format!("Type{id}"),
+            description: Some(format!("The type number {id}.")),
+            attributes: TypeAttributes::Int32(Default::default()),
+        })
+        .collect()
+}
+
+fn loads(c: &mut Criterion) {
+    let mut group = c.benchmark_group("loads");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let tds = type_definitions(size);
+
+        let mut registry = TypeDefinitionRegistry::default();
+        registry.register(tds.clone()).into_result().unwrap();
+        let mut bundle = Vec::new();
+        registry.save_bundle(&mut bundle).unwrap();
+
+        let dir = std::env::temp_dir().join(format!(
+            "gameson-bench-bundle-{}-{size}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for td in &tds {
+            std::fs::write(
+                dir.join(format!("{}.json", td.name)),
+                serde_json::to_vec(td).unwrap(),
+            )
+            .unwrap();
+        }
+
+        group.bench_with_input(BenchmarkId::new("bundle", size), &bundle, |b, bundle| {
+            b.iter(|| {
+                black_box(
+                    TypeDefinitionRegistry::<u32, String>::load_bundle(bundle.as_slice()).unwrap(),
+                );
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("directory", size), &dir, |b, dir| {
+            b.iter(|| {
+                let mut registry = TypeDefinitionRegistry::<u32, String>::default();
+                black_box(registry.load_from_dir(dir).unwrap());
+            });
+        });
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, loads);
+criterion_main!(benches);
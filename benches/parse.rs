@@ -0,0 +1,78 @@
+//! Benchmarks of the parsing of clean documents.
+//!
+//! Most documents are valid: parsing them must not pay for the error reporting it does not
+//! need, such as copying the keys of dictionaries into the path of the parsed value.
+
+use std::{hint::black_box, sync::Arc};
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use gameson::{TypeDefinition, TypeDefinitionInstance, TypeDefinitionRegistry, Value};
+use serde_json::json;
+
+/// The number of characters in the document.
+const CHARACTERS: usize = 1_000;
+
+/// Build the type instance of a document: characters by name, with stats by enum name.
+fn instance() -> Arc<TypeDefinitionInstance<u32, String>> {
+    let tds: Vec<TypeDefinition<u32, String>> = serde_json::from_value(json!([
+        { "id": 1, "name": "Name", "type": "string", "attributes": {} },
+        {
+            "id": 2,
+            "name": "Stat",
+            "type": "enum",
+            "attributes": { "values": { "strength": {}, "agility": {}, "wisdom": {} } },
+        },
+        { "id": 3, "name": "Points", "type": "int32", "attributes": {} },
+        {
+            "id": 4,
+            "name": "Stats",
+            "type": "dictionary",
+            "attributes": { "keys_type_id": 2, "values_type_id": 3 },
+        },
+        {
+            "id": 5,
+            "name": "Characters",
+            "type": "dictionary",
+            "attributes": { "keys_type_id": 1, "values_type_id": 4 },
+        },
+    ]))
+    .unwrap();
+
+    let mut registry = TypeDefinitionRegistry::default();
+    registry.register(tds).into_result().unwrap();
+
+    Arc::clone(registry.get(&5).unwrap())
+}
+
+/// Build a clean document.
+fn document() -> serde_json::Value {
+    (0..CHARACTERS)
+        .map(|i| {
+            (
+                format!("character-{i}"),
+                json!({ "strength": i % 20, "agility": i % 15, "wisdom": i % 10 }),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+fn parse(c: &mut Criterion) {
+    let instance = instance();
+    let document = document();
+    let text = document.to_string();
+
+    c.bench_function("parse_for", |b| {
+        b.iter_batched(
+            || document.clone(),
+            |document| black_box(Value::parse_for(Arc::clone(&instance), document).unwrap()),
+            criterion::BatchSize::LargeInput,
+        );
+    });
+    c.bench_function("parse_str_for", |b| {
+        b.iter(|| black_box(Value::parse_str_for(Arc::clone(&instance), &text).unwrap()));
+    });
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+//! Benchmarks of the registry lookups and registrations.
+//!
+//! Run them with each hasher to compare them:
+//!
+//! ```sh
+//! cargo bench --bench registry
+//! cargo bench --bench registry --features hash-map
+//! cargo bench --bench registry --features ahash
+//! cargo bench --bench registry --features fxhash
+//! ```
+//!
+//! Median times measured with `--warm-up-time 1 --measurement-time 3`, on a single core of a
+//! Linux x86-64 virtual machine, with Rust 1.95:
+//!
+//! | Benchmark               | default  | `hash-map` | `ahash`   | `fxhash`  |
+//! | ----------------------- | -------- | ---------- | --------- | --------- |
+//! | `lookups/by_id/100`     | 1.47 µs  | 1.25 µs    | 0.46 µs   | 0.28 µs   |
+//! | `lookups/by_name/100`   | 4.20 µs  | 2.09 µs    | 1.10 µs   | 0.62 µs   |
+//! | `lookups/by_id/1000`    | 34.9 µs  | 10.8 µs    | 3.07 µs   | 2.26 µs   |
+//! | `lookups/by_name/1000`  | 85.9 µs  | 20.5 µs    | 8.75 µs   | 8.26 µs   |
+//! | `lookups/by_id/10000`   | 485 µs   | 172 µs     | 30.6 µs   | 26.0 µs   |
+//! | `lookups/by_name/10000` | 1.16 ms  | 359 µs     | 156 µs    | 143 µs    |
+//! | `registrations/100`     | 76.5 µs  | 87.0 µs    | 69.4 µs   | 86.6 µs   |
+//! | `registrations/1000`    | 1.05 ms  | 752 µs     | 648 µs    | 769 µs    |
+//! | `registrations/10000`   | 13.7 ms  | 7.67 ms    | 7.19 ms   | 8.58 ms   |
+//!
+//! Lookups are dominated by the hasher. Registrations are dominated by the checks of the type
+//! definitions, and are noisier: some of their confidence intervals span 30% of the median.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use gameson::{TypeAttributes, TypeDefinition, TypeDefinitionRegistry};
+
+/// The sizes of the benchmarked registries.
+const SIZES: [u32; 3] = [100, 1_000, 10_000];
+
+/// Build the type definitions of a registry of the specified size.
+fn type_definitions(size: u32) -> Vec<TypeDefinition<u32, String>> {
+    (0..size)
+        .map(|id| TypeDefinition {
+            id,
+            name: format!("Type{id}"),
+            description: None,
+            attributes: TypeAttributes::Int32(Default::default()),
+        })
+        .collect()
+}
+
+fn lookups(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookups");
+
+    for size in SIZES {
+        let mut registry = TypeDefinitionRegistry::default();
+        registry
+            .register(type_definitions(size))
+            .into_result()
+            .unwrap();
+        let names: Vec<_> = (0..size).map(|id| format!("Type{id}")).collect();
+
+        group.bench_with_input(BenchmarkId::new("by_id", size), &size, |b, &size| {
+            b.iter(|| {
+                for id in 0..size {
+                    black_box(registry.get(&id));
+                }
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("by_name", size), &names, |b, names| {
+            b.iter(|| {
+                for name in names {
+                    black_box(registry.get_by_name(name.as_str()));
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn registrations(c: &mut Criterion) {
+    let mut group = c.benchmark_group("registrations");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let tds = type_definitions(size);
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &tds, |b, tds| {
+            b.iter(|| {
+                let mut registry = TypeDefinitionRegistry::default();
+                black_box(registry.register(tds.clone()).into_result().unwrap());
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, lookups, registrations);
+criterion_main!(benches);
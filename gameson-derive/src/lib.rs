@@ -0,0 +1,319 @@
+//! Derive macros for GameSON types.
+//!
+//! This crate is re-exported by the `gameson` crate, with its `derive` feature.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Attribute, Data, DataEnum, DeriveInput, Expr, Fields, LitStr, Type, parse_macro_input,
+    spanned::Spanned,
+};
+
+/// Derive `GameSonType` for an enum whose variants have no fields, or for a newtype struct.
+///
+/// See the documentation of the `GameSonType` trait for the supported attributes.
+#[proc_macro_derive(GameSonType, attributes(gameson))]
+pub fn derive_gameson_type(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// The attributes of a type.
+#[derive(Default)]
+struct TypeAttributes {
+    /// The name of the GameSON type.
+    name: Option<LitStr>,
+
+    /// The attributes of the GameSON type, for newtype structs.
+    attributes: Vec<(&'static str, Expr)>,
+}
+
+/// The attributes of an enum variant.
+#[derive(Default)]
+struct VariantAttributes {
+    /// The name of the enum value.
+    rename: Option<LitStr>,
+
+    /// The aliases of the enum value.
+    aliases: Vec<LitStr>,
+
+    /// Whether the enum value is the default one.
+    default: bool,
+}
+
+/// Get the `#[gameson(...)]` attributes of a type.
+fn type_attributes(attrs: &[Attribute]) -> syn::Result<TypeAttributes> {
+    let mut attributes = TypeAttributes::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("gameson")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                attributes.name = Some(meta.value()?.parse()?);
+            } else if let Some(key) = ["min", "max", "default"]
+                .into_iter()
+                .find(|key| meta.path.is_ident(key))
+            {
+                attributes.attributes.push((key, meta.value()?.parse()?));
+            } else {
+                return Err(meta.error("unsupported attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(attributes)
+}
+
+/// Get the `#[gameson(...)]` attributes of an enum variant.
+fn variant_attributes(attrs: &[Attribute]) -> syn::Result<VariantAttributes> {
+    let mut attributes = VariantAttributes::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("gameson")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                attributes.rename = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("alias") {
+                attributes.aliases.push(meta.value()?.parse()?);
+            } else if meta.path.is_ident("default") {
+                attributes.default = true;
+            } else {
+                return Err(meta.error("unsupported attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(attributes)
+}
+
+/// Get the description from the doc comments, if any.
+fn description(attrs: &[Attribute]) -> TokenStream {
+    let lines: Vec<_> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta.require_name_value().ok()?.value {
+            Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) => Some(lit.value().trim().to_owned()),
+            _ => None,
+        })
+        .collect();
+
+    if lines.is_empty() {
+        quote!(::core::option::Option::None::<&str>)
+    } else {
+        let description = lines.join("\n");
+
+        quote!(::core::option::Option::Some(#description))
+    }
+}
+
+/// Derive `GameSonType` for a type.
+fn derive(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let attributes = type_attributes(&input.attrs)?;
+    let name = attributes
+        .name
+        .clone()
+        .unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+    let description = description(&input.attrs);
+
+    let body = match &input.data {
+        Data::Enum(data) => {
+            if let Some((key, expr)) = attributes.attributes.first() {
+                return Err(syn::Error::new(
+                    expr.span(),
+                    format!("`{key}` is only supported on newtype structs"),
+                ));
+            }
+
+            derive_enum(data, &description)?
+        }
+        Data::Struct(data) => match &data.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                derive_newtype(&fields.unnamed[0].ty, &attributes, &description)
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    input.span(),
+                    "GameSON has no record types: only newtype structs are supported",
+                ));
+            }
+        },
+        Data::Union(_) => {
+            return Err(syn::Error::new(input.span(), "unions are not supported"));
+        }
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        #[allow(deprecated)]
+        impl #impl_generics ::gameson::GameSonType for #ident #ty_generics #where_clause {
+            fn type_name() -> ::std::string::String {
+                ::std::string::String::from(#name)
+            }
+
+            #body
+        }
+    })
+}
+
+/// Get an expression building a type definition with the specified type and attributes.
+fn type_definition(
+    description: &TokenStream,
+    r#type: TokenStream,
+    attributes: TokenStream,
+) -> TokenStream {
+    quote! {
+        ::gameson::__private::serde_json::from_value(::gameson::__private::serde_json::json!({
+            "name": Self::type_name(),
+            "description": #description,
+            "type": #r#type,
+            "attributes": #attributes,
+        }))
+        .unwrap_or_else(|err| {
+            ::core::panic!("invalid GameSON type `{}`: {err}", Self::type_name())
+        })
+    }
+}
+
+/// Get the body of the implementation of `GameSonType` for an enum.
+fn derive_enum(data: &DataEnum, description: &TokenStream) -> syn::Result<TokenStream> {
+    let mut values = Vec::new();
+    let mut aliases = Vec::new();
+    let mut default = quote!(::core::option::Option::None::<&str>);
+    let mut to_json = Vec::new();
+    let mut from_json = Vec::new();
+
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new(
+                variant.span(),
+                "only enums whose variants have no fields are supported",
+            ));
+        }
+
+        let ident = &variant.ident;
+        let attributes = variant_attributes(&variant.attrs)?;
+        let value = attributes
+            .rename
+            .unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+        let value_description = crate::description(&variant.attrs);
+        let deprecated = variant
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("deprecated"));
+        let names = std::iter::once(&value).chain(&attributes.aliases);
+
+        values.push(quote! {
+            #value: {
+                "description": #value_description,
+                "deprecated": #deprecated,
+            }
+        });
+        aliases.extend(
+            attributes
+                .aliases
+                .iter()
+                .map(|alias| quote!(#alias: #value)),
+        );
+
+        if attributes.default {
+            default = quote!(::core::option::Option::Some(#value));
+        }
+
+        to_json.push(quote! {
+            Self::#ident => ::gameson::__private::serde_json::Value::from(#value)
+        });
+        from_json.push(quote! {
+            #(#names)|* => ::core::option::Option::Some(Self::#ident)
+        });
+    }
+
+    let type_definition = type_definition(
+        description,
+        quote!("enum"),
+        quote!({
+            "values": { #(#values),* },
+            "aliases": { #(#aliases),* },
+            "default": #default,
+        }),
+    );
+
+    Ok(quote! {
+        fn type_definition() -> ::gameson::UnidentifiedTypeDefinition<::std::string::String> {
+            #type_definition
+        }
+
+        fn to_json(&self) -> ::gameson::__private::serde_json::Value {
+            match self {
+                #(#to_json,)*
+            }
+        }
+
+        fn from_json(
+            json: &::gameson::__private::serde_json::Value,
+        ) -> ::core::option::Option<Self> {
+            match json.as_str()? {
+                #(#from_json,)*
+                _ => ::core::option::Option::None,
+            }
+        }
+    })
+}
+
+/// Get the body of the implementation of `GameSonType` for a newtype struct.
+fn derive_newtype(
+    inner: &Type,
+    attributes: &TypeAttributes,
+    description: &TokenStream,
+) -> TokenStream {
+    let keys = attributes.attributes.iter().map(|(key, _)| key);
+    let exprs = attributes.attributes.iter().map(|(_, expr)| expr);
+    let inner_trait = quote!(<#inner as ::gameson::__private::Inner>);
+    let type_definition = type_definition(description, quote!(r#type), quote!(attributes));
+
+    quote! {
+        fn type_definition() -> ::gameson::UnidentifiedTypeDefinition<::std::string::String> {
+            let mut attributes = ::gameson::__private::serde_json::Map::new();
+            #(
+                attributes.insert(
+                    ::std::string::String::from(#keys),
+                    ::gameson::__private::serde_json::json!(#exprs),
+                );
+            )*
+            let (r#type, attributes) = #inner_trait::type_and_attributes(attributes);
+
+            #type_definition
+        }
+
+        fn collect_type_definitions(
+            type_definitions: &mut ::std::collections::BTreeMap<
+                ::std::string::String,
+                ::gameson::UnidentifiedTypeDefinition<::std::string::String>,
+            >,
+        ) {
+            if !type_definitions.contains_key(&Self::type_name()) {
+                type_definitions.insert(Self::type_name(), Self::type_definition());
+                #inner_trait::collect_type_definitions(type_definitions);
+            }
+        }
+
+        fn to_json(&self) -> ::gameson::__private::serde_json::Value {
+            #inner_trait::to_json(&self.0)
+        }
+
+        fn from_json(
+            json: &::gameson::__private::serde_json::Value,
+        ) -> ::core::option::Option<Self> {
+            #inner_trait::from_json(json).map(Self)
+        }
+    }
+}
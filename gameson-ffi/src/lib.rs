@@ -0,0 +1,82 @@
+//! The C ABI of GameSON, built as dynamic and static libraries for native engines.
+//!
+//! The functions are defined by the [`gameson::ffi`] module, and declared in
+//! `include/gameson.h`.
+
+pub use gameson::ffi::*;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    /// The declarations of the C header.
+    const HEADER: &str = include_str!("../../include/gameson.h");
+
+    #[test]
+    fn test_header_declares_exported_functions() {
+        // Taking the address of every function checks that they are all exported.
+        let exported = [
+            ("gameson_registry_new", gameson_registry_new as *const ()),
+            ("gameson_registry_free", gameson_registry_free as *const ()),
+            (
+                "gameson_registry_register_json",
+                gameson_registry_register_json as *const (),
+            ),
+            (
+                "gameson_registry_validate_json",
+                gameson_registry_validate_json as *const (),
+            ),
+            (
+                "gameson_registry_validate_json_by_id",
+                gameson_registry_validate_json_by_id as *const (),
+            ),
+            ("gameson_string_free", gameson_string_free as *const ()),
+        ];
+
+        // The declared functions are the identifiers followed by their parameters.
+        let declared: BTreeSet<_> = HEADER
+            .split('(')
+            .filter_map(|s| {
+                s.rsplit(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .next()
+            })
+            .filter(|name| name.starts_with("gameson_"))
+            .collect();
+
+        assert_eq!(
+            declared,
+            exported
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<BTreeSet<_>>()
+        );
+    }
+
+    #[test]
+    fn test_header_declares_statuses() {
+        for (name, status) in [
+            ("GAMESON_STATUS_OK", GameSonStatus::Ok),
+            (
+                "GAMESON_STATUS_INVALID_ARGUMENT",
+                GameSonStatus::InvalidArgument,
+            ),
+            ("GAMESON_STATUS_INVALID_JSON", GameSonStatus::InvalidJson),
+            (
+                "GAMESON_STATUS_REGISTRATION_FAILED",
+                GameSonStatus::RegistrationFailed,
+            ),
+            ("GAMESON_STATUS_UNKNOWN_TYPE", GameSonStatus::UnknownType),
+            ("GAMESON_STATUS_INVALID_VALUE", GameSonStatus::InvalidValue),
+            (
+                "GAMESON_STATUS_INTERNAL_ERROR",
+                GameSonStatus::InternalError,
+            ),
+        ] {
+            let declaration = format!("{name} = {},", status as i32);
+
+            assert!(HEADER.contains(&declaration), "missing `{declaration}`");
+        }
+    }
+}
@@ -0,0 +1,68 @@
+/*
+ * C ABI of the GameSON library, enabled by its `ffi` feature and built as dynamic and static
+ * libraries by the `gameson-ffi` crate.
+ *
+ * Registries identify their types with 64-bit unsigned integers and name them with strings. All
+ * the strings are NUL-terminated and UTF-8 encoded. The error messages returned through the
+ * `error` out-parameters, which can be null, are owned by the caller and must be freed with
+ * `gameson_string_free`. Handles are not thread-safe.
+ */
+
+#ifndef GAMESON_H
+#define GAMESON_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A registry of type definitions. */
+typedef struct GameSonRegistry GameSonRegistry;
+
+/* The status of a call. */
+typedef enum GameSonStatus {
+    /* The call succeeded. */
+    GAMESON_STATUS_OK = 0,
+    /* A pointer was null, or a string was not valid UTF-8. */
+    GAMESON_STATUS_INVALID_ARGUMENT = 1,
+    /* The JSON text was invalid, or did not describe type definitions. */
+    GAMESON_STATUS_INVALID_JSON = 2,
+    /* Some type definitions could not be registered. */
+    GAMESON_STATUS_REGISTRATION_FAILED = 3,
+    /* The type is not registered. */
+    GAMESON_STATUS_UNKNOWN_TYPE = 4,
+    /* The value is not valid for its type. */
+    GAMESON_STATUS_INVALID_VALUE = 5,
+    /* The library panicked, which is a bug: the handles involved should only be freed. */
+    GAMESON_STATUS_INTERNAL_ERROR = 6,
+} GameSonStatus;
+
+/* Create an empty registry, to be freed with `gameson_registry_free`, or null on failure. */
+GameSonRegistry *gameson_registry_new(void);
+
+/* Free a registry. */
+void gameson_registry_free(GameSonRegistry *registry);
+
+/* Register type definitions, given as a JSON array. */
+GameSonStatus gameson_registry_register_json(GameSonRegistry *registry, const char *json,
+                                             char **error);
+
+/* Validate a JSON value against a registered type, designated by its name. */
+GameSonStatus gameson_registry_validate_json(const GameSonRegistry *registry,
+                                             const char *type_name, const char *json,
+                                             char **error);
+
+/* Validate a JSON value against a registered type, designated by its identifier. */
+GameSonStatus gameson_registry_validate_json_by_id(const GameSonRegistry *registry,
+                                                   uint64_t type_id, const char *json,
+                                                   char **error);
+
+/* Free a string returned by the library. */
+void gameson_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GAMESON_H */
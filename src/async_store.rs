@@ -0,0 +1,193 @@
+//! Asynchronous persistence of type definitions.
+
+use std::{collections::BTreeSet, fmt::Display, hash::Hash};
+
+use tokio::sync::RwLock;
+
+use crate::{RegistrationOutcome, TypeDefinition, TypeDefinitionRegistry, TypeDefinitionStore};
+
+/// An asynchronous storage of type definitions, such as a remote service.
+///
+/// This is the asynchronous counterpart of [`TypeDefinitionStore`]. As such stores are typically
+/// shared handles to a service, all their methods take `&self`.
+pub trait AsyncTypeDefinitionStore<Id, FieldName: Ord + Display + Clone> {
+    /// The error that can occur when accessing the store.
+    type Error: std::error::Error;
+
+    /// Load all the type definitions of the store.
+    fn load_all(
+        &self,
+    ) -> impl Future<Output = Result<Vec<TypeDefinition<Id, FieldName>>, Self::Error>> + Send;
+
+    /// Load the type definition with the specified identifier, if it exists.
+    fn load_by_id(
+        &self,
+        id: &Id,
+    ) -> impl Future<Output = Result<Option<TypeDefinition<Id, FieldName>>, Self::Error>> + Send;
+
+    /// Save a type definition, replacing the one with the same identifier, if any.
+    fn save(
+        &self,
+        type_definition: &TypeDefinition<Id, FieldName>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Delete the type definition with the specified identifier.
+    ///
+    /// Returns whether the type definition existed.
+    fn delete(&self, id: &Id) -> impl Future<Output = Result<bool, Self::Error>> + Send;
+}
+
+/// A synchronous store shared between tasks.
+impl<Id, FieldName, S> AsyncTypeDefinitionStore<Id, FieldName> for RwLock<S>
+where
+    Id: Sync,
+    FieldName: Ord + Display + Clone + Sync,
+    S: TypeDefinitionStore<Id, FieldName> + Send + Sync,
+{
+    type Error = S::Error;
+
+    async fn load_all(&self) -> Result<Vec<TypeDefinition<Id, FieldName>>, Self::Error> {
+        self.read().await.load_all()
+    }
+
+    async fn load_by_id(
+        &self,
+        id: &Id,
+    ) -> Result<Option<TypeDefinition<Id, FieldName>>, Self::Error> {
+        self.read().await.load_by_id(id)
+    }
+
+    async fn save(
+        &self,
+        type_definition: &TypeDefinition<Id, FieldName>,
+    ) -> Result<(), Self::Error> {
+        self.write().await.save(type_definition)
+    }
+
+    async fn delete(&self, id: &Id) -> Result<bool, Self::Error> {
+        self.write().await.delete(id)
+    }
+}
+
+impl<Id: Ord + Hash + Clone + Display, FieldName: Ord + Hash + Clone + Display>
+    TypeDefinitionRegistry<Id, FieldName>
+{
+    /// Register all the type definitions of an asynchronous store, as a single batch.
+    ///
+    /// See [`load_from_store`](Self::load_from_store) for details.
+    pub async fn load_from_store_async<S: AsyncTypeDefinitionStore<Id, FieldName>>(
+        &mut self,
+        store: &S,
+    ) -> Result<RegistrationOutcome<Id, FieldName>, S::Error> {
+        Ok(self.register(store.load_all().await?))
+    }
+
+    /// Register a type definition of an asynchronous store, along with the type definitions it
+    /// references that are not registered yet, as a single batch.
+    ///
+    /// See [`load_from_store_by_id`](Self::load_from_store_by_id) for details.
+    pub async fn load_from_store_by_id_async<S: AsyncTypeDefinitionStore<Id, FieldName>>(
+        &mut self,
+        store: &S,
+        id: &Id,
+    ) -> Result<RegistrationOutcome<Id, FieldName>, S::Error> {
+        let mut type_definitions = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut queue = vec![id.clone()];
+
+        while let Some(id) = queue.pop() {
+            if self.by_id.contains_key(&id) || !visited.insert(id.clone()) {
+                continue;
+            }
+
+            if let Some(td) = store.load_by_id(&id).await? {
+                queue.extend(
+                    td.attributes
+                        .external_identifier_references()
+                        .into_iter()
+                        .cloned(),
+                );
+                type_definitions.push(td);
+            }
+        }
+
+        Ok(self.register(type_definitions))
+    }
+
+    /// Save all the registered type definitions to an asynchronous store.
+    ///
+    /// See [`save_to_store`](Self::save_to_store) for details.
+    pub async fn save_to_store_async<S: AsyncTypeDefinitionStore<Id, FieldName>>(
+        &self,
+        store: &S,
+    ) -> Result<(), S::Error> {
+        for td in self.to_definitions() {
+            store.save(&td).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use tokio::sync::RwLock;
+
+    use crate::{
+        test_support::{TypeAttributes, TypeDefinitionRegistry, td},
+        type_attributes::ArrayTypeAttributes,
+    };
+
+    use super::AsyncTypeDefinitionStore;
+
+    #[tokio::test]
+    async fn test_async_store() {
+        let store = RwLock::new(BTreeMap::new());
+
+        for td in [
+            td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+            td(
+                2,
+                "MyIntArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(3, "MyString", TypeAttributes::String(Default::default())),
+        ] {
+            store.save(&td).await.unwrap();
+        }
+
+        let mut registry = TypeDefinitionRegistry::default();
+
+        let outcome = registry
+            .load_from_store_by_id_async(&store, &2)
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome
+                .registered()
+                .iter()
+                .map(|td| td.id)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        let outcome = registry.load_from_store_async(&store).await.unwrap();
+        assert_eq!(
+            outcome
+                .registered()
+                .iter()
+                .map(|td| td.id)
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+
+        let saved = RwLock::new(BTreeMap::new());
+        registry.save_to_store_async(&saved).await.unwrap();
+        assert_eq!(saved.read().await.len(), 3);
+
+        assert!(saved.delete(&3).await.unwrap());
+        assert!(saved.load_by_id(&3).await.unwrap().is_none());
+    }
+}
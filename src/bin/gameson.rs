@@ -0,0 +1,354 @@
+//! The GameSON command-line tool.
+
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand};
+use gameson::{Compatibility, LintOptions, Severity};
+
+/// The registries handled by the tool, with numeric identifiers and string names.
+type TypeDefinitionRegistry = gameson::TypeDefinitionRegistry<u64, String>;
+
+/// The type definitions handled by the tool.
+type TypeDefinition = gameson::TypeDefinition<u64, String>;
+
+/// Validate, lint, compare and document GameSON registries.
+#[derive(Debug, Parser)]
+#[command(name = "gameson", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Validate JSON files against a type of a registry.
+    Validate {
+        /// The registry document.
+        registry: PathBuf,
+
+        /// The name of the type of the values.
+        #[arg(short = 't', long = "type")]
+        type_name: String,
+
+        /// The JSON files to validate.
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Print the validation reports as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Lint the types of a registry.
+    Lint {
+        /// The registry document.
+        registry: PathBuf,
+
+        /// The names of the root types, to report the types they do not use.
+        #[arg(long = "root")]
+        roots: Vec<String>,
+
+        /// The maximum nesting depth of types.
+        #[arg(long)]
+        max_depth: Option<usize>,
+
+        /// Fail on warnings too.
+        #[arg(long)]
+        deny_warnings: bool,
+
+        /// Print the lint report as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Compare two versions of a registry, and fail on changes that are not backward compatible.
+    Diff {
+        /// The old registry document.
+        old: PathBuf,
+
+        /// The new registry document.
+        new: PathBuf,
+    },
+
+    /// Export the JSON schema of a type of a registry.
+    ExportJsonSchema {
+        /// The registry document.
+        registry: PathBuf,
+
+        /// The name of the type.
+        #[arg(short = 't', long = "type")]
+        type_name: String,
+
+        /// The file to write the schema to, instead of the standard output.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Render the Markdown documentation of a registry.
+    Docs {
+        /// The registry document.
+        registry: PathBuf,
+
+        /// The directory to write the pages to.
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Run a command, returning whether it succeeded.
+fn run(cli: Cli) -> Result<bool, Box<dyn Error>> {
+    match cli.command {
+        Command::Validate {
+            registry,
+            type_name,
+            files,
+            json,
+        } => {
+            let registry = load_registry(&registry)?;
+            let instance = registry
+                .get_by_name(type_name.as_str())
+                .ok_or_else(|| format!("unknown type `{type_name}`"))?;
+
+            let mut valid = true;
+
+            for file in files {
+                let value = serde_json::from_str(&read(&file)?)
+                    .map_err(|err| format!("{}: {err}", file.display()))?;
+                let report = instance.validate(&value);
+
+                valid &= report.is_valid();
+
+                if json {
+                    println!("{}", serde_json::to_string(&report)?);
+                } else if report.severity().is_none() {
+                    println!("{}: valid", file.display());
+                } else {
+                    for err in report.errors() {
+                        println!("{}: error: {err}", file.display());
+                    }
+
+                    for warning in report.warnings() {
+                        println!("{}: warning: {warning}", file.display());
+                    }
+                }
+            }
+
+            Ok(valid)
+        }
+        Command::Lint {
+            registry,
+            roots,
+            max_depth,
+            deny_warnings,
+            json,
+        } => {
+            let registry = load_registry(&registry)?;
+
+            let mut options = LintOptions::default().roots(roots);
+
+            if let Some(max_depth) = max_depth {
+                options = options.max_depth(max_depth);
+            }
+
+            let report = registry.lint(&options);
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                for finding in report.findings() {
+                    let severity = match finding.severity() {
+                        Severity::Warning => "warning",
+                        Severity::Error => "error",
+                    };
+
+                    println!("{severity}[{}]: {finding}", finding.rule().code());
+                }
+            }
+
+            Ok(match report.severity() {
+                None => true,
+                Some(Severity::Warning) => !deny_warnings,
+                Some(Severity::Error) => false,
+            })
+        }
+        Command::Diff { old, new } => {
+            let old = load_registry(&old)?.to_definitions();
+            let new = load_registry(&new)?.to_definitions();
+
+            let (lines, backward) = diff(&old, &new);
+
+            for line in lines {
+                println!("{line}");
+            }
+
+            Ok(backward)
+        }
+        Command::ExportJsonSchema {
+            registry,
+            type_name,
+            output,
+        } => {
+            let registry = load_registry(&registry)?;
+            let instance = registry
+                .get_by_name(type_name.as_str())
+                .ok_or_else(|| format!("unknown type `{type_name}`"))?;
+            let schema = serde_json::to_string_pretty(&instance.to_json_schema())?;
+
+            match output {
+                Some(output) => write(&output, &schema)?,
+                None => println!("{schema}"),
+            }
+
+            Ok(true)
+        }
+        Command::Docs { registry, output } => {
+            let registry = load_registry(&registry)?;
+
+            fs::create_dir_all(&output).map_err(|err| format!("{}: {err}", output.display()))?;
+
+            for (name, page) in gameson::docs::render_markdown(&registry) {
+                write(&output.join(name), &page)?;
+            }
+
+            Ok(true)
+        }
+    }
+}
+
+/// Read a text file, mentioning its path in errors.
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+/// Write a text file, mentioning its path in errors.
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    fs::write(path, contents).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+/// Load a registry document.
+fn load_registry(path: &Path) -> Result<TypeDefinitionRegistry, String> {
+    serde_json::from_str(&read(path)?).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+/// Describe the changes between two versions of the type definitions of a registry, by
+/// identifier.
+///
+/// Returns the description, one line per added, removed or changed type definition, and whether
+/// the changes are backward compatible, that is whether the values of the old version remain
+/// readable.
+fn diff(old: &[TypeDefinition], new: &[TypeDefinition]) -> (Vec<String>, bool) {
+    let old: BTreeMap<_, _> = old.iter().map(|td| (td.id, td)).collect();
+    let new: BTreeMap<_, _> = new.iter().map(|td| (td.id, td)).collect();
+
+    let mut lines = Vec::new();
+    let mut backward = true;
+
+    for (id, old_td) in &old {
+        let Some(new_td) = new.get(id) else {
+            lines.push(format!("- {} ({id}): removed", old_td.name));
+            backward = false;
+            continue;
+        };
+
+        if old_td == new_td {
+            continue;
+        }
+
+        let compatibility = TypeDefinition::compatibility(old_td, new_td);
+        let summary = match &compatibility {
+            Compatibility::Full => "compatible",
+            Compatibility::Backward(_) => "backward compatible",
+            Compatibility::Forward(_) => "forward compatible",
+            Compatibility::Breaking(_) => "breaking",
+        };
+
+        let name = if old_td.name == new_td.name {
+            new_td.name.clone()
+        } else {
+            format!("{} (renamed from {})", new_td.name, old_td.name)
+        };
+
+        lines.push(format!("~ {name} ({id}): {summary}"));
+        lines.extend(
+            compatibility
+                .reasons()
+                .iter()
+                .map(|reason| format!("    {reason}")),
+        );
+
+        backward &= compatibility.is_backward();
+    }
+
+    for (id, new_td) in &new {
+        if !old.contains_key(id) {
+            lines.push(format!("+ {} ({id}): added", new_td.name));
+        }
+    }
+
+    (lines, backward)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+    use serde_json::json;
+
+    use super::{Cli, TypeDefinition, diff};
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_diff() {
+        let td =
+            |value: serde_json::Value| -> TypeDefinition { serde_json::from_value(value).unwrap() };
+
+        let old = [
+            td(json!({ "id": 1, "name": "Level", "type": "int32", "attributes": { "max": 10 } })),
+            td(json!({ "id": 2, "name": "Name", "type": "string", "attributes": {} })),
+            td(json!({ "id": 3, "name": "Flag", "type": "boolean", "attributes": {} })),
+        ];
+        let new = [
+            td(json!({ "id": 1, "name": "Level", "type": "int32", "attributes": { "max": 5 } })),
+            td(json!({ "id": 2, "name": "Title", "type": "string", "attributes": {} })),
+            td(json!({ "id": 4, "name": "Score", "type": "float64", "attributes": {} })),
+        ];
+
+        let (lines, backward) = diff(&old, &new);
+
+        assert!(!backward);
+        assert_eq!(lines[0], "~ Level (1): forward compatible");
+        assert_eq!(
+            &lines[2..],
+            [
+                "~ Title (renamed from Name) (2): compatible",
+                "- Flag (3): removed",
+                "+ Score (4): added",
+            ]
+        );
+
+        let (lines, backward) = diff(&old, &old);
+
+        assert!(backward);
+        assert!(lines.is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+//! Single-file binary bundles of registries.
+
+use std::{
+    fmt::Display,
+    hash::Hash,
+    io::{self, Read, Write},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::TypeDefinitionRegistry;
+
+/// The magic bytes every bundle starts with.
+const BUNDLE_MAGIC: [u8; 4] = *b"GSNB";
+
+/// The version of the bundle format.
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// The zstd compression level of the bundles.
+const BUNDLE_COMPRESSION_LEVEL: i32 = 19;
+
+/// An error that can occur when saving or loading a bundle.
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+    /// The bundle could not be read or written.
+    #[error("cannot access bundle: {0}")]
+    Io(#[from] io::Error),
+
+    /// The bundle payload does not contain a valid registry.
+    #[error("invalid bundle payload: {0}")]
+    Payload(#[from] serde_json::Error),
+
+    /// The data does not start with the bundle magic bytes.
+    #[error("not a bundle")]
+    InvalidMagic,
+
+    /// The bundle format version is not supported.
+    #[error("unsupported bundle format version {0}, expected {BUNDLE_FORMAT_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display + Serialize + DeserializeOwned,
+    FieldName: Ord + Hash + Clone + Display + Serialize + DeserializeOwned,
+{
+    /// Save the registry as a bundle: a compact binary container holding all its type
+    /// definitions.
+    ///
+    /// A bundle starts with 4 magic bytes (`GSNB`) and the format version, as a little-endian
+    /// 32-bit unsigned integer, followed by the zstd-compressed [serialized](serde::Serialize)
+    /// registry. Bundles are meant for shipping builds: loading a single bundle is several times
+    /// faster than loading the type definitions from as many JSON files, and takes about 20 ms
+    /// for 10000 type definitions (see the `bundle` benchmark).
+    pub fn save_bundle(&self, mut writer: impl Write) -> Result<(), BundleError> {
+        writer.write_all(&BUNDLE_MAGIC)?;
+        writer.write_all(&BUNDLE_FORMAT_VERSION.to_le_bytes())?;
+
+        let mut encoder = zstd::Encoder::new(writer, BUNDLE_COMPRESSION_LEVEL)?;
+        serde_json::to_writer(&mut encoder, self)?;
+        encoder.finish()?.flush()?;
+
+        Ok(())
+    }
+
+    /// Load a registry from a bundle, as written by [`save_bundle`](Self::save_bundle).
+    ///
+    /// Fails if any type definition of the bundle cannot be registered.
+    pub fn load_bundle(mut reader: impl Read) -> Result<Self, BundleError> {
+        let mut magic = [0; 4];
+        read_header(&mut reader, &mut magic)?;
+
+        if magic != BUNDLE_MAGIC {
+            return Err(BundleError::InvalidMagic);
+        }
+
+        let mut version = [0; 4];
+        read_header(&mut reader, &mut version)?;
+        let version = u32::from_le_bytes(version);
+
+        if version != BUNDLE_FORMAT_VERSION {
+            return Err(BundleError::UnsupportedVersion(version));
+        }
+
+        // Parsing JSON from a reader goes through it byte by byte: decompressing the whole payload
+        // first is much faster.
+        let mut payload = Vec::new();
+        zstd::Decoder::new(reader)?.read_to_end(&mut payload)?;
+
+        Ok(serde_json::from_slice(&payload)?)
+    }
+}
+
+/// Read a part of the bundle header, reporting truncated data as an invalid bundle.
+fn read_header(reader: &mut impl Read, buf: &mut [u8]) -> Result<(), BundleError> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Err(BundleError::InvalidMagic),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_support::owned::{
+            TypeAttributes, TypeDefinition, TypeDefinitionRegistry, registry, td,
+        },
+        type_attributes::ArrayTypeAttributes,
+    };
+
+    use super::BundleError;
+
+    #[test]
+    fn test_bundle() {
+        let registry = registry([
+            TypeDefinition {
+                id: 1,
+                name: "MyInt".to_owned(),
+                description: Some("An integer.".to_owned()),
+                attributes: TypeAttributes::Int32(Default::default()),
+            },
+            td(
+                2,
+                "MyIntArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+        ]);
+
+        let mut bundle = Vec::new();
+        registry.save_bundle(&mut bundle).unwrap();
+        assert_eq!(&bundle[..8], b"GSNB\x01\x00\x00\x00");
+
+        let loaded = TypeDefinitionRegistry::load_bundle(bundle.as_slice()).unwrap();
+        assert_eq!(loaded.to_definitions(), registry.to_definitions());
+
+        assert!(matches!(
+            TypeDefinitionRegistry::load_bundle(&b"GSN"[..]),
+            Err(BundleError::InvalidMagic)
+        ));
+        assert!(matches!(
+            TypeDefinitionRegistry::load_bundle(&b"{\"version\": 1}"[..]),
+            Err(BundleError::InvalidMagic)
+        ));
+
+        bundle[4] = 2;
+        let err = TypeDefinitionRegistry::load_bundle(bundle.as_slice()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unsupported bundle format version 2, expected 1"
+        );
+
+        bundle[4] = 1;
+        bundle.truncate(bundle.len() - 4);
+        assert!(TypeDefinitionRegistry::load_bundle(bundle.as_slice()).is_err());
+    }
+}
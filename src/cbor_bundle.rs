@@ -0,0 +1,94 @@
+//! CBOR bundles of registries.
+
+use std::{
+    fmt::Display,
+    hash::Hash,
+    io::{self, Read, Write},
+};
+
+use ciborium::tag::Required;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::TypeDefinitionRegistry;
+
+/// The self-described CBOR tag every CBOR bundle starts with.
+const SELF_DESCRIBED_CBOR_TAG: u64 = 55799;
+
+/// An error that can occur when saving or loading a CBOR bundle.
+#[derive(Debug, thiserror::Error)]
+pub enum CborBundleError {
+    /// The registry could not be written.
+    #[error("cannot write CBOR bundle: {0}")]
+    Write(#[from] ciborium::ser::Error<io::Error>),
+
+    /// The bundle could not be read or does not contain a valid registry.
+    #[error("cannot read CBOR bundle: {0}")]
+    Read(#[from] ciborium::de::Error<io::Error>),
+}
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display + Serialize + DeserializeOwned,
+    FieldName: Ord + Hash + Clone + Display + Serialize + DeserializeOwned,
+{
+    /// Save the registry as a CBOR bundle, holding all its type definitions.
+    ///
+    /// A CBOR bundle is the [serialized](serde::Serialize) registry as a CBOR data item, tagged
+    /// with the self-described CBOR tag so that it starts with the `d9 d9 f7` magic bytes.
+    pub fn save_cbor_bundle(&self, writer: impl Write) -> Result<(), CborBundleError> {
+        ciborium::into_writer(&Required::<_, SELF_DESCRIBED_CBOR_TAG>(self), writer)?;
+
+        Ok(())
+    }
+
+    /// Load a registry from a CBOR bundle, as written by
+    /// [`save_cbor_bundle`](Self::save_cbor_bundle).
+    ///
+    /// Fails if any type definition of the bundle cannot be registered.
+    pub fn load_cbor_bundle(reader: impl Read) -> Result<Self, CborBundleError> {
+        let Required(registry) =
+            ciborium::from_reader::<Required<Self, SELF_DESCRIBED_CBOR_TAG>, _>(reader)?;
+
+        Ok(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_support::owned::{
+            TypeAttributes, TypeDefinition, TypeDefinitionRegistry, registry, td,
+        },
+        type_attributes::ArrayTypeAttributes,
+    };
+
+    #[test]
+    fn test_cbor_bundle() {
+        let registry = registry([
+            TypeDefinition {
+                id: 1,
+                name: "MyInt".to_owned(),
+                description: Some("An integer.".to_owned()),
+                attributes: TypeAttributes::Int32(Default::default()),
+            },
+            td(
+                2,
+                "MyIntArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+        ]);
+
+        let mut bundle = Vec::new();
+        registry.save_cbor_bundle(&mut bundle).unwrap();
+        assert_eq!(&bundle[..3], b"\xd9\xd9\xf7");
+
+        let loaded = TypeDefinitionRegistry::load_cbor_bundle(bundle.as_slice()).unwrap();
+        assert_eq!(loaded.to_definitions(), registry.to_definitions());
+
+        // Untagged data is not a bundle.
+        assert!(TypeDefinitionRegistry::load_cbor_bundle(&bundle[3..]).is_err());
+
+        bundle.truncate(bundle.len() - 4);
+        assert!(TypeDefinitionRegistry::load_cbor_bundle(bundle.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! GraphViz DOT graphs.
+
+use std::{fmt::Display, fmt::Write, hash::Hash};
+
+use crate::{TypeDefinitionRegistry, type_attributes_instance::TypeAttributesInstance};
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display,
+{
+    /// Generate a GraphViz DOT graph of the references between the registered types.
+    ///
+    /// Every type is a node, labelled with its name and kind, and identified by its identifier.
+    /// The shape of the node depends on the kind of the type: boxes for arrays, folders for
+    /// dictionaries, hexagons for enums and ellipses for the other types. Every reference is an
+    /// edge from the referencing type, labelled `items`, `keys` or `values`.
+    ///
+    /// Nodes and edges are sorted by type name, so that the graph of a registry is stable.
+    pub fn to_dot(&self) -> String {
+        let mut instances: Vec<_> = self.by_name.values().collect();
+        instances.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut dot = String::from("digraph gameson {\n");
+
+        for instance in &instances {
+            let shape = match &instance.attributes {
+                TypeAttributesInstance::Array(_) => "box",
+                TypeAttributesInstance::Dictionary(_) => "folder",
+                TypeAttributesInstance::Enum(_) => "hexagon",
+                _ => "ellipse",
+            };
+
+            writeln!(
+                dot,
+                "    {} [label={}, shape={shape}];",
+                quoted(&instance.id),
+                quoted(format_args!(
+                    "{}\n{}",
+                    instance.name,
+                    instance.attributes.kind()
+                )),
+            )
+            .unwrap();
+        }
+
+        for instance in &instances {
+            let edges = match &instance.attributes {
+                TypeAttributesInstance::Array(a) => vec![("items", a.items_type_id())],
+                TypeAttributesInstance::Dictionary(a) => {
+                    vec![("keys", a.keys_type_id()), ("values", a.values_type_id())]
+                }
+                _ => vec![],
+            };
+
+            for (label, target) in edges {
+                writeln!(
+                    dot,
+                    "    {} -> {} [label={label}];",
+                    quoted(&instance.id),
+                    quoted(&target.id)
+                )
+                .unwrap();
+            }
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+/// Get a DOT string, quoted and escaped.
+fn quoted(s: impl Display) -> String {
+    let mut quoted = String::from('"');
+
+    for c in s.to_string().chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_support::{TypeAttributes, registry, td},
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, DictionaryWireForm, EnumTypeAttributes,
+        },
+    };
+
+    #[test]
+    fn test_to_dot() {
+        let registry = registry([
+            td(1, "Score", TypeAttributes::Int32(Default::default())),
+            td(
+                2,
+                "Team",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("red")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                3,
+                "Scores",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(2, 1).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+            td(
+                4,
+                "Teams",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+            td(5, "Say \"hi\"", TypeAttributes::String(Default::default())),
+        ]);
+
+        assert_eq!(
+            registry.to_dot(),
+            r#"digraph gameson {
+    "5" [label="Say \"hi\"\nstring", shape=ellipse];
+    "1" [label="Score\nint32", shape=ellipse];
+    "3" [label="Scores\ndictionary of pairs", shape=folder];
+    "2" [label="Team\nenum", shape=hexagon];
+    "4" [label="Teams\narray", shape=box];
+    "3" -> "2" [label=keys];
+    "3" -> "1" [label=values];
+    "4" -> "2" [label=items];
+}
+"#
+        );
+    }
+}
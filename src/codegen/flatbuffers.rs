@@ -0,0 +1,283 @@
+//! Generation of FlatBuffers schemas from registries.
+
+use std::{fmt::Display, fmt::Write, hash::Hash};
+
+use crate::{
+    TypeDefinitionInstance, TypeDefinitionRegistry,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display,
+{
+    /// Generate the text of a FlatBuffers schema (`.fbs`) describing the specified types and all
+    /// the types they reference, in the specified namespace.
+    ///
+    /// Unknown root identifiers are ignored. The types are mapped as follows:
+    ///
+    /// | Kind         | FlatBuffers                                                        |
+    /// |--------------|--------------------------------------------------------------------|
+    /// | `boolean`    | `bool`                                                             |
+    /// | `int32`      | `int`                                                              |
+    /// | `int64`      | `long`                                                             |
+    /// | `uint32`     | `uint`                                                             |
+    /// | `uint64`     | `ulong`                                                            |
+    /// | `float32`    | `float`                                                            |
+    /// | `float64`    | `double`                                                           |
+    /// | `string`     | `string`                                                           |
+    /// | `enum`       | an `enum`, over the smallest unsigned integer type that fits       |
+    /// | `uuid`       | `string`                                                           |
+    /// | `bytes`      | `[ubyte]`                                                          |
+    /// | `array`      | a table with a `value` vector of the items                         |
+    /// | `dictionary` | a table with a `value` vector of `<Name>Entry` tables              |
+    ///
+    /// Booleans, numbers, strings, UUIDs and bytes are inlined where they are referenced, and
+    /// also get a table with a single `value` field, so that they can be used as root types.
+    /// Their default values become the defaults of these fields.
+    ///
+    /// Entry tables have a `key` and a `value` field. Their keys are marked with the `key`
+    /// attribute when they are scalars or strings, so that the entries can be looked up by key
+    /// once sorted.
+    ///
+    /// Enum aliases have no equivalent, as they are resolved when parsing. Enum values are
+    /// numbered in their sorted order, so their numbers change when values are added before
+    /// others: data must then be regenerated along with the schema. Empty enums, which FlatBuffers
+    /// does not support, map to strings. Type and enum value names must be valid FlatBuffers
+    /// identifiers.
+    pub fn to_fbs<'a>(&self, namespace: &str, roots: impl IntoIterator<Item = &'a Id>) -> String
+    where
+        Id: 'a,
+    {
+        let reachable = self.reachable_from(roots);
+        let mut instances: Vec<_> = reachable.iter().map(|id| &self.by_id[id]).collect();
+        instances.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut fbs = String::new();
+
+        if !namespace.is_empty() {
+            writeln!(fbs, "namespace {namespace};").unwrap();
+        }
+
+        for instance in instances {
+            if !fbs.is_empty() {
+                fbs.push('\n');
+            }
+
+            write_type(&mut fbs, instance);
+        }
+
+        fbs
+    }
+}
+
+/// Get the FlatBuffers type of the fields referencing a type instance.
+fn field_type<Id, FieldName: Ord + Display>(
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+) -> String {
+    match &instance.attributes {
+        TypeAttributesInstance::Array(_) | TypeAttributesInstance::Dictionary(_) => {
+            instance.name.to_string()
+        }
+        TypeAttributesInstance::Boolean(_) => "bool".to_owned(),
+        TypeAttributesInstance::Int32(_) => "int".to_owned(),
+        TypeAttributesInstance::Int64(_) => "long".to_owned(),
+        TypeAttributesInstance::Uint32(_) => "uint".to_owned(),
+        TypeAttributesInstance::Uint64(_) => "ulong".to_owned(),
+        TypeAttributesInstance::Float32(_) => "float".to_owned(),
+        TypeAttributesInstance::Float64(_) => "double".to_owned(),
+        TypeAttributesInstance::String(_) => "string".to_owned(),
+        TypeAttributesInstance::Enum(a) if a.values().next().is_none() => "string".to_owned(),
+        TypeAttributesInstance::Enum(_) => instance.name.to_string(),
+        #[cfg(feature = "uuid")]
+        TypeAttributesInstance::Uuid(_) => "string".to_owned(),
+        #[cfg(feature = "bytes")]
+        TypeAttributesInstance::Bytes(_) => "[ubyte]".to_owned(),
+    }
+}
+
+/// Get the default value of the fields of a type instance, if it has one that FlatBuffers
+/// supports.
+fn default_value<Id, FieldName: Ord + Display>(
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+) -> Option<String> {
+    match &instance.attributes {
+        TypeAttributesInstance::Boolean(a) => a.default_value().map(|v| v.to_string()),
+        TypeAttributesInstance::Int32(a) => a.default_value().map(|v| v.to_string()),
+        TypeAttributesInstance::Int64(a) => a.default_value().map(|v| v.to_string()),
+        TypeAttributesInstance::Uint32(a) => a.default_value().map(|v| v.to_string()),
+        TypeAttributesInstance::Uint64(a) => a.default_value().map(|v| v.to_string()),
+        TypeAttributesInstance::Float32(a) => a.default_value().map(|v| v.to_string()),
+        TypeAttributesInstance::Float64(a) => a.default_value().map(|v| v.to_string()),
+        TypeAttributesInstance::Enum(a) => a.default_value().map(|v| v.to_string()),
+        _ => None,
+    }
+}
+
+/// Write a field, with its default value, if any.
+fn write_field<Id, FieldName: Ord + Display>(
+    fbs: &mut String,
+    name: &str,
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+    attribute: &str,
+) {
+    let field_type = field_type(instance);
+
+    match default_value(instance) {
+        Some(default) => writeln!(fbs, "  {name}: {field_type} = {default}{attribute};"),
+        None => writeln!(fbs, "  {name}: {field_type}{attribute};"),
+    }
+    .unwrap();
+}
+
+/// Write the definitions of a type instance.
+fn write_type<Id, FieldName: Ord + Display>(
+    fbs: &mut String,
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+) {
+    let name = &instance.name;
+
+    if let Some(description) = &instance.description {
+        for line in description.lines() {
+            writeln!(fbs, "/// {line}").unwrap();
+        }
+    }
+
+    match &instance.attributes {
+        TypeAttributesInstance::Array(a) => {
+            writeln!(fbs, "table {name} {{").unwrap();
+            writeln!(fbs, "  value: [{}];", field_type(a.items_type_id())).unwrap();
+            writeln!(fbs, "}}").unwrap();
+        }
+        TypeAttributesInstance::Dictionary(a) => {
+            let keys = a.keys_type_id();
+            let is_key = match &keys.attributes {
+                TypeAttributesInstance::Array(_) | TypeAttributesInstance::Dictionary(_) => false,
+                #[cfg(feature = "bytes")]
+                TypeAttributesInstance::Bytes(_) => false,
+                _ => true,
+            };
+
+            writeln!(fbs, "table {name}Entry {{").unwrap();
+            write_field(fbs, "key", keys, if is_key { " (key)" } else { "" });
+            write_field(fbs, "value", a.values_type_id(), "");
+            writeln!(fbs, "}}\n").unwrap();
+            writeln!(fbs, "table {name} {{").unwrap();
+            writeln!(fbs, "  value: [{name}Entry];").unwrap();
+            writeln!(fbs, "}}").unwrap();
+        }
+        TypeAttributesInstance::Enum(a) if a.values().next().is_some() => {
+            let underlying_type = match a.values().count() {
+                0..=0x100 => "ubyte",
+                0x101..=0x10000 => "ushort",
+                _ => "uint",
+            };
+
+            writeln!(fbs, "enum {name} : {underlying_type} {{").unwrap();
+
+            for value in a.values() {
+                if a.is_deprecated(value) {
+                    writeln!(fbs, "  /// Deprecated.").unwrap();
+                }
+
+                writeln!(fbs, "  {value},").unwrap();
+            }
+
+            writeln!(fbs, "}}").unwrap();
+        }
+        _ => {
+            writeln!(fbs, "table {name} {{").unwrap();
+            write_field(fbs, "value", instance, "");
+            writeln!(fbs, "}}").unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_support::{TypeAttributes, TypeDefinition, registry, td},
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, EnumTypeAttributes, NumberTypeAttributes,
+        },
+    };
+
+    #[test]
+    fn test_to_fbs() {
+        let registry = registry([
+            td(
+                1,
+                "MyInt",
+                TypeAttributes::Int32(
+                    NumberTypeAttributes::builder()
+                        .default_value(5)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            TypeDefinition {
+                description: Some("The colors of the teams.".to_owned()),
+                ..td(
+                    2,
+                    "MyColor",
+                    TypeAttributes::Enum(
+                        EnumTypeAttributes::builder()
+                            .with_value("red")
+                            .with_value_ext("green", None, true)
+                            .with_alias("rouge", "red")
+                            .with_default("red")
+                            .build()
+                            .unwrap(),
+                    ),
+                )
+            },
+            td(
+                3,
+                "MyColors",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+            td(
+                4,
+                "MyScores",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(2, 1)),
+            ),
+            td(5, "MyString", TypeAttributes::String(Default::default())),
+        ]);
+
+        assert_eq!(
+            registry.to_fbs("game", &[3, 4]),
+            "namespace game;
+
+/// The colors of the teams.
+enum MyColor : ubyte {
+  /// Deprecated.
+  green,
+  red,
+}
+
+table MyColors {
+  value: [MyColor];
+}
+
+table MyInt {
+  value: int = 5;
+}
+
+table MyScoresEntry {
+  key: MyColor = red (key);
+  value: int = 5;
+}
+
+table MyScores {
+  value: [MyScoresEntry];
+}
+"
+        );
+
+        assert_eq!(
+            registry.to_fbs("", &[5, 6]),
+            "table MyString {\n  value: string;\n}\n"
+        );
+    }
+}
@@ -0,0 +1,389 @@
+//! GDScript resources for Godot.
+
+use std::{collections::BTreeMap, fmt::Display, fmt::Write, hash::Hash};
+
+use crate::{
+    TypeDefinitionInstance, TypeDefinitionRegistry, codegen::NameCase,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+/// The name of the nested enum of the scripts of enum types.
+const ENUM_NAME: &str = "Value";
+
+/// Options that control how GDScript resources for Godot are generated.
+///
+/// See [`to_gdscript`](TypeDefinitionRegistry::to_gdscript).
+#[derive(Debug, Clone, Default)]
+pub struct GodotOptions {
+    /// The prefix of the names of the classes.
+    pub(crate) class_prefix: String,
+}
+
+impl GodotOptions {
+    /// Sets the prefix of the names of the classes, to avoid clashes with the built-in classes
+    /// of Godot, such as `Color`.
+    ///
+    /// Defaults to none.
+    pub fn class_prefix(mut self, class_prefix: impl Into<String>) -> Self {
+        self.class_prefix = class_prefix.into();
+        self
+    }
+
+    /// Get the GDScript class name of a type instance.
+    fn class_name<Id, FieldName: Ord + Display>(
+        &self,
+        instance: &TypeDefinitionInstance<Id, FieldName>,
+    ) -> String {
+        format!(
+            "{}{}",
+            self.class_prefix,
+            NameCase::Pascal.convert(&instance.name)
+        )
+    }
+
+    /// Get the GDScript type of the properties referencing a type instance.
+    fn property_type<Id, FieldName: Ord + Display>(
+        &self,
+        instance: &TypeDefinitionInstance<Id, FieldName>,
+    ) -> String {
+        match &instance.attributes {
+            TypeAttributesInstance::Array(_) | TypeAttributesInstance::Dictionary(_) => {
+                self.class_name(instance)
+            }
+            TypeAttributesInstance::Boolean(_) => "bool".to_owned(),
+            TypeAttributesInstance::Int32(_)
+            | TypeAttributesInstance::Int64(_)
+            | TypeAttributesInstance::Uint32(_)
+            | TypeAttributesInstance::Uint64(_) => "int".to_owned(),
+            TypeAttributesInstance::Float32(_) | TypeAttributesInstance::Float64(_) => {
+                "float".to_owned()
+            }
+            TypeAttributesInstance::String(_) => "String".to_owned(),
+            TypeAttributesInstance::Enum(a) if a.values().next().is_none() => "String".to_owned(),
+            TypeAttributesInstance::Enum(_) => {
+                format!("{}.{ENUM_NAME}", self.class_name(instance))
+            }
+            #[cfg(feature = "uuid")]
+            TypeAttributesInstance::Uuid(_) => "String".to_owned(),
+            #[cfg(feature = "bytes")]
+            TypeAttributesInstance::Bytes(_) => "PackedByteArray".to_owned(),
+        }
+    }
+}
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display,
+{
+    /// Generate GDScript resources for Godot 4.4 or later, describing the specified types and all
+    /// the types they reference.
+    ///
+    /// Unknown root identifiers are ignored. Every type gets a script declaring a global class
+    /// named after it, in `PascalCase`, which extends `Resource` and exports a single typed
+    /// `value` property:
+    ///
+    /// - Booleans map to `bool`, integers to `int`, floating point numbers to `float`, strings
+    ///   and UUIDs to `String`, and bytes to `PackedByteArray`. Default values become the
+    ///   initial values of the properties.
+    /// - Enums map to a `Value` enum declared in their script, whose values are in
+    ///   `UPPER_SNAKE_CASE`. The `NAMES` constant of the script holds the original names of the
+    ///   values, in the same order. Empty enums, which have no valid values, map to `String`.
+    /// - Arrays map to typed `Array`s of their items.
+    /// - Dictionaries map to typed `Dictionary`s. Dictionaries of pairs whose keys are arrays,
+    ///   dictionaries or bytes map to typed `Array`s of `<Name>Entry` resources instead, with
+    ///   `key` and `value` properties, as Godot compares resources by reference.
+    ///
+    /// Properties referencing arrays and dictionaries use their resources. Enum values are
+    /// numbered in their sorted order, so their numbers change when values are added before
+    /// others: resources saved with previous numbers must then be converted.
+    ///
+    /// Returns the sources of the scripts by their file names, in `snake_case`.
+    pub fn to_gdscript<'a>(
+        &self,
+        roots: impl IntoIterator<Item = &'a Id>,
+        options: &GodotOptions,
+    ) -> BTreeMap<String, String>
+    where
+        Id: 'a,
+    {
+        let mut scripts = BTreeMap::new();
+
+        for id in self.reachable_from(roots) {
+            let instance = &self.by_id[&id];
+            let class_name = options.class_name(instance);
+
+            let value = match &instance.attributes {
+                TypeAttributesInstance::Dictionary(a)
+                    if matches!(
+                        a.keys_type_id().attributes,
+                        TypeAttributesInstance::Array(_) | TypeAttributesInstance::Dictionary(_)
+                    ) || is_bytes(a.keys_type_id()) =>
+                {
+                    let entry = format!("{class_name}Entry");
+
+                    scripts.insert(
+                        script_name(&entry),
+                        script(
+                            &entry,
+                            None,
+                            "",
+                            &[
+                                property("key", &options.property_type(a.keys_type_id()), None),
+                                property("value", &options.property_type(a.values_type_id()), None),
+                            ],
+                        ),
+                    );
+
+                    property("value", &format!("Array[{entry}]"), Some("[]".to_owned()))
+                }
+                TypeAttributesInstance::Dictionary(a) => property(
+                    "value",
+                    &format!(
+                        "Dictionary[{}, {}]",
+                        options.property_type(a.keys_type_id()),
+                        options.property_type(a.values_type_id())
+                    ),
+                    Some("{}".to_owned()),
+                ),
+                TypeAttributesInstance::Array(a) => property(
+                    "value",
+                    &format!("Array[{}]", options.property_type(a.items_type_id())),
+                    Some("[]".to_owned()),
+                ),
+                _ => property(
+                    "value",
+                    &options.property_type(instance),
+                    default_value(instance),
+                ),
+            };
+
+            let declarations = match &instance.attributes {
+                TypeAttributesInstance::Enum(a) if a.values().next().is_some() => {
+                    let values: Vec<_> = a.values().collect();
+
+                    format!(
+                        "enum {ENUM_NAME} {{ {} }}\n\n## The names of the values, as GameSON serializes them.\nconst NAMES: Array[String] = [{}]\n\n",
+                        values
+                            .iter()
+                            .map(|value| NameCase::UpperSnake.convert(value))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        values
+                            .iter()
+                            .map(string_literal)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                }
+                _ => String::new(),
+            };
+
+            scripts.insert(
+                script_name(&class_name),
+                script(
+                    &class_name,
+                    instance.description.as_deref(),
+                    &declarations,
+                    &[value],
+                ),
+            );
+        }
+
+        scripts
+    }
+}
+
+/// Check whether a type instance is a bytes type.
+fn is_bytes<Id, FieldName: Ord>(instance: &TypeDefinitionInstance<Id, FieldName>) -> bool {
+    match &instance.attributes {
+        #[cfg(feature = "bytes")]
+        TypeAttributesInstance::Bytes(_) => true,
+        _ => false,
+    }
+}
+
+/// Get the initial value of the properties of a type instance, if it has a default value.
+fn default_value<Id, FieldName: Ord + Display>(
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+) -> Option<String> {
+    match &instance.attributes {
+        TypeAttributesInstance::Boolean(a) => a.default_value().map(|v| v.to_string()),
+        TypeAttributesInstance::Int32(a) => a.default_value().map(|v| v.to_string()),
+        TypeAttributesInstance::Int64(a) => a.default_value().map(|v| v.to_string()),
+        TypeAttributesInstance::Uint32(a) => a.default_value().map(|v| v.to_string()),
+        TypeAttributesInstance::Uint64(a) => a.default_value().map(|v| v.to_string()),
+        TypeAttributesInstance::Float32(a) => a.default_value().map(|v| format!("{v:?}")),
+        TypeAttributesInstance::Float64(a) => a.default_value().map(|v| format!("{v:?}")),
+        TypeAttributesInstance::String(a) => a.default_value().map(string_literal),
+        TypeAttributesInstance::Enum(a) if a.values().next().is_none() => None,
+        TypeAttributesInstance::Enum(a) => a
+            .default_value()
+            .map(|v| format!("{ENUM_NAME}.{}", NameCase::UpperSnake.convert(v))),
+        _ => None,
+    }
+}
+
+/// Get a string literal, quoted and escaped as in JSON, which GDScript accepts.
+fn string_literal(s: impl Display) -> String {
+    serde_json::to_string(&s.to_string()).expect("strings always serialize to JSON")
+}
+
+/// Get the file name of the script of a class.
+fn script_name(class_name: &str) -> String {
+    format!("{}.gd", NameCase::Snake.convert(class_name))
+}
+
+/// Get the declaration of an exported property.
+fn property(name: &str, property_type: &str, initial_value: Option<String>) -> String {
+    match initial_value {
+        Some(initial_value) => format!("@export var {name}: {property_type} = {initial_value}"),
+        None => format!("@export var {name}: {property_type}"),
+    }
+}
+
+/// Get the source of the script of a resource class.
+fn script(
+    class_name: &str,
+    description: Option<&str>,
+    declarations: &str,
+    properties: &[String],
+) -> String {
+    let mut source = String::new();
+
+    for line in description.into_iter().flat_map(str::lines) {
+        writeln!(source, "## {line}").unwrap();
+    }
+
+    writeln!(source, "class_name {class_name}\nextends Resource\n").unwrap();
+    source.push_str(declarations);
+
+    for property in properties {
+        writeln!(source, "{property}").unwrap();
+    }
+
+    source
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        test_support::{TypeAttributes, TypeDefinition, registry, td},
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, DictionaryWireForm, EnumTypeAttributes,
+            NumberTypeAttributes,
+        },
+    };
+
+    use super::GodotOptions;
+
+    fn scripts() -> BTreeMap<String, String> {
+        let registry = registry([
+            td(
+                1,
+                "Speed",
+                TypeAttributes::Float32(
+                    NumberTypeAttributes::builder()
+                        .default_value(1.5)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            TypeDefinition {
+                description: Some("The colors of the teams.".to_owned()),
+                ..td(
+                    2,
+                    "Color",
+                    TypeAttributes::Enum(
+                        EnumTypeAttributes::builder()
+                            .with_value("red")
+                            .with_value("dark-green")
+                            .with_default("red")
+                            .build()
+                            .unwrap(),
+                    ),
+                )
+            },
+            td(
+                3,
+                "Colors",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+            td(
+                4,
+                "Speeds",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(2, 1)),
+            ),
+            td(
+                5,
+                "Palettes",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(3, 4).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+        ]);
+
+        registry.to_gdscript(&[5], &GodotOptions::default().class_prefix("Gs"))
+    }
+
+    #[test]
+    fn test_to_gdscript() {
+        assert_eq!(
+            scripts().keys().collect::<Vec<_>>(),
+            [
+                "gs_color.gd",
+                "gs_colors.gd",
+                "gs_palettes.gd",
+                "gs_palettes_entry.gd",
+                "gs_speed.gd",
+                "gs_speeds.gd",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_gdscript_enum() {
+        assert_eq!(
+            scripts()["gs_color.gd"],
+            r#"## The colors of the teams.
+class_name GsColor
+extends Resource
+
+enum Value { DARK_GREEN, RED }
+
+## The names of the values, as GameSON serializes them.
+const NAMES: Array[String] = ["dark-green", "red"]
+
+@export var value: GsColor.Value = Value.RED
+"#
+        );
+    }
+
+    #[test]
+    fn test_to_gdscript_collections() {
+        let scripts = scripts();
+
+        assert_eq!(
+            scripts["gs_colors.gd"],
+            "class_name GsColors\nextends Resource\n\n@export var value: Array[GsColor.Value] = []\n"
+        );
+        assert_eq!(
+            scripts["gs_speeds.gd"],
+            "class_name GsSpeeds\nextends Resource\n\n@export var value: Dictionary[GsColor.Value, float] = {}\n"
+        );
+        assert_eq!(
+            scripts["gs_palettes.gd"],
+            "class_name GsPalettes\nextends Resource\n\n@export var value: Array[GsPalettesEntry] = []\n"
+        );
+        assert_eq!(
+            scripts["gs_palettes_entry.gd"],
+            "class_name GsPalettesEntry\nextends Resource\n\n@export var key: GsColors\n@export var value: GsSpeeds\n"
+        );
+        assert_eq!(
+            scripts["gs_speed.gd"],
+            "class_name GsSpeed\nextends Resource\n\n@export var value: float = 1.5\n"
+        );
+    }
+}
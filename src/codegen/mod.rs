@@ -0,0 +1,139 @@
+//! Generation of code from registries, for tools written in other languages.
+
+mod dot;
+mod flatbuffers;
+mod godot;
+#[cfg(feature = "protobuf")]
+mod protobuf;
+mod typescript;
+mod unreal;
+
+use std::{collections::BTreeSet, fmt::Display, sync::Arc};
+
+use crate::TypeDefinitionInstance;
+
+pub use godot::GodotOptions;
+pub use unreal::UnrealOptions;
+
+/// How the names of the types and of the enum values are converted to the names of the generated
+/// code.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NameCase {
+    /// The names are kept as-is.
+    Verbatim,
+
+    /// The names are converted to `PascalCase`: they are split on any character that is not
+    /// alphanumeric, and the first letter of every word is uppercased.
+    #[default]
+    Pascal,
+
+    /// The names are converted to `snake_case`: they are split on any character that is not
+    /// alphanumeric and before any uppercase letter following a lowercase letter or a digit, and
+    /// the words are lowercased and joined with underscores.
+    Snake,
+
+    /// The names are converted to `UPPER_SNAKE_CASE`, like [`Snake`](Self::Snake) but uppercased.
+    UpperSnake,
+}
+
+impl NameCase {
+    /// Convert a name.
+    pub fn convert(self, name: impl Display) -> String {
+        let name = name.to_string();
+
+        match self {
+            Self::Verbatim => name,
+            Self::Pascal => name
+                .split(|c: char| !c.is_alphanumeric())
+                .flat_map(|word| {
+                    let mut chars = word.chars();
+
+                    chars
+                        .next()
+                        .into_iter()
+                        .flat_map(char::to_uppercase)
+                        .chain(chars)
+                })
+                .collect(),
+            Self::Snake => words(&name).join("_").to_lowercase(),
+            Self::UpperSnake => words(&name).join("_").to_uppercase(),
+        }
+    }
+}
+
+/// Split a name into words, on any character that is not alphanumeric and before any uppercase
+/// letter following a lowercase letter or a digit.
+fn words(name: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+
+    for part in name.split(|c: char| !c.is_alphanumeric()) {
+        let mut start = 0;
+        let mut previous = None;
+
+        for (index, c) in part.char_indices() {
+            if c.is_uppercase()
+                && previous.is_some_and(|p: char| p.is_lowercase() || p.is_ascii_digit())
+            {
+                words.push(&part[start..index]);
+                start = index;
+            }
+
+            previous = Some(c);
+        }
+
+        if start < part.len() {
+            words.push(&part[start..]);
+        }
+    }
+
+    words
+}
+
+/// Sort type instances so that each one comes after the type instances it references, and by
+/// name otherwise.
+pub(crate) fn topological_order<'a, Id: Ord + Clone, FieldName: Ord>(
+    instances: impl IntoIterator<Item = &'a Arc<TypeDefinitionInstance<Id, FieldName>>>,
+) -> Vec<&'a Arc<TypeDefinitionInstance<Id, FieldName>>> {
+    fn visit<'a, Id: Ord + Clone, FieldName: Ord>(
+        instance: &'a Arc<TypeDefinitionInstance<Id, FieldName>>,
+        visited: &mut BTreeSet<Id>,
+        sorted: &mut Vec<&'a Arc<TypeDefinitionInstance<Id, FieldName>>>,
+    ) {
+        if !visited.insert(instance.id.clone()) {
+            return;
+        }
+
+        for ref_ in instance.attributes.references() {
+            visit(ref_, visited, sorted);
+        }
+
+        sorted.push(instance);
+    }
+
+    let mut instances: Vec<_> = instances.into_iter().collect();
+    instances.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut visited = BTreeSet::new();
+    let mut sorted = Vec::with_capacity(instances.len());
+
+    for instance in instances {
+        visit(instance, &mut visited, &mut sorted);
+    }
+
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NameCase;
+
+    #[test]
+    fn test_name_case() {
+        assert_eq!(NameCase::Pascal.convert("dark-green"), "DarkGreen");
+        assert_eq!(NameCase::Pascal.convert("my_int32"), "MyInt32");
+        assert_eq!(NameCase::Pascal.convert("MyInt"), "MyInt");
+        assert_eq!(NameCase::Snake.convert("MyInt32Array"), "my_int32_array");
+        assert_eq!(NameCase::UpperSnake.convert("dark-green"), "DARK_GREEN");
+        assert_eq!(NameCase::Verbatim.convert("dark-green"), "dark-green");
+    }
+}
@@ -0,0 +1,501 @@
+//! Export of registries as protobuf descriptors.
+
+use std::{fmt::Display, fmt::Write, hash::Hash, sync::Arc};
+
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, EnumOptions, EnumValueDescriptorProto, EnumValueOptions,
+    FieldDescriptorProto, FileDescriptorProto, MessageOptions,
+    field_descriptor_proto::{Label, Type},
+};
+
+use crate::{
+    TypeDefinitionInstance, TypeDefinitionRegistry, type_attributes::DictionaryTypeAttributes,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+/// The name of the field holding the value in every message.
+const VALUE_FIELD: &str = "value";
+
+/// The name of the nested enum of the messages of enum types.
+const ENUM_NAME: &str = "Value";
+
+/// A field type, as a protobuf descriptor references it.
+struct FieldType {
+    /// The type of the field.
+    r#type: Type,
+
+    /// The fully qualified name of the type, for messages and enums.
+    type_name: Option<String>,
+}
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display,
+{
+    /// Describe the registered types as a protobuf file, in the specified package.
+    ///
+    /// Protobuf has no type aliases, so every type is described by a message with the same name,
+    /// holding a single `value` field:
+    ///
+    /// - Booleans, numbers and strings map to their protobuf scalar types. UUIDs are strings and
+    ///   bytes are bytes.
+    /// - Enums map to an enum named `Value`, nested in their message. Aliases share the number of
+    ///   the values they stand for, and deprecated values are marked as such.
+    /// - Arrays map to `repeated` fields.
+    /// - Dictionaries map to `map` fields, with string keys for enums and UUIDs. Dictionaries of
+    ///   pairs whose keys cannot be map keys map to repeated `Entry` messages instead.
+    ///
+    /// References to booleans, numbers, strings and enums use their scalar or enum types directly,
+    /// while references to arrays and dictionaries use their messages.
+    ///
+    /// Enum values are numbered in their sorted order, so their numbers change when values are
+    /// added before others: exchange values with the protobuf JSON mapping, which uses the names
+    /// of the enum values, rather than with the binary encoding. Type and enum value names must be
+    /// valid protobuf identifiers.
+    pub fn to_file_descriptor(&self, package: &str) -> FileDescriptorProto {
+        let mut instances: Vec<_> = self.by_name.values().collect();
+        instances.sort_by(|a, b| a.name.cmp(&b.name));
+
+        FileDescriptorProto {
+            name: Some(format!(
+                "{}.proto",
+                if package.is_empty() { "types" } else { package }
+            )),
+            package: (!package.is_empty()).then(|| package.to_owned()),
+            message_type: instances
+                .into_iter()
+                .map(|instance| message(package, instance))
+                .collect(),
+            syntax: Some("proto3".to_owned()),
+            ..Default::default()
+        }
+    }
+
+    /// Describe the registered types as the source of a `.proto` file, in the specified package.
+    ///
+    /// See [`to_file_descriptor`](Self::to_file_descriptor) for how the types are mapped.
+    pub fn to_proto(&self, package: &str) -> String {
+        let file = self.to_file_descriptor(package);
+        let prefix = match file.package() {
+            "" => ".".to_owned(),
+            package => format!(".{package}."),
+        };
+        let mut proto = String::new();
+
+        writeln!(proto, "syntax = \"{}\";", file.syntax()).unwrap();
+
+        if file.package.is_some() {
+            writeln!(proto, "\npackage {};", file.package()).unwrap();
+        }
+
+        for message in &file.message_type {
+            proto.push('\n');
+            write_message(&mut proto, &prefix, message, 0);
+        }
+
+        proto
+    }
+}
+
+/// Get the fully qualified name of a type of a package.
+fn qualified_name(package: &str, name: impl Display) -> String {
+    if package.is_empty() {
+        format!(".{name}")
+    } else {
+        format!(".{package}.{name}")
+    }
+}
+
+/// Get the type of the fields referencing a type instance.
+fn field_type<Id, FieldName: Ord + Display>(
+    package: &str,
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+) -> FieldType {
+    let scalar = |r#type| FieldType {
+        r#type,
+        type_name: None,
+    };
+
+    match &instance.attributes {
+        TypeAttributesInstance::Array(_) | TypeAttributesInstance::Dictionary(_) => FieldType {
+            r#type: Type::Message,
+            type_name: Some(qualified_name(package, &instance.name)),
+        },
+        TypeAttributesInstance::Boolean(_) => scalar(Type::Bool),
+        TypeAttributesInstance::Int32(_) => scalar(Type::Int32),
+        TypeAttributesInstance::Int64(_) => scalar(Type::Int64),
+        TypeAttributesInstance::Uint32(_) => scalar(Type::Uint32),
+        TypeAttributesInstance::Uint64(_) => scalar(Type::Uint64),
+        TypeAttributesInstance::Float32(_) => scalar(Type::Float),
+        TypeAttributesInstance::Float64(_) => scalar(Type::Double),
+        TypeAttributesInstance::String(_) => scalar(Type::String),
+        // Protobuf enums cannot be empty: empty enums, which have no valid values anyway, fall
+        // back to strings.
+        TypeAttributesInstance::Enum(a) if a.values().next().is_none() => scalar(Type::String),
+        TypeAttributesInstance::Enum(_) => FieldType {
+            r#type: Type::Enum,
+            type_name: Some(format!(
+                "{}.{ENUM_NAME}",
+                qualified_name(package, &instance.name)
+            )),
+        },
+        #[cfg(feature = "uuid")]
+        TypeAttributesInstance::Uuid(_) => scalar(Type::String),
+        #[cfg(feature = "bytes")]
+        TypeAttributesInstance::Bytes(_) => scalar(Type::Bytes),
+    }
+}
+
+/// Get the type of the map keys referencing a type instance, if it can be used as a map key.
+fn key_type<Id, FieldName: Ord>(instance: &TypeDefinitionInstance<Id, FieldName>) -> Option<Type> {
+    match &instance.attributes {
+        TypeAttributesInstance::Boolean(_) => Some(Type::Bool),
+        TypeAttributesInstance::Int32(_) => Some(Type::Int32),
+        TypeAttributesInstance::Int64(_) => Some(Type::Int64),
+        TypeAttributesInstance::Uint32(_) => Some(Type::Uint32),
+        TypeAttributesInstance::Uint64(_) => Some(Type::Uint64),
+        TypeAttributesInstance::String(_) | TypeAttributesInstance::Enum(_) => Some(Type::String),
+        #[cfg(feature = "uuid")]
+        TypeAttributesInstance::Uuid(_) => Some(Type::String),
+        _ => None,
+    }
+}
+
+/// Describe a field.
+fn field(name: &str, number: i32, label: Label, field_type: FieldType) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        name: Some(name.to_owned()),
+        number: Some(number),
+        label: Some(label as i32),
+        r#type: Some(field_type.r#type as i32),
+        type_name: field_type.type_name,
+        ..Default::default()
+    }
+}
+
+/// Describe the message of a type instance.
+fn message<Id, FieldName: Ord + Display>(
+    package: &str,
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+) -> DescriptorProto {
+    let name = instance.name.to_string();
+    let mut message = DescriptorProto {
+        name: Some(name.clone()),
+        ..Default::default()
+    };
+
+    let value = match &instance.attributes {
+        TypeAttributesInstance::Array(a) => field(
+            VALUE_FIELD,
+            1,
+            Label::Repeated,
+            field_type(package, a.items_type_id()),
+        ),
+        TypeAttributesInstance::Dictionary(a) => {
+            // Map fields are repeated fields of map entry messages.
+            let entry = entry_message(package, a);
+            let entry_name = entry.name().to_owned();
+            message.nested_type.push(entry);
+
+            field(
+                VALUE_FIELD,
+                1,
+                Label::Repeated,
+                FieldType {
+                    r#type: Type::Message,
+                    type_name: Some(format!("{}.{entry_name}", qualified_name(package, &name))),
+                },
+            )
+        }
+        TypeAttributesInstance::Enum(a) if a.values().next().is_some() => {
+            let mut enum_type = EnumDescriptorProto {
+                name: Some(ENUM_NAME.to_owned()),
+                ..Default::default()
+            };
+
+            for (number, value) in a.values().enumerate() {
+                enum_type.value.push(EnumValueDescriptorProto {
+                    name: Some(value.to_string()),
+                    number: Some(number as i32),
+                    options: a.is_deprecated(value).then(|| EnumValueOptions {
+                        deprecated: Some(true),
+                        ..Default::default()
+                    }),
+                });
+            }
+
+            for (alias, value) in a.aliases() {
+                let number = a.values().position(|v| v == value).unwrap_or_default();
+
+                enum_type.value.push(EnumValueDescriptorProto {
+                    name: Some(alias.to_string()),
+                    number: Some(number as i32),
+                    options: None,
+                });
+            }
+
+            if a.aliases().next().is_some() {
+                enum_type.options = Some(EnumOptions {
+                    allow_alias: Some(true),
+                    ..Default::default()
+                });
+            }
+
+            message.enum_type.push(enum_type);
+
+            field(
+                VALUE_FIELD,
+                1,
+                Label::Optional,
+                field_type(package, instance),
+            )
+        }
+        _ => field(
+            VALUE_FIELD,
+            1,
+            Label::Optional,
+            field_type(package, instance),
+        ),
+    };
+
+    message.field.push(value);
+    message
+}
+
+/// Describe the entry message of a dictionary type, which is a map entry if its keys can be map
+/// keys.
+fn entry_message<Id, FieldName: Ord + Display>(
+    package: &str,
+    attributes: &DictionaryTypeAttributes<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+) -> DescriptorProto {
+    let (name, key, options) = match key_type(attributes.keys_type_id()) {
+        Some(key) => (
+            "ValueEntry",
+            FieldType {
+                r#type: key,
+                type_name: None,
+            },
+            Some(MessageOptions {
+                map_entry: Some(true),
+                ..Default::default()
+            }),
+        ),
+        None => (
+            "Entry",
+            field_type(package, attributes.keys_type_id()),
+            None,
+        ),
+    };
+
+    DescriptorProto {
+        name: Some(name.to_owned()),
+        field: vec![
+            field("key", 1, Label::Optional, key),
+            field(
+                "value",
+                2,
+                Label::Optional,
+                field_type(package, attributes.values_type_id()),
+            ),
+        ],
+        options,
+        ..Default::default()
+    }
+}
+
+/// Get the name of the type of a field in a `.proto` file, relative to the scope of its message
+/// or to its package.
+fn type_name(scope: &str, prefix: &str, field: &FieldDescriptorProto) -> String {
+    match field.r#type() {
+        Type::Message | Type::Enum | Type::Group => field
+            .type_name()
+            .strip_prefix(scope)
+            .or_else(|| field.type_name().strip_prefix(prefix))
+            .unwrap_or(field.type_name())
+            .to_owned(),
+        Type::Bool => "bool".to_owned(),
+        Type::Int32 => "int32".to_owned(),
+        Type::Int64 => "int64".to_owned(),
+        Type::Uint32 => "uint32".to_owned(),
+        Type::Uint64 => "uint64".to_owned(),
+        Type::Sint32 => "sint32".to_owned(),
+        Type::Sint64 => "sint64".to_owned(),
+        Type::Fixed32 => "fixed32".to_owned(),
+        Type::Fixed64 => "fixed64".to_owned(),
+        Type::Sfixed32 => "sfixed32".to_owned(),
+        Type::Sfixed64 => "sfixed64".to_owned(),
+        Type::Float => "float".to_owned(),
+        Type::Double => "double".to_owned(),
+        Type::String => "string".to_owned(),
+        Type::Bytes => "bytes".to_owned(),
+    }
+}
+
+/// Write a message of a `.proto` file.
+fn write_message(proto: &mut String, prefix: &str, message: &DescriptorProto, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let scope = format!("{prefix}{}.", message.name());
+    let map_entries: Vec<_> = message
+        .nested_type
+        .iter()
+        .filter(|nested| nested.options.as_ref().is_some_and(|o| o.map_entry()))
+        .collect();
+
+    writeln!(proto, "{indent}message {} {{", message.name()).unwrap();
+
+    for nested in &message.nested_type {
+        if !map_entries.contains(&nested) {
+            write_message(proto, prefix, nested, depth + 1);
+            proto.push('\n');
+        }
+    }
+
+    for enum_type in &message.enum_type {
+        writeln!(proto, "{indent}  enum {} {{", enum_type.name()).unwrap();
+
+        if enum_type.options.as_ref().is_some_and(|o| o.allow_alias()) {
+            writeln!(proto, "{indent}    option allow_alias = true;").unwrap();
+        }
+
+        for value in &enum_type.value {
+            let deprecated = if value.options.as_ref().is_some_and(|o| o.deprecated()) {
+                " [deprecated = true]"
+            } else {
+                ""
+            };
+
+            writeln!(
+                proto,
+                "{indent}    {} = {}{deprecated};",
+                value.name(),
+                value.number()
+            )
+            .unwrap();
+        }
+
+        writeln!(proto, "{indent}  }}\n").unwrap();
+    }
+
+    for field in &message.field {
+        let map_entry = map_entries.iter().find(|entry| {
+            field
+                .type_name()
+                .rsplit('.')
+                .next()
+                .is_some_and(|name| name == entry.name())
+        });
+
+        let type_name = match map_entry {
+            Some(entry) => format!(
+                "map<{}, {}>",
+                type_name(&scope, prefix, &entry.field[0]),
+                type_name(&scope, prefix, &entry.field[1])
+            ),
+            None if field.label() == Label::Repeated => {
+                format!("repeated {}", type_name(&scope, prefix, field))
+            }
+            None => type_name(&scope, prefix, field),
+        };
+
+        writeln!(
+            proto,
+            "{indent}  {type_name} {} = {};",
+            field.name(),
+            field.number()
+        )
+        .unwrap();
+    }
+
+    writeln!(proto, "{indent}}}").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_support::{TypeAttributes, registry, td},
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, DictionaryWireForm, EnumTypeAttributes,
+        },
+    };
+
+    #[test]
+    fn test_to_proto() {
+        let registry = registry([
+            td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+            td(
+                2,
+                "MyColor",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("red")
+                        .with_value_ext("green", None, true)
+                        .with_alias("rouge", "red")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                3,
+                "MyColors",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+            td(
+                4,
+                "MyScores",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(2, 1)),
+            ),
+            td(
+                5,
+                "MyPalettes",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(3, 4).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+        ]);
+
+        let file = registry.to_file_descriptor("game");
+        assert_eq!(file.name(), "game.proto");
+        assert_eq!(file.message_type.len(), 5);
+
+        assert_eq!(
+            registry.to_proto("game"),
+            r#"syntax = "proto3";
+
+package game;
+
+message MyColor {
+  enum Value {
+    option allow_alias = true;
+    green = 0 [deprecated = true];
+    red = 1;
+    rouge = 1;
+  }
+
+  Value value = 1;
+}
+
+message MyColors {
+  repeated MyColor.Value value = 1;
+}
+
+message MyInt {
+  int32 value = 1;
+}
+
+message MyPalettes {
+  message Entry {
+    MyColors key = 1;
+    MyScores value = 2;
+  }
+
+  repeated Entry value = 1;
+}
+
+message MyScores {
+  map<string, int32> value = 1;
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! TypeScript declarations.
+
+use std::{fmt::Display, fmt::Write, hash::Hash, sync::Arc};
+
+use crate::{
+    DictionaryWireForm, TypeDefinitionInstance, TypeDefinitionRegistry,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display,
+{
+    /// Generate TypeScript declarations (`.d.ts`) describing the JSON values of the registered
+    /// types.
+    ///
+    /// Every type is declared as a type alias with the same name:
+    ///
+    /// - Booleans map to `boolean`, numbers to `number`, and strings, UUIDs and bytes to `string`.
+    /// - Enums map to unions of the string literals of their values. They also get a constant
+    ///   object with the same name, whose properties are their values: see
+    ///   [`to_typescript_enums`](Self::to_typescript_enums) for its definition.
+    /// - Arrays map to arrays of their items.
+    /// - Dictionaries map to index signatures, or to mapped types with optional properties for
+    ///   enum keys. Dictionaries of pairs map to arrays of key-value tuples.
+    ///
+    /// Descriptions and deprecations become JSDoc comments. Type names must be valid TypeScript
+    /// identifiers.
+    pub fn to_typescript_declarations(&self) -> String {
+        let mut ts = String::new();
+
+        for instance in self.sorted_by_name() {
+            if !ts.is_empty() {
+                ts.push('\n');
+            }
+
+            let name = &instance.name;
+
+            write_doc(&mut ts, "", instance.description.as_deref(), false);
+            writeln!(ts, "export type {name} = {};", type_expression(instance)).unwrap();
+
+            if let TypeAttributesInstance::Enum(a) = &instance.attributes {
+                writeln!(ts, "export declare const {name}: {{").unwrap();
+
+                for value in a.values() {
+                    write_doc(&mut ts, "  ", None, a.is_deprecated(value));
+                    writeln!(
+                        ts,
+                        "  readonly {}: {};",
+                        property_name(value),
+                        string_literal(value)
+                    )
+                    .unwrap();
+                }
+
+                writeln!(ts, "}};").unwrap();
+            }
+        }
+
+        ts
+    }
+
+    /// Generate the JavaScript module defining the runtime objects of the enums, declared by
+    /// [`to_typescript_declarations`](Self::to_typescript_declarations).
+    ///
+    /// Each enum is a frozen object with the same name, whose properties are its values, so that
+    /// tools can list the values of an enum or reference them without repeating string literals.
+    pub fn to_typescript_enums(&self) -> String {
+        let mut js = String::new();
+
+        for instance in self.sorted_by_name() {
+            let TypeAttributesInstance::Enum(a) = &instance.attributes else {
+                continue;
+            };
+
+            if !js.is_empty() {
+                js.push('\n');
+            }
+
+            writeln!(js, "export const {} = Object.freeze({{", instance.name).unwrap();
+
+            for value in a.values() {
+                writeln!(js, "  {}: {},", property_name(value), string_literal(value)).unwrap();
+            }
+
+            writeln!(js, "}});").unwrap();
+        }
+
+        js
+    }
+
+    /// Get the registered type instances, sorted by name.
+    fn sorted_by_name(&self) -> Vec<&Arc<TypeDefinitionInstance<Id, FieldName>>> {
+        let mut instances: Vec<_> = self.by_name.values().collect();
+        instances.sort_by(|a, b| a.name.cmp(&b.name));
+
+        instances
+    }
+}
+
+/// Get the TypeScript type expression of the values of a type instance.
+fn type_expression<Id, FieldName: Ord + Display>(
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+) -> String {
+    match &instance.attributes {
+        TypeAttributesInstance::Array(a) => format!("{}[]", a.items_type_id().name),
+        TypeAttributesInstance::Dictionary(a) => {
+            let keys = a.keys_type_id();
+            let values = &a.values_type_id().name;
+
+            match (a.wire_form(), &keys.attributes) {
+                (DictionaryWireForm::Pairs, _) => format!("[{}, {values}][]", keys.name),
+                (_, TypeAttributesInstance::Enum(_)) => {
+                    format!("{{ [K in {}]?: {values} }}", keys.name)
+                }
+                _ => format!("{{ [key: string]: {values} }}"),
+            }
+        }
+        TypeAttributesInstance::Boolean(_) => "boolean".to_owned(),
+        TypeAttributesInstance::Int32(_)
+        | TypeAttributesInstance::Int64(_)
+        | TypeAttributesInstance::Uint32(_)
+        | TypeAttributesInstance::Uint64(_)
+        | TypeAttributesInstance::Float32(_)
+        | TypeAttributesInstance::Float64(_) => "number".to_owned(),
+        TypeAttributesInstance::String(_) => "string".to_owned(),
+        TypeAttributesInstance::Enum(a) if a.values().next().is_none() => "never".to_owned(),
+        TypeAttributesInstance::Enum(a) => a
+            .values()
+            .map(string_literal)
+            .collect::<Vec<_>>()
+            .join(" | "),
+        #[cfg(feature = "uuid")]
+        TypeAttributesInstance::Uuid(_) => "string".to_owned(),
+        #[cfg(feature = "bytes")]
+        TypeAttributesInstance::Bytes(_) => "string".to_owned(),
+    }
+}
+
+/// Get a string literal, quoted and escaped as in JSON, which TypeScript accepts.
+fn string_literal(s: impl Display) -> String {
+    serde_json::to_string(&s.to_string()).expect("strings always serialize to JSON")
+}
+
+/// Get a property name, quoted unless it is a valid identifier.
+fn property_name(s: impl Display) -> String {
+    let s = s.to_string();
+    let mut chars = s.chars();
+    let is_identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+
+    if is_identifier { s } else { string_literal(s) }
+}
+
+/// Write a JSDoc comment, if there is anything to document.
+fn write_doc(ts: &mut String, indent: &str, description: Option<&str>, deprecated: bool) {
+    let mut lines: Vec<_> = description.into_iter().flat_map(str::lines).collect();
+
+    if deprecated {
+        lines.push("@deprecated");
+    }
+
+    match lines.as_slice() {
+        [] => {}
+        [line] => writeln!(ts, "{indent}/** {line} */").unwrap(),
+        lines => {
+            writeln!(ts, "{indent}/**").unwrap();
+
+            for line in lines {
+                writeln!(ts, "{indent} * {line}").unwrap();
+            }
+
+            writeln!(ts, "{indent} */").unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_support::{TypeAttributes, TypeDefinition, TypeDefinitionRegistry, registry, td},
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, DictionaryWireForm, EnumTypeAttributes,
+        },
+    };
+
+    fn teams_registry() -> TypeDefinitionRegistry {
+        registry([
+            td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+            TypeDefinition {
+                description: Some("The colors of the teams.".to_owned()),
+                ..td(
+                    2,
+                    "MyColor",
+                    TypeAttributes::Enum(
+                        EnumTypeAttributes::builder()
+                            .with_value("red")
+                            .with_value_ext("dark-green", None, true)
+                            .with_alias("rouge", "red")
+                            .build()
+                            .unwrap(),
+                    ),
+                )
+            },
+            td(
+                3,
+                "MyColors",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+            td(
+                4,
+                "MyScores",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(2, 1)),
+            ),
+            td(5, "MyString", TypeAttributes::String(Default::default())),
+            td(
+                6,
+                "MyNames",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(5, 1)),
+            ),
+            td(
+                7,
+                "MyPalettes",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(3, 1).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_to_typescript_declarations() {
+        assert_eq!(
+            teams_registry().to_typescript_declarations(),
+            r#"/** The colors of the teams. */
+export type MyColor = "dark-green" | "red";
+export declare const MyColor: {
+  /** @deprecated */
+  readonly "dark-green": "dark-green";
+  readonly red: "red";
+};
+
+export type MyColors = MyColor[];
+
+export type MyInt = number;
+
+export type MyNames = { [key: string]: MyInt };
+
+export type MyPalettes = [MyColors, MyInt][];
+
+export type MyScores = { [K in MyColor]?: MyInt };
+
+export type MyString = string;
+"#
+        );
+    }
+
+    #[test]
+    fn test_to_typescript_enums() {
+        assert_eq!(
+            teams_registry().to_typescript_enums(),
+            r#"export const MyColor = Object.freeze({
+  "dark-green": "dark-green",
+  red: "red",
+});
+"#
+        );
+    }
+}
@@ -0,0 +1,516 @@
+//! C++ headers for Unreal Engine.
+
+use std::{fmt::Display, fmt::Write, hash::Hash};
+
+use crate::{
+    TypeDefinitionInstance, TypeDefinitionRegistry, codegen::NameCase,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::topological_order;
+
+/// Options that control how C++ headers for Unreal Engine are generated.
+///
+/// See [`to_unreal_header`](TypeDefinitionRegistry::to_unreal_header).
+#[derive(Debug, Clone)]
+pub struct UnrealOptions {
+    /// The name of the header file, without its extension.
+    pub(crate) file_name: String,
+
+    /// The macro exporting the structs from their module, if any.
+    pub(crate) api_macro: Option<String>,
+
+    /// The prefix of the names of the structs.
+    pub(crate) struct_prefix: String,
+
+    /// The prefix of the names of the enums.
+    pub(crate) enum_prefix: String,
+
+    /// How the names of the types are converted.
+    pub(crate) type_case: NameCase,
+
+    /// How the names of the enum values are converted.
+    pub(crate) enum_value_case: NameCase,
+
+    /// The name of the property holding the value in every struct.
+    pub(crate) property_name: String,
+}
+
+impl Default for UnrealOptions {
+    fn default() -> Self {
+        Self {
+            file_name: "GameSONTypes".to_owned(),
+            api_macro: None,
+            struct_prefix: "F".to_owned(),
+            enum_prefix: "E".to_owned(),
+            type_case: NameCase::Pascal,
+            enum_value_case: NameCase::Pascal,
+            property_name: "Value".to_owned(),
+        }
+    }
+}
+
+impl UnrealOptions {
+    /// Sets the name of the header file, without its extension, which the Unreal Header Tool
+    /// requires to include the generated header.
+    ///
+    /// Defaults to `GameSONTypes`.
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = file_name.into();
+        self
+    }
+
+    /// Sets the macro exporting the structs from their module, such as `MYGAME_API`.
+    ///
+    /// Defaults to none.
+    pub fn api_macro(mut self, api_macro: impl Into<String>) -> Self {
+        self.api_macro = Some(api_macro.into());
+        self
+    }
+
+    /// Sets the prefix of the names of the structs.
+    ///
+    /// Defaults to `F`, as Unreal Engine expects.
+    pub fn struct_prefix(mut self, struct_prefix: impl Into<String>) -> Self {
+        self.struct_prefix = struct_prefix.into();
+        self
+    }
+
+    /// Sets the prefix of the names of the enums.
+    ///
+    /// Defaults to `E`, as Unreal Engine expects.
+    pub fn enum_prefix(mut self, enum_prefix: impl Into<String>) -> Self {
+        self.enum_prefix = enum_prefix.into();
+        self
+    }
+
+    /// Sets how the names of the types are converted.
+    ///
+    /// Defaults to [`NameCase::Pascal`].
+    pub fn type_case(mut self, type_case: NameCase) -> Self {
+        self.type_case = type_case;
+        self
+    }
+
+    /// Sets how the names of the enum values are converted.
+    ///
+    /// Defaults to [`NameCase::Pascal`].
+    pub fn enum_value_case(mut self, enum_value_case: NameCase) -> Self {
+        self.enum_value_case = enum_value_case;
+        self
+    }
+
+    /// Sets the name of the property holding the value in every struct.
+    ///
+    /// Defaults to `Value`.
+    pub fn property_name(mut self, property_name: impl Into<String>) -> Self {
+        self.property_name = property_name.into();
+        self
+    }
+
+    /// Get the C++ name of a type instance.
+    fn type_name<Id, FieldName: Ord + Display>(
+        &self,
+        instance: &TypeDefinitionInstance<Id, FieldName>,
+    ) -> String {
+        let prefix = match &instance.attributes {
+            TypeAttributesInstance::Enum(_) => &self.enum_prefix,
+            _ => &self.struct_prefix,
+        };
+
+        format!("{prefix}{}", self.type_case.convert(&instance.name))
+    }
+
+    /// Get the C++ type of the properties referencing a type instance.
+    fn property_type<Id, FieldName: Ord + Display>(
+        &self,
+        instance: &TypeDefinitionInstance<Id, FieldName>,
+    ) -> String {
+        match &instance.attributes {
+            TypeAttributesInstance::Array(_)
+            | TypeAttributesInstance::Dictionary(_)
+            | TypeAttributesInstance::Enum(_) => self.type_name(instance),
+            TypeAttributesInstance::Boolean(_) => "bool".to_owned(),
+            TypeAttributesInstance::Int32(_) => "int32".to_owned(),
+            TypeAttributesInstance::Int64(_) => "int64".to_owned(),
+            TypeAttributesInstance::Uint32(_) => "uint32".to_owned(),
+            TypeAttributesInstance::Uint64(_) => "uint64".to_owned(),
+            TypeAttributesInstance::Float32(_) => "float".to_owned(),
+            TypeAttributesInstance::Float64(_) => "double".to_owned(),
+            TypeAttributesInstance::String(_) => "FString".to_owned(),
+            #[cfg(feature = "uuid")]
+            TypeAttributesInstance::Uuid(_) => "FGuid".to_owned(),
+            #[cfg(feature = "bytes")]
+            TypeAttributesInstance::Bytes(_) => "TArray<uint8>".to_owned(),
+        }
+    }
+
+    /// Get the C++ initializer of the properties of a type instance, if it has a default value.
+    fn default_value<Id, FieldName: Ord + Display>(
+        &self,
+        instance: &TypeDefinitionInstance<Id, FieldName>,
+    ) -> Option<String> {
+        match &instance.attributes {
+            TypeAttributesInstance::Boolean(a) => a.default_value().map(|v| v.to_string()),
+            TypeAttributesInstance::Int32(a) => a.default_value().map(|v| v.to_string()),
+            TypeAttributesInstance::Int64(a) => a.default_value().map(|v| v.to_string()),
+            TypeAttributesInstance::Uint32(a) => a.default_value().map(|v| format!("{v}u")),
+            TypeAttributesInstance::Uint64(a) => a.default_value().map(|v| format!("{v}ull")),
+            TypeAttributesInstance::Float32(a) => a.default_value().map(|v| format!("{v:?}f")),
+            TypeAttributesInstance::Float64(a) => a.default_value().map(|v| format!("{v:?}")),
+            TypeAttributesInstance::String(a) => a.default_value().map(|v| {
+                format!(
+                    "TEXT({})",
+                    serde_json::to_string(v).expect("strings always serialize to JSON")
+                )
+            }),
+            TypeAttributesInstance::Enum(a) => a.default_value().map(|v| {
+                format!(
+                    "{}::{}",
+                    self.type_name(instance),
+                    self.enum_value_case.convert(v)
+                )
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display,
+{
+    /// Generate a C++ header for Unreal Engine, declaring the specified types and all the types
+    /// they reference.
+    ///
+    /// Unknown root identifiers are ignored. Enums map to `UENUM`s, whose values keep their
+    /// original names as display names, and whose deprecated values are hidden. All the other
+    /// types map to `USTRUCT`s, with a single property:
+    ///
+    /// - Booleans and numbers map to the matching C++ types, strings to `FString`, UUIDs to
+    ///   `FGuid` and bytes to `TArray<uint8>`. Default values become the initializers of the
+    ///   properties.
+    /// - Arrays map to `TArray`s of their items.
+    /// - Dictionaries map to `TMap`s. Dictionaries of pairs whose keys are arrays, dictionaries
+    ///   or bytes map to `TArray`s of `Entry` structs instead, with `Key` and `Value` properties.
+    ///
+    /// Properties referencing booleans, numbers, strings, UUIDs and bytes use their C++ types
+    /// directly, while the ones referencing enums, arrays and dictionaries use the generated
+    /// types. The types are declared after the types they reference.
+    pub fn to_unreal_header<'a>(
+        &self,
+        roots: impl IntoIterator<Item = &'a Id>,
+        options: &UnrealOptions,
+    ) -> String
+    where
+        Id: 'a,
+    {
+        let reachable = self.reachable_from(roots);
+        let mut header = String::new();
+
+        writeln!(header, "#pragma once\n").unwrap();
+        writeln!(header, "#include \"CoreMinimal.h\"").unwrap();
+        writeln!(header, "#include \"{}.generated.h\"", options.file_name).unwrap();
+
+        for instance in topological_order(reachable.iter().map(|id| &self.by_id[id])) {
+            header.push('\n');
+
+            write_type(&mut header, instance, options);
+        }
+
+        header
+    }
+}
+
+/// Write the declaration of a type instance.
+fn write_type<Id, FieldName: Ord + Display>(
+    header: &mut String,
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+    options: &UnrealOptions,
+) {
+    let name = options.type_name(instance);
+
+    if let Some(description) = &instance.description {
+        writeln!(
+            header,
+            "/** {} */",
+            description.lines().collect::<Vec<_>>().join(" ")
+        )
+        .unwrap();
+    }
+
+    let property_type = match &instance.attributes {
+        TypeAttributesInstance::Enum(a) => {
+            // Blueprints only support enums whose underlying type is `uint8`.
+            if a.values().count() <= 0x100 {
+                writeln!(
+                    header,
+                    "UENUM(BlueprintType)\nenum class {name} : uint8\n{{"
+                )
+                .unwrap();
+            } else {
+                writeln!(header, "UENUM()\nenum class {name} : int32\n{{").unwrap();
+            }
+
+            for value in a.values() {
+                let hidden = if a.is_deprecated(value) {
+                    ", Hidden"
+                } else {
+                    ""
+                };
+
+                writeln!(
+                    header,
+                    "\t{} UMETA(DisplayName = \"{value}\"{hidden}),",
+                    options.enum_value_case.convert(value)
+                )
+                .unwrap();
+            }
+
+            writeln!(header, "}};").unwrap();
+
+            return;
+        }
+        TypeAttributesInstance::Array(a) => {
+            format!("TArray<{}>", options.property_type(a.items_type_id()))
+        }
+        TypeAttributesInstance::Dictionary(a) => {
+            let keys = a.keys_type_id();
+            let values = a.values_type_id();
+            let is_hashable = match &keys.attributes {
+                TypeAttributesInstance::Array(_) | TypeAttributesInstance::Dictionary(_) => false,
+                #[cfg(feature = "bytes")]
+                TypeAttributesInstance::Bytes(_) => false,
+                _ => true,
+            };
+
+            if is_hashable {
+                format!(
+                    "TMap<{}, {}>",
+                    options.property_type(keys),
+                    options.property_type(values)
+                )
+            } else {
+                let entry = format!("{name}Entry");
+
+                write_struct(
+                    header,
+                    &entry,
+                    [
+                        ("Key", options.property_type(keys), None),
+                        ("Value", options.property_type(values), None),
+                    ],
+                    options,
+                );
+                header.push('\n');
+
+                format!("TArray<{entry}>")
+            }
+        }
+        _ => options.property_type(instance),
+    };
+
+    write_struct(
+        header,
+        &name,
+        [(
+            options.property_name.as_str(),
+            property_type,
+            options.default_value(instance),
+        )],
+        options,
+    );
+}
+
+/// Write a struct, with its properties and their initializers, if any.
+fn write_struct<'a>(
+    header: &mut String,
+    name: &str,
+    properties: impl IntoIterator<Item = (&'a str, String, Option<String>)>,
+    options: &UnrealOptions,
+) {
+    writeln!(header, "USTRUCT(BlueprintType)").unwrap();
+
+    match &options.api_macro {
+        Some(api_macro) => writeln!(header, "struct {api_macro} {name}"),
+        None => writeln!(header, "struct {name}"),
+    }
+    .unwrap();
+
+    writeln!(header, "{{\n\tGENERATED_BODY()").unwrap();
+
+    for (property_name, property_type, default) in properties {
+        writeln!(header, "\n\tUPROPERTY(EditAnywhere, BlueprintReadWrite)").unwrap();
+
+        match default {
+            Some(default) => writeln!(header, "\t{property_type} {property_name} = {default};"),
+            None => writeln!(header, "\t{property_type} {property_name};"),
+        }
+        .unwrap();
+    }
+
+    writeln!(header, "}};").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        codegen::NameCase,
+        test_support::{TypeAttributes, TypeDefinition, TypeDefinitionRegistry, registry, td},
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, DictionaryWireForm, EnumTypeAttributes,
+            NumberTypeAttributes,
+        },
+    };
+
+    use super::UnrealOptions;
+
+    fn teams_registry() -> TypeDefinitionRegistry {
+        registry([
+            td(
+                1,
+                "speed",
+                TypeAttributes::Float32(
+                    NumberTypeAttributes::builder()
+                        .default_value(1.5)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            TypeDefinition {
+                description: Some("The colors of the teams.".to_owned()),
+                ..td(
+                    2,
+                    "team_color",
+                    TypeAttributes::Enum(
+                        EnumTypeAttributes::builder()
+                            .with_value("red")
+                            .with_value_ext("dark-green", None, true)
+                            .with_default("red")
+                            .build()
+                            .unwrap(),
+                    ),
+                )
+            },
+            td(
+                3,
+                "team_colors",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+            td(
+                4,
+                "team_speeds",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(2, 1)),
+            ),
+            td(
+                5,
+                "palettes",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(3, 4).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+            td(6, "name", TypeAttributes::String(Default::default())),
+        ])
+    }
+
+    #[test]
+    fn test_to_unreal_header() {
+        assert_eq!(
+            teams_registry().to_unreal_header(
+                &[5],
+                &UnrealOptions::default()
+                    .file_name("Palettes")
+                    .api_macro("MYGAME_API")
+            ),
+            r#"#pragma once
+
+#include "CoreMinimal.h"
+#include "Palettes.generated.h"
+
+/** The colors of the teams. */
+UENUM(BlueprintType)
+enum class ETeamColor : uint8
+{
+	DarkGreen UMETA(DisplayName = "dark-green", Hidden),
+	Red UMETA(DisplayName = "red"),
+};
+
+USTRUCT(BlueprintType)
+struct MYGAME_API FTeamColors
+{
+	GENERATED_BODY()
+
+	UPROPERTY(EditAnywhere, BlueprintReadWrite)
+	TArray<ETeamColor> Value;
+};
+
+USTRUCT(BlueprintType)
+struct MYGAME_API FSpeed
+{
+	GENERATED_BODY()
+
+	UPROPERTY(EditAnywhere, BlueprintReadWrite)
+	float Value = 1.5f;
+};
+
+USTRUCT(BlueprintType)
+struct MYGAME_API FTeamSpeeds
+{
+	GENERATED_BODY()
+
+	UPROPERTY(EditAnywhere, BlueprintReadWrite)
+	TMap<ETeamColor, float> Value;
+};
+
+USTRUCT(BlueprintType)
+struct MYGAME_API FPalettesEntry
+{
+	GENERATED_BODY()
+
+	UPROPERTY(EditAnywhere, BlueprintReadWrite)
+	FTeamColors Key;
+
+	UPROPERTY(EditAnywhere, BlueprintReadWrite)
+	FTeamSpeeds Value;
+};
+
+USTRUCT(BlueprintType)
+struct MYGAME_API FPalettes
+{
+	GENERATED_BODY()
+
+	UPROPERTY(EditAnywhere, BlueprintReadWrite)
+	TArray<FPalettesEntry> Value;
+};
+"#
+        );
+    }
+
+    #[test]
+    fn test_to_unreal_header_options() {
+        assert_eq!(
+            teams_registry().to_unreal_header(
+                &[6],
+                &UnrealOptions::default()
+                    .struct_prefix("FGs")
+                    .type_case(NameCase::Verbatim)
+                    .property_name("Data")
+            ),
+            r#"#pragma once
+
+#include "CoreMinimal.h"
+#include "GameSONTypes.generated.h"
+
+USTRUCT(BlueprintType)
+struct FGsname
+{
+	GENERATED_BODY()
+
+	UPROPERTY(EditAnywhere, BlueprintReadWrite)
+	FString Data;
+};
+"#
+        );
+    }
+}
@@ -0,0 +1,534 @@
+//! Compatibility of type definitions across schema changes.
+
+use std::{fmt::Display, hash::Hash, sync::Arc};
+
+use crate::{
+    EntryConstraint, EntryRequirement, Expression, RegistrationError, TypeAttributes,
+    TypeDefinition, TypeDefinitionInstance, TypeDefinitionRegistry,
+    type_attributes::{EnumTypeAttributes, NumberTypeAttributes},
+};
+
+/// How values can be exchanged between two versions of a type definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Values of either version can be read with the other one.
+    Full,
+
+    /// Values of the old version can be read with the new one, but not the other way around, for
+    /// the specified reasons.
+    Backward(Vec<String>),
+
+    /// Values of the new version can be read with the old one, but not the other way around, for
+    /// the specified reasons.
+    Forward(Vec<String>),
+
+    /// Values of either version cannot be read with the other one, for the specified reasons.
+    Breaking(Vec<String>),
+}
+
+impl Compatibility {
+    /// Check whether values of the old version can be read with the new one.
+    pub fn is_backward(&self) -> bool {
+        matches!(self, Self::Full | Self::Backward(_))
+    }
+
+    /// Check whether values of the new version can be read with the old one.
+    pub fn is_forward(&self) -> bool {
+        matches!(self, Self::Full | Self::Forward(_))
+    }
+
+    /// Get the reasons why the versions are not fully compatible.
+    pub fn reasons(&self) -> &[String] {
+        match self {
+            Self::Full => &[],
+            Self::Backward(reasons) | Self::Forward(reasons) | Self::Breaking(reasons) => reasons,
+        }
+    }
+}
+
+/// The rules a registry enforces when type definitions are replaced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvolutionPolicy {
+    /// Type definitions can be replaced by any other type definition.
+    #[default]
+    Unrestricted,
+
+    /// Type definitions can only be replaced by backward compatible type definitions, so that
+    /// the values of the previous type definitions remain readable.
+    ///
+    /// The kinds of the types and the types they reference cannot change, numeric ranges may
+    /// only widen, and enum values and aliases may be added or deprecated but never removed. See
+    /// [`TypeDefinition::compatibility`].
+    BackwardCompatible,
+}
+
+/// Get the reasons why values of the old version of a type definition cannot be read with the new
+/// one.
+pub(crate) fn backward_violations<Id: PartialEq + Display, FieldName: Ord + Display + Clone>(
+    old: &TypeDefinition<Id, FieldName>,
+    new: &TypeDefinition<Id, FieldName>,
+) -> Vec<String> {
+    let mut report = Report::default();
+    report.check(&old.attributes, &new.attributes);
+
+    let Report {
+        mut breaking,
+        mut backward,
+        forward: _,
+    } = report;
+    breaking.append(&mut backward);
+
+    breaking
+}
+
+impl<Id: PartialEq + Display, FieldName: Ord + Display + Clone> TypeDefinition<Id, FieldName> {
+    /// Check how values can be exchanged between two versions of a type definition.
+    ///
+    /// Only the attributes of the type definitions matter: the types they reference are compared
+    /// by identifier, and must be checked separately. Changing the kind of a type is breaking,
+    /// while ranges may widen, and enum values and aliases may be added or deprecated, to remain
+    /// backward compatible.
+    pub fn compatibility(old: &Self, new: &Self) -> Compatibility {
+        let mut report = Report::default();
+        report.check(&old.attributes, &new.attributes);
+
+        report.into_compatibility()
+    }
+}
+
+impl<Id: Ord + Hash + Clone + Display, FieldName: Ord + Hash + Clone + Display>
+    TypeDefinitionRegistry<Id, FieldName>
+{
+    /// Replace a registered type definition, if the values of the registered one can be read
+    /// with the new one.
+    ///
+    /// See [`replace`](Self::replace) and [`TypeDefinition::compatibility`] for details.
+    #[expect(
+        clippy::type_complexity,
+        reason = "inherent associated types are not yet stable so we can't do much about it here"
+    )]
+    pub fn replace_compatible(
+        &mut self,
+        type_definition: TypeDefinition<Id, FieldName>,
+    ) -> Result<
+        Vec<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+        (
+            TypeDefinition<Id, FieldName>,
+            RegistrationError<Id, FieldName>,
+        ),
+    > {
+        self.replace_with_check(type_definition, |previous, new| {
+            let violations = backward_violations(&previous.to_definition(), &new.to_definition());
+
+            if violations.is_empty() {
+                Ok(())
+            } else {
+                Err(violations.join("; "))
+            }
+        })
+    }
+}
+
+/// The reasons why two versions of a type definition are not compatible.
+#[derive(Default)]
+struct Report {
+    /// Why values of either version cannot be read with the other one.
+    breaking: Vec<String>,
+
+    /// Why values of the old version cannot be read with the new one.
+    backward: Vec<String>,
+
+    /// Why values of the new version cannot be read with the old one.
+    forward: Vec<String>,
+}
+
+impl Report {
+    /// Compare the attributes of two versions of a type definition.
+    fn check<Id: PartialEq + Display, FieldName: Ord + Display + Clone>(
+        &mut self,
+        old: &TypeAttributes<Id, FieldName>,
+        new: &TypeAttributes<Id, FieldName>,
+    ) {
+        match (old, new) {
+            (TypeAttributes::Array(a), TypeAttributes::Array(b)) => {
+                self.check_reference("items", a.items_type_id(), b.items_type_id());
+            }
+            (TypeAttributes::Dictionary(a), TypeAttributes::Dictionary(b))
+                if a.wire_form() == b.wire_form() =>
+            {
+                self.check_reference("keys", a.keys_type_id(), b.keys_type_id());
+                self.check_reference("values", a.values_type_id(), b.values_type_id());
+                self.check_constraints(a.constraints(), b.constraints());
+                self.check_requirements(a.requirements(), b.requirements());
+                self.check_invariants(a.invariants(), b.invariants());
+            }
+            (TypeAttributes::Boolean(_), TypeAttributes::Boolean(_)) => {}
+            (TypeAttributes::Int32(a), TypeAttributes::Int32(b)) => self.check_range(a, b),
+            (TypeAttributes::Int64(a), TypeAttributes::Int64(b)) => self.check_range(a, b),
+            (TypeAttributes::Uint32(a), TypeAttributes::Uint32(b)) => self.check_range(a, b),
+            (TypeAttributes::Uint64(a), TypeAttributes::Uint64(b)) => self.check_range(a, b),
+            (TypeAttributes::Float32(a), TypeAttributes::Float32(b)) => self.check_range(a, b),
+            (TypeAttributes::Float64(a), TypeAttributes::Float64(b)) => self.check_range(a, b),
+            (TypeAttributes::String(_), TypeAttributes::String(_)) => {}
+            (TypeAttributes::Enum(a), TypeAttributes::Enum(b)) => self.check_enum(a, b),
+            #[cfg(feature = "uuid")]
+            (TypeAttributes::Uuid(_), TypeAttributes::Uuid(_)) => {}
+            #[cfg(feature = "bytes")]
+            (TypeAttributes::Bytes(a), TypeAttributes::Bytes(b)) => {
+                if a.encoding() != b.encoding() {
+                    self.breaking.push(format!(
+                        "encoding changed from {} to {}",
+                        a.encoding(),
+                        b.encoding()
+                    ));
+                }
+            }
+            _ => self
+                .breaking
+                .push(format!("cannot change {} into {}", old.kind(), new.kind())),
+        }
+    }
+
+    /// Compare a type reference of two versions of a type definition.
+    fn check_reference<Id: PartialEq + Display>(&mut self, what: &str, old: &Id, new: &Id) {
+        if old != new {
+            self.breaking
+                .push(format!("{what} type changed from `{old}` to `{new}`"));
+        }
+    }
+
+    /// Compare the ranges of two versions of a number type.
+    fn check_range<Num: PartialOrd + Copy + Display>(
+        &mut self,
+        old: &NumberTypeAttributes<Num>,
+        new: &NumberTypeAttributes<Num>,
+    ) {
+        let bound = |bound: Option<Num>| match bound {
+            Some(bound) => bound.to_string(),
+            None => "unbounded".to_owned(),
+        };
+
+        match (old.min(), new.min()) {
+            (old_min, Some(new_min)) if old_min.is_none_or(|old_min| new_min > old_min) => {
+                self.backward.push(format!(
+                    "minimum raised from {} to {new_min}",
+                    bound(old_min)
+                ));
+            }
+            (Some(old_min), new_min) if new_min.is_none_or(|new_min| new_min < old_min) => {
+                self.forward.push(format!(
+                    "minimum lowered from {old_min} to {}",
+                    bound(new_min)
+                ));
+            }
+            _ => {}
+        }
+
+        match (old.max(), new.max()) {
+            (old_max, Some(new_max)) if old_max.is_none_or(|old_max| new_max < old_max) => {
+                self.backward.push(format!(
+                    "maximum lowered from {} to {new_max}",
+                    bound(old_max)
+                ));
+            }
+            (Some(old_max), new_max) if new_max.is_none_or(|new_max| new_max > old_max) => {
+                self.forward.push(format!(
+                    "maximum raised from {old_max} to {}",
+                    bound(new_max)
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    /// Compare the entry constraints of two versions of a dictionary type.
+    fn check_constraints(&mut self, old: &[EntryConstraint], new: &[EntryConstraint]) {
+        for constraint in new {
+            if !old.contains(constraint) {
+                self.backward
+                    .push(format!("constraint `{constraint}` was added"));
+            }
+        }
+
+        for constraint in old {
+            if !new.contains(constraint) {
+                self.forward
+                    .push(format!("constraint `{constraint}` was removed"));
+            }
+        }
+    }
+
+    /// Compare the conditional requirements of two versions of a dictionary type.
+    fn check_requirements(&mut self, old: &[EntryRequirement], new: &[EntryRequirement]) {
+        for requirement in new {
+            if !old.contains(requirement) {
+                self.backward
+                    .push(format!("requirement `{requirement}` was added"));
+            }
+        }
+
+        for requirement in old {
+            if !new.contains(requirement) {
+                self.forward
+                    .push(format!("requirement `{requirement}` was removed"));
+            }
+        }
+    }
+
+    /// Compare the invariants of two versions of a dictionary type.
+    fn check_invariants(&mut self, old: &[Expression], new: &[Expression]) {
+        for invariant in new {
+            if !old.contains(invariant) {
+                self.backward
+                    .push(format!("invariant `{invariant}` was added"));
+            }
+        }
+
+        for invariant in old {
+            if !new.contains(invariant) {
+                self.forward
+                    .push(format!("invariant `{invariant}` was removed"));
+            }
+        }
+    }
+
+    /// Compare the values and aliases of two versions of an enum type.
+    fn check_enum<EnumName: Ord + Display>(
+        &mut self,
+        old: &EnumTypeAttributes<EnumName>,
+        new: &EnumTypeAttributes<EnumName>,
+    ) {
+        for value in old.values() {
+            if new.resolve(value).is_none() {
+                self.backward.push(format!("value `{value}` was removed"));
+            }
+        }
+
+        for (alias, value) in old.aliases() {
+            match new.resolve(alias) {
+                None => self.backward.push(format!("alias `{alias}` was removed")),
+                Some(new_value) if new_value != value => self.breaking.push(format!(
+                    "alias `{alias}` changed from `{value}` to `{new_value}`"
+                )),
+                Some(_) => {}
+            }
+        }
+
+        for value in new.values() {
+            if old.resolve(value).is_none() {
+                self.forward.push(format!("value `{value}` was added"));
+            }
+        }
+
+        for (alias, _) in new.aliases() {
+            if old.resolve(alias).is_none() {
+                self.forward.push(format!("alias `{alias}` was added"));
+            }
+        }
+    }
+
+    /// Get the compatibility the report amounts to.
+    fn into_compatibility(self) -> Compatibility {
+        let Self {
+            mut breaking,
+            mut backward,
+            mut forward,
+        } = self;
+
+        match (breaking.is_empty(), backward.is_empty(), forward.is_empty()) {
+            (true, true, true) => Compatibility::Full,
+            (true, true, false) => Compatibility::Backward(forward),
+            (true, false, true) => Compatibility::Forward(backward),
+            _ => {
+                breaking.append(&mut backward);
+                breaking.append(&mut forward);
+
+                Compatibility::Breaking(breaking)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        RegistrationError,
+        test_support::{FieldName, TypeAttributes, TypeDefinition, registry, td},
+        type_attributes::{ArrayTypeAttributes, EnumTypeAttributes, NumberTypeAttributes},
+    };
+
+    use super::{Compatibility, EvolutionPolicy};
+
+    fn int32(min: Option<i32>, max: Option<i32>) -> TypeDefinition {
+        let mut builder = NumberTypeAttributes::builder();
+
+        if let Some(min) = min {
+            builder = builder.min(min);
+        }
+
+        if let Some(max) = max {
+            builder = builder.max(max);
+        }
+
+        td(1, "MyType", TypeAttributes::Int32(builder.build().unwrap()))
+    }
+
+    fn color(values: &[FieldName], aliases: &[(FieldName, FieldName)]) -> TypeDefinition {
+        let mut builder = EnumTypeAttributes::builder();
+
+        for value in values {
+            builder = builder.with_value(*value);
+        }
+
+        for (alias, value) in aliases {
+            builder = builder.with_alias(*alias, *value);
+        }
+
+        td(1, "MyType", TypeAttributes::Enum(builder.build().unwrap()))
+    }
+
+    #[test]
+    fn test_compatibility_numbers() {
+        let compatibility = TypeDefinition::compatibility;
+
+        assert_eq!(
+            compatibility(&int32(Some(0), Some(10)), &int32(Some(0), Some(10))),
+            Compatibility::Full
+        );
+        assert_eq!(
+            compatibility(&int32(Some(0), Some(10)), &int32(None, Some(20))),
+            Compatibility::Backward(vec![
+                "minimum lowered from 0 to unbounded".to_owned(),
+                "maximum raised from 10 to 20".to_owned(),
+            ])
+        );
+        assert_eq!(
+            compatibility(&int32(None, Some(10)), &int32(Some(0), Some(10))),
+            Compatibility::Forward(vec!["minimum raised from unbounded to 0".to_owned()])
+        );
+        assert_eq!(
+            compatibility(&int32(Some(0), Some(10)), &int32(Some(5), Some(20))),
+            Compatibility::Breaking(vec![
+                "minimum raised from 0 to 5".to_owned(),
+                "maximum raised from 10 to 20".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_compatibility_enums() {
+        let compatibility = TypeDefinition::compatibility(
+            &color(&["red", "green"], &[("rouge", "red")]),
+            &color(&["red", "blue"], &[("rouge", "red"), ("bleu", "blue")]),
+        );
+        assert!(!compatibility.is_backward());
+        assert!(!compatibility.is_forward());
+        assert_eq!(
+            compatibility.reasons(),
+            [
+                "value `green` was removed",
+                "value `blue` was added",
+                "alias `bleu` was added",
+            ]
+        );
+
+        assert!(
+            TypeDefinition::compatibility(
+                &color(&["red"], &[]),
+                &color(&["red", "green"], &[("rouge", "red")]),
+            )
+            .is_backward()
+        );
+    }
+
+    #[test]
+    fn test_compatibility_kinds() {
+        assert_eq!(
+            TypeDefinition::compatibility(
+                &td(
+                    1,
+                    "MyType",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(2))
+                ),
+                &td(1, "MyType", TypeAttributes::String(Default::default())),
+            ),
+            Compatibility::Breaking(vec!["cannot change array into string".to_owned()])
+        );
+        assert_eq!(
+            TypeDefinition::compatibility(
+                &td(
+                    1,
+                    "MyType",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(2))
+                ),
+                &td(
+                    1,
+                    "MyType",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(3))
+                ),
+            )
+            .reasons(),
+            ["items type changed from `2` to `3`"]
+        );
+    }
+
+    #[test]
+    fn test_replace_compatible() {
+        let mut registry = registry([int32(Some(0), Some(10))]);
+
+        registry.replace_compatible(int32(None, Some(20))).unwrap();
+
+        let (_, err) = registry
+            .replace_compatible(int32(Some(5), None))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            RegistrationError::IncompatibleReplacement(
+                "minimum raised from unbounded to 5".to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn test_evolution_policy() {
+        let mut registry = registry([color(&["red", "green"], &[("rouge", "red")])]);
+        registry.set_evolution_policy(EvolutionPolicy::BackwardCompatible);
+
+        registry
+            .replace(color(&["red", "green", "blue"], &[("rouge", "red")]))
+            .unwrap();
+        registry.rename(&1, "MyColor").unwrap();
+
+        let (_, err) = registry
+            .replace(TypeDefinition {
+                name: "MyColor",
+                ..color(&["red", "blue"], &[("rouge", "blue")])
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "type definition breaks the schema evolution rules: alias `rouge` changed from `red` to `blue`; value `green` was removed"
+        );
+
+        let (_, err) = registry
+            .replace(TypeDefinition {
+                name: "MyColor",
+                ..int32(None, None)
+            })
+            .unwrap_err();
+        assert_eq!(
+            err,
+            RegistrationError::EvolutionRuleViolation {
+                violations: vec!["cannot change enum into int32".to_owned()]
+            }
+        );
+
+        registry.set_evolution_policy(EvolutionPolicy::Unrestricted);
+        registry
+            .replace(TypeDefinition {
+                name: "MyColor",
+                ..int32(None, None)
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,429 @@
+//! Incremental synchronization of registries.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    hash::Hash,
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Fingerprint, RegistrationError, RegistryEvent, TypeDefinition, TypeDefinitionInstance,
+    TypeDefinitionRegistry,
+};
+
+/// The changes of a registry since one of its previous states.
+///
+/// Deltas are computed by [`delta_since`](TypeDefinitionRegistry::delta_since) and applied by
+/// [`apply_delta`](TypeDefinitionRegistry::apply_delta). They serialize, so that a server can
+/// send them to its clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RegistryDelta<Id, FieldName: Ord + Display + Clone> {
+    /// The type definitions that were added, in topological order.
+    pub(crate) added: Vec<TypeDefinition<Id, FieldName>>,
+
+    /// The type definitions that were changed, in topological order.
+    pub(crate) changed: Vec<TypeDefinition<Id, FieldName>>,
+
+    /// The identifiers of the type definitions that were removed, sorted.
+    pub(crate) removed: Vec<Id>,
+}
+
+impl<Id, FieldName: Ord + Display + Clone> RegistryDelta<Id, FieldName> {
+    /// Get the type definitions that were added, in topological order.
+    pub fn added(&self) -> &[TypeDefinition<Id, FieldName>] {
+        &self.added
+    }
+
+    /// Get the type definitions that were changed, in topological order.
+    pub fn changed(&self) -> &[TypeDefinition<Id, FieldName>] {
+        &self.changed
+    }
+
+    /// Get the identifiers of the type definitions that were removed, sorted.
+    pub fn removed(&self) -> &[Id] {
+        &self.removed
+    }
+
+    /// Check whether the delta contains no changes.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A change of a delta that could not be applied to a registry.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("cannot apply the delta to type definition `{}`: {error}", .type_definition.name)]
+pub struct DeltaError<Id, FieldName: Ord + Display + Clone> {
+    /// The type definition whose change failed.
+    pub(crate) type_definition: TypeDefinition<Id, FieldName>,
+
+    /// The reason why the change failed.
+    pub(crate) error: RegistrationError<Id, FieldName>,
+}
+
+impl<Id, FieldName: Ord + Display + Clone> DeltaError<Id, FieldName> {
+    /// Get the type definition whose change failed.
+    pub fn type_definition(&self) -> &TypeDefinition<Id, FieldName> {
+        &self.type_definition
+    }
+
+    /// Get the reason why the change failed.
+    pub fn error(&self) -> &RegistrationError<Id, FieldName> {
+        &self.error
+    }
+}
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display + Serialize,
+{
+    /// Compute the changes of the registry since the state with the specified
+    /// [fingerprint](Self::fingerprint).
+    ///
+    /// The registry walks back its [history](Self::clear_history) until it finds the most recent
+    /// state with that fingerprint. Type definitions that were changed and then changed back are
+    /// not part of the delta.
+    ///
+    /// Returns `None` if the registry never was in that state, or forgot about it: a full
+    /// synchronization is then needed. The registry forgets the states before the changes that
+    /// no longer fit in its [history](Self::set_history_capacity).
+    pub fn delta_since(&self, fingerprint: Fingerprint) -> Option<RegistryDelta<Id, FieldName>> {
+        // The fingerprints of the types by their names, and the type instances that differ from
+        // the current ones, in the state being reconstructed.
+        let mut fingerprints: BTreeMap<&FieldName, Fingerprint> = self
+            .by_name
+            .iter()
+            .map(|(name, instance)| (name, instance.fingerprint()))
+            .collect();
+        let mut past: BTreeMap<&Id, Option<&Arc<TypeDefinitionInstance<Id, FieldName>>>> =
+            BTreeMap::new();
+        let mut entries = self.history.iter().rev();
+        let mut state =
+            Fingerprint::of_sorted_types(fingerprints.iter().map(|(name, fp)| (*name, *fp)));
+
+        while state != fingerprint {
+            let entry = entries.next()?;
+            let change = &entry.change;
+
+            // Names can move from a type to another within a single change: all the names must be
+            // removed before the previous ones are restored.
+            for id in change.keys() {
+                let current = past.get(id).copied().unwrap_or_else(|| self.by_id.get(id));
+
+                if let Some(current) = current {
+                    fingerprints.remove(&current.name);
+                }
+            }
+
+            for (id, previous) in change {
+                if let Some(previous) = previous {
+                    fingerprints.insert(&previous.name, previous.fingerprint());
+                }
+
+                past.insert(id, previous.as_ref());
+            }
+
+            // Deltas are computed from the same states again and again: the fingerprint of each
+            // past state is only computed once.
+            state = *entry.fingerprint.get_or_init(|| {
+                Fingerprint::of_sorted_types(fingerprints.iter().map(|(name, fp)| (*name, *fp)))
+            });
+        }
+
+        let mut added = BTreeSet::new();
+        let mut changed = BTreeSet::new();
+        let mut removed = Vec::new();
+
+        for (id, previous) in past {
+            match (previous, self.by_id.get(id)) {
+                (None, Some(_)) => {
+                    added.insert(id);
+                }
+                (Some(_), None) => removed.push(id.clone()),
+                (Some(previous), Some(current)) => {
+                    if previous.to_definition() != current.to_definition() {
+                        changed.insert(id);
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+
+        let mut delta = RegistryDelta {
+            added: Vec::with_capacity(added.len()),
+            changed: Vec::with_capacity(changed.len()),
+            removed,
+        };
+
+        if !(added.is_empty() && changed.is_empty()) {
+            for td in self.to_definitions() {
+                if added.contains(&td.id) {
+                    delta.added.push(td);
+                } else if changed.contains(&td.id) {
+                    delta.changed.push(td);
+                }
+            }
+        }
+
+        Some(delta)
+    }
+
+    /// Apply the changes of a delta, computed by another registry, to the registry.
+    ///
+    /// The added type definitions are registered, the changed ones [replaced](Self::replace), and
+    /// the removed ones removed, which fails if other type definitions still reference them.
+    /// Unknown removed identifiers are ignored.
+    ///
+    /// Deltas are applied atomically: if any change fails, the registry is left untouched and the
+    /// failing type definition is returned, with the reason why it failed.
+    pub fn apply_delta(
+        &mut self,
+        delta: RegistryDelta<Id, FieldName>,
+    ) -> Result<(), DeltaError<Id, FieldName>> {
+        // The delta is applied to a copy of the registry, whose events are forwarded once all
+        // the changes succeeded. The history is not copied: the changes of the copy are recorded
+        // in it afterwards.
+        let history = std::mem::take(&mut self.history);
+        let mut next = self.clone();
+        self.history = history;
+
+        let events = next.subscribe();
+
+        if let Some((type_definition, error)) = next.register(delta.added).failed.into_iter().next()
+        {
+            return Err(DeltaError {
+                type_definition,
+                error,
+            });
+        }
+
+        for td in delta.changed {
+            next.replace(td)
+                .map_err(|(type_definition, error)| DeltaError {
+                    type_definition,
+                    error,
+                })?;
+        }
+
+        let removed: BTreeSet<_> = delta
+            .removed
+            .into_iter()
+            .filter(|id| next.by_id.contains_key(id))
+            .collect();
+
+        for instance in next.by_id.values() {
+            if removed.contains(&instance.id) {
+                continue;
+            }
+
+            if let Some(ref_) = instance
+                .attributes
+                .references()
+                .into_iter()
+                .find(|ref_| removed.contains(&ref_.id))
+            {
+                return Err(DeltaError {
+                    type_definition: ref_.to_definition(),
+                    error: RegistrationError::StillReferenced {
+                        referencing_id: instance.id.clone(),
+                    },
+                });
+            }
+        }
+
+        let mut change = BTreeMap::new();
+
+        for id in removed {
+            if let Some(instance) = next.by_id.remove(&id) {
+                next.by_name.remove(&instance.name);
+                next.unindex_references(&instance);
+                next.subscribers
+                    .notify(|| [RegistryEvent::Removed(Arc::clone(&instance))]);
+                change.insert(id, Some(instance));
+            }
+        }
+
+        next.record_change(change);
+
+        let changes = std::mem::replace(&mut next.history, std::mem::take(&mut self.history));
+        next.subscribers = std::mem::take(&mut self.subscribers);
+        *self = next;
+
+        for entry in changes {
+            self.record_change(entry.change);
+        }
+
+        self.subscribers
+            .notify(|| events.try_iter().collect::<Vec<_>>());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        RegistrationError, RegistryEvent,
+        test_support::{TypeAttributes, TypeDefinitionRegistry, registry, td},
+        type_attributes::ArrayTypeAttributes,
+    };
+
+    fn base_registry() -> TypeDefinitionRegistry {
+        registry([
+            td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+            td(2, "MyString", TypeAttributes::String(Default::default())),
+        ])
+    }
+
+    /// Change, add and remove a type definition.
+    fn change(server: &mut TypeDefinitionRegistry) {
+        server
+            .replace(td(1, "MyNumber", TypeAttributes::Int64(Default::default())))
+            .unwrap();
+        server
+            .register([td(
+                3,
+                "MyIntArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            )])
+            .into_result()
+            .unwrap();
+        server.prune(&[3]);
+    }
+
+    #[test]
+    fn test_delta() {
+        let mut server = base_registry();
+        let fingerprint = server.fingerprint();
+
+        assert!(server.delta_since(fingerprint).unwrap().is_empty());
+
+        change(&mut server);
+
+        let delta = server.delta_since(fingerprint).unwrap();
+        assert_eq!(
+            delta.added(),
+            [td(
+                3,
+                "MyIntArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1))
+            )]
+        );
+        assert_eq!(
+            delta.changed(),
+            [td(1, "MyNumber", TypeAttributes::Int64(Default::default()))]
+        );
+        assert_eq!(delta.removed(), [2]);
+    }
+
+    #[test]
+    fn test_delta_reverted_change() {
+        let mut server = base_registry();
+        let fingerprint = server.fingerprint();
+
+        change(&mut server);
+
+        // Changing a type definition back cancels its change.
+        server
+            .replace(td(1, "MyInt", TypeAttributes::Int32(Default::default())))
+            .unwrap();
+        let delta = server.delta_since(fingerprint).unwrap();
+        assert!(delta.changed().is_empty());
+        assert_eq!(delta.removed(), [2]);
+    }
+
+    #[test]
+    fn test_delta_cleared_history() {
+        let mut server = base_registry();
+        let fingerprint = server.fingerprint();
+
+        change(&mut server);
+        server.clear_history();
+
+        assert!(server.delta_since(fingerprint).is_none());
+    }
+
+    #[test]
+    fn test_delta_history_capacity() {
+        let mut server = base_registry();
+        server.set_history_capacity(1);
+        let fingerprint = server.fingerprint();
+
+        server
+            .replace(td(1, "MyNumber", TypeAttributes::Int64(Default::default())))
+            .unwrap();
+        let intermediate = server.fingerprint();
+        server
+            .replace(td(2, "MyText", TypeAttributes::String(Default::default())))
+            .unwrap();
+
+        // Only the state before the last change is remembered.
+        assert!(server.delta_since(fingerprint).is_none());
+        assert_eq!(
+            server.delta_since(intermediate).unwrap().changed(),
+            [td(2, "MyText", TypeAttributes::String(Default::default()))]
+        );
+
+        server.set_history_capacity(0);
+        assert!(server.delta_since(intermediate).is_none());
+        assert!(server.delta_since(server.fingerprint()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_delta() {
+        let mut server = base_registry();
+        let mut client = server.clone();
+        let events = client.subscribe();
+        let fingerprint = client.fingerprint();
+
+        change(&mut server);
+
+        client
+            .apply_delta(server.delta_since(client.fingerprint()).unwrap())
+            .unwrap();
+        assert_eq!(client.fingerprint(), server.fingerprint());
+        assert_eq!(client.to_definitions(), server.to_definitions());
+        assert_eq!(
+            events
+                .try_iter()
+                .map(|event| match event {
+                    RegistryEvent::Registered(inst) => format!("+{}", inst.id),
+                    RegistryEvent::Replaced { current, .. } => format!("~{}", current.id),
+                    RegistryEvent::Removed(inst) => format!("-{}", inst.id),
+                    RegistryEvent::Failed(td, _) => format!("!{}", td.id),
+                })
+                .collect::<Vec<_>>(),
+            vec!["+3", "~1", "~3", "-2"]
+        );
+
+        // The changes of the delta are recorded in the history of the client.
+        assert_eq!(
+            client.delta_since(fingerprint),
+            server.delta_since(fingerprint)
+        );
+    }
+
+    #[test]
+    fn test_apply_delta_still_referenced() {
+        let mut server = base_registry();
+        change(&mut server);
+
+        let mut client = server.clone();
+
+        // Removing a referenced type definition fails, and leaves the registry untouched.
+        let mut delta = server.delta_since(server.fingerprint()).unwrap();
+        delta.removed.push(1);
+        let err = client.apply_delta(delta).unwrap_err();
+        assert_eq!(err.type_definition().id, 1);
+        assert_eq!(
+            err.error(),
+            &RegistrationError::StillReferenced { referencing_id: 3 }
+        );
+        assert_eq!(client.to_definitions().len(), 2);
+    }
+}
@@ -0,0 +1,167 @@
+//! Reporting of non-fatal findings.
+
+use crate::{
+    ParseWarning, type_attributes::NumberTypeAttributes,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+/// A receiver of the non-fatal findings emitted while parsing values or registering type
+/// definitions.
+///
+/// Implement this trait to log the findings as they are emitted. Use [`Diagnostics`] to collect
+/// them instead, or `()` to ignore them.
+pub trait DiagnosticsSink<Id, FieldName> {
+    /// Report a warning emitted while parsing a value.
+    fn parse_warning(&mut self, warning: ParseWarning);
+
+    /// Report a warning emitted while registering a type definition.
+    fn registration_warning(&mut self, warning: RegistrationWarning<Id, FieldName>);
+}
+
+impl<Id, FieldName> DiagnosticsSink<Id, FieldName> for () {
+    fn parse_warning(&mut self, _warning: ParseWarning) {}
+
+    fn registration_warning(&mut self, _warning: RegistrationWarning<Id, FieldName>) {}
+}
+
+/// A diagnostics sink that collects all the findings.
+#[derive(Debug)]
+pub struct Diagnostics<Id, FieldName> {
+    /// The warnings emitted while parsing values.
+    pub(crate) parse_warnings: Vec<ParseWarning>,
+
+    /// The warnings emitted while registering type definitions.
+    pub(crate) registration_warnings: Vec<RegistrationWarning<Id, FieldName>>,
+}
+
+impl<Id, FieldName> Default for Diagnostics<Id, FieldName> {
+    fn default() -> Self {
+        Self {
+            parse_warnings: Vec::new(),
+            registration_warnings: Vec::new(),
+        }
+    }
+}
+
+impl<Id, FieldName> Diagnostics<Id, FieldName> {
+    /// Check whether no findings were collected.
+    pub fn is_empty(&self) -> bool {
+        self.parse_warnings.is_empty() && self.registration_warnings.is_empty()
+    }
+
+    /// Get the warnings emitted while parsing values.
+    pub fn parse_warnings(&self) -> &[ParseWarning] {
+        &self.parse_warnings
+    }
+
+    /// Get the warnings emitted while registering type definitions.
+    pub fn registration_warnings(&self) -> &[RegistrationWarning<Id, FieldName>] {
+        &self.registration_warnings
+    }
+}
+
+impl<Id, FieldName> DiagnosticsSink<Id, FieldName> for Diagnostics<Id, FieldName> {
+    fn parse_warning(&mut self, warning: ParseWarning) {
+        self.parse_warnings.push(warning);
+    }
+
+    fn registration_warning(&mut self, warning: RegistrationWarning<Id, FieldName>) {
+        self.registration_warnings.push(warning);
+    }
+}
+
+/// A non-fatal finding emitted while registering a type definition.
+///
+/// The type definition is registered regardless.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("type definition `{name}` ({id}): {kind}")]
+pub struct RegistrationWarning<Id, FieldName> {
+    /// The identifier of the type definition.
+    pub(crate) id: Id,
+
+    /// The name of the type definition.
+    pub(crate) name: FieldName,
+
+    /// The kind of warning.
+    pub(crate) kind: RegistrationWarningKind,
+}
+
+impl<Id, FieldName> RegistrationWarning<Id, FieldName> {
+    /// Get the identifier of the type definition.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Get the name of the type definition.
+    pub fn name(&self) -> &FieldName {
+        &self.name
+    }
+
+    /// Get the kind of warning.
+    pub fn kind(&self) -> &RegistrationWarningKind {
+        &self.kind
+    }
+}
+
+/// The kind of non-fatal finding emitted while registering a type definition.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum RegistrationWarningKind {
+    /// The range of a number type only allows a single value.
+    #[error("the range only allows a single value")]
+    SingleValueRange,
+
+    /// The default value of a floating-point number type is not finite.
+    #[error("the default value is not finite")]
+    NonFiniteDefault,
+
+    /// An enum type has no values, so no value can ever be valid.
+    #[error("the enum has no values")]
+    EmptyEnum,
+}
+
+/// Inspect instantiated type attributes for suspicious definitions.
+pub(crate) fn inspect_attributes<Id, FieldName: Ord>(
+    attributes: &TypeAttributesInstance<Id, FieldName>,
+) -> Vec<RegistrationWarningKind> {
+    let mut warnings = Vec::new();
+
+    match attributes {
+        TypeAttributesInstance::Int32(a) => inspect_range(a, &mut warnings),
+        TypeAttributesInstance::Int64(a) => inspect_range(a, &mut warnings),
+        TypeAttributesInstance::Uint32(a) => inspect_range(a, &mut warnings),
+        TypeAttributesInstance::Uint64(a) => inspect_range(a, &mut warnings),
+        TypeAttributesInstance::Float32(a) => {
+            inspect_range(a, &mut warnings);
+
+            if a.default_value().is_some_and(|v| !v.is_finite()) {
+                warnings.push(RegistrationWarningKind::NonFiniteDefault);
+            }
+        }
+        TypeAttributesInstance::Float64(a) => {
+            inspect_range(a, &mut warnings);
+
+            if a.default_value().is_some_and(|v| !v.is_finite()) {
+                warnings.push(RegistrationWarningKind::NonFiniteDefault);
+            }
+        }
+        TypeAttributesInstance::Enum(a) if a.values().next().is_none() => {
+            warnings.push(RegistrationWarningKind::EmptyEnum);
+        }
+        _ => {}
+    }
+
+    warnings
+}
+
+/// Inspect the range of a number type.
+fn inspect_range<Num: PartialOrd + Copy>(
+    attributes: &NumberTypeAttributes<Num>,
+    warnings: &mut Vec<RegistrationWarningKind>,
+) {
+    if let (Some(min), Some(max)) = (attributes.min(), attributes.max())
+        && min == max
+    {
+        warnings.push(RegistrationWarningKind::SingleValueRange);
+    }
+}
@@ -0,0 +1,329 @@
+//! Loading of type definitions from directory trees.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Display,
+    hash::Hash,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+
+use crate::{RegistrationError, RegistrationOutcome, TypeDefinition, TypeDefinitionRegistry};
+
+/// An error that occurred while loading the type definitions of a file.
+#[derive(Debug, thiserror::Error)]
+#[error("{}: {kind}", path.display())]
+pub struct LoadError<Id: Display, FieldName: Display> {
+    /// The path of the file.
+    pub(crate) path: PathBuf,
+
+    /// The error.
+    pub(crate) kind: LoadErrorKind<Id, FieldName>,
+}
+
+impl<Id: Display, FieldName: Display> LoadError<Id, FieldName> {
+    /// Get the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the kind of the error.
+    pub fn kind(&self) -> &LoadErrorKind<Id, FieldName> {
+        &self.kind
+    }
+}
+
+/// The kind of an error that occurred while loading the type definitions of a file.
+#[derive(Debug, thiserror::Error)]
+pub enum LoadErrorKind<Id: Display, FieldName: Display> {
+    /// The file or directory could not be read.
+    #[error("cannot read: {0}")]
+    Io(#[from] io::Error),
+
+    /// The files could not be watched.
+    #[cfg(feature = "notify")]
+    #[error("cannot watch: {0}")]
+    Watch(#[from] notify::Error),
+
+    /// The file does not contain valid type definitions.
+    #[error("invalid type definitions: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    /// A type definition of the file could not be registered.
+    #[error("type definition `{name}` ({id}): {err}")]
+    Registration {
+        /// The identifier of the type definition.
+        id: Id,
+
+        /// The name of the type definition.
+        name: FieldName,
+
+        /// The reason why the type definition could not be registered.
+        err: RegistrationError<Id, FieldName>,
+    },
+}
+
+/// The result of loading the type definitions of a directory tree.
+#[derive(Debug)]
+pub struct DirectoryLoad<Id: Display, FieldName: Ord + Display + Clone> {
+    /// The outcome of the registration of the type definitions that could be read.
+    pub(crate) outcome: RegistrationOutcome<Id, FieldName>,
+
+    /// The errors, with the paths of the files they occurred in.
+    pub(crate) errors: Vec<LoadError<Id, FieldName>>,
+}
+
+impl<Id: Display, FieldName: Ord + Display + Clone> DirectoryLoad<Id, FieldName> {
+    /// Get the outcome of the registration of the type definitions that could be read.
+    pub fn outcome(&self) -> &RegistrationOutcome<Id, FieldName> {
+        &self.outcome
+    }
+
+    /// Get the errors, with the paths of the files they occurred in, sorted by path.
+    ///
+    /// This includes the type definitions that could not be registered.
+    pub fn errors(&self) -> &[LoadError<Id, FieldName>] {
+        &self.errors
+    }
+
+    /// Check whether all the files were loaded and all their type definitions registered.
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display + DeserializeOwned,
+    FieldName: Ord + Hash + Clone + Display + DeserializeOwned,
+{
+    /// Register the type definitions of all the `*.json` files of a directory tree, including the
+    /// `*.gameson.json` ones, as a single batch.
+    ///
+    /// Each file contains either a type definition or an array of type definitions. Files that
+    /// cannot be read or parsed are reported with their paths and skipped, as are the type
+    /// definitions that cannot be registered. Symbolic links to files are followed, but not
+    /// symbolic links to directories, which could make the walk loop.
+    ///
+    /// Only failing to read the root directory itself is an error.
+    pub fn load_from_dir(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> io::Result<DirectoryLoad<Id, FieldName>> {
+        let mut files = Vec::new();
+        let mut errors = Vec::new();
+
+        collect_json_files(path.as_ref(), &mut files, &mut errors)?;
+        files.sort();
+
+        // The paths of the type definitions, in batch order, by their identifiers and names.
+        let mut sources = BTreeMap::<_, VecDeque<_>>::new();
+        let mut type_definitions = Vec::new();
+
+        for path in files {
+            match read_type_definitions::<Id, FieldName>(&path) {
+                Ok(tds) => {
+                    for td in tds {
+                        sources
+                            .entry((td.id.clone(), td.name.clone()))
+                            .or_default()
+                            .push_back(path.clone());
+                        type_definitions.push(td);
+                    }
+                }
+                Err(kind) => errors.push(LoadError { path, kind }),
+            }
+        }
+
+        let outcome = self.register(type_definitions);
+
+        // Definitions with the same identifier and name are registered in batch order: the
+        // registered one comes first, and the failed ones after it.
+        let mut take_source = |id: &Id, name: &FieldName| {
+            sources
+                .get_mut(&(id.clone(), name.clone()))
+                .and_then(VecDeque::pop_front)
+        };
+
+        for instance in outcome.registered() {
+            take_source(&instance.id, &instance.name);
+        }
+
+        for (td, err) in outcome.failed() {
+            errors.push(LoadError {
+                path: take_source(&td.id, &td.name).unwrap_or_default(),
+                kind: LoadErrorKind::Registration {
+                    id: td.id.clone(),
+                    name: td.name.clone(),
+                    err: err.clone(),
+                },
+            });
+        }
+
+        errors.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(DirectoryLoad { outcome, errors })
+    }
+}
+
+/// Collect the paths of all the JSON files of a directory tree.
+///
+/// Subdirectories that cannot be read are reported as errors.
+fn collect_json_files<Id: Display, FieldName: Display>(
+    root: &Path,
+    files: &mut Vec<PathBuf>,
+    errors: &mut Vec<LoadError<Id, FieldName>>,
+) -> io::Result<()> {
+    // The subdirectories are walked from a stack, as deep trees could overflow the call stack.
+    let mut dirs = Vec::new();
+    collect_dir_entries(root, files, &mut dirs)?;
+
+    while let Some(dir) = dirs.pop() {
+        if let Err(err) = collect_dir_entries(&dir, files, &mut dirs) {
+            errors.push(LoadError {
+                path: dir,
+                kind: LoadErrorKind::Io(err),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Collect the paths of the JSON files and of the subdirectories of a directory.
+///
+/// Symbolic links are not followed to tell directories apart: links to directories are skipped.
+fn collect_dir_entries(
+    dir: &Path,
+    files: &mut Vec<PathBuf>,
+    dirs: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if entry.file_type()?.is_dir() {
+            dirs.push(path);
+        } else if is_json_file(&path) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check whether a path designates a JSON file, by its extension.
+pub(crate) fn is_json_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+/// Read the type definitions of a file.
+pub(crate) fn read_type_definitions<Id, FieldName>(
+    path: &Path,
+) -> Result<Vec<TypeDefinition<Id, FieldName>>, LoadErrorKind<Id, FieldName>>
+where
+    Id: Display + DeserializeOwned,
+    FieldName: Ord + Display + Clone + DeserializeOwned,
+{
+    let content = std::fs::read(path)?;
+
+    Ok(match serde_json::from_slice(&content)? {
+        serde_json::Value::Array(tds) => tds
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<_, _>>()?,
+        td => vec![serde_json::from_value(td)?],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::owned::TypeDefinitionRegistry;
+    use std::path::Path;
+
+    use serde_json::json;
+
+    #[test]
+    fn test_load_from_dir() {
+        let root =
+            std::env::temp_dir().join(format!("gameson-load-from-dir-{}", std::process::id()));
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+
+        write(
+            "numbers.json",
+            &json!([
+                { "id": 1, "name": "MyInt", "type": "int32", "attributes": {} },
+                { "id": 2, "name": "MyIntArray", "type": "array", "attributes": { "items_type_id": 1 } },
+            ])
+            .to_string(),
+        );
+        write(
+            "strings/string.gameson.json",
+            &json!({ "id": 3, "name": "MyString", "type": "string", "attributes": {} }).to_string(),
+        );
+        write(
+            "strings/broken.json",
+            &json!({ "id": 4, "name": "MyBroken", "type": "array", "attributes": { "items_type_id": 5 } })
+                .to_string(),
+        );
+        write("strings/invalid.json", "{");
+        write("strings/notes.txt", "not a type definition");
+
+        // Links to directories are not followed, and cannot make the walk loop.
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&root, root.join("strings/loop")).unwrap();
+
+        let mut registry = TypeDefinitionRegistry::default();
+        let load = registry.load_from_dir(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+        let load = load.unwrap();
+
+        assert!(!load.is_success());
+        assert_eq!(
+            load.outcome()
+                .registered()
+                .iter()
+                .map(|td| td.id)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        let errors = load
+            .errors()
+            .iter()
+            .map(|err| {
+                (
+                    err.path().strip_prefix(&root).unwrap().to_owned(),
+                    err.kind().to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    Path::new("strings/broken.json").to_owned(),
+                    "type definition `MyBroken` (4): type definition has a broken reference to type definition `5`"
+                        .to_owned()
+                ),
+                (
+                    Path::new("strings/invalid.json").to_owned(),
+                    "invalid type definitions: EOF while parsing an object at line 1 column 1"
+                        .to_owned()
+                ),
+            ]
+        );
+
+        assert!(
+            TypeDefinitionRegistry::default()
+                .load_from_dir(&root)
+                .is_err()
+        );
+    }
+}
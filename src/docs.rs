@@ -0,0 +1,390 @@
+//! Generation of documentation from registries.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Display, Write},
+    hash::Hash,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    DictionaryWireForm, TypeDefinitionInstance, TypeDefinitionRegistry, UnicodeNormalization,
+    WhitespacePolicy, type_attributes::NumberTypeAttributes,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+/// Render the documentation of the registered types, as Markdown pages.
+///
+/// Every type gets its own page, named after the type with an `.md` extension: it lists its
+/// description, its kind, its constraints and default value, its enum values with their
+/// descriptions and deprecation notes, and links to the pages of the types it references and of
+/// the types referencing it. An `index.md` page links to all the type pages.
+///
+/// The pages are returned by file name.
+pub fn render_markdown<Id, FieldName>(
+    registry: &TypeDefinitionRegistry<Id, FieldName>,
+) -> BTreeMap<String, String>
+where
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display,
+{
+    let mut instances: Vec<_> = registry.by_name.values().collect();
+    instances.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut referenced_by: BTreeMap<&Id, BTreeSet<&FieldName>> = BTreeMap::new();
+
+    for instance in &instances {
+        for ref_ in instance.attributes.references() {
+            referenced_by
+                .entry(&ref_.id)
+                .or_default()
+                .insert(&instance.name);
+        }
+    }
+
+    let mut pages = BTreeMap::new();
+    let mut index = String::from("# Types\n\n");
+
+    for instance in &instances {
+        let summary = instance
+            .description
+            .as_deref()
+            .and_then(|description| description.lines().next())
+            .map(|line| format!(": {line}"))
+            .unwrap_or_default();
+
+        writeln!(
+            index,
+            "- {} ({}){summary}",
+            link(&instance.name),
+            instance.attributes.kind()
+        )
+        .unwrap();
+
+        pages.insert(
+            page_name(&instance.name),
+            render_page(instance, referenced_by.get(&instance.id)),
+        );
+    }
+
+    pages.insert("index.md".to_owned(), index);
+
+    pages
+}
+
+/// Get the file name of the page of a type.
+fn page_name(name: impl Display) -> String {
+    format!("{name}.md")
+}
+
+/// Get a Markdown link to the page of a type.
+fn link(name: impl Display) -> String {
+    format!("[{name}]({})", page_name(&name))
+}
+
+/// Escape the pipes of a text, so that it fits in a table cell.
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Render the page of a type.
+fn render_page<Id: Display, FieldName: Ord + Display>(
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+    referenced_by: Option<&BTreeSet<&FieldName>>,
+) -> String {
+    let mut page = String::new();
+
+    writeln!(page, "# {}\n", instance.name).unwrap();
+
+    if let Some(description) = &instance.description {
+        writeln!(page, "{description}\n").unwrap();
+    }
+
+    writeln!(page, "- Kind: {}", instance.attributes.kind()).unwrap();
+    writeln!(page, "- Identifier: `{}`", instance.id).unwrap();
+
+    let constraints = constraints(&instance.attributes);
+
+    if !constraints.is_empty() {
+        page.push_str("\n## Constraints\n\n");
+
+        for constraint in constraints {
+            writeln!(page, "- {constraint}").unwrap();
+        }
+    }
+
+    if let TypeAttributesInstance::Enum(a) = &instance.attributes {
+        page.push_str("\n## Values\n\n");
+
+        if a.values().next().is_none() {
+            page.push_str("This enum has no values.\n");
+        } else {
+            page.push_str("| Value | Description |\n| --- | --- |\n");
+
+            for value in a.values() {
+                let deprecated = if a.is_deprecated(value) {
+                    "**Deprecated.** "
+                } else {
+                    ""
+                };
+                let description = a.value_description(value).unwrap_or_default();
+                let cell = format!("{deprecated}{}", table_cell(description));
+
+                writeln!(page, "| `{value}` | {} |", cell.trim_end()).unwrap();
+            }
+        }
+
+        let aliases: Vec<_> = a.aliases().collect();
+
+        if !aliases.is_empty() {
+            page.push_str("\n### Aliases\n\n");
+
+            for (alias, value) in aliases {
+                writeln!(page, "- `{alias}` stands for `{value}`").unwrap();
+            }
+        }
+    }
+
+    let references: BTreeSet<_> = instance
+        .attributes
+        .references()
+        .into_iter()
+        .map(|ref_| &ref_.name)
+        .collect();
+
+    if !references.is_empty() {
+        page.push_str("\n## References\n\n");
+
+        for name in references {
+            writeln!(page, "- {}", link(name)).unwrap();
+        }
+    }
+
+    if let Some(referenced_by) = referenced_by {
+        page.push_str("\n## Referenced by\n\n");
+
+        for name in referenced_by {
+            writeln!(page, "- {}", link(name)).unwrap();
+        }
+    }
+
+    page
+}
+
+/// Describe the constraints of a type, including its default value.
+fn constraints<Id, FieldName: Ord + Display>(
+    attributes: &TypeAttributesInstance<Id, FieldName>,
+) -> Vec<String> {
+    /// Describe the constraints of a number type.
+    fn number<Num: Display + Copy + PartialOrd>(a: &NumberTypeAttributes<Num>) -> Vec<String> {
+        [
+            a.min().map(|min| format!("Minimum: `{min}`")),
+            a.max().map(|max| format!("Maximum: `{max}`")),
+            a.default_value()
+                .map(|default| format!("Default: `{default}`")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    match attributes {
+        TypeAttributesInstance::Array(a) => {
+            vec![format!("Items: {}", link(&a.items_type_id().name))]
+        }
+        TypeAttributesInstance::Dictionary(a) => [
+            format!("Keys: {}", link(&a.keys_type_id().name)),
+            format!("Values: {}", link(&a.values_type_id().name)),
+            match a.wire_form() {
+                DictionaryWireForm::Object => "Encoded as a JSON object".to_owned(),
+                DictionaryWireForm::Pairs => {
+                    "Encoded as an array of `[key, value]` pairs".to_owned()
+                }
+            },
+        ]
+        .into_iter()
+        .chain(
+            a.constraints()
+                .iter()
+                .map(|constraint| format!("Entries satisfy `{constraint}`")),
+        )
+        .chain(a.requirements().iter().map(|requirement| {
+            format!(
+                "When `{}` is `{}`, requires {}",
+                requirement.when(),
+                requirement.equals(),
+                requirement
+                    .require()
+                    .iter()
+                    .map(|key| format!("`{key}`"))
+                    .join(", ")
+            )
+        }))
+        .chain(
+            a.invariants()
+                .iter()
+                .map(|invariant| format!("Entries satisfy `{invariant}`")),
+        )
+        .collect(),
+        TypeAttributesInstance::Boolean(a) => a
+            .default_value()
+            .map(|default| format!("Default: `{default}`"))
+            .into_iter()
+            .collect(),
+        TypeAttributesInstance::Int32(a) => number(a),
+        TypeAttributesInstance::Int64(a) => number(a),
+        TypeAttributesInstance::Uint32(a) => number(a),
+        TypeAttributesInstance::Uint64(a) => number(a),
+        TypeAttributesInstance::Float32(a) => number(a),
+        TypeAttributesInstance::Float64(a) => number(a),
+        TypeAttributesInstance::String(a) => [
+            match a.normalization() {
+                UnicodeNormalization::None => None,
+                UnicodeNormalization::Nfc => Some("Normalized to NFC".to_owned()),
+                UnicodeNormalization::Nfkc => Some("Normalized to NFKC".to_owned()),
+            },
+            match a.whitespace() {
+                WhitespacePolicy::Preserve => None,
+                WhitespacePolicy::Trim => {
+                    Some("Leading and trailing whitespace is trimmed".to_owned())
+                }
+                WhitespacePolicy::Collapse => Some("Whitespace is collapsed".to_owned()),
+            },
+            a.default_value()
+                .map(|default| format!("Default: `{default:?}`")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        TypeAttributesInstance::Enum(a) => a
+            .default_value()
+            .map(|default| format!("Default: `{default}`"))
+            .into_iter()
+            .collect(),
+        #[cfg(feature = "uuid")]
+        TypeAttributesInstance::Uuid(a) => a
+            .default_value()
+            .map(|default| format!("Default: `{default}`"))
+            .into_iter()
+            .collect(),
+        #[cfg(feature = "bytes")]
+        TypeAttributesInstance::Bytes(a) => vec![format!("Encoding: {}", a.encoding())],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        test_support::{TypeAttributes, TypeDefinition, registry, td},
+        type_attributes::{ArrayTypeAttributes, EnumTypeAttributes, NumberTypeAttributes},
+    };
+
+    use super::render_markdown;
+
+    fn pages() -> BTreeMap<String, String> {
+        let registry = registry([
+            TypeDefinition {
+                description: Some("The armor of a piece of equipment.".to_owned()),
+                ..td(
+                    1,
+                    "Armor",
+                    TypeAttributes::Int32(
+                        NumberTypeAttributes::builder()
+                            .min(0)
+                            .max(10)
+                            .default_value(1)
+                            .build()
+                            .unwrap(),
+                    ),
+                )
+            },
+            td(
+                2,
+                "Armors",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(
+                3,
+                "Color",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value_ext("Red", Some("Like | blood".to_owned()), false)
+                        .with_value_ext("Rouge", None, true)
+                        .with_alias("Crimson", "Red")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+        ]);
+
+        render_markdown(&registry)
+    }
+
+    #[test]
+    fn test_render_markdown_index() {
+        let pages = pages();
+
+        assert_eq!(
+            pages.keys().collect::<Vec<_>>(),
+            ["Armor.md", "Armors.md", "Color.md", "index.md"]
+        );
+        assert_eq!(
+            pages["index.md"],
+            "# Types\n\n\
+             - [Armor](Armor.md) (int32): The armor of a piece of equipment.\n\
+             - [Armors](Armors.md) (array)\n\
+             - [Color](Color.md) (enum)\n"
+        );
+    }
+
+    #[test]
+    fn test_render_markdown_number() {
+        assert_eq!(
+            pages()["Armor.md"],
+            "# Armor\n\n\
+             The armor of a piece of equipment.\n\n\
+             - Kind: int32\n\
+             - Identifier: `1`\n\n\
+             ## Constraints\n\n\
+             - Minimum: `0`\n\
+             - Maximum: `10`\n\
+             - Default: `1`\n\n\
+             ## Referenced by\n\n\
+             - [Armors](Armors.md)\n"
+        );
+    }
+
+    #[test]
+    fn test_render_markdown_array() {
+        assert_eq!(
+            pages()["Armors.md"],
+            "# Armors\n\n\
+             - Kind: array\n\
+             - Identifier: `2`\n\n\
+             ## Constraints\n\n\
+             - Items: [Armor](Armor.md)\n\n\
+             ## References\n\n\
+             - [Armor](Armor.md)\n"
+        );
+    }
+
+    #[test]
+    fn test_render_markdown_enum() {
+        assert_eq!(
+            pages()["Color.md"],
+            "# Color\n\n\
+             - Kind: enum\n\
+             - Identifier: `3`\n\n\
+             ## Values\n\n\
+             | Value | Description |\n\
+             | --- | --- |\n\
+             | `Red` | Like \\| blood |\n\
+             | `Rouge` | **Deprecated.** |\n\n\
+             ### Aliases\n\n\
+             - `Crimson` stands for `Red`\n"
+        );
+    }
+}
@@ -0,0 +1,406 @@
+//! Self-describing documents, embedding their type definitions alongside their values.
+
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    hash::Hash,
+};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    ParseError, PathSegment, RegistrationError, SerializeError, TypeDefinition,
+    TypeDefinitionRegistry, Value, type_definition_registry::collect_closure,
+    typed_value::RawTypedValue,
+};
+
+/// The version of the document format.
+const DOCUMENT_VERSION: u32 = 1;
+
+/// A self-describing document: named values, along with the type definitions they need.
+///
+/// Documents are standalone: they can be parsed without any registry, which makes them suitable
+/// for save files or mods. They serialize to JSON as an object with the format `version`, the
+/// needed `type_definitions`, in topological order, and the `values`, by name, each with its
+/// `type_id` and its `value`.
+///
+/// Documents whose types are known in advance, like configuration files, can also leave their type
+/// definitions out and be validated against a registry instead: see
+/// [`Document::parse_with_registry`] and [`Document::values_to_json`].
+#[derive(Debug, Clone)]
+pub struct Document<Id, FieldName: Ord> {
+    /// The values, by their names.
+    pub(crate) values: BTreeMap<FieldName, Value<Id, FieldName>>,
+}
+
+/// An error that can occur when parsing a document.
+#[derive(Debug, thiserror::Error)]
+pub enum DocumentError<Id: Display, FieldName: Ord + Display> {
+    /// The document is not valid JSON, or does not have the structure of a document.
+    #[error("invalid document: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    /// The version of the document format is not supported.
+    #[error("unsupported document version {0}, expected {DOCUMENT_VERSION}")]
+    UnsupportedVersion(u32),
+
+    /// A type definition of the document could not be registered.
+    #[error("type definition `{name}` ({id}): {err}")]
+    Registration {
+        /// The identifier of the type definition.
+        id: Id,
+
+        /// The name of the type definition.
+        name: FieldName,
+
+        /// The reason why the type definition could not be registered.
+        err: RegistrationError<Id, FieldName>,
+    },
+
+    /// A value of the document has a type that the document does not define.
+    #[error("value `{name}`: unknown type `{type_id}`")]
+    UnknownType {
+        /// The name of the value.
+        name: FieldName,
+
+        /// The identifier of its type.
+        type_id: Id,
+    },
+
+    /// A value of the document is not valid for its type.
+    #[error("value `{name}`: {err}")]
+    InvalidValue {
+        /// The name of the value.
+        name: FieldName,
+
+        /// The reason why the value is not valid.
+        err: ParseError<Id, FieldName>,
+    },
+}
+
+impl<Id: Display, FieldName: Ord + Display> DocumentError<Id, FieldName> {
+    /// Get the path of the error in the values of the document, starting with the name of the
+    /// value at fault.
+    ///
+    /// The path is empty if the error is not about a specific value.
+    pub fn path(&self) -> Vec<PathSegment> {
+        match self {
+            Self::InvalidJson(_) | Self::UnsupportedVersion(_) | Self::Registration { .. } => {
+                Vec::new()
+            }
+            Self::UnknownType { name, .. } => vec![PathSegment::DictionaryKey(name.to_string())],
+            Self::InvalidValue { name, err } => {
+                std::iter::once(PathSegment::DictionaryKey(name.to_string()))
+                    .chain(err.path().iter().cloned())
+                    .collect()
+            }
+        }
+    }
+}
+
+/// A document, as serialized.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct RawDocument<Id, FieldName: Ord + Display + Clone> {
+    /// The version of the document format.
+    version: u32,
+
+    /// The type definitions, in topological order.
+    type_definitions: Vec<TypeDefinition<Id, FieldName>>,
+
+    /// The values, by their names.
+    values: BTreeMap<FieldName, RawTypedValue<Id>>,
+}
+
+impl<Id, FieldName: Ord> Default for Document<Id, FieldName> {
+    fn default() -> Self {
+        Self {
+            values: BTreeMap::new(),
+        }
+    }
+}
+
+impl<Id, FieldName: Ord> Document<Id, FieldName> {
+    /// Create an empty document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a named value to the document, replacing the value with the same name, if any.
+    pub fn insert(&mut self, name: FieldName, value: Value<Id, FieldName>) {
+        self.values.insert(name, value);
+    }
+
+    /// Get the value with the specified name.
+    pub fn get<Q>(&self, name: &Q) -> Option<&Value<Id, FieldName>>
+    where
+        FieldName: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.values.get(name)
+    }
+
+    /// Get the values, by their names.
+    pub fn values(&self) -> &BTreeMap<FieldName, Value<Id, FieldName>> {
+        &self.values
+    }
+}
+
+impl<Id, FieldName> Document<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display + DeserializeOwned,
+    FieldName: Ord + Hash + Clone + Display + Borrow<str> + DeserializeOwned,
+{
+    /// Parse a document from a JSON value.
+    ///
+    /// The type definitions of the document are registered in a new registry, and the values are
+    /// parsed for their types: the document is only valid if all of them are.
+    pub fn parse(json: serde_json::Value) -> Result<Self, DocumentError<Id, FieldName>> {
+        let raw: RawDocument<Id, FieldName> = serde_json::from_value(json)?;
+
+        if raw.version != DOCUMENT_VERSION {
+            return Err(DocumentError::UnsupportedVersion(raw.version));
+        }
+
+        let mut registry = TypeDefinitionRegistry::default();
+
+        if let Some((td, err)) = registry
+            .register(raw.type_definitions)
+            .failed
+            .into_iter()
+            .next()
+        {
+            return Err(DocumentError::Registration {
+                id: td.id,
+                name: td.name,
+                err,
+            });
+        }
+
+        Self::parse_values(&registry, raw.values)
+    }
+
+    /// Parse a document from JSON text.
+    pub fn parse_str(s: &str) -> Result<Self, DocumentError<Id, FieldName>> {
+        Self::parse(serde_json::from_str(s)?)
+    }
+
+    /// Parse a document without type definitions from a JSON value, looking the types of its
+    /// values up in a registry.
+    ///
+    /// The JSON value is an object of the values, by name, each with its `type_id` and its
+    /// `value`, as returned by [`Document::values_to_json`].
+    pub fn parse_with_registry(
+        registry: &TypeDefinitionRegistry<Id, FieldName>,
+        json: serde_json::Value,
+    ) -> Result<Self, DocumentError<Id, FieldName>> {
+        Self::parse_values(registry, serde_json::from_value(json)?)
+    }
+
+    /// Parse a document without type definitions from JSON text, looking the types of its values
+    /// up in a registry.
+    pub fn parse_str_with_registry(
+        registry: &TypeDefinitionRegistry<Id, FieldName>,
+        s: &str,
+    ) -> Result<Self, DocumentError<Id, FieldName>> {
+        Self::parse_with_registry(registry, serde_json::from_str(s)?)
+    }
+
+    /// Parse the values of a document for their types in a registry.
+    fn parse_values(
+        registry: &TypeDefinitionRegistry<Id, FieldName>,
+        values: BTreeMap<FieldName, RawTypedValue<Id>>,
+    ) -> Result<Self, DocumentError<Id, FieldName>> {
+        let mut document = Self::new();
+
+        for (name, entry) in values {
+            let Some(instance) = registry.by_id.get(&entry.type_id) else {
+                return Err(DocumentError::UnknownType {
+                    name,
+                    type_id: entry.type_id,
+                });
+            };
+
+            match Value::parse_for(instance.clone(), entry.value) {
+                Ok(value) => document.insert(name, value),
+                Err(err) => return Err(DocumentError::InvalidValue { name, err }),
+            }
+        }
+
+        Ok(document)
+    }
+}
+
+impl<Id, FieldName> Document<Id, FieldName>
+where
+    Id: Ord + Clone + Serialize,
+    FieldName: Ord + Display + Clone + Borrow<str> + Serialize,
+{
+    /// Serialize the document to a JSON value, embedding the type definitions of its values and
+    /// all the type definitions they reference.
+    ///
+    /// Parsing the result yields the same document.
+    pub fn to_json(&self) -> Result<serde_json::Value, SerializeError> {
+        let mut visited = BTreeSet::new();
+        let mut type_definitions = Vec::new();
+
+        for value in self.values.values() {
+            collect_closure(value.instance(), &mut visited, &mut type_definitions);
+        }
+
+        Ok(serde_json::to_value(RawDocument {
+            version: DOCUMENT_VERSION,
+            type_definitions,
+            values: self.raw_values()?,
+        })
+        .expect("documents always serialize to JSON"))
+    }
+
+    /// Serialize the values of the document to a JSON value, without their type definitions.
+    ///
+    /// Parsing the result with [`Document::parse_with_registry`], with a registry that has the
+    /// types of the values, yields the same document.
+    pub fn values_to_json(&self) -> Result<serde_json::Value, SerializeError> {
+        Ok(serde_json::to_value(self.raw_values()?).expect("documents always serialize to JSON"))
+    }
+
+    /// Serialize the values of the document, along with the identifiers of their types.
+    fn raw_values(&self) -> Result<BTreeMap<FieldName, RawTypedValue<Id>>, SerializeError> {
+        self.values
+            .iter()
+            .map(|(name, value)| {
+                Ok((
+                    name.clone(),
+                    RawTypedValue {
+                        type_id: value.instance().id.clone(),
+                        value: value.to_json()?,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        PathSegment,
+        test_support::owned::{Document, Value, registry_from_json},
+    };
+
+    use super::DocumentError;
+
+    #[test]
+    fn test_document() {
+        let registry = registry_from_json(json!([
+            { "id": 1, "name": "MyInt", "type": "int32", "attributes": { "max": 10 } },
+            { "id": 2, "name": "MyIntArray", "type": "array", "attributes": { "items_type_id": 1 } },
+            { "id": 3, "name": "MyString", "type": "string", "attributes": {} },
+        ]));
+
+        let mut document = Document::new();
+        document.insert(
+            "scores".to_owned(),
+            Value::parse_for(registry.by_id[&2].clone(), json!([1, 2, 3])).unwrap(),
+        );
+        document.insert(
+            "best".to_owned(),
+            Value::parse_for(registry.by_id[&1].clone(), json!(3)).unwrap(),
+        );
+
+        let json = document.to_json().unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "version": 1,
+                "type_definitions": [
+                    { "id": 1, "name": "MyInt", "type": "int32", "attributes": { "max": 10 } },
+                    { "id": 2, "name": "MyIntArray", "type": "array", "attributes": { "items_type_id": 1 } },
+                ],
+                "values": {
+                    "best": { "type_id": 1, "value": 3 },
+                    "scores": { "type_id": 2, "value": [1, 2, 3] },
+                },
+            })
+        );
+
+        let parsed = Document::parse(json.clone()).unwrap();
+        assert_eq!(parsed.values().len(), 2);
+        assert_eq!(parsed.get("scores").unwrap().to_string(), "[1, 2, 3]");
+        assert_eq!(parsed.to_json().unwrap(), json);
+
+        let mut invalid = json.clone();
+        invalid["values"]["best"]["value"] = json!(11);
+        let err = Document::parse(invalid).unwrap_err();
+        assert!(matches!(&err, DocumentError::InvalidValue { name, .. } if name == "best"));
+
+        let mut invalid = json.clone();
+        invalid["values"]["best"]["type_id"] = json!(3);
+        assert_eq!(
+            Document::parse(invalid).unwrap_err().to_string(),
+            "value `best`: unknown type `3`"
+        );
+
+        let mut invalid = json;
+        invalid["type_definitions"][1]["attributes"]["items_type_id"] = json!(4);
+        assert_eq!(
+            Document::parse(invalid).unwrap_err().to_string(),
+            "type definition `MyIntArray` (2): type definition has a broken reference to type definition `4`"
+        );
+
+        assert!(matches!(
+            Document::parse_str(r#"{ "version": 2, "type_definitions": [], "values": {} }"#),
+            Err(DocumentError::UnsupportedVersion(2))
+        ));
+    }
+
+    #[test]
+    fn test_document_with_registry() {
+        let registry = registry_from_json(json!([
+            { "id": 1, "name": "MyInt", "type": "int32", "attributes": { "max": 10 } },
+            { "id": 2, "name": "MyIntArray", "type": "array", "attributes": { "items_type_id": 1 } },
+        ]));
+
+        let json = json!({
+            "best": { "type_id": 1, "value": 3 },
+            "scores": { "type_id": 2, "value": [1, 2, 3] },
+        });
+
+        let document = Document::parse_with_registry(&registry, json.clone()).unwrap();
+        assert_eq!(document.get("best").unwrap().instance().name(), "MyInt");
+        assert_eq!(document.values_to_json().unwrap(), json);
+        assert_eq!(
+            document.to_json().unwrap()["values"],
+            document.values_to_json().unwrap()
+        );
+
+        let mut invalid = json.clone();
+        invalid["scores"]["value"][1] = json!(11);
+        let err = Document::parse_with_registry(&registry, invalid).unwrap_err();
+        assert_eq!(
+            err.path(),
+            [
+                PathSegment::DictionaryKey("scores".to_owned()),
+                PathSegment::ArrayIndex(1)
+            ]
+        );
+
+        let err = Document::parse_str_with_registry(
+            &registry,
+            r#"{ "best": { "type_id": 3, "value": 1 } }"#,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "value `best`: unknown type `3`");
+        assert_eq!(err.path(), [PathSegment::DictionaryKey("best".to_owned())]);
+
+        assert!(
+            Document::parse_str_with_registry(&registry, "[]")
+                .unwrap_err()
+                .path()
+                .is_empty()
+        );
+    }
+}
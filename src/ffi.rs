@@ -0,0 +1,401 @@
+//! A C ABI, for embedding GameSON validation in native engines.
+//!
+//! The matching declarations are in `include/gameson.h`, and the `gameson-ffi` crate of the
+//! workspace builds the functions as dynamic and static libraries. Registries identify their types
+//! with 64-bit unsigned integers and name them with strings.
+//!
+//! All the strings are NUL-terminated and UTF-8 encoded. The error messages returned through the
+//! `error` out-parameters are owned by the caller, who must free them with
+//! [`gameson_string_free`]. Handles are not thread-safe: a registry must not be used from several
+//! threads at the same time.
+//!
+//! Panics do not unwind into the caller: they are caught and reported with the
+//! [`GameSonStatus::InternalError`] status, after which the handles involved in the call may be in
+//! an inconsistent state and should only be freed.
+
+use std::{
+    ffi::{CStr, CString, c_char},
+    fmt::Display,
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+};
+
+use crate::{TypeDefinition, TypeDefinitionInstance, Value};
+
+/// The type of the registries handled by the C ABI.
+type TypeDefinitionRegistry = crate::TypeDefinitionRegistry<u64, String>;
+
+/// A registry of type definitions, as an opaque handle.
+pub struct GameSonRegistry(TypeDefinitionRegistry);
+
+/// The status of a call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameSonStatus {
+    /// The call succeeded.
+    Ok = 0,
+
+    /// A pointer was null, or a string was not valid UTF-8.
+    InvalidArgument = 1,
+
+    /// The JSON text was invalid, or did not describe type definitions.
+    InvalidJson = 2,
+
+    /// Some type definitions could not be registered.
+    RegistrationFailed = 3,
+
+    /// The type is not registered.
+    UnknownType = 4,
+
+    /// The value is not valid for its type.
+    InvalidValue = 5,
+
+    /// The library panicked, which is a bug.
+    InternalError = 6,
+}
+
+/// Run the body of a function, returning a fallback value if it panics, as unwinding into C is
+/// undefined behavior.
+fn catch_panic<T>(fallback: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(fallback)
+}
+
+/// Run the body of a function returning a status, reporting panics as internal errors.
+///
+/// # Safety
+///
+/// `error` must be null or valid for writes.
+unsafe fn catch_status(
+    error: *mut *mut c_char,
+    body: impl FnOnce() -> GameSonStatus,
+) -> GameSonStatus {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown panic");
+
+        // SAFETY: the caller guarantees that `error` is null or valid for writes.
+        unsafe { set_error(error, format_args!("internal error: {message}")) };
+
+        GameSonStatus::InternalError
+    })
+}
+
+/// Write an error message to an out-parameter, if it is not null.
+///
+/// # Safety
+///
+/// `error` must be null or valid for writes.
+unsafe fn set_error(error: *mut *mut c_char, message: impl Display) {
+    if !error.is_null() {
+        let message = CString::new(message.to_string().replace('\0', "\u{FFFD}"))
+            .expect("NUL bytes were replaced");
+
+        // SAFETY: the caller guarantees that `error` is valid for writes.
+        unsafe { *error = message.into_raw() };
+    }
+}
+
+/// Get a string from a C string.
+///
+/// # Safety
+///
+/// `s` must be null or point to a NUL-terminated string.
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+
+    // SAFETY: the caller guarantees that `s` points to a NUL-terminated string.
+    unsafe { CStr::from_ptr(s) }.to_str().ok()
+}
+
+/// Create an empty registry, or return null if that fails.
+///
+/// The registry must be freed with [`gameson_registry_free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn gameson_registry_new() -> *mut GameSonRegistry {
+    catch_panic(std::ptr::null_mut(), || {
+        Box::into_raw(Box::new(GameSonRegistry(TypeDefinitionRegistry::default())))
+    })
+}
+
+/// Free a registry.
+///
+/// # Safety
+///
+/// `registry` must be null or a registry returned by [`gameson_registry_new`] that was not freed
+/// yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gameson_registry_free(registry: *mut GameSonRegistry) {
+    catch_panic((), || {
+        if !registry.is_null() {
+            // SAFETY: the caller guarantees that `registry` was returned by
+            // `gameson_registry_new`.
+            drop(unsafe { Box::from_raw(registry) });
+        }
+    });
+}
+
+/// Register type definitions, given as a JSON array.
+///
+/// The type definitions that can be registered are, even if others fail: the error message then
+/// lists the failures, one per line.
+///
+/// # Safety
+///
+/// `registry` must be a valid registry, `json` a NUL-terminated string, and `error` null or valid
+/// for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gameson_registry_register_json(
+    registry: *mut GameSonRegistry,
+    json: *const c_char,
+    error: *mut *mut c_char,
+) -> GameSonStatus {
+    let body = || {
+        // SAFETY: the caller guarantees that `registry` is null or valid.
+        let (Some(registry), Some(json)) = (unsafe { registry.as_mut() }, unsafe { to_str(json) })
+        else {
+            return GameSonStatus::InvalidArgument;
+        };
+
+        let type_definitions: Vec<TypeDefinition<u64, String>> = match serde_json::from_str(json) {
+            Ok(type_definitions) => type_definitions,
+            Err(err) => {
+                // SAFETY: the caller guarantees that `error` is null or valid for writes.
+                unsafe { set_error(error, err) };
+
+                return GameSonStatus::InvalidJson;
+            }
+        };
+
+        match registry.0.register(type_definitions).into_result() {
+            Ok(_) => GameSonStatus::Ok,
+            Err(failed) => {
+                let message = failed
+                    .iter()
+                    .map(|(td, err)| format!("`{}` ({}): {err}", td.name, td.id))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                // SAFETY: the caller guarantees that `error` is null or valid for writes.
+                unsafe { set_error(error, message) };
+
+                GameSonStatus::RegistrationFailed
+            }
+        }
+    };
+
+    // SAFETY: the caller guarantees that `error` is null or valid for writes.
+    unsafe { catch_status(error, body) }
+}
+
+/// Validate a JSON value against a registered type, designated by its name.
+///
+/// # Safety
+///
+/// `registry` must be a valid registry, `type_name` and `json` NUL-terminated strings, and
+/// `error` null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gameson_registry_validate_json(
+    registry: *const GameSonRegistry,
+    type_name: *const c_char,
+    json: *const c_char,
+    error: *mut *mut c_char,
+) -> GameSonStatus {
+    let body = || {
+        // SAFETY: the caller guarantees that `registry` is null or valid.
+        let (Some(registry), Some(type_name)) =
+            (unsafe { registry.as_ref() }, unsafe { to_str(type_name) })
+        else {
+            return GameSonStatus::InvalidArgument;
+        };
+
+        match registry.0.by_name.get(type_name) {
+            // SAFETY: the caller guarantees the validity of `json` and `error`.
+            Some(instance) => unsafe { validate(instance, json, error) },
+            None => GameSonStatus::UnknownType,
+        }
+    };
+
+    // SAFETY: the caller guarantees that `error` is null or valid for writes.
+    unsafe { catch_status(error, body) }
+}
+
+/// Validate a JSON value against a registered type, designated by its identifier.
+///
+/// # Safety
+///
+/// `registry` must be a valid registry, `json` a NUL-terminated string, and `error` null or
+/// valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gameson_registry_validate_json_by_id(
+    registry: *const GameSonRegistry,
+    type_id: u64,
+    json: *const c_char,
+    error: *mut *mut c_char,
+) -> GameSonStatus {
+    let body = || {
+        // SAFETY: the caller guarantees that `registry` is null or valid.
+        let Some(registry) = (unsafe { registry.as_ref() }) else {
+            return GameSonStatus::InvalidArgument;
+        };
+
+        match registry.0.by_id.get(&type_id) {
+            // SAFETY: the caller guarantees the validity of `json` and `error`.
+            Some(instance) => unsafe { validate(instance, json, error) },
+            None => GameSonStatus::UnknownType,
+        }
+    };
+
+    // SAFETY: the caller guarantees that `error` is null or valid for writes.
+    unsafe { catch_status(error, body) }
+}
+
+/// Validate a JSON value against a type instance.
+///
+/// # Safety
+///
+/// `json` must be a NUL-terminated string, and `error` null or valid for writes.
+unsafe fn validate(
+    instance: &Arc<TypeDefinitionInstance<u64, String>>,
+    json: *const c_char,
+    error: *mut *mut c_char,
+) -> GameSonStatus {
+    // SAFETY: the caller guarantees that `json` is null or a NUL-terminated string.
+    let Some(json) = (unsafe { to_str(json) }) else {
+        return GameSonStatus::InvalidArgument;
+    };
+
+    match Value::parse_str_for(Arc::clone(instance), json) {
+        Ok(_) => GameSonStatus::Ok,
+        Err(err) => {
+            // SAFETY: the caller guarantees that `error` is null or valid for writes.
+            unsafe { set_error(error, err) };
+
+            GameSonStatus::InvalidValue
+        }
+    }
+}
+
+/// Free a string returned by the library.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by the library that was not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gameson_string_free(s: *mut c_char) {
+    catch_panic((), || {
+        if !s.is_null() {
+            // SAFETY: the caller guarantees that `s` was returned by the library.
+            drop(unsafe { CString::from_raw(s) });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{CStr, CString, c_char},
+        ptr,
+    };
+
+    use super::{
+        GameSonStatus, catch_panic, catch_status, gameson_registry_free, gameson_registry_new,
+        gameson_registry_register_json, gameson_registry_validate_json,
+        gameson_registry_validate_json_by_id, gameson_string_free,
+    };
+
+    /// Take an error message returned by the library.
+    fn take_error(error: *mut c_char) -> String {
+        assert!(!error.is_null());
+        let message = unsafe { CStr::from_ptr(error) }
+            .to_str()
+            .unwrap()
+            .to_owned();
+        unsafe { gameson_string_free(error) };
+
+        message
+    }
+
+    #[test]
+    fn test_ffi() {
+        let registry = gameson_registry_new();
+        let mut error = ptr::null_mut();
+
+        let json = CString::new(
+            r#"[
+                { "id": 1, "name": "Armor", "type": "int32", "attributes": { "min": 0, "max": 10 } },
+                { "id": 2, "name": "Armors", "type": "array", "attributes": { "items_type_id": 1 } },
+                { "id": 3, "name": "Broken", "type": "array", "attributes": { "items_type_id": 4 } }
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            unsafe { gameson_registry_register_json(registry, json.as_ptr(), &mut error) },
+            GameSonStatus::RegistrationFailed
+        );
+        assert_eq!(
+            take_error(error),
+            "`Broken` (3): type definition has a broken reference to type definition `4`"
+        );
+
+        let armors = CString::new("Armors").unwrap();
+        let value = CString::new("[1, 2, 3]").unwrap();
+        assert_eq!(
+            unsafe {
+                gameson_registry_validate_json(
+                    registry,
+                    armors.as_ptr(),
+                    value.as_ptr(),
+                    ptr::null_mut(),
+                )
+            },
+            GameSonStatus::Ok
+        );
+
+        let value = CString::new("[1, 11]").unwrap();
+        assert_eq!(
+            unsafe {
+                gameson_registry_validate_json_by_id(registry, 2, value.as_ptr(), &mut error)
+            },
+            GameSonStatus::InvalidValue
+        );
+        assert_eq!(
+            take_error(error),
+            "failed to parse GameSON value `Armors` (2): [1]: invalid int32: value 11 is greater than the maximum 10 at line 1 column 6"
+        );
+
+        assert_eq!(
+            unsafe {
+                gameson_registry_validate_json_by_id(registry, 3, value.as_ptr(), &mut error)
+            },
+            GameSonStatus::UnknownType
+        );
+        assert_eq!(
+            unsafe {
+                gameson_registry_validate_json(registry, ptr::null(), value.as_ptr(), &mut error)
+            },
+            GameSonStatus::InvalidArgument
+        );
+
+        unsafe { gameson_registry_free(registry) };
+    }
+    #[test]
+    fn test_ffi_panic() {
+        let mut error = ptr::null_mut();
+
+        assert_eq!(
+            unsafe { catch_status(&mut error, || panic!("boom")) },
+            GameSonStatus::InternalError
+        );
+        assert_eq!(take_error(error), "internal error: boom");
+        assert_eq!(
+            unsafe { catch_status(&mut error, || GameSonStatus::Ok) },
+            GameSonStatus::Ok
+        );
+        assert_eq!(catch_panic(1, || panic!("{}", "boom")), 1);
+    }
+}
@@ -0,0 +1,468 @@
+//! Structural fingerprints and comparison of types.
+
+use std::{collections::BTreeMap, fmt::Display, hash::Hash, sync::Arc};
+
+use itertools::Itertools;
+
+use serde::Serialize;
+
+use crate::{
+    TypeDefinition, TypeDefinitionInstance, TypeDefinitionRegistry,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+/// A stable structural hash of a type or of a registry.
+///
+/// Fingerprints only depend on the structure of the types: neither on their descriptions nor on
+/// their identifiers, nor on the order they were registered in. They are stable across runs and
+/// platforms, which makes them suitable as cache keys or to check that a client and a server
+/// agree on their schemas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// Get the fingerprint as an integer.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl From<Fingerprint> for u64 {
+    fn from(fingerprint: Fingerprint) -> Self {
+        fingerprint.0
+    }
+}
+
+impl From<Fingerprint> for String {
+    fn from(fingerprint: Fingerprint) -> Self {
+        fingerprint.to_string()
+    }
+}
+
+impl<Id, FieldName> TypeDefinition<Id, FieldName>
+where
+    Id: From<Fingerprint> + Serialize,
+    FieldName: Ord + Display + Clone + Serialize,
+{
+    /// Replace the identifier of the type definition by one derived from its content.
+    ///
+    /// The identifier is derived from the name of the type and a stable structural hash of its
+    /// attributes, which include the identifiers of the types it references, but not from its
+    /// description. Independent machines thus derive identical identifiers for identical type
+    /// definitions, provided that the types they reference have content hash identifiers too:
+    /// those must be derived first.
+    pub fn with_content_hash_id(mut self) -> Self {
+        let mut hasher = Hasher::default();
+        hasher.write_serialized(&self.name);
+        hasher.write_serialized(&self.attributes);
+        self.id = Fingerprint(hasher.0).into();
+
+        self
+    }
+}
+
+impl<Id, FieldName: Ord + Serialize> TypeDefinitionInstance<Id, FieldName> {
+    /// Compute the structural fingerprint of the type.
+    ///
+    /// The name of the type is not part of its fingerprint, but the structure of the types it
+    /// references is: two types with the same attributes referencing structurally identical types
+    /// have the same fingerprint.
+    pub fn fingerprint(&self) -> Fingerprint {
+        let mut hasher = Hasher::default();
+        hasher.write_attributes(&self.attributes);
+
+        Fingerprint(hasher.0)
+    }
+}
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display + Serialize,
+{
+    /// Compute the structural fingerprint of the registry.
+    ///
+    /// It depends on the names and the fingerprints of all the registered types.
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::of_types(
+            self.by_name
+                .iter()
+                .map(|(name, instance)| (name, instance.fingerprint())),
+        )
+    }
+}
+
+impl Fingerprint {
+    /// Compute the fingerprint of a serializable value.
+    pub(crate) fn of_serialized(value: &impl Serialize) -> Self {
+        let mut hasher = Hasher::default();
+        hasher.write_serialized(value);
+
+        Fingerprint(hasher.0)
+    }
+
+    /// Compute the fingerprint of a registry from the names and the fingerprints of its types.
+    pub(crate) fn of_types<'a, FieldName: Ord + Serialize + 'a>(
+        types: impl IntoIterator<Item = (&'a FieldName, Fingerprint)>,
+    ) -> Self {
+        Self::of_sorted_types(types.into_iter().sorted_by(|(a, _), (b, _)| a.cmp(b)))
+    }
+
+    /// Compute the fingerprint of a registry from the names and the fingerprints of its types,
+    /// sorted by name.
+    pub(crate) fn of_sorted_types<'a, FieldName: Ord + Serialize + 'a>(
+        types: impl IntoIterator<Item = (&'a FieldName, Fingerprint)>,
+    ) -> Self {
+        let mut hasher = Hasher::default();
+
+        // Names are unique and iterated in order, which makes the result independent of the
+        // identifiers.
+        for (name, fingerprint) in types {
+            hasher.write_serialized(name);
+            hasher.write_u64(fingerprint.0);
+        }
+
+        Fingerprint(hasher.0)
+    }
+}
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display + Serialize,
+{
+    /// Find the groups of types whose attributes are structurally identical.
+    ///
+    /// Types are structurally identical if they have the same attributes, and reference
+    /// structurally identical types: their names, descriptions and identifiers do not matter.
+    /// Only groups of at least two types are returned, each sorted by identifier.
+    pub fn find_structural_duplicates(
+        &self,
+    ) -> Vec<Vec<Arc<TypeDefinitionInstance<Id, FieldName>>>> {
+        let mut by_fingerprint = BTreeMap::<_, Vec<Vec<_>>>::new();
+
+        for instance in self.by_id.values().sorted_by(|a, b| a.id.cmp(&b.id)) {
+            let groups = by_fingerprint.entry(instance.fingerprint()).or_default();
+
+            // Fingerprints could collide: the structures must be compared to be sure.
+            match groups.iter_mut().find(
+                |group: &&mut Vec<&Arc<TypeDefinitionInstance<Id, FieldName>>>| {
+                    group[0].attributes.structurally_eq(&instance.attributes)
+                },
+            ) {
+                Some(group) => group.push(instance),
+                None => groups.push(vec![instance]),
+            }
+        }
+
+        let mut duplicates: Vec<Vec<_>> = by_fingerprint
+            .into_values()
+            .flatten()
+            .filter(|group| group.len() > 1)
+            .map(|group| group.into_iter().map(Arc::clone).collect())
+            .collect();
+
+        duplicates.sort_by(|a, b| a[0].id.cmp(&b[0].id));
+        duplicates
+    }
+}
+
+impl<Id, FieldName: Ord> TypeAttributesInstance<Id, FieldName> {
+    /// Check whether the type attributes are structurally identical to others.
+    fn structurally_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Array(a), Self::Array(b)) => a
+                .items_type_id()
+                .attributes
+                .structurally_eq(&b.items_type_id().attributes),
+            (Self::Dictionary(a), Self::Dictionary(b)) => {
+                a.wire_form() == b.wire_form()
+                    && a.constraints() == b.constraints()
+                    && a.requirements() == b.requirements()
+                    && a.invariants() == b.invariants()
+                    && a.keys_type_id()
+                        .attributes
+                        .structurally_eq(&b.keys_type_id().attributes)
+                    && a.values_type_id()
+                        .attributes
+                        .structurally_eq(&b.values_type_id().attributes)
+            }
+            (Self::Boolean(a), Self::Boolean(b)) => a == b,
+            (Self::Int32(a), Self::Int32(b)) => a == b,
+            (Self::Int64(a), Self::Int64(b)) => a == b,
+            (Self::Uint32(a), Self::Uint32(b)) => a == b,
+            (Self::Uint64(a), Self::Uint64(b)) => a == b,
+            (Self::Float32(a), Self::Float32(b)) => a == b,
+            (Self::Float64(a), Self::Float64(b)) => a == b,
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Enum(a), Self::Enum(b)) => a == b,
+            #[cfg(feature = "uuid")]
+            (Self::Uuid(a), Self::Uuid(b)) => a == b,
+            #[cfg(feature = "bytes")]
+            (Self::Bytes(a), Self::Bytes(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// A 64-bit FNV-1a hasher.
+///
+/// Unlike the hashers of the standard library, its output is guaranteed to never change.
+struct Hasher(u64);
+
+impl Default for Hasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher {
+    /// Hash bytes.
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Hash an integer.
+    fn write_u64(&mut self, v: u64) {
+        self.write(&v.to_le_bytes());
+    }
+
+    /// Hash a value through its JSON representation, prefixed with its length so that
+    /// consecutive values cannot be confused.
+    fn write_serialized(&mut self, value: &impl Serialize) {
+        let json = serde_json::to_vec(value).expect("type attributes always serialize to JSON");
+
+        self.write_u64(json.len() as u64);
+        self.write(&json);
+    }
+
+    /// Hash type attributes, including the structure of the types they reference.
+    fn write_attributes<Id, FieldName: Ord + Serialize>(
+        &mut self,
+        attributes: &TypeAttributesInstance<Id, FieldName>,
+    ) {
+        self.write_serialized(&attributes.kind());
+
+        match attributes {
+            TypeAttributesInstance::Array(a) => {
+                self.write_attributes(&a.items_type_id().attributes);
+            }
+            TypeAttributesInstance::Dictionary(d) => {
+                self.write_serialized(&d.wire_form());
+                self.write_attributes(&d.keys_type_id().attributes);
+                self.write_attributes(&d.values_type_id().attributes);
+
+                // Dictionaries without constraints, requirements or invariants keep the
+                // fingerprints they had before those existed.
+                if !d.constraints().is_empty() {
+                    self.write_serialized(&d.constraints());
+                }
+
+                if !d.requirements().is_empty() {
+                    self.write_serialized(&d.requirements());
+                }
+
+                if !d.invariants().is_empty() {
+                    self.write_serialized(&d.invariants());
+                }
+            }
+            TypeAttributesInstance::Boolean(a) => self.write_serialized(a),
+            TypeAttributesInstance::Int32(a) => self.write_serialized(a),
+            TypeAttributesInstance::Int64(a) => self.write_serialized(a),
+            TypeAttributesInstance::Uint32(a) => self.write_serialized(a),
+            TypeAttributesInstance::Uint64(a) => self.write_serialized(a),
+            TypeAttributesInstance::Float32(a) => self.write_serialized(a),
+            TypeAttributesInstance::Float64(a) => self.write_serialized(a),
+            TypeAttributesInstance::String(a) => self.write_serialized(a),
+            TypeAttributesInstance::Enum(a) => self.write_serialized(a),
+            #[cfg(feature = "uuid")]
+            TypeAttributesInstance::Uuid(a) => self.write_serialized(a),
+            #[cfg(feature = "bytes")]
+            TypeAttributesInstance::Bytes(a) => self.write_serialized(a),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        FieldName, Id, TypeAttributes, TypeDefinition, TypeDefinitionRegistry, registry, td,
+    };
+    use crate::type_attributes::{ArrayTypeAttributes, NumberTypeAttributes};
+
+    fn int_array_registry(
+        base_id: Id,
+        description: Option<&str>,
+        max: i32,
+    ) -> TypeDefinitionRegistry {
+        registry([
+            td(
+                base_id + 1,
+                "MyIntArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(base_id)),
+            ),
+            TypeDefinition {
+                description: description.map(ToOwned::to_owned),
+                ..td(
+                    base_id,
+                    "MyInt",
+                    TypeAttributes::Int32(
+                        NumberTypeAttributes::builder().max(max).build().unwrap(),
+                    ),
+                )
+            },
+        ])
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let reference = int_array_registry(1, None, 10);
+
+        assert_eq!(
+            reference.fingerprint(),
+            int_array_registry(10, Some("An int."), 10).fingerprint()
+        );
+        assert_ne!(
+            reference.fingerprint(),
+            int_array_registry(1, None, 11).fingerprint()
+        );
+
+        let my_int = &reference.by_id[&1];
+        let my_int_array = &reference.by_id[&2];
+
+        assert_ne!(my_int.fingerprint(), my_int_array.fingerprint());
+        assert_eq!(
+            my_int_array.fingerprint(),
+            int_array_registry(5, None, 10).by_id[&6].fingerprint()
+        );
+        assert_ne!(
+            my_int_array.fingerprint(),
+            int_array_registry(5, None, 11).by_id[&6].fingerprint()
+        );
+
+        // Fingerprints must never change.
+        assert_eq!(my_int.fingerprint().to_string().len(), 16);
+        assert_eq!(
+            my_int.fingerprint(),
+            super::Fingerprint({
+                let mut hasher = super::Hasher::default();
+                hasher.write_serialized(&"int32");
+                hasher.write_serialized(&serde_json::json!({ "max": 10 }));
+                hasher.0
+            })
+        );
+    }
+
+    #[test]
+    fn test_find_structural_duplicates() {
+        let mut registry = TypeDefinitionRegistry::default();
+
+        let (_, errors) = registry
+            .register([
+                td(1, "MyString", TypeAttributes::String(Default::default())),
+                TypeDefinition {
+                    id: 2,
+                    name: "MyName",
+                    description: Some("A name.".to_owned()),
+                    attributes: TypeAttributes::String(Default::default()),
+                },
+                td(
+                    3,
+                    "StringArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+                ),
+                td(
+                    4,
+                    "NameArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+                ),
+                td(5, "MyInt", TypeAttributes::Int32(Default::default())),
+                td(
+                    6,
+                    "IntArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(5)),
+                ),
+            ])
+            .into_parts();
+        assert!(errors.is_empty());
+
+        assert_eq!(
+            registry
+                .find_structural_duplicates()
+                .iter()
+                .map(|group| group.iter().map(|td| td.id).collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![vec![1, 2], vec![3, 4]]
+        );
+    }
+
+    #[test]
+    fn test_with_content_hash_id() {
+        type TypeDefinition = crate::TypeDefinition<u64, FieldName>;
+        type TypeAttributes = crate::TypeAttributes<u64, FieldName>;
+
+        let my_int = |description: Option<&str>| {
+            TypeDefinition {
+                id: 0,
+                name: "MyInt",
+                description: description.map(ToOwned::to_owned),
+                attributes: TypeAttributes::Int32(Default::default()),
+            }
+            .with_content_hash_id()
+        };
+
+        let id = my_int(None).id;
+        assert_eq!(my_int(Some("An int.")).id, id);
+
+        let my_number = TypeDefinition {
+            name: "MyNumber",
+            ..my_int(None)
+        }
+        .with_content_hash_id();
+        assert_ne!(my_number.id, id);
+
+        let my_int_array = |items_type_id| {
+            TypeDefinition {
+                id: 0,
+                name: "MyIntArray",
+                description: None,
+                attributes: TypeAttributes::Array(ArrayTypeAttributes::new(items_type_id)),
+            }
+            .with_content_hash_id()
+        };
+        assert_ne!(my_int_array(id).id, my_int_array(my_number.id).id);
+
+        // Identifiers must never change.
+        assert_eq!(
+            id,
+            u64::from(super::Fingerprint({
+                let mut hasher = super::Hasher::default();
+                hasher.write_serialized(&"MyInt");
+                let json = br#"{"type":"int32","attributes":{}}"#;
+                hasher.write_u64(json.len() as u64);
+                hasher.write(json);
+                hasher.0
+            }))
+        );
+        assert_eq!(
+            crate::TypeDefinition::<String, FieldName> {
+                id: String::new(),
+                name: "MyInt",
+                description: None,
+                attributes: crate::TypeAttributes::Int32(Default::default()),
+            }
+            .with_content_hash_id()
+            .id,
+            format!("{id:016x}")
+        );
+    }
+}
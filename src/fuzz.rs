@@ -0,0 +1,560 @@
+//! Integration with `arbitrary`, for fuzzing and property testing.
+
+use std::{borrow::Borrow, fmt::Display, ops::ControlFlow, sync::Arc};
+
+use arbitrary::{Arbitrary, Unstructured};
+
+use crate::{
+    DictionaryWireForm, TypeAttributes, TypeDefinition, TypeDefinitionInstance,
+    type_attributes::{
+        ArrayTypeAttributes, BooleanTypeAttributes, DictionaryTypeAttributes, EnumTypeAttributes,
+        NumberTypeAttributes, StringTypeAttributes,
+    },
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+#[cfg(feature = "bytes")]
+use crate::type_attributes::BytesTypeAttributes;
+
+#[cfg(feature = "uuid")]
+use crate::type_attributes::UuidTypeAttributes;
+
+/// The maximum number of items generated for an array or a dictionary.
+const MAX_ITEMS: u32 = 16;
+
+/// The maximum number of values generated for a type, until one satisfies the entry rules and the
+/// validator of the type.
+const MAX_ATTEMPTS: usize = 16;
+
+/// A number that can be generated for number type attributes.
+trait ArbitraryNumber<'a>: Arbitrary<'a> + PartialOrd + Copy {
+    /// Check whether the number can be represented in JSON.
+    fn is_finite(self) -> bool {
+        true
+    }
+}
+
+impl ArbitraryNumber<'_> for i32 {}
+impl ArbitraryNumber<'_> for i64 {}
+impl ArbitraryNumber<'_> for u32 {}
+impl ArbitraryNumber<'_> for u64 {}
+
+impl ArbitraryNumber<'_> for f32 {
+    fn is_finite(self) -> bool {
+        f32::is_finite(self)
+    }
+}
+
+impl ArbitraryNumber<'_> for f64 {
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+}
+
+/// Generate valid number type attributes: the range is ordered, and the default value is within
+/// it.
+fn arbitrary_number_attributes<'a, Num: ArbitraryNumber<'a>>(
+    u: &mut Unstructured<'a>,
+) -> arbitrary::Result<NumberTypeAttributes<Num>> {
+    let finite = |n: Option<Num>| n.filter(|n| n.is_finite());
+    let mut min = finite(u.arbitrary()?);
+    let mut max = finite(u.arbitrary()?);
+
+    if let (Some(a), Some(b)) = (min, max)
+        && a > b
+    {
+        (min, max) = (max, min);
+    }
+
+    let candidate = finite(u.arbitrary()?)
+        .filter(|n| min.is_none_or(|min| *n >= min) && max.is_none_or(|max| *n <= max));
+    let default = match u.int_in_range(0..=3u8)? {
+        0 => None,
+        1 => min,
+        2 => max,
+        _ => candidate,
+    };
+
+    let mut builder = NumberTypeAttributes::builder();
+
+    if let Some(min) = min {
+        builder = builder.min(min);
+    }
+
+    if let Some(max) = max {
+        builder = builder.max(max);
+    }
+
+    if let Some(default) = default {
+        builder = builder.default_value(default);
+    }
+
+    builder
+        .build()
+        .map_err(|_| arbitrary::Error::IncorrectFormat)
+}
+
+/// Generate valid enum type attributes: aliases never overlap with values and always point to
+/// one, and the default value is one of the values.
+fn arbitrary_enum_attributes<'a, FieldName: Arbitrary<'a> + Ord + Display + Clone>(
+    u: &mut Unstructured<'a>,
+) -> arbitrary::Result<EnumTypeAttributes<FieldName>> {
+    let values: Vec<(FieldName, Option<String>, bool)> = u.arbitrary()?;
+    let mut builder = EnumTypeAttributes::builder();
+
+    for (name, description, deprecated) in &values {
+        builder = builder.with_value_ext(name.clone(), description.clone(), *deprecated);
+    }
+
+    if !values.is_empty() {
+        let aliases: Vec<FieldName> = u.arbitrary()?;
+
+        for alias in aliases {
+            if !values.iter().any(|(name, ..)| *name == alias) {
+                let (name, ..) = u.choose(&values)?;
+                builder = builder.with_alias(alias, name.clone());
+            }
+        }
+
+        if u.arbitrary()? {
+            let (name, ..) = u.choose(&values)?;
+            builder = builder.with_default(name.clone());
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|_| arbitrary::Error::IncorrectFormat)
+}
+
+/// Generate string type attributes.
+fn arbitrary_string_attributes(
+    u: &mut Unstructured<'_>,
+) -> arbitrary::Result<StringTypeAttributes> {
+    let mut builder = StringTypeAttributes::builder()
+        .normalize(u.arbitrary()?)
+        .whitespace(u.arbitrary()?);
+
+    if let Some(default) = u.arbitrary::<Option<String>>()? {
+        builder = builder.default_value(default);
+    }
+
+    Ok(builder.build())
+}
+
+/// Generates valid type attributes, that pass the checks of their deserialization.
+///
+/// The referenced type identifiers are arbitrary: registering the generated type definitions
+/// mostly fails because of broken references. Generate the JSON values of registered types with
+/// [`TypeDefinitionInstance::arbitrary_json`] instead.
+impl<'a, Id, FieldName> Arbitrary<'a> for TypeAttributes<Id, FieldName>
+where
+    Id: Arbitrary<'a>,
+    FieldName: Arbitrary<'a> + Ord + Display + Clone,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let kinds: &[fn(&mut Unstructured<'a>) -> arbitrary::Result<Self>] = &[
+            |u| Ok(Self::Array(ArrayTypeAttributes::new(u.arbitrary()?))),
+            |u| {
+                Ok(Self::Dictionary(
+                    DictionaryTypeAttributes::new(u.arbitrary()?, u.arbitrary()?)
+                        .with_wire_form(u.arbitrary()?),
+                ))
+            },
+            |u| Ok(Self::Boolean(BooleanTypeAttributes::new(u.arbitrary()?))),
+            |u| arbitrary_number_attributes(u).map(Self::Int32),
+            |u| arbitrary_number_attributes(u).map(Self::Int64),
+            |u| arbitrary_number_attributes(u).map(Self::Uint32),
+            |u| arbitrary_number_attributes(u).map(Self::Uint64),
+            |u| arbitrary_number_attributes(u).map(Self::Float32),
+            |u| arbitrary_number_attributes(u).map(Self::Float64),
+            |u| arbitrary_string_attributes(u).map(Self::String),
+            |u| arbitrary_enum_attributes(u).map(Self::Enum),
+            #[cfg(feature = "uuid")]
+            |u| {
+                let default = u.arbitrary::<Option<u128>>()?.map(uuid::Uuid::from_u128);
+
+                Ok(Self::Uuid(UuidTypeAttributes::new(default)))
+            },
+            #[cfg(feature = "bytes")]
+            |u| Ok(Self::Bytes(BytesTypeAttributes::new(u.arbitrary()?))),
+        ];
+
+        u.choose(kinds)?(u)
+    }
+}
+
+impl<'a, Id, FieldName> Arbitrary<'a> for TypeDefinition<Id, FieldName>
+where
+    Id: Arbitrary<'a>,
+    FieldName: Arbitrary<'a> + Ord + Display + Clone,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self {
+            id: u.arbitrary()?,
+            name: u.arbitrary()?,
+            description: u.arbitrary()?,
+            attributes: u.arbitrary()?,
+        })
+    }
+}
+
+impl<Id, FieldName: Ord + Display + Clone + Borrow<str>> TypeDefinitionInstance<Id, FieldName> {
+    /// Generate an arbitrary JSON value that parses successfully for this type.
+    ///
+    /// Numbers are within their range, enum values are either values or aliases of their enum,
+    /// and dictionaries in the object wire form only have keys that serialize as strings. Values
+    /// that violate the entry rules or the validator of their type are generated again, up to 16
+    /// times.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`arbitrary::Error::IncorrectFormat`] if the type, or a type it references, is an
+    /// empty enum, which no value satisfies, or if no generated value satisfied the entry rules or
+    /// the validator of the type.
+    pub fn arbitrary_json(
+        self: &Arc<Self>,
+        u: &mut Unstructured<'_>,
+    ) -> arbitrary::Result<serde_json::Value> {
+        let checked = self.validator.is_some()
+            || matches!(&self.attributes, TypeAttributesInstance::Dictionary(a) if a.has_entry_rules());
+
+        for _ in 0..MAX_ATTEMPTS {
+            let json = self.arbitrary_json_unchecked(u)?;
+
+            if !checked || self.validate(&json).is_valid() {
+                return Ok(json);
+            }
+        }
+
+        Err(arbitrary::Error::IncorrectFormat)
+    }
+
+    /// Generate an arbitrary JSON value for this type, without checking the entry rules and the
+    /// validator of the type.
+    fn arbitrary_json_unchecked(
+        &self,
+        u: &mut Unstructured<'_>,
+    ) -> arbitrary::Result<serde_json::Value> {
+        Ok(match &self.attributes {
+            TypeAttributesInstance::Array(a) => {
+                let mut items = Vec::new();
+
+                u.arbitrary_loop(None, Some(MAX_ITEMS), |u| {
+                    items.push(a.items_type_id().arbitrary_json(u)?);
+
+                    Ok(ControlFlow::Continue(()))
+                })?;
+
+                serde_json::Value::Array(items)
+            }
+            TypeAttributesInstance::Dictionary(a) => {
+                let mut object = serde_json::Map::new();
+                let mut pairs = Vec::new();
+                let mut keys = Vec::new();
+
+                u.arbitrary_loop(None, Some(MAX_ITEMS), |u| {
+                    let key = a.keys_type_id().arbitrary_json(u)?;
+                    let value = a.values_type_id().arbitrary_json(u)?;
+
+                    // Keys that are the same once parsed, such as an enum value and its alias,
+                    // would be duplicates.
+                    let canonical = a.keys_type_id().canonical_key(&key);
+
+                    if keys.contains(&canonical) {
+                        return Ok(ControlFlow::Continue(()));
+                    }
+
+                    keys.push(canonical);
+
+                    match (a.wire_form(), key) {
+                        (DictionaryWireForm::Object, serde_json::Value::String(key)) => {
+                            object.insert(key, value);
+                        }
+                        (DictionaryWireForm::Object, _) => {
+                            return Err(arbitrary::Error::IncorrectFormat);
+                        }
+                        (DictionaryWireForm::Pairs, key) => {
+                            pairs.push(serde_json::Value::Array(vec![key, value]));
+                        }
+                    }
+
+                    Ok(ControlFlow::Continue(()))
+                })?;
+
+                match a.wire_form() {
+                    DictionaryWireForm::Object => serde_json::Value::Object(object),
+                    DictionaryWireForm::Pairs => serde_json::Value::Array(pairs),
+                }
+            }
+            TypeAttributesInstance::Boolean(_) => serde_json::Value::Bool(u.arbitrary()?),
+            TypeAttributesInstance::Int32(a) => u
+                .int_in_range(a.min().unwrap_or(i32::MIN)..=a.max().unwrap_or(i32::MAX))?
+                .into(),
+            TypeAttributesInstance::Int64(a) => u
+                .int_in_range(a.min().unwrap_or(i64::MIN)..=a.max().unwrap_or(i64::MAX))?
+                .into(),
+            TypeAttributesInstance::Uint32(a) => u
+                .int_in_range(a.min().unwrap_or(u32::MIN)..=a.max().unwrap_or(u32::MAX))?
+                .into(),
+            TypeAttributesInstance::Uint64(a) => u
+                .int_in_range(a.min().unwrap_or(u64::MIN)..=a.max().unwrap_or(u64::MAX))?
+                .into(),
+            TypeAttributesInstance::Float32(a) => {
+                let min = a.min().unwrap_or(f32::MIN);
+                let max = a.max().unwrap_or(f32::MAX);
+                let value = (arbitrary_float(u, min.into(), max.into())? as f32).clamp(min, max);
+
+                // Float32 values are expected in their shortest representation.
+                value
+                    .to_string()
+                    .parse::<f64>()
+                    .expect("a finite float32 is a valid float64")
+                    .into()
+            }
+            TypeAttributesInstance::Float64(a) => {
+                arbitrary_float(u, a.min().unwrap_or(f64::MIN), a.max().unwrap_or(f64::MAX))?.into()
+            }
+            TypeAttributesInstance::String(_) => serde_json::Value::String(u.arbitrary()?),
+            TypeAttributesInstance::Enum(a) => {
+                let names = a
+                    .values()
+                    .chain(a.aliases().map(|(alias, _)| alias))
+                    .collect::<Vec<_>>();
+
+                serde_json::Value::String(u.choose(&names)?.to_string())
+            }
+            #[cfg(feature = "uuid")]
+            TypeAttributesInstance::Uuid(_) => {
+                serde_json::Value::String(uuid::Uuid::from_u128(u.arbitrary()?).to_string())
+            }
+            #[cfg(feature = "bytes")]
+            TypeAttributesInstance::Bytes(a) => {
+                serde_json::Value::String(a.encoding().encode(u.arbitrary()?))
+            }
+        })
+    }
+}
+
+impl<Id, FieldName: Ord + Display> TypeDefinitionInstance<Id, FieldName> {
+    /// Get the JSON value of a dictionary key generated for this type, as it is once parsed.
+    fn canonical_key(&self, key: &serde_json::Value) -> serde_json::Value {
+        match (&self.attributes, key) {
+            (TypeAttributesInstance::String(a), serde_json::Value::String(key)) => {
+                serde_json::Value::String(a.normalize(key).into_owned())
+            }
+            (TypeAttributesInstance::Enum(a), serde_json::Value::String(key)) => a
+                .aliases()
+                .find(|(alias, _)| alias.to_string() == *key)
+                .map(|(_, value)| serde_json::Value::String(value.to_string()))
+                .unwrap_or_else(|| key.clone().into()),
+            _ => key.clone(),
+        }
+    }
+}
+
+/// Generate a finite float within a range.
+fn arbitrary_float(u: &mut Unstructured<'_>, min: f64, max: f64) -> arbitrary::Result<f64> {
+    let t = f64::from(u.arbitrary::<u32>()?) / f64::from(u32::MAX);
+
+    // Interpolating this way does not overflow, even for the widest ranges.
+    Ok((min * (1.0 - t) + max * t).clamp(min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arbitrary::Unstructured;
+
+    use crate::{
+        Comparison, EntryConstraint, TypeDefinition,
+        test_support::{Id, TypeAttributes, Value, registry, td},
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, EnumTypeAttributes,
+            NumberTypeAttributes, StringTypeAttributes,
+        },
+    };
+
+    /// Generate deterministic pseudo-random bytes.
+    fn bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed;
+
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_arbitrary_type_definition_round_trip() {
+        for seed in 0..200 {
+            let data = bytes(seed, 256);
+            let mut u = Unstructured::new(&data);
+
+            let td = u.arbitrary::<TypeDefinition<Id, String>>().unwrap();
+
+            let json = serde_json::to_value(&td).unwrap();
+            let back: TypeDefinition<Id, String> = serde_json::from_value(json).unwrap();
+            assert_eq!(back, td, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn test_arbitrary_json() {
+        let registry = registry([
+            td(
+                1,
+                "Level",
+                TypeAttributes::Int32(
+                    NumberTypeAttributes::builder()
+                        .min(1)
+                        .max(99)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                2,
+                "Speed",
+                TypeAttributes::Float32(
+                    NumberTypeAttributes::builder()
+                        .min(-1.5)
+                        .max(2.5)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                3,
+                "Color",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("Red")
+                        .with_value("Green")
+                        .with_alias("Vert", "Green")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                4,
+                "Name",
+                TypeAttributes::String(StringTypeAttributes::builder().build()),
+            ),
+            td(
+                5,
+                "Levels",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(3, 1)),
+            ),
+            td(
+                6,
+                "Speeds",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(1, 2)
+                        .with_wire_form(crate::DictionaryWireForm::Pairs),
+                ),
+            ),
+            td(
+                7,
+                "Names",
+                TypeAttributes::Array(ArrayTypeAttributes::new(4)),
+            ),
+            td(
+                8,
+                "Table",
+                TypeAttributes::Array(ArrayTypeAttributes::new(6)),
+            ),
+        ]);
+
+        let mut generated = 0;
+
+        for seed in 0..100 {
+            let data = bytes(seed, 512);
+            let mut u = Unstructured::new(&data);
+
+            for id in [5, 7, 8] {
+                let instance = &registry.by_id[&id];
+                let json = instance.arbitrary_json(&mut u).unwrap();
+
+                assert!(
+                    instance.validate(&json).is_valid(),
+                    "seed {seed}: {json} is not a valid `{}`",
+                    instance.name()
+                );
+
+                generated += 1;
+            }
+        }
+
+        assert_eq!(generated, 300);
+    }
+
+    #[test]
+    fn test_arbitrary_json_empty_enum() {
+        let empty = registry([td(
+            1,
+            "Empty",
+            TypeAttributes::Enum(EnumTypeAttributes::builder().build().unwrap()),
+        )]);
+
+        assert!(
+            empty.by_id[&1]
+                .arbitrary_json(&mut Unstructured::new(&[0; 16]))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_arbitrary_json_rules() {
+        let registry = registry([
+            td(
+                1,
+                "Stat",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("min_damage")
+                        .with_value("max_damage")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(2, "Damage", TypeAttributes::Uint32(Default::default())),
+            td(
+                3,
+                "Stats",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2).with_constraint(
+                    EntryConstraint::new("min_damage", Comparison::LessOrEqual, "max_damage"),
+                )),
+            ),
+            td(
+                4,
+                "Weapons",
+                TypeAttributes::Array(ArrayTypeAttributes::new(3)),
+            ),
+        ]);
+
+        let instance = &registry.by_id[&4];
+        let mut generated = 0;
+
+        for seed in 0..100 {
+            let data = bytes(seed, 512);
+
+            if let Ok(json) = instance.arbitrary_json(&mut Unstructured::new(&data)) {
+                assert!(
+                    Value::parse_for(Arc::clone(instance), json.clone()).is_ok(),
+                    "seed {seed}: {json} is not a valid `Weapons`"
+                );
+
+                generated += 1;
+            }
+        }
+
+        assert!(generated > 90, "only {generated} values were generated");
+    }
+}
@@ -0,0 +1,376 @@
+//! Rust types describing their own GameSON types.
+
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    hash::Hash,
+    sync::Arc,
+};
+
+use crate::{ParseError, TypeDefinitionInstance, UnidentifiedTypeDefinition, Value};
+
+/// A Rust type with a GameSON type.
+///
+/// With the `derive` feature, this trait can be derived for:
+///
+/// - Enums whose variants have no fields, which map to enum types. The `#[gameson(rename =
+///   "...")]`, `#[gameson(alias = "...")]` and `#[gameson(default)]` attributes of the variants
+///   rename them, add aliases to them and make them the default value, and deprecated variants
+///   are deprecated values.
+/// - Newtype structs wrapping a boolean, a number, a `String`, or a `Vec`, `BTreeMap` or
+///   `HashMap` of other types implementing this trait. The `#[gameson(min = ...)]`, `#[gameson(max
+///   = ...)]` and `#[gameson(default = ...)]` attributes of numbers set their range and default
+///   value.
+///
+/// The types are named after the Rust types, unless they have a `#[gameson(name = "...")]`
+/// attribute, and their doc comments become their descriptions.
+///
+/// ```ignore
+/// #[derive(GameSonType)]
+/// enum Color {
+///     Red,
+///     #[gameson(alias = "Vert")]
+///     Green,
+/// }
+///
+/// #[derive(GameSonType)]
+/// struct Palette(Vec<Color>);
+///
+/// registry.register_unidentified(Palette::type_definitions(), &mut allocator);
+/// ```
+pub trait GameSonType: Sized {
+    /// Get the name of the GameSON type.
+    fn type_name() -> String;
+
+    /// Get the definition of the GameSON type, which references other types by their names.
+    fn type_definition() -> UnidentifiedTypeDefinition<String>;
+
+    /// Collect the definition of the GameSON type and of all the types it references, by their
+    /// names, unless it was already collected.
+    fn collect_type_definitions(
+        type_definitions: &mut BTreeMap<String, UnidentifiedTypeDefinition<String>>,
+    ) {
+        type_definitions
+            .entry(Self::type_name())
+            .or_insert_with(Self::type_definition);
+    }
+
+    /// Get the definitions of the GameSON type and of all the types it references, directly or
+    /// transitively, sorted by name.
+    ///
+    /// They can be registered with
+    /// [`register_unidentified`](crate::TypeDefinitionRegistry::register_unidentified).
+    fn type_definitions() -> Vec<UnidentifiedTypeDefinition<String>> {
+        let mut type_definitions = BTreeMap::new();
+        Self::collect_type_definitions(&mut type_definitions);
+
+        type_definitions.into_values().collect()
+    }
+
+    /// Convert the Rust value to its JSON representation.
+    fn to_json(&self) -> serde_json::Value;
+
+    /// Convert a JSON representation to a Rust value.
+    ///
+    /// Returns `None` if the JSON value does not represent a Rust value.
+    fn from_json(json: &serde_json::Value) -> Option<Self>;
+
+    /// Convert the Rust value to a GameSON value of the specified type instance.
+    fn to_value<Id: Display, FieldName: Ord + Display + Clone + Borrow<str>>(
+        &self,
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+    ) -> Result<Value<Id, FieldName>, ParseError<Id, FieldName>> {
+        Value::parse_for(instance, self.to_json())
+    }
+
+    /// Convert a GameSON value to a Rust value.
+    ///
+    /// Returns `None` if the GameSON value does not represent a Rust value.
+    fn from_value<Id, FieldName: Ord + Borrow<str>>(value: &Value<Id, FieldName>) -> Option<Self> {
+        Self::from_json(&value.to_json().ok()?)
+    }
+}
+
+/// The Rust types that derived newtype structs can wrap.
+///
+/// This is an implementation detail of the derive macro.
+#[doc(hidden)]
+pub trait Inner: Sized {
+    /// Get the type and the attributes of the wrapping type, given the attributes set on it.
+    fn type_and_attributes(
+        attributes: serde_json::Map<String, serde_json::Value>,
+    ) -> (&'static str, serde_json::Map<String, serde_json::Value>);
+
+    /// Collect the definitions of the types referenced by the wrapping type.
+    fn collect_type_definitions(
+        _type_definitions: &mut BTreeMap<String, UnidentifiedTypeDefinition<String>>,
+    ) {
+    }
+
+    /// Convert the Rust value to its JSON representation.
+    fn to_json(&self) -> serde_json::Value;
+
+    /// Convert a JSON representation to a Rust value.
+    fn from_json(json: &serde_json::Value) -> Option<Self>;
+}
+
+/// Implement [`Inner`] for scalar types.
+macro_rules! impl_inner_for_scalar {
+    ($($ty:ty => $type_name:literal, $from_json:expr;)*) => {
+        $(
+            impl Inner for $ty {
+                fn type_and_attributes(
+                    attributes: serde_json::Map<String, serde_json::Value>,
+                ) -> (&'static str, serde_json::Map<String, serde_json::Value>) {
+                    ($type_name, attributes)
+                }
+
+                fn to_json(&self) -> serde_json::Value {
+                    serde_json::Value::from(self.clone())
+                }
+
+                fn from_json(json: &serde_json::Value) -> Option<Self> {
+                    $from_json(json)
+                }
+            }
+        )*
+    };
+}
+
+impl_inner_for_scalar! {
+    bool => "boolean", serde_json::Value::as_bool;
+    i32 => "int32", |json: &serde_json::Value| json.as_i64()?.try_into().ok();
+    i64 => "int64", serde_json::Value::as_i64;
+    u32 => "uint32", |json: &serde_json::Value| json.as_u64()?.try_into().ok();
+    u64 => "uint64", serde_json::Value::as_u64;
+    f32 => "float32", |json: &serde_json::Value| json.as_f64().map(|f| f as f32);
+    f64 => "float64", serde_json::Value::as_f64;
+    String => "string", |json: &serde_json::Value| json.as_str().map(str::to_owned);
+}
+
+impl<T: GameSonType> Inner for Vec<T> {
+    fn type_and_attributes(
+        _attributes: serde_json::Map<String, serde_json::Value>,
+    ) -> (&'static str, serde_json::Map<String, serde_json::Value>) {
+        let mut attributes = serde_json::Map::new();
+        attributes.insert("items_type_id".to_owned(), T::type_name().into());
+
+        ("array", attributes)
+    }
+
+    fn collect_type_definitions(
+        type_definitions: &mut BTreeMap<String, UnidentifiedTypeDefinition<String>>,
+    ) {
+        T::collect_type_definitions(type_definitions);
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.iter().map(T::to_json).collect()
+    }
+
+    fn from_json(json: &serde_json::Value) -> Option<Self> {
+        json.as_array()?.iter().map(T::from_json).collect()
+    }
+}
+
+/// Get the type and the attributes of dictionaries.
+fn dictionary_type_and_attributes<K: GameSonType, V: GameSonType>()
+-> (&'static str, serde_json::Map<String, serde_json::Value>) {
+    let mut attributes = serde_json::Map::new();
+    attributes.insert("keys_type_id".to_owned(), K::type_name().into());
+    attributes.insert("values_type_id".to_owned(), V::type_name().into());
+
+    ("dictionary", attributes)
+}
+
+/// Convert dictionary entries to a JSON object.
+fn dictionary_to_json<'a, K: GameSonType + 'a, V: GameSonType + 'a>(
+    entries: impl IntoIterator<Item = (&'a K, &'a V)>,
+) -> serde_json::Value {
+    entries
+        .into_iter()
+        .map(|(key, value)| {
+            let key = match key.to_json() {
+                serde_json::Value::String(key) => key,
+                key => key.to_string(),
+            };
+
+            (key, value.to_json())
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// Convert a JSON object to dictionary entries.
+fn dictionary_from_json<K: GameSonType, V: GameSonType, M: FromIterator<(K, V)>>(
+    json: &serde_json::Value,
+) -> Option<M> {
+    json.as_object()?
+        .iter()
+        .map(|(key, value)| {
+            Some((
+                K::from_json(&serde_json::Value::String(key.clone()))?,
+                V::from_json(value)?,
+            ))
+        })
+        .collect()
+}
+
+impl<K: GameSonType + Ord, V: GameSonType> Inner for BTreeMap<K, V> {
+    fn type_and_attributes(
+        _attributes: serde_json::Map<String, serde_json::Value>,
+    ) -> (&'static str, serde_json::Map<String, serde_json::Value>) {
+        dictionary_type_and_attributes::<K, V>()
+    }
+
+    fn collect_type_definitions(
+        type_definitions: &mut BTreeMap<String, UnidentifiedTypeDefinition<String>>,
+    ) {
+        K::collect_type_definitions(type_definitions);
+        V::collect_type_definitions(type_definitions);
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        dictionary_to_json(self)
+    }
+
+    fn from_json(json: &serde_json::Value) -> Option<Self> {
+        dictionary_from_json(json)
+    }
+}
+
+impl<K: GameSonType + Eq + Hash, V: GameSonType> Inner for HashMap<K, V> {
+    fn type_and_attributes(
+        _attributes: serde_json::Map<String, serde_json::Value>,
+    ) -> (&'static str, serde_json::Map<String, serde_json::Value>) {
+        dictionary_type_and_attributes::<K, V>()
+    }
+
+    fn collect_type_definitions(
+        type_definitions: &mut BTreeMap<String, UnidentifiedTypeDefinition<String>>,
+    ) {
+        K::collect_type_definitions(type_definitions);
+        V::collect_type_definitions(type_definitions);
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        dictionary_to_json(self)
+    }
+
+    fn from_json(json: &serde_json::Value) -> Option<Self> {
+        dictionary_from_json(json)
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use crate::test_support::owned::TypeDefinitionRegistry;
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use crate::{GameSonType, SequentialIdAllocator};
+
+    /// The colors of the teams.
+    #[derive(Debug, PartialEq, GameSonType)]
+    #[gameson(name = "TeamColor")]
+    enum Color {
+        #[gameson(default, alias = "rouge")]
+        Red,
+
+        /// A dark green.
+        #[gameson(rename = "green")]
+        Green,
+
+        #[deprecated]
+        Blue,
+    }
+
+    #[derive(Debug, PartialEq, GameSonType)]
+    #[gameson(min = 0, max = 10)]
+    struct Score(i32);
+
+    #[derive(Debug, PartialEq, GameSonType)]
+    struct Scores(BTreeMap<Color, Score>);
+
+    #[derive(Debug, PartialEq, GameSonType)]
+    struct Palette(Vec<Color>);
+
+    impl PartialOrd for Color {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Color {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.to_json().as_str().cmp(&other.to_json().as_str())
+        }
+    }
+
+    impl Eq for Color {}
+
+    #[test]
+    fn test_derive() {
+        assert_eq!(
+            serde_json::to_value(Scores::type_definitions()).unwrap(),
+            json!([
+                {
+                    "name": "Score",
+                    "type": "int32",
+                    "attributes": { "min": 0, "max": 10 },
+                },
+                {
+                    "name": "Scores",
+                    "type": "dictionary",
+                    "attributes": { "keys_type_id": "TeamColor", "values_type_id": "Score" },
+                },
+                {
+                    "name": "TeamColor",
+                    "description": "The colors of the teams.",
+                    "type": "enum",
+                    "attributes": {
+                        "values": {
+                            "Blue": { "deprecated": true },
+                            "Red": {},
+                            "green": { "description": "A dark green." },
+                        },
+                        "aliases": { "rouge": "Red" },
+                        "default": "Red",
+                    },
+                },
+            ])
+        );
+
+        let mut registry = TypeDefinitionRegistry::default();
+        let mut allocator = SequentialIdAllocator::new(1);
+        let mut type_definitions = BTreeMap::new();
+        Scores::collect_type_definitions(&mut type_definitions);
+        Palette::collect_type_definitions(&mut type_definitions);
+        registry
+            .register_unidentified(type_definitions.into_values(), &mut allocator)
+            .into_result()
+            .unwrap();
+
+        let scores = Scores(BTreeMap::from([
+            (Color::Red, Score(3)),
+            (Color::Green, Score(7)),
+        ]));
+        let value = scores.to_value(registry.by_name["Scores"].clone()).unwrap();
+        assert_eq!(value.to_json().unwrap(), json!({ "Red": 3, "green": 7 }));
+        assert_eq!(Scores::from_value(&value), Some(scores));
+
+        assert!(
+            Scores(BTreeMap::from([(Color::Red, Score(11))]))
+                .to_value(registry.by_name["Scores"].clone())
+                .is_err()
+        );
+
+        assert_eq!(
+            Palette::from_json(&json!(["rouge", "green"])),
+            Some(Palette(vec![Color::Red, Color::Green]))
+        );
+        assert_eq!(Palette::from_json(&json!(["purple"])), None);
+    }
+}
@@ -0,0 +1,28 @@
+//! The hasher of the internal hash-based structures.
+//!
+//! The standard library hasher resists hash flooding, at the cost of speed. The `ahash` and
+//! `fxhash` features replace it with faster hashers: `ahash` stays randomly seeded, while `fxhash`,
+//! backed by `rustc-hash`, is deterministic and thus exposed to hash flooding. If both features are
+//! enabled, `ahash` is used.
+//!
+//! The `registry` benchmark compares the hashers on registry lookups and registrations, and
+//! records the measured times.
+
+/// The builder of the hashers of the internal hash-based structures.
+#[cfg(feature = "ahash")]
+pub(crate) type BuildHasher = ahash::RandomState;
+
+/// The builder of the hashers of the internal hash-based structures.
+#[cfg(all(feature = "fxhash", not(feature = "ahash")))]
+pub(crate) type BuildHasher = rustc_hash::FxBuildHasher;
+
+/// The builder of the hashers of the internal hash-based structures.
+#[cfg(not(any(feature = "ahash", feature = "fxhash")))]
+pub(crate) type BuildHasher = std::hash::RandomState;
+
+/// A hash map using the hasher of the internal hash-based structures.
+#[cfg(any(feature = "hash-map", feature = "sqlx-postgres"))]
+pub(crate) type HashMap<K, V> = std::collections::HashMap<K, V, BuildHasher>;
+
+/// A hash set using the hasher of the internal hash-based structures.
+pub(crate) type HashSet<T> = std::collections::HashSet<T, BuildHasher>;
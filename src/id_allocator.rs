@@ -0,0 +1,274 @@
+//! Allocation of type identifiers.
+
+use std::{collections::BTreeMap, fmt::Display, hash::Hash, ops::Add};
+
+use serde::Serialize;
+
+use crate::{
+    Fingerprint, RegistrationError, RegistrationOutcome, RegistryEvent, TypeDefinition,
+    TypeDefinitionRegistry, UnidentifiedTypeDefinition,
+};
+
+/// An allocator of identifiers for type definitions authored without them.
+///
+/// See [`register_unidentified`](TypeDefinitionRegistry::register_unidentified).
+pub trait IdAllocator<Id, FieldName: Ord + Display + Clone> {
+    /// Allocate an identifier for a type definition.
+    fn allocate(&mut self, type_definition: &UnidentifiedTypeDefinition<FieldName>) -> Id;
+}
+
+/// An allocator of consecutive integer identifiers.
+#[derive(Debug, Clone)]
+pub struct SequentialIdAllocator<Id> {
+    /// The next identifier to allocate.
+    next: Id,
+}
+
+impl<Id> SequentialIdAllocator<Id> {
+    /// Create an allocator whose first identifier is `first`.
+    ///
+    /// The allocator does not know about the identifiers that are already used: `first` must be
+    /// greater than all of them.
+    pub fn new(first: Id) -> Self {
+        Self { next: first }
+    }
+}
+
+impl<Id, FieldName> IdAllocator<Id, FieldName> for SequentialIdAllocator<Id>
+where
+    Id: Copy + From<u8> + Add<Output = Id>,
+    FieldName: Ord + Display + Clone,
+{
+    fn allocate(&mut self, _type_definition: &UnidentifiedTypeDefinition<FieldName>) -> Id {
+        let id = self.next;
+        self.next = id + Id::from(1);
+
+        id
+    }
+}
+
+/// An allocator of random UUID identifiers.
+#[cfg(feature = "uuid")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidIdAllocator;
+
+#[cfg(feature = "uuid")]
+impl<FieldName: Ord + Display + Clone> IdAllocator<uuid::Uuid, FieldName> for UuidIdAllocator {
+    fn allocate(&mut self, _type_definition: &UnidentifiedTypeDefinition<FieldName>) -> uuid::Uuid {
+        uuid::Uuid::new_v4()
+    }
+}
+
+/// An allocator of identifiers derived from a stable hash of the names of the types.
+///
+/// The identifiers of the types do not change as long as their names do not, which allows
+/// independent machines to allocate the same identifiers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HashIdAllocator;
+
+impl<Id, FieldName> IdAllocator<Id, FieldName> for HashIdAllocator
+where
+    Id: From<Fingerprint>,
+    FieldName: Ord + Display + Clone + Serialize,
+{
+    fn allocate(&mut self, type_definition: &UnidentifiedTypeDefinition<FieldName>) -> Id {
+        Fingerprint::of_serialized(&type_definition.name).into()
+    }
+}
+
+impl<Id: Ord + Hash + Clone + Display, FieldName: Ord + Hash + Clone + Display>
+    TypeDefinitionRegistry<Id, FieldName>
+{
+    /// Register type definitions authored without identifiers.
+    ///
+    /// The type definitions that were [declared](Self::declare) get their declared identifiers,
+    /// and the other ones are assigned identifiers by the allocator. Their references to other
+    /// types, either registered, declared, or in the same batch, are then resolved by name.
+    ///
+    /// Type definitions referencing unknown names are not registered: in the outcome, those
+    /// references are replaced by their own identifiers. See [`register`](Self::register) for
+    /// details.
+    pub fn register_unidentified(
+        &mut self,
+        type_definitions: impl IntoIterator<Item = UnidentifiedTypeDefinition<FieldName>>,
+        allocator: &mut impl IdAllocator<Id, FieldName>,
+    ) -> RegistrationOutcome<Id, FieldName> {
+        let type_definitions: Vec<_> = type_definitions
+            .into_iter()
+            .map(|td| {
+                let id = match self.declaration_of(&td.name) {
+                    Some(id) => id.clone(),
+                    None => allocator.allocate(&td),
+                };
+
+                (id, td)
+            })
+            .collect();
+
+        // The identifiers of the types of the batch, by their names.
+        let mut ids = BTreeMap::new();
+
+        for (id, td) in &type_definitions {
+            ids.entry(td.name.clone()).or_insert_with(|| id.clone());
+        }
+
+        let resolve = |name: &FieldName| {
+            ids.get(name)
+                .or_else(|| self.by_name.get(name).map(|instance| &instance.id))
+                .or_else(|| self.declaration_of(name))
+                .cloned()
+        };
+
+        let mut identified = Vec::with_capacity(type_definitions.len());
+        let mut failed = Vec::new();
+
+        for (id, td) in type_definitions {
+            let unknown = td
+                .attributes
+                .external_identifier_references()
+                .into_iter()
+                .find(|name| resolve(name).is_none())
+                .cloned();
+
+            let td = TypeDefinition {
+                attributes: td
+                    .attributes
+                    .map_ids(|name| resolve(&name).unwrap_or_else(|| id.clone())),
+                id,
+                name: td.name,
+                description: td.description,
+            };
+
+            match unknown {
+                Some(referenced_name) => {
+                    failed.push((td, RegistrationError::UnknownTypeName { referenced_name }));
+                }
+                None => identified.push(td),
+            }
+        }
+
+        self.subscribers.notify(|| {
+            failed
+                .iter()
+                .map(|(td, err)| RegistryEvent::Failed(td.clone(), err.clone()))
+                .collect::<Vec<_>>()
+        });
+
+        let mut outcome = self.register(identified);
+        outcome.failed.extend(failed);
+        outcome
+            .failed
+            .sort_by(|(a, _), (b, _)| (&a.id, &a.name).cmp(&(&b.id, &b.name)));
+
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        RegistrationError,
+        test_support::{FieldName, TypeDefinitionRegistry},
+        type_attributes::ArrayTypeAttributes,
+    };
+
+    use super::{HashIdAllocator, SequentialIdAllocator};
+
+    type UnidentifiedTypeDefinition = crate::UnidentifiedTypeDefinition<FieldName>;
+    type TypeAttributes = crate::TypeAttributes<FieldName, FieldName>;
+
+    fn td(name: FieldName, attributes: TypeAttributes) -> UnidentifiedTypeDefinition {
+        UnidentifiedTypeDefinition {
+            name,
+            description: None,
+            attributes,
+        }
+    }
+
+    #[test]
+    fn test_register_unidentified() {
+        let mut registry = TypeDefinitionRegistry::default();
+        let mut allocator = SequentialIdAllocator::new(10);
+
+        registry.declare(1, "MyString").unwrap();
+
+        let (registered, failed) = registry
+            .register_unidentified(
+                [
+                    td(
+                        "MyIntArray",
+                        TypeAttributes::Array(ArrayTypeAttributes::new("MyInt")),
+                    ),
+                    td("MyInt", TypeAttributes::Int32(Default::default())),
+                    td(
+                        "MyBrokenArray",
+                        TypeAttributes::Array(ArrayTypeAttributes::new("MyUnknown")),
+                    ),
+                    td("MyString", TypeAttributes::String(Default::default())),
+                ],
+                &mut allocator,
+            )
+            .into_parts();
+
+        assert_eq!(
+            registered
+                .iter()
+                .map(|td| td.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "MyString(1): string()",
+                "MyInt(11): int32(..)",
+                "MyIntArray(10): array(MyInt(11): int32(..))",
+            ]
+        );
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0.id, 12);
+        assert_eq!(
+            failed[0].1,
+            RegistrationError::UnknownTypeName {
+                referenced_name: "MyUnknown"
+            }
+        );
+
+        // Registered types are referenced by name too.
+        let outcome = registry.register_unidentified(
+            [td(
+                "MyStringArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new("MyString")),
+            )],
+            &mut allocator,
+        );
+        assert_eq!(
+            outcome.registered()[0].to_string(),
+            "MyStringArray(13): array(MyString(1): string())"
+        );
+    }
+
+    #[test]
+    fn test_hash_id_allocator() {
+        let mut registry = crate::TypeDefinitionRegistry::<u64, FieldName>::default();
+        let mut other = registry.clone();
+
+        let types = || {
+            [
+                td(
+                    "MyIntArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new("MyInt")),
+                ),
+                td("MyInt", TypeAttributes::Int32(Default::default())),
+            ]
+        };
+
+        assert!(
+            registry
+                .register_unidentified(types(), &mut HashIdAllocator)
+                .is_success()
+        );
+        assert!(
+            other
+                .register_unidentified(types().into_iter().rev(), &mut HashIdAllocator)
+                .is_success()
+        );
+        assert_eq!(registry.to_definitions(), other.to_definitions());
+    }
+}
@@ -0,0 +1,196 @@
+//! A bounded cache of the type instances of a store.
+
+use std::{collections::VecDeque, fmt::Display, hash::Hash, sync::Arc};
+
+use crate::{
+    RegistrationError, TypeDefinitionInstance, TypeDefinitionRegistry, TypeDefinitionStore,
+};
+
+/// An error that can occur when getting a type instance from a [`TypeInstanceCache`].
+#[derive(Debug, thiserror::Error)]
+pub enum TypeInstanceCacheError<E, Id, FieldName> {
+    /// The store could not be accessed.
+    #[error("cannot load type definition: {0}")]
+    Store(#[source] E),
+
+    /// A type definition of the store could not be registered.
+    #[error("cannot register type definition `{id}`: {err}")]
+    Registration {
+        /// The identifier of the type definition.
+        id: Id,
+
+        /// The reason why the type definition could not be registered.
+        err: RegistrationError<Id, FieldName>,
+    },
+}
+
+/// A registry hydrated on demand from a store, keeping only the most recently used type
+/// instances resident.
+///
+/// Each requested type definition is registered along with the type definitions it references.
+/// Once more than `capacity` type definitions were requested, the least recently requested ones
+/// are evicted, along with the type definitions only they reference, and are loaded from the store
+/// again on their next request. Evicted type instances stay alive as long as they are used
+/// elsewhere, by values for instance.
+#[derive(Debug)]
+pub struct TypeInstanceCache<S, Id, FieldName: Ord + Display + Clone> {
+    /// The store the type definitions are loaded from.
+    store: S,
+
+    /// The registry of the resident type instances.
+    registry: TypeDefinitionRegistry<Id, FieldName>,
+
+    /// The maximum number of requested type definitions kept resident.
+    capacity: usize,
+
+    /// The identifiers of the requested type definitions, least recently requested first.
+    recent: VecDeque<Id>,
+}
+
+impl<S, Id, FieldName> TypeInstanceCache<S, Id, FieldName>
+where
+    S: TypeDefinitionStore<Id, FieldName>,
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display,
+{
+    /// Create a cache of the type instances of a store, keeping at most `capacity` requested type
+    /// definitions resident, and at least one.
+    pub fn new(store: S, capacity: usize) -> Self {
+        Self {
+            store,
+            registry: TypeDefinitionRegistry::default(),
+            capacity: capacity.max(1),
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Get the maximum number of requested type definitions kept resident.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the store the type definitions are loaded from.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Get the registry of the resident type instances.
+    pub fn registry(&self) -> &TypeDefinitionRegistry<Id, FieldName> {
+        &self.registry
+    }
+
+    /// Get the type instance of a type definition, loading it from the store if it is not
+    /// resident.
+    ///
+    /// Returns `None` if the store has no type definition with the specified identifier.
+    #[expect(
+        clippy::type_complexity,
+        reason = "inherent associated types are not yet stable so we can't do much about it here"
+    )]
+    pub fn get(
+        &mut self,
+        id: &Id,
+    ) -> Result<
+        Option<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+        TypeInstanceCacheError<S::Error, Id, FieldName>,
+    > {
+        if let Some(position) = self.recent.iter().position(|recent| recent == id) {
+            self.recent.remove(position);
+        }
+
+        let instance = match self.registry.get(id) {
+            Some(instance) => Arc::clone(instance),
+            None => {
+                let outcome = self
+                    .registry
+                    .load_from_store_by_id(&self.store, id)
+                    .map_err(TypeInstanceCacheError::Store)?;
+
+                // The registry must not keep evicted type instances alive in its history.
+                self.registry.clear_history();
+
+                if let Some((td, err)) = outcome.failed().first() {
+                    return Err(TypeInstanceCacheError::Registration {
+                        id: td.id.clone(),
+                        err: err.clone(),
+                    });
+                }
+
+                match self.registry.get(id) {
+                    Some(instance) => Arc::clone(instance),
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        self.recent.push_back(id.clone());
+
+        if self.recent.len() > self.capacity {
+            self.recent.drain(..self.recent.len() - self.capacity);
+            self.registry.prune(&self.recent);
+            self.registry.clear_history();
+        }
+
+        Ok(Some(instance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{FieldName, Id, TypeAttributes, TypeDefinition, td};
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use crate::{TypeDefinitionStore, type_attributes::ArrayTypeAttributes};
+
+    type TypeInstanceCache = super::TypeInstanceCache<BTreeMap<Id, TypeDefinition>, Id, FieldName>;
+
+    #[test]
+    fn test_type_instance_cache() {
+        let mut store = BTreeMap::new();
+
+        for td in [
+            td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+            td(
+                2,
+                "MyIntArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(3, "MyString", TypeAttributes::String(Default::default())),
+            td(
+                4,
+                "MyBrokenArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(5)),
+            ),
+        ] {
+            store.save(&td).unwrap();
+        }
+
+        let mut cache = TypeInstanceCache::new(store, 2);
+
+        let array = cache.get(&2).unwrap().unwrap();
+        assert!(cache.registry().get(&1).is_some());
+
+        // `MyInt` evicts `MyString`, the least recently used.
+        cache.get(&3).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&cache.get(&2).unwrap().unwrap(), &array));
+        cache.get(&1).unwrap().unwrap();
+        assert!(cache.registry().get(&3).is_none());
+        assert!(cache.registry().get(&2).is_some());
+
+        // `MyString` evicts `MyIntArray`, but not `MyInt`, which was requested since.
+        cache.get(&3).unwrap().unwrap();
+        assert!(cache.registry().get(&2).is_none());
+        assert!(cache.registry().get(&1).is_some());
+
+        // Reloaded type instances are new ones.
+        assert!(!Arc::ptr_eq(&cache.get(&2).unwrap().unwrap(), &array));
+
+        assert!(cache.get(&6).unwrap().is_none());
+
+        let err = cache.get(&4).unwrap_err();
+        assert!(matches!(
+            err,
+            super::TypeInstanceCacheError::Registration { id: 4, .. }
+        ));
+    }
+}
@@ -0,0 +1,202 @@
+//! Interned names.
+
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::hasher::HashSet;
+
+/// The names interned so far.
+static INTERNER: LazyLock<Mutex<HashSet<Arc<str>>>> = LazyLock::new(Default::default);
+
+/// An interned name, usable as the `FieldName` parameter of registries, type definitions and
+/// values.
+///
+/// All the interned names with the same text share a single allocation, so registries with many
+/// types and enum values do not duplicate identical names, and cloning a name is cheap. Names are
+/// interned when they are created or deserialized, and remain interned for the lifetime of the
+/// process.
+#[derive(Debug, Clone)]
+pub struct InternedName(Arc<str>);
+
+impl InternedName {
+    /// Intern a name.
+    pub fn new(name: &str) -> Self {
+        let mut interner = INTERNER.lock().expect("the lock is not poisoned");
+
+        match interner.get(name) {
+            Some(name) => Self(Arc::clone(name)),
+            None => {
+                let name: Arc<str> = Arc::from(name);
+                interner.insert(Arc::clone(&name));
+
+                Self(name)
+            }
+        }
+    }
+
+    /// Get the text of the name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Get the number of distinct names interned so far.
+    pub fn interned_count() -> usize {
+        INTERNER.lock().expect("the lock is not poisoned").len()
+    }
+}
+
+impl PartialEq for InternedName {
+    fn eq(&self, other: &Self) -> bool {
+        // Interned names with the same text are the same allocation.
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for InternedName {}
+
+impl PartialOrd for InternedName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for InternedName {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for InternedName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Must hash like the `str` it borrows as.
+        self.as_str().hash(state);
+    }
+}
+
+impl Deref for InternedName {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<str> for InternedName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for InternedName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for InternedName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for InternedName {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for InternedName {
+    fn from(name: String) -> Self {
+        Self::new(&name)
+    }
+}
+
+impl Serialize for InternedName {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for InternedName {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = InternedName;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a name")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(InternedName::new(v))
+            }
+        }
+
+        // Names are interned from borrowed text whenever possible, to avoid an allocation.
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for InternedName {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::InternedName;
+
+    type TypeDefinitionRegistry = crate::TypeDefinitionRegistry<u32, InternedName>;
+    type Value = crate::Value<u32, InternedName>;
+
+    #[test]
+    fn test_interned_name() {
+        let a = InternedName::new("Weapon");
+        let b: InternedName = serde_json::from_value(json!("Weapon")).unwrap();
+
+        assert!(Arc::ptr_eq(&a.0, &b.0));
+        assert_eq!(a, b);
+        assert_ne!(a, InternedName::from("Armor"));
+        assert!(a > InternedName::from("Armor"));
+        assert_eq!(serde_json::to_value(&b).unwrap(), "Weapon");
+
+        let registry: TypeDefinitionRegistry = serde_json::from_value(json!({
+            "version": 1,
+            "type_definitions": [
+                {
+                    "id": 1,
+                    "name": "Slot",
+                    "type": "enum",
+                    "attributes": { "values": { "Weapon": {}, "Armor": {} } },
+                },
+                {
+                    "id": 2,
+                    "name": "Slots",
+                    "type": "array",
+                    "attributes": { "items_type_id": 1 },
+                },
+            ],
+        }))
+        .unwrap();
+
+        let slots = registry.get_by_name("Slots").unwrap().clone();
+        let value = Value::parse_for(slots, json!(["Weapon", "Armor"])).unwrap();
+
+        assert_eq!(value.to_json().unwrap(), json!(["Weapon", "Armor"]));
+    }
+}
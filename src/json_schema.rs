@@ -0,0 +1,672 @@
+//! Conversions between GameSON types and JSON schemas, for interoperability with `schemars`.
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use schemars::Schema;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+#[cfg(feature = "uuid")]
+use crate::type_attributes::UuidTypeAttributes;
+#[cfg(feature = "bytes")]
+use crate::{BytesEncoding, type_attributes::BytesTypeAttributes};
+use crate::{
+    DictionaryWireForm, TypeAttributes, TypeDefinitionInstance, UnidentifiedTypeDefinition,
+    type_attributes::{
+        ArrayTypeAttributes, BooleanTypeAttributes, DictionaryTypeAttributes, EnumTypeAttributes,
+        NumberTypeAttributes, StringTypeAttributes,
+    },
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+/// The JSON Schema dialect of the generated schemas.
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+impl<Id, FieldName: Ord + Display> TypeDefinitionInstance<Id, FieldName> {
+    /// Generate a JSON schema validating the JSON representation of the values of the type.
+    ///
+    /// The type is described at the root of the schema, titled after its name, and the types it
+    /// references, directly or transitively, are described in its `$defs`, by name.
+    ///
+    /// Enum aliases are listed along with the enum values, as they are valid representations.
+    /// Dictionaries in the pairs wire form are arrays of two-item arrays.
+    pub fn to_json_schema(&self) -> Schema {
+        let mut definitions = BTreeMap::new();
+        let mut schema = Map::new();
+
+        schema.insert("$schema".to_owned(), json!(DIALECT));
+        schema.insert("title".to_owned(), json!(self.name.to_string()));
+        schema.extend(schema_of(self, &mut definitions));
+
+        definitions.remove(&self.name.to_string());
+
+        if !definitions.is_empty() {
+            schema.insert(
+                "$defs".to_owned(),
+                Value::Object(
+                    definitions
+                        .into_iter()
+                        .map(|(name, definition)| (name, Value::Object(definition)))
+                        .collect(),
+                ),
+            );
+        }
+
+        schema.into()
+    }
+}
+
+/// Get a reference to a type instance, collecting its schema in the definitions if needed.
+fn reference_to<Id, FieldName: Ord + Display>(
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+    definitions: &mut BTreeMap<String, Map<String, Value>>,
+) -> Value {
+    let name = instance.name.to_string();
+
+    if !definitions.contains_key(&name) {
+        let schema = schema_of(instance, definitions);
+        definitions.insert(name.clone(), schema);
+    }
+
+    json!({ "$ref": format!("#/$defs/{name}") })
+}
+
+/// Get the schema of a type instance, collecting the schemas of the types it references in the
+/// definitions.
+fn schema_of<Id, FieldName: Ord + Display>(
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+    definitions: &mut BTreeMap<String, Map<String, Value>>,
+) -> Map<String, Value> {
+    let mut schema = match &instance.attributes {
+        TypeAttributesInstance::Array(a) => json!({
+            "type": "array",
+            "items": reference_to(a.items_type_id(), definitions),
+        }),
+        TypeAttributesInstance::Dictionary(a) => {
+            let keys = reference_to(a.keys_type_id(), definitions);
+            let values = reference_to(a.values_type_id(), definitions);
+
+            match a.wire_form() {
+                DictionaryWireForm::Object => match &a.keys_type_id().attributes {
+                    TypeAttributesInstance::String(_) | TypeAttributesInstance::Enum(_) => json!({
+                        "type": "object",
+                        "propertyNames": keys,
+                        "additionalProperties": values,
+                    }),
+                    _ => json!({
+                        "type": "object",
+                        "additionalProperties": values,
+                    }),
+                },
+                DictionaryWireForm::Pairs => json!({
+                    "type": "array",
+                    "items": {
+                        "type": "array",
+                        "prefixItems": [keys, values],
+                        "minItems": 2,
+                        "maxItems": 2,
+                    },
+                }),
+            }
+        }
+        TypeAttributesInstance::Boolean(a) => {
+            with_default(json!({ "type": "boolean" }), a.default_value())
+        }
+        TypeAttributesInstance::Int32(a) => number_schema("integer", "int32", a),
+        TypeAttributesInstance::Int64(a) => number_schema("integer", "int64", a),
+        TypeAttributesInstance::Uint32(a) => number_schema("integer", "uint32", a),
+        TypeAttributesInstance::Uint64(a) => number_schema("integer", "uint64", a),
+        TypeAttributesInstance::Float32(a) => number_schema("number", "float", a),
+        TypeAttributesInstance::Float64(a) => number_schema("number", "double", a),
+        TypeAttributesInstance::String(a) => {
+            with_default(json!({ "type": "string" }), a.default_value())
+        }
+        TypeAttributesInstance::Enum(a) => {
+            let mut names: Vec<_> = a
+                .values()
+                .chain(a.aliases().map(|(alias, _)| alias))
+                .collect();
+            names.sort();
+
+            let schema = if a
+                .values()
+                .any(|value| a.is_deprecated(value) || a.value_description(value).is_some())
+            {
+                json!({
+                    "oneOf": names
+                        .into_iter()
+                        .map(|name| {
+                            let mut schema = Map::new();
+                            schema.insert("const".to_owned(), json!(name.to_string()));
+
+                            if let Some(description) = a.value_description(name) {
+                                schema.insert("description".to_owned(), json!(description));
+                            }
+
+                            if a.is_deprecated(name) {
+                                schema.insert("deprecated".to_owned(), json!(true));
+                            }
+
+                            Value::Object(schema)
+                        })
+                        .collect::<Vec<_>>(),
+                })
+            } else {
+                json!({
+                    "type": "string",
+                    "enum": names.into_iter().map(ToString::to_string).collect::<Vec<_>>(),
+                })
+            };
+
+            with_default(schema, a.default_value().map(ToString::to_string))
+        }
+        #[cfg(feature = "uuid")]
+        TypeAttributesInstance::Uuid(a) => with_default(
+            json!({ "type": "string", "format": "uuid" }),
+            a.default_value(),
+        ),
+        #[cfg(feature = "bytes")]
+        TypeAttributesInstance::Bytes(a) => json!({
+            "type": "string",
+            "contentEncoding": match a.encoding() {
+                BytesEncoding::Base64 => "base64",
+                BytesEncoding::Base64Url => "base64url",
+                BytesEncoding::Hex => "base16",
+            },
+        }),
+    };
+
+    if let Some(description) = &instance.description {
+        schema["description"] = json!(description);
+    }
+
+    match schema {
+        Value::Object(schema) => schema,
+        _ => unreachable!("schemas are always objects"),
+    }
+}
+
+/// Get the schema of a number type.
+fn number_schema<Num: serde::Serialize + PartialOrd + Copy>(
+    r#type: &str,
+    format: &str,
+    attributes: &NumberTypeAttributes<Num>,
+) -> Value {
+    let mut schema = json!({ "type": r#type, "format": format });
+
+    if let Some(min) = attributes.min() {
+        schema["minimum"] = json!(min);
+    }
+
+    if let Some(max) = attributes.max() {
+        schema["maximum"] = json!(max);
+    }
+
+    with_default(schema, attributes.default_value())
+}
+
+/// Add a default value to a schema, if there is one.
+fn with_default(mut schema: Value, default: Option<impl serde::Serialize>) -> Value {
+    if let Some(default) = default {
+        schema["default"] = json!(default);
+    }
+
+    schema
+}
+
+/// An error that can occur when converting a JSON schema to GameSON type definitions.
+#[derive(Debug, thiserror::Error)]
+pub enum JsonSchemaError {
+    /// The root schema has no title to name its type after.
+    #[error("the root schema has no title")]
+    MissingTitle,
+
+    /// A schema references a definition that does not exist.
+    #[error("unknown schema reference `{0}`")]
+    UnknownReference(String),
+
+    /// A schema has no GameSON equivalent.
+    #[error("unsupported schema for type `{name}`: {reason}")]
+    Unsupported {
+        /// The name of the type.
+        name: String,
+
+        /// The reason why the schema is unsupported.
+        reason: String,
+    },
+}
+
+impl UnidentifiedTypeDefinition<String> {
+    /// Convert a JSON schema, such as one generated by `schemars`, to GameSON type definitions.
+    ///
+    /// The root schema becomes a type named after its title, and each of its `$defs` (or
+    /// `definitions`) becomes a type named after its key. The schemas nested in other schemas,
+    /// rather than referenced, become types named after their parent, with an `Item`, `Key` or
+    /// `Value` suffix. The returned type definitions, sorted by name, can be registered with
+    /// [`register_unidentified`](crate::TypeDefinitionRegistry::register_unidentified).
+    ///
+    /// The schemas are mapped as follows:
+    ///
+    /// - `boolean`, `string` and `integer` or `number` schemas map to the matching scalar types,
+    ///   the number formats generated by `schemars` selecting the width and signedness of the
+    ///   numbers, along with their `minimum`, `maximum` and `default` values.
+    /// - `string` schemas with a `uuid` format map to UUIDs, with the `uuid` feature, and those
+    ///   with a `contentEncoding` map to bytes, with the `bytes` feature.
+    /// - `enum` schemas, and `oneOf` schemas of `const` strings, map to enums: the descriptions
+    ///   and deprecations of the `const` schemas become those of the enum values.
+    /// - `array` schemas map to arrays, or to dictionaries in the pairs wire form if their items
+    ///   are two-item arrays.
+    /// - `object` schemas without properties but with `additionalProperties` map to
+    ///   dictionaries, whose keys are described by `propertyNames`, if any, or are strings.
+    ///
+    /// Objects with properties, nullable types and schema compositions other than the above have
+    /// no GameSON equivalent.
+    ///
+    /// ```ignore
+    /// let type_definitions =
+    ///     UnidentifiedTypeDefinition::from_json_schema(&schemars::schema_for!(Palette))?;
+    ///
+    /// registry.register_unidentified(type_definitions, &mut allocator);
+    /// ```
+    pub fn from_json_schema(schema: &Schema) -> Result<Vec<Self>, JsonSchemaError> {
+        let name = schema
+            .get("title")
+            .and_then(Value::as_str)
+            .ok_or(JsonSchemaError::MissingTitle)?;
+        let mut converter = Converter {
+            definitions: ["$defs", "definitions"]
+                .into_iter()
+                .filter_map(|key| schema.get(key)?.as_object())
+                .flatten()
+                .collect(),
+            type_definitions: BTreeMap::new(),
+        };
+
+        for (name, schema) in converter.definitions.clone() {
+            converter.convert(name, schema)?;
+        }
+
+        converter.convert(name, schema.as_value())?;
+
+        Ok(converter.type_definitions.into_values().collect())
+    }
+}
+
+/// A converter of JSON schemas to GameSON type definitions.
+struct Converter<'a> {
+    /// The schemas that can be referenced, by name.
+    definitions: BTreeMap<&'a String, &'a Value>,
+
+    /// The type definitions converted so far, by name.
+    type_definitions: BTreeMap<String, UnidentifiedTypeDefinition<String>>,
+}
+
+impl Converter<'_> {
+    /// Convert a schema to a type definition with the specified name.
+    fn convert(&mut self, name: &str, schema: &Value) -> Result<(), JsonSchemaError> {
+        let unsupported = |reason: &str| JsonSchemaError::Unsupported {
+            name: name.to_owned(),
+            reason: reason.to_owned(),
+        };
+        let schema = schema
+            .as_object()
+            .ok_or_else(|| unsupported("boolean schemas are not supported"))?;
+        let default = schema.get("default");
+
+        let attributes = if let Some(attributes) = enum_attributes(schema) {
+            attributes.map_err(|reason| unsupported(&reason))?
+        } else {
+            match schema.get("type").and_then(Value::as_str) {
+                Some("boolean") => TypeAttributes::Boolean(BooleanTypeAttributes::new(
+                    default.and_then(Value::as_bool),
+                )),
+                Some("integer") => {
+                    let format = schema.get("format").and_then(Value::as_str);
+
+                    match format {
+                        Some("int8" | "int16" | "int32") => {
+                            number_attributes(schema).map(TypeAttributes::Int32)
+                        }
+                        Some("uint8" | "uint16" | "uint32") => {
+                            number_attributes(schema).map(TypeAttributes::Uint32)
+                        }
+                        Some("uint" | "uint64") => {
+                            number_attributes(schema).map(TypeAttributes::Uint64)
+                        }
+                        _ => number_attributes(schema).map(TypeAttributes::Int64),
+                    }
+                    .map_err(|reason| unsupported(&reason))?
+                }
+                Some("number") => match schema.get("format").and_then(Value::as_str) {
+                    Some("float") => number_attributes(schema).map(TypeAttributes::Float32),
+                    _ => number_attributes(schema).map(TypeAttributes::Float64),
+                }
+                .map_err(|reason| unsupported(&reason))?,
+                Some("string") => string_attributes(schema),
+                Some("array") => {
+                    let items = schema
+                        .get("items")
+                        .ok_or_else(|| unsupported("arrays must have items"))?;
+
+                    match items.get("prefixItems").and_then(Value::as_array) {
+                        Some(pair) if pair.len() == 2 => TypeAttributes::Dictionary(
+                            DictionaryTypeAttributes::new(
+                                self.reference(name, "Key", &pair[0])?,
+                                self.reference(name, "Value", &pair[1])?,
+                            )
+                            .with_wire_form(DictionaryWireForm::Pairs),
+                        ),
+                        _ => TypeAttributes::Array(ArrayTypeAttributes::new(
+                            self.reference(name, "Item", items)?,
+                        )),
+                    }
+                }
+                Some("object") => {
+                    if schema
+                        .get("properties")
+                        .and_then(Value::as_object)
+                        .is_some_and(|properties| !properties.is_empty())
+                    {
+                        return Err(unsupported("objects with properties are not supported"));
+                    }
+
+                    let values = schema
+                        .get("additionalProperties")
+                        .filter(|values| values.is_object())
+                        .ok_or_else(|| unsupported("objects must have additional properties"))?;
+                    let keys = match schema.get("propertyNames") {
+                        Some(keys) => self.reference(name, "Key", keys)?,
+                        None => self.reference(name, "Key", &json!({ "type": "string" }))?,
+                    };
+
+                    TypeAttributes::Dictionary(DictionaryTypeAttributes::new(
+                        keys,
+                        self.reference(name, "Value", values)?,
+                    ))
+                }
+                Some(r#type) => return Err(unsupported(&format!("unsupported type `{type}`"))),
+                None if schema.get("type").is_some() => {
+                    return Err(unsupported("schemas with several types are not supported"));
+                }
+                None => return Err(unsupported("schemas must have a type")),
+            }
+        };
+
+        self.type_definitions.insert(
+            name.to_owned(),
+            UnidentifiedTypeDefinition {
+                name: name.to_owned(),
+                description: schema
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .map(ToOwned::to_owned),
+                attributes,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Get the name of the type a schema references, or convert the schema to a type named after
+    /// its parent type with the specified suffix.
+    fn reference(
+        &mut self,
+        parent: &str,
+        suffix: &str,
+        schema: &Value,
+    ) -> Result<String, JsonSchemaError> {
+        match schema.get("$ref").and_then(Value::as_str) {
+            Some(reference) => {
+                let name = reference
+                    .strip_prefix("#/$defs/")
+                    .or_else(|| reference.strip_prefix("#/definitions/"))
+                    .filter(|name| self.definitions.contains_key(&name.to_string()))
+                    .ok_or_else(|| JsonSchemaError::UnknownReference(reference.to_owned()))?;
+
+                Ok(name.to_owned())
+            }
+            None => {
+                let name = format!("{parent}{suffix}");
+                self.convert(&name, schema)?;
+
+                Ok(name)
+            }
+        }
+    }
+}
+
+/// Get the attributes of an enum schema, if the schema describes an enum.
+fn enum_attributes(
+    schema: &Map<String, Value>,
+) -> Option<Result<TypeAttributes<String, String>, String>> {
+    let default = schema.get("default");
+    let mut builder = EnumTypeAttributes::builder();
+
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        for value in values {
+            let Some(value) = value.as_str() else {
+                return Some(Err("enum values must be strings".to_owned()));
+            };
+
+            builder = builder.with_value(value.to_owned());
+        }
+    } else if let Some(variants) = schema.get("oneOf").and_then(Value::as_array) {
+        for variant in variants {
+            let description = variant
+                .get("description")
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned);
+            let deprecated = variant
+                .get("deprecated")
+                .and_then(Value::as_bool)
+                .unwrap_or_default();
+
+            if let Some(value) = variant.get("const").and_then(Value::as_str) {
+                builder = builder.with_value_ext(value.to_owned(), description, deprecated);
+            } else if let Some(values) = variant.get("enum").and_then(Value::as_array)
+                && values.iter().all(Value::is_string)
+            {
+                for value in values.iter().filter_map(Value::as_str) {
+                    builder = builder.with_value_ext(value.to_owned(), None, deprecated);
+                }
+            } else {
+                return Some(Err(
+                    "only `oneOf` schemas of string constants are supported".to_owned(),
+                ));
+            }
+        }
+    } else {
+        return None;
+    }
+
+    if let Some(default) = default.and_then(Value::as_str) {
+        builder = builder.with_default(default.to_owned());
+    }
+
+    Some(
+        builder
+            .build()
+            .map(TypeAttributes::Enum)
+            .map_err(|err| err.to_string()),
+    )
+}
+
+/// Get the attributes of a string schema.
+fn string_attributes(schema: &Map<String, Value>) -> TypeAttributes<String, String> {
+    let default = schema.get("default");
+
+    #[cfg(feature = "uuid")]
+    if schema.get("format").and_then(Value::as_str) == Some("uuid") {
+        return TypeAttributes::Uuid(UuidTypeAttributes::new(
+            default.and_then(|default| serde_json::from_value(default.clone()).ok()),
+        ));
+    }
+
+    #[cfg(feature = "bytes")]
+    if let Some(encoding) = schema.get("contentEncoding").and_then(Value::as_str) {
+        let encoding = match encoding {
+            "base64url" => BytesEncoding::Base64Url,
+            "base16" => BytesEncoding::Hex,
+            _ => BytesEncoding::Base64,
+        };
+
+        return TypeAttributes::Bytes(BytesTypeAttributes::new(encoding));
+    }
+
+    let mut builder = StringTypeAttributes::builder();
+
+    if let Some(default) = default.and_then(Value::as_str) {
+        builder = builder.default_value(default);
+    }
+
+    TypeAttributes::String(builder.build())
+}
+
+/// Get the attributes of a number schema.
+fn number_attributes<Num: DeserializeOwned + Display + PartialOrd + Copy>(
+    schema: &Map<String, Value>,
+) -> Result<NumberTypeAttributes<Num>, String> {
+    let get = |key: &str| {
+        schema
+            .get(key)
+            .map(|value| {
+                serde_json::from_value::<Num>(value.clone())
+                    .map_err(|err| format!("invalid `{key}`: {err}"))
+            })
+            .transpose()
+    };
+    let mut builder = NumberTypeAttributes::builder();
+
+    if let Some(min) = get("minimum")? {
+        builder = builder.min(min);
+    }
+
+    if let Some(max) = get("maximum")? {
+        builder = builder.max(max);
+    }
+
+    if let Some(default) = get("default")? {
+        builder = builder.default_value(default);
+    }
+
+    builder.build().map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use schemars::JsonSchema;
+    use serde_json::json;
+
+    use super::JsonSchemaError;
+    use crate::test_support::owned::TypeDefinitionRegistry;
+    use crate::{SequentialIdAllocator, UnidentifiedTypeDefinition};
+
+    /// The color of a team.
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    enum Color {
+        /// The red team.
+        Red,
+        #[deprecated]
+        Green,
+    }
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Roster(BTreeMap<String, Vec<Color>>);
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Player {
+        name: String,
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let type_definitions =
+            UnidentifiedTypeDefinition::from_json_schema(&schemars::schema_for!(Roster)).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&type_definitions).unwrap(),
+            json!([
+                {
+                    "name": "Color",
+                    "description": "The color of a team.",
+                    "type": "enum",
+                    "attributes": {
+                        "values": {
+                            "Green": { "deprecated": true },
+                            "Red": { "description": "The red team." },
+                        },
+                    },
+                },
+                {
+                    "name": "Roster",
+                    "type": "dictionary",
+                    "attributes": { "keys_type_id": "RosterKey", "values_type_id": "RosterValue" },
+                },
+                {
+                    "name": "RosterKey",
+                    "type": "string",
+                    "attributes": {},
+                },
+                {
+                    "name": "RosterValue",
+                    "type": "array",
+                    "attributes": { "items_type_id": "Color" },
+                },
+            ])
+        );
+
+        let mut registry = TypeDefinitionRegistry::default();
+        let mut allocator = SequentialIdAllocator::new(1);
+        registry
+            .register_unidentified(type_definitions.clone(), &mut allocator)
+            .into_result()
+            .unwrap();
+
+        let roster = registry.by_name["Roster"].to_json_schema();
+        assert_eq!(
+            roster.as_value(),
+            &json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "Roster",
+                "type": "object",
+                "propertyNames": { "$ref": "#/$defs/RosterKey" },
+                "additionalProperties": { "$ref": "#/$defs/RosterValue" },
+                "$defs": {
+                    "Color": {
+                        "description": "The color of a team.",
+                        "oneOf": [
+                            { "const": "Green", "deprecated": true },
+                            { "const": "Red", "description": "The red team." },
+                        ],
+                    },
+                    "RosterKey": { "type": "string" },
+                    "RosterValue": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/Color" },
+                    },
+                },
+            })
+        );
+        assert_eq!(
+            UnidentifiedTypeDefinition::from_json_schema(&roster).unwrap(),
+            type_definitions
+        );
+
+        assert_eq!(
+            serde_json::to_value(
+                UnidentifiedTypeDefinition::from_json_schema(&schemars::schema_for!(u8)).unwrap()
+            )
+            .unwrap(),
+            json!([{ "name": "uint8", "type": "uint32", "attributes": { "min": 0, "max": 255 } }])
+        );
+        assert!(matches!(
+            UnidentifiedTypeDefinition::from_json_schema(&schemars::schema_for!(Player)),
+            Err(JsonSchemaError::Unsupported { name, .. }) if name == "Player"
+        ));
+    }
+}
@@ -0,0 +1,281 @@
+//! Layered documents, merging chains of documents that extend one another.
+
+use std::{borrow::Borrow, fmt::Display};
+
+use crate::{Document, DocumentError, PathSegment};
+
+/// A base document, extended by a chain of override documents, such as per-platform or per-region
+/// configuration overlays.
+///
+/// The layers are merged in order, and the merge is deterministic: values of the same name are
+/// merged if they have the same type, and replaced otherwise. Dictionaries are merged entry by
+/// entry, recursively, while any other value, arrays included, is replaced as a whole. Merged
+/// dictionaries are checked again for their types, so that the merged document is valid.
+#[derive(Debug, Clone)]
+pub struct LayeredDocument<Id, FieldName: Ord> {
+    /// The layers, by name, base first.
+    layers: Vec<(String, Document<Id, FieldName>)>,
+}
+
+/// The result of merging a [`LayeredDocument`], remembering which layer each value comes from.
+#[derive(Debug, Clone)]
+pub struct MergedDocument<Id, FieldName: Ord> {
+    /// The merged document.
+    document: Document<Id, FieldName>,
+
+    /// The names of the layers, base first.
+    layers: Vec<String>,
+
+    /// The paths of the values taken from a layer, with the index of the layer, in the order they
+    /// were taken. No path is a prefix of another.
+    origins: Vec<(Vec<PathSegment>, usize)>,
+}
+
+impl<Id, FieldName: Ord> LayeredDocument<Id, FieldName> {
+    /// Create a layered document from its base layer.
+    pub fn new(name: impl Into<String>, base: Document<Id, FieldName>) -> Self {
+        Self {
+            layers: vec![(name.into(), base)],
+        }
+    }
+
+    /// Extend the document with an override layer, applied on top of all the previous layers.
+    pub fn extend(mut self, name: impl Into<String>, overlay: Document<Id, FieldName>) -> Self {
+        self.layers.push((name.into(), overlay));
+        self
+    }
+
+    /// Get the layers, by name, base first.
+    pub fn layers(&self) -> impl Iterator<Item = (&str, &Document<Id, FieldName>)> {
+        self.layers
+            .iter()
+            .map(|(name, document)| (name.as_str(), document))
+    }
+}
+
+impl<Id, FieldName> LayeredDocument<Id, FieldName>
+where
+    Id: PartialEq + Clone + Display,
+    FieldName: Ord + Display + Clone + Borrow<str>,
+{
+    /// Merge the layers into a single document.
+    ///
+    /// Fails with [`DocumentError::InvalidValue`] if a merged dictionary is not valid for its
+    /// type.
+    pub fn merge(&self) -> Result<MergedDocument<Id, FieldName>, DocumentError<Id, FieldName>> {
+        let mut document = Document::new();
+        let mut origins: Vec<(Vec<PathSegment>, usize)> = Vec::new();
+
+        for (index, (_, layer)) in self.layers.iter().enumerate() {
+            for (name, overlay) in layer.values() {
+                let prefix = PathSegment::DictionaryKey(name.to_string());
+                let mut replaced = |path: &[PathSegment]| {
+                    let path: Vec<_> = std::iter::once(prefix.clone())
+                        .chain(path.iter().cloned())
+                        .collect();
+
+                    origins.retain(|(origin, _)| !origin.starts_with(&path));
+                    origins.push((path, index));
+                };
+
+                let value = match document.get(name.borrow()) {
+                    Some(value) => value.merge(overlay, &mut replaced).map_err(|err| {
+                        DocumentError::InvalidValue {
+                            name: name.clone(),
+                            err,
+                        }
+                    })?,
+                    None => {
+                        replaced(&[]);
+                        overlay.clone()
+                    }
+                };
+
+                document.insert(name.clone(), value);
+            }
+        }
+
+        Ok(MergedDocument {
+            document,
+            layers: self.layers.iter().map(|(name, _)| name.clone()).collect(),
+            origins,
+        })
+    }
+}
+
+impl<Id, FieldName: Ord> MergedDocument<Id, FieldName> {
+    /// Get the merged document.
+    pub fn document(&self) -> &Document<Id, FieldName> {
+        &self.document
+    }
+
+    /// Get the merged document, consuming the merge result.
+    pub fn into_document(self) -> Document<Id, FieldName> {
+        self.document
+    }
+
+    /// Get the name of the layer the value at the specified path comes from.
+    ///
+    /// The path starts with the name of the value in the document, like the
+    /// [path](DocumentError::path) of document errors. Returns `None` if the document has no value
+    /// with that name.
+    pub fn origin(&self, path: &[PathSegment]) -> Option<&str> {
+        self.origins
+            .iter()
+            .filter(|(origin, _)| path.starts_with(origin))
+            .max_by_key(|(origin, _)| origin.len())
+            .map(|(_, index)| self.layers[*index].as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        Comparison, DocumentError, EntryConstraint, PathSegment,
+        test_support::{
+            Document, FieldName, Id, TypeAttributes, TypeDefinitionRegistry, Value, registry, td,
+        },
+        type_attributes::{ArrayTypeAttributes, DictionaryTypeAttributes, EnumTypeAttributes},
+    };
+
+    type LayeredDocument = super::LayeredDocument<Id, FieldName>;
+
+    fn path(segments: &[&str]) -> Vec<PathSegment> {
+        segments
+            .iter()
+            .map(|segment| match segment.parse() {
+                Ok(index) => PathSegment::ArrayIndex(index),
+                Err(_) => PathSegment::DictionaryKey((*segment).to_owned()),
+            })
+            .collect()
+    }
+
+    fn damage_registry() -> TypeDefinitionRegistry {
+        registry([
+            td(
+                1,
+                "Stat",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("min_damage")
+                        .with_value("max_damage")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(2, "Damage", TypeAttributes::Uint32(Default::default())),
+            td(
+                3,
+                "Stats",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2).with_constraint(
+                    EntryConstraint::new("min_damage", Comparison::LessOrEqual, "max_damage"),
+                )),
+            ),
+            td(4, "Name", TypeAttributes::String(Default::default())),
+            td(
+                5,
+                "Names",
+                TypeAttributes::Array(ArrayTypeAttributes::new(4)),
+            ),
+        ])
+    }
+
+    fn document(
+        registry: &TypeDefinitionRegistry,
+        values: &[(FieldName, Id, serde_json::Value)],
+    ) -> Document {
+        let mut document = Document::new();
+
+        for (name, id, json) in values {
+            let instance = registry.get(id).unwrap().clone();
+            document.insert(name, Value::parse_for(instance, json.clone()).unwrap());
+        }
+
+        document
+    }
+
+    fn layers(registry: &TypeDefinitionRegistry) -> LayeredDocument {
+        LayeredDocument::new(
+            "base",
+            document(
+                registry,
+                &[
+                    ("weapon", 3, json!({ "min_damage": 2, "max_damage": 5 })),
+                    ("tags", 5, json!(["sharp", "heavy"])),
+                ],
+            ),
+        )
+        .extend(
+            "pc",
+            document(
+                registry,
+                &[
+                    ("weapon", 3, json!({ "max_damage": 8 })),
+                    ("tags", 5, json!(["light"])),
+                ],
+            ),
+        )
+        .extend("eu", document(registry, &[("title", 4, json!("Épée"))]))
+    }
+
+    #[test]
+    fn test_layers() {
+        assert_eq!(
+            layers(&damage_registry())
+                .layers()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            ["base", "pc", "eu"]
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let merged = layers(&damage_registry()).merge().unwrap();
+
+        assert_eq!(
+            merged.document().values_to_json().unwrap(),
+            json!({
+                "tags": { "type_id": 5, "value": ["light"] },
+                "title": { "type_id": 4, "value": "Épée" },
+                "weapon": { "type_id": 3, "value": { "max_damage": 8, "min_damage": 2 } },
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_origins() {
+        let merged = layers(&damage_registry()).merge().unwrap();
+
+        assert_eq!(merged.origin(&path(&["weapon", "max_damage"])), Some("pc"));
+        assert_eq!(
+            merged.origin(&path(&["weapon", "min_damage"])),
+            Some("base")
+        );
+        assert_eq!(merged.origin(&path(&["weapon"])), Some("base"));
+        assert_eq!(merged.origin(&path(&["tags", "0"])), Some("pc"));
+        assert_eq!(merged.origin(&path(&["title"])), Some("eu"));
+        assert_eq!(merged.origin(&path(&["shield"])), None);
+    }
+
+    #[test]
+    fn test_merge_invalid() {
+        let registry = damage_registry();
+
+        // Merged dictionaries are checked again.
+        let err = layers(&registry)
+            .extend(
+                "broken",
+                document(&registry, &[("weapon", 3, json!({ "min_damage": 9 }))]),
+            )
+            .merge()
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            DocumentError::InvalidValue { name: "weapon", .. }
+        ));
+        assert_eq!(err.path(), path(&["weapon"]));
+    }
+}
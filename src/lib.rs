@@ -1,15 +1,135 @@
 //! GameSON encoding format.
 
+#[cfg(all(test, feature = "derive"))]
+extern crate self as gameson;
+
 pub(crate) mod type_attributes;
 pub(crate) mod type_attributes_instance;
 
+#[cfg(feature = "tokio")]
+mod async_store;
+#[cfg(feature = "zstd")]
+mod bundle;
+#[cfg(feature = "cbor")]
+mod cbor_bundle;
+mod codegen;
+mod compatibility;
+mod delta;
+mod diagnostics;
+mod directory;
+pub mod docs;
+mod document;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod fingerprint;
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+mod gameson_type;
+mod hasher;
+mod id_allocator;
+mod instance_cache;
+mod interned_name;
+#[cfg(feature = "schemars")]
+mod json_schema;
+mod layered_document;
+mod lint;
+mod localization;
+mod memory;
+mod metrics;
+mod parse_options;
+#[cfg(feature = "sqlx-postgres")]
+mod postgres_store;
+#[cfg(feature = "bevy")]
+mod reflect;
+mod registry_events;
+#[cfg(feature = "rand")]
+mod sample_strategy;
+mod schema_version;
+mod serialize_options;
+#[cfg(feature = "sqlite")]
+mod sqlite_store;
+mod store;
+#[cfg(test)]
+pub(crate) mod test_support;
 mod type_definition;
 mod type_definition_instance;
 mod type_definition_registry;
+mod type_ref;
+mod typed_value;
 mod value;
+#[cfg(feature = "notify")]
+mod watch;
 
-pub use type_attributes::{InstantiationError, InstantiationResult, TypeAttributes};
-pub use type_definition::TypeDefinition;
+#[cfg(feature = "tokio")]
+pub use async_store::AsyncTypeDefinitionStore;
+#[cfg(feature = "zstd")]
+pub use bundle::BundleError;
+#[cfg(feature = "cbor")]
+pub use cbor_bundle::CborBundleError;
+pub use codegen::{GodotOptions, NameCase, UnrealOptions};
+pub use compatibility::{Compatibility, EvolutionPolicy};
+pub use delta::{DeltaError, RegistryDelta};
+pub use diagnostics::{Diagnostics, DiagnosticsSink, RegistrationWarning, RegistrationWarningKind};
+pub use directory::{DirectoryLoad, LoadError, LoadErrorKind};
+pub use document::{Document, DocumentError};
+pub use fingerprint::Fingerprint;
+#[cfg(feature = "derive")]
+pub use gameson_derive::GameSonType;
+pub use gameson_type::GameSonType;
+#[cfg(feature = "uuid")]
+pub use id_allocator::UuidIdAllocator;
+pub use id_allocator::{HashIdAllocator, IdAllocator, SequentialIdAllocator};
+pub use instance_cache::{TypeInstanceCache, TypeInstanceCacheError};
+pub use interned_name::InternedName;
+#[cfg(feature = "schemars")]
+pub use json_schema::JsonSchemaError;
+pub use layered_document::{LayeredDocument, MergedDocument};
+pub use lint::{LintFinding, LintOptions, LintReport, LintRule};
+pub use localization::{LocaleIssue, LocaleIssueKind, SupportedLocales};
+pub use metrics::{Metrics, MetricsCounters};
+pub use parse_options::{
+    Float32Conversion, NullPolicy, NumberConversion, ParseLimits, ParseOptions,
+};
+#[cfg(feature = "sqlx-postgres")]
+pub use postgres_store::{PostgresStore, PostgresStoreError};
+#[cfg(feature = "bevy")]
+pub use reflect::ReflectError;
+pub use registry_events::RegistryEvent;
+#[cfg(feature = "rand")]
+pub use sample_strategy::{SampleLocale, SampleStrategy, TextKind};
+pub use schema_version::{Negotiation, SchemaVersion};
+pub use serialize_options::{NonFiniteFloats, SerializeOptions};
+#[cfg(feature = "sqlite")]
+pub use sqlite_store::{SqliteStore, SqliteStoreError};
+pub use store::TypeDefinitionStore;
+#[cfg(feature = "bytes")]
+pub use type_attributes::{BytesEncoding, DecodeBytesError};
+pub use type_attributes::{
+    Comparison, CompiledEntryRules, DictionaryWireForm, EntryConstraint, EntryRequirement,
+    Expression, ExpressionError, InstantiationError, InstantiationResult, TypeAttributes,
+    UnicodeNormalization, ValidateNumberTypeError, WhitespacePolicy,
+};
+pub use type_definition::{TypeDefinition, UnidentifiedTypeDefinition};
 pub use type_definition_instance::TypeDefinitionInstance;
-pub use type_definition_registry::TypeDefinitionRegistry;
-pub use value::Value;
+pub use type_definition_registry::{
+    RegistrationError, RegistrationOutcome, TypeDefinitionRegistry, TypeRenaming,
+};
+pub use type_ref::TypeRef;
+pub use typed_value::{TypedValue, TypedValueError};
+pub use value::{
+    ArrayItems, DeserializeError, DetachedValue, ParseError, ParseErrorKind, ParseVariantSetError,
+    ParseWarning, ParseWarningKind, PathSegment, PersistentValue, PersistentValueError, Prefab,
+    Reference, ReferenceFilter, SerializeError, SerializeErrorKind, Severity, SourceLocation,
+    ValidationReport, Validator, Value, ValueDeserializer, ValueFormat, ValueRef, ValueSeed,
+    ValueWriter, ValueWriterError, VariantSet, VariantValue, Violation, WriteValueError,
+};
+#[cfg(feature = "notify")]
+pub use watch::{AppliedChanges, SchemaWatcher};
+
+/// Implementation details of the derive macros.
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json;
+
+    pub use crate::gameson_type::Inner;
+}
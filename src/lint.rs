@@ -0,0 +1,512 @@
+//! Linting of registries, for schema conventions and smells.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    hash::Hash,
+    sync::Arc,
+};
+
+use serde::Serialize;
+
+use crate::{
+    NameCase, Severity, TypeDefinitionInstance, TypeDefinitionRegistry,
+    type_attributes::NumberTypeAttributes, type_attributes_instance::TypeAttributesInstance,
+};
+
+/// A lint rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum LintRule {
+    /// Type names follow the naming convention of types.
+    TypeNaming,
+
+    /// Enum values follow the naming convention of enum values.
+    EnumValueNaming,
+
+    /// Types are referenced by another type, unless they are roots.
+    ///
+    /// The rule only applies when roots are specified, as any type that no other type references
+    /// is a root otherwise.
+    UnusedType,
+
+    /// Enum types and their values have descriptions.
+    MissingEnumDescription,
+
+    /// Number types have both a minimum and a maximum.
+    ///
+    /// String types have no length constraints: the lengths of strings can only be bounded by the
+    /// parse limits.
+    UnboundedNumber,
+
+    /// Types are not nested deeper than the maximum depth.
+    DeepNesting,
+}
+
+impl LintRule {
+    /// All the lint rules.
+    const ALL: [Self; 6] = [
+        Self::TypeNaming,
+        Self::EnumValueNaming,
+        Self::UnusedType,
+        Self::MissingEnumDescription,
+        Self::UnboundedNumber,
+        Self::DeepNesting,
+    ];
+
+    /// Get a stable code identifying the rule, for tooling.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::TypeNaming => "type_naming",
+            Self::EnumValueNaming => "enum_value_naming",
+            Self::UnusedType => "unused_type",
+            Self::MissingEnumDescription => "missing_enum_description",
+            Self::UnboundedNumber => "unbounded_number",
+            Self::DeepNesting => "deep_nesting",
+        }
+    }
+}
+
+/// Options that control how registries are linted.
+///
+/// By default, all the rules are enabled as warnings, types must be named in `PascalCase`, enum
+/// values can be named freely, and types can be nested 8 levels deep.
+#[derive(Debug, Clone)]
+pub struct LintOptions {
+    /// The severities of the enabled rules.
+    pub(crate) severities: BTreeMap<LintRule, Severity>,
+
+    /// The naming convention of types.
+    pub(crate) type_case: NameCase,
+
+    /// The naming convention of enum values.
+    pub(crate) enum_value_case: NameCase,
+
+    /// The names of the root types, that other types need not reference.
+    pub(crate) roots: BTreeSet<String>,
+
+    /// The maximum nesting depth of types.
+    pub(crate) max_depth: usize,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            severities: LintRule::ALL
+                .into_iter()
+                .map(|rule| (rule, Severity::Warning))
+                .collect(),
+            type_case: NameCase::Pascal,
+            enum_value_case: NameCase::Verbatim,
+            roots: BTreeSet::new(),
+            max_depth: 8,
+        }
+    }
+}
+
+impl LintOptions {
+    /// Sets the severity of a rule, or disables it with `None`.
+    pub fn rule(mut self, rule: LintRule, severity: Option<Severity>) -> Self {
+        match severity {
+            Some(severity) => self.severities.insert(rule, severity),
+            None => self.severities.remove(&rule),
+        };
+        self
+    }
+
+    /// Sets the naming convention of types.
+    ///
+    /// Names are expected to be unchanged by the conversion: [`NameCase::Verbatim`] accepts any
+    /// name.
+    pub fn type_case(mut self, type_case: NameCase) -> Self {
+        self.type_case = type_case;
+        self
+    }
+
+    /// Sets the naming convention of enum values.
+    pub fn enum_value_case(mut self, enum_value_case: NameCase) -> Self {
+        self.enum_value_case = enum_value_case;
+        self
+    }
+
+    /// Sets the names of the root types, that other types need not reference.
+    pub fn roots(mut self, roots: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.roots = roots.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the maximum nesting depth of types.
+    ///
+    /// Scalar and enum types have a depth of 1, and arrays and dictionaries one more than the
+    /// types they reference.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+}
+
+/// A finding of a lint rule, about a type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[error("type definition `{name}` ({id}): {message}")]
+pub struct LintFinding<Id, FieldName> {
+    /// The rule.
+    rule: LintRule,
+
+    /// The severity.
+    severity: Severity,
+
+    /// The identifier of the type.
+    id: Id,
+
+    /// The name of the type.
+    name: FieldName,
+
+    /// The human-readable message.
+    message: String,
+}
+
+impl<Id, FieldName> LintFinding<Id, FieldName> {
+    /// Get the rule.
+    pub fn rule(&self) -> LintRule {
+        self.rule
+    }
+
+    /// Get the severity.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Get the identifier of the type.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Get the name of the type.
+    pub fn name(&self) -> &FieldName {
+        &self.name
+    }
+
+    /// Get the human-readable message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// The result of linting a registry.
+///
+/// The report serializes to JSON as the list of its findings, suitable for CI artifacts.
+#[derive(Debug, Clone, Serialize)]
+pub struct LintReport<Id, FieldName> {
+    /// The findings, sorted by type name.
+    findings: Vec<LintFinding<Id, FieldName>>,
+}
+
+impl<Id, FieldName> LintReport<Id, FieldName> {
+    /// Check whether the report contains no findings.
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Get the highest severity of the findings, if there are any.
+    pub fn severity(&self) -> Option<Severity> {
+        self.findings.iter().map(|finding| finding.severity).max()
+    }
+
+    /// Get the findings, sorted by type name.
+    pub fn findings(&self) -> &[LintFinding<Id, FieldName>] {
+        &self.findings
+    }
+}
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display,
+{
+    /// Lint the registered types.
+    pub fn lint(&self, options: &LintOptions) -> LintReport<Id, FieldName> {
+        let mut instances: Vec<_> = self.by_name.values().collect();
+        instances.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let referenced: BTreeSet<&Id> = instances
+            .iter()
+            .flat_map(|instance| instance.attributes.references())
+            .map(|ref_| &ref_.id)
+            .collect();
+
+        let mut depths = BTreeMap::new();
+        let mut findings = Vec::new();
+
+        for instance in instances {
+            let mut report = |rule: LintRule, message: String| {
+                if let Some(severity) = options.severities.get(&rule) {
+                    findings.push(LintFinding {
+                        rule,
+                        severity: *severity,
+                        id: instance.id.clone(),
+                        name: instance.name.clone(),
+                        message,
+                    });
+                }
+            };
+
+            let name = instance.name.to_string();
+            let expected = options.type_case.convert(&name);
+
+            if expected != name {
+                report(
+                    LintRule::TypeNaming,
+                    format!("the type name should be `{expected}`"),
+                );
+            }
+
+            if !options.roots.is_empty()
+                && !options.roots.contains(&name)
+                && !referenced.contains(&instance.id)
+            {
+                report(
+                    LintRule::UnusedType,
+                    "no root type uses the type".to_owned(),
+                );
+            }
+
+            let depth = depth(instance, &mut depths);
+
+            if depth > options.max_depth {
+                report(
+                    LintRule::DeepNesting,
+                    format!(
+                        "the type is nested {depth} levels deep, more than the maximum of {}",
+                        options.max_depth
+                    ),
+                );
+            }
+
+            match &instance.attributes {
+                TypeAttributesInstance::Enum(a) => {
+                    for value in a.values() {
+                        let value = value.to_string();
+                        let expected = options.enum_value_case.convert(&value);
+
+                        if expected != value {
+                            report(
+                                LintRule::EnumValueNaming,
+                                format!("the enum value `{value}` should be `{expected}`"),
+                            );
+                        }
+                    }
+
+                    if instance.description.is_none() {
+                        report(
+                            LintRule::MissingEnumDescription,
+                            "the enum has no description".to_owned(),
+                        );
+                    }
+
+                    let undocumented = a
+                        .values()
+                        .filter(|value| a.value_description(*value).is_none())
+                        .map(|value| format!("`{value}`"))
+                        .collect::<Vec<_>>();
+
+                    if !undocumented.is_empty() {
+                        report(
+                            LintRule::MissingEnumDescription,
+                            format!(
+                                "the enum values {} have no description",
+                                undocumented.join(", ")
+                            ),
+                        );
+                    }
+                }
+                TypeAttributesInstance::Int32(a) => check_bounds(a, &mut report),
+                TypeAttributesInstance::Int64(a) => check_bounds(a, &mut report),
+                TypeAttributesInstance::Uint32(a) => check_bounds(a, &mut report),
+                TypeAttributesInstance::Uint64(a) => check_bounds(a, &mut report),
+                TypeAttributesInstance::Float32(a) => check_bounds(a, &mut report),
+                TypeAttributesInstance::Float64(a) => check_bounds(a, &mut report),
+                _ => {}
+            }
+        }
+
+        // Findings are sorted by type name already, and the rules of a type keep the order in
+        // which they were checked.
+        LintReport { findings }
+    }
+}
+
+/// Check that a number type has both a minimum and a maximum.
+fn check_bounds<Num: PartialOrd + Copy>(
+    attributes: &NumberTypeAttributes<Num>,
+    report: &mut impl FnMut(LintRule, String),
+) {
+    let missing = match (attributes.min(), attributes.max()) {
+        (Some(_), Some(_)) => return,
+        (None, Some(_)) => "minimum",
+        (Some(_), None) => "maximum",
+        (None, None) => "minimum nor maximum",
+    };
+
+    report(
+        LintRule::UnboundedNumber,
+        format!("the number has no {missing}"),
+    );
+}
+
+/// Get the nesting depth of a type, memoizing the depths of the types it references.
+fn depth<Id: Ord + Clone, FieldName: Ord>(
+    instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+    depths: &mut BTreeMap<Id, usize>,
+) -> usize {
+    if let Some(depth) = depths.get(&instance.id) {
+        return *depth;
+    }
+
+    let depth = 1 + instance
+        .attributes
+        .references()
+        .into_iter()
+        .map(|ref_| depth(ref_, depths))
+        .max()
+        .unwrap_or(0);
+
+    depths.insert(instance.id.clone(), depth);
+
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{LintOptions, LintRule};
+    use crate::{
+        NameCase, Severity,
+        test_support::{TypeAttributes, TypeDefinition, TypeDefinitionRegistry, registry, td},
+        type_attributes::{ArrayTypeAttributes, EnumTypeAttributes, NumberTypeAttributes},
+    };
+
+    fn equipment_registry() -> TypeDefinitionRegistry {
+        registry([
+            td(
+                1,
+                "Level",
+                TypeAttributes::Int32(NumberTypeAttributes::builder().min(1).build().unwrap()),
+            ),
+            TypeDefinition {
+                description: Some("The slots of the equipment.".to_owned()),
+                ..td(
+                    2,
+                    "Slot",
+                    TypeAttributes::Enum(
+                        EnumTypeAttributes::builder()
+                            .with_value_ext("Head", Some("The head.".to_owned()), false)
+                            .with_value("left_hand")
+                            .build()
+                            .unwrap(),
+                    ),
+                )
+            },
+            td(
+                3,
+                "levels",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(
+                4,
+                "Matrix",
+                TypeAttributes::Array(ArrayTypeAttributes::new(3)),
+            ),
+            td(
+                5,
+                "Orphan",
+                TypeAttributes::Uint32(
+                    NumberTypeAttributes::builder()
+                        .min(0)
+                        .max(9)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+        ])
+    }
+
+    fn options() -> LintOptions {
+        LintOptions::default()
+            .enum_value_case(NameCase::Pascal)
+            .roots(["Matrix", "Slot"])
+            .max_depth(2)
+            .rule(LintRule::UnboundedNumber, Some(Severity::Error))
+    }
+
+    #[test]
+    fn test_lint() {
+        let report = equipment_registry().lint(&options());
+
+        assert_eq!(report.severity(), Some(Severity::Error));
+        assert_eq!(
+            report
+                .findings()
+                .iter()
+                .map(|finding| (finding.name, finding.rule, finding.message.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "Level",
+                    LintRule::UnboundedNumber,
+                    "the number has no maximum"
+                ),
+                (
+                    "Matrix",
+                    LintRule::DeepNesting,
+                    "the type is nested 3 levels deep, more than the maximum of 2"
+                ),
+                ("Orphan", LintRule::UnusedType, "no root type uses the type"),
+                (
+                    "Slot",
+                    LintRule::EnumValueNaming,
+                    "the enum value `left_hand` should be `LeftHand`"
+                ),
+                (
+                    "Slot",
+                    LintRule::MissingEnumDescription,
+                    "the enum values `left_hand` have no description"
+                ),
+                (
+                    "levels",
+                    LintRule::TypeNaming,
+                    "the type name should be `Levels`"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_report_serialization() {
+        let report = equipment_registry().lint(&options());
+
+        assert_eq!(
+            serde_json::to_value(&report).unwrap()["findings"][0],
+            json!({
+                "rule": "unbounded_number",
+                "severity": "error",
+                "id": 1,
+                "name": "Level",
+                "message": "the number has no maximum",
+            })
+        );
+    }
+
+    #[test]
+    fn test_lint_disabled_rules() {
+        let report = equipment_registry().lint(
+            &LintOptions::default()
+                .rule(LintRule::TypeNaming, None)
+                .rule(LintRule::UnboundedNumber, None)
+                .rule(LintRule::MissingEnumDescription, None),
+        );
+
+        assert!(report.is_clean());
+    }
+}
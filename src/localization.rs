@@ -0,0 +1,229 @@
+//! Validation of localized content against the locales supported by a registry.
+
+use std::{borrow::Borrow, collections::BTreeSet, fmt::Display};
+
+use crate::{Document, PathSegment, TypeDefinitionRegistry};
+
+/// The locales supported by the localized content of a registry.
+///
+/// Localized content is stored in dictionaries keyed by the locale type, from locales to
+/// localized values: every such dictionary is expected to have exactly the supported locales as
+/// keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupportedLocales<Id> {
+    /// The identifier of the locale type.
+    locale_type_id: Id,
+
+    /// The supported locales.
+    locales: BTreeSet<String>,
+}
+
+/// Whether a locale is missing from localized content, or is not supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LocaleIssueKind {
+    /// A supported locale is missing.
+    Missing,
+
+    /// A locale is not supported.
+    Extra,
+}
+
+/// An issue with the locales of localized content, found by
+/// [`check_locales`](TypeDefinitionRegistry::check_locales).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "document `{document}`: {}: {} locale `{locale}`",
+    .path.iter().map(ToString::to_string).collect::<String>(),
+    match .kind {
+        LocaleIssueKind::Missing => "missing",
+        LocaleIssueKind::Extra => "unsupported",
+    },
+)]
+pub struct LocaleIssue {
+    /// The name of the document.
+    document: String,
+
+    /// The path of the localized dictionary in the document.
+    path: Vec<PathSegment>,
+
+    /// The locale.
+    locale: String,
+
+    /// Whether the locale is missing, or is not supported.
+    kind: LocaleIssueKind,
+}
+
+impl<Id> SupportedLocales<Id> {
+    /// Create the supported locales of the localized content, for dictionaries keyed by the
+    /// specified locale type.
+    pub fn new(locale_type_id: Id, locales: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            locale_type_id,
+            locales: locales.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Get the identifier of the locale type.
+    pub fn locale_type_id(&self) -> &Id {
+        &self.locale_type_id
+    }
+
+    /// Get the supported locales.
+    pub fn locales(&self) -> &BTreeSet<String> {
+        &self.locales
+    }
+}
+
+impl LocaleIssue {
+    /// Get the name of the document.
+    pub fn document(&self) -> &str {
+        &self.document
+    }
+
+    /// Get the path of the localized dictionary in the document.
+    ///
+    /// The path starts with the name of the value in the document, like the
+    /// [path](crate::DocumentError::path) of document errors.
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+
+    /// Get the locale.
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Get whether the locale is missing, or is not supported.
+    pub fn kind(&self) -> LocaleIssueKind {
+        self.kind
+    }
+}
+
+impl<Id, FieldName: Ord + Display + Clone> TypeDefinitionRegistry<Id, FieldName> {
+    /// Get the locales supported by the localized content, if configured.
+    pub fn supported_locales(&self) -> Option<&SupportedLocales<Id>> {
+        self.supported_locales.as_ref()
+    }
+
+    /// Set the locales supported by the localized content, or stop checking localized content
+    /// with `None`.
+    pub fn set_supported_locales(&mut self, supported_locales: Option<SupportedLocales<Id>>) {
+        self.supported_locales = supported_locales;
+    }
+}
+
+impl<Id: PartialEq, FieldName: Ord + Display + Clone + Borrow<str>>
+    TypeDefinitionRegistry<Id, FieldName>
+{
+    /// Check the localized content of a set of documents, by name, against the supported
+    /// locales.
+    ///
+    /// All the dictionaries keyed by the locale type, at any depth, are checked: the issues are
+    /// reported by document, then by path, in depth-first order, then by locale. There are none if
+    /// the [supported locales](Self::set_supported_locales) are not configured.
+    pub fn check_locales<'a>(
+        &self,
+        documents: impl IntoIterator<Item = (&'a str, &'a Document<Id, FieldName>)>,
+    ) -> Vec<LocaleIssue>
+    where
+        Id: 'a,
+        FieldName: 'a,
+    {
+        let Some(supported) = &self.supported_locales else {
+            return Vec::new();
+        };
+
+        let mut issues = Vec::new();
+
+        for (document, values) in documents {
+            for (name, value) in values.values() {
+                for (path, locales) in value.localized_keys(&supported.locale_type_id) {
+                    let path: Vec<_> =
+                        std::iter::once(PathSegment::DictionaryKey(name.to_string()))
+                            .chain(path)
+                            .collect();
+                    let locales: BTreeSet<_> = locales.into_iter().collect();
+
+                    let missing = supported
+                        .locales
+                        .difference(&locales)
+                        .map(|locale| (locale, LocaleIssueKind::Missing));
+                    let extra = locales
+                        .difference(&supported.locales)
+                        .map(|locale| (locale, LocaleIssueKind::Extra));
+
+                    issues.extend(missing.chain(extra).map(|(locale, kind)| LocaleIssue {
+                        document: document.to_owned(),
+                        path: path.clone(),
+                        locale: locale.clone(),
+                        kind,
+                    }));
+                }
+            }
+        }
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        PathSegment,
+        test_support::owned::{Document, registry_from_json},
+    };
+
+    use super::{LocaleIssueKind, SupportedLocales};
+
+    #[test]
+    fn test_check_locales() {
+        let mut registry = registry_from_json(json!([
+            { "id": 1, "name": "Locale", "type": "string", "attributes": {} },
+            { "id": 2, "name": "Text", "type": "string", "attributes": {} },
+            { "id": 3, "name": "LocalizedText", "type": "dictionary", "attributes": { "keys_type_id": 1, "values_type_id": 2 } },
+            { "id": 4, "name": "LocalizedTexts", "type": "array", "attributes": { "items_type_id": 3 } },
+        ]));
+
+        let items = Document::parse_with_registry(
+            &registry,
+            json!({
+                "names": { "type_id": 4, "value": [
+                    { "en": "Sword", "fr": "Épée" },
+                    { "en": "Shield", "de": "Schild" },
+                ] },
+                "id": { "type_id": 2, "value": "items" },
+            }),
+        )
+        .unwrap();
+        let menu = Document::parse_with_registry(
+            &registry,
+            json!({ "title": { "type_id": 3, "value": { "en": "Play", "fr": "Jouer" } } }),
+        )
+        .unwrap();
+        let documents = [("items", &items), ("menu", &menu)];
+
+        assert!(registry.check_locales(documents).is_empty());
+
+        registry.set_supported_locales(Some(SupportedLocales::new(1, ["en", "fr"])));
+        assert_eq!(registry.supported_locales().unwrap().locales().len(), 2);
+
+        let issues = registry.check_locales(documents);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].document(), "items");
+        assert_eq!(
+            issues[0].path(),
+            [
+                PathSegment::DictionaryKey("names".to_owned()),
+                PathSegment::ArrayIndex(1)
+            ]
+        );
+        assert_eq!(issues[0].locale(), "fr");
+        assert_eq!(issues[0].kind(), LocaleIssueKind::Missing);
+        assert_eq!(
+            issues[1].to_string(),
+            "document `items`: [names][1]: unsupported locale `de`"
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! Memory footprint of registries.
+
+use std::{fmt::Display, sync::Arc};
+
+use crate::{
+    TypeDefinition, TypeDefinitionInstance, TypeDefinitionRegistry, hasher::HashSet,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+impl<Id, FieldName: Ord + Display + Clone> TypeDefinitionRegistry<Id, FieldName> {
+    /// Estimate the memory used by the registry, in bytes.
+    ///
+    /// This counts the type instances, including the previous ones kept by the history of the
+    /// registry, along with the indexes of the registry. The memory owned by identifiers and
+    /// names, by compiled entry rules and by custom validators is not counted.
+    pub fn memory_usage(&self) -> usize {
+        // Type instances are shared between the indexes and the history.
+        let mut seen = HashSet::default();
+        let instances = self
+            .by_id
+            .values()
+            .chain(
+                self.history
+                    .iter()
+                    .flat_map(|entry| entry.change.values().flatten()),
+            )
+            .filter(|instance| seen.insert(Arc::as_ptr(instance)))
+            .map(|instance| instance_memory_usage(instance))
+            .sum::<usize>();
+
+        let history = self.history.capacity()
+            * size_of::<crate::type_definition_registry::HistoryEntry<Id, FieldName>>()
+            + self
+                .history
+                .iter()
+                .map(|entry| entry.change.len() * size_of::<(Id, Option<Arc<()>>)>())
+                .sum::<usize>();
+
+        size_of::<Self>()
+            + instances
+            + history
+            + self.by_id.len() * size_of::<(Id, Arc<()>)>()
+            + self.by_name.len() * size_of::<(FieldName, Arc<()>)>()
+            + self
+                .dependents
+                .values()
+                .map(|dependents| (1 + dependents.len()) * size_of::<Id>())
+                .sum::<usize>()
+            + self.declared.len() * size_of::<(Id, FieldName)>()
+            + self.declared_by_name.len() * size_of::<(FieldName, Id)>()
+            + self.pending.capacity() * size_of::<TypeDefinition<Id, FieldName>>()
+    }
+}
+
+/// Estimate the memory used by a type instance, in bytes.
+fn instance_memory_usage<Id, FieldName: Ord>(
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+) -> usize {
+    let attributes = match &instance.attributes {
+        TypeAttributesInstance::String(a) => a.default_value().map_or(0, str::len),
+        TypeAttributesInstance::Enum(a) => a.heap_size_bytes(),
+        _ => 0,
+    };
+
+    // The reference counts of the `Arc` are allocated along with the instance.
+    2 * size_of::<usize>()
+        + size_of::<TypeDefinitionInstance<Id, FieldName>>()
+        + instance.description.as_ref().map_or(0, String::capacity)
+        + attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_support::owned::{TypeAttributes, TypeDefinition, TypeDefinitionRegistry, td},
+        type_attributes::EnumTypeAttributes,
+    };
+
+    #[test]
+    fn test_memory_usage() {
+        let mut registry = TypeDefinitionRegistry::default();
+        let empty = registry.memory_usage();
+        assert!(empty >= size_of::<TypeDefinitionRegistry>());
+
+        registry
+            .register([td(1, "MyInt", TypeAttributes::Int32(Default::default()))])
+            .into_result()
+            .unwrap();
+        let one = registry.memory_usage();
+        assert!(one > empty);
+
+        let mut builder = EnumTypeAttributes::builder();
+        for i in 0..100 {
+            builder = builder.with_value(format!("Value{i}"));
+        }
+
+        registry
+            .register([TypeDefinition {
+                id: 2,
+                name: "MyEnum".to_owned(),
+                description: Some("x".repeat(1000)),
+                attributes: TypeAttributes::Enum(builder.build().unwrap()),
+            }])
+            .into_result()
+            .unwrap();
+        assert!(registry.memory_usage() >= one + 1000 + 100 * size_of::<String>());
+    }
+}
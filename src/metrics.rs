@@ -0,0 +1,205 @@
+//! Metrics about the parsing and validation of values.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{ParseErrorKind, ParseOptions, ValidationReport};
+
+/// A receiver of metrics about the values that are parsed and validated.
+///
+/// Implement this trait to export metrics, for instance as Prometheus counters and histograms,
+/// and set it on the [`ParseOptions`] used to parse and validate values: it is then invoked once
+/// per parsed or validated value, with the name of its type. All the methods do nothing by
+/// default. Use [`MetricsCounters`] to simply count the values instead.
+///
+/// Recovering parses, which are meant for tools, and values deserialized with a
+/// [`ValueSeed`](crate::ValueSeed) are not measured.
+pub trait Metrics: Debug + Send + Sync {
+    /// Record a value that was parsed successfully, and how long it took.
+    fn parse_succeeded(&self, type_name: &str, duration: Duration) {
+        let _ = (type_name, duration);
+    }
+
+    /// Record a value that failed to parse, with the [code](ParseErrorKind::code) of the error,
+    /// and how long it took.
+    fn parse_failed(&self, type_name: &str, code: &'static str, duration: Duration) {
+        let _ = (type_name, code, duration);
+    }
+
+    /// Record a value that was validated, with its report, and how long it took.
+    fn validated(&self, type_name: &str, report: &ValidationReport, duration: Duration) {
+        let _ = (type_name, report, duration);
+    }
+}
+
+/// Metrics that count the parsed and validated values, and the time spent on them.
+#[derive(Debug, Default)]
+pub struct MetricsCounters {
+    /// The number of values parsed, successfully or not.
+    parses: AtomicU64,
+
+    /// The number of values validated, valid or not.
+    validations: AtomicU64,
+
+    /// The number of values that failed to validate.
+    invalid: AtomicU64,
+
+    /// The number of failed parses and violations, by error code.
+    failures: Mutex<BTreeMap<&'static str, u64>>,
+
+    /// The total time spent parsing and validating values, in nanoseconds.
+    nanos: AtomicU64,
+}
+
+impl MetricsCounters {
+    /// Get the number of values parsed, successfully or not.
+    pub fn parses(&self) -> u64 {
+        self.parses.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of values validated, valid or not.
+    pub fn validations(&self) -> u64 {
+        self.validations.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of values that failed to validate.
+    pub fn invalid(&self) -> u64 {
+        self.invalid.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of failed parses and violations, by error code.
+    pub fn failures(&self) -> BTreeMap<&'static str, u64> {
+        self.failures
+            .lock()
+            .expect("the lock is not poisoned")
+            .clone()
+    }
+
+    /// Get the total time spent parsing and validating values.
+    pub fn duration(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+
+    /// Count the time spent on a value.
+    fn add_duration(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+
+        self.nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// Count failures, by error code.
+    fn add_failures(&self, codes: impl IntoIterator<Item = &'static str>) {
+        let mut failures = self.failures.lock().expect("the lock is not poisoned");
+
+        for code in codes {
+            *failures.entry(code).or_default() += 1;
+        }
+    }
+}
+
+impl Metrics for MetricsCounters {
+    fn parse_succeeded(&self, _type_name: &str, duration: Duration) {
+        self.parses.fetch_add(1, Ordering::Relaxed);
+        self.add_duration(duration);
+    }
+
+    fn parse_failed(&self, _type_name: &str, code: &'static str, duration: Duration) {
+        self.parses.fetch_add(1, Ordering::Relaxed);
+        self.add_failures([code]);
+        self.add_duration(duration);
+    }
+
+    fn validated(&self, _type_name: &str, report: &ValidationReport, duration: Duration) {
+        self.validations.fetch_add(1, Ordering::Relaxed);
+
+        if !report.is_valid() {
+            self.invalid.fetch_add(1, Ordering::Relaxed);
+            self.add_failures(report.errors().iter().map(|err| err.kind().code()));
+        }
+
+        self.add_duration(duration);
+    }
+}
+
+/// A measure of the parse or validation of a value, started if the options have metrics.
+pub(crate) struct Measure<'a> {
+    /// The metrics to record the measure to.
+    metrics: &'a dyn Metrics,
+
+    /// When the measure started.
+    start: Instant,
+}
+
+impl<'a> Measure<'a> {
+    /// Start a measure, if the options have metrics.
+    pub(crate) fn start(options: &'a ParseOptions) -> Option<Self> {
+        options.metrics.as_deref().map(|metrics| Self {
+            metrics,
+            start: Instant::now(),
+        })
+    }
+
+    /// Record the outcome of a parse.
+    pub(crate) fn parsed(self, type_name: &str, result: Result<(), &ParseErrorKind>) {
+        let duration = self.start.elapsed();
+
+        match result {
+            Ok(()) => self.metrics.parse_succeeded(type_name, duration),
+            Err(err) => self.metrics.parse_failed(type_name, err.code(), duration),
+        }
+    }
+
+    /// Record the outcome of a validation.
+    pub(crate) fn validated(self, type_name: &str, report: &ValidationReport) {
+        self.metrics
+            .validated(type_name, report, self.start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::MetricsCounters;
+    use crate::test_support::{TypeAttributes, Value, registry, td};
+    use crate::{ParseOptions, type_attributes::NumberTypeAttributes};
+
+    #[test]
+    fn test_metrics_counters() {
+        let registry = registry([td(
+            1,
+            "Level",
+            TypeAttributes::Int32(NumberTypeAttributes::builder().max(10).build().unwrap()),
+        )]);
+
+        let instance = registry.get(&1).unwrap();
+        let counters = Arc::new(MetricsCounters::default());
+        let options = ParseOptions::default().metrics(counters.clone());
+
+        Value::parse_for_with_options(instance.clone(), json!(5), &options).unwrap();
+        Value::parse_for_with_options(instance.clone(), json!(11), &options).unwrap_err();
+        Value::parse_str_for_with_options(instance.clone(), "\"5\"", &options).unwrap_err();
+        instance.validate_with_options(&json!(12), &options);
+        instance.validate_with_options(&json!(1), &options);
+
+        // Values parsed without metrics are not counted.
+        Value::parse_for(instance.clone(), json!(5)).unwrap();
+
+        assert_eq!(counters.parses(), 3);
+        assert_eq!(counters.validations(), 2);
+        assert_eq!(counters.invalid(), 1);
+        assert_eq!(
+            counters.failures().into_iter().collect::<Vec<_>>(),
+            [("invalid_int32", 2), ("unexpected_value", 1)]
+        );
+    }
+}
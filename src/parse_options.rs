@@ -0,0 +1,176 @@
+//! Options for parsing GameSON values.
+
+use std::sync::Arc;
+
+use crate::Metrics;
+
+/// Options that control how GameSON values are parsed.
+///
+/// The default options are the strictest: the parsed JSON must describe the value completely.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Whether missing entries are filled with their declared default values.
+    pub(crate) apply_defaults: bool,
+
+    /// The resource limits.
+    pub(crate) limits: ParseLimits,
+
+    /// How JSON numbers that the number type cannot represent exactly are handled.
+    pub(crate) number_conversion: NumberConversion,
+
+    /// How floating-point JSON numbers that a float32 cannot represent are handled.
+    pub(crate) float32_conversion: Float32Conversion,
+
+    /// How JSON nulls are handled.
+    pub(crate) null_policy: NullPolicy,
+
+    /// The metrics to record the parsed and validated values to.
+    pub(crate) metrics: Option<Arc<dyn Metrics>>,
+}
+
+impl ParseOptions {
+    /// Sets whether missing entries are filled with their declared default values.
+    ///
+    /// Only dictionaries keyed by an enum type have a known set of entries: when enabled, every
+    /// enum value missing from such a dictionary is added with the default value of the
+    /// dictionary values type, provided that type declares one.
+    pub fn apply_defaults(mut self, apply_defaults: bool) -> Self {
+        self.apply_defaults = apply_defaults;
+        self
+    }
+
+    /// Sets the resource limits.
+    pub fn limits(mut self, limits: ParseLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets how JSON numbers that the number type cannot represent exactly are handled.
+    pub fn number_conversion(mut self, number_conversion: NumberConversion) -> Self {
+        self.number_conversion = number_conversion;
+        self
+    }
+
+    /// Sets how floating-point JSON numbers that a float32 cannot represent are handled.
+    pub fn float32_conversion(mut self, float32_conversion: Float32Conversion) -> Self {
+        self.float32_conversion = float32_conversion;
+        self
+    }
+
+    /// Sets how JSON nulls are handled.
+    pub fn null_policy(mut self, null_policy: NullPolicy) -> Self {
+        self.null_policy = null_policy;
+        self
+    }
+
+    /// Sets the metrics to record the parsed and validated values to.
+    pub fn metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+}
+
+/// How JSON numbers that the number type cannot represent exactly are handled.
+///
+/// This applies to fractional numbers parsed into integer types and to integers parsed into
+/// floating-point types. Numbers outside the range of the number type are always rejected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberConversion {
+    /// Reject numbers that would lose precision.
+    ///
+    /// Integral floating-point numbers, such as `1.0`, are still accepted for integer types.
+    #[default]
+    Exact,
+
+    /// Round numbers to the nearest value the number type can represent.
+    Nearest,
+}
+
+/// How JSON nulls are handled.
+///
+/// GameSON has no null value: a JSON null is never a valid value by itself.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NullPolicy {
+    /// Reject nulls.
+    #[default]
+    Error,
+
+    /// Replace nulls by the declared default value of their type.
+    ///
+    /// Nulls are still rejected if their type has no default value.
+    UseDefault,
+
+    /// Treat nulls as absent values: null array items and dictionary entries are dropped.
+    ///
+    /// Dropped dictionary entries can then be filled by
+    /// [`apply_defaults`](ParseOptions::apply_defaults). A null that cannot be dropped, such as
+    /// the parsed value itself, is rejected.
+    Absent,
+}
+
+/// How floating-point JSON numbers that a float32 cannot represent are handled.
+///
+/// A number loses precision if it has more significant digits than a float32 holds: `0.1` is
+/// fine, but `0.123456789` is not. Integers are governed by [`NumberConversion`] instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Float32Conversion {
+    /// Reject numbers that are out of range or that would lose precision.
+    #[default]
+    Exact,
+
+    /// Round numbers to the nearest float32, but reject numbers that are out of range.
+    Round,
+
+    /// Round numbers to the nearest float32 and clamp numbers that are out of range to the
+    /// largest finite float32 of the same sign.
+    Clamp,
+}
+
+/// Resource limits enforced while parsing GameSON values.
+///
+/// Limits protect against malicious or corrupted input that would otherwise exhaust the stack or
+/// the memory. By default, no limit is enforced.
+#[derive(Debug, Clone, Default)]
+pub struct ParseLimits {
+    /// The maximum nesting depth of arrays and dictionaries.
+    pub(crate) max_depth: Option<usize>,
+
+    /// The maximum number of items in an array.
+    pub(crate) max_array_length: Option<usize>,
+
+    /// The maximum length of a string, in bytes.
+    pub(crate) max_string_length: Option<usize>,
+
+    /// The maximum total number of values.
+    pub(crate) max_nodes: Option<usize>,
+}
+
+impl ParseLimits {
+    /// Sets the maximum nesting depth of arrays and dictionaries.
+    ///
+    /// A scalar value has a depth of 0 and an array of scalars has a depth of 1.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Sets the maximum number of items in an array.
+    pub fn max_array_length(mut self, max_array_length: usize) -> Self {
+        self.max_array_length = Some(max_array_length);
+        self
+    }
+
+    /// Sets the maximum length of a string, in bytes.
+    ///
+    /// This also applies to dictionary keys.
+    pub fn max_string_length(mut self, max_string_length: usize) -> Self {
+        self.max_string_length = Some(max_string_length);
+        self
+    }
+
+    /// Sets the maximum total number of values, including nested ones and dictionary keys.
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+}
@@ -0,0 +1,470 @@
+//! A type definition store backed by PostgreSQL.
+
+use std::{fmt::Display, marker::PhantomData, sync::Mutex};
+
+use serde::{Serialize, de::DeserializeOwned};
+use sqlx::{PgConnection, PgPool};
+
+use crate::{
+    AsyncTypeDefinitionStore, TypeDefinition,
+    hasher::{HashMap, HashSet},
+};
+
+/// The statement creating the table of the type definitions, if it does not exist.
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS type_definitions (
+    id TEXT NOT NULL PRIMARY KEY,
+    name TEXT NOT NULL,
+    json TEXT NOT NULL,
+    version BIGINT NOT NULL
+)";
+
+/// The statement inserting or updating a batch of type definitions, if they were not modified
+/// concurrently.
+///
+/// Each type definition comes with the version it is saved as: 1 if it is not known to exist, or
+/// its last known version plus one. Type definitions that exist with a version other than the
+/// previous one, or that do not exist anymore although they were known to, are left untouched and
+/// are not returned.
+const UPSERT: &str = "INSERT INTO type_definitions (id, name, json, version)
+    SELECT t.id, t.name, t.json, t.version
+    FROM UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[], $4::BIGINT[]) AS t (id, name, json, version)
+    WHERE t.version = 1 OR EXISTS (SELECT 1 FROM type_definitions d WHERE d.id = t.id)
+    ON CONFLICT (id) DO UPDATE SET name = EXCLUDED.name, json = EXCLUDED.json,
+        version = EXCLUDED.version
+    WHERE type_definitions.version = EXCLUDED.version - 1
+    RETURNING id, version";
+
+/// An error that can occur when accessing a PostgreSQL store.
+#[derive(Debug, thiserror::Error)]
+pub enum PostgresStoreError {
+    /// The database could not be accessed.
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    /// A type definition could not be converted from or to JSON.
+    #[error("invalid type definition: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The type definition was modified in the database since the store last loaded or saved it.
+    #[error("type definition {0} was modified concurrently")]
+    Conflict(String),
+}
+
+/// A type definition store backed by a PostgreSQL database.
+///
+/// The type definitions are stored as JSON in a single table, alongside their identifiers, names
+/// and versions. The store remembers the versions of the type definitions it loads and saves, and
+/// refuses to overwrite or delete a type definition that was modified by someone else in the
+/// meantime: the conflicting type definition must be loaded again first. Likewise, a type
+/// definition the store never loaded nor saved cannot be deleted, as its version is unknown.
+#[derive(Debug)]
+pub struct PostgresStore<Id, FieldName> {
+    /// The database connection pool.
+    pool: PgPool,
+
+    /// The last known versions of the type definitions, by their JSON identifiers.
+    versions: Mutex<HashMap<String, i64>>,
+
+    /// The store is typed, although the database is not.
+    _marker: PhantomData<fn() -> (Id, FieldName)>,
+}
+
+impl<Id, FieldName> PostgresStore<Id, FieldName> {
+    /// Open the store in a database, creating its table if needed.
+    pub async fn new(pool: PgPool) -> Result<Self, PostgresStoreError> {
+        sqlx::query(CREATE_TABLE).execute(&pool).await?;
+
+        Ok(Self {
+            pool,
+            versions: Mutex::default(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Get the underlying database connection pool.
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Remember the version of a type definition.
+    fn set_version(&self, id: String, version: i64) {
+        self.versions
+            .lock()
+            .expect("versions lock is poisoned")
+            .insert(id, version);
+    }
+
+    /// Get the last known version of a type definition.
+    fn version(&self, id: &str) -> Option<i64> {
+        self.versions
+            .lock()
+            .expect("versions lock is poisoned")
+            .get(id)
+            .copied()
+    }
+}
+
+impl<Id, FieldName> PostgresStore<Id, FieldName>
+where
+    Id: Serialize,
+    FieldName: Ord + Display + Clone + Serialize,
+{
+    /// Save type definitions in a single transaction: either all of them are saved, or none is.
+    ///
+    /// The type definitions are saved in a single round trip to the database.
+    pub async fn save_all(
+        &self,
+        type_definitions: &[TypeDefinition<Id, FieldName>],
+    ) -> Result<(), PostgresStoreError> {
+        let mut transaction = self.pool.begin().await?;
+        let versions = self.upsert(&mut transaction, type_definitions).await?;
+        transaction.commit().await?;
+
+        // The versions are only known for sure once the transaction is committed.
+        for (id, version) in versions {
+            self.set_version(id, version);
+        }
+
+        Ok(())
+    }
+
+    /// Insert or update type definitions, if they were not modified concurrently.
+    ///
+    /// Returns the JSON identifiers and the new versions of the type definitions. On conflict,
+    /// some type definitions may have been saved already: the caller must roll back.
+    async fn upsert(
+        &self,
+        connection: &mut PgConnection,
+        type_definitions: &[TypeDefinition<Id, FieldName>],
+    ) -> Result<Vec<(String, i64)>, PostgresStoreError> {
+        let mut ids = Vec::with_capacity(type_definitions.len());
+        let mut names = Vec::with_capacity(type_definitions.len());
+        let mut jsons = Vec::with_capacity(type_definitions.len());
+        let mut versions = Vec::with_capacity(type_definitions.len());
+        let mut seen = HashSet::default();
+
+        for type_definition in type_definitions {
+            let id = serde_json::to_string(&type_definition.id)?;
+
+            // The same type definition cannot be saved twice from the same known version.
+            if !seen.insert(id.clone()) {
+                return Err(PostgresStoreError::Conflict(id));
+            }
+
+            versions.push(self.version(&id).map_or(1, |version| version + 1));
+            names.push(type_definition.name.to_string());
+            jsons.push(serde_json::to_string(type_definition)?);
+            ids.push(id);
+        }
+
+        let saved: HashMap<String, i64> = sqlx::query_as::<_, (String, i64)>(UPSERT)
+            .bind(&ids)
+            .bind(names)
+            .bind(jsons)
+            .bind(versions)
+            .fetch_all(connection)
+            .await?
+            .into_iter()
+            .collect();
+
+        ids.into_iter()
+            .map(|id| match saved.get(&id) {
+                Some(&version) => Ok((id, version)),
+                None => Err(PostgresStoreError::Conflict(id)),
+            })
+            .collect()
+    }
+}
+
+impl<Id, FieldName> AsyncTypeDefinitionStore<Id, FieldName> for PostgresStore<Id, FieldName>
+where
+    Id: Serialize + DeserializeOwned + Sync,
+    FieldName: Ord + Display + Clone + Serialize + DeserializeOwned + Sync,
+{
+    type Error = PostgresStoreError;
+
+    async fn load_all(&self) -> Result<Vec<TypeDefinition<Id, FieldName>>, Self::Error> {
+        let rows = sqlx::query_as::<_, (String, String, i64)>(
+            "SELECT id, json, version FROM type_definitions ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(id, json, version)| {
+                let td = serde_json::from_str(&json)?;
+                self.set_version(id, version);
+
+                Ok(td)
+            })
+            .collect()
+    }
+
+    async fn load_by_id(
+        &self,
+        id: &Id,
+    ) -> Result<Option<TypeDefinition<Id, FieldName>>, Self::Error> {
+        let id = serde_json::to_string(id)?;
+        let row = sqlx::query_as::<_, (String, i64)>(
+            "SELECT json, version FROM type_definitions WHERE id = $1",
+        )
+        .bind(&id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|(json, version)| {
+            let td = serde_json::from_str(&json)?;
+            self.set_version(id, version);
+
+            Ok(td)
+        })
+        .transpose()
+    }
+
+    async fn save(
+        &self,
+        type_definition: &TypeDefinition<Id, FieldName>,
+    ) -> Result<(), Self::Error> {
+        let mut connection = self.pool.acquire().await?;
+
+        for (id, version) in self
+            .upsert(&mut connection, std::slice::from_ref(type_definition))
+            .await?
+        {
+            self.set_version(id, version);
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: &Id) -> Result<bool, Self::Error> {
+        let id = serde_json::to_string(id)?;
+
+        // A type definition the store never loaded nor saved may have been modified by someone
+        // else: it must be loaded first, to be deleted from a known version.
+        let Some(version) = self.version(&id) else {
+            let exists = sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS (SELECT 1 FROM type_definitions WHERE id = $1)",
+            )
+            .bind(&id)
+            .fetch_one(&self.pool)
+            .await?;
+
+            return if exists {
+                Err(PostgresStoreError::Conflict(id))
+            } else {
+                Ok(false)
+            };
+        };
+
+        let result = sqlx::query("DELETE FROM type_definitions WHERE id = $1 AND version = $2")
+            .bind(&id)
+            .bind(version)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(PostgresStoreError::Conflict(id));
+        }
+
+        self.versions
+            .lock()
+            .expect("versions lock is poisoned")
+            .remove(&id);
+
+        Ok(true)
+    }
+}
+
+// These tests need a PostgreSQL database, whose URL is read from the `DATABASE_URL` environment
+// variable. Run them with `cargo test --features sqlx-postgres -- --ignored`.
+#[cfg(test)]
+mod tests {
+    use sqlx::{PgPool, postgres::PgPoolOptions};
+
+    use super::PostgresStoreError;
+    use crate::{
+        AsyncTypeDefinitionStore,
+        test_support::owned::{FieldName, Id, TypeAttributes, td},
+    };
+
+    type PostgresStore = super::PostgresStore<Id, FieldName>;
+
+    /// Connect to the test database, in a fresh schema of the given name.
+    async fn pool(schema: &'static str) -> PgPool {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new()
+            .after_connect(move |connection, _| {
+                Box::pin(async move {
+                    sqlx::query(&format!("SET search_path TO {schema}"))
+                        .execute(connection)
+                        .await?;
+
+                    Ok(())
+                })
+            })
+            .connect(&url)
+            .await
+            .unwrap();
+
+        sqlx::query(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(&format!("CREATE SCHEMA {schema}"))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        pool
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a PostgreSQL database"]
+    async fn test_postgres_store_insert() {
+        let pool = pool("gameson_test_insert").await;
+        let store = PostgresStore::new(pool.clone()).await.unwrap();
+
+        assert!(store.load_by_id(&1).await.unwrap().is_none());
+        store
+            .save(&td(1, "MyInt", TypeAttributes::Int32(Default::default())))
+            .await
+            .unwrap();
+        store
+            .save(&td(
+                1,
+                "MyNumber",
+                TypeAttributes::Int64(Default::default()),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.load_by_id(&1).await.unwrap().unwrap().name,
+            "MyNumber"
+        );
+
+        // Another store does not know the type definition exists, and cannot insert it again.
+        let other = PostgresStore::new(pool).await.unwrap();
+        let err = other
+            .save(&td(
+                1,
+                "MyOtherInt",
+                TypeAttributes::Int32(Default::default()),
+            ))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, PostgresStoreError::Conflict(ref id) if id == "1"));
+        assert_eq!(other.load_all().await.unwrap()[0].name, "MyNumber");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a PostgreSQL database"]
+    async fn test_postgres_store_conflict() {
+        let pool = pool("gameson_test_conflict").await;
+        let store = PostgresStore::new(pool.clone()).await.unwrap();
+        let other = PostgresStore::new(pool).await.unwrap();
+
+        store
+            .save(&td(1, "MyInt", TypeAttributes::Int32(Default::default())))
+            .await
+            .unwrap();
+        assert!(other.load_by_id(&1).await.unwrap().is_some());
+        store
+            .save(&td(
+                1,
+                "MyNumber",
+                TypeAttributes::Int64(Default::default()),
+            ))
+            .await
+            .unwrap();
+
+        // The other store saves from a stale version.
+        let err = other
+            .save(&td(
+                1,
+                "MyOtherInt",
+                TypeAttributes::Int32(Default::default()),
+            ))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, PostgresStoreError::Conflict(ref id) if id == "1"));
+        assert_eq!(
+            err.to_string(),
+            "type definition 1 was modified concurrently"
+        );
+        assert!(matches!(
+            other.delete(&1).await,
+            Err(PostgresStoreError::Conflict(_))
+        ));
+
+        // Once loaded again, the type definition can be saved.
+        assert!(other.load_by_id(&1).await.unwrap().is_some());
+        other
+            .save(&td(
+                1,
+                "MyOtherInt",
+                TypeAttributes::Int32(Default::default()),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.load_by_id(&1).await.unwrap().unwrap().name,
+            "MyOtherInt"
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a PostgreSQL database"]
+    async fn test_postgres_store_save_all_duplicate() {
+        let pool = pool("gameson_test_save_all_duplicate").await;
+        let store = PostgresStore::new(pool).await.unwrap();
+
+        let err = store
+            .save_all(&[
+                td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+                td(2, "MyFloat", TypeAttributes::Float32(Default::default())),
+                td(1, "MyNumber", TypeAttributes::Int64(Default::default())),
+            ])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, PostgresStoreError::Conflict(ref id) if id == "1"));
+        assert!(store.load_all().await.unwrap().is_empty());
+
+        store
+            .save_all(&[
+                td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+                td(2, "MyFloat", TypeAttributes::Float32(Default::default())),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(store.load_all().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a PostgreSQL database"]
+    async fn test_postgres_store_delete() {
+        let pool = pool("gameson_test_delete").await;
+        let store = PostgresStore::new(pool.clone()).await.unwrap();
+
+        store
+            .save(&td(1, "MyInt", TypeAttributes::Int32(Default::default())))
+            .await
+            .unwrap();
+
+        // A type definition whose version is unknown cannot be deleted.
+        let other = PostgresStore::new(pool).await.unwrap();
+
+        assert!(matches!(
+            other.delete(&1).await,
+            Err(PostgresStoreError::Conflict(_))
+        ));
+        assert!(!other.delete(&2).await.unwrap());
+
+        assert!(store.delete(&1).await.unwrap());
+        assert!(!store.delete(&1).await.unwrap());
+        assert!(store.load_by_id(&1).await.unwrap().is_none());
+    }
+}
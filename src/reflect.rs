@@ -0,0 +1,347 @@
+//! Integration with `bevy_reflect`.
+
+use std::{borrow::Cow, collections::BTreeMap};
+
+use bevy_reflect::{TypeInfo, TypeRegistry, VariantInfo};
+
+use crate::{
+    DictionaryWireForm, TypeAttributes, UnidentifiedTypeDefinition,
+    type_attributes::{
+        ArrayTypeAttributes, BooleanTypeAttributes, DictionaryTypeAttributes, EnumTypeAttributes,
+        NumberTypeAttributes, StringTypeAttributes,
+    },
+};
+
+/// An error that can occur when converting between GameSON and reflected Rust types.
+#[derive(Debug, thiserror::Error)]
+pub enum ReflectError {
+    /// The Rust type has no GameSON equivalent.
+    #[error("unsupported Rust type `{type_path}`: {reason}")]
+    UnsupportedType {
+        /// The path of the Rust type.
+        type_path: String,
+
+        /// The reason why the Rust type is unsupported.
+        reason: String,
+    },
+
+    /// The GameSON value does not fit in the Rust type.
+    #[error("the GameSON value does not fit in Rust type `{type_path}`")]
+    Mismatch {
+        /// The path of the Rust type.
+        type_path: String,
+    },
+}
+
+impl UnidentifiedTypeDefinition<String> {
+    /// Generate the GameSON type definitions of a reflected Rust type and of all the types it
+    /// references.
+    ///
+    /// The types are mapped as by the [`GameSonType`](crate::GameSonType) derive macro: enums
+    /// whose variants have no fields map to enum types, and tuple structs with a single field map
+    /// to the type of their field. Enums and tuple structs are named after their identifiers, and
+    /// the other types nested in them after their parent, with an `Item`, `Key` or `Value` suffix.
+    ///
+    /// Vectors and arrays map to arrays, and maps to dictionaries, in the pairs wire form
+    /// unless their keys are strings or enums. Integers map to the narrowest GameSON integer type
+    /// they fit in, with the range of the Rust type if it is narrower. The returned type
+    /// definitions are sorted by name.
+    pub fn from_type_info(type_info: &'static TypeInfo) -> Result<Vec<Self>, ReflectError> {
+        let mut type_definitions = BTreeMap::new();
+        let name = type_info
+            .type_path_table()
+            .ident()
+            .unwrap_or_else(|| type_info.type_path_table().short_path());
+
+        collect_type_definitions(name, type_info, &mut type_definitions)?;
+
+        Ok(type_definitions.into_values().collect())
+    }
+
+    /// Generate the GameSON type definitions of all the enums and tuple structs of a type
+    /// registry that have an equivalent, as [`from_type_info`](Self::from_type_info) does,
+    /// sorted by name.
+    ///
+    /// The types without an equivalent, such as structs with named fields, are skipped.
+    pub fn from_type_registry(type_registry: &TypeRegistry) -> Vec<Self> {
+        let mut type_definitions = BTreeMap::new();
+
+        for registration in type_registry.iter() {
+            let type_info = registration.type_info();
+
+            if matches!(type_info, TypeInfo::Enum(_) | TypeInfo::TupleStruct(_))
+                && let Ok(found) = Self::from_type_info(type_info)
+            {
+                type_definitions.extend(found.into_iter().map(|td| (td.name.clone(), td)));
+            }
+        }
+
+        type_definitions.into_values().collect()
+    }
+}
+
+/// Get an error for an unsupported type.
+pub(crate) fn unsupported(type_info: &TypeInfo, reason: &str) -> ReflectError {
+    ReflectError::UnsupportedType {
+        type_path: type_info.type_path().to_owned(),
+        reason: reason.to_owned(),
+    }
+}
+
+/// Get the type information of a nested type, which must be known.
+fn nested(
+    parent: &TypeInfo,
+    type_info: Option<&'static TypeInfo>,
+) -> Result<&'static TypeInfo, ReflectError> {
+    type_info.ok_or_else(|| unsupported(parent, "the type of a field or item is unknown"))
+}
+
+/// Collect the type definition of a reflected type with the specified name, and of all the types
+/// it references.
+fn collect_type_definitions(
+    name: &str,
+    type_info: &'static TypeInfo,
+    type_definitions: &mut BTreeMap<String, UnidentifiedTypeDefinition<String>>,
+) -> Result<(), ReflectError> {
+    let attributes = match type_info {
+        TypeInfo::Enum(info) => {
+            let mut builder = EnumTypeAttributes::builder();
+
+            for variant in info.iter() {
+                match variant {
+                    VariantInfo::Unit(variant) => {
+                        builder = builder.with_value(variant.name().to_owned());
+                    }
+                    _ => return Err(unsupported(type_info, "enum variants must have no fields")),
+                }
+            }
+
+            TypeAttributes::Enum(
+                builder
+                    .build()
+                    .map_err(|err| unsupported(type_info, &err.to_string()))?,
+            )
+        }
+        TypeInfo::TupleStruct(info) if info.field_len() == 1 => {
+            let field = nested(type_info, info.field_at(0).and_then(|f| f.type_info()))?;
+
+            return collect_type_definitions(name, field, type_definitions);
+        }
+        TypeInfo::List(info) => TypeAttributes::Array(ArrayTypeAttributes::new(reference(
+            name,
+            "Item",
+            nested(type_info, info.item_info())?,
+            type_definitions,
+        )?)),
+        TypeInfo::Array(info) => TypeAttributes::Array(ArrayTypeAttributes::new(reference(
+            name,
+            "Item",
+            nested(type_info, info.item_info())?,
+            type_definitions,
+        )?)),
+        TypeInfo::Map(info) => {
+            let keys_info = nested(type_info, info.key_info())?;
+            let keys = reference(name, "Key", keys_info, type_definitions)?;
+            let values = reference(
+                name,
+                "Value",
+                nested(type_info, info.value_info())?,
+                type_definitions,
+            )?;
+            let attributes = DictionaryTypeAttributes::new(keys, values);
+
+            TypeAttributes::Dictionary(
+                match &type_definitions[attributes.keys_type_id()].attributes {
+                    TypeAttributes::String(_) | TypeAttributes::Enum(_) => attributes,
+                    _ => attributes.with_wire_form(DictionaryWireForm::Pairs),
+                },
+            )
+        }
+        TypeInfo::Opaque(_) => opaque_attributes(type_info)?,
+        TypeInfo::Struct(_) | TypeInfo::TupleStruct(_) => {
+            return Err(unsupported(
+                type_info,
+                "GameSON has no record types: only tuple structs with a single field are supported",
+            ));
+        }
+        TypeInfo::Tuple(_) => return Err(unsupported(type_info, "tuples are not supported")),
+        TypeInfo::Set(_) => {
+            return Err(unsupported(
+                type_info,
+                "the type information of set items is not available",
+            ));
+        }
+    };
+
+    type_definitions.insert(
+        name.to_owned(),
+        UnidentifiedTypeDefinition {
+            name: name.to_owned(),
+            description: None,
+            attributes,
+        },
+    );
+
+    Ok(())
+}
+
+/// Get the name of the type a nested type maps to, collecting its type definition if needed.
+///
+/// Enums and tuple structs are named after their identifiers, and the other types after their
+/// parent type, with the specified suffix.
+fn reference(
+    parent: &str,
+    suffix: &str,
+    type_info: &'static TypeInfo,
+    type_definitions: &mut BTreeMap<String, UnidentifiedTypeDefinition<String>>,
+) -> Result<String, ReflectError> {
+    let name = match (type_info, type_info.type_path_table().ident()) {
+        (TypeInfo::Enum(_) | TypeInfo::TupleStruct(_), Some(ident)) => ident.to_owned(),
+        _ => format!("{parent}{suffix}"),
+    };
+
+    if !type_definitions.contains_key(&name) {
+        collect_type_definitions(&name, type_info, type_definitions)?;
+    }
+
+    Ok(name)
+}
+
+/// Get the attributes of an opaque type, such as a number or a string.
+fn opaque_attributes(
+    type_info: &'static TypeInfo,
+) -> Result<TypeAttributes<String, String>, ReflectError> {
+    /// Get the attributes of a number type, restricted to the range of a narrower Rust type.
+    macro_rules! narrow {
+        ($variant:ident, $num:ty, $narrow:ty) => {
+            TypeAttributes::$variant(
+                NumberTypeAttributes::builder()
+                    .min(<$num>::from(<$narrow>::MIN))
+                    .max(<$num>::from(<$narrow>::MAX))
+                    .build()
+                    .expect("the range of a Rust type is valid"),
+            )
+        };
+    }
+
+    Ok(if type_info.is::<bool>() {
+        TypeAttributes::Boolean(BooleanTypeAttributes::default())
+    } else if type_info.is::<i8>() {
+        narrow!(Int32, i32, i8)
+    } else if type_info.is::<i16>() {
+        narrow!(Int32, i32, i16)
+    } else if type_info.is::<i32>() {
+        TypeAttributes::Int32(NumberTypeAttributes::default())
+    } else if type_info.is::<i64>() || type_info.is::<isize>() {
+        TypeAttributes::Int64(NumberTypeAttributes::default())
+    } else if type_info.is::<u8>() {
+        narrow!(Uint32, u32, u8)
+    } else if type_info.is::<u16>() {
+        narrow!(Uint32, u32, u16)
+    } else if type_info.is::<u32>() {
+        TypeAttributes::Uint32(NumberTypeAttributes::default())
+    } else if type_info.is::<u64>() || type_info.is::<usize>() {
+        TypeAttributes::Uint64(NumberTypeAttributes::default())
+    } else if type_info.is::<f32>() {
+        TypeAttributes::Float32(NumberTypeAttributes::default())
+    } else if type_info.is::<f64>() {
+        TypeAttributes::Float64(NumberTypeAttributes::default())
+    } else if type_info.is::<String>() || type_info.is::<Cow<'static, str>>() {
+        TypeAttributes::String(StringTypeAttributes::default())
+    } else {
+        return Err(unsupported(type_info, "the type has no GameSON equivalent"));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bevy_reflect::{Reflect, TypeRegistry, Typed};
+    use serde_json::json;
+
+    use super::ReflectError;
+    use crate::UnidentifiedTypeDefinition;
+
+    #[derive(Reflect)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    #[derive(Reflect)]
+    struct Roster(BTreeMap<String, Vec<Color>>);
+
+    #[derive(Reflect)]
+    struct Levels(BTreeMap<u8, Color>);
+
+    #[derive(Reflect)]
+    struct Player {
+        name: String,
+    }
+
+    #[test]
+    fn test_from_type_info() {
+        assert_eq!(
+            serde_json::to_value(
+                UnidentifiedTypeDefinition::from_type_info(Roster::type_info()).unwrap()
+            )
+            .unwrap(),
+            json!([
+                {
+                    "name": "Color",
+                    "type": "enum",
+                    "attributes": { "values": { "Green": {}, "Red": {} } },
+                },
+                {
+                    "name": "Roster",
+                    "type": "dictionary",
+                    "attributes": { "keys_type_id": "RosterKey", "values_type_id": "RosterValue" },
+                },
+                { "name": "RosterKey", "type": "string", "attributes": {} },
+                {
+                    "name": "RosterValue",
+                    "type": "array",
+                    "attributes": { "items_type_id": "Color" },
+                },
+            ])
+        );
+
+        let mut type_registry = TypeRegistry::new();
+        type_registry.register::<Levels>();
+        type_registry.register::<Player>();
+
+        assert_eq!(
+            serde_json::to_value(UnidentifiedTypeDefinition::from_type_registry(
+                &type_registry
+            ))
+            .unwrap(),
+            json!([
+                {
+                    "name": "Color",
+                    "type": "enum",
+                    "attributes": { "values": { "Green": {}, "Red": {} } },
+                },
+                {
+                    "name": "Levels",
+                    "type": "dictionary",
+                    "attributes": {
+                        "keys_type_id": "LevelsKey",
+                        "values_type_id": "Color",
+                        "wire_form": "pairs",
+                    },
+                },
+                {
+                    "name": "LevelsKey",
+                    "type": "uint32",
+                    "attributes": { "min": 0, "max": 255 },
+                },
+            ])
+        );
+
+        assert!(matches!(
+            UnidentifiedTypeDefinition::from_type_info(Player::type_info()),
+            Err(ReflectError::UnsupportedType { .. })
+        ));
+    }
+}
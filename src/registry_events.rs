@@ -0,0 +1,81 @@
+//! Notifications of the changes of a registry.
+
+use std::{
+    fmt::Display,
+    sync::{Arc, mpsc},
+};
+
+use crate::{RegistrationError, TypeDefinition, TypeDefinitionInstance};
+
+/// A change of a registry.
+#[derive(Debug, Clone)]
+pub enum RegistryEvent<Id, FieldName: Ord + Display + Clone> {
+    /// A type definition was registered.
+    Registered(Arc<TypeDefinitionInstance<Id, FieldName>>),
+
+    /// A type definition could not be registered.
+    Failed(
+        TypeDefinition<Id, FieldName>,
+        RegistrationError<Id, FieldName>,
+    ),
+
+    /// A type instance was replaced, either because its type definition was replaced or because
+    /// one of the type definitions it references was.
+    Replaced {
+        /// The previous type instance.
+        previous: Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+        /// The new type instance.
+        current: Arc<TypeDefinitionInstance<Id, FieldName>>,
+    },
+
+    /// A type definition was removed.
+    Removed(Arc<TypeDefinitionInstance<Id, FieldName>>),
+}
+
+/// The subscribers to the changes of a registry.
+///
+/// Subscribers are not cloned along with their registry, as they subscribed to the changes of
+/// the original one only.
+#[derive(Debug)]
+pub(crate) struct Subscribers<Id, FieldName: Ord + Display + Clone>(
+    Vec<mpsc::Sender<RegistryEvent<Id, FieldName>>>,
+);
+
+impl<Id, FieldName: Ord + Display + Clone> Default for Subscribers<Id, FieldName> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<Id, FieldName: Ord + Display + Clone> Clone for Subscribers<Id, FieldName> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<Id: Clone, FieldName: Ord + Display + Clone> Subscribers<Id, FieldName> {
+    /// Add a subscriber, returning the receiving end of its events.
+    pub(crate) fn subscribe(&mut self) -> mpsc::Receiver<RegistryEvent<Id, FieldName>> {
+        let (sender, receiver) = mpsc::channel();
+        self.0.push(sender);
+
+        receiver
+    }
+
+    /// Send events to all the subscribers, forgetting the ones that were dropped.
+    ///
+    /// The events are only produced if there are subscribers.
+    pub(crate) fn notify<I>(&mut self, events: impl FnOnce() -> I)
+    where
+        I: IntoIterator<Item = RegistryEvent<Id, FieldName>>,
+    {
+        if self.0.is_empty() {
+            return;
+        }
+
+        for event in events() {
+            self.0.retain(|sender| sender.send(event.clone()).is_ok());
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! Strategies for generating sample GameSON values.
+
+use std::collections::BTreeMap;
+
+use rand::{Rng, seq::IndexedRandom};
+
+/// A strategy that controls how sample GameSON values are generated.
+///
+/// The default strategy is uniform: strings are alphanumeric, and enums take any of their
+/// non-deprecated values with the same probability. [`SampleStrategy::realistic`] generates
+/// values that look like real game data instead, for fixtures used in demos and documentation.
+#[derive(Debug, Clone, Default)]
+pub struct SampleStrategy {
+    /// The locale of the generated text.
+    pub(crate) locale: SampleLocale,
+
+    /// Whether the kind of text of string types is inferred from their names.
+    pub(crate) infer_text_kinds: bool,
+
+    /// The kind of text of string types, by type name.
+    pub(crate) text_kinds: BTreeMap<String, TextKind>,
+
+    /// The weights of enum values, by type name and then by value.
+    pub(crate) enum_weights: BTreeMap<String, BTreeMap<String, u32>>,
+}
+
+impl SampleStrategy {
+    /// Create a strategy that generates realistic values, with the kind of text of string types
+    /// inferred from their names.
+    pub fn realistic() -> Self {
+        Self::default().infer_text_kinds(true)
+    }
+
+    /// Sets the locale of the generated text.
+    pub fn locale(mut self, locale: SampleLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Sets whether the kind of text of string types is inferred from their names.
+    ///
+    /// For instance, a `CharacterName` type gets person names and an `ItemDescription` type gets
+    /// sentences. Types whose names give no hint get single words.
+    pub fn infer_text_kinds(mut self, infer_text_kinds: bool) -> Self {
+        self.infer_text_kinds = infer_text_kinds;
+        self
+    }
+
+    /// Sets the kind of text of a string type, overriding the inferred one.
+    pub fn text_kind(mut self, type_name: impl Into<String>, kind: TextKind) -> Self {
+        self.text_kinds.insert(type_name.into(), kind);
+        self
+    }
+
+    /// Sets the weight of an enum value.
+    ///
+    /// Values are picked with a probability proportional to their weight. Values without a weight
+    /// have a weight of 1, or 0 if they are deprecated.
+    pub fn enum_weight(
+        mut self,
+        type_name: impl Into<String>,
+        value: impl Into<String>,
+        weight: u32,
+    ) -> Self {
+        self.enum_weights
+            .entry(type_name.into())
+            .or_default()
+            .insert(value.into(), weight);
+        self
+    }
+
+    /// Get the kind of text of a string type.
+    pub(crate) fn text_kind_for(&self, type_name: &str) -> TextKind {
+        match self.text_kinds.get(type_name) {
+            Some(kind) => *kind,
+            None if self.infer_text_kinds => TextKind::infer(type_name),
+            None => TextKind::Alphanumeric,
+        }
+    }
+
+    /// Get the weight of an enum value.
+    pub(crate) fn enum_weight_for(&self, type_name: &str, value: &str, deprecated: bool) -> u32 {
+        self.enum_weights
+            .get(type_name)
+            .and_then(|weights| weights.get(value))
+            .copied()
+            .unwrap_or(if deprecated { 0 } else { 1 })
+    }
+}
+
+/// The locale of generated text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SampleLocale {
+    /// English.
+    #[default]
+    English,
+
+    /// French.
+    French,
+
+    /// German.
+    German,
+
+    /// Japanese.
+    Japanese,
+}
+
+impl SampleLocale {
+    /// Get the given names of the locale.
+    fn given_names(self) -> &'static [&'static str] {
+        match self {
+            Self::English => &[
+                "Alice",
+                "Bob",
+                "Charlotte",
+                "Edmund",
+                "Grace",
+                "Oliver",
+                "Rose",
+            ],
+            Self::French => &[
+                "Amélie", "Baptiste", "Chloé", "Étienne", "Léa", "Mathis", "Zoé",
+            ],
+            Self::German => &["Anna", "Jürgen", "Greta", "Lukas", "Mia", "Ludwig", "Sönke"],
+            Self::Japanese => &["葵", "蓮", "陽菜", "湊", "結衣", "大和", "さくら"],
+        }
+    }
+
+    /// Get the family names of the locale.
+    fn family_names(self) -> &'static [&'static str] {
+        match self {
+            Self::English => &["Ashford", "Blackwood", "Carter", "Hale", "Morgan", "Thorne"],
+            Self::French => &[
+                "Bernard", "Dubois", "Fournier", "Laurent", "Lefèvre", "Moreau",
+            ],
+            Self::German => &["Becker", "Fischer", "Köhler", "Müller", "Schneider", "Weiß"],
+            Self::Japanese => &["佐藤", "鈴木", "高橋", "田中", "渡辺", "山本"],
+        }
+    }
+
+    /// Get the place names of the locale.
+    fn place_names(self) -> &'static [&'static str] {
+        match self {
+            Self::English => &[
+                "Ravenmoor",
+                "Stonebridge",
+                "Eastwatch",
+                "Mistfall",
+                "Oakhollow",
+            ],
+            Self::French => &[
+                "Val-Brume",
+                "Roche-Noire",
+                "Clairbois",
+                "Montfroid",
+                "Pierrelune",
+            ],
+            Self::German => &[
+                "Eisenfurt",
+                "Dunkelwald",
+                "Steinbach",
+                "Nebelhain",
+                "Wolfsburg",
+            ],
+            Self::Japanese => &["霧ヶ峰", "白石村", "黒川", "月見台", "風の谷"],
+        }
+    }
+
+    /// Get the common words of the locale.
+    fn words(self) -> &'static [&'static str] {
+        match self {
+            Self::English => &[
+                "ancient", "sword", "shield", "dragon", "forest", "iron", "shadow", "potion",
+                "crystal", "ember", "knight", "storm", "silver", "tower", "wolf", "frost",
+            ],
+            Self::French => &[
+                "ancienne",
+                "épée",
+                "bouclier",
+                "dragon",
+                "forêt",
+                "fer",
+                "ombre",
+                "potion",
+                "cristal",
+                "braise",
+                "chevalier",
+                "tempête",
+                "argent",
+                "tour",
+                "loup",
+                "givre",
+            ],
+            Self::German => &[
+                "uralt", "Schwert", "Schild", "Drache", "Wald", "Eisen", "Schatten", "Trank",
+                "Kristall", "Glut", "Ritter", "Sturm", "Silber", "Turm", "Wolf", "Frost",
+            ],
+            Self::Japanese => &[
+                "古代", "剣", "盾", "竜", "森", "鉄", "影", "薬", "水晶", "炎", "騎士", "嵐", "銀",
+                "塔", "狼", "霜",
+            ],
+        }
+    }
+
+    /// Get the separator between words, and the end of sentences.
+    fn punctuation(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Japanese => ("", "。"),
+            _ => (" ", "."),
+        }
+    }
+}
+
+/// The kind of text generated for a string type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextKind {
+    /// Random alphanumeric characters.
+    #[default]
+    Alphanumeric,
+
+    /// A single word.
+    Word,
+
+    /// The name of a person.
+    PersonName,
+
+    /// The name of a place.
+    PlaceName,
+
+    /// A sentence of a few words.
+    Sentence,
+
+    /// A lowercase identifier, such as `iron_sword_7`, whatever the locale.
+    Identifier,
+}
+
+impl TextKind {
+    /// Infer the kind of text of a string type from its name.
+    fn infer(type_name: &str) -> Self {
+        let name = type_name.to_lowercase();
+        let has = |hints: &[&str]| hints.iter().any(|hint| name.contains(hint));
+
+        if has(&[
+            "description",
+            "text",
+            "summary",
+            "lore",
+            "flavor",
+            "message",
+        ]) {
+            Self::Sentence
+        } else if has(&["city", "town", "region", "place", "location", "zone"]) {
+            Self::PlaceName
+        } else if has(&["name"]) {
+            Self::PersonName
+        } else if has(&["key", "slug", "tag", "code"]) || name.ends_with("id") {
+            Self::Identifier
+        } else {
+            Self::Word
+        }
+    }
+
+    /// Generate a text of this kind.
+    pub(crate) fn generate(self, locale: SampleLocale, rng: &mut impl Rng) -> String {
+        /// Pick a random entry of a non-empty list.
+        fn pick(list: &'static [&'static str], rng: &mut impl Rng) -> &'static str {
+            list.choose(rng).expect("the lists are not empty")
+        }
+
+        match self {
+            Self::Alphanumeric => {
+                let len = rng.random_range(0..=16);
+
+                rng.sample_iter(rand::distr::Alphanumeric)
+                    .take(len)
+                    .map(char::from)
+                    .collect()
+            }
+            Self::Word => pick(locale.words(), rng).to_owned(),
+            Self::PersonName => {
+                let (separator, _) = locale.punctuation();
+                let given = pick(locale.given_names(), rng);
+                let family = pick(locale.family_names(), rng);
+
+                // Japanese names are written family name first.
+                match locale {
+                    SampleLocale::Japanese => format!("{family}{separator}{given}"),
+                    _ => format!("{given}{separator}{family}"),
+                }
+            }
+            Self::PlaceName => pick(locale.place_names(), rng).to_owned(),
+            Self::Sentence => {
+                let (separator, end) = locale.punctuation();
+                let len = rng.random_range(3..=8);
+                let sentence = (0..len)
+                    .map(|_| pick(locale.words(), rng))
+                    .collect::<Vec<_>>()
+                    .join(separator);
+
+                let mut chars = sentence.chars();
+                let first = chars.next().map(char::to_uppercase).into_iter().flatten();
+
+                first.chain(chars).chain(end.chars()).collect()
+            }
+            Self::Identifier => format!(
+                "{}_{}_{}",
+                pick(SampleLocale::English.words(), rng),
+                pick(SampleLocale::English.words(), rng),
+                rng.random_range(1..100)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::{SampleLocale, SampleStrategy, TextKind};
+
+    #[test]
+    fn test_text_kinds() {
+        let strategy = SampleStrategy::realistic().text_kind("Motto", TextKind::Sentence);
+
+        assert_eq!(
+            strategy.text_kind_for("CharacterName"),
+            TextKind::PersonName
+        );
+        assert_eq!(
+            strategy.text_kind_for("ItemDescription"),
+            TextKind::Sentence
+        );
+        assert_eq!(strategy.text_kind_for("HomeTown"), TextKind::PlaceName);
+        assert_eq!(strategy.text_kind_for("LootTableId"), TextKind::Identifier);
+        assert_eq!(strategy.text_kind_for("Title"), TextKind::Word);
+        assert_eq!(strategy.text_kind_for("Motto"), TextKind::Sentence);
+        assert_eq!(
+            SampleStrategy::default().text_kind_for("CharacterName"),
+            TextKind::Alphanumeric
+        );
+
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let sentence = TextKind::Sentence.generate(SampleLocale::English, &mut rng);
+        assert!(sentence.ends_with('.'));
+        assert!(sentence.starts_with(char::is_uppercase));
+
+        let sentence = TextKind::Sentence.generate(SampleLocale::Japanese, &mut rng);
+        assert!(sentence.ends_with('。'));
+        assert!(!sentence.contains(' '));
+
+        let identifier = TextKind::Identifier.generate(SampleLocale::French, &mut rng);
+        assert!(
+            identifier
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Schema versioning and negotiation between peers.
+
+use std::{fmt::Display, hash::Hash};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Fingerprint, TypeDefinitionRegistry};
+
+/// The version of the schema of a registry.
+///
+/// Minor versions are expected to only make backward compatible changes, such as adding types,
+/// while major versions can make breaking changes, which require migrating the values.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct SchemaVersion {
+    /// The major version.
+    pub major: u32,
+
+    /// The minor version.
+    pub minor: u32,
+}
+
+impl SchemaVersion {
+    /// Create a schema version.
+    pub fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    /// Check whether this is the initial version, `0.0`.
+    pub(crate) fn is_initial(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Display for SchemaVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Whether the values of a peer can be read with the schema of a registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Negotiation {
+    /// The values can be read as-is.
+    Readable,
+
+    /// The values must be migrated from the schema version of the peer first.
+    NeedsMigration {
+        /// The schema version of the peer.
+        from: SchemaVersion,
+
+        /// The schema version of the registry.
+        to: SchemaVersion,
+    },
+
+    /// The values cannot be read.
+    Incompatible {
+        /// Why the values cannot be read.
+        reason: String,
+    },
+}
+
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display,
+    FieldName: Ord + Hash + Clone + Display + Serialize,
+{
+    /// Decide whether the values of a peer, such as a client, can be read with the schema of the
+    /// registry, given the fingerprint and the schema version of the peer's registry.
+    ///
+    /// The values are readable if both schemas have the same fingerprint, or if the peer's schema
+    /// is an older minor version of the same major version. Values of an older major version
+    /// need a migration. Values of a newer version are incompatible, as are values of a schema
+    /// with the same version but another fingerprint.
+    pub fn negotiate(&self, version: SchemaVersion, fingerprint: Fingerprint) -> Negotiation {
+        let current = self.schema_version;
+
+        if fingerprint == self.fingerprint() {
+            Negotiation::Readable
+        } else if version == current {
+            Negotiation::Incompatible {
+                reason: format!("schemas differ but have the same version {version}"),
+            }
+        } else if version > current {
+            Negotiation::Incompatible {
+                reason: format!("schema version {version} is newer than {current}"),
+            }
+        } else if version.major == current.major {
+            Negotiation::Readable
+        } else {
+            Negotiation::NeedsMigration {
+                from: version,
+                to: current,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Negotiation, SchemaVersion};
+    use crate::test_support::{TypeAttributes, TypeDefinitionRegistry, registry, td};
+
+    #[test]
+    fn test_negotiate() {
+        let mut registry = registry([td(1, "MyInt", TypeAttributes::Int32(Default::default()))]);
+        registry.set_schema_version(SchemaVersion::new(2, 1));
+        assert_eq!(registry.schema_version().to_string(), "2.1");
+
+        let fingerprint = registry.fingerprint();
+        let other = TypeDefinitionRegistry::default().fingerprint();
+
+        assert_eq!(
+            registry.negotiate(SchemaVersion::new(1, 0), fingerprint),
+            Negotiation::Readable
+        );
+        assert_eq!(
+            registry.negotiate(SchemaVersion::new(2, 0), other),
+            Negotiation::Readable
+        );
+        assert_eq!(
+            registry.negotiate(SchemaVersion::new(1, 3), other),
+            Negotiation::NeedsMigration {
+                from: SchemaVersion::new(1, 3),
+                to: SchemaVersion::new(2, 1),
+            }
+        );
+        assert_eq!(
+            registry.negotiate(SchemaVersion::new(2, 2), other),
+            Negotiation::Incompatible {
+                reason: "schema version 2.2 is newer than 2.1".to_owned()
+            }
+        );
+        assert_eq!(
+            registry.negotiate(SchemaVersion::new(2, 1), other),
+            Negotiation::Incompatible {
+                reason: "schemas differ but have the same version 2.1".to_owned()
+            }
+        );
+
+        // The schema version survives serialization.
+        let json = serde_json::to_value(&registry).unwrap();
+        assert_eq!(
+            json["schema_version"],
+            serde_json::json!({ "major": 2, "minor": 1 })
+        );
+    }
+}
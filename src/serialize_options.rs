@@ -0,0 +1,35 @@
+//! Options for serializing GameSON values.
+
+/// Options that control how GameSON values are serialized.
+///
+/// The default options are the strictest: serialization fails rather than produce a lossy
+/// output.
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    /// How non-finite floating-point numbers are serialized.
+    pub(crate) non_finite_floats: NonFiniteFloats,
+}
+
+impl SerializeOptions {
+    /// Sets how non-finite floating-point numbers are serialized.
+    pub fn non_finite_floats(mut self, non_finite_floats: NonFiniteFloats) -> Self {
+        self.non_finite_floats = non_finite_floats;
+        self
+    }
+}
+
+/// How non-finite floating-point numbers (NaN and infinities) are serialized.
+///
+/// JSON has no representation for them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonFiniteFloats {
+    /// Fail the serialization.
+    #[default]
+    Error,
+
+    /// Serialize them as `null`.
+    Null,
+
+    /// Serialize them as the `"NaN"`, `"Infinity"` and `"-Infinity"` strings.
+    String,
+}
@@ -0,0 +1,222 @@
+//! A type definition store backed by SQLite.
+
+use std::{fmt::Display, marker::PhantomData, path::Path};
+
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{TypeDefinition, TypeDefinitionStore};
+
+/// The migrations of the database schema, in order.
+///
+/// The schema version of a database is the number of migrations applied to it.
+const MIGRATIONS: &[&str] = &["CREATE TABLE type_definitions (
+        id TEXT NOT NULL PRIMARY KEY,
+        name TEXT NOT NULL,
+        json TEXT NOT NULL
+    );"];
+
+/// An error that can occur when accessing a SQLite store.
+#[derive(Debug, thiserror::Error)]
+pub enum SqliteStoreError {
+    /// The database could not be accessed.
+    #[error("database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    /// A type definition could not be converted from or to JSON.
+    #[error("invalid type definition: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The database schema is more recent than this version of the store supports.
+    #[error("unsupported schema version {0}, expected at most {max}", max = MIGRATIONS.len())]
+    UnsupportedSchemaVersion(usize),
+}
+
+/// A type definition store backed by a SQLite database.
+///
+/// The type definitions are stored as JSON in a single table, alongside their identifiers and
+/// names. The database schema is migrated when the store is opened.
+#[derive(Debug)]
+pub struct SqliteStore<Id, FieldName> {
+    /// The database connection.
+    connection: Connection,
+
+    /// The store is typed, although the database is not.
+    _marker: PhantomData<fn() -> (Id, FieldName)>,
+}
+
+impl<Id, FieldName> SqliteStore<Id, FieldName> {
+    /// Open the store in the specified database file, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SqliteStoreError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Open a store in a new in-memory database.
+    pub fn open_in_memory() -> Result<Self, SqliteStoreError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Open the store in an existing database connection.
+    pub fn from_connection(mut connection: Connection) -> Result<Self, SqliteStoreError> {
+        migrate(&mut connection)?;
+
+        Ok(Self {
+            connection,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Get the underlying database connection.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+}
+
+/// Apply the migrations that were not applied to a database yet.
+fn migrate(connection: &mut Connection) -> Result<(), SqliteStoreError> {
+    let version: usize = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    if version > MIGRATIONS.len() {
+        return Err(SqliteStoreError::UnsupportedSchemaVersion(version));
+    }
+
+    let transaction = connection.transaction()?;
+
+    for migration in &MIGRATIONS[version..] {
+        transaction.execute_batch(migration)?;
+    }
+
+    transaction.pragma_update(None, "user_version", MIGRATIONS.len())?;
+    transaction.commit()?;
+
+    Ok(())
+}
+
+impl<Id, FieldName> TypeDefinitionStore<Id, FieldName> for SqliteStore<Id, FieldName>
+where
+    Id: Serialize + DeserializeOwned,
+    FieldName: Ord + Display + Clone + Serialize + DeserializeOwned,
+{
+    type Error = SqliteStoreError;
+
+    fn load_all(&self) -> Result<Vec<TypeDefinition<Id, FieldName>>, Self::Error> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT json FROM type_definitions ORDER BY id")?;
+        let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+
+        rows.map(|json| Ok(serde_json::from_str(&json?)?)).collect()
+    }
+
+    fn load_by_id(&self, id: &Id) -> Result<Option<TypeDefinition<Id, FieldName>>, Self::Error> {
+        let json: Option<String> = self
+            .connection
+            .query_row(
+                "SELECT json FROM type_definitions WHERE id = ?1",
+                params![serde_json::to_string(id)?],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(json.map(|json| serde_json::from_str(&json)).transpose()?)
+    }
+
+    fn save(&mut self, type_definition: &TypeDefinition<Id, FieldName>) -> Result<(), Self::Error> {
+        self.connection.execute(
+            "INSERT INTO type_definitions (id, name, json) VALUES (?1, ?2, ?3)
+            ON CONFLICT (id) DO UPDATE SET name = excluded.name, json = excluded.json",
+            params![
+                serde_json::to_string(&type_definition.id)?,
+                type_definition.name.to_string(),
+                serde_json::to_string(type_definition)?,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn delete(&mut self, id: &Id) -> Result<bool, Self::Error> {
+        let count = self.connection.execute(
+            "DELETE FROM type_definitions WHERE id = ?1",
+            params![serde_json::to_string(id)?],
+        )?;
+
+        Ok(count > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{TypeDefinitionStore, type_attributes::ArrayTypeAttributes};
+
+    use super::SqliteStoreError;
+    use crate::test_support::owned::{TypeAttributes, TypeDefinition, TypeDefinitionRegistry, td};
+
+    type SqliteStore = super::SqliteStore<u32, String>;
+
+    #[test]
+    fn test_sqlite_store() {
+        let path = std::env::temp_dir().join(format!("gameson-sqlite-{}.db", std::process::id()));
+        let mut store = SqliteStore::open(&path).unwrap();
+
+        for td in [
+            td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+            TypeDefinition {
+                id: 2,
+                name: "MyIntArray".to_owned(),
+                description: Some("Some integers.".to_owned()),
+                attributes: TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            },
+        ] {
+            store.save(&td).unwrap();
+        }
+
+        store
+            .save(&td(
+                1,
+                "MyNumber",
+                TypeAttributes::Int64(Default::default()),
+            ))
+            .unwrap();
+        drop(store);
+
+        // Reopening the store does not migrate it again.
+        let mut store = SqliteStore::open(&path).unwrap();
+
+        let mut registry = TypeDefinitionRegistry::default();
+        assert!(registry.load_from_store(&store).unwrap().is_success());
+        assert_eq!(
+            registry.by_name["MyIntArray"].to_string(),
+            "MyIntArray(2): array(MyNumber(1): int64(..))"
+        );
+
+        assert_eq!(
+            store
+                .load_by_id(&2)
+                .unwrap()
+                .unwrap()
+                .description
+                .as_deref(),
+            Some("Some integers.")
+        );
+        assert!(store.delete(&2).unwrap());
+        assert!(!store.delete(&2).unwrap());
+        assert!(store.load_by_id(&2).unwrap().is_none());
+        assert_eq!(store.load_all().unwrap().len(), 1);
+
+        store
+            .connection()
+            .pragma_update(None, "user_version", 2)
+            .unwrap();
+        drop(store);
+
+        let err = SqliteStore::open(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(err, SqliteStoreError::UnsupportedSchemaVersion(2)));
+        assert_eq!(
+            err.to_string(),
+            "unsupported schema version 2, expected at most 1"
+        );
+    }
+}
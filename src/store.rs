@@ -0,0 +1,197 @@
+//! Persistence of type definitions.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::Infallible,
+    fmt::Display,
+    hash::Hash,
+};
+
+use crate::{RegistrationOutcome, TypeDefinition, TypeDefinitionRegistry};
+
+/// A storage of type definitions, such as files, a database or a remote service.
+///
+/// A registry can be [hydrated](TypeDefinitionRegistry::load_from_store) from any store, and
+/// [saved](TypeDefinitionRegistry::save_to_store) to any store.
+pub trait TypeDefinitionStore<Id, FieldName: Ord + Display + Clone> {
+    /// The error that can occur when accessing the store.
+    type Error: std::error::Error;
+
+    /// Load all the type definitions of the store.
+    fn load_all(&self) -> Result<Vec<TypeDefinition<Id, FieldName>>, Self::Error>;
+
+    /// Load the type definition with the specified identifier, if it exists.
+    fn load_by_id(&self, id: &Id) -> Result<Option<TypeDefinition<Id, FieldName>>, Self::Error>;
+
+    /// Save a type definition, replacing the one with the same identifier, if any.
+    fn save(&mut self, type_definition: &TypeDefinition<Id, FieldName>) -> Result<(), Self::Error>;
+
+    /// Delete the type definition with the specified identifier.
+    ///
+    /// Returns whether the type definition existed.
+    fn delete(&mut self, id: &Id) -> Result<bool, Self::Error>;
+}
+
+/// An in-memory store, which cannot fail.
+impl<Id: Ord + Clone, FieldName: Ord + Display + Clone> TypeDefinitionStore<Id, FieldName>
+    for BTreeMap<Id, TypeDefinition<Id, FieldName>>
+{
+    type Error = Infallible;
+
+    fn load_all(&self) -> Result<Vec<TypeDefinition<Id, FieldName>>, Self::Error> {
+        Ok(self.values().cloned().collect())
+    }
+
+    fn load_by_id(&self, id: &Id) -> Result<Option<TypeDefinition<Id, FieldName>>, Self::Error> {
+        Ok(self.get(id).cloned())
+    }
+
+    fn save(&mut self, type_definition: &TypeDefinition<Id, FieldName>) -> Result<(), Self::Error> {
+        self.insert(type_definition.id.clone(), type_definition.clone());
+
+        Ok(())
+    }
+
+    fn delete(&mut self, id: &Id) -> Result<bool, Self::Error> {
+        Ok(self.remove(id).is_some())
+    }
+}
+
+impl<Id: Ord + Hash + Clone + Display, FieldName: Ord + Hash + Clone + Display>
+    TypeDefinitionRegistry<Id, FieldName>
+{
+    /// Register all the type definitions of a store, as a single batch.
+    ///
+    /// See [`register`](Self::register) for details.
+    pub fn load_from_store<S: TypeDefinitionStore<Id, FieldName>>(
+        &mut self,
+        store: &S,
+    ) -> Result<RegistrationOutcome<Id, FieldName>, S::Error> {
+        Ok(self.register(store.load_all()?))
+    }
+
+    /// Register a type definition of a store, along with the type definitions it references that
+    /// are not registered yet, as a single batch.
+    ///
+    /// This allows hydrating a registry on demand from a large store. Referenced type
+    /// definitions that are missing from the store are reported as broken references. If the
+    /// type definition itself is missing from the store, nothing is registered.
+    pub fn load_from_store_by_id<S: TypeDefinitionStore<Id, FieldName>>(
+        &mut self,
+        store: &S,
+        id: &Id,
+    ) -> Result<RegistrationOutcome<Id, FieldName>, S::Error> {
+        let mut type_definitions = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut queue = vec![id.clone()];
+
+        while let Some(id) = queue.pop() {
+            if self.by_id.contains_key(&id) || !visited.insert(id.clone()) {
+                continue;
+            }
+
+            if let Some(td) = store.load_by_id(&id)? {
+                queue.extend(
+                    td.attributes
+                        .external_identifier_references()
+                        .into_iter()
+                        .cloned(),
+                );
+                type_definitions.push(td);
+            }
+        }
+
+        Ok(self.register(type_definitions))
+    }
+
+    /// Save all the registered type definitions to a store.
+    ///
+    /// The type definitions are saved in topological order, each one after the type definitions
+    /// it references. The type definitions of the store that are not registered are kept.
+    pub fn save_to_store<S: TypeDefinitionStore<Id, FieldName>>(
+        &self,
+        store: &mut S,
+    ) -> Result<(), S::Error> {
+        for td in self.to_definitions() {
+            store.save(&td)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        test_support::{TypeAttributes, TypeDefinitionRegistry, td},
+        type_attributes::ArrayTypeAttributes,
+    };
+
+    use super::TypeDefinitionStore;
+
+    #[test]
+    fn test_store() {
+        let mut store = BTreeMap::new();
+
+        for td in [
+            td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+            td(
+                2,
+                "MyIntArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(3, "MyString", TypeAttributes::String(Default::default())),
+            td(
+                4,
+                "MyBrokenArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(5)),
+            ),
+        ] {
+            store.save(&td).unwrap();
+        }
+
+        let mut registry = TypeDefinitionRegistry::default();
+
+        let outcome = registry.load_from_store_by_id(&store, &2).unwrap();
+        assert_eq!(
+            outcome
+                .registered()
+                .iter()
+                .map(|td| td.id)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        let outcome = registry.load_from_store_by_id(&store, &4).unwrap();
+        assert!(outcome.registered().is_empty());
+        assert_eq!(outcome.failed().len(), 1);
+
+        assert!(
+            registry
+                .load_from_store_by_id(&store, &6)
+                .unwrap()
+                .registered()
+                .is_empty()
+        );
+
+        let outcome = registry.load_from_store(&store).unwrap();
+        assert_eq!(
+            outcome
+                .registered()
+                .iter()
+                .map(|td| td.id)
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+
+        let mut saved = BTreeMap::new();
+        registry.save_to_store(&mut saved).unwrap();
+        assert_eq!(saved.keys().collect::<Vec<_>>(), vec![&1, &2, &3]);
+
+        assert!(saved.delete(&3).unwrap());
+        assert!(!saved.delete(&3).unwrap());
+        assert!(saved.load_by_id(&3).unwrap().is_none());
+    }
+}
@@ -0,0 +1,81 @@
+//! Fixtures shared by the tests of the crate.
+
+/// The type identifiers used by the tests.
+pub(crate) type Id = u32;
+
+/// The field names used by the tests.
+pub(crate) type FieldName = &'static str;
+
+pub(crate) type TypeDefinitionRegistry = crate::TypeDefinitionRegistry<Id, FieldName>;
+pub(crate) type TypeDefinitionInstance = crate::TypeDefinitionInstance<Id, FieldName>;
+pub(crate) type TypeDefinition = crate::TypeDefinition<Id, FieldName>;
+pub(crate) type TypeAttributes = crate::TypeAttributes<Id, FieldName>;
+pub(crate) type Value = crate::Value<Id, FieldName>;
+pub(crate) type Document = crate::Document<Id, FieldName>;
+
+/// Create a type definition, without description.
+pub(crate) fn td(id: Id, name: FieldName, attributes: TypeAttributes) -> TypeDefinition {
+    TypeDefinition {
+        id,
+        name,
+        description: None,
+        attributes,
+    }
+}
+
+/// Create a registry of type definitions, all of which must register successfully.
+pub(crate) fn registry(
+    type_definitions: impl IntoIterator<Item = TypeDefinition>,
+) -> TypeDefinitionRegistry {
+    let mut registry = TypeDefinitionRegistry::default();
+    registry.register(type_definitions).into_result().unwrap();
+
+    registry
+}
+
+/// The fixtures of the tests of the APIs that read field names from JSON, and thus need owned
+/// field names.
+pub(crate) mod owned {
+    pub(crate) use super::Id;
+
+    /// The owned field names used by the tests.
+    pub(crate) type FieldName = String;
+
+    pub(crate) type TypeDefinitionRegistry = crate::TypeDefinitionRegistry<Id, FieldName>;
+    pub(crate) type TypeDefinition = crate::TypeDefinition<Id, FieldName>;
+    pub(crate) type TypeAttributes = crate::TypeAttributes<Id, FieldName>;
+    pub(crate) type Value = crate::Value<Id, FieldName>;
+    pub(crate) type Document = crate::Document<Id, FieldName>;
+
+    /// Create a type definition, without description.
+    pub(crate) fn td(id: Id, name: &str, attributes: TypeAttributes) -> TypeDefinition {
+        TypeDefinition {
+            id,
+            name: name.to_owned(),
+            description: None,
+            attributes,
+        }
+    }
+
+    /// Create a registry of type definitions, all of which must register successfully.
+    #[cfg(any(feature = "zstd", feature = "cbor"))]
+    pub(crate) fn registry(
+        type_definitions: impl IntoIterator<Item = TypeDefinition>,
+    ) -> TypeDefinitionRegistry {
+        let mut registry = TypeDefinitionRegistry::default();
+        registry.register(type_definitions).into_result().unwrap();
+
+        registry
+    }
+
+    /// Create a registry from the JSON of its type definitions.
+    pub(crate) fn registry_from_json(
+        type_definitions: serde_json::Value,
+    ) -> TypeDefinitionRegistry {
+        serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "type_definitions": type_definitions,
+        }))
+        .unwrap()
+    }
+}
@@ -24,6 +24,18 @@ impl<Id> ArrayTypeAttributes<Id> {
     }
 }
 
+impl<Id> ArrayTypeAttributes<Id> {
+    /// Convert the items type identifier.
+    pub(crate) fn map_ids<Other>(
+        self,
+        mut f: impl FnMut(Id) -> Other,
+    ) -> ArrayTypeAttributes<Other> {
+        ArrayTypeAttributes {
+            items_type_id: f(self.items_type_id),
+        }
+    }
+}
+
 impl<Id: Display> Display for ArrayTypeAttributes<Id> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let Self { items_type_id } = self;
@@ -49,6 +61,16 @@ impl<Id: Ord> ArrayTypeAttributes<Id> {
     }
 }
 
+impl<Id: Clone, FieldName: Ord> ArrayTypeAttributes<Arc<TypeDefinitionInstance<Id, FieldName>>> {
+    /// Revert the instantiation of the array type attributes, referencing the items type by its
+    /// identifier again.
+    pub(crate) fn uninstantiate(&self) -> ArrayTypeAttributes<Id> {
+        ArrayTypeAttributes {
+            items_type_id: self.items_type_id.id.clone(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
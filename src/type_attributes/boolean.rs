@@ -3,7 +3,23 @@ use serde::{Deserialize, Serialize};
 /// Attributes for a boolean type.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
-pub struct BooleanTypeAttributes {}
+pub struct BooleanTypeAttributes {
+    /// The default value of the boolean.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<bool>,
+}
+
+impl BooleanTypeAttributes {
+    /// Create new boolean type attributes.
+    pub fn new(default: Option<bool>) -> Self {
+        Self { default }
+    }
+
+    /// Get the default value of the boolean, if any.
+    pub fn default_value(&self) -> Option<bool> {
+        self.default
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -19,5 +35,13 @@ mod tests {
 
         let t: BooleanTypeAttributes = serde_json::from_value(json).unwrap();
         assert_eq!(t, expected);
+
+        let expected = BooleanTypeAttributes::new(Some(true));
+
+        let json = serde_json::to_value(&expected).unwrap();
+        assert_eq!(json, json!({ "default": true }));
+
+        let t: BooleanTypeAttributes = serde_json::from_value(json).unwrap();
+        assert_eq!(t, expected);
     }
 }
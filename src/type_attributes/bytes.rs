@@ -0,0 +1,154 @@
+use std::fmt::Display;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// Attributes for a bytes type.
+///
+/// Bytes are represented in JSON as strings, in the declared encoding.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct BytesTypeAttributes {
+    /// The encoding of the bytes.
+    #[serde(default, skip_serializing_if = "BytesEncoding::is_base64")]
+    encoding: BytesEncoding,
+}
+
+impl BytesTypeAttributes {
+    /// Create new bytes type attributes.
+    pub fn new(encoding: BytesEncoding) -> Self {
+        Self { encoding }
+    }
+
+    /// Get the encoding of the bytes.
+    pub fn encoding(&self) -> BytesEncoding {
+        self.encoding
+    }
+}
+
+/// The encoding of bytes in a JSON string.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "snake_case")]
+pub enum BytesEncoding {
+    /// Standard base64, with padding.
+    #[default]
+    Base64,
+
+    /// URL-safe base64, without padding.
+    #[serde(rename = "base64url")]
+    Base64Url,
+
+    /// Lowercase hexadecimal.
+    ///
+    /// Uppercase digits are accepted when decoding.
+    Hex,
+}
+
+impl BytesEncoding {
+    /// Check whether the encoding is standard base64.
+    fn is_base64(&self) -> bool {
+        *self == Self::Base64
+    }
+
+    /// Encode bytes.
+    pub(crate) fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::Base64 => base64::engine::general_purpose::STANDARD.encode(bytes),
+            Self::Base64Url => base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes),
+            Self::Hex => hex::encode(bytes),
+        }
+    }
+
+    /// Decode bytes.
+    pub(crate) fn decode(&self, s: &str) -> Result<Vec<u8>, DecodeBytesError> {
+        match self {
+            Self::Base64 => base64::engine::general_purpose::STANDARD
+                .decode(s)
+                .map_err(|err| err.to_string()),
+            Self::Base64Url => base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(s)
+                .map_err(|err| err.to_string()),
+            Self::Hex => hex::decode(s).map_err(|err| err.to_string()),
+        }
+        .map_err(|message| DecodeBytesError {
+            encoding: *self,
+            message,
+        })
+    }
+}
+
+impl Display for BytesEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Base64 => "base64",
+            Self::Base64Url => "base64url",
+            Self::Hex => "hex",
+        })
+    }
+}
+
+/// An error that can occur when decoding bytes.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("not valid {encoding}: {message}")]
+pub struct DecodeBytesError {
+    /// The expected encoding.
+    encoding: BytesEncoding,
+
+    /// The reason the string could not be decoded.
+    message: String,
+}
+
+impl DecodeBytesError {
+    /// Get the expected encoding.
+    pub fn encoding(&self) -> BytesEncoding {
+        self.encoding
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{BytesEncoding, BytesTypeAttributes};
+
+    #[test]
+    fn test_serialization() {
+        let expected = BytesTypeAttributes::default();
+
+        let json = serde_json::to_value(&expected).unwrap();
+        assert_eq!(json, json!({}));
+
+        let t: BytesTypeAttributes = serde_json::from_value(json).unwrap();
+        assert_eq!(t, expected);
+
+        let expected = BytesTypeAttributes::new(BytesEncoding::Base64Url);
+
+        let json = serde_json::to_value(&expected).unwrap();
+        assert_eq!(json, json!({ "encoding": "base64url" }));
+
+        let t: BytesTypeAttributes = serde_json::from_value(json).unwrap();
+        assert_eq!(t, expected);
+    }
+
+    #[test]
+    fn test_encoding() {
+        let bytes = [0xfb, 0xff, 0x00, 0x10];
+
+        for (encoding, encoded) in [
+            (BytesEncoding::Base64, "+/8AEA=="),
+            (BytesEncoding::Base64Url, "-_8AEA"),
+            (BytesEncoding::Hex, "fbff0010"),
+        ] {
+            assert_eq!(encoding.encode(&bytes), encoded);
+            assert_eq!(encoding.decode(encoded).unwrap(), bytes);
+        }
+
+        assert_eq!(BytesEncoding::Hex.decode("FBFF0010").unwrap(), bytes);
+        assert!(BytesEncoding::Base64.decode("-_8AEA").is_err());
+        assert_eq!(
+            BytesEncoding::Hex.decode("fbf").unwrap_err().to_string(),
+            "not valid hex: Odd number of digits"
+        );
+    }
+}
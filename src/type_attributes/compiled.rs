@@ -0,0 +1,199 @@
+//! Entry rules of dictionaries, compiled when their types are instantiated.
+
+use std::{cmp::Ordering, collections::BTreeSet};
+
+use crate::ParseErrorKind;
+
+use super::{Comparison, EntryConstraint, EntryRequirement, Expression};
+
+/// The constraints, requirements and invariants between the entries of a dictionary type,
+/// compiled when the type is instantiated.
+///
+/// The keys referenced by the rules are resolved once, and the values of the requirements are
+/// converted for the values type, so checking a dictionary neither searches its entries once per
+/// rule nor serializes them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledEntryRules {
+    /// The keys referenced by the rules, sorted. The compiled rules reference keys by index.
+    keys: Box<[String]>,
+
+    /// The compiled constraints.
+    constraints: Box<[CompiledConstraint]>,
+
+    /// The compiled requirements.
+    requirements: Box<[CompiledRequirement]>,
+
+    /// The invariants, compiled when they were created.
+    invariants: Box<[Expression]>,
+}
+
+/// A compiled constraint between two entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CompiledConstraint {
+    /// The index of the left-hand key.
+    left: usize,
+
+    /// The index of the right-hand key.
+    right: usize,
+
+    /// The constraint, as declared.
+    constraint: EntryConstraint,
+}
+
+/// A compiled conditional requirement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CompiledRequirement {
+    /// The index of the key of the condition entry.
+    when: usize,
+
+    /// The value of the condition entry, for the values type.
+    equals: Scalar,
+
+    /// The indices of the keys of the required entries.
+    require: Box<[usize]>,
+
+    /// The requirement, as declared.
+    requirement: EntryRequirement,
+}
+
+/// A boolean, number or string value, converted for the values type of a dictionary.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Scalar {
+    /// A boolean.
+    Boolean(bool),
+
+    /// An integer, for integer values types.
+    Integer(i128),
+
+    /// A number, for floating point values types.
+    Float(f64),
+
+    /// A string or enum value.
+    Text(String),
+}
+
+// Scalars are converted from JSON numbers, which are never NaN.
+impl Eq for Scalar {}
+
+impl CompiledEntryRules {
+    /// Compile the rules of a dictionary type, given the values of its requirements converted for
+    /// its values type.
+    ///
+    /// The rules must have been checked against the keys and values types already.
+    pub(crate) fn new(
+        constraints: &[EntryConstraint],
+        requirements: &[EntryRequirement],
+        invariants: &[Expression],
+        equals: Vec<Scalar>,
+    ) -> Self {
+        let keys: BTreeSet<&str> =
+            constraints
+                .iter()
+                .flat_map(|c| [c.left(), c.right()])
+                .chain(requirements.iter().flat_map(|r| {
+                    std::iter::once(r.when()).chain(r.require().iter().map(|k| &**k))
+                }))
+                .chain(invariants.iter().flat_map(Expression::entries))
+                .collect();
+        let keys: Box<[String]> = keys.into_iter().map(str::to_owned).collect();
+
+        let index = |key: &str| {
+            keys.binary_search_by(|k| k.as_str().cmp(key))
+                .expect("all the keys were collected")
+        };
+
+        Self {
+            constraints: constraints
+                .iter()
+                .map(|constraint| CompiledConstraint {
+                    left: index(constraint.left()),
+                    right: index(constraint.right()),
+                    constraint: constraint.clone(),
+                })
+                .collect(),
+            requirements: requirements
+                .iter()
+                .zip(equals)
+                .map(|(requirement, equals)| CompiledRequirement {
+                    when: index(requirement.when()),
+                    equals,
+                    require: requirement.require().iter().map(|k| index(k)).collect(),
+                    requirement: requirement.clone(),
+                })
+                .collect(),
+            invariants: invariants.into(),
+            keys,
+        }
+    }
+
+    /// Get the keys referenced by the rules, sorted.
+    ///
+    /// The compiled rules reference keys by their index in this list.
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Get the compiled constraints, as the indices of their left-hand key, their comparison and
+    /// the index of their right-hand key.
+    pub fn constraints(&self) -> impl Iterator<Item = (usize, Comparison, usize)> {
+        self.constraints
+            .iter()
+            .map(|c| (c.left, c.constraint.op(), c.right))
+    }
+
+    /// Get the compiled requirements, as the index of the key of their condition entry and the
+    /// indices of the keys of the entries they require.
+    pub fn requirements(&self) -> impl Iterator<Item = (usize, &[usize])> {
+        self.requirements.iter().map(|r| (r.when, &*r.require))
+    }
+
+    /// Get the invariants.
+    pub fn invariants(&self) -> &[Expression] {
+        &self.invariants
+    }
+
+    /// Get the index of a key referenced by the rules.
+    pub(crate) fn index(&self, key: &str) -> Option<usize> {
+        self.keys.binary_search_by(|k| k.as_str().cmp(key)).ok()
+    }
+
+    /// Check the rules against the entries of a dictionary.
+    ///
+    /// The entries are given in the order of [`keys`](Self::keys), missing entries being `None`.
+    pub(crate) fn check<V: Copy>(
+        &self,
+        entries: &[Option<V>],
+        compare: impl Fn(V, V) -> Option<Ordering>,
+        equals: impl Fn(V, &Scalar) -> bool,
+        as_number: impl Fn(V) -> Option<f64>,
+    ) -> Result<(), ParseErrorKind> {
+        for c in &self.constraints {
+            if let (Some(left), Some(right)) = (entries[c.left], entries[c.right])
+                && !c.constraint.op().holds(compare(left, right))
+            {
+                return Err(ParseErrorKind::ConstraintViolation(c.constraint.clone()));
+            }
+        }
+
+        for r in &self.requirements {
+            if entries[r.when].is_some_and(|value| equals(value, &r.equals))
+                && let Some(&missing) = r.require.iter().find(|&&key| entries[key].is_none())
+            {
+                return Err(ParseErrorKind::MissingRequiredEntry {
+                    key: self.keys[missing].clone(),
+                    requirement: Box::new(r.requirement.clone()),
+                });
+            }
+        }
+
+        for invariant in &self.invariants {
+            let entry = |key: &str| as_number(entries[self.index(key)?]?);
+
+            if invariant.evaluate(entry) == Some(false) {
+                return Err(ParseErrorKind::InvariantViolation(invariant.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,10 +1,13 @@
-use std::{collections::BTreeMap, fmt::Display, sync::Arc};
+use std::{cmp::Ordering, collections::BTreeMap, fmt::Display, sync::Arc};
 
 use serde::{Deserialize, Serialize};
 
-use crate::TypeDefinitionInstance;
+use crate::{
+    TypeDefinitionInstance,
+    type_attributes_instance::{DictionaryTypeAttributesInstance, TypeAttributesInstance},
+};
 
-use super::{InstantiationError, InstantiationResult};
+use super::{CompiledEntryRules, Expression, InstantiationError, InstantiationResult, Scalar};
 
 /// Attributes for a dictionary type.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -15,6 +18,222 @@ pub struct DictionaryTypeAttributes<Id> {
 
     /// The values type identifier.
     values_type_id: Id,
+
+    /// The JSON encoding of the dictionary.
+    #[serde(default, skip_serializing_if = "DictionaryWireForm::is_object")]
+    wire_form: DictionaryWireForm,
+
+    /// The constraints between the entries of the dictionary.
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    constraints: Box<[EntryConstraint]>,
+
+    /// The entries that are required depending on the value of other entries.
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    requirements: Box<[EntryRequirement]>,
+
+    /// The invariants between the entries of the dictionary.
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    invariants: Box<[Expression]>,
+}
+
+/// A constraint between two entries of a dictionary keyed by an enum type, such as
+/// `min_damage <= max_damage`.
+///
+/// Such dictionaries have a known set of entries, like the fields of a structure. The constraint
+/// holds if either entry is missing. Ordering comparisons require the values type to be a number
+/// type, and never hold for NaN values.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "snake_case")]
+pub struct EntryConstraint {
+    /// The key of the left-hand entry.
+    left: String,
+
+    /// The comparison.
+    op: Comparison,
+
+    /// The key of the right-hand entry.
+    right: String,
+}
+
+impl EntryConstraint {
+    /// Create a constraint between two entries.
+    pub fn new(left: impl Into<String>, op: Comparison, right: impl Into<String>) -> Self {
+        Self {
+            left: left.into(),
+            op,
+            right: right.into(),
+        }
+    }
+
+    /// Get the key of the left-hand entry.
+    pub fn left(&self) -> &str {
+        &self.left
+    }
+
+    /// Get the comparison.
+    pub fn op(&self) -> Comparison {
+        self.op
+    }
+
+    /// Get the key of the right-hand entry.
+    pub fn right(&self) -> &str {
+        &self.right
+    }
+}
+
+impl Display for EntryConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.left, self.op, self.right)
+    }
+}
+
+/// A conditional requirement on the entries of a dictionary keyed by an enum type, such as
+/// `speed` being required when `kind` is `"projectile"`.
+///
+/// The condition holds when the entry `when` is present and its JSON representation equals
+/// `equals`, which must be a boolean, number, string or enum value of the values type. The required entries must then be
+/// present, and not null.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct EntryRequirement {
+    /// The key of the entry of the condition.
+    when: String,
+
+    /// The value of the entry for which the condition holds.
+    equals: serde_json::Value,
+
+    /// The keys of the entries that are required when the condition holds.
+    require: Vec<String>,
+}
+
+impl EntryRequirement {
+    /// Create a requirement of entries, when another entry has a specified value.
+    pub fn new(
+        when: impl Into<String>,
+        equals: serde_json::Value,
+        require: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            when: when.into(),
+            equals,
+            require: require.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Get the key of the entry of the condition.
+    pub fn when(&self) -> &str {
+        &self.when
+    }
+
+    /// Get the value of the entry for which the condition holds.
+    pub fn equals(&self) -> &serde_json::Value {
+        &self.equals
+    }
+
+    /// Get the keys of the entries that are required when the condition holds.
+    pub fn require(&self) -> &[String] {
+        &self.require
+    }
+}
+
+impl Display for EntryRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} == {} requires {}",
+            self.when,
+            self.equals,
+            self.require.join(", ")
+        )
+    }
+}
+
+/// A comparison between two values.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Comparison {
+    /// The values are equal.
+    #[serde(rename = "==")]
+    Equal,
+
+    /// The values are different.
+    #[serde(rename = "!=")]
+    NotEqual,
+
+    /// The left-hand value is less than the right-hand one.
+    #[serde(rename = "<")]
+    Less,
+
+    /// The left-hand value is less than or equal to the right-hand one.
+    #[serde(rename = "<=")]
+    LessOrEqual,
+
+    /// The left-hand value is greater than the right-hand one.
+    #[serde(rename = ">")]
+    Greater,
+
+    /// The left-hand value is greater than or equal to the right-hand one.
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// Check whether the comparison only tests for equality.
+    pub(crate) fn is_equality(self) -> bool {
+        matches!(self, Self::Equal | Self::NotEqual)
+    }
+
+    /// Check whether the comparison holds for two values, given how they compare, if they do.
+    pub(crate) fn holds(self, ordering: Option<Ordering>) -> bool {
+        match self {
+            Self::Equal => ordering == Some(Ordering::Equal),
+            Self::NotEqual => ordering != Some(Ordering::Equal),
+            Self::Less => ordering == Some(Ordering::Less),
+            Self::LessOrEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Self::Greater => ordering == Some(Ordering::Greater),
+            Self::GreaterOrEqual => {
+                matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+            }
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterOrEqual => ">=",
+        })
+    }
+}
+
+/// The JSON encoding of a dictionary.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "snake_case")]
+pub enum DictionaryWireForm {
+    /// A JSON object.
+    ///
+    /// Keys must be of a type that serializes as a string.
+    #[default]
+    Object,
+
+    /// A JSON array of `[key, value]` pairs.
+    ///
+    /// Keys can be of any type.
+    Pairs,
+}
+
+impl DictionaryWireForm {
+    /// Check whether the wire form is a JSON object.
+    fn is_object(&self) -> bool {
+        *self == Self::Object
+    }
 }
 
 impl<Id> DictionaryTypeAttributes<Id> {
@@ -23,9 +242,78 @@ impl<Id> DictionaryTypeAttributes<Id> {
         Self {
             keys_type_id,
             values_type_id,
+            wire_form: DictionaryWireForm::Object,
+            constraints: Box::default(),
+            requirements: Box::default(),
+            invariants: Box::default(),
         }
     }
 
+    /// Sets the JSON encoding of the dictionary.
+    pub fn with_wire_form(mut self, wire_form: DictionaryWireForm) -> Self {
+        self.wire_form = wire_form;
+        self
+    }
+
+    /// Adds a constraint between two entries of the dictionary.
+    ///
+    /// The keys type must be an enum type, and the keys of the entries must be among its values.
+    pub fn with_constraint(mut self, constraint: EntryConstraint) -> Self {
+        let mut constraints = std::mem::take(&mut self.constraints).into_vec();
+        constraints.push(constraint);
+        self.constraints = constraints.into();
+
+        self
+    }
+
+    /// Get the constraints between the entries of the dictionary.
+    pub fn constraints(&self) -> &[EntryConstraint] {
+        &self.constraints
+    }
+
+    /// Adds a conditional requirement on the entries of the dictionary.
+    ///
+    /// The keys type must be an enum type, and the keys of the entries must be among its values.
+    pub fn with_requirement(mut self, requirement: EntryRequirement) -> Self {
+        let mut requirements = std::mem::take(&mut self.requirements).into_vec();
+        requirements.push(requirement);
+        self.requirements = requirements.into();
+
+        self
+    }
+
+    /// Get the conditional requirements on the entries of the dictionary.
+    pub fn requirements(&self) -> &[EntryRequirement] {
+        &self.requirements
+    }
+
+    /// Adds an invariant between the entries of the dictionary.
+    ///
+    /// The keys type must be an enum type, the keys of the entries must be among its values, and
+    /// the values type must be a number type.
+    pub fn with_invariant(mut self, invariant: Expression) -> Self {
+        let mut invariants = std::mem::take(&mut self.invariants).into_vec();
+        invariants.push(invariant);
+        self.invariants = invariants.into();
+
+        self
+    }
+
+    /// Get the invariants between the entries of the dictionary.
+    pub fn invariants(&self) -> &[Expression] {
+        &self.invariants
+    }
+
+    /// Check whether the dictionary has constraints, requirements or invariants on its entries.
+    pub(crate) fn has_entry_rules(&self) -> bool {
+        !self.constraints.is_empty() || !self.requirements.is_empty() || !self.invariants.is_empty()
+    }
+
+    /// Get the JSON encoding of the dictionary.
+    pub fn wire_form(&self) -> DictionaryWireForm {
+        self.wire_form
+    }
+
     /// Get the keys type identifier.
     pub fn keys_type_id(&self) -> &Id {
         &self.keys_type_id
@@ -37,14 +325,53 @@ impl<Id> DictionaryTypeAttributes<Id> {
     }
 }
 
+impl<Id> DictionaryTypeAttributes<Id> {
+    /// Convert the keys and values type identifiers.
+    pub(crate) fn map_ids<Other>(
+        self,
+        mut f: impl FnMut(Id) -> Other,
+    ) -> DictionaryTypeAttributes<Other> {
+        DictionaryTypeAttributes {
+            keys_type_id: f(self.keys_type_id),
+            values_type_id: f(self.values_type_id),
+            wire_form: self.wire_form,
+            constraints: self.constraints,
+            requirements: self.requirements,
+            invariants: self.invariants,
+        }
+    }
+}
+
 impl<Id: Display> Display for DictionaryTypeAttributes<Id> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let Self {
             keys_type_id,
             values_type_id,
+            wire_form,
+            constraints,
+            requirements,
+            invariants,
         } = self;
 
-        write!(f, "({keys_type_id}, {values_type_id})")
+        write!(f, "({keys_type_id}, {values_type_id}")?;
+
+        if *wire_form == DictionaryWireForm::Pairs {
+            f.write_str(", pairs")?;
+        }
+
+        for constraint in constraints {
+            write!(f, ", {constraint}")?;
+        }
+
+        for requirement in requirements {
+            write!(f, ", {requirement}")?;
+        }
+
+        for invariant in invariants {
+            write!(f, ", {invariant}")?;
+        }
+
+        f.write_str(")")
     }
 }
 
@@ -56,16 +383,12 @@ impl<Id: Ord + Clone + Display> DictionaryTypeAttributes<Id> {
     pub(crate) fn instantiate<FieldName: Ord + Clone + Display>(
         &self,
         mut refs_by_id: BTreeMap<Id, Arc<TypeDefinitionInstance<Id, FieldName>>>,
-    ) -> InstantiationResult<
-        DictionaryTypeAttributes<Arc<TypeDefinitionInstance<Id, FieldName>>>,
-        Id,
-        FieldName,
-    > {
+    ) -> InstantiationResult<DictionaryTypeAttributesInstance<Id, FieldName>, Id, FieldName> {
         let keys_type_id = refs_by_id
             .remove(&self.keys_type_id)
             .expect("keys_type_id not found");
 
-        if !keys_type_id.attributes.is_key_type() {
+        if self.wire_form == DictionaryWireForm::Object && !keys_type_id.attributes.is_key_type() {
             return Err(InstantiationError::InappropriateKeyType {
                 key_type_id: keys_type_id.id.clone(),
                 key_type_name: keys_type_id.name.clone(),
@@ -77,10 +400,172 @@ impl<Id: Ord + Clone + Display> DictionaryTypeAttributes<Id> {
             .remove(&self.values_type_id)
             .expect("values_type_id not found");
 
-        Ok(DictionaryTypeAttributes {
-            keys_type_id,
-            values_type_id,
-        })
+        for constraint in &self.constraints {
+            let invalid = |reason: String| InstantiationError::InvalidConstraint {
+                constraint: constraint.to_string(),
+                reason,
+            };
+
+            let TypeAttributesInstance::Enum(keys) = &keys_type_id.attributes else {
+                return Err(invalid(format!(
+                    "the keys type `{}` is not an enum type",
+                    keys_type_id.name
+                )));
+            };
+
+            for key in [&constraint.left, &constraint.right] {
+                if !keys.values().any(|value| value.to_string() == *key) {
+                    return Err(invalid(format!(
+                        "`{key}` is not a value of the keys type `{}`",
+                        keys_type_id.name
+                    )));
+                }
+            }
+
+            if !constraint.op.is_equality() && !values_type_id.attributes.is_number() {
+                return Err(invalid(format!(
+                    "the values type `{}` is not a number type",
+                    values_type_id.name
+                )));
+            }
+        }
+
+        let mut equals = Vec::with_capacity(self.requirements.len());
+
+        for requirement in &self.requirements {
+            let invalid = |reason: String| InstantiationError::InvalidRequirement {
+                requirement: requirement.to_string(),
+                reason,
+            };
+
+            let TypeAttributesInstance::Enum(keys) = &keys_type_id.attributes else {
+                return Err(invalid(format!(
+                    "the keys type `{}` is not an enum type",
+                    keys_type_id.name
+                )));
+            };
+
+            for key in std::iter::once(&requirement.when).chain(&requirement.require) {
+                if !keys.values().any(|value| value.to_string() == *key) {
+                    return Err(invalid(format!(
+                        "`{key}` is not a value of the keys type `{}`",
+                        keys_type_id.name
+                    )));
+                }
+            }
+
+            match scalar_of(&values_type_id.attributes, &requirement.equals) {
+                Some(value) => equals.push(value),
+                None => {
+                    return Err(invalid(format!(
+                        "`{}` is not a boolean, number, string or enum value of the values type `{}`",
+                        requirement.equals, values_type_id.name
+                    )));
+                }
+            }
+        }
+
+        for invariant in &self.invariants {
+            let invalid = |reason: String| InstantiationError::InvalidInvariant {
+                invariant: invariant.to_string(),
+                reason,
+            };
+
+            let TypeAttributesInstance::Enum(keys) = &keys_type_id.attributes else {
+                return Err(invalid(format!(
+                    "the keys type `{}` is not an enum type",
+                    keys_type_id.name
+                )));
+            };
+
+            for key in invariant.entries() {
+                if !keys.values().any(|value| value.to_string() == key) {
+                    return Err(invalid(format!(
+                        "`{key}` is not a value of the keys type `{}`",
+                        keys_type_id.name
+                    )));
+                }
+            }
+
+            if !values_type_id.attributes.is_number() {
+                return Err(invalid(format!(
+                    "the values type `{}` is not a number type",
+                    values_type_id.name
+                )));
+            }
+        }
+
+        let compiled = self.has_entry_rules().then(|| {
+            CompiledEntryRules::new(
+                &self.constraints,
+                &self.requirements,
+                &self.invariants,
+                equals,
+            )
+        });
+
+        Ok(DictionaryTypeAttributesInstance::new(
+            DictionaryTypeAttributes {
+                keys_type_id,
+                values_type_id,
+                wire_form: self.wire_form,
+                constraints: self.constraints.clone(),
+                requirements: self.requirements.clone(),
+                invariants: self.invariants.clone(),
+            },
+            compiled,
+        ))
+    }
+}
+
+impl<Id: Clone, FieldName: Ord>
+    DictionaryTypeAttributes<Arc<TypeDefinitionInstance<Id, FieldName>>>
+{
+    /// Revert the instantiation of the dictionary type attributes, referencing the keys and
+    /// values types by their identifiers again.
+    pub(crate) fn uninstantiate(&self) -> DictionaryTypeAttributes<Id> {
+        DictionaryTypeAttributes {
+            keys_type_id: self.keys_type_id.id.clone(),
+            values_type_id: self.values_type_id.id.clone(),
+            wire_form: self.wire_form,
+            constraints: self.constraints.clone(),
+            requirements: self.requirements.clone(),
+            invariants: self.invariants.clone(),
+        }
+    }
+}
+
+/// Convert a JSON value for a type, if it is a boolean, number, string or enum value of the type.
+///
+/// The constraints of the type, other than the values of enum types, are not checked.
+fn scalar_of<Id, FieldName: Ord + Display>(
+    attributes: &TypeAttributesInstance<Id, FieldName>,
+    value: &serde_json::Value,
+) -> Option<Scalar> {
+    match (attributes, value) {
+        (TypeAttributesInstance::Boolean(_), serde_json::Value::Bool(v)) => {
+            Some(Scalar::Boolean(*v))
+        }
+        (
+            TypeAttributesInstance::Int32(_) | TypeAttributesInstance::Int64(_),
+            serde_json::Value::Number(n),
+        ) => n.as_i64().map(|n| Scalar::Integer(n.into())),
+        (
+            TypeAttributesInstance::Uint32(_) | TypeAttributesInstance::Uint64(_),
+            serde_json::Value::Number(n),
+        ) => n.as_u64().map(|n| Scalar::Integer(n.into())),
+        (
+            TypeAttributesInstance::Float32(_) | TypeAttributesInstance::Float64(_),
+            serde_json::Value::Number(n),
+        ) => n.as_f64().map(Scalar::Float),
+        (TypeAttributesInstance::String(_), serde_json::Value::String(v)) => {
+            Some(Scalar::Text(v.clone()))
+        }
+        (TypeAttributesInstance::Enum(e), serde_json::Value::String(v)) => e
+            .values()
+            .any(|value| value.to_string() == *v)
+            .then(|| Scalar::Text(v.clone())),
+        _ => None,
     }
 }
 
@@ -88,6 +573,8 @@ impl<Id: Ord + Clone + Display> DictionaryTypeAttributes<Id> {
 mod tests {
     use serde_json::json;
 
+    use super::DictionaryWireForm;
+
     type DictionaryTypeAttributes = super::DictionaryTypeAttributes<u32>;
 
     #[test]
@@ -105,5 +592,21 @@ mod tests {
 
         let t: DictionaryTypeAttributes = serde_json::from_value(json).unwrap();
         assert_eq!(t, expected);
+
+        let expected =
+            DictionaryTypeAttributes::new(1, 2).with_wire_form(DictionaryWireForm::Pairs);
+
+        let json = serde_json::to_value(&expected).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "keys_type_id": 1,
+                "values_type_id": 2,
+                "wire_form": "pairs",
+            })
+        );
+
+        let t: DictionaryTypeAttributes = serde_json::from_value(json).unwrap();
+        assert_eq!(t, expected);
     }
 }
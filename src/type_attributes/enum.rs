@@ -21,6 +21,8 @@ use serde::{Deserialize, Serialize};
 /// Aliases can never overlap with other enum names.
 ///
 /// Empty enum types are allowed, although no value will satisfy their parsing requirements.
+///
+/// An enum type can declare a default value, which must be one of its values.
 #[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub struct EnumTypeAttributes<EnumName: Ord> {
@@ -31,6 +33,10 @@ pub struct EnumTypeAttributes<EnumName: Ord> {
     /// The aliases of the enum.
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     aliases: BTreeMap<EnumName, EnumName>,
+
+    /// The default value of the enum.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<EnumName>,
 }
 
 impl<EnumName: Ord + Display> Display for EnumTypeAttributes<EnumName> {
@@ -54,6 +60,68 @@ impl<EnumName: Ord> EnumTypeAttributes<EnumName> {
     pub fn builder() -> EnumTypeAttributesBuilder<EnumName> {
         EnumTypeAttributesBuilder::default()
     }
+
+    /// Get the default value of the enum, if any.
+    pub fn default_value(&self) -> Option<&EnumName> {
+        self.default.as_ref()
+    }
+
+    /// Get the values of the enum.
+    pub fn values(&self) -> impl Iterator<Item = &EnumName> {
+        self.values.keys()
+    }
+
+    /// Get the aliases of the enum, with the values they stand for.
+    pub fn aliases(&self) -> impl Iterator<Item = (&EnumName, &EnumName)> {
+        self.aliases.iter()
+    }
+
+    /// Check whether an enum value is deprecated.
+    pub fn is_deprecated<Q>(&self, name: &Q) -> bool
+    where
+        EnumName: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.values.get(name).is_some_and(|value| value.deprecated)
+    }
+
+    /// Get the description of an enum value, if any.
+    pub fn value_description<Q>(&self, name: &Q) -> Option<&str>
+    where
+        EnumName: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.values.get(name)?.description.as_deref()
+    }
+
+    /// Resolve a name to its canonical enum value.
+    ///
+    /// The name can either be a value or an alias. If the name is neither, `None` is returned.
+    pub fn resolve<Q>(&self, name: &Q) -> Option<&EnumName>
+    where
+        EnumName: std::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.values.get_key_value(name) {
+            Some((value, _)) => Some(value),
+            None => self.aliases.get(name),
+        }
+    }
+
+    /// Estimate the heap memory used by the values and aliases, in bytes, not counting the
+    /// memory owned by the names themselves.
+    pub(crate) fn heap_size_bytes(&self) -> usize {
+        let values = self
+            .values
+            .values()
+            .map(|value| {
+                size_of::<(EnumName, EnumTypeValue)>()
+                    + value.description.as_ref().map_or(0, String::capacity)
+            })
+            .sum::<usize>();
+
+        values + self.aliases.len() * size_of::<(EnumName, EnumName)>()
+    }
 }
 
 /// An error that can occur when instantiating enum type attributes.
@@ -66,6 +134,10 @@ pub enum NewEnumTypeAttributesError<EnumName> {
     /// An enum alias points to a non-existant value.
     #[error("enum alias `{0}` points to a non-existant value `{1}`")]
     EnumAliasPointsToNonExistantValue(EnumName, EnumName),
+
+    /// The enum default is not an enum value.
+    #[error("enum default `{0}` is not an enum value")]
+    DefaultIsNotAValue(EnumName),
 }
 
 impl<EnumName: Ord + Display + Clone> EnumTypeAttributes<EnumName> {
@@ -76,9 +148,11 @@ impl<EnumName: Ord + Display + Clone> EnumTypeAttributes<EnumName> {
     /// This function will return an error if:
     /// - An enum value is also an alias.
     /// - An enum alias points to a non-existant value.
+    /// - The enum default is not an enum value.
     fn new(
         values: BTreeMap<EnumName, EnumTypeValue>,
         aliases: BTreeMap<EnumName, EnumName>,
+        default: Option<EnumName>,
     ) -> Result<Self, NewEnumTypeAttributesError<EnumName>> {
         for (alias, value) in &aliases {
             if values.contains_key(alias) {
@@ -95,7 +169,19 @@ impl<EnumName: Ord + Display + Clone> EnumTypeAttributes<EnumName> {
             }
         }
 
-        Ok(Self { values, aliases })
+        if let Some(default) = &default
+            && !values.contains_key(default)
+        {
+            return Err(NewEnumTypeAttributesError::DefaultIsNotAValue(
+                default.clone(),
+            ));
+        }
+
+        Ok(Self {
+            values,
+            aliases,
+            default,
+        })
     }
 }
 
@@ -113,11 +199,13 @@ impl<'de, EnumName: Ord + Display + Clone + Deserialize<'de>> Deserialize<'de>
             values: BTreeMap<T, EnumTypeValue>,
             #[serde(default = "BTreeMap::new")]
             aliases: BTreeMap<T, T>,
+            default: Option<T>,
         }
 
         let x = X::deserialize(deserializer)?;
 
-        Self::new(x.values, x.aliases).map_err(|err| serde::de::Error::custom(err.to_string()))
+        Self::new(x.values, x.aliases, x.default)
+            .map_err(|err| serde::de::Error::custom(err.to_string()))
     }
 }
 
@@ -141,6 +229,9 @@ pub struct EnumTypeAttributesBuilder<EnumName> {
 
     /// The aliases of the enum.
     aliases: BTreeMap<EnumName, EnumName>,
+
+    /// The default value of the enum.
+    default: Option<EnumName>,
 }
 
 impl<EnumName> Default for EnumTypeAttributesBuilder<EnumName> {
@@ -148,6 +239,7 @@ impl<EnumName> Default for EnumTypeAttributesBuilder<EnumName> {
         Self {
             values: Default::default(),
             aliases: Default::default(),
+            default: None,
         }
     }
 }
@@ -182,11 +274,17 @@ impl<EnumName: Ord + Display + Clone> EnumTypeAttributesBuilder<EnumName> {
         self
     }
 
+    /// Set the default value of the enum type.
+    pub fn with_default(mut self, name: EnumName) -> Self {
+        self.default = Some(name);
+        self
+    }
+
     /// Builds the enum type.
     pub fn build(
         self,
     ) -> Result<EnumTypeAttributes<EnumName>, NewEnumTypeAttributesError<EnumName>> {
-        EnumTypeAttributes::new(self.values, self.aliases)
+        EnumTypeAttributes::new(self.values, self.aliases, self.default)
     }
 }
 
@@ -200,7 +298,7 @@ mod tests {
 
     #[test]
     fn test_validation() {
-        EnumTypeAttributes::new(Default::default(), Default::default()).unwrap();
+        EnumTypeAttributes::new(Default::default(), Default::default(), None).unwrap();
 
         EnumTypeAttributes::new(
             [(
@@ -213,6 +311,7 @@ mod tests {
             .into_iter()
             .collect(),
             [("bar", "foo")].into_iter().collect(),
+            Some("foo"),
         )
         .unwrap();
 
@@ -228,6 +327,7 @@ mod tests {
                 .into_iter()
                 .collect(),
                 [("foo", "bar")].into_iter().collect(),
+                None,
             )
             .unwrap_err(),
             NewEnumTypeAttributesError::EnumValueIsAlias("foo")
@@ -245,10 +345,29 @@ mod tests {
                 .into_iter()
                 .collect(),
                 [("bar", "zoo")].into_iter().collect(),
+                None,
             )
             .unwrap_err(),
             NewEnumTypeAttributesError::EnumAliasPointsToNonExistantValue("bar", "zoo")
         ));
+
+        assert!(matches!(
+            EnumTypeAttributes::new(
+                [(
+                    "foo",
+                    EnumTypeValue {
+                        description: None,
+                        deprecated: false
+                    }
+                )]
+                .into_iter()
+                .collect(),
+                [("bar", "foo")].into_iter().collect(),
+                Some("bar"),
+            )
+            .unwrap_err(),
+            NewEnumTypeAttributesError::DefaultIsNotAValue("bar")
+        ));
     }
 
     #[test]
@@ -266,6 +385,7 @@ mod tests {
             .into_iter()
             .collect(),
             [("bar".to_owned(), "foo".to_owned())].into_iter().collect(),
+            Some("foo".to_owned()),
         )
         .unwrap();
 
@@ -279,6 +399,7 @@ mod tests {
                 "aliases": {
                     "bar": "foo",
                 },
+                "default": "foo",
             })
         );
 
@@ -0,0 +1,446 @@
+//! Expressions over the entries of dictionaries.
+
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use super::Comparison;
+
+/// The maximum nesting depth of the operands of an expression.
+const MAX_DEPTH: usize = 32;
+
+/// An invariant between the entries of a dictionary keyed by an enum type, such as
+/// `max_damage - min_damage <= 10`.
+///
+/// An expression compares two operands with one of `==`, `!=`, `<`, `<=`, `>` and `>=`. Operands
+/// are built from numbers, entry keys, the `+`, `-`, `*`, `/` and `%` operators and parentheses.
+/// Keys that are not identifiers are quoted with backticks, as in `` `max-damage` ``.
+///
+/// Expressions are compiled when they are created or deserialized, and serialize back to their
+/// source. They are evaluated with `f64` arithmetic, and hold if an entry they reference is
+/// missing. There are no functions nor loops: evaluating an expression takes a time proportional
+/// to its length.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Expression {
+    /// The source of the expression.
+    source: String,
+
+    /// The compiled expression.
+    compiled: Arc<Compiled>,
+}
+
+/// A compiled expression.
+#[derive(Debug)]
+struct Compiled {
+    /// The left-hand operand.
+    left: Operand,
+
+    /// The comparison.
+    op: Comparison,
+
+    /// The right-hand operand.
+    right: Operand,
+}
+
+/// An arithmetic operand of an expression.
+#[derive(Debug)]
+enum Operand {
+    /// A number.
+    Number(f64),
+
+    /// The value of an entry, by key.
+    Entry(String),
+
+    /// The negation of an operand.
+    Negate(Box<Operand>),
+
+    /// An arithmetic operation.
+    Binary(Box<Operand>, Operator, Box<Operand>),
+}
+
+/// An arithmetic operator.
+#[derive(Debug, Clone, Copy)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+/// An error that can occur when compiling an expression.
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+#[error("invalid expression at offset {offset}: {reason}")]
+pub struct ExpressionError {
+    /// The byte offset of the error in the source.
+    offset: usize,
+
+    /// Why the expression is invalid.
+    reason: &'static str,
+}
+
+impl ExpressionError {
+    /// Get the byte offset of the error in the source.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Get why the expression is invalid.
+    pub fn reason(&self) -> &'static str {
+        self.reason
+    }
+}
+
+impl Expression {
+    /// Compile an expression.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the source is not a valid expression.
+    pub fn new(source: impl Into<String>) -> Result<Self, ExpressionError> {
+        let source = source.into();
+        let compiled = Parser::new(&source).parse()?;
+
+        Ok(Self {
+            source,
+            compiled: Arc::new(compiled),
+        })
+    }
+
+    /// Get the source of the expression.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Get the keys of the entries referenced by the expression.
+    pub fn entries(&self) -> Vec<&str> {
+        let mut entries = Vec::new();
+
+        self.compiled.left.collect_entries(&mut entries);
+        self.compiled.right.collect_entries(&mut entries);
+
+        entries
+    }
+
+    /// Evaluate the expression, given the values of the entries.
+    ///
+    /// Returns `None` if an entry referenced by the expression is missing.
+    pub(crate) fn evaluate(&self, entry: impl Fn(&str) -> Option<f64>) -> Option<bool> {
+        let left = self.compiled.left.evaluate(&entry)?;
+        let right = self.compiled.right.evaluate(&entry)?;
+
+        Some(self.compiled.op.holds(left.partial_cmp(&right)))
+    }
+}
+
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for Expression {}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for Expression {
+    type Err = ExpressionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for Expression {
+    type Error = ExpressionError;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::new(source)
+    }
+}
+
+impl From<Expression> for String {
+    fn from(expression: Expression) -> Self {
+        expression.source
+    }
+}
+
+impl Operand {
+    /// Collect the keys of the entries referenced by the operand.
+    fn collect_entries<'a>(&'a self, entries: &mut Vec<&'a str>) {
+        match self {
+            Self::Number(_) => {}
+            Self::Entry(key) => entries.push(key),
+            Self::Negate(operand) => operand.collect_entries(entries),
+            Self::Binary(left, _, right) => {
+                left.collect_entries(entries);
+                right.collect_entries(entries);
+            }
+        }
+    }
+
+    /// Evaluate the operand, given the values of the entries.
+    fn evaluate(&self, entry: &impl Fn(&str) -> Option<f64>) -> Option<f64> {
+        Some(match self {
+            Self::Number(v) => *v,
+            Self::Entry(key) => entry(key)?,
+            Self::Negate(operand) => -operand.evaluate(entry)?,
+            Self::Binary(left, op, right) => {
+                let (left, right) = (left.evaluate(entry)?, right.evaluate(entry)?);
+
+                match op {
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    Operator::Divide => left / right,
+                    Operator::Remainder => left % right,
+                }
+            }
+        })
+    }
+}
+
+/// A recursive descent parser of expressions.
+struct Parser<'a> {
+    /// The source of the expression.
+    source: &'a str,
+
+    /// The byte offset of the next character.
+    offset: usize,
+
+    /// The nesting depth of the operand being parsed.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            offset: 0,
+            depth: 0,
+        }
+    }
+
+    /// Build an error at the current offset.
+    fn error(&self, reason: &'static str) -> ExpressionError {
+        ExpressionError {
+            offset: self.offset,
+            reason,
+        }
+    }
+
+    /// Skip whitespace, and peek at the next character.
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.source[self.offset..];
+        self.offset += rest.len() - rest.trim_start().len();
+
+        self.source[self.offset..].chars().next()
+    }
+
+    /// Consume a token, if it is next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.peek();
+
+        match self.source[self.offset..].starts_with(token) {
+            true => {
+                self.offset += token.len();
+                true
+            }
+            false => false,
+        }
+    }
+
+    /// Parse a whole expression.
+    fn parse(mut self) -> Result<Compiled, ExpressionError> {
+        let left = self.parse_sum()?;
+
+        // Two-character comparisons come first, so that `<=` is not read as `<`.
+        let op = [
+            ("==", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ]
+        .into_iter()
+        .find_map(|(token, op)| self.eat(token).then_some(op))
+        .ok_or_else(|| self.error("expected a comparison"))?;
+
+        let right = self.parse_sum()?;
+
+        match self.peek() {
+            None => Ok(Compiled { left, op, right }),
+            Some(_) => Err(self.error("unexpected trailing characters")),
+        }
+    }
+
+    /// Parse a sum or difference of products.
+    fn parse_sum(&mut self) -> Result<Operand, ExpressionError> {
+        let mut left = self.parse_product()?;
+
+        loop {
+            let op = if self.eat("+") {
+                Operator::Add
+            } else if self.eat("-") {
+                Operator::Subtract
+            } else {
+                return Ok(left);
+            };
+
+            left = Operand::Binary(Box::new(left), op, Box::new(self.parse_product()?));
+        }
+    }
+
+    /// Parse a product, quotient or remainder of unary operands.
+    fn parse_product(&mut self) -> Result<Operand, ExpressionError> {
+        let mut left = self.parse_unary()?;
+
+        loop {
+            let op = if self.eat("*") {
+                Operator::Multiply
+            } else if self.eat("/") {
+                Operator::Divide
+            } else if self.eat("%") {
+                Operator::Remainder
+            } else {
+                return Ok(left);
+            };
+
+            left = Operand::Binary(Box::new(left), op, Box::new(self.parse_unary()?));
+        }
+    }
+
+    /// Parse a possibly negated atom.
+    fn parse_unary(&mut self) -> Result<Operand, ExpressionError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("the expression is nested too deeply"));
+        }
+
+        self.depth += 1;
+
+        let operand = if self.eat("-") {
+            Operand::Negate(Box::new(self.parse_unary()?))
+        } else {
+            self.parse_atom()?
+        };
+
+        self.depth -= 1;
+
+        Ok(operand)
+    }
+
+    /// Parse a number, an entry key or a parenthesized operand.
+    fn parse_atom(&mut self) -> Result<Operand, ExpressionError> {
+        let rest = &self.source[self.offset..];
+
+        match self.peek() {
+            Some('(') => {
+                self.offset += 1;
+                let operand = self.parse_sum()?;
+
+                match self.eat(")") {
+                    true => Ok(operand),
+                    false => Err(self.error("expected `)`")),
+                }
+            }
+            Some('`') => {
+                let rest = &self.source[self.offset + 1..];
+                let len = rest
+                    .find('`')
+                    .ok_or_else(|| self.error("unterminated quoted key"))?;
+
+                self.offset += len + 2;
+
+                Ok(Operand::Entry(rest[..len].to_owned()))
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let rest = rest.trim_start();
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                    .unwrap_or(rest.len());
+                let number = rest[..len]
+                    .parse()
+                    .map_err(|_| self.error("invalid number"))?;
+
+                self.offset += len;
+
+                Ok(Operand::Number(number))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let rest = rest.trim_start();
+                let len = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+
+                self.offset += len;
+
+                Ok(Operand::Entry(rest[..len].to_owned()))
+            }
+            _ => Err(self.error("expected a number, an entry key or `(`")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Expression;
+
+    #[test]
+    fn test_expression() {
+        let expression =
+            Expression::new("max_damage - min_damage * 2 <= -(`base-damage` % 4)").unwrap();
+
+        assert_eq!(
+            expression.entries(),
+            ["max_damage", "min_damage", "base-damage"]
+        );
+
+        let entry = |max_damage| {
+            move |key: &str| match key {
+                "max_damage" => Some(max_damage),
+                "min_damage" => Some(5.0),
+                "base-damage" => Some(-7.0),
+                _ => None,
+            }
+        };
+
+        // `-(-7 % 4)` is 3, so the maximum damage can be up to 13.
+        assert_eq!(expression.evaluate(entry(13.0)), Some(true));
+        assert_eq!(expression.evaluate(entry(13.5)), Some(false));
+        assert_eq!(expression.evaluate(|_| None), None);
+
+        assert_eq!(
+            serde_json::to_value(&expression).unwrap(),
+            "max_damage - min_damage * 2 <= -(`base-damage` % 4)"
+        );
+        assert_eq!(
+            serde_json::from_value::<Expression>("(a + 1) / 2 != b".into())
+                .unwrap()
+                .evaluate(|key| Some(if key == "a" { 3.0 } else { 2.0 })),
+            Some(false)
+        );
+
+        for (source, offset) in [
+            ("a + b", 5),
+            ("a < b < c", 6),
+            ("(a < b", 3),
+            ("a == `b", 5),
+            ("a == 1.2.3", 5),
+            ("a == * b", 5),
+        ] {
+            assert_eq!(
+                Expression::new(source).unwrap_err().offset(),
+                offset,
+                "{source}"
+            );
+        }
+
+        Expression::new(format!("{}1 == 1", "-".repeat(64))).unwrap_err();
+    }
+}
@@ -2,8 +2,12 @@
 
 mod array;
 mod boolean;
+#[cfg(feature = "bytes")]
+mod bytes;
+mod compiled;
 mod dictionary;
 mod r#enum;
+mod expression;
 mod number;
 mod string;
 
@@ -16,10 +20,20 @@ use serde::{Deserialize, Serialize};
 
 pub(crate) use array::ArrayTypeAttributes;
 pub(crate) use boolean::BooleanTypeAttributes;
+#[cfg(feature = "bytes")]
+pub(crate) use bytes::BytesTypeAttributes;
+#[cfg(feature = "bytes")]
+pub use bytes::{BytesEncoding, DecodeBytesError};
+pub use compiled::CompiledEntryRules;
+pub(crate) use compiled::Scalar;
 pub(crate) use dictionary::DictionaryTypeAttributes;
+pub use dictionary::{Comparison, DictionaryWireForm, EntryConstraint, EntryRequirement};
 pub(crate) use r#enum::EnumTypeAttributes;
-pub(crate) use number::{NumberTypeAttributes, ValidateNumberTypeError};
+pub use expression::{Expression, ExpressionError};
+pub(crate) use number::NumberTypeAttributes;
+pub use number::ValidateNumberTypeError;
 pub(crate) use string::StringTypeAttributes;
+pub use string::{UnicodeNormalization, WhitespacePolicy};
 
 #[cfg(feature = "uuid")]
 pub(crate) use uuid::UuidTypeAttributes;
@@ -78,6 +92,10 @@ pub enum TypeAttributes<Id, FieldName: Ord + Display + Clone> {
     #[cfg(feature = "uuid")]
     /// An UUID value.
     Uuid(UuidTypeAttributes),
+
+    #[cfg(feature = "bytes")]
+    /// A sequence of bytes, encoded as a string.
+    Bytes(BytesTypeAttributes),
 }
 
 impl<Id, FieldName: Ord + Display + Clone> TypeAttributes<Id, FieldName> {
@@ -97,6 +115,57 @@ impl<Id, FieldName: Ord + Display + Clone> TypeAttributes<Id, FieldName> {
             TypeAttributes::Enum(_) => vec![],
             #[cfg(feature = "uuid")]
             TypeAttributes::Uuid(_) => vec![],
+            #[cfg(feature = "bytes")]
+            TypeAttributes::Bytes(_) => vec![],
+        }
+    }
+}
+
+impl<Id, FieldName: Ord + Display + Clone> TypeAttributes<Id, FieldName> {
+    /// Convert the type identifiers referenced by this type attributes.
+    pub fn map_ids<Other>(self, f: impl FnMut(Id) -> Other) -> TypeAttributes<Other, FieldName> {
+        match self {
+            TypeAttributes::Array(a) => TypeAttributes::Array(a.map_ids(f)),
+            TypeAttributes::Dictionary(d) => TypeAttributes::Dictionary(d.map_ids(f)),
+            TypeAttributes::Boolean(b) => TypeAttributes::Boolean(b),
+            TypeAttributes::Int32(i) => TypeAttributes::Int32(i),
+            TypeAttributes::Int64(i) => TypeAttributes::Int64(i),
+            TypeAttributes::Uint32(i) => TypeAttributes::Uint32(i),
+            TypeAttributes::Uint64(i) => TypeAttributes::Uint64(i),
+            TypeAttributes::Float32(f) => TypeAttributes::Float32(f),
+            TypeAttributes::Float64(f) => TypeAttributes::Float64(f),
+            TypeAttributes::String(s) => TypeAttributes::String(s),
+            TypeAttributes::Enum(e) => TypeAttributes::Enum(e),
+            #[cfg(feature = "uuid")]
+            TypeAttributes::Uuid(u) => TypeAttributes::Uuid(u),
+            #[cfg(feature = "bytes")]
+            TypeAttributes::Bytes(b) => TypeAttributes::Bytes(b),
+        }
+    }
+}
+
+impl<Id, FieldName: Ord + Display + Clone> TypeAttributes<Id, FieldName> {
+    /// Get the kind of the type, as a string.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Self::Array(_) => "array",
+            Self::Dictionary(a) => match a.wire_form() {
+                DictionaryWireForm::Object => "dictionary",
+                DictionaryWireForm::Pairs => "dictionary of pairs",
+            },
+            Self::Boolean(_) => "boolean",
+            Self::Int32(_) => "int32",
+            Self::Int64(_) => "int64",
+            Self::Uint32(_) => "uint32",
+            Self::Uint64(_) => "uint64",
+            Self::Float32(_) => "float32",
+            Self::Float64(_) => "float64",
+            Self::String(_) => "string",
+            Self::Enum(_) => "enum",
+            #[cfg(feature = "uuid")]
+            Self::Uuid(_) => "uuid",
+            #[cfg(feature = "bytes")]
+            Self::Bytes(_) => "bytes",
         }
     }
 }
@@ -105,7 +174,7 @@ impl<Id, FieldName: Ord + Display + Clone> TypeAttributes<Id, FieldName> {
 pub type InstantiationResult<T, Id, FieldName> = Result<T, InstantiationError<Id, FieldName>>;
 
 /// An error that can occur when instantiating type attributes.
-#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
 pub enum InstantiationError<Id, FieldName> {
     /// The dictionary key type is not appropriate.
     #[error(
@@ -116,6 +185,18 @@ pub enum InstantiationError<Id, FieldName> {
         key_type_name: FieldName,
         key_type_str: String,
     },
+
+    /// A constraint between dictionary entries is invalid.
+    #[error("invalid dictionary constraint `{constraint}`: {reason}")]
+    InvalidConstraint { constraint: String, reason: String },
+
+    /// A conditional requirement on dictionary entries is invalid.
+    #[error("invalid dictionary requirement `{requirement}`: {reason}")]
+    InvalidRequirement { requirement: String, reason: String },
+
+    /// An invariant between dictionary entries is invalid.
+    #[error("invalid dictionary invariant `{invariant}`: {reason}")]
+    InvalidInvariant { invariant: String, reason: String },
 }
 
 impl<Id: Ord + Clone + Display, FieldName: Ord + Clone + Display> TypeAttributes<Id, FieldName> {
@@ -153,6 +234,8 @@ impl<Id: Ord + Clone + Display, FieldName: Ord + Clone + Display> TypeAttributes
             TypeAttributes::Enum(e) => TypeAttributesInstance::Enum(e),
             #[cfg(feature = "uuid")]
             TypeAttributes::Uuid(u) => TypeAttributesInstance::Uuid(u),
+            #[cfg(feature = "bytes")]
+            TypeAttributes::Bytes(b) => TypeAttributesInstance::Bytes(b),
         })
     }
 }
@@ -13,11 +13,15 @@ pub struct NumberTypeAttributes<Num> {
     /// The maximum value of the number.
     #[serde(skip_serializing_if = "Option::is_none")]
     max: Option<Num>,
+
+    /// The default value of the number.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<Num>,
 }
 
 impl<Num: Display> Display for NumberTypeAttributes<Num> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let Self { min, max } = self;
+        let Self { min, max, .. } = self;
         match (min, max) {
             (Some(min), Some(max)) => write!(f, "{min}..{max}"),
             (Some(min), None) => write!(f, "{min}.."),
@@ -41,11 +45,13 @@ impl<'de, Num: Copy + Display + PartialOrd + Deserialize<'de>> Deserialize<'de>
             min: Option<T>,
             #[serde(skip_serializing_if = "Option::is_none")]
             max: Option<T>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            default: Option<T>,
         }
 
         let x = X::deserialize(deserializer)?;
 
-        NumberTypeAttributes::new(x.min, x.max)
+        NumberTypeAttributes::new(x.min, x.max, x.default)
             .map_err(|err| serde::de::Error::custom(err.to_string()))
     }
 }
@@ -56,6 +62,10 @@ pub enum NewNumberTypeAttributesError<Num> {
     /// The range is invalid.
     #[error("invalid range: {0} > {1}")]
     InvalidRange(Num, Num),
+
+    /// The default value is outside of the range.
+    #[error("default value {0} is outside of the range")]
+    DefaultOutOfRange(Num),
 }
 
 impl<Num: PartialOrd + Copy> NumberTypeAttributes<Num> {
@@ -70,14 +80,53 @@ impl<Num: PartialOrd + Copy> NumberTypeAttributes<Num> {
     ///
     /// This function will return an error if:
     /// - The range is invalid.
-    fn new(min: Option<Num>, max: Option<Num>) -> Result<Self, NewNumberTypeAttributesError<Num>> {
-        if let (Some(min), Some(max)) = (min, max) {
-            if min > max {
-                return Err(NewNumberTypeAttributesError::InvalidRange(min, max));
-            }
+    /// - The default value is outside of the range.
+    fn new(
+        min: Option<Num>,
+        max: Option<Num>,
+        default: Option<Num>,
+    ) -> Result<Self, NewNumberTypeAttributesError<Num>> {
+        if let (Some(min), Some(max)) = (min, max)
+            && min > max
+        {
+            return Err(NewNumberTypeAttributesError::InvalidRange(min, max));
+        }
+
+        if let Some(default) = default
+            && (min.is_some_and(|min| default < min) || max.is_some_and(|max| default > max))
+        {
+            return Err(NewNumberTypeAttributesError::DefaultOutOfRange(default));
         }
 
-        Ok(Self { min, max })
+        Ok(Self { min, max, default })
+    }
+
+    /// Get the minimum value of the number, if any.
+    pub fn min(&self) -> Option<Num> {
+        self.min
+    }
+
+    /// Get the maximum value of the number, if any.
+    pub fn max(&self) -> Option<Num> {
+        self.max
+    }
+
+    /// Get the default value of the number, if any.
+    pub fn default_value(&self) -> Option<Num> {
+        self.default
+    }
+}
+
+impl<Num: PartialOrd + Copy + Default> NumberTypeAttributes<Num> {
+    /// Get the value closest to zero that satisfies the type.
+    pub(crate) fn placeholder(&self) -> Num {
+        let zero = Num::default();
+
+        match (self.min, self.max) {
+            (Some(min), _) if zero < min => min,
+            (_, Some(max)) if zero > max => max,
+            _ => zero,
+        }
     }
 }
 
@@ -86,6 +135,7 @@ impl<Num: PartialOrd + Copy> NumberTypeAttributes<Num> {
 pub struct NumberTypeAttributesBuilder<Num> {
     min: Option<Num>,
     max: Option<Num>,
+    default: Option<Num>,
 }
 
 impl<Num> Default for NumberTypeAttributesBuilder<Num> {
@@ -93,6 +143,7 @@ impl<Num> Default for NumberTypeAttributesBuilder<Num> {
         Self {
             min: None,
             max: None,
+            default: None,
         }
     }
 }
@@ -110,9 +161,15 @@ impl<Num: PartialOrd + Copy> NumberTypeAttributesBuilder<Num> {
         self
     }
 
+    /// Sets the default value of the number.
+    pub fn default_value(mut self, default: Num) -> Self {
+        self.default = Some(default);
+        self
+    }
+
     /// Builds the number type.
     pub fn build(self) -> Result<NumberTypeAttributes<Num>, NewNumberTypeAttributesError<Num>> {
-        NumberTypeAttributes::new(self.min, self.max)
+        NumberTypeAttributes::new(self.min, self.max, self.default)
     }
 }
 
@@ -130,9 +187,13 @@ pub enum ValidateNumberTypeError<Num> {
     /// The value is greater than the maximum.
     #[error("value {0} is greater than the maximum {1}")]
     GreaterThanMax(Num, Num),
+
+    /// The value cannot be represented exactly by the number type.
+    #[error("value {0} cannot be represented exactly")]
+    Inexact(String),
 }
 
-impl<Num: Ord + Copy> NumberTypeAttributes<Num> {
+impl<Num: PartialOrd + Copy> NumberTypeAttributes<Num> {
     /// Validates a number type.
     ///
     /// # Errors
@@ -141,15 +202,50 @@ impl<Num: Ord + Copy> NumberTypeAttributes<Num> {
     /// - The value is less than the minimum.
     /// - The value is greater than the maximum.
     pub fn validate(&self, value: Num) -> Result<(), ValidateNumberTypeError<Num>> {
-        if let Some(min) = self.min {
-            if value < min {
-                return Err(ValidateNumberTypeError::LessThanMin(value, min));
-            }
+        if let Some(min) = self.min
+            && value < min
+        {
+            return Err(ValidateNumberTypeError::LessThanMin(value, min));
+        }
+
+        if let Some(max) = self.max
+            && value > max
+        {
+            return Err(ValidateNumberTypeError::GreaterThanMax(value, max));
         }
 
-        if let Some(max) = self.max {
-            if value > max {
-                return Err(ValidateNumberTypeError::GreaterThanMax(value, max));
+        Ok(())
+    }
+
+    /// Validates many numbers at once.
+    ///
+    /// The numbers are checked by whole chunks in a tight loop, which the compiler can vectorize,
+    /// and only the chunks holding invalid numbers are checked one number at a time.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the index of the first invalid number, along with the reason why
+    /// it is invalid.
+    pub(crate) fn validate_slice(
+        &self,
+        values: &[Num],
+    ) -> Result<(), (usize, ValidateNumberTypeError<Num>)> {
+        const CHUNK_SIZE: usize = 16;
+
+        let (min, max) = (self.min, self.max);
+
+        for (index, chunk) in values.chunks(CHUNK_SIZE).enumerate() {
+            // Not short-circuiting keeps the loop free of branches. NaNs fail the check here but
+            // pass the exact one, as with `validate`.
+            let valid = chunk.iter().fold(true, |valid, &value| {
+                valid & min.is_none_or(|min| value >= min) & max.is_none_or(|max| value <= max)
+            });
+
+            if !valid {
+                for (offset, &value) in chunk.iter().enumerate() {
+                    self.validate(value)
+                        .map_err(|err| (index * CHUNK_SIZE + offset, err))?;
+                }
             }
         }
 
@@ -178,5 +274,45 @@ mod tests {
 
         let t: NumberType = serde_json::from_value(json).unwrap();
         assert_eq!(t, expected);
+
+        let expected = NumberType::builder()
+            .min(0)
+            .max(10)
+            .default_value(5)
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&expected).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "min": 0,
+                "max": 10,
+                "default": 5
+            })
+        );
+
+        let t: NumberType = serde_json::from_value(json).unwrap();
+        assert_eq!(t, expected);
+    }
+
+    #[test]
+    fn test_default_out_of_range() {
+        assert!(matches!(
+            NumberType::builder()
+                .min(1)
+                .max(10)
+                .default_value(0)
+                .build(),
+            Err(super::NewNumberTypeAttributesError::DefaultOutOfRange(0))
+        ));
+        assert!(matches!(
+            NumberType::builder()
+                .min(1)
+                .max(10)
+                .default_value(11)
+                .build(),
+            Err(super::NewNumberTypeAttributesError::DefaultOutOfRange(11))
+        ));
     }
 }
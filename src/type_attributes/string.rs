@@ -1,23 +1,208 @@
-use std::fmt::Display;
+use std::{borrow::Cow, fmt::Display};
 
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use unicode_normalization::{IsNormalized, UnicodeNormalization as _};
 
-/// A number type.
+/// Attributes for a string type.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
-pub struct StringTypeAttributes {}
+pub struct StringTypeAttributes {
+    /// The default value of the string.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+
+    /// The Unicode normalization form applied to parsed strings.
+    #[serde(default, skip_serializing_if = "UnicodeNormalization::is_none")]
+    normalize: UnicodeNormalization,
+
+    /// The whitespace policy applied to parsed strings.
+    #[serde(default, skip_serializing_if = "WhitespacePolicy::is_preserve")]
+    whitespace: WhitespacePolicy,
+}
 
 impl Display for StringTypeAttributes {
-    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let Self {} = self;
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            default: _,
+            normalize,
+            whitespace,
+        } = self;
+
+        let normalize = match normalize {
+            UnicodeNormalization::None => None,
+            UnicodeNormalization::Nfc => Some("nfc"),
+            UnicodeNormalization::Nfkc => Some("nfkc"),
+        };
+
+        let whitespace = match whitespace {
+            WhitespacePolicy::Preserve => None,
+            WhitespacePolicy::Trim => Some("trim"),
+            WhitespacePolicy::Collapse => Some("collapse"),
+        };
 
-        Ok(())
+        f.write_str(&normalize.into_iter().chain(whitespace).join(", "))
+    }
+}
+
+/// A Unicode normalization form.
+///
+/// Normalizing strings makes them compare consistently, whatever the tool that authored them:
+/// some platforms store accented characters decomposed, others precomposed.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "snake_case")]
+pub enum UnicodeNormalization {
+    /// Strings are kept as-is.
+    #[default]
+    None,
+
+    /// Canonical composition.
+    Nfc,
+
+    /// Compatibility composition, which also folds compatibility characters such as ligatures.
+    Nfkc,
+}
+
+impl UnicodeNormalization {
+    /// Check whether no normalization is applied.
+    fn is_none(&self) -> bool {
+        *self == Self::None
+    }
+}
+
+/// How whitespace in parsed strings is handled.
+///
+/// Strings exported from spreadsheets often carry stray spaces, that then fail lookups.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "snake_case")]
+pub enum WhitespacePolicy {
+    /// Strings are kept as-is.
+    #[default]
+    Preserve,
+
+    /// Leading and trailing whitespace is removed.
+    Trim,
+
+    /// Leading and trailing whitespace is removed, and every other run of whitespace is replaced
+    /// by a single space.
+    Collapse,
+}
+
+impl WhitespacePolicy {
+    /// Check whether whitespace is kept as-is.
+    fn is_preserve(&self) -> bool {
+        *self == Self::Preserve
+    }
+}
+
+impl StringTypeAttributes {
+    /// Create a builder for the string type.
+    pub fn builder() -> StringTypeAttributesBuilder {
+        StringTypeAttributesBuilder::default()
+    }
+
+    /// Get the default value of the string, if any.
+    pub fn default_value(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    /// Get the Unicode normalization form applied to parsed strings.
+    pub fn normalization(&self) -> UnicodeNormalization {
+        self.normalize
+    }
+
+    /// Get the whitespace policy applied to parsed strings.
+    pub fn whitespace(&self) -> WhitespacePolicy {
+        self.whitespace
+    }
+
+    /// Normalize a parsed string, applying the whitespace policy and then the Unicode
+    /// normalization form.
+    ///
+    /// Strings that are already normalized are not copied.
+    pub(crate) fn normalize<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        let s = match self.whitespace {
+            WhitespacePolicy::Preserve => Cow::Borrowed(s),
+            WhitespacePolicy::Trim => Cow::Borrowed(s.trim()),
+            WhitespacePolicy::Collapse => collapse_whitespace(s),
+        };
+
+        match self.normalize {
+            UnicodeNormalization::None => s,
+            UnicodeNormalization::Nfc => match unicode_normalization::is_nfc_quick(s.chars()) {
+                IsNormalized::Yes => s,
+                _ => Cow::Owned(s.nfc().collect()),
+            },
+            UnicodeNormalization::Nfkc => match unicode_normalization::is_nfkc_quick(s.chars()) {
+                IsNormalized::Yes => s,
+                _ => Cow::Owned(s.nfkc().collect()),
+            },
+        }
+    }
+}
+
+/// Trim a string and replace its inner runs of whitespace by a single space.
+fn collapse_whitespace(s: &str) -> Cow<'_, str> {
+    let s = s.trim();
+    let mut previous_is_whitespace = false;
+
+    let is_collapsed = s.chars().all(|c| {
+        let is_whitespace = c.is_whitespace();
+        let ok = !is_whitespace || (c == ' ' && !previous_is_whitespace);
+        previous_is_whitespace = is_whitespace;
+
+        ok
+    });
+
+    if is_collapsed {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(s.split_whitespace().join(" "))
+    }
+}
+
+/// A builder for string type attributes.
+#[derive(Debug, Default)]
+pub struct StringTypeAttributesBuilder {
+    default: Option<String>,
+    normalize: UnicodeNormalization,
+    whitespace: WhitespacePolicy,
+}
+
+impl StringTypeAttributesBuilder {
+    /// Sets the default value of the string.
+    pub fn default_value(mut self, default: impl Into<String>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    /// Sets the Unicode normalization form applied to parsed strings.
+    pub fn normalize(mut self, normalize: UnicodeNormalization) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Sets the whitespace policy applied to parsed strings.
+    pub fn whitespace(mut self, whitespace: WhitespacePolicy) -> Self {
+        self.whitespace = whitespace;
+        self
+    }
+
+    /// Builds the string type.
+    pub fn build(self) -> StringTypeAttributes {
+        StringTypeAttributes {
+            default: self.default,
+            normalize: self.normalize,
+            whitespace: self.whitespace,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::StringTypeAttributes;
+    use super::{StringTypeAttributes, UnicodeNormalization, WhitespacePolicy};
     use serde_json::json;
 
     #[test]
@@ -29,5 +214,63 @@ mod tests {
 
         let t: StringTypeAttributes = serde_json::from_value(json).unwrap();
         assert_eq!(t, expected);
+
+        let expected = StringTypeAttributes::builder().default_value("foo").build();
+
+        let json = serde_json::to_value(&expected).unwrap();
+        assert_eq!(json, json!({ "default": "foo" }));
+
+        let t: StringTypeAttributes = serde_json::from_value(json).unwrap();
+        assert_eq!(t, expected);
+
+        let expected = StringTypeAttributes::builder()
+            .normalize(UnicodeNormalization::Nfc)
+            .build();
+
+        let json = serde_json::to_value(&expected).unwrap();
+        assert_eq!(json, json!({ "normalize": "nfc" }));
+
+        let t: StringTypeAttributes = serde_json::from_value(json).unwrap();
+        assert_eq!(t, expected);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let decomposed = "e\u{301}";
+
+        let t = StringTypeAttributes::default();
+        assert_eq!(t.normalize(decomposed), decomposed);
+
+        let t = StringTypeAttributes::builder()
+            .normalize(UnicodeNormalization::Nfc)
+            .build();
+        assert_eq!(t.normalize(decomposed), "\u{e9}");
+        assert_eq!(t.normalize("\u{fb01}"), "\u{fb01}");
+
+        let t = StringTypeAttributes::builder()
+            .normalize(UnicodeNormalization::Nfkc)
+            .build();
+        assert_eq!(t.normalize("\u{fb01}"), "fi");
+    }
+
+    #[test]
+    fn test_whitespace() {
+        let t = StringTypeAttributes::builder()
+            .whitespace(WhitespacePolicy::Trim)
+            .build();
+        assert_eq!(t.normalize("  Iron  Sword \t"), "Iron  Sword");
+
+        let t = StringTypeAttributes::builder()
+            .whitespace(WhitespacePolicy::Collapse)
+            .build();
+        assert_eq!(t.normalize("  Iron  Sword \t"), "Iron Sword");
+        assert_eq!(t.normalize("Iron\tSword"), "Iron Sword");
+        assert!(matches!(
+            t.normalize("Iron Sword "),
+            std::borrow::Cow::Borrowed("Iron Sword")
+        ));
+
+        let json = serde_json::to_value(&t).unwrap();
+        assert_eq!(json, json!({ "whitespace": "collapse" }));
     }
 }
@@ -3,7 +3,23 @@ use serde::{Deserialize, Serialize};
 /// Attributes for a UUID type.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
-pub struct UuidTypeAttributes {}
+pub struct UuidTypeAttributes {
+    /// The default value of the UUID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<uuid::Uuid>,
+}
+
+impl UuidTypeAttributes {
+    /// Create new UUID type attributes.
+    pub fn new(default: Option<uuid::Uuid>) -> Self {
+        Self { default }
+    }
+
+    /// Get the default value of the UUID, if any.
+    pub fn default_value(&self) -> Option<uuid::Uuid> {
+        self.default
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -1,7 +1,7 @@
-use std::{fmt::Display, sync::Arc};
+use std::{fmt::Display, ops::Deref, sync::Arc};
 
 use crate::{
-    TypeDefinitionInstance,
+    CompiledEntryRules, DictionaryWireForm, TypeAttributes, TypeDefinitionInstance,
     type_attributes::{
         ArrayTypeAttributes, BooleanTypeAttributes, DictionaryTypeAttributes, EnumTypeAttributes,
         NumberTypeAttributes, StringTypeAttributes,
@@ -11,6 +11,9 @@ use crate::{
 #[cfg(feature = "uuid")]
 use crate::type_attributes::UuidTypeAttributes;
 
+#[cfg(feature = "bytes")]
+use crate::type_attributes::BytesTypeAttributes;
+
 /// A type attributes instance.
 #[derive(Debug)]
 pub enum TypeAttributesInstance<Id, FieldName: Ord> {
@@ -18,7 +21,7 @@ pub enum TypeAttributesInstance<Id, FieldName: Ord> {
     Array(ArrayTypeAttributes<Arc<TypeDefinitionInstance<Id, FieldName>>>),
 
     /// A dictionary type.
-    Dictionary(DictionaryTypeAttributes<Arc<TypeDefinitionInstance<Id, FieldName>>>),
+    Dictionary(DictionaryTypeAttributesInstance<Id, FieldName>),
 
     /// A boolean type.
     Boolean(BooleanTypeAttributes),
@@ -50,6 +53,48 @@ pub enum TypeAttributesInstance<Id, FieldName: Ord> {
     /// A UUID type.
     #[cfg(feature = "uuid")]
     Uuid(UuidTypeAttributes),
+
+    /// A bytes type.
+    #[cfg(feature = "bytes")]
+    Bytes(BytesTypeAttributes),
+}
+
+/// The attributes of a dictionary type instance, with its compiled entry rules.
+#[derive(Debug)]
+pub struct DictionaryTypeAttributesInstance<Id, FieldName: Ord> {
+    /// The dictionary type attributes.
+    attributes: DictionaryTypeAttributes<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+
+    /// The constraints, requirements and invariants between the entries, compiled at
+    /// instantiation, if there are any.
+    compiled: Option<CompiledEntryRules>,
+}
+
+impl<Id, FieldName: Ord> DictionaryTypeAttributesInstance<Id, FieldName> {
+    /// Create dictionary type attributes instance.
+    pub(crate) fn new(
+        attributes: DictionaryTypeAttributes<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+        compiled: Option<CompiledEntryRules>,
+    ) -> Self {
+        Self {
+            attributes,
+            compiled,
+        }
+    }
+
+    /// Get the compiled constraints, requirements and invariants between the entries, if there
+    /// are any.
+    pub(crate) fn compiled_rules(&self) -> Option<&CompiledEntryRules> {
+        self.compiled.as_ref()
+    }
+}
+
+impl<Id, FieldName: Ord> Deref for DictionaryTypeAttributesInstance<Id, FieldName> {
+    type Target = DictionaryTypeAttributes<Arc<TypeDefinitionInstance<Id, FieldName>>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.attributes
+    }
 }
 
 impl<Id, FieldName: Ord> Display for TypeAttributesInstance<Id, FieldName>
@@ -60,7 +105,7 @@ where
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Array(a) => write!(f, "array({a})"),
-            Self::Dictionary(d) => write!(f, "dictionary({d})",),
+            Self::Dictionary(d) => write!(f, "dictionary({})", **d),
             Self::Boolean(_) => f.write_str("boolean"),
             Self::Int32(n) => write!(f, "int32({n})"),
             Self::Int64(n) => write!(f, "int64({n})"),
@@ -72,11 +117,37 @@ where
             Self::Enum(e) => write!(f, "enum({})", e),
             #[cfg(feature = "uuid")]
             Self::Uuid(_) => f.write_str("uuid"),
+            #[cfg(feature = "bytes")]
+            Self::Bytes(b) => write!(f, "bytes({})", b.encoding()),
         }
     }
 }
 
 impl<Id, FieldName: Ord> TypeAttributesInstance<Id, FieldName> {
+    /// Get the name of the kind of type.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Self::Array(_) => "array",
+            Self::Dictionary(a) => match a.wire_form() {
+                DictionaryWireForm::Object => "dictionary",
+                DictionaryWireForm::Pairs => "dictionary of pairs",
+            },
+            Self::Boolean(_) => "boolean",
+            Self::Int32(_) => "int32",
+            Self::Int64(_) => "int64",
+            Self::Uint32(_) => "uint32",
+            Self::Uint64(_) => "uint64",
+            Self::Float32(_) => "float32",
+            Self::Float64(_) => "float64",
+            Self::String(_) => "string",
+            Self::Enum(_) => "enum",
+            #[cfg(feature = "uuid")]
+            Self::Uuid(_) => "uuid",
+            #[cfg(feature = "bytes")]
+            Self::Bytes(_) => "bytes",
+        }
+    }
+
     /// Check if the type is suitable for usage as a key in a dictionary.
     ///
     /// Usually, this means that the type serializes as a string.
@@ -95,6 +166,54 @@ impl<Id, FieldName: Ord> TypeAttributesInstance<Id, FieldName> {
             Self::Enum(_) => true,
             #[cfg(feature = "uuid")]
             Self::Uuid(_) => true,
+            #[cfg(feature = "bytes")]
+            Self::Bytes(_) => false,
+        }
+    }
+
+    /// Check if the type is a number type.
+    pub(crate) fn is_number(&self) -> bool {
+        matches!(
+            self,
+            Self::Int32(_)
+                | Self::Int64(_)
+                | Self::Uint32(_)
+                | Self::Uint64(_)
+                | Self::Float32(_)
+                | Self::Float64(_)
+        )
+    }
+
+    /// Get the type instances referenced by the type attributes.
+    pub(crate) fn references(&self) -> Vec<&Arc<TypeDefinitionInstance<Id, FieldName>>> {
+        match self {
+            Self::Array(a) => vec![a.items_type_id()],
+            Self::Dictionary(d) => vec![d.keys_type_id(), d.values_type_id()],
+            _ => vec![],
+        }
+    }
+}
+
+impl<Id: Clone, FieldName: Ord + Display + Clone> TypeAttributesInstance<Id, FieldName> {
+    /// Revert the instantiation of the type attributes, referencing other types by their
+    /// identifiers again.
+    pub(crate) fn uninstantiate(&self) -> TypeAttributes<Id, FieldName> {
+        match self {
+            Self::Array(a) => TypeAttributes::Array(a.uninstantiate()),
+            Self::Dictionary(d) => TypeAttributes::Dictionary(d.uninstantiate()),
+            Self::Boolean(b) => TypeAttributes::Boolean(b.clone()),
+            Self::Int32(n) => TypeAttributes::Int32(n.clone()),
+            Self::Int64(n) => TypeAttributes::Int64(n.clone()),
+            Self::Uint32(n) => TypeAttributes::Uint32(n.clone()),
+            Self::Uint64(n) => TypeAttributes::Uint64(n.clone()),
+            Self::Float32(n) => TypeAttributes::Float32(n.clone()),
+            Self::Float64(n) => TypeAttributes::Float64(n.clone()),
+            Self::String(s) => TypeAttributes::String(s.clone()),
+            Self::Enum(e) => TypeAttributes::Enum(e.clone()),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(u) => TypeAttributes::Uuid(u.clone()),
+            #[cfg(feature = "bytes")]
+            Self::Bytes(b) => TypeAttributes::Bytes(b.clone()),
         }
     }
 }
@@ -12,7 +12,7 @@ use crate::TypeAttributes;
 /// broken or circular references. In order to validate the integrity of the type definitions
 /// hierarchy, those must be loaded into a
 /// [`TypeDefinitionRegistry`](crate::TypeDefinitionRegistry).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct TypeDefinition<Id, FieldName: Ord + Display + Clone> {
     /// The identifier of the type.
@@ -33,3 +33,25 @@ pub struct TypeDefinition<Id, FieldName: Ord + Display + Clone> {
     #[serde(flatten)]
     pub attributes: TypeAttributes<Id, FieldName>,
 }
+
+/// A type definition without an identifier, for a GameSON type.
+///
+/// Unidentified type definitions reference other type definitions by their names instead of
+/// their identifiers, so that schema authors only have to name their types. Their identifiers are
+/// assigned when they are [registered](crate::TypeDefinitionRegistry::register_unidentified).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct UnidentifiedTypeDefinition<FieldName: Ord + Display + Clone> {
+    /// A name for the type.
+    ///
+    /// Names must be unique for different types.
+    pub name: FieldName,
+
+    /// A description for the type.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// The type, referencing other types by their names.
+    #[serde(flatten)]
+    pub attributes: TypeAttributes<FieldName, FieldName>,
+}
@@ -1,6 +1,16 @@
-use std::fmt::Display;
+use std::{
+    borrow::Borrow,
+    fmt::Display,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
-use crate::type_attributes_instance::TypeAttributesInstance;
+use crate::{
+    CompiledEntryRules, ParseOptions, TypeDefinition, TypeRef, ValidationReport, Validator,
+    type_attributes_instance::TypeAttributesInstance,
+};
 
 /// A type instance.
 ///
@@ -13,8 +23,17 @@ pub struct TypeDefinitionInstance<Id, FieldName: Ord> {
     /// The name of the type.
     pub(crate) name: FieldName,
 
+    /// The description of the type.
+    pub(crate) description: Option<String>,
+
     /// The type attributes.
     pub(crate) attributes: TypeAttributesInstance<Id, FieldName>,
+
+    /// The custom validator of the values of the type, if any.
+    pub(crate) validator: Option<Arc<dyn Validator<Id, FieldName>>>,
+
+    /// The serial number of the type instance, unique within the process.
+    pub(crate) serial: u64,
 }
 
 impl<Id, FieldName> Display for TypeDefinitionInstance<Id, FieldName>
@@ -26,9 +45,119 @@ where
         let Self {
             id,
             name,
+            description: _,
             attributes,
+            validator: _,
+            serial: _,
         } = self;
 
         write!(f, "{name}({id}): {attributes}")
     }
 }
+
+impl<Id, FieldName: Ord> TypeDefinitionInstance<Id, FieldName> {
+    /// Get the identifier of the type.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Get the name of the type.
+    pub fn name(&self) -> &FieldName {
+        &self.name
+    }
+
+    /// Get the description of the type, if any.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Get a lightweight handle to the type instance.
+    pub fn type_ref(&self) -> TypeRef<Id>
+    where
+        Id: Clone,
+    {
+        TypeRef::new(self.id.clone(), self.serial)
+    }
+
+    /// Get a new serial number for a type instance.
+    pub(crate) fn next_serial() -> u64 {
+        static NEXT_SERIAL: AtomicU64 = AtomicU64::new(0);
+
+        NEXT_SERIAL.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Get the rules between the entries of the type compiled when it was instantiated, if it is
+    /// a dictionary type with constraints, requirements or invariants.
+    pub fn compiled_rules(&self) -> Option<&CompiledEntryRules> {
+        match &self.attributes {
+            TypeAttributesInstance::Dictionary(a) => a.compiled_rules(),
+            _ => None,
+        }
+    }
+}
+
+impl<Id: Clone, FieldName: Ord + Display + Clone> TypeDefinitionInstance<Id, FieldName> {
+    /// Get the type definition the type instance was instantiated from.
+    pub(crate) fn to_definition(&self) -> TypeDefinition<Id, FieldName> {
+        TypeDefinition {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            attributes: self.attributes.uninstantiate(),
+        }
+    }
+}
+
+impl<Id, FieldName: Ord + Clone + Borrow<str>> TypeDefinitionInstance<Id, FieldName> {
+    /// Validate a JSON value against the type instance.
+    ///
+    /// Unlike [`Value::parse_for`](crate::Value::parse_for), this reports all the violations
+    /// instead of stopping at the first one. Values are only constructed to be passed to the
+    /// [validators](crate::Validator) of their types, once they are otherwise valid.
+    pub fn validate(self: &Arc<Self>, value: &serde_json::Value) -> ValidationReport {
+        self.validate_with_options(value, &ParseOptions::default())
+    }
+
+    /// Validate a JSON value against the type instance, using the specified parse options.
+    pub fn validate_with_options(
+        self: &Arc<Self>,
+        value: &serde_json::Value,
+        options: &ParseOptions,
+    ) -> ValidationReport {
+        crate::value::validate(self, value, options)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<Id, FieldName> TypeDefinitionInstance<Id, FieldName>
+where
+    Id: Send + Sync,
+    FieldName: Ord + Clone + Borrow<str> + Send + Sync,
+{
+    /// Validate a batch of JSON values against the type instance, in parallel.
+    ///
+    /// The results are returned in the order of the values.
+    pub fn validate_many(
+        self: &Arc<Self>,
+        values: impl rayon::iter::IntoParallelIterator<Item = serde_json::Value>,
+    ) -> Vec<ValidationReport> {
+        self.validate_many_with_options(values, &ParseOptions::default())
+    }
+
+    /// Validate a batch of JSON values against the type instance, in parallel, using the
+    /// specified parse options.
+    ///
+    /// The results are returned in the order of the values.
+    pub fn validate_many_with_options(
+        self: &Arc<Self>,
+        values: impl rayon::iter::IntoParallelIterator<Item = serde_json::Value>,
+        options: &ParseOptions,
+    ) -> Vec<ValidationReport> {
+        use rayon::iter::ParallelIterator;
+
+        values
+            .into_par_iter()
+            .map(|value| self.validate_with_options(&value, options))
+            .collect()
+    }
+}
@@ -1,278 +1,1427 @@
 //! A registry of type definitions.
 
 use itertools::Itertools;
+use serde::{Deserialize, Serialize, de};
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, VecDeque},
     fmt::Display,
-    sync::Arc,
+    hash::Hash,
+    sync::{Arc, OnceLock, mpsc},
+    time::{Duration, Instant},
 };
 
-use crate::{InstantiationError, TypeDefinition, TypeDefinitionInstance};
+use crate::{
+    DiagnosticsSink, EvolutionPolicy, Fingerprint, InstantiationError, RegistrationWarning,
+    RegistrationWarningKind, RegistryEvent, SchemaVersion, SupportedLocales, TypeAttributes,
+    TypeDefinition, TypeDefinitionInstance, Validator, compatibility::backward_violations,
+    diagnostics::inspect_attributes, registry_events::Subscribers,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+/// The map type indexing the registered type definitions.
+#[cfg(not(feature = "hash-map"))]
+pub(crate) type RegistryMap<K, V> = BTreeMap<K, V>;
+
+/// The map type indexing the registered type definitions.
+///
+/// Hash maps make lookups constant-time, which matters for large registries.
+#[cfg(feature = "hash-map")]
+pub(crate) type RegistryMap<K, V> = crate::hasher::HashMap<K, V>;
+
+/// A registry of type definitions.
+///
+/// By default, the type definitions are indexed in ordered maps. The `hash-map` feature indexes
+/// them in hash maps instead, which is faster for large registries: this is why the identifiers
+/// and names of the type definitions must be hashable. The `ahash` and `fxhash` features imply
+/// `hash-map` and replace its hasher with a faster one. The results of the registry methods are
+/// ordered the same way regardless.
+#[derive(Debug, Clone)]
+pub struct TypeDefinitionRegistry<Id, FieldName: Ord + Display + Clone> {
+    /// The type definitions instances, by their identifiers.
+    pub(crate) by_id: RegistryMap<Id, Arc<TypeDefinitionInstance<Id, FieldName>>>,
+
+    /// The type definitions, by their names.
+    pub(crate) by_name: RegistryMap<FieldName, Arc<TypeDefinitionInstance<Id, FieldName>>>,
+
+    /// The identifiers of the type definitions that reference each type definition directly, by
+    /// the identifiers of the referenced type definitions.
+    pub(crate) dependents: RegistryMap<Id, BTreeSet<Id>>,
+
+    /// The names of the declared type definitions that are not defined yet, by their identifiers.
+    pub(crate) declared: BTreeMap<Id, FieldName>,
+
+    /// The identifiers of the declared type definitions that are not defined yet, by their names.
+    pub(crate) declared_by_name: RegistryMap<FieldName, Id>,
+
+    /// The type definitions waiting for declared type definitions to be defined.
+    pub(crate) pending: Vec<TypeDefinition<Id, FieldName>>,
+
+    /// The subscribers to the changes of the registry.
+    pub(crate) subscribers: Subscribers<Id, FieldName>,
+
+    /// The last changes of the registry, oldest first.
+    pub(crate) history: VecDeque<HistoryEntry<Id, FieldName>>,
+
+    /// The maximum number of changes kept in the history.
+    pub(crate) history_capacity: usize,
+
+    /// The version of the schema.
+    pub(crate) schema_version: SchemaVersion,
+
+    /// The rules enforced when type definitions are replaced.
+    pub(crate) evolution_policy: EvolutionPolicy,
+
+    /// The locales of localized content, if configured.
+    pub(crate) supported_locales: Option<SupportedLocales<Id>>,
+}
+
+impl<Id, FieldName: Ord + Display + Clone> Default for TypeDefinitionRegistry<Id, FieldName> {
+    fn default() -> Self {
+        Self {
+            by_id: RegistryMap::default(),
+            by_name: RegistryMap::default(),
+            dependents: RegistryMap::default(),
+            declared: BTreeMap::new(),
+            declared_by_name: RegistryMap::default(),
+            pending: Vec::new(),
+            subscribers: Subscribers::default(),
+            history: VecDeque::new(),
+            history_capacity: DEFAULT_HISTORY_CAPACITY,
+            schema_version: SchemaVersion::default(),
+            evolution_policy: EvolutionPolicy::default(),
+            supported_locales: None,
+        }
+    }
+}
+
+/// The previous type instances of the type definitions affected by a change of a registry, with
+/// `None` for the type definitions that did not exist.
+pub(crate) type Change<Id, FieldName> =
+    BTreeMap<Id, Option<Arc<TypeDefinitionInstance<Id, FieldName>>>>;
+
+/// A change of a registry, as recorded in its history.
+#[derive(Debug, Clone)]
+pub(crate) struct HistoryEntry<Id, FieldName: Ord> {
+    /// The previous type instances of the type definitions affected by the change.
+    pub(crate) change: Change<Id, FieldName>,
+
+    /// The fingerprint of the registry before the change, once computed.
+    pub(crate) fingerprint: OnceLock<Fingerprint>,
+}
+
+/// The number of changes a registry keeps in its history by default.
+const DEFAULT_HISTORY_CAPACITY: usize = 64;
+
+/// The version of the document format a registry serializes to.
+const REGISTRY_DOCUMENT_VERSION: u32 = 1;
+
+/// A registry serialized as a single document.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct RegistryDocument<Id, FieldName: Ord + Display + Clone> {
+    /// The version of the document format.
+    version: u32,
+
+    /// The version of the schema.
+    #[serde(default, skip_serializing_if = "SchemaVersion::is_initial")]
+    schema_version: SchemaVersion,
+
+    /// The type definitions, in topological order.
+    type_definitions: Vec<TypeDefinition<Id, FieldName>>,
+}
+
+/// A registry serializes as a single versioned document, containing all its type definitions.
+impl<Id, FieldName> Serialize for TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display + Serialize,
+    FieldName: Ord + Hash + Clone + Display + Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RegistryDocument {
+            version: REGISTRY_DOCUMENT_VERSION,
+            schema_version: self.schema_version,
+            type_definitions: self.to_definitions(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializing a registry registers all the type definitions of the document: it fails if any
+/// of them cannot be registered.
+impl<'de, Id, FieldName> Deserialize<'de> for TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display + Deserialize<'de>,
+    FieldName: Ord + Hash + Clone + Display + Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let document = RegistryDocument::<Id, FieldName>::deserialize(deserializer)?;
+
+        if document.version != REGISTRY_DOCUMENT_VERSION {
+            return Err(de::Error::custom(format!(
+                "unsupported registry document version {}, expected {REGISTRY_DOCUMENT_VERSION}",
+                document.version
+            )));
+        }
+
+        let mut registry = Self {
+            schema_version: document.schema_version,
+            ..Self::default()
+        };
+
+        let outcome = registry.register(document.type_definitions);
+
+        match outcome.failed.into_iter().next() {
+            Some((td, err)) => Err(de::Error::custom(format!(
+                "type definition `{}` ({}): {err}",
+                td.name, td.id
+            ))),
+            None => Ok(registry),
+        }
+    }
+}
+
+/// The renaming of a type definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeRenaming<Id, FieldName> {
+    /// The identifier of the type definition.
+    pub(crate) id: Id,
+
+    /// The name of the type definition before the renaming.
+    pub(crate) old_name: FieldName,
+
+    /// The name of the type definition after the renaming.
+    pub(crate) new_name: FieldName,
+}
+
+impl<Id, FieldName> TypeRenaming<Id, FieldName> {
+    /// Get the identifier of the type definition.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Get the name of the type definition before the renaming.
+    pub fn old_name(&self) -> &FieldName {
+        &self.old_name
+    }
+
+    /// Get the name of the type definition after the renaming.
+    pub fn new_name(&self) -> &FieldName {
+        &self.new_name
+    }
+}
+
+/// The outcome of the registration of a batch of type definitions.
+#[derive(Debug)]
+pub struct RegistrationOutcome<Id, FieldName: Ord + Display + Clone> {
+    /// The type instances of the registered type definitions.
+    pub(crate) registered: Vec<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+
+    /// The type definitions that could not be registered, with the reason why.
+    pub(crate) failed: Vec<(
+        TypeDefinition<Id, FieldName>,
+        RegistrationError<Id, FieldName>,
+    )>,
+
+    /// The warnings emitted while registering the type definitions.
+    pub(crate) warnings: Vec<RegistrationWarning<Id, FieldName>>,
+
+    /// The time spent instantiating each registered type definition, by identifier.
+    pub(crate) timings: BTreeMap<Id, Duration>,
+}
+
+impl<Id, FieldName: Ord + Display + Clone> RegistrationOutcome<Id, FieldName> {
+    /// Get the type instances of the registered type definitions, in registration order.
+    pub fn registered(&self) -> &[Arc<TypeDefinitionInstance<Id, FieldName>>] {
+        &self.registered
+    }
+
+    /// Get the type definitions that could not be registered, with the reason why.
+    #[expect(
+        clippy::type_complexity,
+        reason = "inherent associated types are not yet stable so we can't do much about it here"
+    )]
+    pub fn failed(
+        &self,
+    ) -> &[(
+        TypeDefinition<Id, FieldName>,
+        RegistrationError<Id, FieldName>,
+    )] {
+        &self.failed
+    }
+
+    /// Get the warnings emitted while registering the type definitions.
+    ///
+    /// The type definitions were registered regardless.
+    pub fn warnings(&self) -> &[RegistrationWarning<Id, FieldName>] {
+        &self.warnings
+    }
+
+    /// Get the time spent instantiating a registered type definition.
+    ///
+    /// Returns `None` if the type definition was not registered by this batch.
+    pub fn timing(&self, id: &Id) -> Option<Duration>
+    where
+        Id: Ord,
+    {
+        self.timings.get(id).copied()
+    }
+
+    /// Get the time spent instantiating each registered type definition.
+    pub fn timings(&self) -> impl Iterator<Item = (&Id, Duration)> {
+        self.timings.iter().map(|(id, timing)| (id, *timing))
+    }
+
+    /// Check whether all the type definitions were registered.
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Get the registered type instances, or the failed type definitions if any type definition
+    /// could not be registered.
+    #[expect(
+        clippy::type_complexity,
+        reason = "inherent associated types are not yet stable so we can't do much about it here"
+    )]
+    pub fn into_result(
+        self,
+    ) -> Result<
+        Vec<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+        Vec<(
+            TypeDefinition<Id, FieldName>,
+            RegistrationError<Id, FieldName>,
+        )>,
+    > {
+        if self.failed.is_empty() {
+            Ok(self.registered)
+        } else {
+            Err(self.failed)
+        }
+    }
+
+    /// Split the outcome into the registered type instances and the failed type definitions.
+    #[expect(
+        clippy::type_complexity,
+        reason = "inherent associated types are not yet stable so we can't do much about it here"
+    )]
+    pub fn into_parts(
+        self,
+    ) -> (
+        Vec<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+        Vec<(
+            TypeDefinition<Id, FieldName>,
+            RegistrationError<Id, FieldName>,
+        )>,
+    ) {
+        (self.registered, self.failed)
+    }
+}
+
+/// An error that can occur when registering type definitions.
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum RegistrationError<Id, FieldName> {
+    /// A type definition with the same identifier already exists.
+    #[error("another type definition `{existing_name}` with the same id already exists")]
+    DuplicateTypeDefinition { existing_name: FieldName },
+
+    /// A type definition with the same name already exists.
+    #[error("another type definition with id `{existing_id}` has the same name")]
+    DuplicateTypeDefinitionName { existing_id: Id },
+
+    /// A type definition has a broken reference.
+    #[error("type definition has a broken reference to type definition `{referenced_id}`")]
+    BrokenReference { referenced_id: Id },
+
+    /// A type definition has a circular reference.
+    #[error(
+        "registering type definition would cause a circular reference cycle: {}",
+        cycle.iter().map(|(id, name)| format!("`{name}` (`{id}`)")).join(" -> ")
+    )]
+    CircularReference { cycle: Vec<(Id, FieldName)> },
+
+    /// A type definition has a blocked reference.
+    #[error("type definition has a reference to a type definition that cannot be registered")]
+    BlockedReference,
+
+    /// An error occurred while instantiating the type attributes.
+    #[error("unable to instantiate type attributes for type definition: {0}")]
+    InstantiationError(#[from] InstantiationError<Id, FieldName>),
+
+    /// The type definition was declared with another name.
+    #[error("type definition was declared with another name `{declared_name}`")]
+    DeclaredNameMismatch { declared_name: FieldName },
+
+    /// No type definition with the same identifier is registered, so there is nothing to replace.
+    #[error("no type definition with the same id is registered")]
+    UnknownTypeDefinition,
+
+    /// The type definition cannot be removed, as another type definition references it.
+    #[error("type definition is still referenced by type definition `{referencing_id}`")]
+    StillReferenced { referencing_id: Id },
+
+    /// The replacement type definition breaks the schema evolution rules of the registry.
+    #[error(
+        "type definition breaks the schema evolution rules: {}",
+        violations.join("; ")
+    )]
+    EvolutionRuleViolation { violations: Vec<String> },
+
+    /// A type definition without identifier references a type by a name that is not known.
+    #[error("type definition references an unknown type `{referenced_name}`")]
+    UnknownTypeName { referenced_name: FieldName },
+
+    /// The replacement type definition is incompatible with the registered one.
+    #[error("type definition is incompatible with the registered one: {0}")]
+    IncompatibleReplacement(String),
+}
+
+impl<Id: Ord + Hash + Clone + Display, FieldName: Ord + Hash + Clone + Display>
+    TypeDefinitionRegistry<Id, FieldName>
+{
+    /// Register type definitions.
+    ///
+    /// The passed-in type definitions can only have references to other, previously registered,
+    /// type definitions or to other types definitions in the same batch.
+    ///
+    /// If the batch contains broken or circular references, those type definitions will not be
+    /// registered.
+    ///
+    /// If the batch contains duplicate type definitions, those will not be registered.
+    ///
+    /// Type definitions can also reference [declared](Self::declare) type definitions that are not
+    /// defined yet: those are kept pending, and registered by the batch that completes them. A
+    /// batch also retries all the pending type definitions, and reports them as registered or
+    /// failed accordingly.
+    ///
+    /// The method returns an outcome listing all the type definitions that were registered as well
+    /// as those who were not registered alongside the reason why they were not registered.
+    ///
+    /// The order of the outcome does not depend on the order of the batch. The type definitions
+    /// are registered in rounds: each round registers, by ascending identifier, the type
+    /// definitions whose references are all registered. The failed type definitions are sorted
+    /// by identifier.
+    pub fn register(
+        &mut self,
+        type_definitions: impl IntoIterator<Item = TypeDefinition<Id, FieldName>>,
+    ) -> RegistrationOutcome<Id, FieldName> {
+        self.register_with_diagnostics(type_definitions, &mut ())
+    }
+
+    /// Register type definitions, reporting the warnings emitted while registering them to a
+    /// diagnostics sink.
+    ///
+    /// The warnings are reported to the sink as they are emitted, and are also part of the
+    /// returned outcome. See [`register`](Self::register) for details.
+    pub fn register_with_diagnostics(
+        &mut self,
+        type_definitions: impl IntoIterator<Item = TypeDefinition<Id, FieldName>>,
+        sink: &mut impl DiagnosticsSink<Id, FieldName>,
+    ) -> RegistrationOutcome<Id, FieldName> {
+        self.register_in_rounds(type_definitions, sink, |_, round| {
+            round.iter().map(|_| None).collect()
+        })
+    }
+
+    /// Register type definitions in rounds, as [`register`](Self::register) describes.
+    ///
+    /// At the start of each round, `instantiate_round` may instantiate some of the type
+    /// definitions of the round ahead of their registration, given the registered type instances
+    /// by identifier. It returns the instantiated attributes in the order of the round.
+    fn register_in_rounds(
+        &mut self,
+        type_definitions: impl IntoIterator<Item = TypeDefinition<Id, FieldName>>,
+        sink: &mut impl DiagnosticsSink<Id, FieldName>,
+        instantiate_round: impl Fn(
+            &RegistryMap<Id, Arc<TypeDefinitionInstance<Id, FieldName>>>,
+            &mut [(Vec<Id>, TypeDefinition<Id, FieldName>)],
+        ) -> Vec<Option<Instantiated<Id, FieldName>>>,
+    ) -> RegistrationOutcome<Id, FieldName> {
+        // This gives us a list of all the type definitions to register, with the references they
+        // have.
+        let mut type_definitions: Vec<_> = std::mem::take(&mut self.pending)
+            .into_iter()
+            .chain(type_definitions)
+            .map(|td| {
+                (
+                    td.attributes
+                        .external_identifier_references()
+                        .into_iter()
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                    td,
+                )
+            })
+            .collect();
+
+        // By sorting the definitions by identifier, the registration order does not depend on the
+        // order of the batch: it is the order of the type definitions from then on.
+        type_definitions.sort_by(|(_, a), (_, b)| (&a.id, &a.name).cmp(&(&b.id, &b.name)));
+
+        // The number of distinct references of each type definition that are not registered yet,
+        // and the type definitions waiting for each unregistered identifier.
+        let mut missing = Vec::with_capacity(type_definitions.len());
+        let mut waiting: BTreeMap<Id, Vec<usize>> = BTreeMap::new();
+
+        for (index, (refs, _)) in type_definitions.iter().enumerate() {
+            let refs: BTreeSet<_> = refs
+                .iter()
+                .filter(|ref_| !self.by_id.contains_key(ref_))
+                .collect();
+
+            for ref_ in &refs {
+                waiting.entry((*ref_).clone()).or_default().push(index);
+            }
+
+            missing.push(refs.len());
+        }
+
+        let mut type_definitions: Vec<_> = type_definitions.into_iter().map(Some).collect();
+        let mut outcome = RegistrationOutcome {
+            registered: Vec::new(),
+            failed: Vec::new(),
+            warnings: Vec::new(),
+            timings: BTreeMap::new(),
+        };
+
+        // Each round registers, in order, the type definitions whose references are registered.
+        // A type definition whose last reference gets registered joins the current round if it
+        // comes after it, or the next one otherwise.
+        let mut round: BTreeSet<_> = (0..type_definitions.len())
+            .filter(|&index| missing[index] == 0)
+            .collect();
+        let mut next_round = BTreeSet::new();
+
+        while !round.is_empty() {
+            let indices: Vec<_> = round.iter().copied().collect();
+            let mut ready: Vec<_> = indices
+                .iter()
+                .map(|&index| {
+                    type_definitions[index]
+                        .take()
+                        .expect("ready type definition")
+                })
+                .collect();
+            let instantiated = instantiate_round(&self.by_id, &mut ready);
+            let mut ahead = BTreeMap::new();
+
+            for ((index, entry), instantiated) in indices.into_iter().zip(ready).zip(instantiated) {
+                type_definitions[index] = Some(entry);
+
+                if let Some(instantiated) = instantiated {
+                    ahead.insert(index, instantiated);
+                }
+            }
+
+            while let Some(index) = round.pop_first() {
+                let (refs, mut td) = type_definitions[index]
+                    .take()
+                    .expect("ready type definition");
+                let instantiated = ahead.remove(&index);
+
+                if let Some(err) = self.registration_conflict(&td) {
+                    // Type definitions instantiated ahead get their attributes back.
+                    if let Some(Instantiated {
+                        attributes: Ok(attributes),
+                        ..
+                    }) = instantiated
+                    {
+                        td.attributes = attributes.uninstantiate();
+                    }
+
+                    outcome.failed.push((td, err));
+
+                    continue;
+                }
+
+                let refs_by_id = refs
+                    .iter()
+                    .map(|ref_| {
+                        let inst = self.by_id.get(ref_).expect("registered reference");
+
+                        (ref_.clone(), Arc::clone(inst))
+                    })
+                    .collect();
+
+                // Instantiate the type attributes, unless it was done ahead: this can fail if the
+                // type attributes are incompatible (for instance if the key type of a dictionary
+                // is not a key-type).
+                let Instantiated {
+                    attributes,
+                    warnings,
+                    elapsed,
+                } = match instantiated {
+                    Some(instantiated) => instantiated,
+                    None => Instantiated::new(&mut td, refs_by_id),
+                };
+
+                let attributes = match attributes {
+                    Ok(attributes) => attributes,
+                    Err(err) => {
+                        outcome
+                            .failed
+                            .push((td, RegistrationError::InstantiationError(err)));
+
+                        continue;
+                    }
+                };
+
+                for kind in warnings {
+                    let warning = RegistrationWarning {
+                        id: td.id.clone(),
+                        name: td.name.clone(),
+                        kind,
+                    };
+
+                    sink.registration_warning(warning.clone());
+                    outcome.warnings.push(warning);
+                }
+
+                // At this point all the references were looked up and there are no duplicates: we
+                // can register the type definition.
+                let type_definition_instance = TypeDefinitionInstance {
+                    id: td.id,
+                    name: td.name,
+                    description: td.description,
+                    attributes,
+                    validator: None,
+                    serial: TypeDefinitionInstance::<Id, FieldName>::next_serial(),
+                };
+
+                // The type definitions waiting for this one may now be ready.
+                for waiting_index in waiting
+                    .remove(&type_definition_instance.id)
+                    .unwrap_or_default()
+                {
+                    missing[waiting_index] -= 1;
+
+                    if missing[waiting_index] == 0 {
+                        match waiting_index > index {
+                            true => round.insert(waiting_index),
+                            false => next_round.insert(waiting_index),
+                        };
+                    }
+                }
+
+                // Register the type definition.
+                if let Some(name) = self.declared.remove(&type_definition_instance.id) {
+                    self.declared_by_name.remove(&name);
+                }
+                outcome
+                    .timings
+                    .insert(type_definition_instance.id.clone(), elapsed);
+                outcome
+                    .registered
+                    .push(self.insert_type_definition_instance(type_definition_instance));
+            }
+
+            round = std::mem::take(&mut next_round);
+        }
+
+        // The remaining type definitions have references that are not registered: those that
+        // conflict with a registered type definition fail as such first.
+        let mut postponed_type_definitions = Vec::new();
+
+        for (refs, td) in type_definitions.into_iter().flatten() {
+            match self.registration_conflict(&td) {
+                Some(err) => outcome.failed.push((td, err)),
+                None => postponed_type_definitions.push((refs, td)),
+            }
+        }
+
+        let mut type_definitions = std::mem::take(&mut postponed_type_definitions);
+
+        // Compute a list of all remaining identifiers to register.
+        let remaining_ids: BTreeSet<_> = type_definitions
+            .iter()
+            .map(|(_, td)| td.id.clone())
+            .collect();
+
+        // Check for broken references.
+        'outer: for (refs, td) in type_definitions {
+            for ref_ in &refs {
+                if !(remaining_ids.contains(ref_)
+                    || self.by_id.contains_key(ref_)
+                    || self.declared.contains_key(ref_))
+                {
+                    outcome.failed.push((
+                        td,
+                        RegistrationError::BrokenReference {
+                            referenced_id: ref_.clone(),
+                        },
+                    ));
+
+                    continue 'outer;
+                }
+            }
+
+            postponed_type_definitions.push((refs, td));
+        }
+
+        type_definitions = std::mem::take(&mut postponed_type_definitions);
+
+        // The remaining type definitions are the ones that lead to circular references.
+        loop {
+            let deps = type_definitions
+                .iter()
+                .map(|(refs, td)| (td.id.clone(), refs.iter().cloned().collect()))
+                .collect::<BTreeMap<_, _>>();
+
+            let cycle = detect_minimal_cycle(&deps);
+
+            if cycle.is_empty() {
+                // No cycle found: we can break.
+                break;
+            }
+
+            let mut cyclic_type_definitions = Vec::with_capacity(cycle.len() - 1);
+
+            for (refs_, td) in std::mem::take(&mut type_definitions) {
+                if cycle.contains(&td.id) {
+                    cyclic_type_definitions.push(td);
+                } else {
+                    postponed_type_definitions.push((refs_, td));
+                }
+            }
+
+            type_definitions = std::mem::take(&mut postponed_type_definitions);
+
+            let cycle = cycle
+                .into_iter()
+                .map(|id| {
+                    // It's impossible for the cycle to contain an id that is not in the new
+                    // type definitions, as the already registered type definitions are
+                    // guaranteed to not contain any external references by this very function.
+
+                    let td = cyclic_type_definitions
+                        .iter()
+                        .find(|td| td.id == id)
+                        .expect("we should have a type definition for this id");
+                    (td.id.clone(), td.name.clone())
+                })
+                .collect::<Vec<_>>();
+
+            for td in cyclic_type_definitions {
+                outcome.failed.push((
+                    td,
+                    RegistrationError::CircularReference {
+                        cycle: cycle.clone(),
+                    },
+                ));
+            }
+        }
+
+        // The remaining type definitions either wait for declared type definitions or
+        // lead to circular references without being part of a cycle.
+        loop {
+            let remaining_ids: BTreeSet<_> = type_definitions
+                .iter()
+                .map(|(_, td)| td.id.clone())
+                .collect();
+
+            let (blocked, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut type_definitions)
+                .into_iter()
+                .partition(|(refs, _)| {
+                    refs.iter().any(|ref_| {
+                        !(remaining_ids.contains(ref_)
+                            || self.by_id.contains_key(ref_)
+                            || self.declared.contains_key(ref_))
+                    })
+                });
+
+            type_definitions = waiting;
+
+            if blocked.is_empty() {
+                break;
+            }
+
+            for (_, td) in blocked {
+                outcome
+                    .failed
+                    .push((td, RegistrationError::BlockedReference));
+            }
+        }
+
+        self.pending
+            .extend(type_definitions.into_iter().map(|(_, td)| td));
+
+        outcome
+            .failed
+            .sort_by(|(a, _), (b, _)| (&a.id, &a.name).cmp(&(&b.id, &b.name)));
+
+        self.record_change(
+            outcome
+                .registered
+                .iter()
+                .map(|instance| (instance.id.clone(), None))
+                .collect(),
+        );
+
+        self.subscribers.notify(|| {
+            let registered = outcome
+                .registered
+                .iter()
+                .map(|inst| RegistryEvent::Registered(Arc::clone(inst)));
+            let failed = outcome
+                .failed
+                .iter()
+                .map(|(td, err)| RegistryEvent::Failed(td.clone(), err.clone()));
+
+            registered.chain(failed)
+        });
+
+        outcome
+    }
+
+    /// Replace a registered type definition.
+    ///
+    /// The type definitions that reference it, directly or transitively, are re-instantiated to
+    /// reference the new type instance. If any of them cannot be re-instantiated, for instance
+    /// because a dictionary key type is no longer a key type, nothing is replaced.
+    ///
+    /// The method returns the new type instances, starting with the one of the replaced type
+    /// definition. Values parsed before the replacement keep referencing the previous type
+    /// instances. The validators of the type definitions are kept.
+    #[expect(
+        clippy::type_complexity,
+        reason = "inherent associated types are not yet stable so we can't do much about it here"
+    )]
+    pub fn replace(
+        &mut self,
+        type_definition: TypeDefinition<Id, FieldName>,
+    ) -> Result<
+        Vec<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+        (
+            TypeDefinition<Id, FieldName>,
+            RegistrationError<Id, FieldName>,
+        ),
+    > {
+        self.replace_with_check(type_definition, |_, _| Ok(()))
+    }
+
+    /// Replace a registered type definition, if the new type instance is compatible with the
+    /// previous one.
+    ///
+    /// The `check` function is called with the previous and the new type instances, and returns
+    /// why they are incompatible, if they are.
+    ///
+    /// See [`replace`](Self::replace) for details.
+    #[expect(
+        clippy::type_complexity,
+        reason = "inherent associated types are not yet stable so we can't do much about it here"
+    )]
+    pub fn replace_with_check(
+        &mut self,
+        type_definition: TypeDefinition<Id, FieldName>,
+        check: impl FnOnce(
+            &TypeDefinitionInstance<Id, FieldName>,
+            &TypeDefinitionInstance<Id, FieldName>,
+        ) -> Result<(), String>,
+    ) -> Result<
+        Vec<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+        (
+            TypeDefinition<Id, FieldName>,
+            RegistrationError<Id, FieldName>,
+        ),
+    > {
+        let validator = self
+            .by_id
+            .get(&type_definition.id)
+            .and_then(|previous| previous.validator.clone());
+
+        self.replace_instance(type_definition, validator, check)
+    }
+
+    /// Attach a custom validator to a registered type definition, replacing its current one, or
+    /// detach it with `None`.
+    ///
+    /// The type instance is replaced by one with the validator, and so are the type instances
+    /// that reference it, directly or transitively, as [`replace`](Self::replace) does. The method
+    /// returns the new type instances, starting with the one of the type definition. Values parsed
+    /// before keep referencing the previous type instances.
+    #[expect(
+        clippy::type_complexity,
+        reason = "inherent associated types are not yet stable so we can't do much about it here"
+    )]
+    pub fn set_validator(
+        &mut self,
+        id: &Id,
+        validator: Option<Arc<dyn Validator<Id, FieldName>>>,
+    ) -> Result<Vec<Arc<TypeDefinitionInstance<Id, FieldName>>>, RegistrationError<Id, FieldName>>
+    {
+        let Some(instance) = self.by_id.get(id) else {
+            return Err(RegistrationError::UnknownTypeDefinition);
+        };
+
+        self.replace_instance(instance.to_definition(), validator, |_, _| Ok(()))
+            .map_err(|(_, err)| err)
+    }
+
+    /// Replace a registered type definition, with the specified validator.
+    ///
+    /// See [`replace_with_check`](Self::replace_with_check) for details.
+    #[expect(
+        clippy::type_complexity,
+        reason = "inherent associated types are not yet stable so we can't do much about it here"
+    )]
+    fn replace_instance(
+        &mut self,
+        type_definition: TypeDefinition<Id, FieldName>,
+        validator: Option<Arc<dyn Validator<Id, FieldName>>>,
+        check: impl FnOnce(
+            &TypeDefinitionInstance<Id, FieldName>,
+            &TypeDefinitionInstance<Id, FieldName>,
+        ) -> Result<(), String>,
+    ) -> Result<
+        Vec<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+        (
+            TypeDefinition<Id, FieldName>,
+            RegistrationError<Id, FieldName>,
+        ),
+    > {
+        let td = type_definition;
+
+        let Some(previous) = self.by_id.get(&td.id) else {
+            return Err((td, RegistrationError::UnknownTypeDefinition));
+        };
+
+        if self.evolution_policy == EvolutionPolicy::BackwardCompatible {
+            let violations = backward_violations(&previous.to_definition(), &td);
+
+            if !violations.is_empty() {
+                return Err((td, RegistrationError::EvolutionRuleViolation { violations }));
+            }
+        }
+
+        if let Some(existing) = self.by_name.get(&td.name)
+            && existing.id != td.id
+        {
+            let existing_id = existing.id.clone();
+
+            return Err((
+                td,
+                RegistrationError::DuplicateTypeDefinitionName { existing_id },
+            ));
+        }
+
+        if let Some(existing_id) = self.declaration_of(&td.name) {
+            let existing_id = existing_id.clone();
+
+            return Err((
+                td,
+                RegistrationError::DuplicateTypeDefinitionName { existing_id },
+            ));
+        }
+
+        let refs: BTreeSet<Id> = td
+            .attributes
+            .external_identifier_references()
+            .into_iter()
+            .cloned()
+            .collect();
+
+        if let Some(ref_) = refs.iter().find(|ref_| !self.by_id.contains_key(ref_)) {
+            let referenced_id = ref_.clone();
+
+            return Err((td, RegistrationError::BrokenReference { referenced_id }));
+        }
+
+        // The type definitions that reference the replaced one, directly or not, and their
+        // references.
+        let mut stale = self.transitive_dependents(&td.id);
+        let deps: BTreeMap<Id, BTreeSet<Id>> = stale
+            .iter()
+            .map(|id| (id.clone(), references_of(&self.by_id[id])))
+            .collect();
+
+        // The registered type definitions have no cycles, so any cycle goes through the
+        // replacement, and thus only through the type definitions that reference it.
+        let in_cycle = |id: &Id| *id == td.id || stale.contains(id);
+
+        if refs.iter().any(in_cycle) {
+            let cycle_deps = deps
+                .iter()
+                .chain([(&td.id, &refs)])
+                .map(|(id, refs)| {
+                    (
+                        id.clone(),
+                        refs.iter().filter(|ref_| in_cycle(ref_)).cloned().collect(),
+                    )
+                })
+                .collect();
+
+            let cycle = detect_minimal_cycle(&cycle_deps);
+            let cycle = cycle
+                .into_iter()
+                .map(|id| {
+                    let name = if id == td.id {
+                        td.name.clone()
+                    } else {
+                        self.by_id[&id].name.clone()
+                    };
+
+                    (id, name)
+                })
+                .collect();
+
+            return Err((td, RegistrationError::CircularReference { cycle }));
+        }
+
+        let refs_by_id = refs
+            .iter()
+            .map(|ref_| (ref_.clone(), Arc::clone(&self.by_id[ref_])))
+            .collect();
+
+        let attributes = match td.attributes.clone().instantiate(refs_by_id) {
+            Ok(attributes) => attributes,
+            Err((_, err)) => return Err((td, RegistrationError::InstantiationError(err))),
+        };
+
+        let instance = Arc::new(TypeDefinitionInstance {
+            id: td.id.clone(),
+            name: td.name.clone(),
+            description: td.description.clone(),
+            attributes,
+            validator,
+            serial: TypeDefinitionInstance::<Id, FieldName>::next_serial(),
+        });
+
+        if let Err(reason) = check(previous, &instance) {
+            return Err((td, RegistrationError::IncompatibleReplacement(reason)));
+        }
+
+        let mut replaced = BTreeMap::from([(td.id.clone(), Arc::clone(&instance))]);
+        let mut instances = vec![instance];
+
+        // Re-instantiate the stale type definitions once all their references are up to date.
+        while !stale.is_empty() {
+            let ready: Vec<Id> = stale
+                .iter()
+                .filter(|id| deps[*id].iter().all(|ref_| !stale.contains(ref_)))
+                .cloned()
+                .collect();
+
+            for id in ready {
+                stale.remove(&id);
+
+                let previous = &self.by_id[&id];
+                let refs_by_id = deps[&id]
+                    .iter()
+                    .map(|ref_| {
+                        let inst = replaced.get(ref_).unwrap_or(&self.by_id[ref_]);
+
+                        (ref_.clone(), Arc::clone(inst))
+                    })
+                    .collect();
+
+                let attributes = match previous.attributes.uninstantiate().instantiate(refs_by_id) {
+                    Ok(attributes) => attributes,
+                    Err((_, err)) => return Err((td, RegistrationError::InstantiationError(err))),
+                };
+
+                let instance = Arc::new(TypeDefinitionInstance {
+                    id: previous.id.clone(),
+                    name: previous.name.clone(),
+                    description: previous.description.clone(),
+                    attributes,
+                    validator: previous.validator.clone(),
+                    serial: TypeDefinitionInstance::<Id, FieldName>::next_serial(),
+                });
+
+                replaced.insert(id, Arc::clone(&instance));
+                instances.push(instance);
+            }
+        }
+
+        // Everything was re-instantiated successfully: we can commit the replacement. Only the
+        // references of the replaced type definition changed.
+        let previous = Arc::clone(&self.by_id[&td.id]);
+
+        if previous.name != td.name {
+            self.by_name.remove(&previous.name);
+        }
+
+        self.unindex_references(&previous);
+        self.index_references(&instances[0]);
+
+        let mut events = Vec::with_capacity(instances.len());
+        let mut change = BTreeMap::new();
+
+        for instance in &instances {
+            if let Some(previous) = self.by_id.insert(instance.id.clone(), Arc::clone(instance)) {
+                change.insert(instance.id.clone(), Some(Arc::clone(&previous)));
+                events.push(RegistryEvent::Replaced {
+                    previous,
+                    current: Arc::clone(instance),
+                });
+            }
+
+            self.by_name
+                .insert(instance.name.clone(), Arc::clone(instance));
+        }
+
+        self.record_change(change);
+        self.subscribers.notify(|| events);
+
+        Ok(instances)
+    }
+
+    /// Subscribe to the changes of the registry.
+    ///
+    /// The returned receiver gets an event for every type definition that is registered, fails to
+    /// register, is replaced or is removed, in the order the changes happen. Dropping the receiver
+    /// unsubscribes.
+    pub fn subscribe(&mut self) -> mpsc::Receiver<RegistryEvent<Id, FieldName>> {
+        self.subscribers.subscribe()
+    }
+
+    /// Get the version of the schema.
+    pub fn schema_version(&self) -> SchemaVersion {
+        self.schema_version
+    }
+
+    /// Set the version of the schema.
+    ///
+    /// The version is not updated automatically: it is up to the owner of the registry to bump it
+    /// when the type definitions change. See [`negotiate`](Self::negotiate).
+    pub fn set_schema_version(&mut self, schema_version: SchemaVersion) {
+        self.schema_version = schema_version;
+    }
+
+    /// Get the rules enforced when type definitions are replaced.
+    pub fn evolution_policy(&self) -> EvolutionPolicy {
+        self.evolution_policy
+    }
+
+    /// Set the rules enforced when type definitions are replaced.
+    ///
+    /// This applies to all the ways of replacing type definitions, including
+    /// [renaming](Self::rename) them and [applying deltas](Self::apply_delta).
+    pub fn set_evolution_policy(&mut self, evolution_policy: EvolutionPolicy) {
+        self.evolution_policy = evolution_policy;
+    }
 
-/// A registry of type definitions.
-#[derive(Debug, Clone, Default)]
-pub struct TypeDefinitionRegistry<Id, FieldName: Ord + Display + Clone> {
-    /// The type definitions instances, by their identifiers.
-    by_id: BTreeMap<Id, Arc<TypeDefinitionInstance<Id, FieldName>>>,
+    /// Declare a type definition, without defining it yet.
+    ///
+    /// Declaring a type definition reserves its identifier and name, and allows
+    /// [registering](Self::register) type definitions that reference it before it is defined.
+    /// Declaring an already declared type definition again, with the same name, does nothing.
+    pub fn declare(
+        &mut self,
+        id: Id,
+        name: FieldName,
+    ) -> Result<(), RegistrationError<Id, FieldName>> {
+        if let Some(existing) = self.by_id.get(&id) {
+            return Err(RegistrationError::DuplicateTypeDefinition {
+                existing_name: existing.name.clone(),
+            });
+        }
 
-    /// The type definitions, by their names.
-    by_name: BTreeMap<FieldName, Arc<TypeDefinitionInstance<Id, FieldName>>>,
-}
+        if let Some(existing_name) = self.declared.get(&id) {
+            if *existing_name == name {
+                return Ok(());
+            }
 
-/// An error that can occur when registering type definitions.
-#[derive(Debug, thiserror::Error, PartialEq, Eq)]
-pub enum RegistrationError<Id, FieldName> {
-    /// A type definition with the same identifier already exists.
-    #[error("another type definition `{existing_name}` with the same id already exists")]
-    DuplicateTypeDefinition { existing_name: FieldName },
+            return Err(RegistrationError::DuplicateTypeDefinition {
+                existing_name: existing_name.clone(),
+            });
+        }
 
-    /// A type definition with the same name already exists.
-    #[error("another type definition with id `{existing_id}` has the same name")]
-    DuplicateTypeDefinitionName { existing_id: Id },
+        if let Some(existing) = self.by_name.get(&name) {
+            return Err(RegistrationError::DuplicateTypeDefinitionName {
+                existing_id: existing.id.clone(),
+            });
+        }
 
-    /// A type definition has a broken reference.
-    #[error("type definition has a broken reference to type definition `{referenced_id}`")]
-    BrokenReference { referenced_id: Id },
+        if let Some(existing_id) = self.declaration_of(&name) {
+            return Err(RegistrationError::DuplicateTypeDefinitionName {
+                existing_id: existing_id.clone(),
+            });
+        }
 
-    /// A type definition has a circular reference.
-    #[error(
-        "registering type definition would cause a circular reference cycle: {}",
-        cycle.iter().map(|(id, name)| format!("`{name}` (`{id}`)")).join(" -> ")
-    )]
-    CircularReference { cycle: Vec<(Id, FieldName)> },
+        self.declared_by_name.insert(name.clone(), id.clone());
+        self.declared.insert(id, name);
 
-    /// A type definition has a blocked reference.
-    #[error("type definition has a reference to a type definition that cannot be registered")]
-    BlockedReference,
+        Ok(())
+    }
 
-    /// An error occurred while instantiating the type attributes.
-    #[error("unable to instantiate type attributes for type definition: {0}")]
-    InstantiationError(#[from] InstantiationError<Id, FieldName>),
-}
+    /// Get the declared type definitions that are not defined yet, with their names.
+    pub fn declarations(&self) -> impl Iterator<Item = (&Id, &FieldName)> {
+        self.declared.iter()
+    }
 
-impl<Id: Ord + Clone + Display, FieldName: Ord + Clone + Display>
-    TypeDefinitionRegistry<Id, FieldName>
-{
-    /// Register type definitions.
-    ///
-    /// The passed-in type definitions can only have references to other, previously registered,
-    /// type definitions or to other types definitions in the same batch.
+    /// Get the type definitions waiting for declared type definitions to be defined.
+    pub fn pending(&self) -> &[TypeDefinition<Id, FieldName>] {
+        &self.pending
+    }
+
+    /// Get the identifier of the declared type definition with the specified name, if any.
+    pub(crate) fn declaration_of(&self, name: &FieldName) -> Option<&Id> {
+        self.declared_by_name.get(name)
+    }
+
+    /// Check whether a type definition conflicts with the registered or declared type
+    /// definitions.
+    fn registration_conflict(
+        &self,
+        td: &TypeDefinition<Id, FieldName>,
+    ) -> Option<RegistrationError<Id, FieldName>> {
+        // Check for duplicate type definitions.
+        if let Some(existing) = self.by_id.get(&td.id) {
+            return Some(RegistrationError::DuplicateTypeDefinition {
+                existing_name: existing.name.clone(),
+            });
+        }
+
+        if let Some(existing) = self.by_name.get(&td.name) {
+            return Some(RegistrationError::DuplicateTypeDefinitionName {
+                existing_id: existing.id.clone(),
+            });
+        }
+
+        // Check that the type definition matches its declaration, if any.
+        if let Some(declared_name) = self.declared.get(&td.id)
+            && *declared_name != td.name
+        {
+            return Some(RegistrationError::DeclaredNameMismatch {
+                declared_name: declared_name.clone(),
+            });
+        }
+
+        if let Some(existing_id) = self.declaration_of(&td.name)
+            && *existing_id != td.id
+        {
+            return Some(RegistrationError::DuplicateTypeDefinitionName {
+                existing_id: existing_id.clone(),
+            });
+        }
+
+        None
+    }
+
+    /// Rename a registered type definition.
     ///
-    /// If the batch contains broken or circular references, those type definitions will not be
-    /// registered.
+    /// The type definitions that reference it are re-instantiated, as with
+    /// [`replace`](Self::replace). The returned renaming can be used to migrate the content that
+    /// refers to the type by its name.
+    pub fn rename(
+        &mut self,
+        id: &Id,
+        new_name: FieldName,
+    ) -> Result<TypeRenaming<Id, FieldName>, RegistrationError<Id, FieldName>> {
+        let Some(instance) = self.by_id.get(id) else {
+            return Err(RegistrationError::UnknownTypeDefinition);
+        };
+
+        let old_name = instance.name.clone();
+        let mut td = instance.to_definition();
+        td.name = new_name.clone();
+
+        self.replace(td).map_err(|(_, err)| err)?;
+
+        Ok(TypeRenaming {
+            id: id.clone(),
+            old_name,
+            new_name,
+        })
+    }
+
+    /// Remove all the type definitions that are not reachable from the specified roots.
     ///
-    /// If the batch contains duplicate type definitions, those will not be registered.
+    /// A type definition is reachable if it is a root or is referenced, directly or
+    /// transitively, by a root. Unknown root identifiers are ignored.
     ///
-    /// The method returns list of all the type definitions that were registered as well as those
-    /// who were not registered alongside the reason why they were not registered.
-    #[expect(
-        clippy::type_complexity,
-        reason = "inherent associated types are not yet stable so we can't do much about it here"
-    )]
-    pub fn register(
+    /// The method returns the removed type instances, sorted by identifier.
+    pub fn prune<'a>(
         &mut self,
-        type_definitions: impl IntoIterator<Item = TypeDefinition<Id, FieldName>>,
-    ) -> (
-        Vec<Arc<TypeDefinitionInstance<Id, FieldName>>>,
-        Vec<(
-            TypeDefinition<Id, FieldName>,
-            RegistrationError<Id, FieldName>,
-        )>,
-    ) {
-        // This gives us a list of all the type definitions to register, with the references they
-        // have.
-        let mut type_definitions: Vec<_> = type_definitions
+        roots: impl IntoIterator<Item = &'a Id>,
+    ) -> Vec<Arc<TypeDefinitionInstance<Id, FieldName>>>
+    where
+        Id: 'a,
+    {
+        let reachable = self.reachable_from(roots);
+
+        let (kept, removed) = std::mem::take(&mut self.by_id)
             .into_iter()
-            .map(|td| {
-                (
-                    td.attributes
-                        .external_identifier_references()
-                        .into_iter()
-                        .cloned()
-                        .collect::<Vec<_>>(),
-                    td,
-                )
+            .partition::<RegistryMap<_, _>, _>(|(id, _)| reachable.contains(id));
+
+        self.by_id = kept;
+
+        let removed: Vec<_> = removed
+            .into_values()
+            .sorted_by(|a, b| a.id.cmp(&b.id))
+            .inspect(|instance| {
+                self.by_name.remove(&instance.name);
+                self.unindex_references(instance);
             })
             .collect();
 
-        // Contains the list of type definitions that have not been registered yet.
-        let mut postponed_type_definitions = Vec::with_capacity(type_definitions.len());
-        let mut last_count = type_definitions.len();
-        let mut failed_type_definitions = Vec::new();
-        let mut registered_type_definitions = Vec::new();
-
-        // While we have type definitions to register, we continue.
-        while !type_definitions.is_empty() {
-            // By sorting the definitions by the ascending number of references, we can ensure that the
-            // first type definitions to be registered are the ones with the least number of
-            // references and the lesser likelihood of broken or circular references.
-            type_definitions.sort_by_key(|(refs, _)| refs.len());
-
-            'outer: for (refs, mut td) in type_definitions {
-                // Check for duplicate type definitions.
-                if let Some(existing) = self.by_id.get(&td.id) {
-                    failed_type_definitions.push((
-                        td,
-                        RegistrationError::DuplicateTypeDefinition {
-                            existing_name: existing.name.clone(),
-                        },
-                    ));
+        self.record_change(
+            removed
+                .iter()
+                .map(|instance| (instance.id.clone(), Some(Arc::clone(instance))))
+                .collect(),
+        );
 
-                    continue 'outer;
-                }
+        self.subscribers.notify(|| {
+            removed
+                .iter()
+                .map(|instance| RegistryEvent::Removed(Arc::clone(instance)))
+        });
 
-                if let Some(existing) = self.by_name.get(&td.name) {
-                    failed_type_definitions.push((
-                        td,
-                        RegistrationError::DuplicateTypeDefinitionName {
-                            existing_id: existing.id.clone(),
-                        },
-                    ));
+        removed
+    }
 
-                    continue 'outer;
-                }
+    /// Get the identifiers of the specified roots and all the type definitions they reference,
+    /// directly or transitively. Unknown root identifiers are ignored.
+    pub(crate) fn reachable_from<'a>(&self, roots: impl IntoIterator<Item = &'a Id>) -> BTreeSet<Id>
+    where
+        Id: 'a,
+    {
+        let mut reachable = BTreeSet::new();
+        let mut queue: Vec<_> = roots
+            .into_iter()
+            .filter_map(|id| self.by_id.get(id))
+            .collect();
 
-                let mut refs_by_id = BTreeMap::new();
-
-                for ref_ in &refs {
-                    // Ensure that the reference was already registered.
-                    match self.by_id.get(ref_) {
-                        Some(inst) => {
-                            refs_by_id.insert(ref_.clone(), Arc::clone(inst));
-                        }
-                        None => {
-                            // If the reference was not registered, we need to postpone the
-                            // registration of this type definition.
-                            //
-                            // This is not an error (yet), as we might be able to register it
-                            // later.
-                            postponed_type_definitions.push((refs, td));
-                            continue 'outer;
-                        }
-                    }
-                }
+        while let Some(instance) = queue.pop() {
+            if reachable.insert(instance.id.clone()) {
+                queue.extend(instance.attributes.references());
+            }
+        }
 
-                // Instantiate the type attributes: this can fail if the type attributes are
-                // incompatible (for instance if the key type of a dictionary is not a key-type).
+        reachable
+    }
 
-                let attributes = match td.attributes.instantiate(refs_by_id) {
-                    Ok(attributes) => attributes,
-                    Err((attributes, err)) => {
-                        td.attributes = attributes;
+    /// Get the type instance of a registered type definition, by identifier.
+    pub fn get(&self, id: &Id) -> Option<&Arc<TypeDefinitionInstance<Id, FieldName>>> {
+        self.by_id.get(id)
+    }
 
-                        failed_type_definitions
-                            .push((td, RegistrationError::InstantiationError(err)));
+    /// Get the type instance of a registered type definition, by name.
+    pub fn get_by_name<Q>(&self, name: &Q) -> Option<&Arc<TypeDefinitionInstance<Id, FieldName>>>
+    where
+        FieldName: std::borrow::Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.by_name.get(name)
+    }
 
-                        continue 'outer;
-                    }
-                };
+    /// Get a type definition and all the type definitions it references, directly or
+    /// transitively.
+    ///
+    /// The type definitions are returned in topological order, each one after the type
+    /// definitions it references, so that they can be registered as-is in another registry.
+    ///
+    /// Returns `None` if no type definition with the specified identifier is registered.
+    pub fn closure_of(&self, id: &Id) -> Option<Vec<TypeDefinition<Id, FieldName>>> {
+        let instance = self.by_id.get(id)?;
 
-                // At this point all the references were looked up and there are no duplicates: we
-                // can register the type definition.
-                let type_definition_instance = TypeDefinitionInstance {
-                    id: td.id,
-                    name: td.name,
-                    attributes,
-                };
+        let mut visited = BTreeSet::new();
+        let mut type_definitions = Vec::new();
 
-                // Register the type definition.
-                registered_type_definitions
-                    .push(self.insert_type_definition_instance(type_definition_instance));
-            }
+        collect_closure(instance, &mut visited, &mut type_definitions);
 
-            type_definitions = std::mem::take(&mut postponed_type_definitions);
+        Some(type_definitions)
+    }
 
-            // If the number of type definitions to register did not change, we have a broken or circular
-            // reference.
-            if type_definitions.len() == last_count {
-                // Compute a list of all remaining identifiers to register.
-                let remaining_ids: BTreeSet<_> = type_definitions
-                    .iter()
-                    .map(|(_, td)| td.id.clone())
-                    .collect();
+    /// Get all the registered type definitions.
+    ///
+    /// The type definitions are returned in topological order, each one after the type
+    /// definitions it references: this is the inverse of [`register`](Self::register), and allows
+    /// persisting a registry or comparing it to its source files.
+    pub fn to_definitions(&self) -> Vec<TypeDefinition<Id, FieldName>> {
+        let mut visited = BTreeSet::new();
+        let mut type_definitions = Vec::with_capacity(self.by_id.len());
+
+        for instance in self.by_id.values().sorted_by(|a, b| a.id.cmp(&b.id)) {
+            collect_closure(instance, &mut visited, &mut type_definitions);
+        }
 
-                // Check for broken references.
-                'outer: for (refs, td) in type_definitions {
-                    for ref_ in &refs {
-                        if !(remaining_ids.contains(ref_) || self.by_id.contains_key(ref_)) {
-                            failed_type_definitions.push((
-                                td,
-                                RegistrationError::BrokenReference {
-                                    referenced_id: ref_.clone(),
-                                },
-                            ));
-
-                            continue 'outer;
-                        }
-                    }
+        type_definitions
+    }
 
-                    postponed_type_definitions.push((refs, td));
-                }
+    /// Forget the history of the changes of the registry.
+    ///
+    /// The registry keeps the previous type instances of the type definitions affected by its
+    /// last changes, so that it can compute [deltas](Self::delta_since) from the states before
+    /// those. Clearing the history frees them, but deltas can then only be computed from the
+    /// current state.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
 
-                type_definitions = std::mem::take(&mut postponed_type_definitions);
+    /// Get the maximum number of changes kept in the history of the registry.
+    pub fn history_capacity(&self) -> usize {
+        self.history_capacity
+    }
 
-                // The remaining type definitions are the ones that lead to circular references.
-                loop {
-                    let deps = type_definitions
-                        .iter()
-                        .map(|(refs, td)| (td.id.clone(), refs.iter().cloned().collect()))
-                        .collect::<BTreeMap<_, _>>();
+    /// Set the maximum number of changes kept in the history of the registry.
+    ///
+    /// Once the history is full, the oldest changes are forgotten: deltas can then not be
+    /// computed from the states before them. A capacity of zero disables the history. The
+    /// default capacity is 64 changes.
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.history_capacity = capacity;
+
+        if self.history.len() > capacity {
+            self.history.drain(..self.history.len() - capacity);
+        }
+    }
 
-                    let cycle = detect_minimal_cycle(&deps);
+    /// Record a change of the registry, unless it is empty, forgetting the oldest change if the
+    /// history is full.
+    pub(crate) fn record_change(&mut self, change: Change<Id, FieldName>) {
+        if change.is_empty() || self.history_capacity == 0 {
+            return;
+        }
 
-                    if cycle.is_empty() {
-                        // No cycle found: we can break.
-                        break;
-                    }
+        if self.history.len() == self.history_capacity {
+            self.history.pop_front();
+        }
 
-                    let mut cyclic_type_definitions = Vec::with_capacity(cycle.len() - 1);
+        self.history.push_back(HistoryEntry {
+            change,
+            fingerprint: OnceLock::new(),
+        });
+    }
 
-                    for (refs_, td) in std::mem::take(&mut type_definitions) {
-                        if cycle.contains(&td.id) {
-                            cyclic_type_definitions.push(td);
-                        } else {
-                            postponed_type_definitions.push((refs_, td));
-                        }
-                    }
+    /// Get the identifiers of the type definitions that reference a type definition, directly or
+    /// transitively.
+    fn transitive_dependents(&self, id: &Id) -> BTreeSet<Id> {
+        let mut dependents = BTreeSet::new();
+        let mut queue = vec![id];
 
-                    let cycle = cycle
-                        .into_iter()
-                        .map(|id| {
-                            // It's impossible for the cycle to contain an id that is not in the new
-                            // type definitions, as the already registered type definitions are
-                            // guaranteed to not contain any external references by this very function.
-
-                            let td = cyclic_type_definitions
-                                .iter()
-                                .find(|td| td.id == id)
-                                .expect("we should have a type definition for this id");
-                            (td.id.clone(), td.name.clone())
-                        })
-                        .collect::<Vec<_>>();
-
-                    for td in cyclic_type_definitions {
-                        failed_type_definitions.push((
-                            td,
-                            RegistrationError::CircularReference {
-                                cycle: cycle.clone(),
-                            },
-                        ));
-                    }
+        while let Some(id) = queue.pop() {
+            for dependent in self.dependents.get(id).into_iter().flatten() {
+                if dependents.insert(dependent.clone()) {
+                    queue.push(dependent);
                 }
+            }
+        }
 
-                // All the remaining type definitions are the ones that lead to circular
-                // references but weren't part of the cycle.
-                for (_, td) in postponed_type_definitions {
-                    failed_type_definitions.push((td, RegistrationError::BlockedReference));
-                }
+        dependents
+    }
 
-                break;
-            } else {
-                last_count = type_definitions.len();
-            }
+    /// Record a type instance as a dependent of the type definitions it references.
+    fn index_references(&mut self, instance: &TypeDefinitionInstance<Id, FieldName>) {
+        for ref_ in references_of(instance) {
+            self.dependents
+                .entry(ref_)
+                .or_default()
+                .insert(instance.id.clone());
         }
+    }
 
-        (registered_type_definitions, failed_type_definitions)
+    /// Forget a type instance as a dependent of the type definitions it references.
+    pub(crate) fn unindex_references(&mut self, instance: &TypeDefinitionInstance<Id, FieldName>) {
+        for ref_ in references_of(instance) {
+            if let Some(dependents) = self.dependents.get_mut(&ref_) {
+                dependents.remove(&instance.id);
+
+                if dependents.is_empty() {
+                    self.dependents.remove(&ref_);
+                }
+            }
+        }
     }
 
     fn insert_type_definition_instance(
@@ -281,6 +1430,8 @@ impl<Id: Ord + Clone + Display, FieldName: Ord + Clone + Display>
     ) -> Arc<TypeDefinitionInstance<Id, FieldName>> {
         let type_definition_instance = Arc::new(type_definition_instance);
 
+        self.index_references(&type_definition_instance);
+
         self.by_id.insert(
             type_definition_instance.id.clone(),
             Arc::clone(&type_definition_instance),
@@ -294,6 +1445,127 @@ impl<Id: Ord + Clone + Display, FieldName: Ord + Clone + Display>
     }
 }
 
+#[cfg(feature = "rayon")]
+impl<Id, FieldName> TypeDefinitionRegistry<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display + Send + Sync,
+    FieldName: Ord + Hash + Clone + Display + Send + Sync,
+{
+    /// Register type definitions, instantiating independent type definitions in parallel.
+    ///
+    /// At the start of each round, the type definitions whose references are all registered do
+    /// not depend on each other: they are instantiated in parallel, then registered in order. The
+    /// outcome is the same as with [`register`](Self::register).
+    pub fn register_parallel(
+        &mut self,
+        type_definitions: impl IntoIterator<Item = TypeDefinition<Id, FieldName>>,
+    ) -> RegistrationOutcome<Id, FieldName> {
+        self.register_parallel_with_diagnostics(type_definitions, &mut ())
+    }
+
+    /// Register type definitions, instantiating independent type definitions in parallel and
+    /// reporting the warnings emitted while registering them to a diagnostics sink.
+    ///
+    /// See [`register_parallel`](Self::register_parallel) for details.
+    pub fn register_parallel_with_diagnostics(
+        &mut self,
+        type_definitions: impl IntoIterator<Item = TypeDefinition<Id, FieldName>>,
+        sink: &mut impl DiagnosticsSink<Id, FieldName>,
+    ) -> RegistrationOutcome<Id, FieldName> {
+        use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+        self.register_in_rounds(type_definitions, sink, |by_id, round| {
+            round
+                .par_iter_mut()
+                .map(|(refs, td)| {
+                    let refs_by_id = refs
+                        .iter()
+                        .map(|ref_| Some((ref_.clone(), Arc::clone(by_id.get(ref_)?))))
+                        .collect::<Option<_>>()?;
+
+                    Some(Instantiated::new(td, refs_by_id))
+                })
+                .collect()
+        })
+    }
+}
+
+/// Type attributes instantiated for registration, with the warnings they emit.
+struct Instantiated<Id, FieldName: Ord> {
+    /// The instantiated attributes, or why they could not be instantiated.
+    attributes: Result<TypeAttributesInstance<Id, FieldName>, InstantiationError<Id, FieldName>>,
+
+    /// The warnings emitted by the attributes.
+    warnings: Vec<RegistrationWarningKind>,
+
+    /// The time spent instantiating the attributes.
+    elapsed: Duration,
+}
+
+impl<Id: Ord + Clone + Display, FieldName: Ord + Clone + Display> Instantiated<Id, FieldName> {
+    /// Instantiate the attributes of a type definition.
+    ///
+    /// The attributes are taken from the type definition, and given back if they cannot be
+    /// instantiated.
+    fn new(
+        td: &mut TypeDefinition<Id, FieldName>,
+        refs_by_id: BTreeMap<Id, Arc<TypeDefinitionInstance<Id, FieldName>>>,
+    ) -> Self {
+        let start = Instant::now();
+        let attributes = std::mem::replace(
+            &mut td.attributes,
+            TypeAttributes::Boolean(Default::default()),
+        );
+
+        let attributes = attributes
+            .instantiate(refs_by_id)
+            .map_err(|(attributes, err)| {
+                td.attributes = attributes;
+                err
+            });
+        let warnings = match &attributes {
+            Ok(attributes) => inspect_attributes(attributes),
+            Err(_) => Vec::new(),
+        };
+
+        Self {
+            attributes,
+            warnings,
+            elapsed: start.elapsed(),
+        }
+    }
+}
+
+/// Collect a type definition after all the type definitions it references, skipping the ones
+/// already visited.
+/// Get the identifiers of the type definitions a type instance references directly.
+fn references_of<Id: Ord + Clone, FieldName: Ord>(
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+) -> BTreeSet<Id> {
+    instance
+        .attributes
+        .references()
+        .into_iter()
+        .map(|ref_| ref_.id.clone())
+        .collect()
+}
+
+pub(crate) fn collect_closure<Id: Ord + Clone, FieldName: Ord + Display + Clone>(
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+    visited: &mut BTreeSet<Id>,
+    type_definitions: &mut Vec<TypeDefinition<Id, FieldName>>,
+) {
+    if !visited.insert(instance.id.clone()) {
+        return;
+    }
+
+    for ref_ in instance.attributes.references() {
+        collect_closure(ref_, visited, type_definitions);
+    }
+
+    type_definitions.push(instance.to_definition());
+}
+
 fn detect_minimal_cycle<Id: Ord + Clone>(dependencies: &BTreeMap<Id, BTreeSet<Id>>) -> Vec<Id> {
     let mut in_current_path: BTreeSet<Id> = BTreeSet::new();
     let mut parent: BTreeMap<Id, Id> = BTreeMap::new();
@@ -334,28 +1606,28 @@ fn detect_minimal_cycle<Id: Ord + Clone>(dependencies: &BTreeMap<Id, BTreeSet<Id
     }
 
     for node in dependencies.keys() {
-        if !visited.contains(node) {
-            if let Some((cycle_start, cycle_end)) = dfs(
+        if !visited.contains(node)
+            && let Some((cycle_start, cycle_end)) = dfs(
                 node.clone(),
                 dependencies,
                 &mut in_current_path,
                 &mut parent,
                 &mut visited,
-            ) {
-                let mut cycle = Vec::new();
-                cycle.push(cycle_start.clone());
-
-                let mut current = cycle_end.clone();
-                while current != cycle_start {
-                    cycle.push(current.clone());
-                    current = parent.get(&current).expect("parent not found").clone();
-                }
+            )
+        {
+            let mut cycle = Vec::new();
+            cycle.push(cycle_start.clone());
+
+            let mut current = cycle_end.clone();
+            while current != cycle_start {
+                cycle.push(current.clone());
+                current = parent.get(&current).expect("parent not found").clone();
+            }
 
-                cycle.push(cycle_start); // Close the cycle.
-                cycle.reverse(); // Reverse the cycle to get the correct order.
+            cycle.push(cycle_start); // Close the cycle.
+            cycle.reverse(); // Reverse the cycle to get the correct order.
 
-                return cycle;
-            }
+            return cycle;
         }
     }
 
@@ -364,57 +1636,44 @@ fn detect_minimal_cycle<Id: Ord + Clone>(dependencies: &BTreeMap<Id, BTreeSet<Id
 
 #[cfg(test)]
 mod tests {
-    use crate::type_attributes::{ArrayTypeAttributes, EnumTypeAttributes};
+    use crate::{
+        Diagnostics, RegistrationWarningKind, RegistryEvent,
+        test_support::{Id, TypeAttributes, TypeDefinition, TypeDefinitionRegistry, registry, td},
+        type_attributes::{ArrayTypeAttributes, EnumTypeAttributes, NumberTypeAttributes},
+    };
 
-    use super::{RegistrationError, detect_minimal_cycle};
+    use itertools::Itertools;
 
-    type Id = u32;
-    type FieldName = &'static str;
-    type TypeDefinitionRegistry = super::TypeDefinitionRegistry<Id, FieldName>;
-    type TypeDefinition = crate::TypeDefinition<Id, FieldName>;
-    type TypeAttributes = crate::TypeAttributes<Id, FieldName>;
+    use super::{RegistrationError, detect_minimal_cycle};
 
     #[test]
     fn test_type_definitions_registration() {
         let mut registry = TypeDefinitionRegistry::default();
 
-        let my_int = TypeDefinition {
-            id: 1,
-            name: "MyInt",
-            description: None,
-            attributes: TypeAttributes::Int32(Default::default()),
-        };
-        let my_string = TypeDefinition {
-            id: 2,
-            name: "MyString",
-            description: None,
-            attributes: TypeAttributes::String(Default::default()),
-        };
-        let my_int_array = TypeDefinition {
-            id: 3,
-            name: "MyIntArray",
-            description: None,
-            attributes: TypeAttributes::Array(ArrayTypeAttributes::new(my_int.id)),
-        };
-        let my_string_array = TypeDefinition {
-            id: 4,
-            name: "MyStringArray",
-            description: None,
-            attributes: TypeAttributes::Array(ArrayTypeAttributes::new(my_string.id)),
-        };
-        let my_int_dictionary = TypeDefinition {
-            id: 5,
-            name: "MyIntDictionary",
-            description: None,
-            attributes: TypeAttributes::Dictionary(
-                crate::type_attributes::DictionaryTypeAttributes::new(my_string.id, my_int.id),
-            ),
-        };
-        let my_enum = TypeDefinition {
-            id: 6,
-            name: "MyEnum",
-            description: None,
-            attributes: TypeAttributes::Enum(
+        let my_int = td(1, "MyInt", TypeAttributes::Int32(Default::default()));
+        let my_string = td(2, "MyString", TypeAttributes::String(Default::default()));
+        let my_int_array = td(
+            3,
+            "MyIntArray",
+            TypeAttributes::Array(ArrayTypeAttributes::new(my_int.id)),
+        );
+        let my_string_array = td(
+            4,
+            "MyStringArray",
+            TypeAttributes::Array(ArrayTypeAttributes::new(my_string.id)),
+        );
+        let my_int_dictionary = td(
+            5,
+            "MyIntDictionary",
+            TypeAttributes::Dictionary(crate::type_attributes::DictionaryTypeAttributes::new(
+                my_string.id,
+                my_int.id,
+            )),
+        );
+        let my_enum = td(
+            6,
+            "MyEnum",
+            TypeAttributes::Enum(
                 EnumTypeAttributes::builder()
                     .with_value("alpha")
                     .with_value("beta")
@@ -422,63 +1681,121 @@ mod tests {
                     .build()
                     .unwrap(),
             ),
-        };
+        );
 
         // This one will be registered later.
-        let my_enum_array = TypeDefinition {
-            id: 7,
-            name: "MyEnumArray",
-            description: None,
-            attributes: TypeAttributes::Array(ArrayTypeAttributes::new(my_enum.id)),
-        };
+        let my_enum_array = td(
+            7,
+            "MyEnumArray",
+            TypeAttributes::Array(ArrayTypeAttributes::new(my_enum.id)),
+        );
 
         // Register the type definitions.
-        let (registered, errors) = registry.register([
-            my_int,
-            my_string,
-            my_int_array,
-            my_string_array,
-            my_int_dictionary,
-            my_enum,
-        ]);
+        let (registered, errors) = registry
+            .register([
+                my_int,
+                my_string,
+                my_int_array,
+                my_string_array,
+                my_int_dictionary,
+                my_enum,
+            ])
+            .into_parts();
 
         assert_eq!(
             registered.iter().map(|td| td.id).collect::<Vec<_>>(),
-            vec![1, 2, 6, 3, 4, 5],
+            vec![1, 2, 3, 4, 5, 6],
         );
         assert!(errors.is_empty());
 
         // Register the enum array type definition.
-        let (registered, errors) = registry.register([my_enum_array]);
+        let (registered, errors) = registry.register([my_enum_array]).into_parts();
 
         assert_eq!(
             registered.iter().map(|td| td.id).collect::<Vec<_>>(),
             vec![7]
         );
         assert!(errors.is_empty());
+
+        assert_eq!(registry.get(&7).unwrap().name, "MyEnumArray");
+        assert_eq!(registry.get_by_name("MyEnumArray").unwrap().id, 7);
+        assert!(registry.get(&8).is_none());
+        assert!(registry.get_by_name("MyFloat").is_none());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_register_parallel() {
+        let batch = || {
+            vec![
+                td(
+                    4,
+                    "MyIntArrays",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(3)),
+                ),
+                td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+                td(
+                    3,
+                    "MyIntArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+                ),
+                td(2, "MyString", TypeAttributes::String(Default::default())),
+                // Instantiated ahead of its round, then rejected as a duplicate.
+                td(
+                    3,
+                    "MyOtherIntArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+                ),
+                td(
+                    5,
+                    "MyIntDictionary",
+                    TypeAttributes::Dictionary(
+                        crate::type_attributes::DictionaryTypeAttributes::new(1, 2),
+                    ),
+                ),
+                td(
+                    6,
+                    "MyBrokenArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(9)),
+                ),
+            ]
+        };
+
+        let (registered, failed) = TypeDefinitionRegistry::default()
+            .register(batch())
+            .into_parts();
+        let (registered_parallel, failed_parallel) = TypeDefinitionRegistry::default()
+            .register_parallel(batch())
+            .into_parts();
+
+        assert_eq!(
+            registered_parallel.iter().map(|td| td.id).collect_vec(),
+            [1, 2, 3, 4]
+        );
+        assert_eq!(
+            registered.iter().map(|td| td.id).collect_vec(),
+            registered_parallel.iter().map(|td| td.id).collect_vec()
+        );
+        assert_eq!(failed_parallel, failed);
+        assert_eq!(
+            failed_parallel[0].0.attributes,
+            TypeAttributes::Array(ArrayTypeAttributes::new(1))
+        );
     }
 
     #[test]
     fn test_type_definitions_registration_broken_reference() {
         let mut registry = TypeDefinitionRegistry::default();
 
-        let my_int = TypeDefinition {
-            id: 1,
-            name: "MyInt",
-            description: None,
-            attributes: TypeAttributes::Int32(Default::default()),
-        };
-        let my_string_array = TypeDefinition {
-            id: 4,
-            name: "MyStringArray",
-            description: None,
-            attributes: TypeAttributes::Array(ArrayTypeAttributes::new(
-                2, /* THIS DOES NOT EXIST */
-            )),
-        };
+        let my_int = td(1, "MyInt", TypeAttributes::Int32(Default::default()));
+        let my_string_array = td(
+            4,
+            "MyStringArray",
+            TypeAttributes::Array(ArrayTypeAttributes::new(2 /* THIS DOES NOT EXIST */)),
+        );
 
         // Register the type definitions.
-        let (registered, failed) = registry.register([my_int, my_string_array]);
+        let (registered, failed) = registry.register([my_int, my_string_array]).into_parts();
 
         assert_eq!(
             registered.into_iter().map(|td| td.id).collect::<Vec<_>>(),
@@ -501,23 +1818,15 @@ mod tests {
     fn test_type_definitions_registration_duplicate_id() {
         let mut registry = TypeDefinitionRegistry::default();
 
-        let my_int = TypeDefinition {
-            id: 1,
-            name: "MyInt",
-            description: None,
-            attributes: TypeAttributes::Int32(Default::default()),
-        };
-        let my_string_array = TypeDefinition {
-            id: 1,
-            name: "MyStringArray",
-            description: None,
-            attributes: TypeAttributes::Array(ArrayTypeAttributes::new(
-                2, /* THIS DOES NOT EXIST */
-            )),
-        };
+        let my_int = td(1, "MyInt", TypeAttributes::Int32(Default::default()));
+        let my_string_array = td(
+            1,
+            "MyStringArray",
+            TypeAttributes::Array(ArrayTypeAttributes::new(2 /* THIS DOES NOT EXIST */)),
+        );
 
         // Register the type definitions.
-        let (registered, failed) = registry.register([my_int, my_string_array]);
+        let (registered, failed) = registry.register([my_int, my_string_array]).into_parts();
 
         assert_eq!(
             registered.into_iter().map(|td| td.id).collect::<Vec<_>>(),
@@ -542,23 +1851,15 @@ mod tests {
     fn test_type_definitions_registration_duplicate_name() {
         let mut registry = TypeDefinitionRegistry::default();
 
-        let my_int = TypeDefinition {
-            id: 1,
-            name: "MyInt",
-            description: None,
-            attributes: TypeAttributes::Int32(Default::default()),
-        };
-        let my_string_array = TypeDefinition {
-            id: 2,
-            name: "MyInt",
-            description: None,
-            attributes: TypeAttributes::Array(ArrayTypeAttributes::new(
-                2, /* THIS DOES NOT EXIST */
-            )),
-        };
+        let my_int = td(1, "MyInt", TypeAttributes::Int32(Default::default()));
+        let my_string_array = td(
+            2,
+            "MyInt",
+            TypeAttributes::Array(ArrayTypeAttributes::new(2 /* THIS DOES NOT EXIST */)),
+        );
 
         // Register the type definitions.
-        let (registered, failed) = registry.register([my_int, my_string_array]);
+        let (registered, failed) = registry.register([my_int, my_string_array]).into_parts();
 
         assert_eq!(
             registered.into_iter().map(|td| td.id).collect::<Vec<_>>(),
@@ -581,40 +1882,32 @@ mod tests {
     fn test_type_definitions_registration_circular_reference() {
         let mut registry = TypeDefinitionRegistry::default();
 
-        let my_int = TypeDefinition {
-            id: 1,
-            name: "MyInt",
-            description: None,
-            attributes: TypeAttributes::Int32(Default::default()),
-        };
-        let my_array_a = TypeDefinition {
-            id: 2,
-            name: "MyArrayA",
-            description: None,
-            attributes: TypeAttributes::Array(ArrayTypeAttributes::new(3)),
-        };
-        let my_array_b = TypeDefinition {
-            id: 3,
-            name: "MyArrayB",
-            description: None,
-            attributes: TypeAttributes::Array(ArrayTypeAttributes::new(4)),
-        };
-        let my_array_c = TypeDefinition {
-            id: 4,
-            name: "MyArrayC",
-            description: None,
-            attributes: TypeAttributes::Array(ArrayTypeAttributes::new(5)),
-        };
-        let my_array_d = TypeDefinition {
-            id: 5,
-            name: "MyArrayD",
-            description: None,
-            attributes: TypeAttributes::Array(ArrayTypeAttributes::new(3)),
-        };
+        let my_int = td(1, "MyInt", TypeAttributes::Int32(Default::default()));
+        let my_array_a = td(
+            2,
+            "MyArrayA",
+            TypeAttributes::Array(ArrayTypeAttributes::new(3)),
+        );
+        let my_array_b = td(
+            3,
+            "MyArrayB",
+            TypeAttributes::Array(ArrayTypeAttributes::new(4)),
+        );
+        let my_array_c = td(
+            4,
+            "MyArrayC",
+            TypeAttributes::Array(ArrayTypeAttributes::new(5)),
+        );
+        let my_array_d = td(
+            5,
+            "MyArrayD",
+            TypeAttributes::Array(ArrayTypeAttributes::new(3)),
+        );
 
         // Register the type definitions.
-        let (registered, failed) =
-            registry.register([my_int, my_array_a, my_array_b, my_array_c, my_array_d]);
+        let (registered, failed) = registry
+            .register([my_int, my_array_a, my_array_b, my_array_c, my_array_d])
+            .into_parts();
 
         assert_eq!(
             registered.into_iter().map(|td| td.id).collect::<Vec<_>>(),
@@ -626,6 +1919,7 @@ mod tests {
                 .map(|(td, err)| (td.id, td.name, err))
                 .collect::<Vec<_>>(),
             vec![
+                (2, "MyArrayA", RegistrationError::BlockedReference),
                 (
                     3,
                     "MyArrayB",
@@ -662,11 +1956,70 @@ mod tests {
                         ]
                     }
                 ),
-                (2, "MyArrayA", RegistrationError::BlockedReference),
             ]
         );
     }
 
+    #[test]
+    fn test_type_definitions_registration_order() {
+        let type_definitions = vec![
+            td(
+                1,
+                "MyIntArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(4)),
+            ),
+            td(
+                2,
+                "MyBrokenArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(7)),
+            ),
+            td(3, "MyString", TypeAttributes::String(Default::default())),
+            td(4, "MyInt", TypeAttributes::Int32(Default::default())),
+            td(5, "MyInt", TypeAttributes::Int32(Default::default())),
+        ];
+
+        let orders = [
+            type_definitions.clone(),
+            type_definitions.iter().rev().cloned().collect(),
+        ];
+
+        for type_definitions in orders {
+            let (registered, failed) = TypeDefinitionRegistry::default()
+                .register(type_definitions)
+                .into_parts();
+
+            assert_eq!(
+                registered.iter().map(|td| td.id).collect::<Vec<_>>(),
+                vec![3, 4, 1]
+            );
+            assert_eq!(
+                failed.iter().map(|(td, _)| td.id).collect::<Vec<_>>(),
+                vec![2, 5]
+            );
+        }
+    }
+
+    #[test]
+    fn test_type_definitions_registration_long_chain() {
+        // Each type definition references the next one, so that they are registered in reverse.
+        let type_definitions = (0..5000u32).map(|id| crate::TypeDefinition {
+            id,
+            name: format!("Type{id}"),
+            description: None,
+            attributes: match id {
+                4999 => crate::TypeAttributes::Int32(Default::default()),
+                id => crate::TypeAttributes::Array(ArrayTypeAttributes::new(id + 1)),
+            },
+        });
+
+        let registered = super::TypeDefinitionRegistry::<u32, String>::default()
+            .register(type_definitions)
+            .into_result()
+            .unwrap();
+
+        assert!(registered.iter().map(|td| td.id).eq((0..5000).rev()));
+    }
+
     #[test]
     fn test_detect_minimal_cycle() {
         let deps = [(1, [2]), (2, [3]), (3, [1])]
@@ -714,4 +2067,636 @@ mod tests {
         let cycle = detect_minimal_cycle(&deps);
         assert_eq!(cycle, Vec::<i32>::default());
     }
+
+    #[test]
+    fn test_type_definitions_registration_diagnostics() {
+        let mut registry = TypeDefinitionRegistry::default();
+        let mut diagnostics = Diagnostics::default();
+
+        let outcome = registry.register_with_diagnostics(
+            [
+                td(
+                    1,
+                    "Constant",
+                    TypeAttributes::Int32(
+                        NumberTypeAttributes::builder()
+                            .min(3)
+                            .max(3)
+                            .build()
+                            .unwrap(),
+                    ),
+                ),
+                td(
+                    2,
+                    "Nothing",
+                    TypeAttributes::Enum(EnumTypeAttributes::builder().build().unwrap()),
+                ),
+                td(
+                    3,
+                    "Ratio",
+                    TypeAttributes::Float64(
+                        NumberTypeAttributes::builder()
+                            .default_value(f64::INFINITY)
+                            .build()
+                            .unwrap(),
+                    ),
+                ),
+                td(4, "Fine", TypeAttributes::Int32(Default::default())),
+            ],
+            &mut diagnostics,
+        );
+
+        assert_eq!(
+            outcome.warnings(),
+            diagnostics.registration_warnings(),
+            "the outcome carries the warnings reported to the sink"
+        );
+        assert_eq!(outcome.timings().count(), 4);
+        assert!(outcome.timing(&4).is_some());
+        assert!(outcome.timing(&5).is_none());
+        assert!(outcome.is_success());
+        assert_eq!(outcome.into_result().unwrap().len(), 4);
+
+        let mut warnings = diagnostics
+            .registration_warnings()
+            .iter()
+            .map(|warning| (*warning.id(), warning.kind().clone()))
+            .collect::<Vec<_>>();
+        warnings.sort_by_key(|(id, _)| *id);
+
+        assert_eq!(
+            warnings,
+            vec![
+                (1, RegistrationWarningKind::SingleValueRange),
+                (2, RegistrationWarningKind::EmptyEnum),
+                (3, RegistrationWarningKind::NonFiniteDefault),
+            ]
+        );
+
+        let warning = diagnostics
+            .registration_warnings()
+            .iter()
+            .find(|warning| *warning.id() == 2)
+            .unwrap();
+        assert_eq!(
+            warning.to_string(),
+            "type definition `Nothing` (2): the enum has no values"
+        );
+    }
+
+    #[test]
+    fn test_type_definitions_replace() {
+        let mut registry = TypeDefinitionRegistry::default();
+
+        let (_, errors) = registry
+            .register([
+                td(1, "MyKey", TypeAttributes::String(Default::default())),
+                td(2, "MyInt", TypeAttributes::Int32(Default::default())),
+                td(
+                    3,
+                    "MyIntArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+                ),
+                td(
+                    4,
+                    "MyIntArrayArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(3)),
+                ),
+                td(
+                    5,
+                    "MyIntDictionary",
+                    TypeAttributes::Dictionary(
+                        crate::type_attributes::DictionaryTypeAttributes::new(1, 2),
+                    ),
+                ),
+            ])
+            .into_parts();
+        assert!(errors.is_empty());
+
+        let previous_array = std::sync::Arc::clone(&registry.by_id[&4]);
+
+        // Replacing a type re-instantiates its dependents, in dependency order.
+        let replaced = registry
+            .replace(td(2, "MyLong", TypeAttributes::Int64(Default::default())))
+            .unwrap();
+        assert_eq!(
+            replaced.iter().map(|td| td.id).collect::<Vec<_>>(),
+            vec![2, 3, 5, 4]
+        );
+        assert_eq!(
+            registry.by_id[&4].to_string(),
+            "MyIntArrayArray(4): array(MyIntArray(3): array(MyLong(2): int64(..)))"
+        );
+        assert!(!std::sync::Arc::ptr_eq(
+            &registry.by_id[&4],
+            &previous_array
+        ));
+        assert!(!registry.by_name.contains_key("MyInt"));
+        assert_eq!(registry.by_name["MyLong"].id, 2);
+
+        // Replacements that break a dependent are rejected as a whole.
+        let (_, err) = registry
+            .replace(td(1, "MyKey", TypeAttributes::Int32(Default::default())))
+            .unwrap_err();
+        assert!(matches!(err, RegistrationError::InstantiationError(_)));
+        assert_eq!(registry.by_id[&1].to_string(), "MyKey(1): string()");
+
+        let (_, err) = registry
+            .replace(td(
+                2,
+                "MyLong",
+                TypeAttributes::Array(ArrayTypeAttributes::new(4)),
+            ))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            RegistrationError::CircularReference {
+                cycle: vec![
+                    (2, "MyLong"),
+                    (4, "MyIntArrayArray"),
+                    (3, "MyIntArray"),
+                    (2, "MyLong")
+                ]
+            }
+        );
+
+        let (_, err) = registry
+            .replace(td(8, "MyOther", TypeAttributes::Int32(Default::default())))
+            .unwrap_err();
+        assert_eq!(err, RegistrationError::UnknownTypeDefinition);
+
+        let (_, err) = registry
+            .replace(td(2, "MyKey", TypeAttributes::Int32(Default::default())))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            RegistrationError::DuplicateTypeDefinitionName { existing_id: 1 }
+        );
+
+        let (_, err) = registry
+            .replace_with_check(
+                td(2, "MyLong", TypeAttributes::Int32(Default::default())),
+                |previous, new| {
+                    if previous.attributes.kind() == new.attributes.kind() {
+                        Ok(())
+                    } else {
+                        Err(format!(
+                            "cannot change {} into {}",
+                            previous.attributes.kind(),
+                            new.attributes.kind()
+                        ))
+                    }
+                },
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "type definition is incompatible with the registered one: cannot change int64 into int32"
+        );
+    }
+
+    #[test]
+    fn test_type_definitions_dependents() {
+        let mut registry = registry([
+            td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+            td(2, "MyLong", TypeAttributes::Int64(Default::default())),
+            td(
+                3,
+                "MyArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+        ]);
+
+        let dependents = |registry: &TypeDefinitionRegistry| {
+            registry
+                .dependents
+                .iter()
+                .map(|(id, dependents)| (*id, dependents.iter().copied().collect::<Vec<_>>()))
+                .sorted()
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(dependents(&registry), [(1, vec![3])]);
+
+        // The index follows the references of the replaced type definitions.
+        registry
+            .replace(td(
+                3,
+                "MyArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ))
+            .unwrap();
+        assert_eq!(dependents(&registry), [(2, vec![3])]);
+
+        registry.prune(&[1]);
+        assert!(registry.dependents.is_empty());
+    }
+
+    #[test]
+    fn test_type_definitions_closure_of() {
+        let mut registry = TypeDefinitionRegistry::default();
+
+        let (_, errors) = registry
+            .register([
+                TypeDefinition {
+                    id: 1,
+                    name: "MyKey",
+                    description: Some("A key.".to_owned()),
+                    attributes: TypeAttributes::String(Default::default()),
+                },
+                td(2, "MyInt", TypeAttributes::Int32(Default::default())),
+                td(
+                    3,
+                    "MyIntArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+                ),
+                td(
+                    4,
+                    "MyIntArrayDictionary",
+                    TypeAttributes::Dictionary(
+                        crate::type_attributes::DictionaryTypeAttributes::new(1, 3),
+                    ),
+                ),
+                td(
+                    5,
+                    "MyUnrelated",
+                    TypeAttributes::Boolean(Default::default()),
+                ),
+            ])
+            .into_parts();
+        assert!(errors.is_empty());
+
+        let closure = registry.closure_of(&4).unwrap();
+        assert_eq!(
+            closure.iter().map(|td| td.id).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(closure[0].description.as_deref(), Some("A key."));
+        assert_eq!(
+            closure[3].attributes,
+            TypeAttributes::Dictionary(crate::type_attributes::DictionaryTypeAttributes::new(1, 3))
+        );
+
+        assert_eq!(registry.closure_of(&2).unwrap().len(), 1);
+        assert!(registry.closure_of(&6).is_none());
+
+        // The closure can be registered on its own.
+        let (registered, errors) = TypeDefinitionRegistry::default()
+            .register(closure)
+            .into_parts();
+        assert_eq!(registered.len(), 4);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_type_definitions_to_definitions() {
+        let mut registry = TypeDefinitionRegistry::default();
+
+        let type_definitions = vec![
+            TypeDefinition {
+                id: 1,
+                name: "MyIntArray",
+                description: Some("Some ints.".to_owned()),
+                attributes: TypeAttributes::Array(ArrayTypeAttributes::new(3)),
+            },
+            td(
+                2,
+                "MyIntArrayArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(3, "MyInt", TypeAttributes::Int32(Default::default())),
+        ];
+
+        let (_, errors) = registry.register(type_definitions.clone()).into_parts();
+        assert!(errors.is_empty());
+
+        let exported = registry.to_definitions();
+        assert_eq!(
+            exported.iter().map(|td| td.id).collect::<Vec<_>>(),
+            vec![3, 1, 2]
+        );
+
+        for td in &type_definitions {
+            let exported = exported.iter().find(|e| e.id == td.id).unwrap();
+
+            assert_eq!(exported.name, td.name);
+            assert_eq!(exported.description, td.description);
+            assert_eq!(exported.attributes, td.attributes);
+        }
+
+        assert!(
+            TypeDefinitionRegistry::default()
+                .to_definitions()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_type_definitions_registry_serialization() {
+        let mut registry = TypeDefinitionRegistry::default();
+
+        let (_, errors) = registry
+            .register([
+                td(
+                    1,
+                    "MyIntArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+                ),
+                TypeDefinition {
+                    id: 2,
+                    name: "MyInt",
+                    description: Some("An int.".to_owned()),
+                    attributes: TypeAttributes::Int32(Default::default()),
+                },
+            ])
+            .into_parts();
+        assert!(errors.is_empty());
+
+        let json = serde_json::to_value(&registry).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "version": 1,
+                "type_definitions": [
+                    {
+                        "id": 2,
+                        "name": "MyInt",
+                        "description": "An int.",
+                        "type": "int32",
+                        "attributes": {},
+                    },
+                    {
+                        "id": 1,
+                        "name": "MyIntArray",
+                        "type": "array",
+                        "attributes": { "items_type_id": 2 },
+                    },
+                ],
+            })
+        );
+
+        let text = json.to_string();
+        let loaded: super::TypeDefinitionRegistry<Id, String> =
+            serde_json::from_str(&text).unwrap();
+        assert_eq!(loaded.by_id.len(), 2);
+        assert_eq!(loaded.by_id[&1].to_string(), registry.by_id[&1].to_string());
+
+        let err = serde_json::from_str::<super::TypeDefinitionRegistry<Id, String>>(
+            r#"{ "version": 2, "type_definitions": [] }"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unsupported registry document version 2, expected 1"
+        );
+
+        let err = serde_json::from_str::<super::TypeDefinitionRegistry<Id, String>>(
+            r#"{
+                "version": 1,
+                "type_definitions": [
+                    { "id": 1, "name": "MyIntArray", "type": "array", "attributes": { "items_type_id": 2 } }
+                ]
+            }"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "type definition `MyIntArray` (1): type definition has a broken reference to type definition `2`"
+        );
+    }
+
+    #[test]
+    fn test_type_definitions_rename() {
+        let mut registry = TypeDefinitionRegistry::default();
+
+        let (_, errors) = registry
+            .register([
+                td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+                td(
+                    2,
+                    "MyIntArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+                ),
+            ])
+            .into_parts();
+        assert!(errors.is_empty());
+
+        let renaming = registry.rename(&1, "MyNumber").unwrap();
+        assert_eq!(renaming.id(), &1);
+        assert_eq!(renaming.old_name(), &"MyInt");
+        assert_eq!(renaming.new_name(), &"MyNumber");
+
+        assert!(!registry.by_name.contains_key("MyInt"));
+        assert_eq!(
+            registry.by_name["MyIntArray"].to_string(),
+            "MyIntArray(2): array(MyNumber(1): int32(..))"
+        );
+        assert_eq!(
+            registry
+                .to_definitions()
+                .iter()
+                .map(|td| td.name)
+                .collect::<Vec<_>>(),
+            vec!["MyNumber", "MyIntArray"]
+        );
+
+        assert_eq!(
+            registry.rename(&1, "MyIntArray").unwrap_err(),
+            RegistrationError::DuplicateTypeDefinitionName { existing_id: 2 }
+        );
+        assert_eq!(
+            registry.rename(&3, "MyOther").unwrap_err(),
+            RegistrationError::UnknownTypeDefinition
+        );
+    }
+
+    #[test]
+    fn test_type_definitions_declare() {
+        let mut registry = TypeDefinitionRegistry::default();
+
+        registry.declare(1, "MyString").unwrap();
+        registry.declare(1, "MyString").unwrap();
+        assert_eq!(registry.declaration_of(&"MyString"), Some(&1));
+
+        assert_eq!(
+            registry.declare(2, "MyString").unwrap_err(),
+            RegistrationError::DuplicateTypeDefinitionName { existing_id: 1 }
+        );
+        assert_eq!(
+            registry.declare(1, "MyOther").unwrap_err(),
+            RegistrationError::DuplicateTypeDefinition {
+                existing_name: "MyString"
+            }
+        );
+
+        let (registered, errors) = registry
+            .register([
+                td(
+                    2,
+                    "MyStringArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+                ),
+                td(
+                    3,
+                    "MyStringArrayArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+                ),
+                td(
+                    4,
+                    "MyBrokenArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(5)),
+                ),
+            ])
+            .into_parts();
+        assert!(registered.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0.id, 4);
+        assert_eq!(
+            errors[0].1,
+            RegistrationError::BrokenReference { referenced_id: 5 }
+        );
+        assert_eq!(
+            registry
+                .pending()
+                .iter()
+                .map(|td| td.id)
+                .collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+
+        let (registered, errors) = registry
+            .register([td(1, "MyOther", TypeAttributes::String(Default::default()))])
+            .into_parts();
+        assert!(registered.is_empty());
+        assert_eq!(
+            errors[0].1,
+            RegistrationError::DeclaredNameMismatch {
+                declared_name: "MyString"
+            }
+        );
+        assert_eq!(registry.pending().len(), 2);
+
+        let (registered, errors) = registry
+            .register([td(
+                1,
+                "MyString",
+                TypeAttributes::String(Default::default()),
+            )])
+            .into_parts();
+        assert!(errors.is_empty());
+        assert_eq!(
+            registered.iter().map(|td| td.id).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!(registry.pending().is_empty());
+        assert_eq!(registry.declarations().count(), 0);
+        assert_eq!(registry.declaration_of(&"MyString"), None);
+        assert_eq!(
+            registry.by_name["MyStringArrayArray"].to_string(),
+            "MyStringArrayArray(3): array(MyStringArray(2): array(MyString(1): string()))"
+        );
+
+        assert_eq!(
+            registry.declare(1, "MyString").unwrap_err(),
+            RegistrationError::DuplicateTypeDefinition {
+                existing_name: "MyString"
+            }
+        );
+    }
+
+    #[test]
+    fn test_type_definitions_prune() {
+        let mut registry = TypeDefinitionRegistry::default();
+
+        let (_, errors) = registry
+            .register([
+                td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+                td(
+                    2,
+                    "MyIntArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+                ),
+                td(
+                    3,
+                    "MyIntArrayArray",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+                ),
+                td(
+                    4,
+                    "MyExperiment",
+                    TypeAttributes::Boolean(Default::default()),
+                ),
+            ])
+            .into_parts();
+        assert!(errors.is_empty());
+
+        let removed = registry.prune(&[2, 5]);
+        assert_eq!(
+            removed.iter().map(|td| td.id).collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+        assert_eq!(
+            registry.by_id.keys().sorted().collect::<Vec<_>>(),
+            vec![&1, &2]
+        );
+        assert_eq!(
+            registry.by_name.keys().sorted().collect::<Vec<_>>(),
+            vec![&"MyInt", &"MyIntArray"]
+        );
+
+        assert!(registry.prune(&[2]).is_empty());
+        assert_eq!(registry.prune([]).len(), 2);
+        assert!(registry.by_name.is_empty());
+    }
+
+    #[test]
+    fn test_type_definitions_subscribe() {
+        let mut registry = TypeDefinitionRegistry::default();
+        let events = registry.subscribe();
+
+        let outcome = registry.register([
+            td(1, "MyInt", TypeAttributes::Int32(Default::default())),
+            td(
+                2,
+                "MyIntArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(
+                3,
+                "MyBrokenArray",
+                TypeAttributes::Array(ArrayTypeAttributes::new(4)),
+            ),
+        ]);
+        assert_eq!(outcome.registered().len(), 2);
+
+        registry.rename(&1, "MyNumber").unwrap();
+        registry.prune([&1]);
+
+        // Clones do not notify the subscribers of the original registry.
+        registry.clone().prune([]);
+
+        let events = events
+            .try_iter()
+            .map(|event| match event {
+                RegistryEvent::Registered(inst) => format!("registered {}", inst.name),
+                RegistryEvent::Failed(td, err) => format!("failed {}: {err}", td.name),
+                RegistryEvent::Replaced { previous, current } => {
+                    format!("replaced {} with {}", previous.name, current.name)
+                }
+                RegistryEvent::Removed(inst) => format!("removed {}", inst.name),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            events,
+            vec![
+                "registered MyInt",
+                "registered MyIntArray",
+                "failed MyBrokenArray: type definition has a broken reference to type definition `4`",
+                "replaced MyInt with MyNumber",
+                "replaced MyIntArray with MyIntArray",
+                "removed MyIntArray",
+            ]
+        );
+    }
 }
@@ -0,0 +1,54 @@
+//! Lightweight handles to type instances.
+
+use std::{fmt::Display, hash::Hash, sync::Arc};
+
+use crate::{TypeDefinitionInstance, TypeDefinitionRegistry};
+
+/// A lightweight handle to a type instance, resolved in the registry it belongs to.
+///
+/// Unlike an `Arc<TypeDefinitionInstance>`, a handle does not keep its type instance alive and
+/// copying it does not touch any reference count. A handle only resolves to the very type
+/// instance it was taken from: once its type definition is replaced or removed, the handle no
+/// longer resolves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TypeRef<Id> {
+    /// The identifier of the type.
+    id: Id,
+
+    /// The serial number of the type instance.
+    serial: u64,
+}
+
+impl<Id> TypeRef<Id> {
+    /// Create a handle.
+    pub(crate) fn new(id: Id, serial: u64) -> Self {
+        Self { id, serial }
+    }
+
+    /// Get the identifier of the type.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Check whether the handle refers to a type instance.
+    pub fn refers_to<FieldName: Ord>(
+        &self,
+        instance: &TypeDefinitionInstance<Id, FieldName>,
+    ) -> bool {
+        self.serial == instance.serial
+    }
+}
+
+impl<Id: Ord + Hash + Clone + Display> TypeRef<Id> {
+    /// Resolve the handle in a registry.
+    ///
+    /// Returns `None` if the type instance is not registered in the registry anymore.
+    pub fn resolve<'r, FieldName: Ord + Hash + Clone + Display>(
+        &self,
+        registry: &'r TypeDefinitionRegistry<Id, FieldName>,
+    ) -> Option<&'r Arc<TypeDefinitionInstance<Id, FieldName>>> {
+        registry
+            .get(&self.id)
+            .filter(|instance| self.refers_to(instance))
+    }
+}
@@ -0,0 +1,156 @@
+//! Self-describing values, carrying the identifier of their type.
+
+use std::{borrow::Borrow, fmt::Display, hash::Hash};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{ParseError, SerializeError, TypeDefinitionRegistry, Value};
+
+/// A value along with the identifier of its type.
+///
+/// Typed values serialize to JSON as an object with the `type_id` of the value and the `value`
+/// itself, so that they can be parsed back with a registry, without knowing their type
+/// beforehand.
+#[derive(Debug, Clone)]
+pub struct TypedValue<Id, FieldName: Ord> {
+    /// The value.
+    value: Value<Id, FieldName>,
+}
+
+/// An error that can occur when parsing a typed value.
+#[derive(Debug, thiserror::Error)]
+pub enum TypedValueError<Id: Display, FieldName: Ord + Display> {
+    /// The JSON is not valid, or does not have the structure of a typed value.
+    #[error("invalid typed value: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    /// The type of the value is not registered.
+    #[error("unknown type `{0}`")]
+    UnknownType(Id),
+
+    /// The value is not valid for its type.
+    #[error(transparent)]
+    InvalidValue(#[from] ParseError<Id, FieldName>),
+}
+
+/// A typed value, as serialized.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct RawTypedValue<Id> {
+    /// The identifier of the type of the value.
+    pub(crate) type_id: Id,
+
+    /// The value.
+    pub(crate) value: serde_json::Value,
+}
+
+impl<Id, FieldName: Ord> TypedValue<Id, FieldName> {
+    /// Create a typed value.
+    pub fn new(value: Value<Id, FieldName>) -> Self {
+        Self { value }
+    }
+
+    /// Get the value.
+    pub fn value(&self) -> &Value<Id, FieldName> {
+        &self.value
+    }
+
+    /// Get the value, consuming the typed value.
+    pub fn into_value(self) -> Value<Id, FieldName> {
+        self.value
+    }
+}
+
+impl<Id, FieldName: Ord> From<Value<Id, FieldName>> for TypedValue<Id, FieldName> {
+    fn from(value: Value<Id, FieldName>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<Id, FieldName> TypedValue<Id, FieldName>
+where
+    Id: Clone + Serialize,
+    FieldName: Ord + Display + Clone + Borrow<str>,
+{
+    /// Serialize the typed value to a JSON value.
+    ///
+    /// Parsing the result with a registry that has the type of the value yields the same typed
+    /// value.
+    pub fn to_json(&self) -> Result<serde_json::Value, SerializeError> {
+        Ok(serde_json::to_value(RawTypedValue {
+            type_id: self.value.instance().id.clone(),
+            value: self.value.to_json()?,
+        })
+        .expect("typed values always serialize to JSON"))
+    }
+}
+
+impl<Id, FieldName> TypedValue<Id, FieldName>
+where
+    Id: Ord + Hash + Clone + Display + DeserializeOwned,
+    FieldName: Ord + Hash + Clone + Display + Borrow<str>,
+{
+    /// Parse a typed value from a JSON value, looking its type up in a registry.
+    pub fn parse(
+        registry: &TypeDefinitionRegistry<Id, FieldName>,
+        json: serde_json::Value,
+    ) -> Result<Self, TypedValueError<Id, FieldName>> {
+        let raw: RawTypedValue<Id> = serde_json::from_value(json)?;
+
+        let Some(instance) = registry.by_id.get(&raw.type_id) else {
+            return Err(TypedValueError::UnknownType(raw.type_id));
+        };
+
+        Ok(Self::new(Value::parse_for(instance.clone(), raw.value)?))
+    }
+
+    /// Parse a typed value from JSON text, looking its type up in a registry.
+    pub fn parse_str(
+        registry: &TypeDefinitionRegistry<Id, FieldName>,
+        s: &str,
+    ) -> Result<Self, TypedValueError<Id, FieldName>> {
+        Self::parse(registry, serde_json::from_str(s)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::TypedValueError;
+    use crate::test_support::owned::{FieldName, Id, Value, registry_from_json};
+
+    type TypedValue = super::TypedValue<Id, FieldName>;
+
+    #[test]
+    fn test_typed_value() {
+        let registry = registry_from_json(json!([
+            { "id": 1, "name": "MyInt", "type": "int32", "attributes": { "max": 10 } },
+            { "id": 2, "name": "MyIntArray", "type": "array", "attributes": { "items_type_id": 1 } },
+        ]));
+
+        let typed_value = TypedValue::from(
+            Value::parse_for(registry.by_id[&2].clone(), json!([1, 2, 3])).unwrap(),
+        );
+
+        let json = typed_value.to_json().unwrap();
+        assert_eq!(json, json!({ "type_id": 2, "value": [1, 2, 3] }));
+
+        let parsed = TypedValue::parse(&registry, json).unwrap();
+        assert_eq!(parsed.value().instance().name(), "MyIntArray");
+        assert_eq!(parsed.into_value().to_json().unwrap(), json!([1, 2, 3]));
+
+        assert!(matches!(
+            TypedValue::parse_str(&registry, r#"{ "type_id": 3, "value": 1 }"#).unwrap_err(),
+            TypedValueError::UnknownType(3)
+        ));
+        assert!(matches!(
+            TypedValue::parse_str(&registry, r#"{ "type_id": 1, "value": 11 }"#).unwrap_err(),
+            TypedValueError::InvalidValue(_)
+        ));
+        assert!(matches!(
+            TypedValue::parse_str(&registry, r#"{ "value": 1 }"#).unwrap_err(),
+            TypedValueError::InvalidJson(_)
+        ));
+    }
+}
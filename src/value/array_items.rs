@@ -0,0 +1,425 @@
+//! Streaming parse of the items of JSON arrays.
+
+use std::{
+    borrow::Borrow,
+    fmt::Display,
+    io::{self, BufReader, Read},
+    sync::Arc,
+};
+
+use crate::{
+    ParseOptions, TypeDefinitionInstance, type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::{ParseError, ParseErrorKind, ParseErrorPath, PathSegment, SourceLocation, Value};
+
+impl<Id: Display, FieldName: Ord + Display + Clone + Borrow<str>> Value<Id, FieldName> {
+    /// Parse the items of a JSON array from a reader for a specified array type instance, one
+    /// item at a time.
+    ///
+    /// Only one item is held in memory at a time, which makes it possible to process arrays far
+    /// larger than the available memory. The reader is buffered internally.
+    pub fn parse_array_items<R: Read>(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        reader: R,
+    ) -> ArrayItems<R, Id, FieldName> {
+        Self::parse_array_items_with_options(instance, reader, &ParseOptions::default())
+    }
+
+    /// Parse the items of a JSON array from a reader for a specified array type instance, one
+    /// item at a time, using the specified parse options.
+    pub fn parse_array_items_with_options<R: Read>(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        reader: R,
+        options: &ParseOptions,
+    ) -> ArrayItems<R, Id, FieldName> {
+        ArrayItems {
+            bytes: BufReader::new(reader).bytes(),
+            instance,
+            options: options.clone(),
+            state: State::Start,
+            index: 0,
+            line: 1,
+            column: 0,
+            item: Vec::new(),
+        }
+    }
+}
+
+/// An iterator over the items of a JSON array, parsed from a reader.
+///
+/// Each item is parsed and validated for the items type. An invalid item is reported as an error
+/// with its path in the array, and iteration goes on with the next item. An error in the
+/// structure of the array itself, or in the reader, ends the iteration.
+///
+/// See [`Value::parse_array_items`].
+pub struct ArrayItems<R, Id, FieldName: Ord> {
+    /// The bytes of the reader.
+    bytes: io::Bytes<BufReader<R>>,
+
+    /// The type instance of the array.
+    instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The parse options.
+    options: ParseOptions,
+
+    /// The state of the parse.
+    state: State,
+
+    /// The index of the next item.
+    index: usize,
+
+    /// The line of the last read byte, starting at 1.
+    line: usize,
+
+    /// The column of the last read byte, starting at 1.
+    column: usize,
+
+    /// The text of the item being read.
+    item: Vec<u8>,
+}
+
+/// The state of the parse of an array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// The opening bracket was not read yet.
+    Start,
+
+    /// The opening bracket, or the comma after an item, was read.
+    Items,
+
+    /// The closing bracket was read.
+    End,
+
+    /// The iteration is over.
+    Done,
+}
+
+impl<R: Read, Id: Display, FieldName: Ord + Display + Clone + Borrow<str>>
+    ArrayItems<R, Id, FieldName>
+{
+    /// Read the next byte, if any.
+    fn next_byte(&mut self) -> Result<Option<u8>, ParseError<Id, FieldName>> {
+        match self.bytes.next().transpose() {
+            Ok(Some(byte)) => {
+                if byte == b'\n' {
+                    self.line += 1;
+                    self.column = 0;
+                } else {
+                    self.column += 1;
+                }
+
+                Ok(Some(byte))
+            }
+            Ok(None) => Ok(None),
+            Err(err) => Err(self.syntax_error(&err.to_string())),
+        }
+    }
+
+    /// Read the next byte that is not whitespace, if any.
+    fn next_token(&mut self) -> Result<Option<u8>, ParseError<Id, FieldName>> {
+        while let Some(byte) = self.next_byte()? {
+            if !byte.is_ascii_whitespace() {
+                return Ok(Some(byte));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Build an error in the structure of the array, at the last read byte, ending the iteration.
+    fn syntax_error(&mut self, message: &str) -> ParseError<Id, FieldName> {
+        self.state = State::Done;
+
+        ParseError {
+            instance: Arc::clone(&self.instance),
+            path: ParseErrorPath::default(),
+            err: ParseErrorKind::InvalidJson(message.to_owned()),
+            location: Some(SourceLocation {
+                line: self.line,
+                column: self.column,
+            }),
+        }
+    }
+
+    /// Read the opening bracket of the array.
+    fn start(&mut self) -> Result<(), ParseError<Id, FieldName>> {
+        if !matches!(self.instance.attributes, TypeAttributesInstance::Array(_)) {
+            self.state = State::Done;
+
+            return Err(ParseError {
+                instance: Arc::clone(&self.instance),
+                path: ParseErrorPath::default(),
+                err: ParseErrorKind::UnexpectedValue {
+                    expected: self.instance.attributes.kind(),
+                    found: "array",
+                },
+                location: None,
+            });
+        }
+
+        match self.next_token()? {
+            Some(b'[') => Ok(()),
+            Some(_) => Err(self.syntax_error("expected `[`")),
+            None => Err(self.syntax_error("EOF while parsing a value")),
+        }
+    }
+
+    /// Read the text of the next item, up to the comma or closing bracket that ends it.
+    ///
+    /// Returns the location of the item, or `None` if the array is empty.
+    fn read_item(&mut self) -> Result<Option<SourceLocation>, ParseError<Id, FieldName>> {
+        self.item.clear();
+
+        let Some(first) = self.next_token()? else {
+            return Err(self.syntax_error("EOF while parsing a list"));
+        };
+
+        if first == b']' && self.index == 0 {
+            self.state = State::End;
+            return Ok(None);
+        }
+
+        let location = SourceLocation {
+            line: self.line,
+            column: self.column,
+        };
+        let mut depth = 0_usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut byte = first;
+
+        loop {
+            if in_string {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+            } else {
+                match byte {
+                    b'"' => in_string = true,
+                    b'[' | b'{' => depth += 1,
+                    b']' | b'}' if depth > 0 => depth -= 1,
+                    b',' | b']' if depth == 0 => {
+                        if byte == b']' {
+                            self.state = State::End;
+                        }
+
+                        return Ok(Some(location));
+                    }
+                    _ => {}
+                }
+            }
+
+            self.item.push(byte);
+
+            byte = match self.next_byte()? {
+                Some(byte) => byte,
+                None => return Err(self.syntax_error("EOF while parsing a list")),
+            };
+        }
+    }
+
+    /// Parse the text of the item that was read.
+    fn parse_item(
+        &self,
+        location: SourceLocation,
+    ) -> Result<Value<Id, FieldName>, ParseError<Id, FieldName>> {
+        let TypeAttributesInstance::Array(a) = &self.instance.attributes else {
+            unreachable!("the type was checked to be an array type");
+        };
+
+        let result = match std::str::from_utf8(&self.item) {
+            Ok(text) => Value::parse_str_for_with_options(
+                a.items_type_id().clone(),
+                text.trim_end(),
+                &self.options,
+            ),
+            Err(err) => Err(ParseError {
+                instance: a.items_type_id().clone(),
+                path: ParseErrorPath::default(),
+                err: ParseErrorKind::InvalidJson(err.to_string()),
+                location: None,
+            }),
+        };
+
+        result.map_err(|err| {
+            let mut path = vec![PathSegment::ArrayIndex(self.index)];
+            path.extend(err.path.0);
+
+            ParseError {
+                instance: Arc::clone(&self.instance),
+                path: ParseErrorPath(path),
+                err: err.err,
+                // Locations within the item are relative to its start.
+                location: Some(match err.location {
+                    Some(SourceLocation { line: 1, column }) => SourceLocation {
+                        line: location.line,
+                        column: location.column + column - 1,
+                    },
+                    Some(SourceLocation { line, column }) => SourceLocation {
+                        line: location.line + line - 1,
+                        column,
+                    },
+                    None => location,
+                }),
+            }
+        })
+    }
+
+    /// Parse the next item, if any.
+    fn next_item(&mut self) -> Result<Option<Value<Id, FieldName>>, ParseError<Id, FieldName>> {
+        match self.state {
+            State::Start => {
+                self.start()?;
+                self.state = State::Items;
+            }
+            State::Items => {}
+            State::End => {
+                return match self.next_token()? {
+                    Some(_) => Err(self.syntax_error("trailing characters")),
+                    None => Ok(None),
+                };
+            }
+            State::Done => return Ok(None),
+        }
+
+        let Some(location) = self.read_item()? else {
+            return self.next_item();
+        };
+
+        if let Some(max) = self.options.limits.max_array_length
+            && self.index >= max
+        {
+            self.state = State::Done;
+
+            return Err(ParseError {
+                instance: Arc::clone(&self.instance),
+                path: ParseErrorPath(vec![PathSegment::ArrayIndex(self.index)]),
+                err: ParseErrorKind::ArrayTooLong(max),
+                location: Some(location),
+            });
+        }
+
+        // An invalid item does not end the iteration, as the structure of the array is intact.
+        let item = self.parse_item(location);
+        self.index += 1;
+
+        item.map(Some)
+    }
+}
+
+impl<R: Read, Id: Display, FieldName: Ord + Display + Clone + Borrow<str>> Iterator
+    for ArrayItems<R, Id, FieldName>
+{
+    type Item = Result<Value<Id, FieldName>, ParseError<Id, FieldName>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_item() {
+            Ok(Some(item)) => Some(Ok(item)),
+            Ok(None) => {
+                self.state = State::Done;
+                None
+            }
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ParseErrorKind, ParseLimits, ParseOptions, PathSegment,
+        test_support::{TypeAttributes, Value, registry, td},
+        type_attributes::{ArrayTypeAttributes, NumberTypeAttributes},
+    };
+
+    #[test]
+    fn test_parse_array_items() {
+        let registry = registry([
+            td(
+                1,
+                "Level",
+                TypeAttributes::Uint32(NumberTypeAttributes::builder().max(10).build().unwrap()),
+            ),
+            td(
+                2,
+                "Levels",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(
+                3,
+                "Grid",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+        ]);
+
+        let grid = registry.get(&3).unwrap().clone();
+        let text = "[ [1, 2],\n  [],\n  [3, 11] , [\"]\"], [4] ]\n";
+        let items: Vec<_> = Value::parse_array_items(grid.clone(), text.as_bytes()).collect();
+        assert_eq!(items.len(), 5);
+        assert_eq!(items[0].as_ref().unwrap().to_string(), "[1, 2]");
+        assert_eq!(items[1].as_ref().unwrap().to_string(), "[]");
+        assert_eq!(items[4].as_ref().unwrap().to_string(), "[4]");
+
+        // Invalid items are reported in the array, and do not end the iteration.
+        let err = items[2].as_ref().unwrap_err();
+        assert_eq!(
+            err.path(),
+            [PathSegment::ArrayIndex(2), PathSegment::ArrayIndex(1)]
+        );
+        assert!(matches!(err.kind(), ParseErrorKind::InvalidUint32(_)));
+        assert_eq!(
+            (
+                err.location().unwrap().line(),
+                err.location().unwrap().column()
+            ),
+            (3, 8)
+        );
+
+        let whole = Value::parse_str_for(grid.clone(), text).unwrap_err();
+        assert_eq!(whole.location(), err.location());
+
+        let err = items[3].as_ref().unwrap_err();
+        assert_eq!(
+            err.path(),
+            [PathSegment::ArrayIndex(3), PathSegment::ArrayIndex(0)]
+        );
+
+        assert_eq!(
+            Value::parse_array_items(grid.clone(), " [ ] ".as_bytes()).count(),
+            0
+        );
+
+        // Errors in the structure of the array end the iteration.
+        let items: Vec<_> =
+            Value::parse_array_items(grid.clone(), "[[1], [2]".as_bytes()).collect();
+        assert_eq!(items.len(), 2);
+        assert!(items[0].is_ok());
+        assert_eq!(items[1].as_ref().unwrap_err().kind().code(), "invalid_json");
+
+        let items: Vec<_> = Value::parse_array_items(grid.clone(), "[[1]] [".as_bytes()).collect();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].as_ref().unwrap_err().kind().code(), "invalid_json");
+
+        let options = ParseOptions::default().limits(ParseLimits::default().max_array_length(1));
+        let items: Vec<_> =
+            Value::parse_array_items_with_options(grid, "[[1], [2], [3]]".as_bytes(), &options)
+                .collect();
+        assert_eq!(items.len(), 2);
+        assert!(matches!(
+            items[1].as_ref().unwrap_err().kind(),
+            ParseErrorKind::ArrayTooLong(1)
+        ));
+
+        let levels = registry.get(&1).unwrap().clone();
+        let items: Vec<_> = Value::parse_array_items(levels, "[1]".as_bytes()).collect();
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            items[0].as_ref().unwrap_err().kind().code(),
+            "unexpected_value"
+        );
+    }
+}
@@ -0,0 +1,666 @@
+//! The GameSON binary encoding of values.
+
+use std::{
+    borrow::Borrow,
+    fmt::{self, Display},
+    marker::PhantomData,
+    sync::Arc,
+};
+
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess};
+
+use crate::{
+    DictionaryWireForm, ParseOptions, TypeDefinitionInstance, metrics::Measure,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::{ParseContext, ParseError, ParseErrorKind, Value, ValueImpl, seed::ValueImplSeed};
+
+/// The bytes starting the binary encoding: a magic number, followed by the format version.
+const HEADER: &[u8; 4] = b"GSB\x01";
+
+impl<Id, FieldName: Ord + Borrow<str>> Value<Id, FieldName> {
+    /// Encode the value in the GameSON binary encoding.
+    ///
+    /// The binary encoding is much more compact and faster to decode than JSON, but it is not
+    /// self-describing: it can only be decoded for the type instance of the value, with
+    /// [`decode_binary`](Self::decode_binary). It starts with the `GSB` magic number and a
+    /// version byte, followed by the value:
+    ///
+    /// | Kind               | Encoding                                                    |
+    /// |--------------------|-------------------------------------------------------------|
+    /// | `boolean`          | a byte, `0` or `1`                                          |
+    /// | `int32`, `int64`   | a zigzag LEB128 varint                                      |
+    /// | `uint32`, `uint64` | a LEB128 varint                                             |
+    /// | `float32`          | 4 little-endian bytes                                       |
+    /// | `float64`          | 8 little-endian bytes                                       |
+    /// | `string`, `enum`   | the varint length of the UTF-8 text, then the text          |
+    /// | `uuid`             | 16 bytes                                                    |
+    /// | `bytes`            | the varint length of the bytes, then the bytes              |
+    /// | `array`            | the varint number of items, then the items                  |
+    /// | `dictionary`       | the varint number of entries, then each key and its value   |
+    ///
+    /// Enum values are encoded by name rather than by number, so that encoded values survive
+    /// adding values to their enum type.
+    pub fn encode_binary(&self) -> Vec<u8> {
+        let mut bytes = HEADER.to_vec();
+        self.value.encode_binary(&mut bytes);
+
+        bytes
+    }
+}
+
+impl<Id: Display, FieldName: Ord + Display + Clone + Borrow<str>> Value<Id, FieldName> {
+    /// Decode a GameSON value from its binary encoding, for a specified type instance.
+    ///
+    /// The value is validated exactly as when parsing JSON. See
+    /// [`encode_binary`](Self::encode_binary) for the encoding.
+    pub fn decode_binary(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        bytes: &[u8],
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        Self::decode_binary_with_options(instance, bytes, &ParseOptions::default())
+    }
+
+    /// Decode a GameSON value from its binary encoding, for a specified type instance, using the
+    /// specified parse options.
+    pub fn decode_binary_with_options(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        let measure = Measure::start(options);
+        let mut context = ParseContext::new(options);
+        let mut reader = Reader { bytes, offset: 0 };
+
+        let result = reader
+            .header()
+            .and_then(|()| {
+                ValueImplSeed {
+                    context: &mut context,
+                    instance: &instance,
+                    strings: PhantomData,
+                }
+                .deserialize(Deserializer {
+                    reader: &mut reader,
+                    instance: &instance,
+                })
+            })
+            .and_then(|value| match reader.offset == bytes.len() {
+                true => Ok(value),
+                false => Err(reader.error("trailing bytes after the value")),
+            })
+            .map_err(|err| match context.error.take() {
+                Some(err) => err,
+                None => ParseErrorKind::InvalidBinary(err.0),
+            });
+
+        if let Some(measure) = measure {
+            measure.parsed(instance.name.borrow(), result.as_ref().map(|_| ()));
+        }
+
+        match result {
+            Ok(value) => Ok(Self { instance, value }),
+            Err(err) => Err(ParseError {
+                instance,
+                path: context.path.into_path(),
+                err,
+                location: None,
+            }),
+        }
+    }
+}
+
+impl<FieldName: Borrow<str>> ValueImpl<FieldName> {
+    /// Encode the value in the binary encoding.
+    fn encode_binary(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Self::Array(items) => {
+                write_varint(bytes, items.len() as u64);
+
+                for item in items {
+                    item.encode_binary(bytes);
+                }
+            }
+            Self::Dictionary(entries) => {
+                write_varint(bytes, entries.len() as u64);
+
+                for (key, value) in entries {
+                    key.encode_binary(bytes);
+                    value.encode_binary(bytes);
+                }
+            }
+            Self::Boolean(v) => bytes.push(u8::from(*v)),
+            Self::Int32(v) => write_varint(bytes, zigzag((*v).into())),
+            Self::Int64(v) => write_varint(bytes, zigzag(*v)),
+            Self::Uint32(v) => write_varint(bytes, (*v).into()),
+            Self::Uint64(v) => write_varint(bytes, *v),
+            Self::Float32(v) => bytes.extend(v.to_le_bytes()),
+            Self::Float64(v) => bytes.extend(v.to_le_bytes()),
+            Self::String(v) => write_bytes(bytes, v.as_bytes()),
+            Self::Enum(v) => write_bytes(bytes, v.borrow().as_bytes()),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(v) => bytes.extend(v.as_bytes()),
+            #[cfg(feature = "bytes")]
+            Self::Bytes(v) => write_bytes(bytes, v),
+        }
+    }
+}
+
+/// Write a LEB128 varint.
+fn write_varint(bytes: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        bytes.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+
+    bytes.push(v as u8);
+}
+
+/// Write bytes, prefixed with their length.
+fn write_bytes(bytes: &mut Vec<u8>, v: &[u8]) {
+    write_varint(bytes, v.len() as u64);
+    bytes.extend(v);
+}
+
+/// Map a signed integer to an unsigned one, so that small negative numbers have short varints.
+fn zigzag(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+/// Revert [`zigzag`].
+fn unzigzag(v: u64) -> i64 {
+    (v >> 1) as i64 ^ -((v & 1) as i64)
+}
+
+/// An error in the binary encoding.
+#[derive(Debug)]
+struct Error(String);
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+/// A reader of the binary encoding.
+struct Reader<'de> {
+    /// The encoded bytes.
+    bytes: &'de [u8],
+
+    /// The offset of the next byte to read.
+    offset: usize,
+}
+
+impl<'de> Reader<'de> {
+    /// Build an error at the current offset.
+    fn error(&self, reason: &str) -> Error {
+        Error(format!("{reason} at offset {}", self.offset))
+    }
+
+    /// Read and check the header.
+    fn header(&mut self) -> Result<(), Error> {
+        match self.take(HEADER.len()) {
+            Ok(header) if header == HEADER => Ok(()),
+            _ => {
+                self.offset = 0;
+                Err(self.error("missing GameSON binary header"))
+            }
+        }
+    }
+
+    /// Read a number of bytes.
+    fn take(&mut self, len: usize) -> Result<&'de [u8], Error> {
+        match self
+            .bytes
+            .get(self.offset..)
+            .and_then(|rest| rest.get(..len))
+        {
+            Some(bytes) => {
+                self.offset += len;
+                Ok(bytes)
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    /// Read an array of bytes.
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        Ok(self.take(N)?.try_into().expect("the length is right"))
+    }
+
+    /// Read a LEB128 varint.
+    fn varint(&mut self) -> Result<u64, Error> {
+        let mut v = 0;
+
+        for shift in (0..64).step_by(7) {
+            let [byte] = self.take_array()?;
+            v |= u64::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return match shift == 63 && byte > 1 {
+                    true => Err(self.error("varint overflow")),
+                    false => Ok(v),
+                };
+            }
+        }
+
+        Err(self.error("varint overflow"))
+    }
+
+    /// Read a length.
+    fn len(&mut self) -> Result<usize, Error> {
+        let len = self.varint()?;
+
+        usize::try_from(len).map_err(|_| self.error("length overflow"))
+    }
+
+    /// Read bytes prefixed with their length.
+    fn bytes(&mut self) -> Result<&'de [u8], Error> {
+        let len = self.len()?;
+        self.take(len)
+    }
+}
+
+/// A deserializer of a value in the binary encoding, guided by its type instance.
+struct Deserializer<'r, 'de, 'i, Id, FieldName: Ord> {
+    /// The reader.
+    reader: &'r mut Reader<'de>,
+
+    /// The type instance of the value.
+    instance: &'i TypeDefinitionInstance<Id, FieldName>,
+}
+
+impl<'de, Id, FieldName: Ord> de::Deserializer<'de> for Deserializer<'_, 'de, '_, Id, FieldName> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let reader = self.reader;
+
+        match &self.instance.attributes {
+            TypeAttributesInstance::Array(a) => {
+                let remaining = reader.len()?;
+
+                visitor.visit_seq(Items {
+                    reader,
+                    instance: a.items_type_id(),
+                    remaining,
+                })
+            }
+            TypeAttributesInstance::Dictionary(a) => {
+                let entries = Entries {
+                    remaining: reader.len()?,
+                    reader,
+                    keys: a.keys_type_id(),
+                    values: a.values_type_id(),
+                };
+
+                match a.wire_form() {
+                    DictionaryWireForm::Object => visitor.visit_map(entries),
+                    DictionaryWireForm::Pairs => visitor.visit_seq(entries),
+                }
+            }
+            TypeAttributesInstance::Boolean(_) => match reader.take_array()? {
+                [0] => visitor.visit_bool(false),
+                [1] => visitor.visit_bool(true),
+                _ => Err(reader.error("invalid boolean")),
+            },
+            TypeAttributesInstance::Int32(_) | TypeAttributesInstance::Int64(_) => {
+                visitor.visit_i64(unzigzag(reader.varint()?))
+            }
+            TypeAttributesInstance::Uint32(_) | TypeAttributesInstance::Uint64(_) => {
+                visitor.visit_u64(reader.varint()?)
+            }
+            TypeAttributesInstance::Float32(_) => {
+                visitor.visit_f64(f32::from_le_bytes(reader.take_array()?).into())
+            }
+            TypeAttributesInstance::Float64(_) => {
+                visitor.visit_f64(f64::from_le_bytes(reader.take_array()?))
+            }
+            TypeAttributesInstance::String(_) | TypeAttributesInstance::Enum(_) => {
+                let bytes = reader.bytes()?;
+
+                match std::str::from_utf8(bytes) {
+                    Ok(v) => visitor.visit_borrowed_str(v),
+                    Err(_) => Err(reader.error("invalid UTF-8")),
+                }
+            }
+            #[cfg(feature = "uuid")]
+            TypeAttributesInstance::Uuid(_) => visitor.visit_borrowed_bytes(reader.take(16)?),
+            #[cfg(feature = "bytes")]
+            TypeAttributesInstance::Bytes(_) => visitor.visit_borrowed_bytes(reader.bytes()?),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+/// The items of an array in the binary encoding.
+struct Items<'r, 'de, 'i, Id, FieldName: Ord> {
+    /// The reader.
+    reader: &'r mut Reader<'de>,
+
+    /// The type instance of the items.
+    instance: &'i TypeDefinitionInstance<Id, FieldName>,
+
+    /// The number of items left to read.
+    remaining: usize,
+}
+
+impl<'de, Id, FieldName: Ord> SeqAccess<'de> for Items<'_, 'de, '_, Id, FieldName> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+
+        seed.deserialize(Deserializer {
+            reader: &mut *self.reader,
+            instance: self.instance,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// The entries of a dictionary in the binary encoding.
+///
+/// They are visited as a map for dictionaries in the object wire form, and as a sequence of
+/// `[key, value]` pairs for dictionaries in the pairs wire form.
+struct Entries<'r, 'de, 'i, Id, FieldName: Ord> {
+    /// The reader.
+    reader: &'r mut Reader<'de>,
+
+    /// The type instance of the keys.
+    keys: &'i TypeDefinitionInstance<Id, FieldName>,
+
+    /// The type instance of the values.
+    values: &'i TypeDefinitionInstance<Id, FieldName>,
+
+    /// The number of entries left to read.
+    remaining: usize,
+}
+
+impl<'de, Id, FieldName: Ord> MapAccess<'de> for Entries<'_, 'de, '_, Id, FieldName> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+
+        seed.deserialize(Deserializer {
+            reader: &mut *self.reader,
+            instance: self.keys,
+        })
+        .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(Deserializer {
+            reader: &mut *self.reader,
+            instance: self.values,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de, Id, FieldName: Ord> SeqAccess<'de> for Entries<'_, 'de, '_, Id, FieldName> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+
+        seed.deserialize(Pair {
+            reader: &mut *self.reader,
+            key: self.keys,
+            value: self.values,
+            read: 0,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// A `[key, value]` pair of a dictionary in the pairs wire form, in the binary encoding.
+struct Pair<'r, 'de, 'i, Id, FieldName: Ord> {
+    /// The reader.
+    reader: &'r mut Reader<'de>,
+
+    /// The type instance of the key.
+    key: &'i TypeDefinitionInstance<Id, FieldName>,
+
+    /// The type instance of the value.
+    value: &'i TypeDefinitionInstance<Id, FieldName>,
+
+    /// The number of elements read so far.
+    read: usize,
+}
+
+impl<'de, Id, FieldName: Ord> de::Deserializer<'de> for Pair<'_, 'de, '_, Id, FieldName> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+impl<'de, Id, FieldName: Ord> SeqAccess<'de> for Pair<'_, 'de, '_, Id, FieldName> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        let instance = match self.read {
+            0 => self.key,
+            1 => self.value,
+            _ => return Ok(None),
+        };
+
+        self.read += 1;
+
+        seed.deserialize(Deserializer {
+            reader: &mut *self.reader,
+            instance,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(2 - self.read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        DictionaryWireForm, ParseErrorKind, PathSegment,
+        test_support::{TypeAttributes, TypeDefinitionRegistry, Value, registry, td},
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, EnumTypeAttributes, NumberTypeAttributes,
+        },
+    };
+
+    use super::{unzigzag, zigzag};
+
+    fn equipment_registry() -> TypeDefinitionRegistry {
+        registry([
+            td(
+                1,
+                "Slot",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("head")
+                        .with_value("torso")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(2, "Offset", TypeAttributes::Int64(Default::default())),
+            td(3, "Weight", TypeAttributes::Float32(Default::default())),
+            td(4, "Name", TypeAttributes::String(Default::default())),
+            td(
+                5,
+                "Offsets",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+            td(
+                6,
+                "Weights",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 3)),
+            ),
+            td(
+                7,
+                "Names",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(2, 4).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+            td(8, "Level", TypeAttributes::Uint32(Default::default())),
+            td(
+                9,
+                "SmallLevel",
+                TypeAttributes::Uint32(NumberTypeAttributes::builder().max(10).build().unwrap()),
+            ),
+            td(
+                10,
+                "Levels",
+                TypeAttributes::Array(ArrayTypeAttributes::new(8)),
+            ),
+            td(
+                11,
+                "SmallLevels",
+                TypeAttributes::Array(ArrayTypeAttributes::new(9)),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let registry = equipment_registry();
+
+        for (id, json) in [
+            (5, json!([0, -1, 1, i64::MIN, i64::MAX])),
+            (6, json!({ "head": 1.5, "torso": -0.25 })),
+            (7, json!([[-3, "Ærøskøbing"], [300, ""]])),
+        ] {
+            let instance = registry.get(&id).unwrap().clone();
+            let value = Value::parse_for(instance.clone(), json.clone()).unwrap();
+            let bytes = value.encode_binary();
+
+            assert!(bytes.len() < json.to_string().len(), "{json}");
+            assert_eq!(
+                Value::decode_binary(instance, &bytes)
+                    .unwrap()
+                    .to_json()
+                    .unwrap(),
+                json
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode_binary() {
+        let registry = equipment_registry();
+
+        let levels = Value::parse_for(registry.get(&10).unwrap().clone(), json!([1, 2, 300]));
+        let bytes = levels.unwrap().encode_binary();
+        assert_eq!(bytes, b"GSB\x01\x03\x01\x02\xac\x02");
+    }
+
+    #[test]
+    fn test_decode_binary_invalid_value() {
+        let small_levels = equipment_registry().get(&11).unwrap().clone();
+
+        // Decoded values are validated.
+        let err = Value::decode_binary(small_levels, b"GSB\x01\x03\x01\x02\xac\x02").unwrap_err();
+        assert_eq!(err.path(), [PathSegment::ArrayIndex(2)]);
+        assert_eq!(err.kind().code(), "invalid_uint32");
+    }
+
+    #[test]
+    fn test_decode_binary_malformed() {
+        let small_levels = equipment_registry().get(&11).unwrap().clone();
+
+        for (bytes, message) in [
+            (
+                &b"GSB\x02\x00"[..],
+                "missing GameSON binary header at offset 0",
+            ),
+            (
+                b"GSB\x01\x03\x01\x02",
+                "unexpected end of input at offset 7",
+            ),
+            (
+                b"GSB\x01\x01\x01\x00",
+                "trailing bytes after the value at offset 6",
+            ),
+            (
+                b"GSB\x01\x01\xff\xff\xff\xff\xff\xff\xff\xff\xff\x02",
+                "varint overflow at offset 15",
+            ),
+        ] {
+            let err = Value::decode_binary(small_levels.clone(), bytes).unwrap_err();
+            assert!(
+                matches!(err.kind(), ParseErrorKind::InvalidBinary(m) if m == message),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_zigzag() {
+        for v in [0, 1, -1, 63, -64, i64::MIN, i64::MAX] {
+            assert_eq!(unzigzag(zigzag(v)), v);
+        }
+        assert_eq!(zigzag(-1), 1);
+    }
+}
@@ -0,0 +1,196 @@
+//! GameSON values borrowing their strings from the parsed text.
+
+use std::{
+    borrow::{Borrow, Cow},
+    fmt::Display,
+    sync::Arc,
+};
+
+use crate::{ParseOptions, TypeDefinitionInstance};
+
+use super::{ParseError, ParseErrorKind, Value, ValueImpl, seed::SeedString, validator};
+
+/// A GameSON value whose strings borrow from the JSON text it was parsed from.
+///
+/// Parsing a value this way avoids allocating its strings whenever they are not escaped nor
+/// normalized in the text, which makes it cheaper to check documents that are discarded right
+/// after. Otherwise, parsing behaves exactly like [`Value::parse_str_for`]. Use
+/// [`into_owned`](Self::into_owned) to keep the value longer than the text.
+#[derive(Debug, Clone)]
+pub struct ValueRef<'a, Id, FieldName: Ord> {
+    /// The type instance.
+    instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The value.
+    value: ValueImpl<FieldName, Cow<'a, str>>,
+}
+
+impl<'a, Id, FieldName: Ord> ValueRef<'a, Id, FieldName> {
+    /// Get the type instance of the value.
+    pub fn instance(&self) -> &Arc<TypeDefinitionInstance<Id, FieldName>> {
+        &self.instance
+    }
+}
+
+impl<'a, Id: Display, FieldName: Ord + Display + Clone + Borrow<str>> ValueRef<'a, Id, FieldName> {
+    /// Parse a GameSON value from JSON text for a specified type instance, borrowing its strings
+    /// from the text.
+    pub fn parse_str_for(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        s: &'a str,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        Self::parse_str_for_with_options(instance, s, &ParseOptions::default())
+    }
+
+    /// Parse a GameSON value from JSON text for a specified type instance, borrowing its strings
+    /// from the text, using the specified parse options.
+    pub fn parse_str_for_with_options(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        s: &'a str,
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        let value = super::seed::parse_json_impl(
+            &instance,
+            serde_json::Deserializer::from_str(s),
+            options,
+        )?;
+
+        Ok(Self { instance, value })
+    }
+
+    /// Convert the value into an owned value, copying its borrowed strings.
+    pub fn into_owned(self) -> Value<Id, FieldName> {
+        Value {
+            instance: self.instance,
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+impl<Id, FieldName: Ord + Display> Display for ValueRef<'_, Id, FieldName> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.value.fmt_for(&self.instance, f)
+    }
+}
+
+impl<'de> SeedString<'de> for Cow<'de, str> {
+    fn from_input(v: Cow<'de, str>) -> Self {
+        v
+    }
+
+    fn run_validator<Id, FieldName: Ord + Clone>(
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: ValueImpl<FieldName, Self>,
+    ) -> Result<ValueImpl<FieldName, Self>, ParseErrorKind> {
+        // Validators are given owned values: only values with a validator are copied.
+        if instance.validator.is_some() {
+            validator::run_validator(instance, value.clone().into_owned())?;
+        }
+
+        Ok(value)
+    }
+}
+
+impl<FieldName: Clone> ValueImpl<FieldName, Cow<'_, str>> {
+    /// Convert the value into an owned value, copying its borrowed strings.
+    pub(super) fn into_owned(mut self) -> ValueImpl<FieldName> {
+        // Values implement `Drop`: their contents are taken rather than moved out.
+        match &mut self {
+            Self::Array(items) => ValueImpl::Array(
+                std::mem::take(items)
+                    .into_iter()
+                    .map(Self::into_owned)
+                    .collect(),
+            ),
+            Self::Dictionary(items) => ValueImpl::Dictionary(
+                std::mem::take(items)
+                    .into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect(),
+            ),
+            Self::Boolean(v) => ValueImpl::Boolean(*v),
+            Self::Int32(v) => ValueImpl::Int32(*v),
+            Self::Int64(v) => ValueImpl::Int64(*v),
+            Self::Uint32(v) => ValueImpl::Uint32(*v),
+            Self::Uint64(v) => ValueImpl::Uint64(*v),
+            Self::Float32(v) => ValueImpl::Float32(*v),
+            Self::Float64(v) => ValueImpl::Float64(*v),
+            Self::String(v) => ValueImpl::String(std::mem::take(v).into()),
+            Self::Enum(v) => ValueImpl::Enum(v.clone()),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(v) => ValueImpl::Uuid(*v),
+            #[cfg(feature = "bytes")]
+            Self::Bytes(v) => ValueImpl::Bytes(std::mem::take(v)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use serde_json::json;
+
+    use super::ValueRef;
+    use crate::{
+        ParseErrorKind, PathSegment,
+        test_support::{TypeAttributes, registry, td},
+        type_attributes::{DictionaryTypeAttributes, StringTypeAttributes},
+    };
+
+    #[test]
+    fn test_value_ref() {
+        let registry = registry([
+            td(
+                1,
+                "Name",
+                TypeAttributes::String(StringTypeAttributes::default()),
+            ),
+            td(
+                2,
+                "Item",
+                TypeAttributes::String(StringTypeAttributes::default()),
+            ),
+            td(
+                3,
+                "Names",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(2, 1)),
+            ),
+        ]);
+
+        let names = registry.get(&3).unwrap().clone();
+        let text = r#"{"sword": "Excalibur", "bow": "Long\nbow"}"#;
+        let value = ValueRef::parse_str_for(names.clone(), text).unwrap();
+
+        let super::ValueImpl::Dictionary(entries) = &value.value else {
+            panic!("not a dictionary");
+        };
+        let strings: Vec<_> = entries
+            .iter()
+            .flat_map(|(key, value)| [key, value])
+            .map(|v| match v {
+                super::ValueImpl::String(v) => matches!(v, Cow::Borrowed(_)),
+                _ => panic!("not a string"),
+            })
+            .collect();
+
+        // Only the escaped string is copied.
+        assert_eq!(strings, [true, true, true, false]);
+
+        assert_eq!(
+            value.to_string(),
+            "{\"sword\": \"Excalibur\", \"bow\": \"Long\nbow\"}"
+        );
+        assert_eq!(
+            value.into_owned().to_json().unwrap(),
+            json!({ "sword": "Excalibur", "bow": "Long\nbow" })
+        );
+
+        let err = ValueRef::parse_str_for(names, r#"{"sword": 1}"#).unwrap_err();
+        assert_eq!(err.path(), [PathSegment::DictionaryKey("sword".to_owned())]);
+        assert!(matches!(
+            err.kind(),
+            ParseErrorKind::InvalidDictionaryValue(_)
+        ));
+    }
+}
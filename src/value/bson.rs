@@ -0,0 +1,365 @@
+//! BSON conversion of GameSON values.
+
+use std::{borrow::Borrow, fmt::Display, marker::PhantomData, sync::Arc};
+
+use bson::{Bson, Document};
+use serde::{
+    de::{
+        self, DeserializeSeed, IntoDeserializer, Visitor,
+        value::{Error, MapDeserializer, SeqDeserializer},
+    },
+    forward_to_deserialize_any,
+};
+
+use crate::{
+    DictionaryWireForm, ParseOptions, TypeDefinitionInstance, metrics::Measure,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::{
+    ParseContext, ParseError, ParseErrorKind, PathSegment, PathStack, SerializeError,
+    SerializeErrorKind, Value, ValueImpl, seed::ValueImplSeed,
+};
+
+impl<Id, FieldName: Ord + Borrow<str>> Value<Id, FieldName> {
+    /// Convert the value to BSON.
+    ///
+    /// The value has the same structure as in JSON. Integers are converted to the smallest BSON
+    /// integer that holds their type, floating-point numbers to doubles, bytes to generic binaries
+    /// and UUIDs to UUID binaries.
+    ///
+    /// # Errors
+    ///
+    /// BSON has no unsigned integers: `uint64` values above `i64::MAX` cannot be converted.
+    pub fn to_bson(&self) -> Result<Bson, SerializeError> {
+        let mut path = PathStack::default();
+
+        self.value
+            .to_bson(&self.instance, &mut path)
+            .map_err(|kind| SerializeError {
+                path: path.into_path(),
+                kind,
+            })
+    }
+}
+
+impl<Id: Display, FieldName: Ord + Display + Clone + Borrow<str>> Value<Id, FieldName> {
+    /// Parse a GameSON value from BSON for a specified type instance.
+    ///
+    /// The value is validated exactly as when parsing JSON. BSON integers of any size are accepted
+    /// for all integer types, and binaries of any subtype for bytes and UUIDs.
+    pub fn parse_bson(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        bson: &Bson,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        Self::parse_bson_with_options(instance, bson, &ParseOptions::default())
+    }
+
+    /// Parse a GameSON value from BSON for a specified type instance, using the specified parse
+    /// options.
+    pub fn parse_bson_with_options(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        bson: &Bson,
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        let measure = Measure::start(options);
+        let mut context = ParseContext::new(options);
+
+        let result = ValueImplSeed {
+            context: &mut context,
+            instance: &instance,
+            strings: PhantomData,
+        }
+        .deserialize(Item(bson))
+        .map_err(|err| match context.error.take() {
+            Some(err) => err,
+            None => ParseErrorKind::InvalidBson(err.to_string()),
+        });
+
+        if let Some(measure) = measure {
+            measure.parsed(instance.name.borrow(), result.as_ref().map(|_| ()));
+        }
+
+        match result {
+            Ok(value) => Ok(Self { instance, value }),
+            Err(err) => Err(ParseError {
+                instance,
+                path: context.path.into_path(),
+                err,
+                location: None,
+            }),
+        }
+    }
+}
+
+impl<FieldName: Ord + Borrow<str>> ValueImpl<FieldName> {
+    /// Convert the value to BSON, for a specified type instance.
+    ///
+    /// On error, `path` points to the value that could not be converted.
+    fn to_bson<Id>(
+        &self,
+        instance: &TypeDefinitionInstance<Id, FieldName>,
+        path: &mut PathStack,
+    ) -> Result<Bson, SerializeErrorKind> {
+        Ok(match (self, &instance.attributes) {
+            (Self::Array(items), TypeAttributesInstance::Array(a)) => Bson::Array(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        path.push(PathSegment::ArrayIndex(i));
+                        let item = item.to_bson(a.items_type_id(), path)?;
+
+                        // We only must pop if the conversion was successful.
+                        path.pop();
+
+                        Ok(item)
+                    })
+                    .collect::<Result<_, SerializeErrorKind>>()?,
+            ),
+            (Self::Dictionary(items), TypeAttributesInstance::Dictionary(a)) => {
+                match a.wire_form() {
+                    DictionaryWireForm::Object => Bson::Document(
+                        items
+                            .iter()
+                            .map(|(key, value)| {
+                                let key = key.key_string().into_owned();
+
+                                path.push(PathSegment::DictionaryKey(key.clone()));
+                                let value = value.to_bson(a.values_type_id(), path)?;
+
+                                // We only must pop if the conversion was successful.
+                                path.pop();
+
+                                Ok((key, value))
+                            })
+                            .collect::<Result<Document, SerializeErrorKind>>()?,
+                    ),
+                    DictionaryWireForm::Pairs => Bson::Array(
+                        items
+                            .iter()
+                            .enumerate()
+                            .map(|(i, (key, value))| {
+                                path.push(PathSegment::ArrayIndex(i));
+                                path.push(PathSegment::ArrayIndex(0));
+                                let key = key.to_bson(a.keys_type_id(), path)?;
+                                path.pop();
+                                path.push(PathSegment::ArrayIndex(1));
+                                let value = value.to_bson(a.values_type_id(), path)?;
+
+                                // We only must pop if the conversion was successful.
+                                path.pop();
+                                path.pop();
+
+                                Ok(Bson::Array(vec![key, value]))
+                            })
+                            .collect::<Result<_, SerializeErrorKind>>()?,
+                    ),
+                }
+            }
+            (Self::Array(_) | Self::Dictionary(_), _) => {
+                panic!("inconsistent value and type attributes");
+            }
+            (Self::Boolean(v), _) => Bson::Boolean(*v),
+            (Self::Int32(v), _) => Bson::Int32(*v),
+            (Self::Int64(v), _) => Bson::Int64(*v),
+            (Self::Uint32(v), _) => Bson::Int64((*v).into()),
+            (Self::Uint64(v), _) => Bson::Int64(
+                i64::try_from(*v).map_err(|_| SerializeErrorKind::UnrepresentableUint64(*v))?,
+            ),
+            (Self::Float32(v), _) => Bson::Double((*v).into()),
+            (Self::Float64(v), _) => Bson::Double(*v),
+            (Self::String(v), _) => Bson::String(v.as_str().to_owned()),
+            (Self::Enum(v), _) => Bson::String(v.borrow().to_owned()),
+            #[cfg(feature = "uuid")]
+            (Self::Uuid(v), _) => Bson::Binary(bson::Binary {
+                subtype: bson::spec::BinarySubtype::Uuid,
+                bytes: v.as_bytes().to_vec(),
+            }),
+            #[cfg(feature = "bytes")]
+            (Self::Bytes(v), _) => Bson::Binary(bson::Binary {
+                subtype: bson::spec::BinarySubtype::Generic,
+                bytes: v.clone(),
+            }),
+        })
+    }
+}
+
+/// A deserializer of a BSON value.
+#[derive(Clone, Copy)]
+struct Item<'a>(&'a Bson);
+
+impl<'de> de::Deserializer<'de> for Item<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Bson::Int32(v) => visitor.visit_i64((*v).into()),
+            Bson::Int64(v) => visitor.visit_i64(*v),
+            Bson::Double(v) => visitor.visit_f64(*v),
+            Bson::Binary(v) => visitor.visit_borrowed_bytes(&v.bytes),
+            Bson::String(v) => visitor.visit_borrowed_str(v),
+            Bson::Boolean(v) => visitor.visit_bool(*v),
+            Bson::Null => visitor.visit_unit(),
+            Bson::Array(items) => visitor.visit_seq(SeqDeserializer::new(items.iter().map(Item))),
+            Bson::Document(entries) => visitor.visit_map(MapDeserializer::new(
+                entries
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), Item(value))),
+            )),
+            v => Err(de::Error::custom(format_args!(
+                "unsupported BSON {:?}",
+                v.element_type()
+            ))),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de> for Item<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::{Bson, bson, doc};
+    use serde_json::json;
+
+    use crate::{
+        DictionaryWireForm, PathSegment, SerializeErrorKind,
+        test_support::{TypeAttributes, Value, registry, td},
+        type_attributes::{ArrayTypeAttributes, DictionaryTypeAttributes, NumberTypeAttributes},
+    };
+
+    #[test]
+    fn test_bson() {
+        let registry = registry([
+            td(1, "Name", TypeAttributes::String(Default::default())),
+            td(
+                2,
+                "Level",
+                TypeAttributes::Int32(NumberTypeAttributes::builder().max(10).build().unwrap()),
+            ),
+            td(3, "Score", TypeAttributes::Uint64(Default::default())),
+            td(
+                4,
+                "Levels",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2)),
+            ),
+            td(
+                5,
+                "Scores",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(1, 3).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+            td(6, "Weight", TypeAttributes::Float64(Default::default())),
+            td(
+                7,
+                "Weights",
+                TypeAttributes::Array(ArrayTypeAttributes::new(6)),
+            ),
+        ]);
+
+        let levels = registry.get(&4).unwrap().clone();
+        let value = Value::parse_for(levels.clone(), json!({ "warrior": 3 })).unwrap();
+        assert_eq!(
+            value.to_bson().unwrap(),
+            Bson::Document(doc! { "warrior": 3_i32 })
+        );
+
+        let scores = registry.get(&5).unwrap().clone();
+        let value = Value::parse_for(scores.clone(), json!([["mage", 7]])).unwrap();
+        let bson = value.to_bson().unwrap();
+        assert_eq!(bson, bson!([["mage", 7_i64]]));
+        assert_eq!(
+            Value::parse_bson(scores.clone(), &bson)
+                .unwrap()
+                .to_json()
+                .unwrap(),
+            json!([["mage", 7]])
+        );
+
+        let value = Value::parse_for(scores.clone(), json!([["mage", u64::MAX]])).unwrap();
+        let err = value.to_bson().unwrap_err();
+        assert_eq!(
+            err.path(),
+            [PathSegment::ArrayIndex(0), PathSegment::ArrayIndex(1)]
+        );
+        assert!(matches!(
+            err.kind(),
+            SerializeErrorKind::UnrepresentableUint64(u64::MAX)
+        ));
+
+        // Integers of any size are accepted, and validated as in JSON.
+        let weights = registry.get(&7).unwrap().clone();
+        let value = Value::parse_bson(weights, &bson!([1_i32, 2_i64, 0.5])).unwrap();
+        assert_eq!(value.to_json().unwrap(), json!([1.0, 2.0, 0.5]));
+
+        let err = Value::parse_bson(levels.clone(), &bson!({ "warrior": 11_i64 })).unwrap_err();
+        assert_eq!(
+            err.path(),
+            [PathSegment::DictionaryKey("warrior".to_owned())]
+        );
+        assert_eq!(err.kind().code(), "invalid_dictionary_value");
+
+        let err = Value::parse_bson(levels, &bson!({ "warrior": Bson::MaxKey })).unwrap_err();
+        assert_eq!(err.kind().code(), "invalid_bson");
+    }
+
+    #[cfg(all(feature = "bytes", feature = "uuid"))]
+    #[test]
+    fn test_bson_binaries() {
+        use bson::{Binary, spec::BinarySubtype};
+
+        let registry = registry([
+            td(1, "Id", TypeAttributes::Uuid(Default::default())),
+            td(2, "Blob", TypeAttributes::Bytes(Default::default())),
+            td(
+                3,
+                "Blobs",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2)),
+            ),
+        ]);
+
+        let id = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+        let blobs = registry.get(&3).unwrap().clone();
+        let value = Value::parse_for(blobs.clone(), json!({ id: "AQID" })).unwrap();
+        let bson = value.to_bson().unwrap();
+        assert_eq!(
+            bson,
+            Bson::Document(doc! {
+                id: Binary { subtype: BinarySubtype::Generic, bytes: vec![1, 2, 3] },
+            })
+        );
+        assert_eq!(
+            Value::parse_bson(blobs, &bson).unwrap().to_json().unwrap(),
+            json!({ id: "AQID" })
+        );
+
+        let ids = registry.get(&1).unwrap().clone();
+        let uuid = uuid::Uuid::parse_str(id).unwrap();
+        let value = Value::parse_for(ids.clone(), json!(id)).unwrap();
+        let bson = value.to_bson().unwrap();
+        assert_eq!(
+            bson,
+            Bson::Binary(Binary {
+                subtype: BinarySubtype::Uuid,
+                bytes: uuid.as_bytes().to_vec()
+            })
+        );
+        assert_eq!(
+            Value::parse_bson(ids, &bson).unwrap().to_json().unwrap(),
+            json!(id)
+        );
+    }
+}
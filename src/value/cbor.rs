@@ -0,0 +1,220 @@
+//! CBOR serialization of GameSON values.
+
+use std::{borrow::Borrow, fmt::Display, marker::PhantomData, sync::Arc};
+
+use serde::{
+    de::{
+        self, DeserializeSeed, IntoDeserializer, Visitor,
+        value::{Error, MapDeserializer, SeqDeserializer},
+    },
+    forward_to_deserialize_any,
+};
+
+use crate::{ParseOptions, TypeDefinitionInstance, metrics::Measure};
+
+use super::{ParseContext, ParseError, ParseErrorKind, Value, seed::ValueImplSeed, wire::Wire};
+
+impl<Id, FieldName: Ord + Borrow<str>> Value<Id, FieldName> {
+    /// Serialize the value to CBOR.
+    ///
+    /// The value has the same structure as in JSON, except that floating-point numbers keep their
+    /// precision, and that bytes and UUIDs are serialized as CBOR byte strings.
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        ciborium::into_writer(
+            &Wire {
+                value: &self.value,
+                instance: &self.instance,
+            },
+            &mut bytes,
+        )
+        .expect("serializing to memory cannot fail");
+
+        bytes
+    }
+}
+
+impl<Id: Display, FieldName: Ord + Display + Clone + Borrow<str>> Value<Id, FieldName> {
+    /// Parse a GameSON value from CBOR for a specified type instance.
+    ///
+    /// The value is validated exactly as when parsing JSON. Tags are ignored.
+    pub fn parse_cbor(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        bytes: &[u8],
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        Self::parse_cbor_with_options(instance, bytes, &ParseOptions::default())
+    }
+
+    /// Parse a GameSON value from CBOR for a specified type instance, using the specified parse
+    /// options.
+    pub fn parse_cbor_with_options(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        mut bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        let measure = Measure::start(options);
+        let mut context = ParseContext::new(options);
+
+        // `ciborium` only deserializes owned types, so the CBOR data item is decoded first.
+        let result = ciborium::from_reader::<ciborium::Value, _>(&mut bytes)
+            .map_err(|err| ParseErrorKind::InvalidCbor(err.to_string()))
+            .and_then(|item| match bytes.is_empty() {
+                true => Ok(item),
+                false => Err(ParseErrorKind::InvalidCbor(
+                    "trailing bytes after the value".to_owned(),
+                )),
+            })
+            .and_then(|item| {
+                ValueImplSeed {
+                    context: &mut context,
+                    instance: &instance,
+                    strings: PhantomData,
+                }
+                .deserialize(Item(&item))
+                .map_err(|err| match context.error.take() {
+                    Some(err) => err,
+                    None => ParseErrorKind::InvalidCbor(err.to_string()),
+                })
+            });
+
+        if let Some(measure) = measure {
+            measure.parsed(instance.name.borrow(), result.as_ref().map(|_| ()));
+        }
+
+        match result {
+            Ok(value) => Ok(Self { instance, value }),
+            Err(err) => Err(ParseError {
+                instance,
+                path: context.path.into_path(),
+                err,
+                location: None,
+            }),
+        }
+    }
+}
+
+/// A deserializer of a decoded CBOR data item.
+#[derive(Clone, Copy)]
+struct Item<'a>(&'a ciborium::Value);
+
+impl<'de> de::Deserializer<'de> for Item<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            ciborium::Value::Integer(v) => {
+                let v = i128::from(*v);
+
+                match (u64::try_from(v), i64::try_from(v)) {
+                    (Ok(v), _) => visitor.visit_u64(v),
+                    (_, Ok(v)) => visitor.visit_i64(v),
+                    _ => visitor.visit_i128(v),
+                }
+            }
+            ciborium::Value::Bytes(v) => visitor.visit_borrowed_bytes(v),
+            ciborium::Value::Float(v) => visitor.visit_f64(*v),
+            ciborium::Value::Text(v) => visitor.visit_borrowed_str(v),
+            ciborium::Value::Bool(v) => visitor.visit_bool(*v),
+            ciborium::Value::Null => visitor.visit_unit(),
+            ciborium::Value::Tag(_, v) => Item(v).deserialize_any(visitor),
+            ciborium::Value::Array(items) => {
+                visitor.visit_seq(SeqDeserializer::new(items.iter().map(Item)))
+            }
+            ciborium::Value::Map(entries) => visitor.visit_map(MapDeserializer::new(
+                entries.iter().map(|(key, value)| (Item(key), Item(value))),
+            )),
+            _ => Err(de::Error::custom("unsupported CBOR data item")),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de> for Item<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        DictionaryWireForm, PathSegment,
+        test_support::{TypeAttributes, Value, registry, td},
+        type_attributes::{ArrayTypeAttributes, DictionaryTypeAttributes, NumberTypeAttributes},
+    };
+
+    #[test]
+    fn test_cbor() {
+        let registry = registry([
+            td(1, "Name", TypeAttributes::String(Default::default())),
+            td(
+                2,
+                "Level",
+                TypeAttributes::Int32(NumberTypeAttributes::builder().max(10).build().unwrap()),
+            ),
+            td(3, "Weight", TypeAttributes::Float32(Default::default())),
+            td(
+                4,
+                "Levels",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2)),
+            ),
+            td(
+                5,
+                "Weights",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(1, 3).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+            td(
+                6,
+                "Offsets",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+        ]);
+
+        for (id, json) in [
+            (4, json!({ "warrior": -3, "mage": 10 })),
+            (5, json!([["sword", 1.25], ["bow", -0.5]])),
+            (6, json!([i32::MIN, 0, 7])),
+        ] {
+            let instance = registry.get(&id).unwrap().clone();
+            let value = Value::parse_for(instance.clone(), json.clone()).unwrap();
+            let bytes = value.to_cbor();
+
+            assert_eq!(
+                Value::parse_cbor(instance, &bytes)
+                    .unwrap()
+                    .to_json()
+                    .unwrap(),
+                json
+            );
+        }
+
+        // The same validation applies as for JSON.
+        let offsets = registry.get(&6).unwrap().clone();
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&json!([1, 11]), &mut bytes).unwrap();
+        let err = Value::parse_cbor(offsets.clone(), &bytes).unwrap_err();
+        assert_eq!(err.path(), [PathSegment::ArrayIndex(1)]);
+        assert_eq!(err.kind().code(), "invalid_int32");
+
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&json!([1, 2]), &mut bytes).unwrap();
+        bytes.push(0xf6);
+        let err = Value::parse_cbor(offsets.clone(), &bytes).unwrap_err();
+        assert_eq!(err.kind().code(), "invalid_cbor");
+
+        let err = Value::parse_cbor(offsets, &bytes[..2]).unwrap_err();
+        assert_eq!(err.kind().code(), "invalid_cbor");
+    }
+}
@@ -0,0 +1,509 @@
+//! Constraints, conditional requirements and invariants between the entries of dictionaries.
+
+use std::{borrow::Borrow, cmp::Ordering};
+
+use crate::{
+    CompiledEntryRules, TypeDefinitionInstance,
+    type_attributes::Scalar,
+    type_attributes_instance::{DictionaryTypeAttributesInstance, TypeAttributesInstance},
+};
+
+use super::{ParseErrorKind, ValueImpl};
+
+/// Check the constraints, requirements and invariants between the entries of a parsed
+/// dictionary, if the value is one.
+pub(super) fn check_constraints<Id, FieldName: Ord + Borrow<str>, S: PartialEq + Borrow<str>>(
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+    value: &ValueImpl<FieldName, S>,
+) -> Result<(), ParseErrorKind> {
+    let (TypeAttributesInstance::Dictionary(a), ValueImpl::Dictionary(entries)) =
+        (&instance.attributes, value)
+    else {
+        return Ok(());
+    };
+
+    let Some(rules) = a.compiled_rules() else {
+        return Ok(());
+    };
+
+    let mut slots = vec![None; rules.keys().len()];
+
+    for (key, value) in entries {
+        if let ValueImpl::Enum(key) = key
+            && let Some(index) = rules.index(key.borrow())
+        {
+            slots[index].get_or_insert(value);
+        }
+    }
+
+    check_rules(rules, &slots)
+}
+
+/// Check compiled entry rules against the entries of a dictionary, given in the order of the keys
+/// of the rules.
+pub(super) fn check_rules<FieldName: PartialEq + Borrow<str>, S: PartialEq + Borrow<str>>(
+    rules: &CompiledEntryRules,
+    entries: &[Option<&ValueImpl<FieldName, S>>],
+) -> Result<(), ParseErrorKind> {
+    rules.check(
+        entries,
+        ValueImpl::compare,
+        ValueImpl::equals,
+        ValueImpl::as_number,
+    )
+}
+
+/// Check the constraints, requirements and invariants between the entries of a JSON dictionary,
+/// as validated.
+///
+/// The entries are specified by key, aliases included. Null entries are missing entries.
+pub(super) fn check_json_constraints<Id, FieldName: Ord + Borrow<str>>(
+    attributes: &DictionaryTypeAttributesInstance<Id, FieldName>,
+    entries: &[(&str, &serde_json::Value)],
+) -> Result<(), ParseErrorKind> {
+    let (Some(rules), TypeAttributesInstance::Enum(keys)) = (
+        attributes.compiled_rules(),
+        &attributes.keys_type_id().attributes,
+    ) else {
+        return Ok(());
+    };
+
+    let mut slots = vec![None; rules.keys().len()];
+
+    for &(key, value) in entries {
+        if let Some(key) = keys.resolve(key)
+            && let Some(index) = rules.index(key.borrow())
+            && !value.is_null()
+        {
+            slots[index].get_or_insert(value);
+        }
+    }
+
+    rules.check(&slots, compare_json, equals_json, serde_json::Value::as_f64)
+}
+
+impl<FieldName: PartialEq, S: PartialEq> ValueImpl<FieldName, S> {
+    /// Compare two values of the same type.
+    ///
+    /// Numbers are ordered, while other values are only compared for equality.
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Int32(a), Self::Int32(b)) => a.partial_cmp(b),
+            (Self::Int64(a), Self::Int64(b)) => a.partial_cmp(b),
+            (Self::Uint32(a), Self::Uint32(b)) => a.partial_cmp(b),
+            (Self::Uint64(a), Self::Uint64(b)) => a.partial_cmp(b),
+            (Self::Float32(a), Self::Float32(b)) => a.partial_cmp(b),
+            (Self::Float64(a), Self::Float64(b)) => a.partial_cmp(b),
+            (a, b) => (a == b).then_some(Ordering::Equal),
+        }
+    }
+
+    /// Check whether the value is the value of a requirement.
+    fn equals(&self, value: &Scalar) -> bool
+    where
+        FieldName: Borrow<str>,
+        S: Borrow<str>,
+    {
+        match (self, value) {
+            (Self::Boolean(a), Scalar::Boolean(b)) => a == b,
+            (Self::Int32(a), Scalar::Integer(b)) => i128::from(*a) == *b,
+            (Self::Int64(a), Scalar::Integer(b)) => i128::from(*a) == *b,
+            (Self::Uint32(a), Scalar::Integer(b)) => i128::from(*a) == *b,
+            (Self::Uint64(a), Scalar::Integer(b)) => i128::from(*a) == *b,
+            (Self::Float32(a), Scalar::Float(b)) => *a == *b as f32,
+            (Self::Float64(a), Scalar::Float(b)) => a == b,
+            (Self::String(a), Scalar::Text(b)) => a.borrow() == b,
+            (Self::Enum(a), Scalar::Text(b)) => a.borrow() == b,
+            _ => false,
+        }
+    }
+
+    /// Get the value of a number, as a `f64`, if the value is one.
+    fn as_number(&self) -> Option<f64> {
+        match *self {
+            Self::Int32(v) => Some(f64::from(v)),
+            Self::Int64(v) => Some(v as f64),
+            Self::Uint32(v) => Some(f64::from(v)),
+            Self::Uint64(v) => Some(v as f64),
+            Self::Float32(v) => Some(f64::from(v)),
+            Self::Float64(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Check whether a JSON value is the value of a requirement.
+fn equals_json(a: &serde_json::Value, b: &Scalar) -> bool {
+    match (a, b) {
+        (serde_json::Value::Bool(a), Scalar::Boolean(b)) => a == b,
+        (serde_json::Value::Number(a), Scalar::Integer(b)) => {
+            a.as_i64()
+                .map(i128::from)
+                .or_else(|| a.as_u64().map(i128::from))
+                == Some(*b)
+        }
+        (serde_json::Value::Number(a), Scalar::Float(b)) => a.as_f64() == Some(*b),
+        (serde_json::Value::String(a), Scalar::Text(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Compare two JSON values of the same type.
+///
+/// Numbers are ordered, exactly for integers, while other values are only compared for equality.
+fn compare_json(a: &serde_json::Value, b: &serde_json::Value) -> Option<Ordering> {
+    match (a, b) {
+        (serde_json::Value::Number(a), serde_json::Value::Number(b)) => {
+            if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+                Some(a.cmp(&b))
+            } else if let (Some(a), Some(b)) = (a.as_u64(), b.as_u64()) {
+                Some(a.cmp(&b))
+            } else {
+                a.as_f64()?.partial_cmp(&b.as_f64()?)
+            }
+        }
+        (a, b) => (a == b).then_some(Ordering::Equal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        Comparison, EntryConstraint, EntryRequirement, Expression, ParseErrorKind,
+        RegistrationError,
+        test_support::{TypeAttributes, TypeDefinition, Value, registry, td},
+        type_attributes::{
+            DictionaryTypeAttributes, EnumTypeAttributes, InstantiationError, NumberTypeAttributes,
+        },
+    };
+
+    fn stat() -> TypeDefinition {
+        td(
+            1,
+            "Stat",
+            TypeAttributes::Enum(
+                EnumTypeAttributes::builder()
+                    .with_value("min_damage")
+                    .with_value("max_damage")
+                    .with_alias("max", "max_damage")
+                    .build()
+                    .unwrap(),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_entry_constraints() {
+        let registry = registry([
+            stat(),
+            td(2, "Damage", TypeAttributes::Uint32(Default::default())),
+            td(
+                3,
+                "Stats",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2).with_constraint(
+                    EntryConstraint::new("min_damage", Comparison::LessOrEqual, "max_damage"),
+                )),
+            ),
+        ]);
+
+        let stats = registry.get(&3).unwrap().clone();
+
+        Value::parse_for(stats.clone(), json!({ "min_damage": 2, "max_damage": 5 })).unwrap();
+        Value::parse_for(stats.clone(), json!({ "min_damage": 5, "max": 5 })).unwrap();
+
+        // Constraints on missing entries are satisfied.
+        Value::parse_for(stats.clone(), json!({ "min_damage": 5 })).unwrap();
+
+        let err =
+            Value::parse_for(stats.clone(), json!({ "min_damage": 6, "max": 5 })).unwrap_err();
+        assert!(matches!(err.kind(), ParseErrorKind::ConstraintViolation(_)));
+        assert_eq!(
+            err.kind().to_string(),
+            "the entries `min_damage` and `max_damage` violate the constraint `min_damage <= max_damage`"
+        );
+
+        let err = Value::parse_str_for(stats.clone(), r#"{"max_damage": 1, "min_damage": 2}"#)
+            .unwrap_err();
+        assert_eq!(err.kind().code(), "constraint_violation");
+
+        assert!(
+            stats
+                .validate(&json!({ "min_damage": 2, "max": 5 }))
+                .is_valid()
+        );
+
+        let report = stats.validate(&json!({ "min_damage": 6, "max": 5 }));
+        assert_eq!(report.errors().len(), 1);
+        assert_eq!(report.errors()[0].kind().code(), "constraint_violation");
+    }
+
+    #[test]
+    fn test_invalid_entry_constraints() {
+        let mut registry = registry([
+            stat(),
+            td(2, "Label", TypeAttributes::String(Default::default())),
+            td(
+                3,
+                "Damage",
+                TypeAttributes::Uint32(NumberTypeAttributes::default()),
+            ),
+        ]);
+
+        let invalid = |keys_type_id, values_type_id, constraint| {
+            let mut registry = registry.clone();
+            let (_, err) = registry
+                .register([td(
+                    4,
+                    "Stats",
+                    TypeAttributes::Dictionary(
+                        DictionaryTypeAttributes::new(keys_type_id, values_type_id)
+                            .with_constraint(constraint),
+                    ),
+                )])
+                .into_result()
+                .unwrap_err()
+                .remove(0);
+
+            match err {
+                RegistrationError::InstantiationError(InstantiationError::InvalidConstraint {
+                    reason,
+                    ..
+                }) => reason,
+                err => panic!("unexpected error: {err}"),
+            }
+        };
+
+        // The keys are not enum values.
+        invalid(
+            2,
+            3,
+            EntryConstraint::new("min_damage", Comparison::Less, "max_damage"),
+        );
+
+        // An entry does not exist.
+        invalid(
+            1,
+            3,
+            EntryConstraint::new("min_damage", Comparison::Less, "crit"),
+        );
+
+        // Strings are not ordered.
+        invalid(
+            1,
+            2,
+            EntryConstraint::new("min_damage", Comparison::Less, "max_damage"),
+        );
+
+        // But they can be compared for equality.
+        registry
+            .register([td(
+                4,
+                "Labels",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2).with_constraint(
+                    EntryConstraint::new("min_damage", Comparison::NotEqual, "max_damage"),
+                )),
+            )])
+            .into_result()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_entry_requirements() {
+        let mut registry = registry([
+            td(
+                1,
+                "Property",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("kind")
+                        .with_value("speed")
+                        .with_value("range")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(2, "Label", TypeAttributes::String(Default::default())),
+            td(
+                3,
+                "Item",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2).with_requirement(
+                    EntryRequirement::new("kind", json!("projectile"), ["speed", "range"]),
+                )),
+            ),
+        ]);
+
+        let item = registry.get(&3).unwrap().clone();
+
+        Value::parse_for(item.clone(), json!({ "kind": "sword" })).unwrap();
+        Value::parse_for(
+            item.clone(),
+            json!({ "kind": "projectile", "speed": "fast", "range": "far" }),
+        )
+        .unwrap();
+
+        let err = Value::parse_for(
+            item.clone(),
+            json!({ "kind": "projectile", "speed": "fast" }),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err.kind(), ParseErrorKind::MissingRequiredEntry { key, .. } if key == "range")
+        );
+        assert_eq!(
+            err.kind().to_string(),
+            "the entry `range` is required when `kind` is \"projectile\""
+        );
+
+        let err = Value::parse_str_for(item.clone(), r#"{"kind": "projectile"}"#).unwrap_err();
+        assert_eq!(err.kind().code(), "missing_required_entry");
+
+        assert!(item.validate(&json!({ "kind": "sword" })).is_valid());
+
+        let report = item.validate(&json!({ "kind": "projectile", "range": "far" }));
+        assert_eq!(report.errors().len(), 1);
+        assert_eq!(report.errors()[0].kind().code(), "missing_required_entry");
+
+        // The value of the condition must be a value of the values type.
+        let (_, err) =
+            registry
+                .register([td(
+                    4,
+                    "Flags",
+                    TypeAttributes::Dictionary(
+                        DictionaryTypeAttributes::new(1, 2).with_requirement(
+                            EntryRequirement::new("kind", json!(true), ["speed"]),
+                        ),
+                    ),
+                )])
+                .into_result()
+                .unwrap_err()
+                .remove(0);
+        assert!(matches!(
+            err,
+            RegistrationError::InstantiationError(InstantiationError::InvalidRequirement { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invariants() {
+        let mut registry = registry([
+            stat(),
+            td(2, "Damage", TypeAttributes::Float32(Default::default())),
+            td(
+                3,
+                "Stats",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(1, 2)
+                        .with_invariant(Expression::new("max_damage - min_damage <= 10").unwrap()),
+                ),
+            ),
+        ]);
+
+        let stats = registry.get(&3).unwrap().clone();
+
+        Value::parse_for(stats.clone(), json!({ "min_damage": 2, "max": 12 })).unwrap();
+        Value::parse_for(stats.clone(), json!({ "max_damage": 100 })).unwrap();
+
+        let err =
+            Value::parse_for(stats.clone(), json!({ "min_damage": 2, "max": 12.5 })).unwrap_err();
+        assert_eq!(
+            err.kind().to_string(),
+            "the entries violate the invariant `max_damage - min_damage <= 10`"
+        );
+
+        let err = Value::parse_str_for(stats.clone(), r#"{"min_damage": 0, "max_damage": 11}"#)
+            .unwrap_err();
+        assert_eq!(err.kind().code(), "invariant_violation");
+
+        assert!(
+            stats
+                .validate(&json!({ "min_damage": 1, "max": 11 }))
+                .is_valid()
+        );
+        assert_eq!(
+            stats
+                .validate(&json!({ "min_damage": 0, "max": 11 }))
+                .errors()[0]
+                .kind()
+                .code(),
+            "invariant_violation"
+        );
+
+        // Invariants only reference the values of the keys type.
+        let (_, err) = registry
+            .register([td(
+                4,
+                "Ratios",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(1, 2)
+                        .with_invariant(Expression::new("max_damage / crit > 1").unwrap()),
+                ),
+            )])
+            .into_result()
+            .unwrap_err()
+            .remove(0);
+        assert_eq!(
+            err,
+            RegistrationError::InstantiationError(InstantiationError::InvalidInvariant {
+                invariant: "max_damage / crit > 1".to_owned(),
+                reason: "`crit` is not a value of the keys type `Stat`".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_compiled_rules() {
+        let registry = registry([
+            stat(),
+            td(2, "Damage", TypeAttributes::Float32(Default::default())),
+            td(
+                3,
+                "Stats",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(1, 2)
+                        .with_constraint(EntryConstraint::new(
+                            "min_damage",
+                            Comparison::LessOrEqual,
+                            "max_damage",
+                        ))
+                        .with_requirement(EntryRequirement::new(
+                            "min_damage",
+                            json!(1.5),
+                            ["max_damage"],
+                        ))
+                        .with_invariant(Expression::new("max_damage <= 10").unwrap()),
+                ),
+            ),
+            td(
+                4,
+                "Damages",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2)),
+            ),
+        ]);
+
+        let stats = registry.get(&3).unwrap().clone();
+        let rules = stats.compiled_rules().unwrap();
+
+        assert_eq!(rules.keys(), ["max_damage", "min_damage"]);
+        assert_eq!(
+            rules.constraints().collect::<Vec<_>>(),
+            [(1, Comparison::LessOrEqual, 0)]
+        );
+        assert_eq!(
+            rules.requirements().collect::<Vec<_>>(),
+            [(1, [0].as_slice())]
+        );
+        assert_eq!(rules.invariants()[0].source(), "max_damage <= 10");
+
+        // Requirement values are compared for the values type, without serializing entries.
+        let err = Value::parse_for(stats.clone(), json!({ "min_damage": 1.5 })).unwrap_err();
+        assert_eq!(err.kind().code(), "missing_required_entry");
+        Value::parse_for(stats.clone(), json!({ "min_damage": 1.25 })).unwrap();
+        assert!(!stats.validate(&json!({ "min_damage": 1.5 })).is_valid());
+
+        assert!(registry.get(&4).unwrap().compiled_rules().is_none());
+        assert!(registry.get(&1).unwrap().compiled_rules().is_none());
+    }
+}
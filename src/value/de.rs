@@ -0,0 +1,368 @@
+//! Deserialization of Rust types from GameSON values.
+
+use std::{borrow::Borrow, fmt::Display, sync::Arc};
+
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+
+use crate::{TypeDefinitionInstance, type_attributes_instance::TypeAttributesInstance};
+
+use super::{Value, ValueImpl};
+
+/// An error that can occur when deserializing a Rust type from a GameSON value.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{0}")]
+pub struct DeserializeError(String);
+
+impl de::Error for DeserializeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+/// A deserializer of Rust types from a GameSON value.
+///
+/// See [`Value::as_deserializer`].
+pub struct ValueDeserializer<'a, Id, FieldName: Ord> {
+    /// The type instance of the value.
+    instance: &'a Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The value.
+    value: &'a ValueImpl<FieldName>,
+}
+
+impl<Id, FieldName: Ord> Value<Id, FieldName> {
+    /// Get a deserializer of Rust types from the value.
+    ///
+    /// As the value is valid for its type, this gives Rust types that were validated against
+    /// the schema, without going through JSON: `T::deserialize(value.as_deserializer())`.
+    ///
+    /// Arrays deserialize as sequences, dictionaries as maps, whatever their wire form, and enums
+    /// as strings or as unit variants. Strings and bytes are borrowed from the value.
+    pub fn as_deserializer(&self) -> ValueDeserializer<'_, Id, FieldName> {
+        ValueDeserializer {
+            instance: &self.instance,
+            value: &self.value,
+        }
+    }
+}
+
+impl<'de, Id, FieldName> de::Deserializer<'de> for ValueDeserializer<'de, Id, FieldName>
+where
+    FieldName: Ord + Borrow<str>,
+{
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match (self.value, &self.instance.attributes) {
+            (ValueImpl::Array(items), TypeAttributesInstance::Array(a)) => {
+                visitor.visit_seq(SeqDeserializer {
+                    instance: a.items_type_id(),
+                    items: items.iter(),
+                })
+            }
+            (ValueImpl::Dictionary(entries), TypeAttributesInstance::Dictionary(a)) => visitor
+                .visit_map(MapDeserializer {
+                    keys_instance: a.keys_type_id(),
+                    values_instance: a.values_type_id(),
+                    entries: entries.iter(),
+                    value: None,
+                }),
+            (ValueImpl::Boolean(b), _) => visitor.visit_bool(*b),
+            (ValueImpl::Int32(n), _) => visitor.visit_i32(*n),
+            (ValueImpl::Int64(n), _) => visitor.visit_i64(*n),
+            (ValueImpl::Uint32(n), _) => visitor.visit_u32(*n),
+            (ValueImpl::Uint64(n), _) => visitor.visit_u64(*n),
+            (ValueImpl::Float32(n), _) => visitor.visit_f32(*n),
+            (ValueImpl::Float64(n), _) => visitor.visit_f64(*n),
+            (ValueImpl::String(s), _) => visitor.visit_borrowed_str(s),
+            (ValueImpl::Enum(name), _) => visitor.visit_borrowed_str(name.borrow()),
+            #[cfg(feature = "uuid")]
+            (ValueImpl::Uuid(uuid), _) => visitor.visit_string(uuid.to_string()),
+            #[cfg(feature = "bytes")]
+            (ValueImpl::Bytes(bytes), _) => visitor.visit_borrowed_bytes(bytes),
+            (ValueImpl::Array(_) | ValueImpl::Dictionary(_), _) => {
+                unreachable!("values always match their type instances")
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value {
+            ValueImpl::Enum(name) => visitor.visit_enum(UnitVariant(name.borrow())),
+            ValueImpl::String(s) => visitor.visit_enum(UnitVariant(s)),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+/// A deserializer of the items of an array.
+struct SeqDeserializer<'a, Id, FieldName: Ord> {
+    /// The type instance of the items.
+    instance: &'a Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The remaining items.
+    items: std::slice::Iter<'a, ValueImpl<FieldName>>,
+}
+
+impl<'de, Id, FieldName: Ord + Borrow<str>> SeqAccess<'de> for SeqDeserializer<'de, Id, FieldName> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        self.items
+            .next()
+            .map(|value| {
+                seed.deserialize(ValueDeserializer {
+                    instance: self.instance,
+                    value,
+                })
+            })
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+/// A deserializer of the entries of a dictionary.
+struct MapDeserializer<'a, Id, FieldName: Ord> {
+    /// The type instance of the keys.
+    keys_instance: &'a Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The type instance of the values.
+    values_instance: &'a Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The remaining entries.
+    entries: std::slice::Iter<'a, (ValueImpl<FieldName>, ValueImpl<FieldName>)>,
+
+    /// The value of the entry whose key was just deserialized.
+    value: Option<&'a ValueImpl<FieldName>>,
+}
+
+impl<'de, Id, FieldName: Ord + Borrow<str>> MapAccess<'de> for MapDeserializer<'de, Id, FieldName> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+
+        self.value = Some(value);
+
+        seed.deserialize(ValueDeserializer {
+            instance: self.keys_instance,
+            value: key,
+        })
+        .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed is always called after next_key_seed");
+
+        seed.deserialize(ValueDeserializer {
+            instance: self.values_instance,
+            value,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// A unit variant of an enum, designated by its name.
+struct UnitVariant<'a>(&'a str);
+
+impl<'de> EnumAccess<'de> for UnitVariant<'de> {
+    type Error = DeserializeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant = seed.deserialize(self.0.into_deserializer())?;
+
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for UnitVariant<'de> {
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        _seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"newtype variant",
+        ))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"struct variant",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::DeserializeError;
+    use crate::{
+        test_support::{TypeAttributes, Value, registry, td},
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, EnumTypeAttributes, NumberTypeAttributes,
+        },
+    };
+
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Color {
+        Red,
+        DarkGreen,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Scores(BTreeMap<Color, Vec<u8>>);
+
+    #[test]
+    fn test_as_deserializer() {
+        let registry = registry([
+            td(
+                1,
+                "MyInt",
+                TypeAttributes::Int32(NumberTypeAttributes::builder().min(0).build().unwrap()),
+            ),
+            td(
+                2,
+                "MyInts",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(
+                3,
+                "MyColor",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("red")
+                        .with_value("dark_green")
+                        .with_alias("rouge", "red")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                4,
+                "MyScores",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(3, 2)),
+            ),
+            td(5, "MyString", TypeAttributes::String(Default::default())),
+        ]);
+
+        let value = Value::parse_for(
+            registry.by_id[&4].clone(),
+            json!({ "rouge": [1, 2], "dark_green": [] }),
+        )
+        .unwrap();
+        assert_eq!(
+            Scores::deserialize(value.as_deserializer()).unwrap(),
+            Scores(BTreeMap::from([
+                (Color::Red, vec![1, 2]),
+                (Color::DarkGreen, vec![])
+            ]))
+        );
+        assert_eq!(
+            BTreeMap::<String, Vec<i64>>::deserialize(value.as_deserializer()).unwrap(),
+            BTreeMap::from([
+                ("red".to_owned(), vec![1, 2]),
+                ("dark_green".to_owned(), vec![])
+            ])
+        );
+
+        let value = Value::parse_for(registry.by_id[&5].clone(), json!("hello")).unwrap();
+        assert_eq!(
+            <&str>::deserialize(value.as_deserializer()).unwrap(),
+            "hello"
+        );
+        assert_eq!(
+            Option::<String>::deserialize(value.as_deserializer()).unwrap(),
+            Some("hello".to_owned())
+        );
+        assert_eq!(
+            Color::deserialize(value.as_deserializer()).unwrap_err(),
+            DeserializeError("unknown variant `hello`, expected `red` or `dark_green`".to_owned())
+        );
+
+        let value = Value::parse_for(registry.by_id[&2].clone(), json!([300])).unwrap();
+        assert_eq!(
+            Vec::<u8>::deserialize(value.as_deserializer())
+                .unwrap_err()
+                .to_string(),
+            "invalid value: integer `300`, expected u8"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! GameSON values referencing their type instance by handle.
+
+use std::{fmt::Display, hash::Hash, sync::Arc};
+
+use crate::{TypeDefinitionRegistry, TypeRef};
+
+use super::{Value, ValueImpl};
+
+/// A GameSON value referencing its type instance by a [`TypeRef`] handle rather than by an `Arc`.
+///
+/// Detached values are cheaper to clone and keep around in bulk, as in editor undo stacks: cloning
+/// them does not touch the reference count of their type instance, and they do not keep type
+/// instances alive after their type definitions are replaced. They are attached back to their
+/// registry to be inspected or serialized.
+#[derive(Debug, Clone)]
+pub struct DetachedValue<Id, FieldName> {
+    /// The handle to the type instance.
+    type_ref: TypeRef<Id>,
+
+    /// The value.
+    value: ValueImpl<FieldName>,
+}
+
+impl<Id, FieldName> DetachedValue<Id, FieldName> {
+    /// Get the handle to the type instance of the value.
+    pub fn type_ref(&self) -> &TypeRef<Id> {
+        &self.type_ref
+    }
+}
+
+impl<Id: Ord + Hash + Clone + Display, FieldName: Ord + Hash + Clone + Display>
+    DetachedValue<Id, FieldName>
+{
+    /// Attach the value to the registry of its type instance.
+    ///
+    /// # Errors
+    ///
+    /// The value is given back if its type instance is not registered in the registry anymore,
+    /// as it might not be valid for the type instance that replaced it.
+    pub fn attach(
+        self,
+        registry: &TypeDefinitionRegistry<Id, FieldName>,
+    ) -> Result<Value<Id, FieldName>, Self> {
+        match self.type_ref.resolve(registry) {
+            Some(instance) => Ok(Value {
+                instance: Arc::clone(instance),
+                value: self.value,
+            }),
+            None => Err(self),
+        }
+    }
+}
+
+impl<Id: Clone, FieldName: Ord> Value<Id, FieldName> {
+    /// Detach the value from its type instance, referencing it by handle instead.
+    pub fn detach(self) -> DetachedValue<Id, FieldName> {
+        DetachedValue {
+            type_ref: self.instance.type_ref(),
+            value: self.value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{TypeAttributes, Value, registry, td};
+    use serde_json::json;
+
+    use crate::type_attributes::{ArrayTypeAttributes, NumberTypeAttributes};
+
+    #[test]
+    fn test_detached_value() {
+        let mut registry = registry([
+            td(1, "Level", TypeAttributes::Uint32(Default::default())),
+            td(
+                2,
+                "Levels",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+        ]);
+
+        let levels = registry.get(&2).unwrap().clone();
+        let value = Value::parse_for(levels.clone(), json!([1, 50])).unwrap();
+        let detached = value.detach();
+
+        assert_eq!(detached.type_ref().id(), &2);
+        assert!(detached.type_ref().refers_to(&levels));
+        assert!(std::ptr::eq(
+            detached.type_ref().resolve(&registry).unwrap().as_ref(),
+            levels.as_ref()
+        ));
+
+        let value = detached.clone().attach(&registry).unwrap();
+        assert_eq!(value.to_json().unwrap(), json!([1, 50]));
+
+        // Once the items type is replaced, the value might not be valid anymore.
+        registry
+            .replace(td(
+                1,
+                "Level",
+                TypeAttributes::Uint32(NumberTypeAttributes::builder().max(10).build().unwrap()),
+            ))
+            .unwrap();
+
+        assert!(detached.type_ref().resolve(&registry).is_none());
+
+        let detached = detached.attach(&registry).unwrap_err();
+        assert_eq!(detached.type_ref().id(), &2);
+    }
+}
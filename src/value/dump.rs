@@ -0,0 +1,378 @@
+//! Writing of GameSON values to an `io::Write`.
+
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    fmt::Display,
+    io::{self, Write},
+};
+
+use serde::{
+    Serialize, Serializer,
+    ser::{Error as _, SerializeMap, SerializeSeq, SerializeTuple},
+};
+
+use crate::{
+    DictionaryWireForm, NonFiniteFloats, SerializeOptions, TypeDefinitionInstance,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::{PathSegment, PathStack, SerializeError, SerializeErrorKind, Value, ValueImpl};
+
+/// The format a value is written in by [`Value::write_to`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValueFormat {
+    /// The text of the value, as formatted by its `Display` implementation.
+    #[default]
+    Text,
+
+    /// Compact JSON, as returned by [`Value::to_json`].
+    Json,
+
+    /// Indented JSON, as returned by [`Value::to_json`].
+    PrettyJson,
+}
+
+/// An error that can occur when writing a value with [`Value::write_to`].
+#[derive(Debug, thiserror::Error)]
+pub enum WriteValueError {
+    /// The value could not be serialized.
+    #[error(transparent)]
+    Serialize(#[from] SerializeError),
+
+    /// The underlying writer failed.
+    #[error("cannot write value: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl<Id: Display, FieldName: Ord + Display + Borrow<str>> Value<Id, FieldName> {
+    /// Write the value to an `io::Write`, in the specified format.
+    ///
+    /// The value is written as it is formatted, so that arbitrarily large values can be written
+    /// without ever holding their text in memory. On error, part of the value may already have
+    /// been written.
+    pub fn write_to(
+        &self,
+        writer: &mut impl Write,
+        format: ValueFormat,
+    ) -> Result<(), WriteValueError> {
+        self.write_to_with_options(writer, format, &SerializeOptions::default())
+    }
+
+    /// Write the value to an `io::Write`, in the specified format, using the specified serialize
+    /// options.
+    ///
+    /// The serialize options only apply to the JSON formats.
+    pub fn write_to_with_options(
+        &self,
+        writer: &mut impl Write,
+        format: ValueFormat,
+        options: &SerializeOptions,
+    ) -> Result<(), WriteValueError> {
+        let state = State {
+            options,
+            path: RefCell::default(),
+            failure: RefCell::default(),
+        };
+        let json = Json {
+            value: &self.value,
+            instance: &self.instance,
+            state: &state,
+        };
+
+        let result = match format {
+            ValueFormat::Text => return Ok(write!(writer, "{self}")?),
+            ValueFormat::Json => serde_json::to_writer(&mut *writer, &json),
+            ValueFormat::PrettyJson => serde_json::to_writer_pretty(&mut *writer, &json),
+        };
+
+        result.map_err(|err| match state.failure.into_inner() {
+            Some(kind) => SerializeError {
+                path: state.path.into_inner().into_path(),
+                kind,
+            }
+            .into(),
+            None => io::Error::from(err).into(),
+        })
+    }
+}
+
+/// The state shared by the serialization of a value and of all its descendants.
+struct State<'a> {
+    /// The serialize options.
+    options: &'a SerializeOptions,
+
+    /// The path of the value being serialized.
+    path: RefCell<PathStack>,
+
+    /// The reason why the serialization failed, if it failed on a value rather than on the
+    /// underlying writer.
+    failure: RefCell<Option<SerializeErrorKind>>,
+}
+
+/// A value, serialized to JSON for its type instance.
+///
+/// The output is identical to that of [`Value::to_json`].
+struct Json<'a, Id, FieldName: Ord> {
+    /// The value.
+    value: &'a ValueImpl<FieldName>,
+
+    /// The type instance of the value.
+    instance: &'a TypeDefinitionInstance<Id, FieldName>,
+
+    /// The shared serialization state.
+    state: &'a State<'a>,
+}
+
+impl<'a, Id, FieldName: Ord> Json<'a, Id, FieldName> {
+    /// Get a descendant value at the specified path segment.
+    fn child(
+        &self,
+        value: &'a ValueImpl<FieldName>,
+        instance: &'a TypeDefinitionInstance<Id, FieldName>,
+        segment: PathSegment,
+    ) -> Child<'a, Self> {
+        Child {
+            json: Json {
+                value,
+                instance,
+                state: self.state,
+            },
+            segment,
+            state: self.state,
+        }
+    }
+}
+
+impl<Id, FieldName: Ord + Borrow<str>> Serialize for Json<'_, Id, FieldName> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self.value, &self.instance.attributes) {
+            (ValueImpl::Array(items), TypeAttributesInstance::Array(a)) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+
+                for (i, item) in items.iter().enumerate() {
+                    seq.serialize_element(&self.child(
+                        item,
+                        a.items_type_id(),
+                        PathSegment::ArrayIndex(i),
+                    ))?;
+                }
+
+                seq.end()
+            }
+            (ValueImpl::Dictionary(entries), TypeAttributesInstance::Dictionary(a)) => {
+                match a.wire_form() {
+                    DictionaryWireForm::Object => {
+                        let mut map = serializer.serialize_map(Some(entries.len()))?;
+
+                        for (key, value) in entries {
+                            let key = key.key_string();
+                            let segment = PathSegment::DictionaryKey(key.clone().into_owned());
+
+                            map.serialize_entry(
+                                &key,
+                                &self.child(value, a.values_type_id(), segment),
+                            )?;
+                        }
+
+                        map.end()
+                    }
+                    DictionaryWireForm::Pairs => {
+                        let mut seq = serializer.serialize_seq(Some(entries.len()))?;
+
+                        for (i, (key, value)) in entries.iter().enumerate() {
+                            seq.serialize_element(&Child {
+                                json: Pair {
+                                    key: self.child(
+                                        key,
+                                        a.keys_type_id(),
+                                        PathSegment::ArrayIndex(0),
+                                    ),
+                                    value: self.child(
+                                        value,
+                                        a.values_type_id(),
+                                        PathSegment::ArrayIndex(1),
+                                    ),
+                                },
+                                segment: PathSegment::ArrayIndex(i),
+                                state: self.state,
+                            })?;
+                        }
+
+                        seq.end()
+                    }
+                }
+            }
+            (ValueImpl::Array(_) | ValueImpl::Dictionary(_), _) => {
+                panic!("inconsistent value and type attributes");
+            }
+            (ValueImpl::Boolean(v), _) => serializer.serialize_bool(*v),
+            (ValueImpl::String(v), _) => serializer.serialize_str(v),
+            (ValueImpl::Enum(v), _) => serializer.serialize_str(v.borrow()),
+            #[cfg(feature = "uuid")]
+            (ValueImpl::Uuid(v), _) => serializer.collect_str(v),
+            #[cfg(feature = "bytes")]
+            (ValueImpl::Bytes(v), TypeAttributesInstance::Bytes(a)) => {
+                serializer.serialize_str(&a.encoding().encode(v))
+            }
+            (number, _) => match number.json_number() {
+                Some(Some(v)) => v.serialize(serializer),
+                _ => {
+                    let v = number.as_f64();
+
+                    match self.state.options.non_finite_floats {
+                        NonFiniteFloats::Error => {
+                            let kind = SerializeErrorKind::NonFiniteFloat(v);
+                            let err = S::Error::custom(&kind);
+                            *self.state.failure.borrow_mut() = Some(kind);
+
+                            Err(err)
+                        }
+                        NonFiniteFloats::Null => serializer.serialize_unit(),
+                        NonFiniteFloats::String => serializer.serialize_str(match v {
+                            v if v.is_nan() => "NaN",
+                            v if v > 0.0 => "Infinity",
+                            _ => "-Infinity",
+                        }),
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// A descendant value, maintaining the path of the value being serialized.
+struct Child<'a, T> {
+    /// The serialized descendant.
+    json: T,
+
+    /// The path segment of the descendant, relative to its parent.
+    segment: PathSegment,
+
+    /// The shared serialization state.
+    state: &'a State<'a>,
+}
+
+impl<T: Serialize> Serialize for Child<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.state.path.borrow_mut().push(self.segment.clone());
+        let result = self.json.serialize(serializer)?;
+
+        // We only must pop if the serialization was successful.
+        self.state.path.borrow_mut().pop();
+
+        Ok(result)
+    }
+}
+
+/// A dictionary entry in the pairs wire form.
+struct Pair<'a, Id, FieldName: Ord> {
+    /// The key.
+    key: Child<'a, Json<'a, Id, FieldName>>,
+
+    /// The value.
+    value: Child<'a, Json<'a, Id, FieldName>>,
+}
+
+impl<Id, FieldName: Ord + Borrow<str>> Serialize for Pair<'_, Id, FieldName> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&self.key)?;
+        tuple.serialize_element(&self.value)?;
+        tuple.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        DictionaryWireForm, NonFiniteFloats, NullPolicy, ParseOptions, PathSegment,
+        SerializeOptions,
+        test_support::{TypeAttributes, Value, registry, td},
+        type_attributes::{ArrayTypeAttributes, DictionaryTypeAttributes, NumberTypeAttributes},
+    };
+
+    use super::{ValueFormat, WriteValueError};
+
+    #[test]
+    fn test_write_to() {
+        let registry = registry([
+            td(1, "Name", TypeAttributes::String(Default::default())),
+            td(
+                2,
+                "Ratio",
+                TypeAttributes::Float32(
+                    NumberTypeAttributes::builder()
+                        .default_value(f32::INFINITY)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                3,
+                "Ratios",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+            td(
+                4,
+                "RatiosByName",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(1, 3).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+        ]);
+
+        let instance = registry.get(&4).unwrap().clone();
+        let value = Value::parse_for_with_options(
+            instance,
+            json!([["mage", [0.1, 2]], ["rogue", [null]]]),
+            &ParseOptions::default().null_policy(NullPolicy::UseDefault),
+        )
+        .unwrap();
+
+        let write = |format, non_finite_floats| {
+            let mut output = Vec::new();
+            value
+                .write_to_with_options(
+                    &mut output,
+                    format,
+                    &SerializeOptions::default().non_finite_floats(non_finite_floats),
+                )
+                .map(|()| String::from_utf8(output).unwrap())
+        };
+
+        let expected = value
+            .to_json_with_options(
+                &SerializeOptions::default().non_finite_floats(NonFiniteFloats::String),
+            )
+            .unwrap();
+        assert_eq!(
+            write(ValueFormat::Json, NonFiniteFloats::String).unwrap(),
+            expected.to_string()
+        );
+        assert_eq!(
+            write(ValueFormat::PrettyJson, NonFiniteFloats::String).unwrap(),
+            serde_json::to_string_pretty(&expected).unwrap()
+        );
+        assert_eq!(
+            write(ValueFormat::Text, NonFiniteFloats::Error).unwrap(),
+            value.to_string()
+        );
+
+        let err = write(ValueFormat::Json, NonFiniteFloats::Error).unwrap_err();
+        let WriteValueError::Serialize(err) = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(
+            err.path(),
+            [
+                PathSegment::ArrayIndex(1),
+                PathSegment::ArrayIndex(1),
+                PathSegment::ArrayIndex(0)
+            ]
+        );
+        assert_eq!(err.to_string(), value.to_json().unwrap_err().to_string());
+    }
+}
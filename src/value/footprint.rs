@@ -0,0 +1,92 @@
+//! Memory footprint of GameSON values.
+
+use super::{Value, ValueImpl};
+
+impl<Id, FieldName: Ord> Value<Id, FieldName> {
+    /// Compute the memory used by the value, in bytes.
+    ///
+    /// This counts the value and all the heap memory it owns, at its allocated capacity, but not
+    /// its type instance, which is shared, nor the memory owned by the names of enum values.
+    pub fn deep_size_bytes(&self) -> usize {
+        size_of::<Self>() + self.value.heap_size_bytes()
+    }
+}
+
+impl<FieldName> ValueImpl<FieldName> {
+    /// Compute the heap memory owned by the value, in bytes.
+    fn heap_size_bytes(&self) -> usize {
+        match self {
+            Self::Array(items) => {
+                items.capacity() * size_of::<Self>()
+                    + items.iter().map(Self::heap_size_bytes).sum::<usize>()
+            }
+            Self::Dictionary(entries) => {
+                entries.capacity() * size_of::<(Self, Self)>()
+                    + entries
+                        .iter()
+                        .map(|(key, value)| key.heap_size_bytes() + value.heap_size_bytes())
+                        .sum::<usize>()
+            }
+            #[cfg(feature = "compact_str")]
+            Self::String(v) if !v.is_heap_allocated() => 0,
+            Self::String(v) => v.capacity(),
+            #[cfg(feature = "bytes")]
+            Self::Bytes(v) => v.capacity(),
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        test_support::{TypeAttributes, Value, registry, td},
+        type_attributes::{ArrayTypeAttributes, DictionaryTypeAttributes},
+    };
+
+    #[test]
+    fn test_deep_size_bytes() {
+        let registry = registry([
+            td(1, "Name", TypeAttributes::String(Default::default())),
+            td(
+                2,
+                "Names",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(
+                3,
+                "Aliases",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2)),
+            ),
+        ]);
+
+        let name = registry.get(&1).unwrap().clone();
+        let names = registry.get(&2).unwrap().clone();
+        let aliases = registry.get(&3).unwrap().clone();
+
+        let empty = Value::parse_for(name.clone(), json!("")).unwrap();
+        assert_eq!(empty.deep_size_bytes(), size_of::<Value>());
+
+        // Short strings are kept inline.
+        #[cfg(feature = "compact_str")]
+        assert_eq!(
+            Value::parse_for(name.clone(), json!("warrior"))
+                .unwrap()
+                .deep_size_bytes(),
+            size_of::<Value>()
+        );
+
+        let long = Value::parse_for(name, json!("a".repeat(1000))).unwrap();
+        assert!(long.deep_size_bytes() >= size_of::<Value>() + 1000);
+
+        // Nested collections count the strings they contain.
+        let short = Value::parse_for(names.clone(), json!(["a"])).unwrap();
+        let long = Value::parse_for(names, json!(["a".repeat(1000)])).unwrap();
+        assert!(long.deep_size_bytes() >= short.deep_size_bytes() + 999);
+
+        let value = Value::parse_for(aliases, json!({ "bob": ["a".repeat(1000)] })).unwrap();
+        assert!(value.deep_size_bytes() > long.deep_size_bytes());
+    }
+}
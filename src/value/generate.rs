@@ -0,0 +1,439 @@
+//! Random generation of GameSON values.
+
+use std::{borrow::Borrow, fmt::Display, sync::Arc};
+
+use rand::{Rng, seq::IndexedRandom};
+
+use crate::{
+    SampleStrategy, TypeDefinitionInstance, type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::{Value, ValueImpl};
+
+/// The maximum number of items generated for an array or a dictionary.
+const MAX_ITEMS: usize = 8;
+
+/// The maximum length of generated bytes.
+const MAX_LENGTH: usize = 16;
+
+/// The maximum number of values generated for a type, until one satisfies the entry rules and the
+/// validator of the type.
+const MAX_ATTEMPTS: usize = 16;
+
+impl<Id, FieldName: Ord + Clone + Display + Borrow<str>> Value<Id, FieldName> {
+    /// Generate a random value for a type instance.
+    ///
+    /// The value satisfies all the constraints of its type: numbers are within their range, and
+    /// enums take one of their values, preferably a non-deprecated one. Strings are alphanumeric,
+    /// and arrays and dictionaries have up to 8 items. Numbers without bounds span their whole
+    /// type. Values that violate the entry rules or the validator of their type are generated
+    /// again, up to 16 times.
+    ///
+    /// Returns `None` if no value satisfies the type, which happens for empty enums, or if no
+    /// generated value satisfied the entry rules or the validator of the type. Arrays and
+    /// dictionaries of such types are generated with fewer items.
+    pub fn generate(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        rng: &mut impl Rng,
+    ) -> Option<Self> {
+        Self::sample(instance, &SampleStrategy::default(), rng)
+    }
+
+    /// Generate a random value for a type instance, following a sample strategy.
+    ///
+    /// The value satisfies all the constraints of its type, as with [`generate`](Self::generate),
+    /// but its strings and enums are chosen as the strategy specifies.
+    pub fn sample(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        strategy: &SampleStrategy,
+        rng: &mut impl Rng,
+    ) -> Option<Self> {
+        let value = ValueImpl::generate(&instance, strategy, rng)?;
+
+        Some(Self { instance, value })
+    }
+}
+
+impl<FieldName: Ord + Clone + Display + Borrow<str>> ValueImpl<FieldName> {
+    /// Generate a random value for a type instance, that satisfies the entry rules and the
+    /// validator of the type.
+    fn generate<Id>(
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        strategy: &SampleStrategy,
+        rng: &mut impl Rng,
+    ) -> Option<Self> {
+        for _ in 0..MAX_ATTEMPTS {
+            let value = Self::generate_unchecked(instance, strategy, rng)?;
+
+            if let Ok(value) = Self::check_for(instance, value) {
+                return Some(value);
+            }
+        }
+
+        None
+    }
+
+    /// Generate a random value for a type instance, without checking the entry rules and the
+    /// validator of the type.
+    fn generate_unchecked<Id>(
+        instance: &TypeDefinitionInstance<Id, FieldName>,
+        strategy: &SampleStrategy,
+        rng: &mut impl Rng,
+    ) -> Option<Self> {
+        Some(match &instance.attributes {
+            TypeAttributesInstance::Array(a) => {
+                let len = rng.random_range(0..=MAX_ITEMS);
+
+                Self::Array(
+                    (0..len)
+                        .map_while(|_| Self::generate(a.items_type_id(), strategy, rng))
+                        .collect(),
+                )
+            }
+            TypeAttributesInstance::Dictionary(a) => {
+                let len = rng.random_range(0..=MAX_ITEMS);
+                let mut items: Vec<(Self, Self)> = Vec::with_capacity(len);
+
+                for _ in 0..len {
+                    let (Some(key), Some(value)) = (
+                        Self::generate(a.keys_type_id(), strategy, rng),
+                        Self::generate(a.values_type_id(), strategy, rng),
+                    ) else {
+                        break;
+                    };
+
+                    // Drawing a key twice yields fewer entries instead of duplicate keys.
+                    if !items.iter().any(|(k, _)| *k == key) {
+                        items.push((key, value));
+                    }
+                }
+
+                Self::Dictionary(items)
+            }
+            TypeAttributesInstance::Boolean(_) => Self::Boolean(rng.random()),
+            TypeAttributesInstance::Int32(a) => Self::Int32(
+                rng.random_range(a.min().unwrap_or(i32::MIN)..=a.max().unwrap_or(i32::MAX)),
+            ),
+            TypeAttributesInstance::Int64(a) => Self::Int64(
+                rng.random_range(a.min().unwrap_or(i64::MIN)..=a.max().unwrap_or(i64::MAX)),
+            ),
+            TypeAttributesInstance::Uint32(a) => Self::Uint32(
+                rng.random_range(a.min().unwrap_or(u32::MIN)..=a.max().unwrap_or(u32::MAX)),
+            ),
+            TypeAttributesInstance::Uint64(a) => Self::Uint64(
+                rng.random_range(a.min().unwrap_or(u64::MIN)..=a.max().unwrap_or(u64::MAX)),
+            ),
+            TypeAttributesInstance::Float32(a) => {
+                let min = a.min().unwrap_or(f32::MIN);
+                let max = a.max().unwrap_or(f32::MAX);
+
+                Self::Float32((random_float(rng, min.into(), max.into()) as f32).clamp(min, max))
+            }
+            TypeAttributesInstance::Float64(a) => Self::Float64(random_float(
+                rng,
+                a.min().unwrap_or(f64::MIN),
+                a.max().unwrap_or(f64::MAX),
+            )),
+            TypeAttributesInstance::String(a) => {
+                let type_name = instance.name.to_string();
+                let text = strategy
+                    .text_kind_for(&type_name)
+                    .generate(strategy.locale, rng);
+
+                // Generated text is normalized like parsed text would be.
+                Self::String(a.normalize(&text).into())
+            }
+            TypeAttributesInstance::Enum(a) => {
+                let type_name = instance.name.to_string();
+                let values = a.values().collect::<Vec<_>>();
+                let weight = |value: &&FieldName| {
+                    strategy.enum_weight_for(
+                        &type_name,
+                        &value.to_string(),
+                        a.is_deprecated::<FieldName>(*value),
+                    )
+                };
+
+                // When all the weights are zero, any value is picked rather than none.
+                let value = match values.choose_weighted(rng, weight) {
+                    Ok(value) => value,
+                    Err(_) => values.choose(rng)?,
+                };
+
+                Self::Enum((*value).clone())
+            }
+            #[cfg(feature = "uuid")]
+            TypeAttributesInstance::Uuid(_) => Self::Uuid(uuid::Uuid::from_u128(rng.random())),
+            #[cfg(feature = "bytes")]
+            TypeAttributesInstance::Bytes(_) => {
+                let len = rng.random_range(0..=MAX_LENGTH);
+
+                Self::Bytes((0..len).map(|_| rng.random()).collect())
+            }
+        })
+    }
+}
+
+/// Generate a random finite float within a range.
+fn random_float(rng: &mut impl Rng, min: f64, max: f64) -> f64 {
+    let t: f64 = rng.random();
+
+    // Interpolating this way does not overflow, even for the widest ranges.
+    (min * (1.0 - t) + max * t).clamp(min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::{
+        Comparison, DictionaryWireForm, EntryConstraint, SampleLocale, SampleStrategy, Validator,
+        test_support::{
+            FieldName, Id, TypeAttributes, TypeDefinitionRegistry, Value, registry, td,
+        },
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, EnumTypeAttributes,
+            NumberTypeAttributes, StringTypeAttributes,
+        },
+    };
+
+    fn equipment_registry() -> TypeDefinitionRegistry {
+        registry([
+            td(
+                1,
+                "Level",
+                TypeAttributes::Int32(
+                    NumberTypeAttributes::builder()
+                        .min(1)
+                        .max(99)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                2,
+                "Slot",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("Head")
+                        .with_value_ext("Hat", None, true)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                3,
+                "Levels",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(2, 1)),
+            ),
+            td(
+                4,
+                "Weight",
+                TypeAttributes::Float32(
+                    NumberTypeAttributes::builder()
+                        .min(0.5)
+                        .max(1.5)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                5,
+                "Weights",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(1, 4).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+            td(
+                6,
+                "Name",
+                TypeAttributes::String(StringTypeAttributes::builder().build()),
+            ),
+            td(
+                7,
+                "Names",
+                TypeAttributes::Array(ArrayTypeAttributes::new(6)),
+            ),
+            td(
+                8,
+                "Empty",
+                TypeAttributes::Enum(EnumTypeAttributes::builder().build().unwrap()),
+            ),
+            td(
+                9,
+                "Empties",
+                TypeAttributes::Array(ArrayTypeAttributes::new(8)),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_generate() {
+        let registry = equipment_registry();
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..100 {
+            for id in [3, 5, 7] {
+                let instance = &registry.by_id[&id];
+                let value = Value::generate(Arc::clone(instance), &mut rng).unwrap();
+                let json = value.to_json().unwrap();
+
+                assert!(
+                    instance.validate(&json).is_valid(),
+                    "{json} is not a valid `{}`",
+                    instance.name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_deprecated() {
+        let registry = equipment_registry();
+        let mut rng = StdRng::seed_from_u64(42);
+
+        // Deprecated enum values are never generated.
+        for _ in 0..100 {
+            let levels = Value::generate(Arc::clone(&registry.by_id[&3]), &mut rng).unwrap();
+            assert!(
+                levels
+                    .to_json()
+                    .unwrap()
+                    .as_object()
+                    .unwrap()
+                    .keys()
+                    .all(|key| key == "Head")
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_empty_enum() {
+        let registry = equipment_registry();
+        let mut rng = StdRng::seed_from_u64(42);
+
+        assert!(Value::generate(Arc::clone(&registry.by_id[&8]), &mut rng).is_none());
+        assert_eq!(
+            Value::generate(Arc::clone(&registry.by_id[&9]), &mut rng)
+                .unwrap()
+                .to_json()
+                .unwrap(),
+            serde_json::json!([])
+        );
+    }
+
+    /// A validator of arrays, that must have at most 4 items.
+    #[derive(Debug)]
+    struct Short;
+
+    impl Validator<Id, FieldName> for Short {
+        fn validate(&self, value: &Value) -> Result<(), String> {
+            match value.to_json().unwrap().as_array().unwrap().len() {
+                0..=4 => Ok(()),
+                len => Err(format!("{len} items is too many")),
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_rules() {
+        let mut registry = registry([
+            td(
+                1,
+                "Stat",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("min_damage")
+                        .with_value("max_damage")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(2, "Damage", TypeAttributes::Uint32(Default::default())),
+            td(
+                3,
+                "Stats",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2).with_constraint(
+                    EntryConstraint::new("min_damage", Comparison::LessOrEqual, "max_damage"),
+                )),
+            ),
+            td(
+                4,
+                "Weapons",
+                TypeAttributes::Array(ArrayTypeAttributes::new(3)),
+            ),
+        ]);
+        registry.set_validator(&4, Some(Arc::new(Short))).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..100 {
+            let instance = &registry.by_id[&4];
+            let json = Value::generate(Arc::clone(instance), &mut rng)
+                .unwrap()
+                .to_json()
+                .unwrap();
+
+            // Parsing checks the entry rules and runs the validators.
+            assert!(
+                Value::parse_for(Arc::clone(instance), json.clone()).is_ok(),
+                "{json} is not a valid `Weapons`"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample() {
+        let registry = registry([
+            td(
+                1,
+                "Rarity",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("Common")
+                        .with_value("Rare")
+                        .with_value("Legendary")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                2,
+                "HeroName",
+                TypeAttributes::String(StringTypeAttributes::builder().build()),
+            ),
+        ]);
+
+        let strategy = SampleStrategy::realistic()
+            .locale(SampleLocale::French)
+            .enum_weight("Rarity", "Common", 9)
+            .enum_weight("Rarity", "Legendary", 0);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut common = 0;
+
+        for _ in 0..100 {
+            let rarity = Value::sample(Arc::clone(&registry.by_id[&1]), &strategy, &mut rng)
+                .unwrap()
+                .to_json()
+                .unwrap();
+
+            assert_ne!(rarity, "Legendary");
+
+            if rarity == "Common" {
+                common += 1;
+            }
+        }
+
+        assert!(common > 75, "{common} common values out of 100");
+
+        let name = Value::sample(Arc::clone(&registry.by_id[&2]), &strategy, &mut rng)
+            .unwrap()
+            .to_json()
+            .unwrap();
+        let name = name.as_str().unwrap();
+        assert_eq!(name.split(' ').count(), 2, "{name} is not a person name");
+    }
+}
@@ -0,0 +1,67 @@
+//! Collection of the locales of localized values.
+
+use std::{borrow::Borrow, sync::Arc};
+
+use crate::{TypeDefinitionInstance, type_attributes_instance::TypeAttributesInstance};
+
+use super::{PathSegment, Value, ValueImpl};
+
+impl<Id: PartialEq, FieldName: Ord + Borrow<str>> Value<Id, FieldName> {
+    /// Collect the keys of all the dictionaries keyed by the specified locale type, along with
+    /// their paths, in depth-first order.
+    pub(crate) fn localized_keys(
+        &self,
+        locale_type_id: &Id,
+    ) -> Vec<(Vec<PathSegment>, Vec<String>)> {
+        let mut found = Vec::new();
+
+        self.value.collect_localized_keys(
+            &self.instance,
+            locale_type_id,
+            &mut Vec::new(),
+            &mut found,
+        );
+
+        found
+    }
+}
+
+impl<FieldName: Ord + Borrow<str>> ValueImpl<FieldName> {
+    /// Collect the keys of all the dictionaries keyed by the specified locale type, along with
+    /// their paths, in the value and its descendants.
+    fn collect_localized_keys<Id: PartialEq>(
+        &self,
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        locale_type_id: &Id,
+        path: &mut Vec<PathSegment>,
+        found: &mut Vec<(Vec<PathSegment>, Vec<String>)>,
+    ) {
+        match (self, &instance.attributes) {
+            (Self::Array(items), TypeAttributesInstance::Array(a)) => {
+                for (i, item) in items.iter().enumerate() {
+                    path.push(PathSegment::ArrayIndex(i));
+                    item.collect_localized_keys(a.items_type_id(), locale_type_id, path, found);
+                    path.pop();
+                }
+            }
+            (Self::Dictionary(entries), TypeAttributesInstance::Dictionary(a)) => {
+                if a.keys_type_id().id == *locale_type_id {
+                    found.push((
+                        path.clone(),
+                        entries
+                            .iter()
+                            .map(|(key, _)| key.key_string().into_owned())
+                            .collect(),
+                    ));
+                }
+
+                for (key, value) in entries {
+                    path.push(PathSegment::DictionaryKey(key.key_string().into_owned()));
+                    value.collect_localized_keys(a.values_type_id(), locale_type_id, path, found);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
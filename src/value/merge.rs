@@ -0,0 +1,101 @@
+//! Merging of GameSON values, for layered documents.
+
+use std::{borrow::Borrow, fmt::Display, sync::Arc};
+
+use crate::{TypeDefinitionInstance, type_attributes_instance::TypeAttributesInstance};
+
+use super::{ParseError, ParseErrorKind, ParseErrorPath, PathSegment, Value, ValueImpl};
+
+impl<Id, FieldName> Value<Id, FieldName>
+where
+    Id: PartialEq + Display,
+    FieldName: Ord + Display + Clone + Borrow<str>,
+{
+    /// Merge an overlay onto a copy of the value.
+    ///
+    /// Dictionaries of the same type are merged entry by entry: entries of the same key are
+    /// merged recursively, and the other entries of the overlay are added. Any other value,
+    /// arrays included, is replaced by the overlay, as is the whole value if the overlay has
+    /// another type. Merged dictionaries are checked again for their types.
+    ///
+    /// `replaced` is called with the path of every value taken from the overlay, relative to the
+    /// value.
+    pub(crate) fn merge(
+        &self,
+        overlay: &Self,
+        replaced: &mut impl FnMut(&[PathSegment]),
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        if self.instance.id != overlay.instance.id {
+            replaced(&[]);
+
+            return Ok(Self {
+                instance: Arc::clone(&overlay.instance),
+                value: overlay.value.clone(),
+            });
+        }
+
+        let mut path = Vec::new();
+
+        match self
+            .value
+            .merge_for(&self.instance, &overlay.value, &mut path, replaced)
+        {
+            Ok(value) => Ok(Self {
+                instance: Arc::clone(&self.instance),
+                value,
+            }),
+            Err(err) => Err(ParseError {
+                instance: Arc::clone(&self.instance),
+                path: ParseErrorPath(path),
+                err,
+                location: None,
+            }),
+        }
+    }
+}
+
+impl<FieldName: Ord + Clone + Borrow<str>> ValueImpl<FieldName> {
+    /// Merge an overlay onto a copy of the value, for the specified type instance.
+    ///
+    /// On error, `path` is the path of the value that caused it.
+    fn merge_for<Id>(
+        &self,
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        overlay: &Self,
+        path: &mut Vec<PathSegment>,
+        replaced: &mut impl FnMut(&[PathSegment]),
+    ) -> Result<Self, ParseErrorKind> {
+        let (
+            Self::Dictionary(entries),
+            Self::Dictionary(overlay_entries),
+            TypeAttributesInstance::Dictionary(a),
+        ) = (self, overlay, &instance.attributes)
+        else {
+            replaced(path);
+
+            return Ok(overlay.clone());
+        };
+
+        let mut entries = entries.clone();
+
+        for (key, value) in overlay_entries {
+            path.push(PathSegment::DictionaryKey(key.key_string().into_owned()));
+
+            match entries.iter_mut().find(|(k, _)| k == key) {
+                Some((_, v)) => {
+                    *v = v
+                        .merge_for(a.values_type_id(), value, path, replaced)
+                        .map_err(|err| ParseErrorKind::InvalidDictionaryValue(Box::new(err)))?;
+                }
+                None => {
+                    replaced(path);
+                    entries.push((key.clone(), value.clone()));
+                }
+            }
+
+            path.pop();
+        }
+
+        Self::check_for(instance, Self::Dictionary(entries))
+    }
+}
@@ -0,0 +1,3108 @@
+//! A GameSON value.
+
+mod array_items;
+mod binary;
+mod borrowed;
+#[cfg(feature = "bson")]
+mod bson;
+#[cfg(feature = "cbor")]
+mod cbor;
+mod constraints;
+mod de;
+mod detached;
+mod dump;
+mod footprint;
+#[cfg(feature = "rand")]
+mod generate;
+mod locales;
+mod merge;
+#[cfg(feature = "rmp")]
+mod msgpack;
+mod number;
+mod persistent;
+mod prefab;
+mod recover;
+mod references;
+#[cfg(feature = "bevy")]
+mod reflect;
+mod report;
+mod seed;
+mod ser;
+mod serialize;
+mod validate;
+mod validator;
+mod variant;
+#[cfg(any(feature = "cbor", feature = "rmp"))]
+mod wire;
+mod writer;
+
+use std::{
+    borrow::{Borrow, Cow},
+    collections::BTreeSet,
+    fmt::{Display, Write},
+    hash::{BuildHasher, Hasher, RandomState},
+    sync::Arc,
+};
+
+pub use array_items::ArrayItems;
+pub use borrowed::ValueRef;
+use constraints::check_constraints;
+pub use de::{DeserializeError, ValueDeserializer};
+pub use detached::DetachedValue;
+pub use dump::{ValueFormat, WriteValueError};
+pub use persistent::{PersistentValue, PersistentValueError};
+pub use prefab::Prefab;
+use recover::Checkpoint;
+pub use references::{Reference, ReferenceFilter};
+pub use report::{Severity, ValidationReport};
+pub use seed::ValueSeed;
+pub use serialize::{SerializeError, SerializeErrorKind};
+pub use validate::Violation;
+pub(crate) use validate::validate;
+pub use validator::Validator;
+use validator::run_validator;
+pub use variant::{ParseVariantSetError, VariantSet, VariantValue};
+pub use writer::{ValueWriter, ValueWriterError};
+
+#[cfg(feature = "bytes")]
+use crate::DecodeBytesError;
+use crate::{
+    Diagnostics, DiagnosticsSink, DictionaryWireForm, EntryConstraint, EntryRequirement,
+    Expression, NullPolicy, ParseOptions, TypeDefinitionInstance,
+    metrics::Measure,
+    type_attributes::{DictionaryTypeAttributes, EnumTypeAttributes, ValidateNumberTypeError},
+    type_attributes_instance::{DictionaryTypeAttributesInstance, TypeAttributesInstance},
+};
+
+/// A GameSON value.
+///
+/// The value is guaranteed to be valid for the type instance it is associated with.
+///
+/// Dictionaries keep their entries in the order they were parsed in. JSON objects given as a
+/// `serde_json::Value` are only ordered as in their source text, and serialized back in that
+/// order, if the `indexmap` feature is enabled: otherwise their entries are sorted by key.
+#[derive(Debug, Clone)]
+pub struct Value<Id, FieldName: Ord> {
+    /// The type instance.
+    instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The value.
+    value: ValueImpl<FieldName>,
+}
+
+impl<Id, FieldName: Ord> Value<Id, FieldName> {
+    /// Get the type instance of the value.
+    pub fn instance(&self) -> &Arc<TypeDefinitionInstance<Id, FieldName>> {
+        &self.instance
+    }
+}
+
+impl<Id, FieldName: Ord> Display for Value<Id, FieldName>
+where
+    Id: Display,
+    FieldName: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.value.fmt_for(&self.instance, f)
+    }
+}
+
+/// An error that can occur when parsing a GameSON value.
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "failed to parse GameSON value `{}` ({}): {path}: {err}{}",
+    .instance.name,
+    instance.id,
+    .location.map(|location| format!(" at {location}")).unwrap_or_default()
+)]
+pub struct ParseError<Id: Display, FieldName: Ord + Display> {
+    /// The name of the type.
+    instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The path of the value that caused the error.
+    path: ParseErrorPath,
+
+    /// The value parse error.
+    err: ParseErrorKind,
+
+    /// The location of the error in the source text, if the value was parsed from text.
+    location: Option<SourceLocation>,
+}
+
+impl<Id: Display, FieldName: Ord + Display> ParseError<Id, FieldName> {
+    /// Get the type instance of the value that failed to parse.
+    pub fn instance(&self) -> &Arc<TypeDefinitionInstance<Id, FieldName>> {
+        &self.instance
+    }
+
+    /// Get the path of the value that caused the error.
+    ///
+    /// An empty path designates the parsed value itself.
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path.0
+    }
+
+    /// Get the kind of the error.
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.err
+    }
+
+    /// Get the location of the error in the source text.
+    ///
+    /// This is only available for values parsed from text.
+    pub fn location(&self) -> Option<SourceLocation> {
+        self.location
+    }
+}
+
+/// A location in a source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The line, starting at 1.
+    line: usize,
+
+    /// The column, starting at 1.
+    column: usize,
+}
+
+impl SourceLocation {
+    /// Get the line, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Get the column, starting at 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {} column {}", self.line, self.column)
+    }
+}
+
+/// A non-fatal finding emitted while parsing a GameSON value.
+#[derive(Debug, Clone)]
+pub struct ParseWarning {
+    /// The path of the value that caused the warning.
+    path: ParseErrorPath,
+
+    /// The kind of warning.
+    kind: ParseWarningKind,
+}
+
+impl ParseWarning {
+    /// Get the path of the value that caused the warning.
+    ///
+    /// An empty path designates the parsed value itself.
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path.0
+    }
+
+    /// Get the kind of warning.
+    pub fn kind(&self) -> &ParseWarningKind {
+        &self.kind
+    }
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+/// The kind of non-fatal finding emitted while parsing a GameSON value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseWarningKind {
+    /// An enum alias was replaced by its canonical value.
+    EnumAliasNormalized {
+        /// The alias that was parsed.
+        alias: String,
+
+        /// The canonical value that was stored instead.
+        value: String,
+    },
+
+    /// An array is close to the maximum number of items.
+    ArrayNearLimit {
+        /// The number of items.
+        length: usize,
+
+        /// The maximum number of items.
+        max: usize,
+    },
+
+    /// A string is close to the maximum length.
+    StringNearLimit {
+        /// The length of the string, in bytes.
+        length: usize,
+
+        /// The maximum length, in bytes.
+        max: usize,
+    },
+}
+
+impl Display for ParseWarningKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EnumAliasNormalized { alias, value } => {
+                write!(f, "enum alias `{alias}` was normalized to `{value}`")
+            }
+            Self::ArrayNearLimit { length, max } => {
+                write!(f, "array has {length} items, close to the maximum of {max}")
+            }
+            Self::StringNearLimit { length, max } => {
+                write!(
+                    f,
+                    "string is {length} bytes long, close to the maximum of {max}"
+                )
+            }
+        }
+    }
+}
+
+/// GameSON value parse error path.
+///
+/// Unlike the path of the value being parsed or serialized, its segments stay in a `Vec` even
+/// with the `smallvec` feature: errors are moved around in results, which inline segments would
+/// make hundreds of bytes large, while the path is only allocated once an error is reported.
+#[derive(Debug, Clone)]
+struct ParseErrorPath(Vec<PathSegment>);
+
+impl Default for ParseErrorPath {
+    fn default() -> Self {
+        Self(Vec::with_capacity(8))
+    }
+}
+
+impl Display for ParseErrorPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for segment in &self.0 {
+            segment.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The segments of the path of the value being parsed or serialized.
+///
+/// With the `smallvec` feature, the segments of short paths are kept inline, which saves an
+/// allocation per parse or serialization.
+#[cfg(feature = "smallvec")]
+type PathSegments = smallvec::SmallVec<[PathSegment; 8]>;
+
+/// The segments of the path of the value being parsed or serialized.
+#[cfg(not(feature = "smallvec"))]
+type PathSegments = Vec<PathSegment>;
+
+/// The path of the value being parsed or serialized.
+#[derive(Debug, Clone)]
+struct PathStack(PathSegments);
+
+impl Default for PathStack {
+    fn default() -> Self {
+        Self(PathSegments::with_capacity(8))
+    }
+}
+
+impl PathStack {
+    /// Push a new segment to the path.
+    fn push(&mut self, segment: PathSegment) {
+        self.0.push(segment);
+    }
+
+    /// Pop the last segment from the path.
+    ///
+    /// If the path is empty, this function panics.
+    fn pop(&mut self) {
+        self.0.pop().expect("pop from empty path");
+    }
+
+    /// Get the number of segments of the path.
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Shorten the path to the specified number of segments.
+    fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
+    /// Copy the path, to report it.
+    fn to_path(&self) -> ParseErrorPath {
+        ParseErrorPath(self.0.to_vec())
+    }
+
+    /// Convert the path, to report it.
+    fn into_path(self) -> ParseErrorPath {
+        ParseErrorPath(self.0.into_iter().collect())
+    }
+}
+
+/// A segment of the path of a value within a GameSON value.
+///
+/// Segments serialize as a bare number or string.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum PathSegment {
+    /// An array index.
+    ArrayIndex(usize),
+
+    /// A dictionary key.
+    DictionaryKey(String),
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ArrayIndex(index) => write!(f, "[{index}]"),
+            Self::DictionaryKey(key) => write!(f, "[{key}]"),
+        }
+    }
+}
+
+impl<Id: Display, FieldName: Ord + Display + Clone + Borrow<str>> Value<Id, FieldName> {
+    /// Parse a GameSON value from a JSON value for a specified type instance.
+    pub fn parse_for(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: serde_json::Value,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        Self::parse_for_with_options(instance, value, &ParseOptions::default())
+    }
+
+    /// Parse a GameSON value from a JSON value for a specified type instance, using the specified
+    /// parse options.
+    pub fn parse_for_with_options(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: serde_json::Value,
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        Self::parse_for_with_diagnostics(instance, value, options, &mut ())
+    }
+
+    /// Parse a GameSON value from a JSON value for a specified type instance, using the specified
+    /// parse options, and return the warnings emitted while parsing.
+    pub fn parse_for_with_warnings(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: serde_json::Value,
+        options: &ParseOptions,
+    ) -> Result<(Self, Vec<ParseWarning>), ParseError<Id, FieldName>> {
+        let mut diagnostics = Diagnostics::default();
+        let value = Self::parse_for_with_diagnostics(instance, value, options, &mut diagnostics)?;
+
+        Ok((value, diagnostics.parse_warnings))
+    }
+
+    /// Parse a GameSON value from a JSON value for a specified type instance, using the specified
+    /// parse options, and report the warnings emitted while parsing to a diagnostics sink.
+    ///
+    /// Warnings are reported even if the parse eventually fails.
+    pub fn parse_for_with_diagnostics(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: serde_json::Value,
+        options: &ParseOptions,
+        sink: &mut impl DiagnosticsSink<Id, FieldName>,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        let measure = Measure::start(options);
+        let mut context = ParseContext::new(options);
+        let result = ValueImpl::parse_for(&mut context, &instance, value);
+
+        if let Some(measure) = measure {
+            measure.parsed(instance.name.borrow(), result.as_ref().map(|_| ()));
+        }
+
+        for warning in context.warnings {
+            sink.parse_warning(warning);
+        }
+
+        match result {
+            Ok(value) => Ok(Self { instance, value }),
+            Err(err) => Err(ParseError {
+                instance,
+                path: context.path.into_path(),
+                err,
+                location: None,
+            }),
+        }
+    }
+
+    /// Build a GameSON value from a Rust value for a specified type instance.
+    ///
+    /// The Rust value is serialized straight into a GameSON value, and validated against the type
+    /// instance as [`Value::parse_for`] would: enum variants without fields map to enum values,
+    /// sequences to arrays, and maps and structs to dictionaries, whatever their wire form. Other
+    /// enum variants map to dictionaries with a single entry, named after the variant. Bytes map to
+    /// bytes and UUIDs, and non-finite floats are rejected. This is the mirror of
+    /// [`Value::as_deserializer`].
+    pub fn from_serialize(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: &impl serde::Serialize,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        Self::from_serialize_with_options(instance, value, &ParseOptions::default())
+    }
+
+    /// Build a GameSON value from a Rust value for a specified type instance, using the specified
+    /// parse options.
+    pub fn from_serialize_with_options(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: &impl serde::Serialize,
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        let measure = Measure::start(options);
+        let mut context = ParseContext::new(options);
+        let result = ser::serialize_for(&mut context, &instance, value);
+
+        if let Some(measure) = measure {
+            measure.parsed(instance.name.borrow(), result.as_ref().map(|_| ()));
+        }
+
+        match result {
+            Ok(value) => Ok(Self { instance, value }),
+            Err(err) => Err(ParseError {
+                instance,
+                path: context.path.into_path(),
+                err,
+                location: None,
+            }),
+        }
+    }
+
+    /// Parse a GameSON value from JSON text for a specified type instance.
+    ///
+    /// The value is parsed straight from the text, without an intermediate JSON value.
+    pub fn parse_str_for(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        s: &str,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        Self::parse_str_for_with_options(instance, s, &ParseOptions::default())
+    }
+
+    /// Parse a GameSON value from JSON text for a specified type instance, using the specified
+    /// parse options.
+    pub fn parse_str_for_with_options(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        s: &str,
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        seed::parse_json(instance, serde_json::Deserializer::from_str(s), options)
+    }
+
+    /// Parse a GameSON value from a JSON reader for a specified type instance.
+    ///
+    /// The value is parsed straight from the reader, without an intermediate JSON value. The
+    /// reader is not buffered: wrap it in a [`std::io::BufReader`] if needed.
+    pub fn parse_reader_for(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        reader: impl std::io::Read,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        Self::parse_reader_for_with_options(instance, reader, &ParseOptions::default())
+    }
+
+    /// Parse a GameSON value from a JSON reader for a specified type instance, using the
+    /// specified parse options.
+    pub fn parse_reader_for_with_options(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        reader: impl std::io::Read,
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        seed::parse_json(
+            instance,
+            serde_json::Deserializer::from_reader(reader),
+            options,
+        )
+    }
+}
+
+/// The state of an ongoing parse.
+struct ParseContext<'a> {
+    /// The parse options.
+    options: &'a ParseOptions,
+
+    /// The path of the value being parsed.
+    path: PathStack,
+
+    /// The current nesting depth.
+    depth: usize,
+
+    /// The number of values parsed so far.
+    nodes: usize,
+
+    /// The error that interrupted a deserialization, if any.
+    ///
+    /// Deserializers only carry their own error type, so the actual error is kept here.
+    error: Option<ParseErrorKind>,
+
+    /// The errors recovered from so far, if parsing in recovery mode.
+    recovered: Option<Vec<(ParseErrorPath, ParseErrorKind)>>,
+
+    /// The warnings emitted so far.
+    warnings: Vec<ParseWarning>,
+}
+
+impl<'a> ParseContext<'a> {
+    /// Create a new parse context.
+    fn new(options: &'a ParseOptions) -> Self {
+        Self {
+            options,
+            path: PathStack::default(),
+            depth: 0,
+            nodes: 0,
+            error: None,
+            recovered: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Account for a new value, enforcing the maximum number of values.
+    fn enter_node(&mut self) -> Result<(), ParseErrorKind> {
+        self.nodes += 1;
+
+        match self.options.limits.max_nodes {
+            Some(max) if self.nodes > max => Err(ParseErrorKind::TooManyNodes(max)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Enter an array or a dictionary, enforcing the maximum nesting depth.
+    ///
+    /// Every successful call must be matched by a call to `leave_collection`.
+    fn enter_collection(&mut self) -> Result<(), ParseErrorKind> {
+        match self.options.limits.max_depth {
+            Some(max) if self.depth >= max => Err(ParseErrorKind::TooDeep(max)),
+            _ => {
+                self.depth += 1;
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Leave an array or a dictionary.
+    fn leave_collection(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Check the length of an array against the limits.
+    fn check_array_length(&self, len: usize) -> Result<(), ParseErrorKind> {
+        match self.options.limits.max_array_length {
+            Some(max) if len > max => Err(ParseErrorKind::ArrayTooLong(max)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Resolve an enum name to its canonical value.
+    ///
+    /// A warning is emitted if the name is an alias.
+    fn resolve_enum<FieldName: Ord + Clone + Borrow<str>>(
+        &mut self,
+        attributes: &EnumTypeAttributes<FieldName>,
+        name: &str,
+    ) -> Result<FieldName, ParseErrorKind> {
+        let value = attributes
+            .resolve(name)
+            .ok_or_else(|| ParseErrorKind::UnknownEnumValue(name.to_owned()))?;
+
+        if value.borrow() != name {
+            self.warn(ParseWarningKind::EnumAliasNormalized {
+                alias: name.to_owned(),
+                value: value.borrow().to_owned(),
+            });
+        }
+
+        Ok(value.clone())
+    }
+
+    /// Check the length of a string against the limits.
+    ///
+    /// A warning is emitted if the length is close to the limit.
+    fn check_string_length(&mut self, s: &str) -> Result<(), ParseErrorKind> {
+        match self.options.limits.max_string_length {
+            Some(max) if s.len() > max => Err(ParseErrorKind::StringTooLong(s.len(), max)),
+            Some(max) if is_near_limit(s.len(), max) => {
+                self.warn(ParseWarningKind::StringNearLimit {
+                    length: s.len(),
+                    max,
+                });
+
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Emit a warning if the length of a parsed array is close to the limit.
+    fn check_array_near_limit(&mut self, len: usize) {
+        if let Some(max) = self.options.limits.max_array_length
+            && is_near_limit(len, max)
+        {
+            self.warn(ParseWarningKind::ArrayNearLimit { length: len, max });
+        }
+    }
+
+    /// Emit a warning for the value being parsed.
+    fn warn(&mut self, kind: ParseWarningKind) {
+        self.warnings.push(ParseWarning {
+            path: self.path.to_path(),
+            kind,
+        });
+    }
+
+    /// Enter a dictionary entry, whose key is left blank in the path.
+    ///
+    /// Most entries are parsed without error nor warning: their keys are only copied into the
+    /// paths that are reported, by [`fill_key`](Self::fill_key), once the entry is done.
+    fn push_key(&mut self) -> KeyMark {
+        let mark = KeyMark {
+            index: self.path.len(),
+            warnings: self.warnings.len(),
+            recovered: self.recovered.as_ref().map_or(0, Vec::len),
+        };
+
+        self.path.push(PathSegment::DictionaryKey(String::new()));
+
+        mark
+    }
+
+    /// Fill in the key of a dictionary entry in the paths reported since it was entered.
+    ///
+    /// If the entry failed, its key is also filled in the path of the context, which is left as
+    /// it was when the error occurred.
+    fn fill_key(&mut self, mark: KeyMark, key: &str) {
+        let segment = || PathSegment::DictionaryKey(key.to_owned());
+
+        if let Some(current) = self.path.0.get_mut(mark.index) {
+            *current = segment();
+        }
+
+        for warning in &mut self.warnings[mark.warnings..] {
+            warning.path.0[mark.index] = segment();
+        }
+
+        if let Some(recovered) = &mut self.recovered {
+            for (path, _) in &mut recovered[mark.recovered..] {
+                path.0[mark.index] = segment();
+            }
+        }
+    }
+}
+
+/// The position of a dictionary entry in the path and in the reports of a parse context.
+#[derive(Debug, Clone, Copy)]
+struct KeyMark {
+    /// The index of the key in the path.
+    index: usize,
+
+    /// The number of warnings emitted before the entry.
+    warnings: usize,
+
+    /// The number of errors recovered from before the entry.
+    recovered: usize,
+}
+
+/// Check whether a non-zero size is within 10% of its limit, without exceeding it.
+fn is_near_limit(value: usize, max: usize) -> bool {
+    value > 0 && value <= max && value >= max - max / 10
+}
+
+/// The owned strings of values and of dictionary keys.
+///
+/// With the `compact_str` feature, short strings are kept inline, which saves an allocation for
+/// each of the short identifiers game data is usually made of.
+#[cfg(feature = "compact_str")]
+type ValueString = compact_str::CompactString;
+
+/// The owned strings of values and of dictionary keys.
+#[cfg(not(feature = "compact_str"))]
+type ValueString = String;
+
+/// Store an owned string in a value.
+#[cfg(feature = "compact_str")]
+fn value_string(v: String) -> ValueString {
+    v.into()
+}
+
+/// Store an owned string in a value.
+#[cfg(not(feature = "compact_str"))]
+fn value_string(v: String) -> ValueString {
+    v
+}
+
+/// An entry of a dictionary value: its key and its value.
+type DictionaryEntry<FieldName, S> = (ValueImpl<FieldName, S>, ValueImpl<FieldName, S>);
+
+/// A GameSON value implementation.
+///
+/// Strings are stored as `S`: owned by default, or borrowed from the parsed text by
+/// [`ValueRef`].
+#[derive(Debug, Clone, PartialEq)]
+enum ValueImpl<FieldName, S = ValueString> {
+    // Items and entries stay in a `Vec` even with the `smallvec` feature: a value cannot hold
+    // inline copies of itself, and boxing them would allocate anyway.
+    /// An array.
+    Array(Vec<ValueImpl<FieldName, S>>),
+
+    /// A dictionary.
+    Dictionary(Vec<DictionaryEntry<FieldName, S>>),
+
+    /// A boolean value.
+    Boolean(bool),
+
+    /// A 32-bit signed integer.
+    Int32(i32),
+
+    /// A 64-bit signed integer.
+    Int64(i64),
+
+    /// An unsigned 32-bit integer.
+    Uint32(u32),
+
+    /// An unsigned 64-bit integer.
+    Uint64(u64),
+
+    /// A 32-bit floating point number.
+    Float32(f32),
+
+    /// A 64-bit floating point number.
+    Float64(f64),
+
+    /// A string.
+    String(S),
+
+    /// An enum.
+    Enum(FieldName),
+
+    /// A UUID.
+    #[cfg(feature = "uuid")]
+    Uuid(uuid::Uuid),
+
+    /// Bytes.
+    #[cfg(feature = "bytes")]
+    Bytes(Vec<u8>),
+}
+
+impl<FieldName, S> Drop for ValueImpl<FieldName, S> {
+    /// Drop the value without recursion, so that deeply nested values can be dropped.
+    ///
+    /// The nested arrays and dictionaries are moved to a heap stack, and only dropped once their
+    /// own items were moved out of them.
+    fn drop(&mut self) {
+        fn is_collection<FieldName, S>(value: &ValueImpl<FieldName, S>) -> bool {
+            matches!(value, ValueImpl::Array(_) | ValueImpl::Dictionary(_))
+        }
+
+        let mut stack = match self {
+            Self::Array(items) if items.iter().any(is_collection) => std::mem::take(items),
+            Self::Dictionary(entries)
+                if entries
+                    .iter()
+                    .any(|(key, value)| is_collection(key) || is_collection(value)) =>
+            {
+                std::mem::take(entries)
+                    .into_iter()
+                    .flat_map(|(key, value)| [key, value])
+                    .collect()
+            }
+            _ => return,
+        };
+
+        while let Some(mut value) = stack.pop() {
+            match &mut value {
+                Self::Array(items) => stack.append(items),
+                Self::Dictionary(entries) => stack.extend(
+                    std::mem::take(entries)
+                        .into_iter()
+                        .flat_map(|(key, value)| [key, value]),
+                ),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<FieldName: Ord + Display, S: Borrow<str>> ValueImpl<FieldName, S> {
+    /// Format the value as a string.
+    ///
+    /// Arrays and dictionaries are formatted with an explicit stack rather than recursively, so
+    /// that deeply nested values can be formatted.
+    fn fmt_for<Id>(
+        &self,
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        /// An array or a dictionary being formatted.
+        enum Frame<'v, 'i, Id, FieldName: Ord, S> {
+            /// An array, with the items left to format.
+            Array {
+                items: std::slice::Iter<'v, ValueImpl<FieldName, S>>,
+                instance: &'i Arc<TypeDefinitionInstance<Id, FieldName>>,
+                first: bool,
+            },
+
+            /// A dictionary, with the entries left to format and the value of the entry whose
+            /// key is being formatted.
+            Dictionary {
+                entries: std::slice::Iter<'v, (ValueImpl<FieldName, S>, ValueImpl<FieldName, S>)>,
+                attributes: &'i DictionaryTypeAttributesInstance<Id, FieldName>,
+                value: Option<&'v ValueImpl<FieldName, S>>,
+                first: bool,
+            },
+        }
+
+        let mut stack = Vec::new();
+        let mut next = Some((self, instance));
+
+        loop {
+            if let Some((value, instance)) = next.take() {
+                match (value, &instance.attributes) {
+                    (Self::Array(items), TypeAttributesInstance::Array(a)) => {
+                        f.write_char('[')?;
+                        stack.push(Frame::Array {
+                            items: items.iter(),
+                            instance: a.items_type_id(),
+                            first: true,
+                        });
+                    }
+                    (Self::Dictionary(entries), TypeAttributesInstance::Dictionary(a)) => {
+                        f.write_char('{')?;
+                        stack.push(Frame::Dictionary {
+                            entries: entries.iter(),
+                            attributes: a,
+                            value: None,
+                            first: true,
+                        });
+                    }
+                    _ => value.fmt_scalar_for(instance, f)?,
+                }
+            }
+
+            match stack.last_mut() {
+                None => return Ok(()),
+                Some(Frame::Array {
+                    items,
+                    instance,
+                    first,
+                }) => match items.next() {
+                    Some(item) => {
+                        if !std::mem::take(first) {
+                            f.write_str(", ")?;
+                        }
+
+                        next = Some((item, *instance));
+                    }
+                    None => {
+                        f.write_char(']')?;
+                        stack.pop();
+                    }
+                },
+                Some(Frame::Dictionary {
+                    entries,
+                    attributes,
+                    value,
+                    first,
+                }) => {
+                    if let Some(value) = value.take() {
+                        f.write_str(": ")?;
+                        next = Some((value, attributes.values_type_id()));
+                    } else if let Some((k, v)) = entries.next() {
+                        if !std::mem::take(first) {
+                            f.write_str(", ")?;
+                        }
+
+                        *value = Some(v);
+                        next = Some((k, attributes.keys_type_id()));
+                    } else {
+                        f.write_char('}')?;
+                        stack.pop();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Format a value that is neither an array nor a dictionary as a string.
+    fn fmt_scalar_for<Id>(
+        &self,
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match (self, &instance.attributes) {
+            (Self::Boolean(v), TypeAttributesInstance::Boolean(_)) => write!(f, "{v}")?,
+            (Self::Int32(v), TypeAttributesInstance::Int32(_)) => write!(f, "{v}")?,
+            (Self::Int64(v), TypeAttributesInstance::Int64(_)) => write!(f, "{v}")?,
+            (Self::Uint32(v), TypeAttributesInstance::Uint32(_)) => write!(f, "{v}")?,
+            (Self::Uint64(v), TypeAttributesInstance::Uint64(_)) => write!(f, "{v}")?,
+            (Self::Float32(v), TypeAttributesInstance::Float32(_)) => write!(f, "{v}")?,
+            (Self::Float64(v), TypeAttributesInstance::Float64(_)) => write!(f, "{v}")?,
+            (Self::String(v), TypeAttributesInstance::String(_)) => {
+                f.write_char('"')?;
+                f.write_str(v.borrow())?;
+                f.write_char('"')?;
+            }
+            (Self::Enum(v), TypeAttributesInstance::Enum(_)) => {
+                write!(f, "{}::{v}", instance.name)?
+            }
+            #[cfg(feature = "uuid")]
+            (Self::Uuid(v), TypeAttributesInstance::Uuid(_)) => write!(f, "\"{v}\"")?,
+            #[cfg(feature = "bytes")]
+            (Self::Bytes(v), TypeAttributesInstance::Bytes(a)) => {
+                write!(f, "\"{}\"", a.encoding().encode(v))?
+            }
+            _ => {
+                panic!("inconsistent value and type attributes");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The kind of error that can occur when parsing a GameSON value.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// The JSON value is not of the expected kind.
+    #[error("expected {expected}, found {found}")]
+    UnexpectedValue {
+        /// The kind of type that was expected.
+        expected: &'static str,
+
+        /// The kind of JSON value that was found.
+        found: &'static str,
+    },
+
+    /// The dictionary key is invalid.
+    #[error("invalid dictionary key: {0}")]
+    InvalidDictionaryKey(#[source] Box<Self>),
+
+    /// The dictionary value is invalid.
+    #[error("invalid dictionary value: {0}")]
+    InvalidDictionaryValue(#[source] Box<Self>),
+
+    /// The dictionary entry is not a `[key, value]` pair.
+    #[error("expected a [key, value] pair, found an array of {0} items")]
+    InvalidDictionaryPair(usize),
+
+    /// The dictionary has several entries with the same key, once parsed.
+    #[error("duplicate dictionary key `{0}`")]
+    DuplicateKey(String),
+
+    /// The number is invalid.
+    #[error("invalid int32: {0}")]
+    InvalidInt32(#[from] ValidateNumberTypeError<i32>),
+
+    /// The number is invalid.
+    #[error("invalid int64: {0}")]
+    InvalidInt64(#[from] ValidateNumberTypeError<i64>),
+
+    /// The number is invalid.
+    #[error("invalid uint32: {0}")]
+    InvalidUint32(#[from] ValidateNumberTypeError<u32>),
+
+    /// The number is invalid.
+    #[error("invalid uint64: {0}")]
+    InvalidUint64(#[from] ValidateNumberTypeError<u64>),
+
+    /// The number is invalid.
+    #[error("invalid float32: {0}")]
+    InvalidFloat32(#[from] ValidateNumberTypeError<f32>),
+
+    /// The number is outside the range of a float32 or has more precision than a float32 holds.
+    #[error("value {0} cannot be represented as a float32")]
+    UnrepresentableFloat32(f64),
+
+    /// The number is invalid.
+    #[error("invalid float64: {0}")]
+    InvalidFloat64(#[from] ValidateNumberTypeError<f64>),
+
+    /// The enum value is unknown.
+    #[error("unknown enum value `{0}`")]
+    UnknownEnumValue(String),
+
+    /// The UUID is invalid.
+    #[cfg(feature = "uuid")]
+    #[error("invalid uuid: {0}")]
+    InvalidUuid(#[from] uuid::Error),
+
+    /// The bytes are invalid.
+    #[cfg(feature = "bytes")]
+    #[error("invalid bytes: {0}")]
+    InvalidBytes(#[from] DecodeBytesError),
+
+    /// The maximum nesting depth was exceeded.
+    #[error("maximum nesting depth of {0} exceeded")]
+    TooDeep(usize),
+
+    /// The array has too many items.
+    #[error("array has more than {0} items")]
+    ArrayTooLong(usize),
+
+    /// The string is too long.
+    #[error("string has {0} bytes, which exceeds the maximum of {1}")]
+    StringTooLong(usize, usize),
+
+    /// The maximum number of values was exceeded.
+    #[error("maximum number of values of {0} exceeded")]
+    TooManyNodes(usize),
+
+    /// The JSON text is invalid.
+    ///
+    /// The message does not contain the location of the error, which is reported separately.
+    #[error("invalid JSON: {0}")]
+    InvalidJson(String),
+
+    /// The binary encoding is invalid.
+    #[error("invalid binary encoding: {0}")]
+    InvalidBinary(String),
+
+    /// The BSON value is not supported.
+    #[cfg(feature = "bson")]
+    #[error("invalid BSON: {0}")]
+    InvalidBson(String),
+
+    /// The CBOR encoding is invalid.
+    #[cfg(feature = "cbor")]
+    #[error("invalid CBOR: {0}")]
+    InvalidCbor(String),
+
+    /// The MessagePack encoding is invalid.
+    #[cfg(feature = "rmp")]
+    #[error("invalid MessagePack: {0}")]
+    InvalidMsgpack(String),
+
+    /// The Rust value could not be serialized.
+    #[error("failed to serialize: {0}")]
+    Unserializable(String),
+
+    /// The entries of a dictionary violate one of its constraints.
+    #[error(
+        "the entries `{left}` and `{right}` violate the constraint `{0}`",
+        left = .0.left(),
+        right = .0.right()
+    )]
+    ConstraintViolation(EntryConstraint),
+
+    /// An entry of a dictionary is missing, while a conditional requirement demands it.
+    #[error(
+        "the entry `{key}` is required when `{when}` is {equals}",
+        when = requirement.when(),
+        equals = requirement.equals()
+    )]
+    MissingRequiredEntry {
+        key: String,
+        requirement: Box<EntryRequirement>,
+    },
+
+    /// The entries of a dictionary violate one of its invariants.
+    #[error("the entries violate the invariant `{0}`")]
+    InvariantViolation(Expression),
+
+    /// The validator of the type rejected the value.
+    #[error("rejected: {0}")]
+    Rejected(String),
+}
+
+/// The keys of the entries of a dictionary being parsed, to detect duplicate keys.
+///
+/// Distinct keys in the source can be the same key once parsed, through enum aliases or string
+/// normalization: they are rejected like keys that are repeated as they are. Keys are not copied:
+/// they are recorded by the hashes of their texts and the positions of their entries, and only
+/// compared with the keys whose hashes are the same. Absent entries are not recorded.
+#[derive(Default)]
+struct Keys {
+    /// The state of the hasher of the key texts.
+    state: RandomState,
+
+    /// The hashes of the keys, with the positions of their entries.
+    hashes: BTreeSet<(u64, usize)>,
+}
+
+impl Keys {
+    /// Check that a key is not the key of one of the entries recorded so far, returning its hash
+    /// to record it.
+    fn check<FieldName: Borrow<str>, S: Borrow<str>>(
+        &self,
+        entries: &[DictionaryEntry<FieldName, S>],
+        key: &ValueImpl<FieldName, S>,
+    ) -> Result<u64, ParseErrorKind> {
+        let mut hasher = HashWriter(self.state.build_hasher());
+        key.write_key_text(&mut hasher)
+            .expect("hashing a key never fails");
+        let hash = hasher.0.finish();
+
+        let mut same_hashes = self.hashes.range((hash, 0)..=(hash, usize::MAX));
+
+        if same_hashes.any(|&(_, i)| entries[i].0.key_text() == key.key_text()) {
+            return Err(ParseErrorKind::DuplicateKey(key.key_text().into_owned()));
+        }
+
+        Ok(hash)
+    }
+
+    /// Record the key of the entry at a position, given its hash.
+    fn record(&mut self, hash: u64, position: usize) {
+        self.hashes.insert((hash, position));
+    }
+}
+
+/// A writer that hashes the text written to it.
+struct HashWriter<H>(H);
+
+impl<H: Hasher> Write for HashWriter<H> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.0.write(s.as_bytes());
+
+        Ok(())
+    }
+}
+
+/// Get the name of the kind of a JSON value, for error reporting.
+fn json_kind(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+/// Split a JSON dictionary entry in the pairs wire form into its key and value.
+fn split_pair(
+    context: &mut ParseContext<'_>,
+    pair: serde_json::Value,
+) -> Result<(serde_json::Value, serde_json::Value), ParseErrorKind> {
+    context.enter_node()?;
+
+    match pair {
+        serde_json::Value::Array(pair) => match <[_; 2]>::try_from(pair) {
+            Ok([key, value]) => Ok((key, value)),
+            Err(pair) => Err(ParseErrorKind::InvalidDictionaryPair(pair.len())),
+        },
+        pair => Err(ParseErrorKind::UnexpectedValue {
+            expected: "[key, value] pair",
+            found: json_kind(&pair),
+        }),
+    }
+}
+
+impl<FieldName: Ord + Clone + Borrow<str>> ValueImpl<FieldName> {
+    /// Parse a GameSON value for a specified type instance, and check its constraints and
+    /// validator.
+    ///
+    /// Arrays and dictionaries are parsed with an explicit stack rather than recursively, so that
+    /// the nesting depth of values is only limited by the parse limits.
+    fn parse_for<Id>(
+        context: &mut ParseContext<'_>,
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: serde_json::Value,
+    ) -> Result<Self, ParseErrorKind> {
+        let mut stack = Vec::new();
+        let mut parsed = Self::parse_node_for(context, instance, value, &mut stack);
+
+        loop {
+            // Each parsed value is handed to the collection being parsed, if any, which then
+            // either parses its next element or is complete.
+            let step = match (parsed, stack.last_mut()) {
+                (Some(result), None) => return result,
+                (parsed, Some(frame)) => frame.resume(context, parsed),
+                (None, None) => unreachable!("a collection is being parsed"),
+            };
+
+            parsed = match step {
+                Ok(Step::Parse(instance, value)) => {
+                    Self::parse_node_for(context, instance, value, &mut stack)
+                }
+                Ok(Step::Complete(value)) => {
+                    let frame = stack.pop().expect("a collection is being parsed");
+
+                    Some(Self::check_for(frame.instance, value))
+                }
+                Err(err) => {
+                    stack.pop();
+
+                    Some(Err(err))
+                }
+            };
+        }
+    }
+
+    /// Check the constraints and validator of a parsed value.
+    fn check_for<Id>(
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: Self,
+    ) -> Result<Self, ParseErrorKind> {
+        check_constraints(instance, &value)?;
+
+        run_validator(instance, value)
+    }
+
+    /// Parse a GameSON value for a specified type instance, unless it is an array or a
+    /// dictionary: those are pushed to the stack, to be parsed element by element.
+    ///
+    /// Returns `None` if a collection was pushed to the stack.
+    fn parse_node_for<'a, Id>(
+        context: &mut ParseContext<'_>,
+        instance: &'a Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: serde_json::Value,
+        stack: &mut Vec<Frame<'a, Id, FieldName>>,
+    ) -> Option<Result<Self, ParseErrorKind>> {
+        let state = match Self::parse_structure_for(context, instance, value) {
+            Ok(Node::Value(value)) => return Some(Self::check_for(instance, value)),
+            Ok(Node::Collection(state)) => state,
+            Err(err) => return Some(Err(err)),
+        };
+
+        stack.push(Frame {
+            instance,
+            checkpoint: context.checkpoint(),
+            state,
+        });
+
+        None
+    }
+
+    /// Parse the key of an entry of a JSON object, for the keys type instance of a dictionary.
+    ///
+    /// Keys stored as they are take the key string itself, without copying it: the key is only
+    /// returned alongside the parsed key if it was not, or if the parse failed.
+    fn parse_key_for<Id>(
+        context: &mut ParseContext<'_>,
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        raw: String,
+    ) -> Result<(Self, Option<String>), (ParseErrorKind, String)> {
+        let key = context.enter_node().and_then(|()| {
+            seed::parse_str(context, &instance.attributes, Cow::Borrowed(&raw), |v| v)
+        });
+
+        // Constraints only apply to dictionaries: only the validator has to be run on keys.
+        // Normalized keys borrow a subslice of the key: only one of the same length is the key.
+        if instance.validator.is_none()
+            && matches!(&key, Ok(ValueImpl::String(Cow::Borrowed(key))) if key.len() == raw.len())
+        {
+            drop(key);
+
+            return Ok((Self::String(value_string(raw)), None));
+        }
+
+        match key.map(ValueImpl::into_owned) {
+            Ok(key) => match Self::check_for(instance, key) {
+                Ok(key) => Ok((key, Some(raw))),
+                Err(err) => Err((err, raw)),
+            },
+            Err(err) => Err((err, raw)),
+        }
+    }
+
+    /// Parse a GameSON value for a specified type instance, or start parsing an array or a
+    /// dictionary.
+    fn parse_structure_for<'a, Id>(
+        context: &mut ParseContext<'_>,
+        instance: &'a Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: serde_json::Value,
+    ) -> Result<Node<'a, Id, FieldName>, ParseErrorKind> {
+        context.enter_node()?;
+
+        let value = match (&instance.attributes, value) {
+            (TypeAttributesInstance::Array(a), serde_json::Value::Array(v)) => {
+                context.check_array_length(v.len())?;
+                context.check_array_near_limit(v.len());
+                context.enter_collection()?;
+
+                // Large arrays of numbers, such as heightmaps or curves, are parsed in bulk.
+                if let Some(items) = Self::parse_number_array(context, a.items_type_id(), &v) {
+                    let items = items?;
+                    context.leave_collection();
+
+                    return Ok(Node::Value(Self::Array(items)));
+                }
+
+                return Ok(Node::Collection(FrameState::Array {
+                    items_instance: a.items_type_id(),
+                    values: v.into_iter().enumerate(),
+                    items: Vec::new(),
+                }));
+            }
+            (TypeAttributesInstance::Dictionary(a), serde_json::Value::Object(v))
+                if a.wire_form() == DictionaryWireForm::Object =>
+            {
+                context.enter_collection()?;
+
+                return Ok(Node::Collection(FrameState::Object {
+                    attributes: a,
+                    items: Vec::with_capacity(v.len()),
+                    keys: Keys::default(),
+                    entries: v.into_iter(),
+                    entry: None,
+                }));
+            }
+            (TypeAttributesInstance::Dictionary(a), serde_json::Value::Array(v))
+                if a.wire_form() == DictionaryWireForm::Pairs =>
+            {
+                context.check_array_length(v.len())?;
+                context.check_array_near_limit(v.len());
+                context.enter_collection()?;
+
+                return Ok(Node::Collection(FrameState::Pairs {
+                    attributes: a,
+                    items: Vec::with_capacity(v.len()),
+                    keys: Keys::default(),
+                    pairs: v.into_iter().enumerate(),
+                    pending: Pending::None,
+                }));
+            }
+            (TypeAttributesInstance::Boolean(_), serde_json::Value::Bool(v)) => Self::Boolean(v),
+            (
+                attributes @ (TypeAttributesInstance::Int32(_)
+                | TypeAttributesInstance::Int64(_)
+                | TypeAttributesInstance::Uint32(_)
+                | TypeAttributesInstance::Uint64(_)
+                | TypeAttributesInstance::Float32(_)
+                | TypeAttributesInstance::Float64(_)),
+                serde_json::Value::Number(v),
+            ) => Self::parse_number(context.options, attributes, &v)?,
+            (TypeAttributesInstance::String(a), serde_json::Value::String(v)) => {
+                context.check_string_length(&v)?;
+
+                // Only copy the string if normalizing changed it.
+                let normalized = match a.normalize(&v) {
+                    Cow::Borrowed(normalized) if normalized.len() == v.len() => None,
+                    normalized => Some(normalized.into_owned()),
+                };
+
+                Self::String(value_string(normalized.unwrap_or(v)))
+            }
+            (TypeAttributesInstance::Enum(a), serde_json::Value::String(v)) => {
+                context.check_string_length(&v)?;
+
+                Self::Enum(context.resolve_enum(a, &v)?)
+            }
+            #[cfg(feature = "uuid")]
+            (TypeAttributesInstance::Uuid(_), serde_json::Value::String(v)) => {
+                Self::Uuid(uuid::Uuid::parse_str(&v)?)
+            }
+            #[cfg(feature = "bytes")]
+            (TypeAttributesInstance::Bytes(a), serde_json::Value::String(v)) => {
+                context.check_string_length(&v)?;
+
+                Self::Bytes(a.encoding().decode(&v)?)
+            }
+            (_, serde_json::Value::Null) => Self::parse_null(context, instance)?,
+            (attributes, value) => {
+                return Err(ParseErrorKind::UnexpectedValue {
+                    expected: attributes.kind(),
+                    found: json_kind(&value),
+                });
+            }
+        };
+
+        Ok(Node::Value(value))
+    }
+}
+
+/// A parsed value, or a collection to parse element by element.
+enum Node<'a, Id, FieldName: Ord> {
+    /// A parsed value, whose constraints and validator are not checked yet.
+    Value(ValueImpl<FieldName>),
+
+    /// An array or a dictionary to parse.
+    Collection(FrameState<'a, Id, FieldName>),
+}
+
+/// What to do after a step of parsing a collection.
+enum Step<'a, Id, FieldName: Ord> {
+    /// Parse an element of the collection.
+    Parse(
+        &'a Arc<TypeDefinitionInstance<Id, FieldName>>,
+        serde_json::Value,
+    ),
+
+    /// The collection is complete, but its constraints and validator are not checked yet.
+    Complete(ValueImpl<FieldName>),
+}
+
+/// An array or a dictionary being parsed.
+struct Frame<'a, Id, FieldName: Ord> {
+    /// The type instance of the collection.
+    instance: &'a Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The snapshot of the parse context before the element being parsed.
+    checkpoint: Checkpoint,
+
+    /// The state of the collection.
+    state: FrameState<'a, Id, FieldName>,
+}
+
+/// The state of an array or a dictionary being parsed.
+enum FrameState<'a, Id, FieldName: Ord> {
+    /// An array.
+    Array {
+        /// The type instance of the items.
+        items_instance: &'a Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+        /// The items left to parse, with their indices.
+        values: std::iter::Enumerate<std::vec::IntoIter<serde_json::Value>>,
+
+        /// The items parsed so far.
+        items: Vec<ValueImpl<FieldName>>,
+    },
+
+    /// A dictionary, as a JSON object.
+    Object {
+        /// The dictionary type attributes.
+        attributes: &'a DictionaryTypeAttributesInstance<Id, FieldName>,
+
+        /// The entries left to parse.
+        entries: serde_json::map::IntoIter,
+
+        /// The entries parsed so far.
+        items: Vec<(ValueImpl<FieldName>, ValueImpl<FieldName>)>,
+
+        /// The keys of the entries parsed so far.
+        keys: Keys,
+
+        /// The entry whose value is being parsed.
+        entry: Option<Entry<FieldName>>,
+    },
+
+    /// A dictionary, as an array of `[key, value]` pairs.
+    Pairs {
+        /// The dictionary type attributes.
+        attributes: &'a DictionaryTypeAttributesInstance<Id, FieldName>,
+
+        /// The pairs left to parse, with their indices.
+        pairs: std::iter::Enumerate<std::vec::IntoIter<serde_json::Value>>,
+
+        /// The entries parsed so far.
+        items: Vec<(ValueImpl<FieldName>, ValueImpl<FieldName>)>,
+
+        /// The keys of the entries parsed so far.
+        keys: Keys,
+
+        /// The entry being parsed.
+        pending: Pending<FieldName>,
+    },
+}
+
+/// The entry of a JSON object whose value is being parsed.
+struct Entry<FieldName> {
+    /// The parsed key.
+    key: ValueImpl<FieldName>,
+
+    /// The key, as it appears in the object, unless it was stored as it is in the parsed key.
+    raw: Option<String>,
+
+    /// The position of the entry in the path and in the reports.
+    mark: KeyMark,
+
+    /// The hash of the key, to record it.
+    hash: u64,
+}
+
+impl<FieldName: Borrow<str>> Entry<FieldName> {
+    /// Get the key, as it appears in the object.
+    fn raw(&self) -> Cow<'_, str> {
+        match &self.raw {
+            Some(raw) => Cow::Borrowed(raw),
+            None => self.key.key_string(),
+        }
+    }
+}
+
+/// The dictionary entry being parsed.
+enum Pending<FieldName> {
+    /// No entry is being parsed.
+    None,
+
+    /// The key is being parsed, and the value is next.
+    Key(serde_json::Value),
+
+    /// The value is being parsed, for the parsed key and its hash.
+    Value(ValueImpl<FieldName>, u64),
+}
+
+impl<'a, Id, FieldName: Ord + Clone + Borrow<str>> Frame<'a, Id, FieldName> {
+    /// Resume parsing the collection, with the result of parsing its last element, if any.
+    ///
+    /// On error, the path of the context is left as it was when the error occurred.
+    fn resume(
+        &mut self,
+        context: &mut ParseContext<'_>,
+        parsed: Option<Result<ValueImpl<FieldName>, ParseErrorKind>>,
+    ) -> Result<Step<'a, Id, FieldName>, ParseErrorKind> {
+        let skip_nulls = context.options.null_policy == NullPolicy::Absent;
+
+        match &mut self.state {
+            FrameState::Array {
+                items_instance,
+                values,
+                items,
+            } => {
+                if let Some(parsed) = parsed {
+                    let item = match parsed {
+                        Ok(item) => item,
+                        Err(err) => context.recover(self.checkpoint, items_instance, err)?,
+                    };
+
+                    // We only must pop if the parse was successful.
+                    context.path.pop();
+                    items.push(item);
+                }
+
+                match values.find(|(_, v)| !(skip_nulls && v.is_null())) {
+                    Some((i, v)) => {
+                        context.path.push(PathSegment::ArrayIndex(i));
+                        self.checkpoint = context.checkpoint();
+
+                        Ok(Step::Parse(items_instance, v))
+                    }
+                    None => {
+                        context.leave_collection();
+
+                        Ok(Step::Complete(ValueImpl::Array(std::mem::take(items))))
+                    }
+                }
+            }
+            FrameState::Object {
+                attributes,
+                entries,
+                items,
+                keys,
+                entry: current,
+            } => {
+                if let Some(entry) = current.take()
+                    && let Some(parsed) = parsed
+                {
+                    let value = match parsed {
+                        Ok(value) => value,
+                        Err(err) => context
+                            .recover(
+                                self.checkpoint,
+                                attributes.values_type_id(),
+                                ParseErrorKind::InvalidDictionaryValue(Box::new(err)),
+                            )
+                            .inspect_err(|_| context.fill_key(entry.mark, &entry.raw()))?,
+                    };
+
+                    // We only must pop if the parse was successful.
+                    context.path.pop();
+                    context.fill_key(entry.mark, &entry.raw());
+                    keys.record(entry.hash, items.len());
+                    items.push((entry.key, value));
+                }
+
+                for (k, v) in entries.by_ref() {
+                    let mark = context.push_key();
+                    self.checkpoint = context.checkpoint();
+
+                    let key = ValueImpl::parse_key_for(context, attributes.keys_type_id(), k)
+                        .map_err(|(err, raw)| {
+                            (ParseErrorKind::InvalidDictionaryKey(Box::new(err)), raw)
+                        })
+                        .and_then(|(key, raw)| match keys.check(items, &key) {
+                            Ok(hash) => Ok((key, raw, hash)),
+                            Err(err) => {
+                                let raw = raw.unwrap_or_else(|| key.key_string().into_owned());
+
+                                Err((err, raw))
+                            }
+                        });
+
+                    match key {
+                        Ok((key, raw, hash)) => {
+                            let entry = Entry {
+                                key,
+                                raw,
+                                mark,
+                                hash,
+                            };
+
+                            if !(skip_nulls && v.is_null()) {
+                                *current = Some(entry);
+
+                                return Ok(Step::Parse(attributes.values_type_id(), v));
+                            }
+
+                            context.path.pop();
+                            context.fill_key(mark, &entry.raw());
+                        }
+                        Err((err, raw)) => {
+                            // Entries with an invalid or duplicate key are dropped when
+                            // recovering.
+                            context
+                                .skip(self.checkpoint, err)
+                                .inspect_err(|_| context.fill_key(mark, &raw))?;
+                            context.path.pop();
+                            context.fill_key(mark, &raw);
+                        }
+                    }
+                }
+
+                context.leave_collection();
+
+                let mut items = std::mem::take(items);
+
+                if context.options.apply_defaults {
+                    ValueImpl::apply_defaults(attributes, &mut items);
+                }
+
+                Ok(Step::Complete(ValueImpl::Dictionary(items)))
+            }
+            FrameState::Pairs {
+                attributes,
+                pairs,
+                items,
+                keys,
+                pending,
+            } => {
+                match (std::mem::replace(pending, Pending::None), parsed) {
+                    (Pending::Key(value), Some(Ok(key))) => {
+                        context.path.pop();
+
+                        match keys.check(items, &key) {
+                            Err(err) => {
+                                // Entries with a duplicate key are dropped when recovering.
+                                context.skip(self.checkpoint, err)?;
+                                context.path.pop();
+                            }
+                            Ok(_) if skip_nulls && value.is_null() => context.path.pop(),
+                            Ok(hash) => {
+                                context.path.push(PathSegment::ArrayIndex(1));
+                                *pending = Pending::Value(key, hash);
+
+                                return Ok(Step::Parse(attributes.values_type_id(), value));
+                            }
+                        }
+                    }
+                    (Pending::Key(_), Some(Err(err))) => {
+                        context.skip(
+                            self.checkpoint,
+                            ParseErrorKind::InvalidDictionaryKey(Box::new(err)),
+                        )?;
+                        context.path.pop();
+                    }
+                    (Pending::Value(key, hash), Some(parsed)) => {
+                        let value = match parsed {
+                            Ok(value) => {
+                                context.path.pop();
+
+                                value
+                            }
+                            Err(err) => context.recover(
+                                self.checkpoint,
+                                attributes.values_type_id(),
+                                ParseErrorKind::InvalidDictionaryValue(Box::new(err)),
+                            )?,
+                        };
+
+                        // We only must pop if the parse was successful.
+                        context.path.pop();
+                        keys.record(hash, items.len());
+                        items.push((key, value));
+                    }
+                    _ => {}
+                }
+
+                for (i, pair) in pairs.by_ref() {
+                    if skip_nulls && pair.is_null() {
+                        continue;
+                    }
+
+                    context.path.push(PathSegment::ArrayIndex(i));
+                    self.checkpoint = context.checkpoint();
+
+                    // Entries that are not pairs or have an invalid key are dropped when
+                    // recovering.
+                    match split_pair(context, pair) {
+                        Ok((key, value)) => {
+                            context.path.push(PathSegment::ArrayIndex(0));
+                            *pending = Pending::Key(value);
+
+                            return Ok(Step::Parse(attributes.keys_type_id(), key));
+                        }
+                        Err(err) => {
+                            context.skip(self.checkpoint, err)?;
+                            context.path.pop();
+                        }
+                    }
+                }
+
+                context.leave_collection();
+
+                let mut items = std::mem::take(items);
+
+                if context.options.apply_defaults {
+                    ValueImpl::apply_defaults(attributes, &mut items);
+                }
+
+                Ok(Step::Complete(ValueImpl::Dictionary(items)))
+            }
+        }
+    }
+}
+
+impl<FieldName: Ord + Clone + Borrow<str>, S: Clone + From<String>> ValueImpl<FieldName, S> {
+    /// Add the missing entries of a dictionary, using the default value of its values type.
+    ///
+    /// Only enum-keyed dictionaries have a known set of keys that can be completed: this function
+    /// does nothing for other dictionaries or if the values type has no default value.
+    fn apply_defaults<Id>(
+        attributes: &DictionaryTypeAttributes<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+        items: &mut Vec<(Self, Self)>,
+    ) {
+        if let TypeAttributesInstance::Enum(e) = &attributes.keys_type_id().attributes
+            && let Some(default) = Self::default_for(attributes.values_type_id())
+        {
+            let present: BTreeSet<&FieldName> = items
+                .iter()
+                .filter_map(|(key, _)| match key {
+                    Self::Enum(name) => e.resolve::<FieldName>(name),
+                    _ => None,
+                })
+                .collect();
+
+            let missing: Vec<_> = e
+                .values()
+                .filter(|name| !present.contains(name))
+                .cloned()
+                .collect();
+
+            for name in missing {
+                items.push((Self::Enum(name), default.clone()));
+            }
+        }
+    }
+
+    /// Parse a JSON null for a specified type instance, according to the null policy.
+    ///
+    /// Nulls that can be dropped are handled by their array or dictionary instead.
+    fn parse_null<Id>(
+        context: &ParseContext<'_>,
+        instance: &TypeDefinitionInstance<Id, FieldName>,
+    ) -> Result<Self, ParseErrorKind> {
+        let default = match context.options.null_policy {
+            NullPolicy::UseDefault => Self::default_for(instance),
+            NullPolicy::Error | NullPolicy::Absent => None,
+        };
+
+        default.ok_or(ParseErrorKind::UnexpectedValue {
+            expected: instance.attributes.kind(),
+            found: "null",
+        })
+    }
+
+    /// Get the declared default value for a specified type instance, if it has one.
+    fn default_for<Id>(instance: &TypeDefinitionInstance<Id, FieldName>) -> Option<Self> {
+        match &instance.attributes {
+            TypeAttributesInstance::Array(_) => None,
+            TypeAttributesInstance::Dictionary(_) => None,
+            TypeAttributesInstance::Boolean(a) => a.default_value().map(Self::Boolean),
+            TypeAttributesInstance::Int32(a) => a.default_value().map(Self::Int32),
+            TypeAttributesInstance::Int64(a) => a.default_value().map(Self::Int64),
+            TypeAttributesInstance::Uint32(a) => a.default_value().map(Self::Uint32),
+            TypeAttributesInstance::Uint64(a) => a.default_value().map(Self::Uint64),
+            TypeAttributesInstance::Float32(a) => a.default_value().map(Self::Float32),
+            TypeAttributesInstance::Float64(a) => a.default_value().map(Self::Float64),
+            TypeAttributesInstance::String(a) => {
+                a.default_value().map(|v| Self::String(v.to_owned().into()))
+            }
+            TypeAttributesInstance::Enum(a) => a.default_value().cloned().map(Self::Enum),
+            #[cfg(feature = "uuid")]
+            TypeAttributesInstance::Uuid(a) => a.default_value().map(Self::Uuid),
+            #[cfg(feature = "bytes")]
+            TypeAttributesInstance::Bytes(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use serde_json::json;
+
+    use crate::{
+        Diagnostics, Float32Conversion, NonFiniteFloats, NullPolicy, NumberConversion,
+        ParseErrorKind, ParseLimits, ParseOptions, ParseWarningKind, PathSegment, SerializeOptions,
+        Severity, ValueSeed,
+        test_support::{
+            Id, TypeAttributes, TypeDefinitionInstance, TypeDefinitionRegistry, Value, registry, td,
+        },
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, DictionaryWireForm, EnumTypeAttributes,
+            NumberTypeAttributes, StringTypeAttributes, ValidateNumberTypeError, WhitespacePolicy,
+        },
+    };
+
+    /// Register the test type definitions and return their instances, by identifier.
+    fn instances() -> Vec<Arc<TypeDefinitionInstance>> {
+        let mut registry = TypeDefinitionRegistry::default();
+
+        let (mut registered, errors) = registry
+            .register([
+                td(
+                    1,
+                    "Armor",
+                    TypeAttributes::Int32(
+                        NumberTypeAttributes::builder()
+                            .min(0)
+                            .max(10)
+                            .default_value(0)
+                            .build()
+                            .unwrap(),
+                    ),
+                ),
+                td(
+                    2,
+                    "Slot",
+                    TypeAttributes::Enum(
+                        EnumTypeAttributes::builder()
+                            .with_value("head")
+                            .with_value("body")
+                            .with_value("feet")
+                            .with_alias("torso", "body")
+                            .build()
+                            .unwrap(),
+                    ),
+                ),
+                td(
+                    3,
+                    "ArmorBySlot",
+                    TypeAttributes::Dictionary(DictionaryTypeAttributes::new(2, 1)),
+                ),
+                td(
+                    4,
+                    "ArmorGrid",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(5)),
+                ),
+                td(
+                    5,
+                    "ArmorRow",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+                ),
+                td(
+                    6,
+                    "Weight",
+                    TypeAttributes::Float32(NumberTypeAttributes::default()),
+                ),
+            ])
+            .into_parts();
+
+        assert!(errors.is_empty());
+        registered.sort_by_key(|td| td.id);
+
+        registered
+    }
+
+    #[test]
+    fn test_parse_scalars() {
+        let instances = instances();
+
+        let value = Value::parse_for(Arc::clone(&instances[0]), json!(3)).unwrap();
+        assert_eq!(value.to_string(), "3");
+
+        let err = Value::parse_for(Arc::clone(&instances[0]), json!(11)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `Armor` (1): : invalid int32: value 11 is greater than the maximum 10"
+        );
+
+        let err = Value::parse_for(Arc::clone(&instances[0]), json!("3")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `Armor` (1): : expected int32, found string"
+        );
+
+        let value = Value::parse_for(Arc::clone(&instances[1]), json!("torso")).unwrap();
+        assert_eq!(value.to_string(), "Slot::body");
+
+        let err = Value::parse_for(Arc::clone(&instances[1]), json!("hands")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `Slot` (2): : unknown enum value `hands`"
+        );
+    }
+
+    #[test]
+    fn test_parse_apply_defaults() {
+        let instances = instances();
+
+        let value = Value::parse_for(Arc::clone(&instances[2]), json!({ "head": 3 })).unwrap();
+        assert_eq!(value.to_string(), "{Slot::head: 3}");
+
+        let options = ParseOptions::default().apply_defaults(true);
+
+        let value = Value::parse_for_with_options(
+            Arc::clone(&instances[2]),
+            json!({ "head": 3, "torso": 5 }),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            value.to_string(),
+            "{Slot::head: 3, Slot::body: 5, Slot::feet: 0}"
+        );
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let instances = instances();
+        let grid = json!([[1, 2], [3, 4, 5]]);
+
+        let parse = |limits: ParseLimits| {
+            Value::parse_for_with_options(
+                Arc::clone(&instances[3]),
+                grid.clone(),
+                &ParseOptions::default().limits(limits),
+            )
+            .map_err(|err| err.to_string())
+        };
+
+        assert!(parse(ParseLimits::default()).is_ok());
+        assert!(parse(ParseLimits::default().max_depth(2)).is_ok());
+        assert_eq!(
+            parse(ParseLimits::default().max_depth(1)).unwrap_err(),
+            "failed to parse GameSON value `ArmorGrid` (4): [0]: maximum nesting depth of 1 exceeded"
+        );
+
+        assert!(parse(ParseLimits::default().max_array_length(3)).is_ok());
+        assert_eq!(
+            parse(ParseLimits::default().max_array_length(2)).unwrap_err(),
+            "failed to parse GameSON value `ArmorGrid` (4): [1]: array has more than 2 items"
+        );
+
+        assert!(parse(ParseLimits::default().max_nodes(8)).is_ok());
+        assert_eq!(
+            parse(ParseLimits::default().max_nodes(7)).unwrap_err(),
+            "failed to parse GameSON value `ArmorGrid` (4): [1][2]: maximum number of values of 7 exceeded"
+        );
+
+        let err = Value::parse_for_with_options(
+            Arc::clone(&instances[2]),
+            json!({ "head": 1, "torso": 2 }),
+            &ParseOptions::default().limits(ParseLimits::default().max_string_length(4)),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `ArmorBySlot` (3): [torso]: invalid dictionary key: string has 5 bytes, which exceeds the maximum of 4"
+        );
+    }
+
+    #[test]
+    fn test_parse_deeply_nested() {
+        const DEPTH: u32 = 200_000;
+
+        // Registering and dropping a chain of types is recursive: both are done on a thread with a
+        // large stack, while values are handled on the test thread.
+        fn with_large_stack<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+            std::thread::Builder::new()
+                .stack_size(1 << 30)
+                .spawn(f)
+                .unwrap()
+                .join()
+                .unwrap()
+        }
+
+        // Arrays of dictionaries of arrays... of integers.
+        let registry = with_large_stack(|| {
+            let mut registry = crate::TypeDefinitionRegistry::<u32, String>::default();
+            registry
+                .register((0..DEPTH + 2).map(|id| crate::TypeDefinition {
+                    id,
+                    name: format!("Type{id}"),
+                    description: None,
+                    attributes: match id {
+                        0 => crate::TypeAttributes::Int32(Default::default()),
+                        1 => crate::TypeAttributes::String(Default::default()),
+                        id if id % 2 == 0 => {
+                            crate::TypeAttributes::Array(ArrayTypeAttributes::new(id - 1))
+                        }
+                        id => crate::TypeAttributes::Dictionary(DictionaryTypeAttributes::new(
+                            1,
+                            if id == 3 { 0 } else { id - 1 },
+                        )),
+                    },
+                }))
+                .into_result()
+                .unwrap();
+
+            registry
+        });
+
+        // JSON values are built again when needed, as cloning and dropping them is recursive.
+        let nested = || {
+            let mut json = json!(7);
+
+            for id in 3..DEPTH + 2 {
+                json = if id % 2 == 0 {
+                    serde_json::Value::Array(vec![json])
+                } else {
+                    serde_json::Value::Object([("k".to_owned(), json)].into_iter().collect())
+                };
+            }
+
+            json
+        };
+
+        let mut text = String::new();
+        (3..DEPTH + 2)
+            .rev()
+            .for_each(|id| text.push_str(if id % 2 == 0 { "[" } else { "{\"k\": " }));
+        text.push('7');
+        (3..DEPTH + 2).for_each(|id| text.push(if id % 2 == 0 { ']' } else { '}' }));
+
+        let instance = registry.get(&(DEPTH + 1)).unwrap().clone();
+
+        // Parsing consumes the JSON value, which is then never dropped as a whole.
+        let json = nested();
+        assert!(instance.validate(&json).is_valid());
+        let value = super::Value::parse_for(Arc::clone(&instance), json).unwrap();
+        assert_eq!(value.to_string(), text);
+
+        let json = value.to_json().unwrap();
+        let parsed = super::Value::parse_for(Arc::clone(&instance), json).unwrap();
+        assert_eq!(parsed.to_string(), text);
+        drop(parsed);
+        drop(value);
+
+        let err = with_large_stack({
+            let instance = Arc::clone(&instance);
+
+            move || {
+                super::Value::parse_for_with_options(
+                    instance,
+                    nested(),
+                    &ParseOptions::default().limits(ParseLimits::default().max_depth(100)),
+                )
+                .unwrap_err()
+            }
+        });
+        assert!(
+            err.to_string()
+                .ends_with("maximum nesting depth of 100 exceeded")
+        );
+        assert_eq!(err.path().len(), 100);
+
+        with_large_stack(move || drop((err, instance, registry)));
+    }
+
+    #[test]
+    fn test_validate() {
+        let instances = instances();
+
+        assert!(instances[2].validate(&json!({ "head": 3 })).is_valid());
+
+        let violations = instances[2]
+            .validate(&json!({ "hands": 3, "head": 11, "torso": "x" }))
+            .errors()
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            violations,
+            vec![
+                "[hands]: invalid dictionary key: unknown enum value `hands`",
+                "[head]: invalid int32: value 11 is greater than the maximum 10",
+                "[torso]: expected int32, found string",
+            ]
+        );
+
+        let violations = instances[3]
+            .validate_with_options(
+                &json!([[1, 2], [3, 4, 5]]),
+                &ParseOptions::default().limits(ParseLimits::default().max_array_length(2)),
+            )
+            .errors()
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(violations, vec!["[1]: array has more than 2 items"]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_validate_many() {
+        let instances = instances();
+
+        let results = instances[0].validate_many((0..100).map(|i| json!(i)).collect::<Vec<_>>());
+        assert_eq!(results.len(), 100);
+        assert!(results[..11].iter().all(crate::ValidationReport::is_valid));
+        assert!(!results[11..].iter().any(crate::ValidationReport::is_valid));
+    }
+
+    #[test]
+    fn test_validation_report() {
+        let instances = instances();
+
+        let report = instances[2].validate(&json!({ "torso": 3 }));
+        assert!(report.is_valid());
+        assert_eq!(report.severity(), Some(Severity::Warning));
+
+        let report = instances[2].validate(&json!({ "torso": 3, "head": [1] }));
+        assert!(!report.is_valid());
+        assert_eq!(report.severity(), Some(Severity::Error));
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            json!({
+                "valid": false,
+                "diagnostics": [
+                    {
+                        "severity": "error",
+                        "code": "unexpected_value",
+                        "path": ["head"],
+                        "message": "expected int32, found array",
+                    },
+                    {
+                        "severity": "warning",
+                        "code": "enum_alias_normalized",
+                        "path": ["torso"],
+                        "message": "enum alias `torso` was normalized to `body`",
+                    },
+                ],
+            })
+        );
+
+        assert_eq!(instances[2].validate(&json!({})).severity(), None);
+    }
+
+    #[test]
+    fn test_parse_str() {
+        let instances = instances();
+        let options = ParseOptions::default().apply_defaults(true);
+
+        let value = Value::parse_str_for_with_options(
+            Arc::clone(&instances[2]),
+            r#"{ "head": 3, "torso": 5 }"#,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            value.to_string(),
+            "{Slot::head: 3, Slot::body: 5, Slot::feet: 0}"
+        );
+
+        let value =
+            Value::parse_reader_for(Arc::clone(&instances[3]), &b"[[1, 2], [3]]"[..]).unwrap();
+        assert_eq!(value.to_string(), "[[1, 2], [3]]");
+
+        let err = Value::parse_str_for(Arc::clone(&instances[3]), "[[1, 2], [3, 11]]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `ArmorGrid` (4): [1][1]: invalid int32: value 11 is greater than the maximum 10 at line 1 column 15"
+        );
+
+        let err =
+            Value::parse_str_for(Arc::clone(&instances[2]), r#"{ "head": "x" }"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `ArmorBySlot` (3): [head]: invalid dictionary value: expected int32, found string at line 1 column 13"
+        );
+
+        let err = Value::parse_str_for(Arc::clone(&instances[3]), "[[1, 2], [3").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `ArmorGrid` (4): [1][1]: invalid JSON: EOF while parsing a list at line 1 column 11"
+        );
+
+        let err = Value::parse_str_for_with_options(
+            Arc::clone(&instances[3]),
+            "[[1, 2], [3, 4, 5]]",
+            &ParseOptions::default().limits(ParseLimits::default().max_array_length(2)),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `ArmorGrid` (4): [1][2]: array has more than 2 items at line 1 column 18"
+        );
+
+        // Repeated keys are rejected, rather than keeping several entries with the same key.
+        let text = r#"{ "head": 1, "head": 2 }"#;
+        let err = Value::parse_str_for(Arc::clone(&instances[2]), text).unwrap_err();
+        assert!(matches!(err.kind(), ParseErrorKind::DuplicateKey(key) if key == "head"));
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `ArmorBySlot` (3): [head]: duplicate dictionary key `head` at line 1 column 19"
+        );
+        assert!(super::ValueRef::parse_str_for(Arc::clone(&instances[2]), text).is_err());
+        assert!(Value::parse_reader_for(Arc::clone(&instances[2]), text.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_value_seed() {
+        use serde::de::DeserializeSeed;
+
+        let instances = instances();
+        let options = ParseOptions::default();
+
+        let mut deserializer = serde_json::Deserializer::from_str("[3, 4]");
+        let value = ValueSeed::new(Arc::clone(&instances[4]), &options)
+            .deserialize(&mut deserializer)
+            .unwrap();
+        assert_eq!(value.to_string(), "[3, 4]");
+
+        let mut deserializer = serde_json::Deserializer::from_str("[3, 40]");
+        let err = ValueSeed::new(Arc::clone(&instances[4]), &options)
+            .deserialize(&mut deserializer)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `ArmorRow` (5): [1]: invalid int32: value 40 is greater than the maximum 10"
+        );
+    }
+
+    #[test]
+    fn test_parse_str_location() {
+        let instances = instances();
+
+        let err = Value::parse_str_for(
+            Arc::clone(&instances[2]),
+            "{\n  \"head\": 3,\n  \"feet\": 12\n}",
+        )
+        .unwrap_err();
+        let location = err.location().unwrap();
+        assert_eq!((location.line(), location.column()), (3, 12));
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `ArmorBySlot` (3): [feet]: invalid dictionary value: invalid int32: value 12 is greater than the maximum 10 at line 3 column 12"
+        );
+
+        let err = Value::parse_for(Arc::clone(&instances[2]), json!({ "feet": 12 })).unwrap_err();
+        assert_eq!(err.location(), None);
+    }
+
+    #[test]
+    fn test_parse_error_introspection() {
+        let instances = instances();
+
+        let err = Value::parse_for(Arc::clone(&instances[3]), json!([[1], [2, "x"]])).unwrap_err();
+        assert_eq!(err.instance().name, "ArmorGrid");
+        assert_eq!(
+            err.path(),
+            &[PathSegment::ArrayIndex(1), PathSegment::ArrayIndex(1)]
+        );
+        assert!(matches!(
+            err.kind(),
+            ParseErrorKind::UnexpectedValue {
+                expected: "int32",
+                found: "string"
+            }
+        ));
+
+        let err = Value::parse_for(Arc::clone(&instances[2]), json!({ "hands": 1 })).unwrap_err();
+        assert_eq!(
+            err.path(),
+            &[PathSegment::DictionaryKey("hands".to_owned())]
+        );
+        assert!(matches!(
+            err.kind(),
+            ParseErrorKind::InvalidDictionaryKey(kind)
+                if matches!(**kind, ParseErrorKind::UnknownEnumValue(ref v) if v == "hands")
+        ));
+    }
+
+    #[test]
+    fn test_parse_recovering() {
+        let instances = instances();
+        let options = ParseOptions::default();
+
+        let (value, errors) = Value::parse_for_recovering(
+            Arc::clone(&instances[3]),
+            json!([[1, "x"], 12, [11]]),
+            &options,
+        );
+        assert_eq!(value.unwrap().to_string(), "[[1, 0], [], [0]]");
+        assert_eq!(
+            errors.iter().map(|err| err.path()).collect::<Vec<_>>(),
+            vec![
+                &[PathSegment::ArrayIndex(0), PathSegment::ArrayIndex(1)][..],
+                &[PathSegment::ArrayIndex(1)][..],
+                &[PathSegment::ArrayIndex(2), PathSegment::ArrayIndex(0)][..],
+            ]
+        );
+
+        let (value, errors) = Value::parse_for_recovering(
+            Arc::clone(&instances[2]),
+            json!({ "feet": 2, "hands": 1, "head": "x" }),
+            &options,
+        );
+        assert_eq!(value.unwrap().to_string(), "{Slot::feet: 2, Slot::head: 0}");
+        assert_eq!(
+            errors.iter().map(|err| err.to_string()).collect::<Vec<_>>(),
+            vec![
+                "failed to parse GameSON value `ArmorBySlot` (3): [hands]: invalid dictionary key: unknown enum value `hands`",
+                "failed to parse GameSON value `ArmorBySlot` (3): [head]: invalid dictionary value: expected int32, found string",
+            ]
+        );
+
+        let (value, errors) = Value::parse_for_recovering(
+            Arc::clone(&instances[3]),
+            json!([[1, 2], [3, 4, 5]]),
+            &ParseOptions::default().limits(ParseLimits::default().max_nodes(4)),
+        );
+        assert!(value.is_none());
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind(), ParseErrorKind::TooManyNodes(4)));
+    }
+
+    #[test]
+    fn test_parse_warnings() {
+        let instances = instances();
+        let options = ParseOptions::default();
+
+        let (value, warnings) = Value::parse_for_with_warnings(
+            Arc::clone(&instances[2]),
+            json!({ "head": 3, "torso": 5 }),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(value.to_string(), "{Slot::head: 3, Slot::body: 5}");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].path(),
+            &[PathSegment::DictionaryKey("torso".to_owned())]
+        );
+        assert_eq!(
+            warnings[0].kind(),
+            &ParseWarningKind::EnumAliasNormalized {
+                alias: "torso".to_owned(),
+                value: "body".to_owned(),
+            }
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "[torso]: enum alias `torso` was normalized to `body`"
+        );
+
+        let (_, warnings) =
+            Value::parse_for_with_warnings(Arc::clone(&instances[1]), json!("head"), &options)
+                .unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_parse_duplicate_keys() {
+        let instances = instances();
+        let json = json!({ "body": 1, "torso": 2 });
+
+        let err = Value::parse_for(Arc::clone(&instances[2]), json.clone()).unwrap_err();
+        assert!(matches!(err.kind(), ParseErrorKind::DuplicateKey(key) if key == "body"));
+        assert_eq!(
+            err.path(),
+            &[PathSegment::DictionaryKey("torso".to_owned())]
+        );
+        assert_eq!(err.kind().code(), "duplicate_key");
+
+        let err = Value::parse_str_for(Arc::clone(&instances[2]), &json.to_string()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `ArmorBySlot` (3): [torso]: duplicate dictionary key `body` at line 1 column 17"
+        );
+
+        let report = instances[2].validate(&json);
+        assert_eq!(
+            report
+                .errors()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["[torso]: duplicate dictionary key `body`"]
+        );
+
+        // The duplicate entries are dropped when recovering.
+        let (value, errors) = Value::parse_for_recovering(
+            Arc::clone(&instances[2]),
+            json!({ "body": 1, "head": 3, "torso": 2 }),
+            &ParseOptions::default(),
+        );
+        assert_eq!(value.unwrap().to_string(), "{Slot::body: 1, Slot::head: 3}");
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_str_whitespace() {
+        let registry = registry([
+            td(
+                1,
+                "Name",
+                TypeAttributes::String(
+                    StringTypeAttributes::builder()
+                        .whitespace(WhitespacePolicy::Trim)
+                        .build(),
+                ),
+            ),
+            td(
+                2,
+                "Title",
+                TypeAttributes::String(
+                    StringTypeAttributes::builder()
+                        .whitespace(WhitespacePolicy::Collapse)
+                        .build(),
+                ),
+            ),
+            td(
+                3,
+                "TitlesByName",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2)),
+            ),
+        ]);
+
+        let parse = |id: Id, s: &str| {
+            let instance = Arc::clone(registry.get(&id).unwrap());
+            let value = Value::parse_str_for(Arc::clone(&instance), s).unwrap();
+
+            // Strings borrowed from the text are trimmed too.
+            let value_ref = super::ValueRef::parse_str_for(instance, s).unwrap();
+            assert_eq!(
+                value_ref.into_owned().to_json().unwrap(),
+                value.to_json().unwrap()
+            );
+
+            value.to_json().unwrap()
+        };
+
+        assert_eq!(parse(1, r#""  x  ""#), json!("x"));
+        assert_eq!(parse(1, r#""  \tx  ""#), json!("x"));
+        assert_eq!(parse(2, r#"" Iron Sword ""#), json!("Iron Sword"));
+        assert_eq!(parse(2, r#"" Iron  Sword ""#), json!("Iron Sword"));
+        assert_eq!(
+            parse(3, r#"{"sword ": " Iron Sword ", " bow": "Long  bow"}"#),
+            json!({ "sword": "Iron Sword", "bow": "Long bow" })
+        );
+
+        // Keys of JSON values are normalized too.
+        let instance = Arc::clone(registry.get(&3).unwrap());
+        let value =
+            Value::parse_for(Arc::clone(&instance), json!({ "sword ": "Iron Sword" })).unwrap();
+        assert_eq!(value.to_json().unwrap(), json!({ "sword": "Iron Sword" }));
+
+        // Keys that are the same once normalized are duplicates.
+        let json = json!({ "sword ": "Iron Sword", " sword": "Long Sword" });
+        let err = Value::parse_for(Arc::clone(&instance), json.clone()).unwrap_err();
+        assert!(matches!(err.kind(), ParseErrorKind::DuplicateKey(key) if key == "sword"));
+        let err = Value::parse_str_for(Arc::clone(&instance), &json.to_string()).unwrap_err();
+        assert!(matches!(err.kind(), ParseErrorKind::DuplicateKey(key) if key == "sword"));
+        assert_eq!(instance.validate(&json).errors().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_key_paths() {
+        let registry = registry([
+            td(
+                1,
+                "Armor",
+                TypeAttributes::Int32(
+                    NumberTypeAttributes::builder()
+                        .max(10)
+                        .default_value(0)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                2,
+                "Slot",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("head")
+                        .with_value("body")
+                        .with_value("feet")
+                        .with_alias("torso", "body")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                3,
+                "ArmorBySlot",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(2, 1)),
+            ),
+            td(
+                4,
+                "Name",
+                TypeAttributes::String(StringTypeAttributes::default()),
+            ),
+            td(
+                5,
+                "ArmorByName",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(4, 3)),
+            ),
+        ]);
+
+        let instance = Arc::clone(registry.get(&5).unwrap());
+        let path = |keys: &[&str]| {
+            keys.iter()
+                .map(|key| PathSegment::DictionaryKey((*key).to_owned()))
+                .collect::<Vec<_>>()
+        };
+
+        let err = Value::parse_for(
+            Arc::clone(&instance),
+            json!({ "bob": { "head": 3 }, "alice": { "torso": 11 } }),
+        )
+        .unwrap_err();
+        assert_eq!(err.path(), path(&["alice", "torso"]));
+
+        let err = Value::parse_str_for(
+            Arc::clone(&instance),
+            r#"{ "bob": { "head": 3 }, "alice": { "hands": 1 } }"#,
+        )
+        .unwrap_err();
+        assert_eq!(err.path(), path(&["alice", "hands"]));
+
+        let (_, warnings) = Value::parse_for_with_warnings(
+            Arc::clone(&instance),
+            json!({ "bob": { "torso": 1 } }),
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(warnings[0].path(), path(&["bob", "torso"]));
+
+        let (value, errors) = Value::parse_for_recovering(
+            instance,
+            json!({ "alice": { "feet": 1 }, "bob": { "hands": 1, "torso": 11 } }),
+            &ParseOptions::default(),
+        );
+        assert_eq!(
+            value.unwrap().to_string(),
+            "{\"alice\": {Slot::feet: 1}, \"bob\": {Slot::body: 0}}"
+        );
+        assert_eq!(
+            errors.iter().map(|err| err.path()).collect::<Vec<_>>(),
+            [path(&["bob", "hands"]), path(&["bob", "torso"])]
+        );
+    }
+
+    #[test]
+    fn test_parse_number_conversion() {
+        let instances = instances();
+
+        let value = Value::parse_for(Arc::clone(&instances[0]), json!(2.0)).unwrap();
+        assert_eq!(value.to_string(), "2");
+
+        let err = Value::parse_for(Arc::clone(&instances[0]), json!(1.5)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `Armor` (1): : invalid int32: value 1.5 cannot be represented exactly"
+        );
+
+        let value = Value::parse_for(Arc::clone(&instances[5]), json!(16777216)).unwrap();
+        assert_eq!(value.to_string(), "16777216");
+
+        let err = Value::parse_for(Arc::clone(&instances[5]), json!(16777217)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `Weight` (6): : invalid float32: value 16777217 cannot be represented exactly"
+        );
+
+        let options = ParseOptions::default().number_conversion(NumberConversion::Nearest);
+
+        let value =
+            Value::parse_for_with_options(Arc::clone(&instances[0]), json!(1.5), &options).unwrap();
+        assert_eq!(value.to_string(), "2");
+
+        let value =
+            Value::parse_for_with_options(Arc::clone(&instances[5]), json!(16777217), &options)
+                .unwrap();
+        assert_eq!(value.to_string(), "16777216");
+
+        let err = Value::parse_for_with_options(Arc::clone(&instances[0]), json!(10.6), &options)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to parse GameSON value `Armor` (1): : invalid int32: value 11 is greater than the maximum 10"
+        );
+
+        let err = Value::parse_str_for(Arc::clone(&instances[0]), "4294967296.0").unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ParseErrorKind::InvalidInt32(ValidateNumberTypeError::InvalidValue)
+        ));
+    }
+
+    #[test]
+    fn test_parse_float32_conversion() {
+        let instances = instances();
+
+        let parse = |value, conversion| {
+            Value::parse_for_with_options(
+                Arc::clone(&instances[5]),
+                value,
+                &ParseOptions::default().float32_conversion(conversion),
+            )
+            .map(|value| value.to_string())
+            .map_err(|err| err.to_string())
+        };
+
+        assert_eq!(parse(json!(0.1), Float32Conversion::Exact).unwrap(), "0.1");
+        assert_eq!(
+            parse(json!(0.123456789), Float32Conversion::Exact).unwrap_err(),
+            "failed to parse GameSON value `Weight` (6): : value 0.123456789 cannot be represented as a float32"
+        );
+        assert!(parse(json!(1e39), Float32Conversion::Exact).is_err());
+
+        assert_eq!(
+            parse(json!(0.123456789), Float32Conversion::Round).unwrap(),
+            "0.12345679"
+        );
+        assert!(parse(json!(1e39), Float32Conversion::Round).is_err());
+
+        assert_eq!(
+            parse(json!(-1e39), Float32Conversion::Clamp).unwrap(),
+            f32::MIN.to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let instances = instances();
+        let options = ParseOptions::default().limits(
+            ParseLimits::default()
+                .max_array_length(10)
+                .max_string_length(5),
+        );
+
+        let mut diagnostics = Diagnostics::default();
+        let value = Value::parse_for_with_diagnostics(
+            Arc::clone(&instances[4]),
+            json!([1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            &options,
+            &mut diagnostics,
+        )
+        .unwrap();
+        assert_eq!(value.to_string(), "[1, 2, 3, 4, 5, 6, 7, 8, 9]");
+        assert_eq!(
+            diagnostics
+                .parse_warnings()
+                .iter()
+                .map(|warning| warning.to_string())
+                .collect::<Vec<_>>(),
+            vec![": array has 9 items, close to the maximum of 10"]
+        );
+
+        let mut diagnostics = Diagnostics::default();
+        Value::parse_for_with_diagnostics(
+            Arc::clone(&instances[2]),
+            json!({ "torso": 1, "zzz": 2 }),
+            &options,
+            &mut diagnostics,
+        )
+        .unwrap_err();
+        assert_eq!(
+            diagnostics
+                .parse_warnings()
+                .iter()
+                .map(|warning| warning.kind().code())
+                .collect::<Vec<_>>(),
+            vec!["string_near_limit", "enum_alias_normalized"]
+        );
+
+        let mut diagnostics = Diagnostics::default();
+        Value::parse_for_with_diagnostics(
+            Arc::clone(&instances[4]),
+            json!([1, 2]),
+            &options,
+            &mut diagnostics,
+        )
+        .unwrap();
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_revalidate() {
+        let instances = instances();
+
+        let value = Value::parse_for(Arc::clone(&instances[3]), json!([[1, 2], [3]])).unwrap();
+        assert!(value.revalidate(&instances[3]).is_valid());
+        assert_eq!(
+            value
+                .revalidate(&instances[4])
+                .errors()
+                .iter()
+                .map(|violation| violation.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "[0]: expected int32, found array",
+                "[1]: expected int32, found array"
+            ]
+        );
+
+        let value = Value::parse_for(Arc::clone(&instances[0]), json!(3)).unwrap();
+        assert!(value.revalidate(&instances[5]).is_valid());
+        assert_eq!(
+            value.revalidate(&instances[1]).errors()[0].to_string(),
+            ": expected enum, found number"
+        );
+
+        let value = Value::parse_for(Arc::clone(&instances[5]), json!(0.1)).unwrap();
+        assert!(value.revalidate(&instances[5]).is_valid());
+        assert!(!value.revalidate(&instances[0]).is_valid());
+
+        let value = Value::parse_for(Arc::clone(&instances[2]), json!({ "head": 3 })).unwrap();
+        let report = value.revalidate_with_options(
+            &instances[2],
+            &ParseOptions::default().limits(ParseLimits::default().max_string_length(4)),
+        );
+        assert_eq!(report.errors().len(), 0);
+        assert_eq!(
+            report.warnings()[0].to_string(),
+            "[head]: string is 4 bytes long, close to the maximum of 4"
+        );
+        assert_eq!(
+            value.revalidate(&instances[3]).errors()[0].to_string(),
+            ": expected array, found dictionary"
+        );
+    }
+
+    #[test]
+    fn test_null_policy() {
+        let instances = instances();
+        let text = r#"{ "body": 2, "feet": null, "head": null }"#;
+
+        let parse = |instance: &Arc<TypeDefinitionInstance>, text: &str, null_policy| {
+            let options = ParseOptions::default().null_policy(null_policy);
+            let from_value = Value::parse_for_with_options(
+                Arc::clone(instance),
+                serde_json::from_str(text).unwrap(),
+                &options,
+            )
+            .map(|value| value.to_string())
+            .map_err(|err| err.to_string());
+            let from_str = Value::parse_str_for_with_options(Arc::clone(instance), text, &options)
+                .map(|value| value.to_string())
+                .map_err(|err| err.to_string());
+
+            // Both paths must agree, barring the error location.
+            assert_eq!(
+                from_value.is_ok(),
+                from_str.is_ok(),
+                "{from_value:?} != {from_str:?}"
+            );
+            assert_eq!(
+                instance
+                    .validate_with_options(&serde_json::from_str(text).unwrap(), &options)
+                    .is_valid(),
+                from_value.is_ok()
+            );
+
+            from_value
+        };
+
+        assert_eq!(
+            parse(&instances[2], text, NullPolicy::Error).unwrap_err(),
+            "failed to parse GameSON value `ArmorBySlot` (3): [feet]: invalid dictionary value: expected int32, found null"
+        );
+        assert_eq!(
+            parse(&instances[2], text, NullPolicy::UseDefault).unwrap(),
+            "{Slot::body: 2, Slot::feet: 0, Slot::head: 0}"
+        );
+        assert_eq!(
+            parse(&instances[2], text, NullPolicy::Absent).unwrap(),
+            "{Slot::body: 2}"
+        );
+
+        assert_eq!(
+            parse(&instances[3], "[[1, null], null, [3]]", NullPolicy::Absent).unwrap(),
+            "[[1], [3]]"
+        );
+        assert!(
+            parse(
+                &instances[3],
+                "[[1, null], null, [3]]",
+                NullPolicy::UseDefault
+            )
+            .is_err()
+        );
+        assert!(parse(&instances[0], "null", NullPolicy::Absent).is_err());
+        assert_eq!(
+            parse(&instances[0], "null", NullPolicy::UseDefault).unwrap(),
+            "0"
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let instances = instances();
+
+        for (instance, json) in [
+            (&instances[0], json!(3)),
+            (&instances[1], json!("body")),
+            (&instances[2], json!({ "head": 3, "feet": 1 })),
+            (&instances[3], json!([[1, 2], [], [3]])),
+            (&instances[5], json!(0.1)),
+        ] {
+            let value = Value::parse_for(Arc::clone(instance), json.clone()).unwrap();
+            assert_eq!(value.to_json().unwrap(), json);
+        }
+
+        let value = Value::parse_for(Arc::clone(&instances[1]), json!("torso")).unwrap();
+        assert_eq!(value.to_json().unwrap(), json!("body"));
+    }
+
+    #[test]
+    fn test_to_json_non_finite() {
+        let mut registry = TypeDefinitionRegistry::default();
+        let (registered, errors) = registry
+            .register([
+                td(
+                    1,
+                    "Ratio",
+                    TypeAttributes::Float64(
+                        NumberTypeAttributes::builder()
+                            .default_value(f64::NEG_INFINITY)
+                            .build()
+                            .unwrap(),
+                    ),
+                ),
+                td(
+                    2,
+                    "Ratios",
+                    TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+                ),
+            ])
+            .into_parts();
+        assert!(errors.is_empty());
+
+        let ratios = registered.into_iter().find(|td| td.id == 2).unwrap();
+        let value = Value::parse_for_with_options(
+            Arc::clone(&ratios),
+            json!([1.5, null]),
+            &ParseOptions::default().null_policy(NullPolicy::UseDefault),
+        )
+        .unwrap();
+
+        let err = value.to_json().unwrap_err();
+        assert_eq!(err.path(), &[PathSegment::ArrayIndex(1)]);
+        assert_eq!(
+            err.to_string(),
+            "failed to serialize GameSON value: [1]: non-finite number -inf has no JSON representation"
+        );
+
+        let to_json = |non_finite_floats| {
+            value
+                .to_json_with_options(
+                    &SerializeOptions::default().non_finite_floats(non_finite_floats),
+                )
+                .unwrap()
+        };
+        assert_eq!(to_json(NonFiniteFloats::Null), json!([1.5, null]));
+        assert_eq!(to_json(NonFiniteFloats::String), json!([1.5, "-Infinity"]));
+
+        assert!(value.revalidate(&ratios).is_valid());
+    }
+
+    #[test]
+    fn test_dictionary_pairs() {
+        let mut registry = TypeDefinitionRegistry::default();
+        let (registered, errors) = registry
+            .register([
+                td(
+                    1,
+                    "Level",
+                    TypeAttributes::Int32(
+                        NumberTypeAttributes::builder()
+                            .min(0)
+                            .max(10)
+                            .build()
+                            .unwrap(),
+                    ),
+                ),
+                td(
+                    2,
+                    "Name",
+                    TypeAttributes::String(StringTypeAttributes::default()),
+                ),
+                td(
+                    3,
+                    "NamesByLevel",
+                    TypeAttributes::Dictionary(
+                        DictionaryTypeAttributes::new(1, 2)
+                            .with_wire_form(DictionaryWireForm::Pairs),
+                    ),
+                ),
+                td(
+                    4,
+                    "NamesByLevelObject",
+                    TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2)),
+                ),
+            ])
+            .into_parts();
+
+        // Only the pairs wire form allows non-string keys.
+        assert_eq!(errors.len(), 1);
+
+        let instance = registered.into_iter().find(|td| td.id == 3).unwrap();
+        let parse = |text: &str| {
+            let from_value =
+                Value::parse_for(Arc::clone(&instance), serde_json::from_str(text).unwrap())
+                    .map(|value| value.to_string())
+                    .map_err(|err| err.to_string());
+            let from_str = Value::parse_str_for(Arc::clone(&instance), text)
+                .map(|value| value.to_string())
+                .map_err(|err| err.to_string());
+
+            // Both paths must agree, barring the error location.
+            assert_eq!(
+                from_value.is_ok(),
+                from_str.is_ok(),
+                "{from_value:?} != {from_str:?}"
+            );
+            assert_eq!(
+                instance
+                    .validate(&serde_json::from_str(text).unwrap())
+                    .is_valid(),
+                from_value.is_ok()
+            );
+
+            from_value
+        };
+
+        assert_eq!(
+            parse(r#"[[1, "one"], [2, "two"]]"#).unwrap(),
+            r#"{1: "one", 2: "two"}"#
+        );
+        assert_eq!(parse("[]").unwrap(), "{}");
+        assert_eq!(
+            parse(r#"[[1, "one", 2]]"#).unwrap_err(),
+            "failed to parse GameSON value `NamesByLevel` (3): [0]: expected a [key, value] pair, found an array of 3 items"
+        );
+        assert_eq!(
+            parse(r#"[[11, "eleven"]]"#).unwrap_err(),
+            "failed to parse GameSON value `NamesByLevel` (3): [0][0]: invalid dictionary key: invalid int32: value 11 is greater than the maximum 10"
+        );
+        assert_eq!(
+            parse(r#"[[1, 1]]"#).unwrap_err(),
+            "failed to parse GameSON value `NamesByLevel` (3): [0][1]: invalid dictionary value: expected string, found number"
+        );
+        assert_eq!(
+            parse(r#"{ "1": "one" }"#).unwrap_err(),
+            "failed to parse GameSON value `NamesByLevel` (3): : expected dictionary of pairs, found object"
+        );
+        assert!(parse(r#"[1]"#).is_err());
+        assert_eq!(
+            parse(r#"[[1, "one"], [2, "two"], [1, "uno"]]"#).unwrap_err(),
+            "failed to parse GameSON value `NamesByLevel` (3): [2]: duplicate dictionary key `1`"
+        );
+
+        let json = json!([[1, "one"], [2, "two"]]);
+        let value = Value::parse_for(Arc::clone(&instance), json.clone()).unwrap();
+        assert_eq!(value.to_json().unwrap(), json);
+        assert!(value.revalidate(&instance).is_valid());
+    }
+
+    #[test]
+    fn test_dictionary_order() {
+        let instances = instances();
+        let text = r#"{"head":1,"feet":3,"body":2}"#;
+
+        let value = Value::parse_str_for(Arc::clone(&instances[2]), text).unwrap();
+        assert_eq!(
+            value.to_string(),
+            "{Slot::head: 1, Slot::feet: 3, Slot::body: 2}"
+        );
+
+        let value = Value::parse_for(
+            Arc::clone(&instances[2]),
+            serde_json::from_str(text).unwrap(),
+        )
+        .unwrap();
+
+        if cfg!(feature = "indexmap") {
+            assert_eq!(
+                value.to_string(),
+                "{Slot::head: 1, Slot::feet: 3, Slot::body: 2}"
+            );
+            assert_eq!(value.to_json().unwrap().to_string(), text);
+        } else {
+            assert_eq!(
+                value.to_string(),
+                "{Slot::body: 2, Slot::feet: 3, Slot::head: 1}"
+            );
+            assert_eq!(
+                value.to_json().unwrap().to_string(),
+                r#"{"body":2,"feet":3,"head":1}"#
+            );
+        }
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_bytes() {
+        use crate::{BytesEncoding, type_attributes::BytesTypeAttributes};
+
+        let mut registry = TypeDefinitionRegistry::default();
+        let (registered, errors) = registry
+            .register([
+                td(
+                    1,
+                    "Hash",
+                    TypeAttributes::Bytes(BytesTypeAttributes::new(BytesEncoding::Hex)),
+                ),
+                td(
+                    2,
+                    "Blob",
+                    TypeAttributes::Bytes(BytesTypeAttributes::default()),
+                ),
+            ])
+            .into_parts();
+        assert!(errors.is_empty());
+
+        let hash = registered.iter().find(|td| td.id == 1).unwrap();
+        let blob = registered.iter().find(|td| td.id == 2).unwrap();
+
+        let value = Value::parse_for(Arc::clone(hash), json!("00FF10")).unwrap();
+        assert_eq!(value.to_string(), r#""00ff10""#);
+        assert_eq!(value.to_json().unwrap(), json!("00ff10"));
+
+        // Encodings only matter on the wire.
+        assert!(value.revalidate(blob).is_valid());
+
+        let value = Value::parse_str_for(Arc::clone(blob), r#""AP8Q""#).unwrap();
+        assert_eq!(value.to_json().unwrap(), json!("AP8Q"));
+
+        assert_eq!(
+            Value::parse_str_for(Arc::clone(hash), r#""0g""#)
+                .unwrap_err()
+                .kind()
+                .to_string(),
+            "invalid bytes: not valid hex: Invalid character 'g' at position 1"
+        );
+        assert!(!hash.validate(&json!("AP8Q")).is_valid());
+    }
+
+    #[test]
+    fn test_from_serialize() {
+        #[derive(serde::Serialize, PartialEq, Eq, PartialOrd, Ord)]
+        #[serde(rename_all = "lowercase")]
+        enum Slot {
+            Head,
+            Torso,
+        }
+
+        let instances = instances();
+
+        let value = Value::from_serialize(
+            Arc::clone(&instances[2]),
+            &BTreeMap::from([(Slot::Head, 3), (Slot::Torso, 5)]),
+        )
+        .unwrap();
+        assert_eq!(value.to_json().unwrap(), json!({ "head": 3, "body": 5 }));
+
+        let err = Value::from_serialize(Arc::clone(&instances[4]), &[4, 11]).unwrap_err();
+        assert_eq!(err.path(), [PathSegment::ArrayIndex(1)]);
+        assert_eq!(err.kind().code(), "invalid_int32");
+
+        let err = Value::from_serialize(Arc::clone(&instances[2]), &BTreeMap::from([((1, 2), 3)]))
+            .unwrap_err();
+        assert!(err.path().is_empty());
+        assert_eq!(
+            err.kind().to_string(),
+            "invalid dictionary key: expected enum, found array"
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! MessagePack serialization of GameSON values.
+
+use std::{borrow::Borrow, fmt::Display, marker::PhantomData, sync::Arc};
+
+use serde::de::DeserializeSeed;
+
+use crate::{ParseOptions, TypeDefinitionInstance, metrics::Measure};
+
+use super::{ParseContext, ParseError, ParseErrorKind, Value, seed::ValueImplSeed, wire::Wire};
+
+impl<Id, FieldName: Ord + Borrow<str>> Value<Id, FieldName> {
+    /// Serialize the value to MessagePack.
+    ///
+    /// The value has the same structure as in JSON, except that floating-point numbers keep their
+    /// precision, and that bytes and UUIDs are serialized as MessagePack binaries.
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        rmp_serde::to_vec(&Wire {
+            value: &self.value,
+            instance: &self.instance,
+        })
+        .expect("serializing to memory cannot fail")
+    }
+}
+
+impl<Id: Display, FieldName: Ord + Display + Clone + Borrow<str>> Value<Id, FieldName> {
+    /// Parse a GameSON value from MessagePack for a specified type instance.
+    ///
+    /// The value is validated exactly as when parsing JSON.
+    pub fn parse_msgpack(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        bytes: &[u8],
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        Self::parse_msgpack_with_options(instance, bytes, &ParseOptions::default())
+    }
+
+    /// Parse a GameSON value from MessagePack for a specified type instance, using the specified
+    /// parse options.
+    pub fn parse_msgpack_with_options(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        let measure = Measure::start(options);
+        let mut context = ParseContext::new(options);
+        let mut deserializer = rmp_serde::Deserializer::new(bytes);
+
+        let result = ValueImplSeed {
+            context: &mut context,
+            instance: &instance,
+            strings: PhantomData,
+        }
+        .deserialize(&mut deserializer)
+        .map_err(|err| match context.error.take() {
+            Some(err) => err,
+            None => ParseErrorKind::InvalidMsgpack(err.to_string()),
+        })
+        .and_then(|value| match deserializer.get_ref().is_empty() {
+            true => Ok(value),
+            false => Err(ParseErrorKind::InvalidMsgpack(
+                "trailing bytes after the value".to_owned(),
+            )),
+        });
+
+        if let Some(measure) = measure {
+            measure.parsed(instance.name.borrow(), result.as_ref().map(|_| ()));
+        }
+
+        match result {
+            Ok(value) => Ok(Self { instance, value }),
+            Err(err) => Err(ParseError {
+                instance,
+                path: context.path.into_path(),
+                err,
+                location: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        DictionaryWireForm, ParseErrorKind, PathSegment,
+        test_support::{TypeAttributes, Value, registry, td},
+        type_attributes::{ArrayTypeAttributes, DictionaryTypeAttributes, NumberTypeAttributes},
+    };
+
+    #[test]
+    fn test_msgpack() {
+        let registry = registry([
+            td(1, "Name", TypeAttributes::String(Default::default())),
+            td(
+                2,
+                "Level",
+                TypeAttributes::Uint32(NumberTypeAttributes::builder().max(10).build().unwrap()),
+            ),
+            td(3, "Weight", TypeAttributes::Float64(Default::default())),
+            td(
+                4,
+                "Levels",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2)),
+            ),
+            td(
+                5,
+                "Weights",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(1, 3).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+            td(
+                6,
+                "Offsets",
+                TypeAttributes::Array(ArrayTypeAttributes::new(3)),
+            ),
+        ]);
+
+        for (id, json) in [
+            (4, json!({ "warrior": 3, "mage": 10 })),
+            (5, json!([["sword", 1.25], ["bow", -0.5]])),
+            (6, json!([0.1, 1e300])),
+        ] {
+            let instance = registry.get(&id).unwrap().clone();
+            let value = Value::parse_for(instance.clone(), json.clone()).unwrap();
+            let bytes = value.to_msgpack();
+
+            assert_eq!(
+                Value::parse_msgpack(instance, &bytes)
+                    .unwrap()
+                    .to_json()
+                    .unwrap(),
+                json
+            );
+        }
+
+        // The same validation applies as for JSON.
+        let levels = registry.get(&4).unwrap().clone();
+        let bytes = rmp_serde::to_vec(&json!({ "warrior": 3, "mage": 11 })).unwrap();
+        let err = Value::parse_msgpack(levels.clone(), &bytes).unwrap_err();
+        assert_eq!(err.path(), [PathSegment::DictionaryKey("mage".to_owned())]);
+        assert!(matches!(
+            err.kind(),
+            ParseErrorKind::InvalidDictionaryValue(_)
+        ));
+
+        let bytes = rmp_serde::to_vec(&json!({ "warrior": "3" })).unwrap();
+        let err = Value::parse_msgpack(levels.clone(), &bytes).unwrap_err();
+        assert_eq!(err.kind().code(), "invalid_dictionary_value");
+
+        let mut bytes = rmp_serde::to_vec(&json!({ "warrior": 3 })).unwrap();
+        bytes.push(0xc0);
+        let err = Value::parse_msgpack(levels.clone(), &bytes).unwrap_err();
+        assert_eq!(err.kind().code(), "invalid_msgpack");
+
+        let err = Value::parse_msgpack(levels, &bytes[..3]).unwrap_err();
+        assert_eq!(err.kind().code(), "invalid_msgpack");
+    }
+}
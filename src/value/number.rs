@@ -0,0 +1,353 @@
+//! Conversion of JSON numbers to GameSON numbers.
+
+use std::borrow::Borrow;
+
+use crate::{
+    Float32Conversion, NumberConversion, ParseOptions, TypeDefinitionInstance,
+    type_attributes::{NumberTypeAttributes, ValidateNumberTypeError},
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::{ParseContext, ParseErrorKind, PathSegment, ValueImpl};
+
+impl<FieldName: Ord + Clone + Borrow<str>, S> ValueImpl<FieldName, S> {
+    /// Parse a JSON number for a specified numeric type.
+    pub(super) fn parse_number<Id>(
+        options: &ParseOptions,
+        attributes: &TypeAttributesInstance<Id, FieldName>,
+        v: &serde_json::Number,
+    ) -> Result<Self, ParseErrorKind> {
+        let conversion = options.number_conversion;
+
+        Ok(match attributes {
+            TypeAttributesInstance::Int32(a) => {
+                Self::Int32(validate_number(a, to_integer(v, conversion))?)
+            }
+            TypeAttributesInstance::Int64(a) => {
+                Self::Int64(validate_number(a, to_integer(v, conversion))?)
+            }
+            TypeAttributesInstance::Uint32(a) => {
+                Self::Uint32(validate_number(a, to_integer(v, conversion))?)
+            }
+            TypeAttributesInstance::Uint64(a) => {
+                Self::Uint64(validate_number(a, to_integer(v, conversion))?)
+            }
+            TypeAttributesInstance::Float32(a) => {
+                Self::Float32(validate_number(a, Ok(float32_from(options, v)?))?)
+            }
+            TypeAttributesInstance::Float64(a) => {
+                Self::Float64(validate_number(a, to_float(v, conversion, |v| v))?)
+            }
+            attributes => {
+                return Err(ParseErrorKind::UnexpectedValue {
+                    expected: attributes.kind(),
+                    found: "number",
+                });
+            }
+        })
+    }
+}
+
+impl<FieldName: Ord + Clone + Borrow<str>> ValueImpl<FieldName> {
+    /// Check a non-finite number for a specified numeric type.
+    ///
+    /// Non-finite numbers have no JSON representation, but are valid floating-point numbers.
+    pub(super) fn parse_non_finite<Id>(
+        attributes: &TypeAttributesInstance<Id, FieldName>,
+        v: f64,
+    ) -> Result<Self, ParseErrorKind> {
+        Ok(match attributes {
+            TypeAttributesInstance::Float32(a) => Self::Float32(validate_number(a, Ok(v as f32))?),
+            TypeAttributesInstance::Float64(a) => Self::Float64(validate_number(a, Ok(v))?),
+            attributes => {
+                return Err(ParseErrorKind::UnexpectedValue {
+                    expected: attributes.kind(),
+                    found: "number",
+                });
+            }
+        })
+    }
+
+    /// Parse the items of an array of numbers in a tight loop, converting them all before
+    /// validating them at once, rather than parsing each of them as a value.
+    ///
+    /// Returns `None` if the fast path does not apply: if the items type is not numeric or has a
+    /// validator, if some items are not numbers, when recovering from errors, or if the items
+    /// would exceed the maximum number of values. Errors are reported exactly as by the general
+    /// path.
+    pub(super) fn parse_number_array<Id>(
+        context: &mut ParseContext<'_>,
+        instance: &TypeDefinitionInstance<Id, FieldName>,
+        items: &[serde_json::Value],
+    ) -> Option<Result<Vec<Self>, ParseErrorKind>> {
+        if instance.validator.is_some()
+            || context.recovered.is_some()
+            || !items.iter().all(serde_json::Value::is_number)
+            || context
+                .options
+                .limits
+                .max_nodes
+                .is_some_and(|max| context.nodes + items.len() > max)
+        {
+            return None;
+        }
+
+        let options = context.options;
+        let conversion = options.number_conversion;
+
+        let items = match &instance.attributes {
+            TypeAttributesInstance::Int32(a) => parse_numbers(
+                context,
+                a,
+                items,
+                |v| to_integer(v, conversion),
+                Self::Int32,
+            ),
+            TypeAttributesInstance::Int64(a) => parse_numbers(
+                context,
+                a,
+                items,
+                |v| to_integer(v, conversion),
+                Self::Int64,
+            ),
+            TypeAttributesInstance::Uint32(a) => parse_numbers(
+                context,
+                a,
+                items,
+                |v| to_integer(v, conversion),
+                Self::Uint32,
+            ),
+            TypeAttributesInstance::Uint64(a) => parse_numbers(
+                context,
+                a,
+                items,
+                |v| to_integer(v, conversion),
+                Self::Uint64,
+            ),
+            TypeAttributesInstance::Float32(a) => parse_numbers(
+                context,
+                a,
+                items,
+                |v| float32_from(options, v),
+                Self::Float32,
+            ),
+            TypeAttributesInstance::Float64(a) => parse_numbers(
+                context,
+                a,
+                items,
+                |v| to_float(v, conversion, |v| v),
+                Self::Float64,
+            ),
+            _ => return None,
+        };
+
+        context.nodes += items.as_ref().map_or(0, Vec::len);
+
+        Some(items)
+    }
+}
+
+/// Convert the items of an array of numbers, then validate them at once against their type
+/// attributes.
+///
+/// The path of the first invalid item is pushed to the context.
+fn parse_numbers<Num, FieldName, E>(
+    context: &mut ParseContext<'_>,
+    attributes: &NumberTypeAttributes<Num>,
+    items: &[serde_json::Value],
+    convert: impl Fn(&serde_json::Number) -> Result<Num, E>,
+    wrap: impl Fn(Num) -> ValueImpl<FieldName>,
+) -> Result<Vec<ValueImpl<FieldName>>, ParseErrorKind>
+where
+    Num: PartialOrd + Copy,
+    ParseErrorKind: From<E> + From<ValidateNumberTypeError<Num>>,
+{
+    let mut values = Vec::with_capacity(items.len());
+
+    for (i, item) in items.iter().enumerate() {
+        let serde_json::Value::Number(v) = item else {
+            unreachable!("only arrays of numbers are parsed in bulk");
+        };
+
+        match convert(v) {
+            Ok(value) => values.push(value),
+            Err(err) => {
+                context.path.push(PathSegment::ArrayIndex(i));
+
+                return Err(err.into());
+            }
+        }
+    }
+
+    if let Err((i, err)) = attributes.validate_slice(&values) {
+        context.path.push(PathSegment::ArrayIndex(i));
+
+        return Err(err.into());
+    }
+
+    Ok(values.into_iter().map(wrap).collect())
+}
+
+/// Convert a JSON number to a float32.
+fn float32_from(options: &ParseOptions, v: &serde_json::Number) -> Result<f32, ParseErrorKind> {
+    match v.as_f64().filter(|_| v.is_f64()) {
+        Some(v) => to_float32(v, options.float32_conversion),
+        None => Ok(to_float(v, options.number_conversion, |v| v as f32)?),
+    }
+}
+
+/// Validate a converted number against its type attributes.
+fn validate_number<Num: PartialOrd + Copy>(
+    attributes: &NumberTypeAttributes<Num>,
+    value: Result<Num, ValidateNumberTypeError<Num>>,
+) -> Result<Num, ValidateNumberTypeError<Num>> {
+    let value = value?;
+
+    attributes.validate(value)?;
+
+    Ok(value)
+}
+
+/// Convert a JSON number to an integer type.
+///
+/// Fractional numbers are rejected or rounded, depending on the conversion policy.
+fn to_integer<T: TryFrom<i128>>(
+    v: &serde_json::Number,
+    conversion: NumberConversion,
+) -> Result<T, ValidateNumberTypeError<T>> {
+    let value = match as_integer(v) {
+        Some(value) => value,
+        None => {
+            let value = v.as_f64().ok_or(ValidateNumberTypeError::InvalidValue)?;
+            let value = match conversion {
+                NumberConversion::Exact => value,
+                NumberConversion::Nearest => value.round(),
+            };
+
+            if value.fract() != 0.0 {
+                return Err(ValidateNumberTypeError::Inexact(v.to_string()));
+            }
+
+            // The conversion saturates, which is fine as such values are out of range anyway.
+            value as i128
+        }
+    };
+
+    T::try_from(value).map_err(|_| ValidateNumberTypeError::InvalidValue)
+}
+
+/// Convert a JSON number to a floating-point type.
+///
+/// Integers that the type cannot represent exactly are rejected or rounded, depending on the
+/// conversion policy.
+fn to_float<T: Copy + Into<f64>>(
+    v: &serde_json::Number,
+    conversion: NumberConversion,
+    convert: impl FnOnce(f64) -> T,
+) -> Result<T, ValidateNumberTypeError<T>> {
+    let value = convert(v.as_f64().ok_or(ValidateNumberTypeError::InvalidValue)?);
+
+    if conversion == NumberConversion::Exact
+        && let Some(integer) = as_integer(v)
+        && value.into() as i128 != integer
+    {
+        return Err(ValidateNumberTypeError::Inexact(v.to_string()));
+    }
+
+    Ok(value)
+}
+
+/// Convert a floating-point JSON number to a float32.
+fn to_float32(v: f64, conversion: Float32Conversion) -> Result<f32, ParseErrorKind> {
+    let value = v as f32;
+
+    if value.is_infinite() {
+        return match conversion {
+            Float32Conversion::Clamp => Ok(f32::MAX.copysign(value)),
+            _ => Err(ParseErrorKind::UnrepresentableFloat32(v)),
+        };
+    }
+
+    // The shortest representation of the float32 is the one that was parsed, unless precision
+    // was lost.
+    if conversion == Float32Conversion::Exact && value.to_string().parse() != Ok(v) {
+        return Err(ParseErrorKind::UnrepresentableFloat32(v));
+    }
+
+    Ok(value)
+}
+
+/// Get the value of a JSON number, if it is an integer.
+fn as_integer(v: &serde_json::Number) -> Option<i128> {
+    v.as_i64()
+        .map(i128::from)
+        .or_else(|| v.as_u64().map(i128::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        PathSegment,
+        test_support::{TypeAttributes, Value, registry, td},
+        type_attributes::{ArrayTypeAttributes, NumberTypeAttributes},
+    };
+
+    #[test]
+    fn test_parse_number_array() {
+        let registry = registry([
+            td(
+                1,
+                "Height",
+                TypeAttributes::Float32(
+                    NumberTypeAttributes::builder()
+                        .min(0.0)
+                        .max(100.0)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                2,
+                "Heightmap",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(3, "Index", TypeAttributes::Int32(Default::default())),
+            td(
+                4,
+                "Indices",
+                TypeAttributes::Array(ArrayTypeAttributes::new(3)),
+            ),
+        ]);
+
+        let height = registry.get(&1).unwrap().clone();
+        let heightmap = registry.get(&2).unwrap().clone();
+        let indices = registry.get(&4).unwrap().clone();
+
+        let mut heights: Vec<_> = (0..1000).map(|i| f64::from(i % 100) + 0.5).collect();
+        let value = Value::parse_for(heightmap.clone(), json!(heights)).unwrap();
+        assert_eq!(value.to_json().unwrap(), json!(heights));
+
+        // Errors are reported as for a single item.
+        heights[777] = 150.5;
+        heights[900] = -1.0;
+        let err = Value::parse_for(heightmap, json!(heights)).unwrap_err();
+        assert_eq!(err.path(), [PathSegment::ArrayIndex(777)]);
+        assert_eq!(
+            err.kind().to_string(),
+            Value::parse_for(height, json!(150.5))
+                .unwrap_err()
+                .kind()
+                .to_string()
+        );
+
+        let err = Value::parse_for(indices.clone(), json!([1, 2.5, 3])).unwrap_err();
+        assert_eq!(err.path(), [PathSegment::ArrayIndex(1)]);
+        assert_eq!(err.kind().code(), "invalid_int32");
+
+        let err = Value::parse_for(indices, json!([1, 2, i64::MAX])).unwrap_err();
+        assert_eq!(err.path(), [PathSegment::ArrayIndex(2)]);
+        assert_eq!(err.kind().code(), "invalid_int32");
+    }
+}
@@ -0,0 +1,410 @@
+//! Persistent GameSON values, sharing their unmodified subtrees between versions.
+
+use std::{borrow::Borrow, fmt::Display, sync::Arc};
+
+use crate::{
+    ParseOptions, TypeDefinitionInstance, type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::{
+    ParseContext, ParseError, ParseErrorKind, ParseErrorPath, PathSegment, Value, ValueImpl,
+    constraints::check_rules, run_validator,
+};
+
+/// A GameSON value whose arrays and dictionaries are shared between its clones.
+///
+/// Cloning a persistent value is cheap, and [`set`](Self::set) only copies the collections along
+/// the path of the value it replaces: all other subtrees stay shared with the previous versions,
+/// which keeps editor undo histories from duplicating entire documents.
+///
+/// Like a [`Value`], a persistent value is guaranteed to be valid for its type instance.
+#[derive(Debug, Clone)]
+pub struct PersistentValue<Id, FieldName: Ord> {
+    /// The type instance.
+    instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The root of the value.
+    node: Node<FieldName>,
+}
+
+/// An error that can occur when modifying a [`PersistentValue`].
+#[derive(Debug, thiserror::Error)]
+pub enum PersistentValueError<Id: Display, FieldName: Ord + Display> {
+    /// The path does not lead to a value, nor to a missing entry of a dictionary.
+    #[error("no value at `{}`", ParseErrorPath(.0.clone()))]
+    NoSuchPath(Vec<PathSegment>),
+
+    /// The new value is invalid, or makes one of the collections containing it invalid.
+    #[error(transparent)]
+    Invalid(#[from] ParseError<Id, FieldName>),
+}
+
+/// A node of a persistent value.
+#[derive(Debug, Clone)]
+enum Node<FieldName> {
+    /// An array, shared between versions.
+    Array(Arc<Vec<Node<FieldName>>>),
+
+    /// A dictionary, shared between versions.
+    Dictionary(Arc<Vec<(ValueImpl<FieldName>, Node<FieldName>)>>),
+
+    /// Any other value.
+    Scalar(ValueImpl<FieldName>),
+}
+
+/// The reason why a persistent value could not be modified.
+enum Failure {
+    /// The path does not lead to a value.
+    NoSuchPath,
+
+    /// A value is invalid, at the specified path.
+    Invalid(Vec<PathSegment>, ParseErrorKind),
+}
+
+impl Failure {
+    /// Report the failure of a dictionary value as a failure of the dictionary.
+    fn in_dictionary(self) -> Self {
+        match self {
+            Self::Invalid(path, err) => {
+                Self::Invalid(path, ParseErrorKind::InvalidDictionaryValue(Box::new(err)))
+            }
+            failure => failure,
+        }
+    }
+}
+
+impl<FieldName> From<ValueImpl<FieldName>> for Node<FieldName> {
+    fn from(mut value: ValueImpl<FieldName>) -> Self {
+        // Values implement `Drop`: their items are taken rather than moved out.
+        match &mut value {
+            ValueImpl::Array(items) => Self::Array(Arc::new(
+                std::mem::take(items).into_iter().map(Self::from).collect(),
+            )),
+            ValueImpl::Dictionary(entries) => Self::Dictionary(Arc::new(
+                std::mem::take(entries)
+                    .into_iter()
+                    .map(|(key, value)| (key, Self::from(value)))
+                    .collect(),
+            )),
+            _ => Self::Scalar(value),
+        }
+    }
+}
+
+impl<FieldName: Clone> Node<FieldName> {
+    /// Copy the node into a value implementation.
+    fn to_impl(&self) -> ValueImpl<FieldName> {
+        match self {
+            Self::Array(items) => ValueImpl::Array(items.iter().map(Self::to_impl).collect()),
+            Self::Dictionary(entries) => ValueImpl::Dictionary(
+                entries
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.to_impl()))
+                    .collect(),
+            ),
+            Self::Scalar(value) => value.clone(),
+        }
+    }
+}
+
+impl<FieldName: Ord + Clone + Borrow<str>> Node<FieldName> {
+    /// Replace the value at the specified path, starting at the specified depth, copying the
+    /// collections along the way if they are shared.
+    ///
+    /// The collections along the path are checked again once the value is replaced.
+    fn set<Id>(
+        &mut self,
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        path: &[PathSegment],
+        depth: usize,
+        value: serde_json::Value,
+        options: &ParseOptions,
+    ) -> Result<(), Failure> {
+        let Some(segment) = path.get(depth) else {
+            *self = Self::from(parse_at(instance, path, value, options)?);
+
+            return Ok(());
+        };
+
+        match (&mut *self, &instance.attributes, segment) {
+            (Self::Array(items), TypeAttributesInstance::Array(a), PathSegment::ArrayIndex(i)) => {
+                Arc::make_mut(items)
+                    .get_mut(*i)
+                    .ok_or(Failure::NoSuchPath)?
+                    .set(a.items_type_id(), path, depth + 1, value, options)?;
+            }
+            (
+                Self::Dictionary(entries),
+                TypeAttributesInstance::Dictionary(a),
+                PathSegment::DictionaryKey(key),
+            ) => {
+                let entries = Arc::make_mut(entries);
+
+                match entries.iter().position(|(k, _)| is_key(k, key)) {
+                    Some(i) => entries[i]
+                        .1
+                        .set(a.values_type_id(), path, depth + 1, value, options)
+                        .map_err(Failure::in_dictionary)?,
+                    None if depth + 1 == path.len() => {
+                        let key = serde_json::Value::String(key.clone());
+                        let key = parse_at(a.keys_type_id(), &path[..=depth], key, options)
+                            .map_err(|failure| match failure {
+                                Failure::Invalid(path, err) => Failure::Invalid(
+                                    path,
+                                    ParseErrorKind::InvalidDictionaryKey(Box::new(err)),
+                                ),
+                                failure => failure,
+                            })?;
+                        let value = parse_at(a.values_type_id(), path, value, options)
+                            .map_err(Failure::in_dictionary)?;
+
+                        entries.push((key, Self::from(value)));
+                    }
+                    None => return Err(Failure::NoSuchPath),
+                }
+            }
+            _ => return Err(Failure::NoSuchPath),
+        }
+
+        self.check(instance)
+            .map_err(|err| Failure::Invalid(path[..depth].to_vec(), err))
+    }
+
+    /// Check the entry rules and the validator of the type instance of a modified collection.
+    fn check<Id>(
+        &self,
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+    ) -> Result<(), ParseErrorKind> {
+        if let (TypeAttributesInstance::Dictionary(a), Self::Dictionary(entries)) =
+            (&instance.attributes, self)
+            && let Some(rules) = a.compiled_rules()
+        {
+            // Only the entries referenced by the rules are copied.
+            let mut slots = vec![None; rules.keys().len()];
+
+            for (key, value) in entries.iter() {
+                if let ValueImpl::Enum(key) = key
+                    && let Some(index) = rules.index(key.borrow())
+                {
+                    slots[index].get_or_insert_with(|| value.to_impl());
+                }
+            }
+
+            check_rules(rules, &slots.iter().map(Option::as_ref).collect::<Vec<_>>())?;
+        }
+
+        // The validator needs the whole collection.
+        if instance.validator.is_some() {
+            run_validator(instance, self.to_impl())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse the value at the specified path.
+fn parse_at<Id, FieldName: Ord + Clone + Borrow<str>>(
+    instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+    path: &[PathSegment],
+    value: serde_json::Value,
+    options: &ParseOptions,
+) -> Result<ValueImpl<FieldName>, Failure> {
+    let mut context = ParseContext::new(options);
+
+    ValueImpl::parse_for(&mut context, instance, value).map_err(|err| {
+        let mut path = path.to_vec();
+        path.extend(context.path.into_path().0);
+
+        Failure::Invalid(path, err)
+    })
+}
+
+/// Check whether a dictionary key is addressed by a path segment.
+fn is_key<FieldName: Borrow<str>>(key: &ValueImpl<FieldName>, segment: &str) -> bool {
+    match key {
+        ValueImpl::String(_) | ValueImpl::Enum(_) => key.key_string() == segment,
+        #[cfg(feature = "uuid")]
+        ValueImpl::Uuid(_) => key.key_string() == segment,
+        _ => false,
+    }
+}
+
+impl<Id, FieldName: Ord> Value<Id, FieldName> {
+    /// Convert the value into a persistent value, sharing its subtrees between its versions.
+    pub fn into_persistent(self) -> PersistentValue<Id, FieldName> {
+        PersistentValue {
+            instance: self.instance,
+            node: Node::from(self.value),
+        }
+    }
+}
+
+impl<Id, FieldName: Ord + Clone> PersistentValue<Id, FieldName> {
+    /// Get the type instance of the value.
+    pub fn instance(&self) -> &Arc<TypeDefinitionInstance<Id, FieldName>> {
+        &self.instance
+    }
+
+    /// Copy the current version of the value into a value.
+    pub fn to_value(&self) -> Value<Id, FieldName> {
+        Value {
+            instance: Arc::clone(&self.instance),
+            value: self.node.to_impl(),
+        }
+    }
+}
+
+impl<Id: Display, FieldName: Ord + Display + Clone + Borrow<str>> PersistentValue<Id, FieldName> {
+    /// Replace the value at the specified path.
+    ///
+    /// Array items are addressed by index, and dictionary entries by key, whatever the wire form
+    /// of the dictionary: a missing dictionary entry is added. The new value is validated for its
+    /// type, and the arrays and dictionaries containing it are checked again, including their
+    /// validators, which must see the whole collection and are therefore best kept to small ones.
+    ///
+    /// The value is left untouched if it cannot be modified.
+    pub fn set(
+        &mut self,
+        path: &[PathSegment],
+        value: serde_json::Value,
+    ) -> Result<(), PersistentValueError<Id, FieldName>> {
+        self.set_with_options(path, value, &ParseOptions::default())
+    }
+
+    /// Replace the value at the specified path, using the specified parse options to parse the
+    /// new value.
+    pub fn set_with_options(
+        &mut self,
+        path: &[PathSegment],
+        value: serde_json::Value,
+        options: &ParseOptions,
+    ) -> Result<(), PersistentValueError<Id, FieldName>> {
+        // Modify a new version, so that the current one is kept on failure.
+        let mut node = self.node.clone();
+
+        match node.set(&self.instance, path, 0, value, options) {
+            Ok(()) => {
+                self.node = node;
+
+                Ok(())
+            }
+            Err(Failure::NoSuchPath) => Err(PersistentValueError::NoSuchPath(path.to_vec())),
+            Err(Failure::Invalid(path, err)) => Err(ParseError {
+                instance: Arc::clone(&self.instance),
+                path: ParseErrorPath(path),
+                err,
+                location: None,
+            }
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::{Node, PersistentValueError};
+    use crate::{
+        PathSegment,
+        test_support::{FieldName, Id, TypeAttributes, Value, registry, td},
+        type_attributes::{ArrayTypeAttributes, DictionaryTypeAttributes, NumberTypeAttributes},
+    };
+
+    type PersistentValue = super::PersistentValue<Id, FieldName>;
+
+    fn party() -> PersistentValue {
+        let registry = registry([
+            td(1, "Name", TypeAttributes::String(Default::default())),
+            td(
+                2,
+                "Level",
+                TypeAttributes::Int32(NumberTypeAttributes::builder().max(10).build().unwrap()),
+            ),
+            td(
+                3,
+                "Levels",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(1, 2)),
+            ),
+            td(
+                4,
+                "Party",
+                TypeAttributes::Array(ArrayTypeAttributes::new(3)),
+            ),
+        ]);
+
+        let instance = registry.get(&4).unwrap().clone();
+
+        Value::parse_for(instance, json!([{ "warrior": 3 }, { "mage": 5 }]))
+            .unwrap()
+            .into_persistent()
+    }
+
+    fn path(index: usize, key: &str) -> [PathSegment; 2] {
+        [
+            PathSegment::ArrayIndex(index),
+            PathSegment::DictionaryKey(key.to_owned()),
+        ]
+    }
+
+    #[test]
+    fn test_persistent_value_set() {
+        let first = party();
+
+        let mut second = first.clone();
+        second.set(&path(1, "mage"), json!(7)).unwrap();
+        second.set(&path(1, "thief"), json!(2)).unwrap();
+
+        assert_eq!(
+            first.to_value().to_json().unwrap(),
+            json!([{ "warrior": 3 }, { "mage": 5 }])
+        );
+        assert_eq!(
+            second.to_value().to_json().unwrap(),
+            json!([{ "warrior": 3 }, { "mage": 7, "thief": 2 }])
+        );
+    }
+
+    #[test]
+    fn test_persistent_value_sharing() {
+        let first = party();
+
+        let mut second = first.clone();
+        second.set(&path(1, "mage"), json!(7)).unwrap();
+
+        // Only the modified path was copied.
+        let (Node::Array(a), Node::Array(b)) = (&first.node, &second.node) else {
+            panic!("not arrays");
+        };
+        assert!(!Arc::ptr_eq(a, b));
+        let (Node::Dictionary(a0), Node::Dictionary(b0)) = (&a[0], &b[0]) else {
+            panic!("not dictionaries");
+        };
+        assert!(Arc::ptr_eq(a0, b0));
+    }
+
+    #[test]
+    fn test_persistent_value_set_invalid() {
+        let mut value = party();
+
+        // Invalid modifications leave the value untouched.
+        let err = value.set(&path(0, "warrior"), json!(11)).unwrap_err();
+        let PersistentValueError::Invalid(err) = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(err.path(), path(0, "warrior"));
+        assert_eq!(err.kind().code(), "invalid_dictionary_value");
+
+        let err = value
+            .set(&[PathSegment::ArrayIndex(2)], json!({}))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "no value at `[2]`");
+
+        assert_eq!(
+            value.to_value().to_json().unwrap(),
+            json!([{ "warrior": 3 }, { "mage": 5 }])
+        );
+    }
+}
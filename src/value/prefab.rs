@@ -0,0 +1,156 @@
+//! Prefabs: template values, instantiated with overrides.
+
+use std::{borrow::Borrow, fmt::Display, sync::Arc};
+
+use crate::{ParseOptions, TypeDefinitionInstance};
+
+use super::{PathSegment, PersistentValue, PersistentValueError, Value};
+
+/// A template value, from which copies are instantiated with some of their values overridden.
+///
+/// A prefab is authored once, like a `goblin_base`, and its variants, like a `goblin_elite`, only
+/// list the values they change. Overrides are applied like [`PersistentValue::set`]: each of them
+/// is validated for its type, and the arrays and dictionaries containing it are checked again, so
+/// that instances are guaranteed to be valid. Instances share their unmodified subtrees with the
+/// prefab until they are converted into values.
+#[derive(Debug, Clone)]
+pub struct Prefab<Id, FieldName: Ord> {
+    /// The template value.
+    template: PersistentValue<Id, FieldName>,
+}
+
+impl<Id, FieldName: Ord> Prefab<Id, FieldName> {
+    /// Create a prefab from its template value.
+    pub fn new(template: Value<Id, FieldName>) -> Self {
+        Self {
+            template: template.into_persistent(),
+        }
+    }
+}
+
+impl<Id, FieldName: Ord + Clone> Prefab<Id, FieldName> {
+    /// Get the type instance of the prefab.
+    pub fn instance(&self) -> &Arc<TypeDefinitionInstance<Id, FieldName>> {
+        self.template.instance()
+    }
+
+    /// Get a copy of the template value.
+    pub fn template(&self) -> Value<Id, FieldName> {
+        self.template.to_value()
+    }
+}
+
+impl<Id: Clone + Display, FieldName: Ord + Display + Clone + Borrow<str>> Prefab<Id, FieldName> {
+    /// Instantiate a copy of the template, with the values at the specified paths overridden, in
+    /// order.
+    pub fn instantiate<P: AsRef<[PathSegment]>>(
+        &self,
+        overrides: impl IntoIterator<Item = (P, serde_json::Value)>,
+    ) -> Result<Value<Id, FieldName>, PersistentValueError<Id, FieldName>> {
+        self.instantiate_with_options(overrides, &ParseOptions::default())
+    }
+
+    /// Instantiate a copy of the template, with the values at the specified paths overridden, in
+    /// order, using the specified parse options to parse the overrides.
+    pub fn instantiate_with_options<P: AsRef<[PathSegment]>>(
+        &self,
+        overrides: impl IntoIterator<Item = (P, serde_json::Value)>,
+        options: &ParseOptions,
+    ) -> Result<Value<Id, FieldName>, PersistentValueError<Id, FieldName>> {
+        Ok(self
+            .derive_with_options(overrides, options)?
+            .template
+            .to_value())
+    }
+
+    /// Derive a new prefab from this one, with the values at the specified paths overridden, in
+    /// order.
+    ///
+    /// Derived prefabs share their unmodified subtrees with this one.
+    pub fn derive<P: AsRef<[PathSegment]>>(
+        &self,
+        overrides: impl IntoIterator<Item = (P, serde_json::Value)>,
+    ) -> Result<Self, PersistentValueError<Id, FieldName>> {
+        self.derive_with_options(overrides, &ParseOptions::default())
+    }
+
+    /// Derive a new prefab from this one, with the values at the specified paths overridden, in
+    /// order, using the specified parse options to parse the overrides.
+    pub fn derive_with_options<P: AsRef<[PathSegment]>>(
+        &self,
+        overrides: impl IntoIterator<Item = (P, serde_json::Value)>,
+        options: &ParseOptions,
+    ) -> Result<Self, PersistentValueError<Id, FieldName>> {
+        let mut template = self.template.clone();
+
+        for (path, value) in overrides {
+            template.set_with_options(path.as_ref(), value, options)?;
+        }
+
+        Ok(Self { template })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        PathSegment,
+        test_support::owned::{FieldName, Id, Value, registry_from_json},
+    };
+
+    type Prefab = super::Prefab<Id, FieldName>;
+
+    fn key(key: &str) -> Vec<PathSegment> {
+        vec![PathSegment::DictionaryKey(key.to_owned())]
+    }
+
+    #[test]
+    fn test_prefab() {
+        let registry = registry_from_json(json!([
+            { "id": 1, "name": "Stat", "type": "string", "attributes": {} },
+            { "id": 2, "name": "Amount", "type": "int32", "attributes": { "max": 100 } },
+            { "id": 3, "name": "Stats", "type": "dictionary", "attributes": { "keys_type_id": 1, "values_type_id": 2 } },
+        ]));
+
+        let goblin_base = Prefab::new(
+            Value::parse_for(
+                registry.by_id[&3].clone(),
+                json!({ "attack": 5, "health": 20, "speed": 3 }),
+            )
+            .unwrap(),
+        );
+
+        let goblin_elite = goblin_base
+            .derive([
+                (key("attack"), json!(12)),
+                (key("health"), json!(45)),
+                (key("armor"), json!(4)),
+            ])
+            .unwrap();
+        assert_eq!(
+            goblin_elite.template().to_json().unwrap(),
+            json!({ "armor": 4, "attack": 12, "health": 45, "speed": 3 })
+        );
+
+        let goblin_king = goblin_elite
+            .instantiate([(key("speed"), json!(1))])
+            .unwrap();
+        assert_eq!(goblin_king.instance().name(), "Stats");
+        assert_eq!(
+            goblin_king.to_json().unwrap(),
+            json!({ "armor": 4, "attack": 12, "health": 45, "speed": 1 })
+        );
+
+        // Overrides are validated, and leave the prefab untouched.
+        let err = goblin_base
+            .instantiate([(key("speed"), json!(4)), (key("health"), json!(200))])
+            .unwrap_err();
+        assert!(err.to_string().contains("health"));
+        assert_eq!(
+            goblin_base.template().to_json().unwrap(),
+            json!({ "attack": 5, "health": 20, "speed": 3 })
+        );
+    }
+}
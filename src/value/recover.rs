@@ -0,0 +1,164 @@
+//! Recovery from errors while parsing GameSON values.
+
+use std::{borrow::Borrow, fmt::Display, sync::Arc};
+
+use crate::{
+    ParseOptions, TypeDefinitionInstance, type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::{ParseContext, ParseError, ParseErrorKind, Value, ValueImpl};
+
+impl<Id: Display, FieldName: Ord + Display + Clone + Borrow<str>> Value<Id, FieldName> {
+    /// Parse a GameSON value from a JSON value for a specified type instance, recovering from
+    /// errors.
+    ///
+    /// Invalid parts of the value are replaced by their declared default value or, if the type
+    /// has none, by a placeholder value that satisfies the type (an empty array, `0` or the
+    /// nearest bound for numbers, the first variant for enums...). Dictionary entries with an
+    /// invalid key are dropped.
+    ///
+    /// All the errors that were recovered from are returned alongside the value. If the value
+    /// could not be recovered at all, for instance because a resource limit was exceeded, `None`
+    /// is returned instead.
+    ///
+    /// This is meant for tools, such as editors, that need to open slightly invalid documents
+    /// for repair.
+    pub fn parse_for_recovering(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: serde_json::Value,
+        options: &ParseOptions,
+    ) -> (Option<Self>, Vec<ParseError<Id, FieldName>>) {
+        let mut context = ParseContext::new(options);
+        context.recovered = Some(Vec::new());
+
+        let checkpoint = context.checkpoint();
+        let result = ValueImpl::parse_for(&mut context, &instance, value)
+            .or_else(|err| context.recover(checkpoint, &instance, err));
+
+        let mut errors: Vec<_> = context
+            .recovered
+            .take()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(path, err)| ParseError {
+                instance: Arc::clone(&instance),
+                path,
+                err,
+                location: None,
+            })
+            .collect();
+
+        match result {
+            Ok(value) => (Some(Self { instance, value }), errors),
+            Err(err) => {
+                errors.push(ParseError {
+                    instance,
+                    path: context.path.into_path(),
+                    err,
+                    location: None,
+                });
+
+                (None, errors)
+            }
+        }
+    }
+}
+
+/// A snapshot of the parse context, to restore after recovering from an error.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Checkpoint {
+    /// The length of the path.
+    path_len: usize,
+
+    /// The nesting depth.
+    depth: usize,
+}
+
+impl ParseContext<'_> {
+    /// Take a snapshot of the parse context.
+    pub(super) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            path_len: self.path.len(),
+            depth: self.depth,
+        }
+    }
+
+    /// Skip an invalid value, in recovery mode.
+    ///
+    /// The error is recorded and the context is restored to the specified checkpoint. If not in
+    /// recovery mode or if the error cannot be recovered from, the error is returned instead.
+    pub(super) fn skip(
+        &mut self,
+        checkpoint: Checkpoint,
+        err: ParseErrorKind,
+    ) -> Result<(), ParseErrorKind> {
+        match &mut self.recovered {
+            Some(recovered) if err.is_recoverable() => {
+                recovered.push((self.path.to_path(), err));
+                self.path.truncate(checkpoint.path_len);
+                self.depth = checkpoint.depth;
+
+                Ok(())
+            }
+            _ => Err(err),
+        }
+    }
+
+    /// Replace an invalid value by a placeholder for the specified type instance, in recovery
+    /// mode.
+    ///
+    /// If not in recovery mode, if the error cannot be recovered from or if the type has no
+    /// possible placeholder, the error is returned instead.
+    pub(super) fn recover<Id, FieldName: Ord + Clone + Borrow<str>>(
+        &mut self,
+        checkpoint: Checkpoint,
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        err: ParseErrorKind,
+    ) -> Result<ValueImpl<FieldName>, ParseErrorKind> {
+        match ValueImpl::placeholder_for(instance) {
+            Some(value) => self.skip(checkpoint, err).map(|()| value),
+            None => Err(err),
+        }
+    }
+}
+
+impl ParseErrorKind {
+    /// Check whether the parse can recover from the error.
+    ///
+    /// Exceeding resource limits is never recoverable.
+    fn is_recoverable(&self) -> bool {
+        match self {
+            Self::InvalidDictionaryKey(err) | Self::InvalidDictionaryValue(err) => {
+                err.is_recoverable()
+            }
+            Self::TooDeep(_) | Self::TooManyNodes(_) => false,
+            _ => true,
+        }
+    }
+}
+
+impl<FieldName: Ord + Clone + Borrow<str>> ValueImpl<FieldName> {
+    /// Get a placeholder value for a specified type instance.
+    ///
+    /// This is the declared default value, if any, or the simplest valid value for the type.
+    /// Empty enum types have no possible placeholder.
+    fn placeholder_for<Id>(instance: &Arc<TypeDefinitionInstance<Id, FieldName>>) -> Option<Self> {
+        Self::default_for(instance).or_else(|| match &instance.attributes {
+            TypeAttributesInstance::Array(_) => Some(Self::Array(Vec::new())),
+            TypeAttributesInstance::Dictionary(_) => Some(Self::Dictionary(Vec::new())),
+            TypeAttributesInstance::Boolean(_) => Some(Self::Boolean(false)),
+            TypeAttributesInstance::Int32(a) => Some(Self::Int32(a.placeholder())),
+            TypeAttributesInstance::Int64(a) => Some(Self::Int64(a.placeholder())),
+            TypeAttributesInstance::Uint32(a) => Some(Self::Uint32(a.placeholder())),
+            TypeAttributesInstance::Uint64(a) => Some(Self::Uint64(a.placeholder())),
+            TypeAttributesInstance::Float32(a) => Some(Self::Float32(a.placeholder())),
+            TypeAttributesInstance::Float64(a) => Some(Self::Float64(a.placeholder())),
+            TypeAttributesInstance::String(_) => Some(Self::String(Default::default())),
+            TypeAttributesInstance::Enum(a) => a.values().next().cloned().map(Self::Enum),
+            #[cfg(feature = "uuid")]
+            TypeAttributesInstance::Uuid(_) => Some(Self::Uuid(uuid::Uuid::nil())),
+            #[cfg(feature = "bytes")]
+            TypeAttributesInstance::Bytes(_) => Some(Self::Bytes(Vec::new())),
+        })
+    }
+}
@@ -0,0 +1,240 @@
+//! Collection of the references, such as asset references, held by GameSON values.
+
+use std::{borrow::Borrow, sync::Arc};
+
+use crate::{TypeDefinitionInstance, type_attributes_instance::TypeAttributesInstance};
+
+use super::{PathSegment, Value, ValueImpl};
+
+/// The kinds of references collected by [`Value::collect_references`].
+///
+/// References are the values of the reference types, like an `AssetPath` string type or a
+/// `TextureId` integer type, and, with the `uuid` feature, optionally all the UUIDs. By default,
+/// nothing is collected.
+#[derive(Debug, Clone)]
+pub struct ReferenceFilter<Id> {
+    /// The identifiers of the reference types.
+    type_ids: Vec<Id>,
+
+    /// Whether all the UUIDs are references.
+    #[cfg(feature = "uuid")]
+    uuids: bool,
+}
+
+/// A reference held by a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference<Id> {
+    /// The path of the reference in the value.
+    path: Vec<PathSegment>,
+
+    /// The identifier of the type of the reference.
+    type_id: Id,
+
+    /// The referenced target, as text.
+    target: String,
+}
+
+impl<Id> Default for ReferenceFilter<Id> {
+    fn default() -> Self {
+        Self {
+            type_ids: Vec::new(),
+            #[cfg(feature = "uuid")]
+            uuids: false,
+        }
+    }
+}
+
+impl<Id: PartialEq> ReferenceFilter<Id> {
+    /// Create a filter that collects nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect the values of a reference type.
+    ///
+    /// Only string, enum, integer and UUID types can be reference types: the values of other
+    /// types are never collected.
+    pub fn with_type(mut self, type_id: Id) -> Self {
+        if !self.type_ids.contains(&type_id) {
+            self.type_ids.push(type_id);
+        }
+        self
+    }
+
+    /// Sets whether all the UUIDs are collected, whatever their types.
+    #[cfg(feature = "uuid")]
+    pub fn uuids(mut self, uuids: bool) -> Self {
+        self.uuids = uuids;
+        self
+    }
+
+    /// Check whether the values of a type are collected.
+    fn matches<FieldName: Ord>(&self, instance: &TypeDefinitionInstance<Id, FieldName>) -> bool {
+        #[cfg(feature = "uuid")]
+        if self.uuids && matches!(instance.attributes, TypeAttributesInstance::Uuid(_)) {
+            return true;
+        }
+
+        self.type_ids.contains(&instance.id)
+    }
+}
+
+impl<Id> Reference<Id> {
+    /// Get the path of the reference in the value.
+    ///
+    /// Dictionary keys that are references are reported at the path of their entry.
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+
+    /// Get the identifier of the type of the reference.
+    pub fn type_id(&self) -> &Id {
+        &self.type_id
+    }
+
+    /// Get the referenced target, as text: strings and enum values as is, and integers and UUIDs
+    /// as formatted.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+}
+
+impl<Id: PartialEq + Clone, FieldName: Ord + Borrow<str>> Value<Id, FieldName> {
+    /// Collect the references held by the value, along with their paths, in depth-first order.
+    ///
+    /// This allows build tooling to compute the dependency graph of assets directly from
+    /// validated data.
+    pub fn collect_references(&self, filter: &ReferenceFilter<Id>) -> Vec<Reference<Id>> {
+        let mut references = Vec::new();
+
+        self.value
+            .collect_references_for(&self.instance, filter, &mut Vec::new(), &mut references);
+
+        references
+    }
+}
+
+impl<FieldName: Ord + Borrow<str>> ValueImpl<FieldName> {
+    /// Collect the references held by the value and its descendants.
+    fn collect_references_for<Id: PartialEq + Clone>(
+        &self,
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        filter: &ReferenceFilter<Id>,
+        path: &mut Vec<PathSegment>,
+        references: &mut Vec<Reference<Id>>,
+    ) {
+        match (self, &instance.attributes) {
+            (Self::Array(items), TypeAttributesInstance::Array(a)) => {
+                for (i, item) in items.iter().enumerate() {
+                    path.push(PathSegment::ArrayIndex(i));
+                    item.collect_references_for(a.items_type_id(), filter, path, references);
+                    path.pop();
+                }
+            }
+            (Self::Dictionary(entries), TypeAttributesInstance::Dictionary(a)) => {
+                for (key, value) in entries {
+                    path.push(PathSegment::DictionaryKey(key.key_string().into_owned()));
+                    key.collect_references_for(a.keys_type_id(), filter, path, references);
+                    value.collect_references_for(a.values_type_id(), filter, path, references);
+                    path.pop();
+                }
+            }
+            (value, _) if filter.matches(instance) => {
+                let target = match value {
+                    Self::String(v) => v.to_string(),
+                    Self::Enum(v) => v.borrow().to_owned(),
+                    Self::Int32(v) => v.to_string(),
+                    Self::Int64(v) => v.to_string(),
+                    Self::Uint32(v) => v.to_string(),
+                    Self::Uint64(v) => v.to_string(),
+                    #[cfg(feature = "uuid")]
+                    Self::Uuid(v) => v.to_string(),
+                    _ => return,
+                };
+
+                references.push(Reference {
+                    path: path.clone(),
+                    type_id: instance.id.clone(),
+                    target,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        PathSegment,
+        test_support::owned::{Value, registry_from_json},
+    };
+
+    use super::ReferenceFilter;
+
+    #[test]
+    fn test_collect_references() {
+        let registry = registry_from_json(json!([
+            { "id": 1, "name": "AssetPath", "type": "string", "attributes": {} },
+            { "id": 2, "name": "Name", "type": "string", "attributes": {} },
+            { "id": 3, "name": "TextureId", "type": "uint32", "attributes": {} },
+            { "id": 4, "name": "Textures", "type": "array", "attributes": { "items_type_id": 3 } },
+            { "id": 5, "name": "Sounds", "type": "dictionary", "attributes": { "keys_type_id": 2, "values_type_id": 1 } },
+            { "id": 6, "name": "SoundsByAsset", "type": "dictionary", "attributes": { "keys_type_id": 1, "values_type_id": 4 } },
+        ]));
+
+        let sounds = Value::parse_for(
+            registry.by_id[&5].clone(),
+            json!({ "hit": "sfx/hit.wav", "miss": "sfx/miss.wav" }),
+        )
+        .unwrap();
+
+        assert!(
+            sounds
+                .collect_references(&ReferenceFilter::new())
+                .is_empty()
+        );
+
+        let references = sounds.collect_references(&ReferenceFilter::new().with_type(1));
+        assert_eq!(
+            references
+                .iter()
+                .map(|reference| (reference.path(), reference.target()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    [PathSegment::DictionaryKey("hit".to_owned())].as_slice(),
+                    "sfx/hit.wav"
+                ),
+                (
+                    [PathSegment::DictionaryKey("miss".to_owned())].as_slice(),
+                    "sfx/miss.wav"
+                ),
+            ]
+        );
+
+        let value = Value::parse_for(
+            registry.by_id[&6].clone(),
+            json!({ "models/goblin.mesh": [7, 12] }),
+        )
+        .unwrap();
+        let references =
+            value.collect_references(&ReferenceFilter::new().with_type(1).with_type(3));
+        assert_eq!(
+            references
+                .iter()
+                .map(|reference| (*reference.type_id(), reference.target()))
+                .collect::<Vec<_>>(),
+            [(1, "models/goblin.mesh"), (3, "7"), (3, "12")]
+        );
+        assert_eq!(
+            references[2].path(),
+            [
+                PathSegment::DictionaryKey("models/goblin.mesh".to_owned()),
+                PathSegment::ArrayIndex(1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,385 @@
+//! Conversions between GameSON values and reflected Rust values.
+
+use std::{
+    borrow::{Borrow, Cow},
+    fmt::Display,
+    sync::Arc,
+};
+
+use bevy_reflect::{
+    DynamicArray, DynamicEnum, DynamicList, DynamicMap, DynamicSet, DynamicTupleStruct,
+    DynamicVariant, FromReflect, Map, PartialReflect, Reflect, ReflectFromReflect, ReflectRef, Set,
+    TypeInfo, TypeRegistration, Typed, VariantType,
+};
+
+use crate::{
+    DictionaryWireForm, ParseOptions, ReflectError, TypeDefinitionInstance, reflect::unsupported,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::{ParseError, ParseErrorKind, ParseErrorPath, Value, ValueImpl};
+
+impl<Id, FieldName: Ord + Borrow<str>> Value<Id, FieldName> {
+    /// Convert the value to a dynamic reflected value.
+    ///
+    /// Arrays become dynamic lists, dictionaries dynamic maps, enums dynamic enums with a unit
+    /// variant, and the other values the matching Rust values: UUIDs become strings, and bytes
+    /// vectors of bytes. To get a concrete Rust value, use [`to_reflect_as`](Self::to_reflect_as)
+    /// or [`to_reflect_for`](Self::to_reflect_for) instead, which also convert the numbers to
+    /// the width of the Rust fields.
+    pub fn to_reflect(&self) -> Box<dyn PartialReflect> {
+        to_dynamic(&self.value, &self.instance, None)
+            .expect("values always convert to dynamic values")
+    }
+
+    /// Convert the value to a Rust value of a reflected type.
+    pub fn to_reflect_as<T: FromReflect + Typed>(&self) -> Result<T, ReflectError> {
+        let dynamic = to_dynamic(&self.value, &self.instance, Some(T::type_info()))?;
+
+        T::from_reflect(dynamic.as_ref()).ok_or_else(|| ReflectError::Mismatch {
+            type_path: T::type_path().to_owned(),
+        })
+    }
+
+    /// Convert the value to a Rust value of a reflected type, designated by its registration in
+    /// a type registry.
+    ///
+    /// The type must be registered with its `ReflectFromReflect` type data, as it is by default
+    /// when deriving `Reflect`.
+    pub fn to_reflect_for(
+        &self,
+        registration: &TypeRegistration,
+    ) -> Result<Box<dyn Reflect>, ReflectError> {
+        let type_info = registration.type_info();
+        let mismatch = || ReflectError::Mismatch {
+            type_path: type_info.type_path().to_owned(),
+        };
+        let from_reflect = registration
+            .data::<ReflectFromReflect>()
+            .ok_or_else(|| unsupported(type_info, "the type has no `ReflectFromReflect` data"))?;
+        let dynamic = to_dynamic(&self.value, &self.instance, Some(type_info))?;
+
+        from_reflect
+            .from_reflect(dynamic.as_ref())
+            .ok_or_else(mismatch)
+    }
+}
+
+/// Convert a value to a dynamic reflected value, for a reflected type, if known.
+fn to_dynamic<Id, FieldName: Ord + Borrow<str>>(
+    value: &ValueImpl<FieldName>,
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+    type_info: Option<&'static TypeInfo>,
+) -> Result<Box<dyn PartialReflect>, ReflectError> {
+    let mismatch = || ReflectError::Mismatch {
+        type_path: type_info.map_or("?", TypeInfo::type_path).to_owned(),
+    };
+
+    /// Convert a number to the reflected number type, if any, or to its own type.
+    macro_rules! number {
+        ($n:expr, $($num:ty),*) => {
+            match type_info {
+                $(Some(type_info) if type_info.is::<$num>() => {
+                    Box::new(<$num>::try_from($n).map_err(|_| mismatch())?)
+                        as Box<dyn PartialReflect>
+                })*
+                Some(type_info) if type_info.is::<f32>() => Box::new($n as f32),
+                Some(type_info) if type_info.is::<f64>() => Box::new($n as f64),
+                _ => Box::new($n),
+            }
+        };
+    }
+
+    if let Some(TypeInfo::TupleStruct(info)) = type_info
+        && info.field_len() == 1
+    {
+        let field = info.field_at(0).and_then(|field| field.type_info());
+        let mut dynamic = DynamicTupleStruct::default();
+        dynamic.insert_boxed(to_dynamic(value, instance, field)?);
+        dynamic.set_represented_type(type_info);
+
+        return Ok(Box::new(dynamic));
+    }
+
+    Ok(match (value, &instance.attributes) {
+        (ValueImpl::Array(items), TypeAttributesInstance::Array(a)) => {
+            let item_info = match type_info {
+                Some(TypeInfo::List(info)) => info.item_info(),
+                Some(TypeInfo::Array(info)) => info.item_info(),
+                _ => None,
+            };
+            let items = items
+                .iter()
+                .map(|item| to_dynamic(item, a.items_type_id(), item_info))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            match type_info {
+                Some(TypeInfo::Array(_)) => {
+                    let mut dynamic = DynamicArray::new(items.into_boxed_slice());
+                    dynamic.set_represented_type(type_info);
+                    Box::new(dynamic)
+                }
+                Some(TypeInfo::Set(_)) => {
+                    let mut dynamic = DynamicSet::default();
+                    items.into_iter().for_each(|item| {
+                        dynamic.insert_boxed(item);
+                    });
+                    dynamic.set_represented_type(type_info);
+                    Box::new(dynamic)
+                }
+                _ => {
+                    let mut dynamic: DynamicList = items.into_iter().collect();
+                    dynamic
+                        .set_represented_type(type_info.filter(|t| matches!(t, TypeInfo::List(_))));
+                    Box::new(dynamic)
+                }
+            }
+        }
+        (ValueImpl::Dictionary(entries), TypeAttributesInstance::Dictionary(a)) => {
+            let (key_info, value_info) = match type_info {
+                Some(TypeInfo::Map(info)) => (info.key_info(), info.value_info()),
+                _ => (None, None),
+            };
+            let mut dynamic = DynamicMap::default();
+
+            for (key, value) in entries {
+                dynamic.insert_boxed(
+                    to_dynamic(key, a.keys_type_id(), key_info)?,
+                    to_dynamic(value, a.values_type_id(), value_info)?,
+                );
+            }
+
+            dynamic.set_represented_type(type_info.filter(|t| matches!(t, TypeInfo::Map(_))));
+            Box::new(dynamic)
+        }
+        (ValueImpl::Boolean(b), _) => Box::new(*b),
+        (ValueImpl::Int32(n), _) => number!(*n, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize),
+        (ValueImpl::Int64(n), _) => number!(*n, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize),
+        (ValueImpl::Uint32(n), _) => {
+            number!(*n, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize)
+        }
+        (ValueImpl::Uint64(n), _) => {
+            number!(*n, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize)
+        }
+        (ValueImpl::Float32(n), _) => match type_info {
+            Some(type_info) if type_info.is::<f64>() => Box::new(f64::from(*n)),
+            _ => Box::new(*n),
+        },
+        (ValueImpl::Float64(n), _) => match type_info {
+            Some(type_info) if type_info.is::<f32>() => Box::new(*n as f32),
+            _ => Box::new(*n),
+        },
+        (ValueImpl::String(s), _) => Box::new(s.as_str().to_owned()),
+        (ValueImpl::Enum(name), _) => match type_info {
+            Some(type_info) if type_info.is::<String>() => Box::new(name.borrow().to_owned()),
+            _ => {
+                let mut dynamic = DynamicEnum::new(name.borrow(), DynamicVariant::Unit);
+                dynamic.set_represented_type(type_info.filter(|t| matches!(t, TypeInfo::Enum(_))));
+                Box::new(dynamic)
+            }
+        },
+        #[cfg(feature = "uuid")]
+        (ValueImpl::Uuid(uuid), _) => Box::new(uuid.to_string()),
+        #[cfg(feature = "bytes")]
+        (ValueImpl::Bytes(bytes), _) => Box::new(bytes.clone()),
+        (ValueImpl::Array(_) | ValueImpl::Dictionary(_), _) => {
+            unreachable!("values always match their type instances")
+        }
+    })
+}
+
+impl<Id: Display, FieldName: Ord + Display + Clone + Borrow<str>> Value<Id, FieldName> {
+    /// Build a GameSON value from a reflected Rust value for a specified type instance.
+    ///
+    /// This accepts the Rust values that [`to_reflect_as`](Self::to_reflect_as) produces, as
+    /// well as dynamic reflected values: tuple structs with a single field are unwrapped, enums
+    /// must be unit variants, and lists, arrays, sets and maps must match arrays and
+    /// dictionaries. The value is then parsed and validated against the type instance.
+    pub fn from_reflect(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: &dyn PartialReflect,
+    ) -> Result<Self, ParseError<Id, FieldName>> {
+        match reflect_to_json(&instance, value) {
+            Ok(value) => Self::parse_for_with_options(instance, value, &ParseOptions::default()),
+            Err(reason) => Err(ParseError {
+                instance,
+                path: ParseErrorPath::default(),
+                err: ParseErrorKind::Unserializable(reason),
+                location: None,
+            }),
+        }
+    }
+}
+
+/// Convert a reflected value to its JSON representation for a type instance.
+fn reflect_to_json<Id, FieldName: Ord>(
+    instance: &TypeDefinitionInstance<Id, FieldName>,
+    value: &dyn PartialReflect,
+) -> Result<serde_json::Value, String> {
+    let unsupported = || format!("unsupported reflected type `{}`", value.reflect_type_path());
+    let items = |items: &mut dyn Iterator<Item = &dyn PartialReflect>| match &instance.attributes {
+        TypeAttributesInstance::Array(a) => items
+            .map(|item| reflect_to_json(a.items_type_id(), item))
+            .collect(),
+        _ => Err(format!(
+            "unexpected sequence `{}` for a non-array type",
+            value.reflect_type_path()
+        )),
+    };
+
+    /// Convert an opaque reflected value to JSON, if it is of one of the specified types.
+    macro_rules! opaque {
+        ($($t:ty),*) => {
+            $(if let Some(v) = value.try_downcast_ref::<$t>() {
+                return Ok(serde_json::json!(v));
+            })*
+        };
+    }
+
+    match value.reflect_ref() {
+        ReflectRef::TupleStruct(s) if s.field_len() == 1 => {
+            reflect_to_json(instance, s.field(0).expect("the field exists"))
+        }
+        ReflectRef::List(l) => items(&mut l.iter()),
+        ReflectRef::Array(a) => items(&mut a.iter()),
+        ReflectRef::Set(s) => items(&mut s.iter()),
+        ReflectRef::Map(m) => {
+            let TypeAttributesInstance::Dictionary(a) = &instance.attributes else {
+                return Err(format!(
+                    "unexpected map `{}` for a non-dictionary type",
+                    value.reflect_type_path()
+                ));
+            };
+            let mut entries = Vec::new();
+
+            for (key, value) in m.iter() {
+                entries.push((
+                    reflect_to_json(a.keys_type_id(), key)?,
+                    reflect_to_json(a.values_type_id(), value)?,
+                ));
+            }
+
+            Ok(match a.wire_form() {
+                DictionaryWireForm::Object => serde_json::Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| match key {
+                            serde_json::Value::String(key) => (key, value),
+                            key => (key.to_string(), value),
+                        })
+                        .collect(),
+                ),
+                DictionaryWireForm::Pairs => serde_json::Value::Array(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| serde_json::json!([key, value]))
+                        .collect(),
+                ),
+            })
+        }
+        ReflectRef::Enum(e) if e.variant_type() == VariantType::Unit => {
+            Ok(serde_json::Value::String(e.variant_name().to_owned()))
+        }
+        ReflectRef::Opaque(_) => {
+            opaque!(
+                bool,
+                i8,
+                i16,
+                i32,
+                i64,
+                isize,
+                u8,
+                u16,
+                u32,
+                u64,
+                usize,
+                f32,
+                f64,
+                String,
+                Cow<'static, str>
+            );
+
+            Err(unsupported())
+        }
+        _ => Err(unsupported()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::owned::{TypeDefinitionRegistry, Value};
+    use std::collections::BTreeMap;
+
+    use bevy_reflect::{FromReflect, Reflect, TypeRegistry};
+    use serde_json::json;
+
+    use crate::{ReflectError, SequentialIdAllocator, UnidentifiedTypeDefinition};
+
+    #[derive(Debug, PartialEq, Reflect)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    #[derive(Debug, PartialEq, Reflect)]
+    struct Levels(BTreeMap<u8, Vec<Color>>);
+
+    #[test]
+    fn test_reflect() {
+        let mut registry = TypeDefinitionRegistry::default();
+        registry
+            .register_unidentified(
+                UnidentifiedTypeDefinition::from_type_info(
+                    <Levels as bevy_reflect::Typed>::type_info(),
+                )
+                .unwrap(),
+                &mut SequentialIdAllocator::new(1),
+            )
+            .into_result()
+            .unwrap();
+        let instance = &registry.by_name["Levels"];
+
+        let value = Value::parse_for(
+            instance.clone(),
+            json!([[1, ["Red"]], [200, ["Green", "Red"]]]),
+        )
+        .unwrap();
+        let levels = Levels(BTreeMap::from([
+            (1, vec![Color::Red]),
+            (200, vec![Color::Green, Color::Red]),
+        ]));
+        assert_eq!(value.to_reflect_as::<Levels>().unwrap(), levels);
+
+        let mut type_registry = TypeRegistry::new();
+        type_registry.register::<Levels>();
+        let reflected = value
+            .to_reflect_for(type_registry.get(std::any::TypeId::of::<Levels>()).unwrap())
+            .unwrap();
+        assert_eq!(reflected.downcast_ref::<Levels>(), Some(&levels));
+
+        assert_eq!(
+            Levels::from_reflect(value.to_reflect().as_ref()),
+            None,
+            "dynamic values keep the GameSON widths of the numbers"
+        );
+
+        let value = Value::from_reflect(instance.clone(), &levels).unwrap();
+        assert_eq!(
+            value.to_json().unwrap(),
+            json!([[1, ["Red"]], [200, ["Green", "Red"]]])
+        );
+        assert_eq!(
+            Value::from_reflect(instance.clone(), &Color::Red)
+                .unwrap_err()
+                .kind()
+                .to_string(),
+            "expected dictionary of pairs, found string"
+        );
+
+        let value = Value::parse_for(instance.clone(), json!([[200, []]])).unwrap();
+        assert!(matches!(
+            value.to_reflect_as::<BTreeMap<i8, Vec<u8>>>(),
+            Err(ReflectError::Mismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+//! Aggregated validation results.
+
+use serde::{Serialize, ser::SerializeStruct};
+
+use super::{ParseErrorKind, ParseWarning, ParseWarningKind, PathSegment, Violation};
+
+/// The severity of a finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The value is valid, but something is worth looking at.
+    Warning,
+
+    /// The value is invalid.
+    Error,
+}
+
+/// The result of validating a JSON value against a type instance.
+///
+/// The report serializes to JSON as a flat list of diagnostics, suitable for CI artifacts.
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    /// The violations, which make the value invalid.
+    pub(super) errors: Vec<Violation>,
+
+    /// The warnings, which do not.
+    pub(super) warnings: Vec<ParseWarning>,
+}
+
+impl ValidationReport {
+    /// Check whether the value is valid, that is whether the report contains no errors.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Get the highest severity of the findings, if there are any.
+    pub fn severity(&self) -> Option<Severity> {
+        if !self.errors.is_empty() {
+            Some(Severity::Error)
+        } else if !self.warnings.is_empty() {
+            Some(Severity::Warning)
+        } else {
+            None
+        }
+    }
+
+    /// Get the violations.
+    pub fn errors(&self) -> &[Violation] {
+        &self.errors
+    }
+
+    /// Get the warnings.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+}
+
+impl Serialize for ValidationReport {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let diagnostics: Vec<_> = self
+            .errors
+            .iter()
+            .map(|err| Diagnostic {
+                severity: Severity::Error,
+                code: err.kind().code(),
+                path: err.path(),
+                message: err.kind().to_string(),
+            })
+            .chain(self.warnings.iter().map(|warning| Diagnostic {
+                severity: Severity::Warning,
+                code: warning.kind().code(),
+                path: warning.path(),
+                message: warning.kind().to_string(),
+            }))
+            .collect();
+
+        let mut s = serializer.serialize_struct("ValidationReport", 2)?;
+        s.serialize_field("valid", &self.is_valid())?;
+        s.serialize_field("diagnostics", &diagnostics)?;
+        s.end()
+    }
+}
+
+/// A serialized finding.
+#[derive(Serialize)]
+struct Diagnostic<'a> {
+    /// The severity.
+    severity: Severity,
+
+    /// The stable code of the finding.
+    code: &'static str,
+
+    /// The path of the offending value.
+    path: &'a [PathSegment],
+
+    /// The human-readable message.
+    message: String,
+}
+
+impl ParseErrorKind {
+    /// Get a stable code identifying the kind of error, for tooling.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::UnexpectedValue { .. } => "unexpected_value",
+            Self::InvalidDictionaryKey(_) => "invalid_dictionary_key",
+            Self::InvalidDictionaryValue(_) => "invalid_dictionary_value",
+            Self::InvalidDictionaryPair(_) => "invalid_dictionary_pair",
+            Self::DuplicateKey(_) => "duplicate_key",
+            Self::InvalidInt32(_) => "invalid_int32",
+            Self::InvalidInt64(_) => "invalid_int64",
+            Self::InvalidUint32(_) => "invalid_uint32",
+            Self::InvalidUint64(_) => "invalid_uint64",
+            Self::InvalidFloat32(_) => "invalid_float32",
+            Self::UnrepresentableFloat32(_) => "unrepresentable_float32",
+            Self::InvalidFloat64(_) => "invalid_float64",
+            Self::UnknownEnumValue(_) => "unknown_enum_value",
+            #[cfg(feature = "uuid")]
+            Self::InvalidUuid(_) => "invalid_uuid",
+            #[cfg(feature = "bytes")]
+            Self::InvalidBytes(_) => "invalid_bytes",
+            Self::TooDeep(_) => "too_deep",
+            Self::ArrayTooLong(_) => "array_too_long",
+            Self::StringTooLong(..) => "string_too_long",
+            Self::TooManyNodes(_) => "too_many_nodes",
+            Self::InvalidJson(_) => "invalid_json",
+            Self::InvalidBinary(_) => "invalid_binary",
+            #[cfg(feature = "bson")]
+            Self::InvalidBson(_) => "invalid_bson",
+            #[cfg(feature = "cbor")]
+            Self::InvalidCbor(_) => "invalid_cbor",
+            #[cfg(feature = "rmp")]
+            Self::InvalidMsgpack(_) => "invalid_msgpack",
+            Self::Unserializable(_) => "unserializable",
+            Self::ConstraintViolation(_) => "constraint_violation",
+            Self::MissingRequiredEntry { .. } => "missing_required_entry",
+            Self::InvariantViolation(_) => "invariant_violation",
+            Self::Rejected(_) => "rejected",
+        }
+    }
+}
+
+impl ParseWarningKind {
+    /// Get a stable code identifying the kind of warning, for tooling.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::EnumAliasNormalized { .. } => "enum_alias_normalized",
+            Self::ArrayNearLimit { .. } => "array_near_limit",
+            Self::StringNearLimit { .. } => "string_near_limit",
+        }
+    }
+}
@@ -0,0 +1,888 @@
+//! Parsing of GameSON values straight from a deserializer.
+
+use std::{
+    borrow::{Borrow, Cow},
+    fmt::{self, Display},
+    marker::PhantomData,
+    sync::Arc,
+};
+
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+
+use crate::{
+    DictionaryWireForm, NullPolicy, ParseOptions, TypeDefinitionInstance, metrics::Measure,
+    type_attributes::DictionaryTypeAttributes, type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::{
+    Keys, ParseContext, ParseError, ParseErrorKind, PathSegment, SourceLocation, Value, ValueImpl,
+    ValueString, check_constraints, validator,
+};
+
+/// A seed to deserialize a GameSON value for a specified type instance.
+///
+/// This allows parsing values straight from any self-describing format supported by `serde`,
+/// without going through an intermediate `serde_json::Value`.
+pub struct ValueSeed<'a, Id, FieldName: Ord> {
+    /// The type instance.
+    instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The parse options.
+    options: &'a ParseOptions,
+}
+
+impl<'a, Id, FieldName: Ord> ValueSeed<'a, Id, FieldName> {
+    /// Create a new value seed for a specified type instance.
+    pub fn new(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        options: &'a ParseOptions,
+    ) -> Self {
+        Self { instance, options }
+    }
+}
+
+impl<'de, Id, FieldName> DeserializeSeed<'de> for ValueSeed<'_, Id, FieldName>
+where
+    Id: Display,
+    FieldName: Ord + Display + Clone + Borrow<str>,
+{
+    type Value = Value<Id, FieldName>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut context = ParseContext::new(self.options);
+
+        let result = ValueImplSeed {
+            context: &mut context,
+            instance: &self.instance,
+            strings: PhantomData,
+        }
+        .deserialize(deserializer);
+
+        match result {
+            Ok(value) => Ok(Value {
+                instance: self.instance,
+                value,
+            }),
+            Err(err) => match context.error.take() {
+                Some(err) => Err(de::Error::custom(ParseError {
+                    instance: self.instance,
+                    path: context.path.into_path(),
+                    err,
+                    location: None,
+                })),
+                None => Err(err),
+            },
+        }
+    }
+}
+
+/// Parse a GameSON value from a JSON deserializer.
+///
+/// The whole input must be consumed by the value. Errors are reported with their location in the
+/// source text.
+pub(crate) fn parse_json<'de, R, Id, FieldName>(
+    instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+    deserializer: serde_json::Deserializer<R>,
+    options: &ParseOptions,
+) -> Result<Value<Id, FieldName>, ParseError<Id, FieldName>>
+where
+    R: serde_json::de::Read<'de>,
+    Id: Display,
+    FieldName: Ord + Display + Clone + Borrow<str>,
+{
+    let value = parse_json_impl(&instance, deserializer, options)?;
+
+    Ok(Value { instance, value })
+}
+
+/// Parse a GameSON value implementation from a JSON deserializer, storing its strings as `S`.
+pub(super) fn parse_json_impl<'de, R, Id, FieldName, S>(
+    instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+    mut deserializer: serde_json::Deserializer<R>,
+    options: &ParseOptions,
+) -> Result<ValueImpl<FieldName, S>, ParseError<Id, FieldName>>
+where
+    R: serde_json::de::Read<'de>,
+    Id: Display,
+    FieldName: Ord + Display + Clone + Borrow<str>,
+    S: SeedString<'de>,
+{
+    let measure = Measure::start(options);
+    let mut context = ParseContext::new(options);
+
+    let result = ValueImplSeed {
+        context: &mut context,
+        instance,
+        strings: PhantomData,
+    }
+    .deserialize(&mut deserializer)
+    .and_then(|value| deserializer.end().map(|()| value))
+    .map_err(|err| {
+        // `serde_json` records the location of every error raised while deserializing,
+        // including ours.
+        let location = (err.line() > 0).then(|| SourceLocation {
+            line: err.line(),
+            column: err.column(),
+        });
+
+        let err = match context.error.take() {
+            Some(err) => err,
+            None => {
+                let message = err.to_string();
+                let suffix = format!(" at line {} column {}", err.line(), err.column());
+
+                ParseErrorKind::InvalidJson(match message.strip_suffix(&suffix) {
+                    Some(message) => message.to_owned(),
+                    None => message,
+                })
+            }
+        };
+
+        (err, location)
+    });
+
+    if let Some(measure) = measure {
+        measure.parsed(
+            instance.name.borrow(),
+            result.as_ref().map(|_| ()).map_err(|(err, _)| err),
+        );
+    }
+
+    result.map_err(|(err, location)| ParseError {
+        instance: Arc::clone(instance),
+        path: context.path.into_path(),
+        err,
+        location,
+    })
+}
+
+impl ParseContext<'_> {
+    /// Convert a parse result into a deserialization result.
+    ///
+    /// The parse error, if any, is kept in the context so that it can be reported accurately.
+    fn raise<T, E: de::Error>(&mut self, result: Result<T, ParseErrorKind>) -> Result<T, E> {
+        result.map_err(|err| {
+            let de_err = E::custom(&err);
+            self.error = Some(err);
+
+            de_err
+        })
+    }
+}
+
+/// The storage of the strings of the values parsed from a deserializer.
+pub(super) trait SeedString<'de>: Clone + PartialEq + Borrow<str> + From<String> {
+    /// Store a string of the input.
+    fn from_input(v: Cow<'de, str>) -> Self;
+
+    /// Run the validator of a type instance, if it has one, on a parsed value.
+    fn run_validator<Id, FieldName: Ord + Clone>(
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: ValueImpl<FieldName, Self>,
+    ) -> Result<ValueImpl<FieldName, Self>, ParseErrorKind>;
+}
+
+impl<'de> SeedString<'de> for ValueString {
+    fn from_input(v: Cow<'de, str>) -> Self {
+        v.into()
+    }
+
+    fn run_validator<Id, FieldName: Ord + Clone>(
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        value: ValueImpl<FieldName>,
+    ) -> Result<ValueImpl<FieldName>, ParseErrorKind> {
+        validator::run_validator(instance, value)
+    }
+}
+
+/// A seed to deserialize a GameSON value implementation.
+pub(super) struct ValueImplSeed<'c, 'o, Id, FieldName: Ord, S> {
+    /// The parse context.
+    pub(super) context: &'c mut ParseContext<'o>,
+
+    /// The type instance.
+    pub(super) instance: &'c Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The storage of the strings.
+    pub(super) strings: PhantomData<S>,
+}
+
+impl<'de, Id, FieldName, S> DeserializeSeed<'de> for ValueImplSeed<'_, '_, Id, FieldName, S>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+    S: SeedString<'de>,
+{
+    type Value = ValueImpl<FieldName, S>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let Self {
+            context, instance, ..
+        } = self;
+
+        let result = context.enter_node();
+        context.raise(result)?;
+
+        let value = deserializer.deserialize_any(ValueImplSeed {
+            context: &mut *context,
+            instance,
+            strings: PhantomData,
+        })?;
+
+        let result = check_constraints(instance, &value);
+        context.raise(result)?;
+
+        context.raise(S::run_validator(instance, value))
+    }
+}
+
+impl<'de, Id, FieldName, S> Visitor<'de> for ValueImplSeed<'_, '_, Id, FieldName, S>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+    S: SeedString<'de>,
+{
+    type Value = ValueImpl<FieldName, S>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a value of type {}", self.instance.attributes.kind())
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        let result = match &self.instance.attributes {
+            TypeAttributesInstance::Boolean(_) => Ok(ValueImpl::Boolean(v)),
+            attributes => Err(ParseErrorKind::UnexpectedValue {
+                expected: attributes.kind(),
+                found: "boolean",
+            }),
+        };
+
+        self.context.raise(result)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        let result =
+            ValueImpl::parse_number(self.context.options, &self.instance.attributes, &v.into());
+
+        self.context.raise(result)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        let result =
+            ValueImpl::parse_number(self.context.options, &self.instance.attributes, &v.into());
+
+        self.context.raise(result)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        let result = match serde_json::Number::from_f64(v) {
+            Some(v) => ValueImpl::parse_number(self.context.options, &self.instance.attributes, &v),
+            None => Err(ParseErrorKind::UnexpectedValue {
+                expected: self.instance.attributes.kind(),
+                found: "non-finite number",
+            }),
+        };
+
+        self.context.raise(result)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        let result = parse_str(
+            self.context,
+            &self.instance.attributes,
+            Cow::Borrowed(v),
+            |v| S::from(v.into_owned()),
+        );
+
+        self.context.raise(result)
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        let result = parse_str(
+            self.context,
+            &self.instance.attributes,
+            Cow::Borrowed(v),
+            S::from_input,
+        );
+
+        self.context.raise(result)
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        let result = parse_str(
+            self.context,
+            &self.instance.attributes,
+            Cow::Owned(v),
+            |v| S::from(v.into_owned()),
+        );
+
+        self.context.raise(result)
+    }
+
+    #[cfg(any(feature = "bytes", feature = "uuid"))]
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        // Formats with native bytes need not encode bytes and UUIDs as strings.
+        let result = match &self.instance.attributes {
+            #[cfg(feature = "bytes")]
+            TypeAttributesInstance::Bytes(_) => Ok(ValueImpl::Bytes(v.to_vec())),
+            #[cfg(feature = "uuid")]
+            TypeAttributesInstance::Uuid(_) => uuid::Uuid::from_slice(v)
+                .map(ValueImpl::Uuid)
+                .map_err(ParseErrorKind::from),
+            attributes => Err(ParseErrorKind::UnexpectedValue {
+                expected: attributes.kind(),
+                found: "bytes",
+            }),
+        };
+
+        self.context.raise(result)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        let result = ValueImpl::parse_null(self.context, self.instance);
+
+        self.context.raise(result)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.visit_unit()
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let a = match &self.instance.attributes {
+            TypeAttributesInstance::Array(a) => a,
+            TypeAttributesInstance::Dictionary(a) if a.wire_form() == DictionaryWireForm::Pairs => {
+                return self.visit_pairs(seq, a);
+            }
+            attributes => {
+                return self.context.raise(Err(ParseErrorKind::UnexpectedValue {
+                    expected: attributes.kind(),
+                    found: "array",
+                }));
+            }
+        };
+
+        let result = self.context.enter_collection();
+        self.context.raise(result)?;
+
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or_default().min(1024));
+        let mut len = 0;
+
+        loop {
+            let result = self.context.check_array_length(len + 1);
+            self.context.path.push(PathSegment::ArrayIndex(len));
+
+            let item = seq.next_element_seed(ItemSeed(ValueImplSeed {
+                context: &mut *self.context,
+                instance: a.items_type_id(),
+                strings: PhantomData,
+            }))?;
+
+            match item {
+                Some(item) => {
+                    self.context.raise(result)?;
+                    items.extend(item);
+                }
+                None => break,
+            }
+
+            // We only must pop if the parse was successful.
+            self.context.path.pop();
+            len += 1;
+        }
+
+        // The last, missing, item was never parsed.
+        self.context.path.pop();
+        self.context.leave_collection();
+        self.context.check_array_near_limit(len);
+
+        Ok(ValueImpl::Array(items))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let a = match &self.instance.attributes {
+            TypeAttributesInstance::Dictionary(a)
+                if a.wire_form() == DictionaryWireForm::Object =>
+            {
+                a
+            }
+            attributes => {
+                return self.context.raise(Err(ParseErrorKind::UnexpectedValue {
+                    expected: attributes.kind(),
+                    found: "object",
+                }));
+            }
+        };
+
+        let result = self.context.enter_collection();
+        self.context.raise(result)?;
+
+        let mut items = Vec::new();
+        let mut keys = Keys::default();
+
+        while let Some(k) = map.next_key_seed(KeySeed)? {
+            // Keys borrowed from the input are never copied, unless they are reported.
+            let mark = self.context.push_key();
+
+            let result = self
+                .context
+                .enter_node()
+                .and_then(|()| {
+                    parse_str(
+                        self.context,
+                        &a.keys_type_id().attributes,
+                        k.clone(),
+                        S::from_input,
+                    )
+                })
+                .map_err(Box::new)
+                .map_err(ParseErrorKind::InvalidDictionaryKey)
+                .and_then(|key| keys.check(&items, &key).map(|hash| (key, hash)));
+            let (key, hash) = self
+                .context
+                .raise(result)
+                .inspect_err(|_| self.context.fill_key(mark, &k))?;
+
+            let value = map
+                .next_value_seed(ItemSeed(ValueImplSeed {
+                    context: &mut *self.context,
+                    instance: a.values_type_id(),
+                    strings: PhantomData,
+                }))
+                .inspect_err(|_| {
+                    if let Some(err) = self.context.error.take() {
+                        self.context.error =
+                            Some(ParseErrorKind::InvalidDictionaryValue(Box::new(err)));
+                    }
+
+                    self.context.fill_key(mark, &k);
+                })?;
+
+            // We only must pop if the parse was successful.
+            self.context.path.pop();
+            self.context.fill_key(mark, &k);
+
+            if let Some(value) = value {
+                keys.record(hash, items.len());
+                items.push((key, value));
+            }
+        }
+
+        self.context.leave_collection();
+
+        if self.context.options.apply_defaults {
+            ValueImpl::apply_defaults(a, &mut items);
+        }
+
+        Ok(ValueImpl::Dictionary(items))
+    }
+}
+
+impl<'de, Id, FieldName, S> ValueImplSeed<'_, '_, Id, FieldName, S>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+    S: SeedString<'de>,
+{
+    /// Visit a dictionary in the pairs wire form.
+    fn visit_pairs<A>(
+        self,
+        mut seq: A,
+        a: &DictionaryTypeAttributes<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+    ) -> Result<ValueImpl<FieldName, S>, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let result = self.context.enter_collection();
+        self.context.raise(result)?;
+
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or_default().min(1024));
+        let mut keys = Keys::default();
+        let mut len = 0;
+
+        loop {
+            let result = self.context.check_array_length(len + 1);
+            self.context.path.push(PathSegment::ArrayIndex(len));
+
+            let pair = seq.next_element_seed(PairSeed {
+                context: &mut *self.context,
+                attributes: a,
+                strings: PhantomData,
+            })?;
+
+            match pair {
+                Some(pair) => {
+                    self.context.raise(result)?;
+
+                    if let Some((key, _)) = &pair {
+                        let result = keys.check(&items, key);
+                        let hash = self.context.raise(result)?;
+                        keys.record(hash, items.len());
+                    }
+
+                    items.extend(pair);
+                }
+                None => break,
+            }
+
+            // We only must pop if the parse was successful.
+            self.context.path.pop();
+            len += 1;
+        }
+
+        // The last, missing, pair was never parsed.
+        self.context.path.pop();
+        self.context.leave_collection();
+        self.context.check_array_near_limit(len);
+
+        if self.context.options.apply_defaults {
+            ValueImpl::apply_defaults(a, &mut items);
+        }
+
+        Ok(ValueImpl::Dictionary(items))
+    }
+}
+
+/// A seed to deserialize a `[key, value]` pair of a dictionary in the pairs wire form.
+///
+/// Pairs are deserialized as `None` if they, or their value, are absent according to the null
+/// policy.
+struct PairSeed<'c, 'o, Id, FieldName: Ord, S> {
+    /// The parse context.
+    context: &'c mut ParseContext<'o>,
+
+    /// The dictionary type attributes.
+    attributes: &'c DictionaryTypeAttributes<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+
+    /// The storage of the strings.
+    strings: PhantomData<S>,
+}
+
+impl<Id, FieldName: Ord, S> PairSeed<'_, '_, Id, FieldName, S> {
+    /// Raise an error for a JSON value that is not a pair.
+    fn unexpected<T, E: de::Error>(self, found: &'static str) -> Result<T, E> {
+        self.context.raise(Err(ParseErrorKind::UnexpectedValue {
+            expected: "[key, value] pair",
+            found,
+        }))
+    }
+}
+
+impl<'de, Id, FieldName, S> DeserializeSeed<'de> for PairSeed<'_, '_, Id, FieldName, S>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+    S: SeedString<'de>,
+{
+    type Value = Option<(ValueImpl<FieldName, S>, ValueImpl<FieldName, S>)>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let result = self.context.enter_node();
+        self.context.raise(result)?;
+
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, Id, FieldName, S> Visitor<'de> for PairSeed<'_, '_, Id, FieldName, S>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+    S: SeedString<'de>,
+{
+    type Value = Option<(ValueImpl<FieldName, S>, ValueImpl<FieldName, S>)>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a [key, value] pair")
+    }
+
+    fn visit_bool<E: de::Error>(self, _v: bool) -> Result<Self::Value, E> {
+        self.unexpected("boolean")
+    }
+
+    fn visit_i64<E: de::Error>(self, _v: i64) -> Result<Self::Value, E> {
+        self.unexpected("number")
+    }
+
+    fn visit_u64<E: de::Error>(self, _v: u64) -> Result<Self::Value, E> {
+        self.unexpected("number")
+    }
+
+    fn visit_f64<E: de::Error>(self, _v: f64) -> Result<Self::Value, E> {
+        self.unexpected("number")
+    }
+
+    fn visit_str<E: de::Error>(self, _v: &str) -> Result<Self::Value, E> {
+        self.unexpected("string")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        match self.context.options.null_policy {
+            NullPolicy::Absent => Ok(None),
+            NullPolicy::Error | NullPolicy::UseDefault => self.unexpected("null"),
+        }
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.visit_unit()
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.context.path.push(PathSegment::ArrayIndex(0));
+
+        let key = seq
+            .next_element_seed(ValueImplSeed {
+                context: &mut *self.context,
+                instance: self.attributes.keys_type_id(),
+                strings: PhantomData,
+            })
+            .inspect_err(|_| {
+                if let Some(err) = self.context.error.take() {
+                    self.context.error = Some(ParseErrorKind::InvalidDictionaryKey(Box::new(err)));
+                }
+            })?;
+
+        self.context.path.pop();
+
+        let Some(key) = key else {
+            return self
+                .context
+                .raise(Err(ParseErrorKind::InvalidDictionaryPair(0)));
+        };
+
+        self.context.path.push(PathSegment::ArrayIndex(1));
+
+        let value = seq
+            .next_element_seed(ItemSeed(ValueImplSeed {
+                context: &mut *self.context,
+                instance: self.attributes.values_type_id(),
+                strings: PhantomData,
+            }))
+            .inspect_err(|_| {
+                if let Some(err) = self.context.error.take() {
+                    self.context.error =
+                        Some(ParseErrorKind::InvalidDictionaryValue(Box::new(err)));
+                }
+            })?;
+
+        self.context.path.pop();
+
+        let Some(value) = value else {
+            return self
+                .context
+                .raise(Err(ParseErrorKind::InvalidDictionaryPair(1)));
+        };
+
+        let mut len = 2;
+
+        while seq.next_element::<de::IgnoredAny>()?.is_some() {
+            len += 1;
+        }
+
+        if len != 2 {
+            return self
+                .context
+                .raise(Err(ParseErrorKind::InvalidDictionaryPair(len)));
+        }
+
+        Ok(value.map(|value| (key, value)))
+    }
+
+    fn visit_map<A>(self, _map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.unexpected("object")
+    }
+}
+
+/// A seed to deserialize an array item or a dictionary value, that may be absent.
+///
+/// Nulls are deserialized as `None` if the null policy treats them as absent.
+struct ItemSeed<'c, 'o, Id, FieldName: Ord, S>(ValueImplSeed<'c, 'o, Id, FieldName, S>);
+
+impl<'de, Id, FieldName, S> DeserializeSeed<'de> for ItemSeed<'_, '_, Id, FieldName, S>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+    S: SeedString<'de>,
+{
+    type Value = Option<ValueImpl<FieldName, S>>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        if self.0.context.options.null_policy == NullPolicy::Absent {
+            let result = self.0.context.enter_node();
+            self.0.context.raise(result)?;
+
+            deserializer.deserialize_any(self)
+        } else {
+            self.0.deserialize(deserializer).map(Some)
+        }
+    }
+}
+
+impl<'de, Id, FieldName, S> Visitor<'de> for ItemSeed<'_, '_, Id, FieldName, S>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+    S: SeedString<'de>,
+{
+    type Value = Option<ValueImpl<FieldName, S>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.expecting(f)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        self.0.visit_bool(v).map(Some)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        self.0.visit_i64(v).map(Some)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        self.0.visit_u64(v).map(Some)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        self.0.visit_f64(v).map(Some)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.0.visit_str(v).map(Some)
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        self.0.visit_borrowed_str(v).map(Some)
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        self.0.visit_string(v).map(Some)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.0.visit_seq(seq).map(Some)
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.0.visit_map(map).map(Some)
+    }
+}
+
+/// A seed to deserialize a dictionary key, borrowed from the input when possible.
+struct KeySeed;
+
+impl<'de> DeserializeSeed<'de> for KeySeed {
+    type Value = Cow<'de, str>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeySeed {
+    type Value = Cow<'de, str>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string key")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(v.to_owned()))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(Cow::Borrowed(v))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(v))
+    }
+}
+
+/// Parse a string for the specified type attributes.
+///
+/// Values of string types are stored with `store`, which is given the string, normalized.
+pub(super) fn parse_str<'v, Id, FieldName: Ord + Clone + Borrow<str>, S>(
+    context: &mut ParseContext<'_>,
+    attributes: &TypeAttributesInstance<Id, FieldName>,
+    v: Cow<'v, str>,
+    store: impl FnOnce(Cow<'v, str>) -> S,
+) -> Result<ValueImpl<FieldName, S>, ParseErrorKind> {
+    match attributes {
+        TypeAttributesInstance::String(a) => {
+            context.check_string_length(&v)?;
+
+            // Normalizing may only trim the string: the trimmed string is then a subslice of it,
+            // which we keep borrowed if the string was.
+            let v = match a.normalize(&v) {
+                Cow::Borrowed(normalized) => {
+                    let start = normalized.as_ptr() as usize - v.as_ptr() as usize;
+                    let end = start + normalized.len();
+
+                    match v {
+                        Cow::Borrowed(v) => Cow::Borrowed(&v[start..end]),
+                        Cow::Owned(mut v) => {
+                            v.truncate(end);
+                            v.drain(..start);
+
+                            Cow::Owned(v)
+                        }
+                    }
+                }
+                Cow::Owned(normalized) => Cow::Owned(normalized),
+            };
+
+            Ok(ValueImpl::String(store(v)))
+        }
+        TypeAttributesInstance::Enum(a) => {
+            context.check_string_length(&v)?;
+
+            Ok(ValueImpl::Enum(context.resolve_enum(a, &v)?))
+        }
+        #[cfg(feature = "uuid")]
+        TypeAttributesInstance::Uuid(_) => Ok(ValueImpl::Uuid(uuid::Uuid::parse_str(&v)?)),
+        #[cfg(feature = "bytes")]
+        TypeAttributesInstance::Bytes(a) => {
+            context.check_string_length(&v)?;
+
+            Ok(ValueImpl::Bytes(a.encoding().decode(&v)?))
+        }
+        attributes => Err(ParseErrorKind::UnexpectedValue {
+            expected: attributes.kind(),
+            found: "string",
+        }),
+    }
+}
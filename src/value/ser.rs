@@ -0,0 +1,947 @@
+//! Serialization of Rust types into GameSON values.
+
+use std::{
+    borrow::{Borrow, Cow},
+    fmt::Display,
+    sync::Arc,
+};
+
+use serde::{Serialize, ser};
+
+use crate::{
+    NullPolicy, TypeDefinitionInstance,
+    type_attributes_instance::{DictionaryTypeAttributesInstance, TypeAttributesInstance},
+};
+
+use super::{Keys, ParseContext, ParseErrorKind, PathSegment, ValueImpl, ValueString, seed};
+
+/// An error that can occur when serializing a Rust value into a GameSON value.
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub(super) struct Error(ParseErrorKind);
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(ParseErrorKind::Unserializable(msg.to_string()))
+    }
+}
+
+impl From<ParseErrorKind> for Error {
+    fn from(err: ParseErrorKind) -> Self {
+        Self(err)
+    }
+}
+
+/// Build a GameSON value implementation from a Rust value, for a specified type instance.
+///
+/// The value is checked as if it was parsed: on error, the path of the context is the path of
+/// the offending value.
+pub(super) fn serialize_for<Id, FieldName, T>(
+    context: &mut ParseContext<'_>,
+    instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+    value: &T,
+) -> Result<ValueImpl<FieldName>, ParseErrorKind>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+    T: Serialize + ?Sized,
+{
+    serialize_node(context, instance, value, false)
+        .map(|value| value.expect("only items can be absent"))
+}
+
+/// Build a GameSON value implementation from a Rust value, for a specified type instance.
+///
+/// If `item` is set, the value is an array item or a dictionary value, which is absent if it is
+/// `None` and the null policy treats nulls as absent.
+fn serialize_node<Id, FieldName, T>(
+    context: &mut ParseContext<'_>,
+    instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+    value: &T,
+    item: bool,
+) -> Result<Option<ValueImpl<FieldName>>, ParseErrorKind>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+    T: Serialize + ?Sized,
+{
+    context.enter_node()?;
+
+    let absent = item && context.options.null_policy == NullPolicy::Absent;
+    let value = value
+        .serialize(ValueSerializer {
+            context,
+            instance,
+            absent,
+        })
+        .map_err(|Error(err)| err)?;
+
+    value
+        .map(|value| ValueImpl::check_for(instance, value))
+        .transpose()
+}
+
+/// A serializer of Rust values into GameSON values, for a specified type instance.
+///
+/// This is the mirror of [`ValueDeserializer`](super::ValueDeserializer): sequences serialize as
+/// arrays, maps and structs as dictionaries, whatever their wire form, and unit variants as enum
+/// values. Newtype, tuple and struct variants serialize as dictionaries with a single entry, named
+/// after the variant.
+struct ValueSerializer<'c, 'o, Id, FieldName: Ord> {
+    /// The parse context.
+    context: &'c mut ParseContext<'o>,
+
+    /// The type instance.
+    instance: &'c Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// Whether `None` and units are absent values, rather than nulls.
+    absent: bool,
+}
+
+impl<'c, 'o, Id, FieldName> ValueSerializer<'c, 'o, Id, FieldName>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    /// Build a value from a number.
+    fn number(
+        self,
+        v: impl Into<serde_json::Number>,
+    ) -> Result<Option<ValueImpl<FieldName>>, Error> {
+        ValueImpl::parse_number(self.context.options, &self.instance.attributes, &v.into())
+            .map(Some)
+            .map_err(Error)
+    }
+
+    /// Start serializing the single entry of a dictionary, named after an enum variant.
+    ///
+    /// The path of the context is then the path of the value of the entry.
+    fn variant(self, variant: &'static str) -> Result<MapSerializer<'c, 'o, Id, FieldName>, Error> {
+        let mut map = MapSerializer::new(self.context, self.instance)?;
+
+        ser::SerializeMap::serialize_key(&mut map, variant)?;
+
+        Ok(map)
+    }
+}
+
+impl<'c, 'o, Id, FieldName> ser::Serializer for ValueSerializer<'c, 'o, Id, FieldName>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    type Ok = Option<ValueImpl<FieldName>>;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'c, 'o, Id, FieldName>;
+    type SerializeTuple = SeqSerializer<'c, 'o, Id, FieldName>;
+    type SerializeTupleStruct = SeqSerializer<'c, 'o, Id, FieldName>;
+    type SerializeTupleVariant = VariantSerializer<'c, Id, FieldName, Self::SerializeSeq>;
+    type SerializeMap = MapSerializer<'c, 'o, Id, FieldName>;
+    type SerializeStruct = MapSerializer<'c, 'o, Id, FieldName>;
+    type SerializeStructVariant = VariantSerializer<'c, Id, FieldName, Self::SerializeMap>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        match &self.instance.attributes {
+            TypeAttributesInstance::Boolean(_) => Ok(Some(ValueImpl::Boolean(v))),
+            attributes => Err(Error(ParseErrorKind::UnexpectedValue {
+                expected: attributes.kind(),
+                found: "boolean",
+            })),
+        }
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.number(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        self.number(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.number(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        self.number(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.number(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.number(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.number(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.number(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        match serde_json::Number::from_f64(v) {
+            Some(v) => self.number(v),
+            None => Err(Error(ParseErrorKind::UnexpectedValue {
+                expected: self.instance.attributes.kind(),
+                found: "non-finite number",
+            })),
+        }
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        seed::parse_str(
+            self.context,
+            &self.instance.attributes,
+            Cow::Borrowed(v),
+            |v| ValueString::from(v.into_owned()),
+        )
+        .map(Some)
+        .map_err(Error)
+    }
+
+    #[cfg_attr(not(any(feature = "bytes", feature = "uuid")), allow(unused_variables))]
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        match &self.instance.attributes {
+            #[cfg(feature = "bytes")]
+            TypeAttributesInstance::Bytes(_) => Ok(Some(ValueImpl::Bytes(v.to_vec()))),
+            #[cfg(feature = "uuid")]
+            TypeAttributesInstance::Uuid(_) => uuid::Uuid::from_slice(v)
+                .map(|v| Some(ValueImpl::Uuid(v)))
+                .map_err(|err| Error(err.into())),
+            attributes => Err(Error(ParseErrorKind::UnexpectedValue {
+                expected: attributes.kind(),
+                found: "bytes",
+            })),
+        }
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        match self.absent {
+            true => Ok(None),
+            false => ValueImpl::parse_null(self.context, self.instance)
+                .map(Some)
+                .map_err(Error),
+        }
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let mut map = self.variant(variant)?;
+
+        ser::SerializeMap::serialize_value(&mut map, value)?;
+        ser::SerializeMap::end(map)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        SeqSerializer::new(self.context, self.instance)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        let map = self.variant(variant)?;
+
+        VariantSerializer::new(map, SeqSerializer::new)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        MapSerializer::new(self.context, self.instance)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        let map = self.variant(variant)?;
+
+        VariantSerializer::new(map, MapSerializer::new)
+    }
+}
+
+/// A serializer of the items of an array.
+struct SeqSerializer<'c, 'o, Id, FieldName: Ord> {
+    /// The parse context.
+    context: &'c mut ParseContext<'o>,
+
+    /// The type instance of the items.
+    instance: &'c Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The items serialized so far.
+    items: Vec<ValueImpl<FieldName>>,
+
+    /// The number of items serialized so far, absent ones included.
+    len: usize,
+}
+
+impl<'c, 'o, Id, FieldName> SeqSerializer<'c, 'o, Id, FieldName>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    /// Start serializing an array, for a specified type instance.
+    fn new(
+        context: &'c mut ParseContext<'o>,
+        instance: &'c Arc<TypeDefinitionInstance<Id, FieldName>>,
+    ) -> Result<Self, Error> {
+        let TypeAttributesInstance::Array(a) = &instance.attributes else {
+            return Err(Error(ParseErrorKind::UnexpectedValue {
+                expected: instance.attributes.kind(),
+                found: "array",
+            }));
+        };
+
+        context.enter_collection()?;
+
+        Ok(Self {
+            context,
+            instance: a.items_type_id(),
+            items: Vec::new(),
+            len: 0,
+        })
+    }
+
+    /// Finish serializing the array.
+    fn finish(&mut self) -> ValueImpl<FieldName> {
+        self.context.leave_collection();
+        self.context.check_array_near_limit(self.len);
+
+        ValueImpl::Array(std::mem::take(&mut self.items))
+    }
+}
+
+impl<Id, FieldName> ser::SerializeSeq for SeqSerializer<'_, '_, Id, FieldName>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    type Ok = Option<ValueImpl<FieldName>>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.context.check_array_length(self.len + 1)?;
+        self.context.path.push(PathSegment::ArrayIndex(self.len));
+
+        let item = serialize_node(self.context, self.instance, value, true)?;
+
+        // We only must pop if the serialization was successful.
+        self.context.path.pop();
+        self.items.extend(item);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(self.finish()))
+    }
+}
+
+impl<Id, FieldName> ser::SerializeTuple for SeqSerializer<'_, '_, Id, FieldName>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    type Ok = Option<ValueImpl<FieldName>>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl<Id, FieldName> ser::SerializeTupleStruct for SeqSerializer<'_, '_, Id, FieldName>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    type Ok = Option<ValueImpl<FieldName>>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// A serializer of the entries of a dictionary.
+struct MapSerializer<'c, 'o, Id, FieldName: Ord> {
+    /// The parse context.
+    context: &'c mut ParseContext<'o>,
+
+    /// The dictionary type attributes.
+    attributes: &'c DictionaryTypeAttributesInstance<Id, FieldName>,
+
+    /// The entries serialized so far.
+    items: Vec<(ValueImpl<FieldName>, ValueImpl<FieldName>)>,
+
+    /// The keys of the entries serialized so far.
+    keys: Keys,
+
+    /// The key of the entry whose value is to be serialized, with its hash.
+    key: Option<(ValueImpl<FieldName>, u64)>,
+}
+
+impl<'c, 'o, Id, FieldName> MapSerializer<'c, 'o, Id, FieldName>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    /// Start serializing a dictionary, for a specified type instance.
+    fn new(
+        context: &'c mut ParseContext<'o>,
+        instance: &'c Arc<TypeDefinitionInstance<Id, FieldName>>,
+    ) -> Result<Self, Error> {
+        let TypeAttributesInstance::Dictionary(attributes) = &instance.attributes else {
+            return Err(Error(ParseErrorKind::UnexpectedValue {
+                expected: instance.attributes.kind(),
+                found: "object",
+            }));
+        };
+
+        context.enter_collection()?;
+
+        Ok(Self {
+            context,
+            attributes,
+            items: Vec::new(),
+            keys: Keys::default(),
+            key: None,
+        })
+    }
+
+    /// Finish serializing the dictionary.
+    fn finish(&mut self) -> ValueImpl<FieldName> {
+        self.context.leave_collection();
+
+        if self.context.options.apply_defaults {
+            ValueImpl::apply_defaults(self.attributes, &mut self.items);
+        }
+
+        ValueImpl::Dictionary(std::mem::take(&mut self.items))
+    }
+
+    /// Add the entry whose key was serialized, given its value, if it is not absent.
+    fn insert(&mut self, value: Option<ValueImpl<FieldName>>) {
+        let (key, hash) = self
+            .key
+            .take()
+            .expect("serialize_value is always called after serialize_key");
+
+        // We only must pop if the serialization was successful.
+        self.context.path.pop();
+
+        if let Some(value) = value {
+            self.keys.record(hash, self.items.len());
+            self.items.push((key, value));
+        }
+    }
+}
+
+impl<Id, FieldName> ser::SerializeMap for MapSerializer<'_, '_, Id, FieldName>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    type Ok = Option<ValueImpl<FieldName>>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        let key = serialize_for(self.context, self.attributes.keys_type_id(), key)
+            .map_err(|err| ParseErrorKind::InvalidDictionaryKey(Box::new(err)))?;
+
+        self.context
+            .path
+            .push(PathSegment::DictionaryKey(key.key_text().into_owned()));
+
+        let hash = self.keys.check(&self.items, &key)?;
+        self.key = Some((key, hash));
+
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let value = serialize_node(self.context, self.attributes.values_type_id(), value, true)
+            .map_err(|err| ParseErrorKind::InvalidDictionaryValue(Box::new(err)))?;
+
+        self.insert(value);
+
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(self.finish()))
+    }
+}
+
+impl<Id, FieldName> ser::SerializeStruct for MapSerializer<'_, '_, Id, FieldName>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    type Ok = Option<ValueImpl<FieldName>>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        ser::SerializeMap::serialize_entry(self, key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// A serializer of the fields of a tuple or struct variant, as the value of the single entry of a
+/// dictionary, named after the variant.
+struct VariantSerializer<'c, Id, FieldName: Ord, S> {
+    /// The dictionary type attributes.
+    attributes: &'c DictionaryTypeAttributesInstance<Id, FieldName>,
+
+    /// The key of the entry.
+    key: ValueImpl<FieldName>,
+
+    /// The serializer of the fields, which holds the parse context.
+    fields: S,
+}
+
+/// A serializer of the fields of a variant.
+trait Fields<'o, FieldName> {
+    /// Get the parse context.
+    fn context(&mut self) -> &mut ParseContext<'o>;
+
+    /// Finish serializing the fields.
+    fn finish(&mut self) -> ValueImpl<FieldName>;
+}
+
+impl<'o, Id, FieldName> Fields<'o, FieldName> for SeqSerializer<'_, 'o, Id, FieldName>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    fn context(&mut self) -> &mut ParseContext<'o> {
+        self.context
+    }
+
+    fn finish(&mut self) -> ValueImpl<FieldName> {
+        Self::finish(self)
+    }
+}
+
+impl<'o, Id, FieldName> Fields<'o, FieldName> for MapSerializer<'_, 'o, Id, FieldName>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    fn context(&mut self) -> &mut ParseContext<'o> {
+        self.context
+    }
+
+    fn finish(&mut self) -> ValueImpl<FieldName> {
+        Self::finish(self)
+    }
+}
+
+impl<'c, 'o, Id, FieldName, S> VariantSerializer<'c, Id, FieldName, S>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+    S: Fields<'o, FieldName>,
+{
+    /// Start serializing the fields of a variant, as the value of the entry whose key was
+    /// serialized by `map`.
+    fn new(
+        map: MapSerializer<'c, 'o, Id, FieldName>,
+        fields: impl FnOnce(
+            &'c mut ParseContext<'o>,
+            &'c Arc<TypeDefinitionInstance<Id, FieldName>>,
+        ) -> Result<S, Error>,
+    ) -> Result<Self, Error> {
+        let MapSerializer {
+            context,
+            attributes,
+            key,
+            ..
+        } = map;
+
+        let fields = context
+            .enter_node()
+            .map_err(Error)
+            .and_then(|()| fields(context, attributes.values_type_id()))
+            .map_err(|Error(err)| Error(ParseErrorKind::InvalidDictionaryValue(Box::new(err))))?;
+
+        Ok(Self {
+            attributes,
+            key: key.expect("the variant was serialized as a key").0,
+            fields,
+        })
+    }
+
+    /// Serialize a field of the variant.
+    fn field(&mut self, field: impl FnOnce(&mut S) -> Result<(), Error>) -> Result<(), Error> {
+        field(&mut self.fields)
+            .map_err(|Error(err)| Error(ParseErrorKind::InvalidDictionaryValue(Box::new(err))))
+    }
+
+    /// Finish serializing the variant, as a dictionary with a single entry.
+    fn finish(mut self) -> Result<Option<ValueImpl<FieldName>>, Error> {
+        let value = self.fields.finish();
+        let value = ValueImpl::check_for(self.attributes.values_type_id(), value)
+            .map_err(|err| ParseErrorKind::InvalidDictionaryValue(Box::new(err)))?;
+
+        let context = self.fields.context();
+        let mut items = vec![(self.key, value)];
+
+        // We only must pop if the serialization was successful.
+        context.path.pop();
+        context.leave_collection();
+
+        if context.options.apply_defaults {
+            ValueImpl::apply_defaults(self.attributes, &mut items);
+        }
+
+        Ok(Some(ValueImpl::Dictionary(items)))
+    }
+}
+
+impl<'c, 'o, Id, FieldName> ser::SerializeTupleVariant
+    for VariantSerializer<'c, Id, FieldName, SeqSerializer<'c, 'o, Id, FieldName>>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    type Ok = Option<ValueImpl<FieldName>>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.field(|seq| ser::SerializeSeq::serialize_element(seq, value))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'c, 'o, Id, FieldName> ser::SerializeStructVariant
+    for VariantSerializer<'c, Id, FieldName, MapSerializer<'c, 'o, Id, FieldName>>
+where
+    FieldName: Ord + Clone + Borrow<str>,
+{
+    type Ok = Option<ValueImpl<FieldName>>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.field(|map| ser::SerializeMap::serialize_entry(map, key, value))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use serde::Serialize;
+    use serde_json::json;
+
+    use crate::{
+        NullPolicy, ParseErrorKind, ParseOptions, PathSegment, Validator,
+        test_support::{
+            FieldName, Id, TypeAttributes, TypeDefinitionRegistry, Value, registry, td,
+        },
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, EnumTypeAttributes, NumberTypeAttributes,
+        },
+    };
+
+    /// A validator of arrays of numbers, that must not be empty.
+    #[derive(Debug)]
+    struct NonEmpty;
+
+    impl Validator<Id, FieldName> for NonEmpty {
+        fn validate(&self, value: &Value) -> Result<(), String> {
+            match value.to_json().unwrap().as_array().unwrap().is_empty() {
+                true => Err("empty".to_owned()),
+                false => Ok(()),
+            }
+        }
+    }
+
+    fn stats_registry() -> TypeDefinitionRegistry {
+        let mut registry = registry([
+            td(
+                1,
+                "Speed",
+                TypeAttributes::Float64(
+                    NumberTypeAttributes::builder()
+                        .default_value(1.0)
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                2,
+                "Stat",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("speed")
+                        .with_value("boosts")
+                        .with_alias("pace", "speed")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                3,
+                "Speeds",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(
+                4,
+                "Stats",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(2, 1)),
+            ),
+            td(
+                5,
+                "Effects",
+                TypeAttributes::Dictionary(DictionaryTypeAttributes::new(2, 3)),
+            ),
+        ]);
+        registry
+            .set_validator(&3, Some(Arc::new(NonEmpty)))
+            .unwrap();
+
+        registry
+    }
+
+    #[test]
+    fn test_serialize() {
+        let registry = stats_registry();
+
+        let value = Value::from_serialize(
+            registry.by_id[&4].clone(),
+            &BTreeMap::from([("pace", 2.5f32), ("boosts", 3.0)]),
+        )
+        .unwrap();
+        assert_eq!(
+            value.to_json().unwrap(),
+            json!({ "boosts": 3.0, "speed": 2.5 })
+        );
+
+        let err = Value::from_serialize(registry.by_id[&1].clone(), &"fast").unwrap_err();
+        assert_eq!(err.kind().code(), "unexpected_value");
+    }
+
+    #[test]
+    fn test_serialize_non_finite() {
+        let registry = stats_registry();
+        let options = ParseOptions::default().null_policy(NullPolicy::UseDefault);
+
+        // Non-finite numbers are not nulls, which would be replaced by their default value.
+        let err = Value::from_serialize_with_options(
+            registry.by_id[&3].clone(),
+            &[1.0, f64::NAN],
+            &options,
+        )
+        .unwrap_err();
+        assert_eq!(err.path(), [PathSegment::ArrayIndex(1)]);
+        assert_eq!(
+            err.kind().to_string(),
+            "expected float64, found non-finite number"
+        );
+
+        let value = Value::from_serialize_with_options(
+            registry.by_id[&3].clone(),
+            &[Some(2.0), None],
+            &options,
+        )
+        .unwrap();
+        assert_eq!(value.to_json().unwrap(), json!([2.0, 1.0]));
+    }
+
+    #[test]
+    fn test_serialize_absent() {
+        let registry = stats_registry();
+        let options = ParseOptions::default().null_policy(NullPolicy::Absent);
+
+        let value = Value::from_serialize_with_options(
+            registry.by_id[&4].clone(),
+            &BTreeMap::from([("speed", Some(2.0)), ("boosts", None)]),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(value.to_json().unwrap(), json!({ "speed": 2.0 }));
+
+        let err =
+            Value::from_serialize_with_options(registry.by_id[&1].clone(), &None::<f64>, &options)
+                .unwrap_err();
+        assert_eq!(err.kind().code(), "unexpected_value");
+    }
+
+    #[test]
+    fn test_serialize_variants() {
+        #[derive(Serialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Effect {
+            Speed(f64),
+            Boosts(f64, f64),
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Stats {
+            Speed { speed: f64 },
+        }
+
+        let registry = stats_registry();
+
+        let value = Value::from_serialize(registry.by_id[&4].clone(), &Effect::Speed(2.0)).unwrap();
+        assert_eq!(value.to_json().unwrap(), json!({ "speed": 2.0 }));
+
+        let value =
+            Value::from_serialize(registry.by_id[&5].clone(), &Effect::Boosts(1.0, 2.0)).unwrap();
+        assert_eq!(value.to_json().unwrap(), json!({ "boosts": [1.0, 2.0] }));
+
+        let err = Value::from_serialize(registry.by_id[&4].clone(), &Effect::Boosts(1.0, 2.0))
+            .unwrap_err();
+        assert_eq!(
+            err.path(),
+            [PathSegment::DictionaryKey("boosts".to_owned())]
+        );
+        assert_eq!(err.kind().code(), "invalid_dictionary_value");
+
+        let err = Value::from_serialize(registry.by_id[&5].clone(), &Stats::Speed { speed: 1.0 })
+            .unwrap_err();
+        assert_eq!(
+            err.kind().to_string(),
+            "invalid dictionary value: expected array, found object"
+        );
+    }
+
+    #[test]
+    fn test_serialize_checks() {
+        let registry = stats_registry();
+
+        // Keys are checked once parsed.
+        let err = Value::from_serialize(
+            registry.by_id[&4].clone(),
+            &BTreeMap::from([("speed", 1.0), ("pace", 2.0)]),
+        )
+        .unwrap_err();
+        assert_eq!(err.path(), [PathSegment::DictionaryKey("speed".to_owned())]);
+        assert!(matches!(err.kind(), ParseErrorKind::DuplicateKey(key) if key == "speed"));
+
+        // Validators run on the values, nested ones included.
+        let err = Value::from_serialize(
+            registry.by_id[&5].clone(),
+            &BTreeMap::from([("speed", Vec::<f64>::new())]),
+        )
+        .unwrap_err();
+        assert_eq!(err.path(), [PathSegment::DictionaryKey("speed".to_owned())]);
+        assert_eq!(
+            err.kind().to_string(),
+            "invalid dictionary value: rejected: empty"
+        );
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_serialize_bytes() {
+        use crate::{BytesEncoding, type_attributes::BytesTypeAttributes};
+
+        /// Bytes, serialized as such rather than as a sequence.
+        struct Bytes(&'static [u8]);
+
+        impl Serialize for Bytes {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(self.0)
+            }
+        }
+
+        let registry = registry([td(
+            1,
+            "Hash",
+            TypeAttributes::Bytes(BytesTypeAttributes::new(BytesEncoding::Hex)),
+        )]);
+
+        let value =
+            Value::from_serialize(registry.by_id[&1].clone(), &Bytes(&[0, 255, 16])).unwrap();
+        assert_eq!(value.to_json().unwrap(), json!("00ff10"));
+
+        let err = Value::from_serialize(registry.by_id[&1].clone(), &[0, 255, 16]).unwrap_err();
+        assert_eq!(err.kind().code(), "unexpected_value");
+    }
+}
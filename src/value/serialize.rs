@@ -0,0 +1,301 @@
+//! Serialization of GameSON values to JSON.
+
+use std::borrow::Borrow;
+
+use crate::{
+    DictionaryWireForm, NonFiniteFloats, SerializeOptions, TypeDefinitionInstance,
+    type_attributes_instance::{DictionaryTypeAttributesInstance, TypeAttributesInstance},
+};
+
+use super::{ParseErrorPath, PathSegment, PathStack, Value, ValueImpl};
+
+impl<Id, FieldName: Ord + Borrow<str>> Value<Id, FieldName> {
+    /// Serialize the value to a JSON value.
+    ///
+    /// Parsing the result for the same type instance yields the same value.
+    pub fn to_json(&self) -> Result<serde_json::Value, SerializeError> {
+        self.to_json_with_options(&SerializeOptions::default())
+    }
+
+    /// Serialize the value to a JSON value, using the specified serialize options.
+    pub fn to_json_with_options(
+        &self,
+        options: &SerializeOptions,
+    ) -> Result<serde_json::Value, SerializeError> {
+        let mut path = PathStack::default();
+
+        self.value
+            .to_json(&self.instance, options, &mut path)
+            .map_err(|kind| SerializeError {
+                path: path.into_path(),
+                kind,
+            })
+    }
+}
+
+/// An error that can occur when serializing a GameSON value.
+#[derive(Debug, thiserror::Error)]
+#[error("failed to serialize GameSON value: {path}: {kind}")]
+pub struct SerializeError {
+    /// The path of the value that could not be serialized.
+    pub(super) path: ParseErrorPath,
+
+    /// The kind of error.
+    pub(super) kind: SerializeErrorKind,
+}
+
+impl SerializeError {
+    /// Get the path of the value that could not be serialized.
+    ///
+    /// An empty path designates the serialized value itself.
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path.0
+    }
+
+    /// Get the kind of error.
+    pub fn kind(&self) -> &SerializeErrorKind {
+        &self.kind
+    }
+}
+
+/// The kind of error that can occur when serializing a GameSON value.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SerializeErrorKind {
+    /// The number is not finite and has no JSON representation.
+    #[error("non-finite number {0} has no JSON representation")]
+    NonFiniteFloat(f64),
+
+    /// The number is too large for a BSON 64-bit integer.
+    #[cfg(feature = "bson")]
+    #[error("uint64 {0} does not fit in a BSON int64")]
+    UnrepresentableUint64(u64),
+}
+
+impl<FieldName: Ord + Borrow<str>, S: Borrow<str>> ValueImpl<FieldName, S> {
+    /// Serialize the value to a JSON value, for a specified type instance.
+    ///
+    /// Arrays and dictionaries are serialized with an explicit stack rather than recursively, so
+    /// that deeply nested values can be serialized. On error, `path` points to the value that
+    /// could not be serialized.
+    pub(super) fn to_json<Id>(
+        &self,
+        instance: &TypeDefinitionInstance<Id, FieldName>,
+        options: &SerializeOptions,
+        path: &mut PathStack,
+    ) -> Result<serde_json::Value, SerializeErrorKind> {
+        /// An array or a dictionary being serialized.
+        enum Frame<'v, 'i, Id, FieldName: Ord, S> {
+            /// An array, with the items left to serialize and the serialized ones.
+            Array {
+                items: std::slice::Iter<'v, ValueImpl<FieldName, S>>,
+                instance: &'i TypeDefinitionInstance<Id, FieldName>,
+                json: Vec<serde_json::Value>,
+            },
+
+            /// A dictionary in the object wire form, with the entries left to serialize, the
+            /// serialized ones and the key of the entry whose value is being serialized.
+            Object {
+                entries: std::slice::Iter<'v, (ValueImpl<FieldName, S>, ValueImpl<FieldName, S>)>,
+                instance: &'i TypeDefinitionInstance<Id, FieldName>,
+                json: serde_json::Map<String, serde_json::Value>,
+                key: Option<String>,
+            },
+
+            /// A dictionary in the pairs wire form, with the entries left to serialize, the
+            /// serialized ones and the value of the entry whose key is being serialized.
+            Pairs {
+                entries: std::slice::Iter<'v, (ValueImpl<FieldName, S>, ValueImpl<FieldName, S>)>,
+                attributes: &'i DictionaryTypeAttributesInstance<Id, FieldName>,
+                json: Vec<serde_json::Value>,
+                value: Option<&'v ValueImpl<FieldName, S>>,
+                key: Option<serde_json::Value>,
+            },
+        }
+
+        let mut stack = Vec::new();
+        let mut next = (self, instance);
+
+        loop {
+            let (value, instance) = next;
+
+            let mut serialized = match (value, &instance.attributes) {
+                (Self::Array(items), TypeAttributesInstance::Array(a)) => {
+                    stack.push(Frame::Array {
+                        items: items.iter(),
+                        instance: a.items_type_id(),
+                        json: Vec::with_capacity(items.len()),
+                    });
+
+                    None
+                }
+                (Self::Dictionary(entries), TypeAttributesInstance::Dictionary(a)) => {
+                    stack.push(match a.wire_form() {
+                        DictionaryWireForm::Object => Frame::Object {
+                            entries: entries.iter(),
+                            instance: a.values_type_id(),
+                            json: serde_json::Map::new(),
+                            key: None,
+                        },
+                        DictionaryWireForm::Pairs => Frame::Pairs {
+                            entries: entries.iter(),
+                            attributes: a,
+                            json: Vec::with_capacity(entries.len()),
+                            value: None,
+                            key: None,
+                        },
+                    });
+
+                    None
+                }
+                _ => Some(value.scalar_to_json(instance, options)?),
+            };
+
+            // Each serialized value is handed to the collection being serialized, if any, which
+            // then either serializes its next element or is complete.
+            next = loop {
+                let Some(frame) = stack.last_mut() else {
+                    return Ok(serialized.expect("the value is serialized"));
+                };
+
+                match frame {
+                    Frame::Array {
+                        items,
+                        instance,
+                        json,
+                    } => {
+                        if let Some(item) = serialized.take() {
+                            // We only must pop if the serialization was successful.
+                            path.pop();
+                            json.push(item);
+                        }
+
+                        if let Some(item) = items.next() {
+                            path.push(PathSegment::ArrayIndex(json.len()));
+
+                            break (item, instance);
+                        }
+
+                        serialized = Some(serde_json::Value::Array(std::mem::take(json)));
+                    }
+                    Frame::Object {
+                        entries,
+                        instance,
+                        json,
+                        key,
+                    } => {
+                        if let Some(value) = serialized.take() {
+                            path.pop();
+                            json.insert(key.take().expect("a value was serialized"), value);
+                        }
+
+                        if let Some((k, v)) = entries.next() {
+                            let k = k.key_string().into_owned();
+                            path.push(PathSegment::DictionaryKey(k.clone()));
+                            *key = Some(k);
+
+                            break (v, instance);
+                        }
+
+                        serialized = Some(serde_json::Value::Object(std::mem::take(json)));
+                    }
+                    Frame::Pairs {
+                        entries,
+                        attributes,
+                        json,
+                        value,
+                        key,
+                    } => {
+                        if let Some(serialized) = serialized.take() {
+                            path.pop();
+
+                            // The key of the entry is serialized first, then its value.
+                            if let Some(value) = value.take() {
+                                *key = Some(serialized);
+                                path.push(PathSegment::ArrayIndex(1));
+
+                                break (value, attributes.values_type_id());
+                            }
+
+                            path.pop();
+                            json.push(serde_json::Value::Array(vec![
+                                key.take().expect("a key was serialized"),
+                                serialized,
+                            ]));
+                        }
+
+                        if let Some((k, v)) = entries.next() {
+                            path.push(PathSegment::ArrayIndex(json.len()));
+                            path.push(PathSegment::ArrayIndex(0));
+                            *value = Some(v);
+
+                            break (k, attributes.keys_type_id());
+                        }
+
+                        serialized = Some(serde_json::Value::Array(std::mem::take(json)));
+                    }
+                }
+
+                stack.pop();
+            };
+        }
+    }
+
+    /// Serialize a value that is neither an array nor a dictionary to a JSON value.
+    fn scalar_to_json<Id>(
+        &self,
+        instance: &TypeDefinitionInstance<Id, FieldName>,
+        options: &SerializeOptions,
+    ) -> Result<serde_json::Value, SerializeErrorKind> {
+        Ok(match (self, &instance.attributes) {
+            (Self::Array(_) | Self::Dictionary(_), _) => {
+                panic!("inconsistent value and type attributes");
+            }
+            (Self::Boolean(v), _) => serde_json::Value::Bool(*v),
+            (Self::String(v), _) => serde_json::Value::String(v.borrow().to_owned()),
+            (Self::Enum(v), _) => serde_json::Value::String(v.borrow().to_owned()),
+            #[cfg(feature = "uuid")]
+            (Self::Uuid(v), _) => serde_json::Value::String(v.to_string()),
+            #[cfg(feature = "bytes")]
+            (Self::Bytes(v), TypeAttributesInstance::Bytes(a)) => {
+                serde_json::Value::String(a.encoding().encode(v))
+            }
+            (number, _) => match number.json_number() {
+                Some(Some(v)) => serde_json::Value::Number(v),
+                _ => non_finite_to_json(number.as_f64(), options)?,
+            },
+        })
+    }
+
+    /// Get the value of a floating-point number, as a `f64`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the value is not a floating-point number.
+    pub(super) fn as_f64(&self) -> f64 {
+        match self {
+            Self::Float32(v) => f64::from(*v),
+            Self::Float64(v) => *v,
+            _ => panic!("not a floating-point number"),
+        }
+    }
+}
+
+/// Serialize a non-finite number, according to the serialize options.
+fn non_finite_to_json(
+    v: f64,
+    options: &SerializeOptions,
+) -> Result<serde_json::Value, SerializeErrorKind> {
+    match options.non_finite_floats {
+        NonFiniteFloats::Error => Err(SerializeErrorKind::NonFiniteFloat(v)),
+        NonFiniteFloats::Null => Ok(serde_json::Value::Null),
+        NonFiniteFloats::String => Ok(serde_json::Value::String(
+            match v {
+                v if v.is_nan() => "NaN",
+                v if v > 0.0 => "Infinity",
+                _ => "-Infinity",
+            }
+            .to_owned(),
+        )),
+    }
+}
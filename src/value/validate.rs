@@ -0,0 +1,1081 @@
+//! Validation of JSON values, without constructing GameSON values, and of existing GameSON
+//! values against other type instances.
+
+use std::{
+    borrow::{Borrow, Cow},
+    collections::BTreeSet,
+    sync::Arc,
+};
+
+use crate::{
+    DictionaryWireForm, NullPolicy, ParseOptions, TypeDefinitionInstance,
+    metrics::Measure,
+    type_attributes_instance::{DictionaryTypeAttributesInstance, TypeAttributesInstance},
+};
+
+use super::{
+    DictionaryEntry, ParseContext, ParseErrorKind, ParseErrorPath, PathSegment, ValidationReport,
+    Value, ValueImpl, ValueString, constraints::check_json_constraints, json_kind, seed,
+    validator::run_validator, value_string,
+};
+
+/// A violation of a type instance by a JSON value.
+#[derive(Debug, thiserror::Error)]
+#[error("{path}: {err}")]
+pub struct Violation {
+    /// The path of the offending value.
+    path: ParseErrorPath,
+
+    /// The violation.
+    err: ParseErrorKind,
+}
+
+impl Violation {
+    /// Get the path of the offending value.
+    ///
+    /// An empty path designates the validated value itself.
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path.0
+    }
+
+    /// Get the kind of the violation.
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.err
+    }
+}
+
+/// Validate a JSON value against a type instance, collecting all the violations and warnings.
+///
+/// Exceeding the maximum nesting depth or the maximum number of values stops the validation, as
+/// the remaining of the value is not inspected.
+pub(crate) fn validate<Id, FieldName: Ord + Clone + Borrow<str>>(
+    instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+    value: &serde_json::Value,
+    options: &ParseOptions,
+) -> ValidationReport {
+    report(instance, options, |context, violations| {
+        validate_for(context, instance, value, violations)
+    })
+}
+
+/// Run a validation against a type instance, collecting all the violations and warnings into a
+/// report.
+fn report<Id, FieldName: Ord + Borrow<str>>(
+    instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+    options: &ParseOptions,
+    validate: impl FnOnce(&mut ParseContext<'_>, &mut Vec<Violation>) -> Result<(), ParseErrorKind>,
+) -> ValidationReport {
+    let measure = Measure::start(options);
+    let mut context = ParseContext::new(options);
+    let mut violations = Vec::new();
+
+    if let Err(err) = validate(&mut context, &mut violations) {
+        violations.push(Violation {
+            path: context.path.into_path(),
+            err,
+        });
+    }
+
+    let report = ValidationReport {
+        errors: violations,
+        warnings: context.warnings,
+    };
+
+    if let Some(measure) = measure {
+        measure.validated(instance.name.borrow(), &report);
+    }
+
+    report
+}
+
+/// A step of the validation of a JSON value.
+enum Task<'v, 'i, Id, FieldName: Ord> {
+    /// Validate a value for a type instance, at the current path.
+    Value(
+        &'i Arc<TypeDefinitionInstance<Id, FieldName>>,
+        &'v serde_json::Value,
+    ),
+
+    /// Run the validator of a type instance on the value built for it, once validated, given the
+    /// number of violations before it.
+    Check(&'i Arc<TypeDefinitionInstance<Id, FieldName>>, usize),
+
+    /// Validate an entry of a dictionary in the object wire form.
+    Entry(
+        &'i DictionaryTypeAttributesInstance<Id, FieldName>,
+        &'v String,
+        &'v serde_json::Value,
+    ),
+
+    /// Validate an entry of a dictionary in the pairs wire form.
+    Pair(
+        &'i DictionaryTypeAttributesInstance<Id, FieldName>,
+        &'v serde_json::Value,
+    ),
+
+    /// Check the key of an entry in the pairs wire form, once validated, given the number of
+    /// violations before it, the key if it is a string, and whether the entry is present.
+    PairKey(usize, Option<&'v str>, bool),
+
+    /// Append a segment to the path.
+    Push(PathSegment),
+
+    /// Remove the last segment of the path.
+    Pop,
+
+    /// Leave an array or a dictionary, given the number of violations before its elements.
+    ///
+    /// The entry rules of a dictionary are then checked. If the collection is built, it is built
+    /// from the values built for its elements, from the given position.
+    Leave {
+        /// The dictionary being left, if it is one.
+        dictionary: Option<(
+            &'i DictionaryTypeAttributesInstance<Id, FieldName>,
+            &'v serde_json::Value,
+        )>,
+
+        /// The number of violations before the elements.
+        violations_before: usize,
+
+        /// The position of the values built for the elements, if the collection is built.
+        built_from: Option<usize>,
+    },
+}
+
+/// Validate a JSON value for a specified type instance.
+///
+/// Arrays and dictionaries are validated with an explicit stack of tasks rather than
+/// recursively, so that deeply nested values can be validated. Violations are pushed to
+/// `violations`. An error is only returned if the validation cannot continue.
+///
+/// Values are only built for the validators and for the keys in the pairs wire form: the values
+/// of the nodes with a validator, and of their descendants, are built bottom-up as they are
+/// validated. A node that is built yields exactly one value if it has no violations, and none
+/// otherwise.
+fn validate_for<Id, FieldName: Ord + Clone + Borrow<str>>(
+    context: &mut ParseContext<'_>,
+    instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+    value: &serde_json::Value,
+    violations: &mut Vec<Violation>,
+) -> Result<(), ParseErrorKind> {
+    let skip_nulls = context.options.null_policy == NullPolicy::Absent;
+    let mut tasks = vec![Task::Value(instance, value)];
+
+    // The parsed keys of the dictionaries being validated, innermost last.
+    let mut keys: Vec<BTreeSet<Cow<'_, str>>> = Vec::new();
+
+    // The values built so far, and the number of enclosing nodes that are built.
+    let mut built: Vec<ValueImpl<FieldName>> = Vec::new();
+    let mut building = 0;
+
+    while let Some(task) = tasks.pop() {
+        let result = match task {
+            Task::Value(instance, value) => {
+                context.enter_node()?;
+
+                // The validator runs once the value and its descendants are validated and built.
+                if instance.validator.is_some() {
+                    building += 1;
+                    tasks.push(Task::Check(instance, violations.len()));
+                }
+
+                let build = building > 0;
+
+                match (&instance.attributes, value) {
+                    (TypeAttributesInstance::Array(a), serde_json::Value::Array(v)) => {
+                        let result = context.check_array_length(v.len());
+
+                        // There is no point in inspecting the items of an array that is too long.
+                        if result.is_ok() {
+                            context.check_array_near_limit(v.len());
+                            context.enter_collection()?;
+
+                            tasks.push(Task::Leave {
+                                dictionary: None,
+                                violations_before: violations.len(),
+                                built_from: build.then_some(built.len()),
+                            });
+
+                            for (i, v) in v.iter().enumerate().rev() {
+                                if !(skip_nulls && v.is_null()) {
+                                    tasks.extend([
+                                        Task::Pop,
+                                        Task::Value(a.items_type_id(), v),
+                                        Task::Push(PathSegment::ArrayIndex(i)),
+                                    ]);
+                                }
+                            }
+                        }
+
+                        result
+                    }
+                    (TypeAttributesInstance::Dictionary(a), serde_json::Value::Object(v))
+                        if a.wire_form() == DictionaryWireForm::Object =>
+                    {
+                        context.enter_collection()?;
+
+                        keys.push(BTreeSet::new());
+                        tasks.push(Task::Leave {
+                            dictionary: Some((a, value)),
+                            violations_before: violations.len(),
+                            built_from: build.then_some(built.len()),
+                        });
+                        tasks.extend(v.iter().rev().map(|(k, v)| Task::Entry(a, k, v)));
+
+                        Ok(())
+                    }
+                    (TypeAttributesInstance::Dictionary(a), serde_json::Value::Array(v))
+                        if a.wire_form() == DictionaryWireForm::Pairs =>
+                    {
+                        let result = context.check_array_length(v.len());
+
+                        // There is no point in inspecting the pairs of a dictionary that is too
+                        // long.
+                        if result.is_ok() {
+                            context.check_array_near_limit(v.len());
+                            context.enter_collection()?;
+
+                            keys.push(BTreeSet::new());
+                            tasks.push(Task::Leave {
+                                dictionary: Some((a, value)),
+                                violations_before: violations.len(),
+                                built_from: build.then_some(built.len()),
+                            });
+
+                            for (i, pair) in v.iter().enumerate().rev() {
+                                if !(skip_nulls && pair.is_null()) {
+                                    tasks.extend([
+                                        Task::Pop,
+                                        Task::Pair(a, pair),
+                                        Task::Push(PathSegment::ArrayIndex(i)),
+                                    ]);
+                                }
+                            }
+                        }
+
+                        result
+                    }
+                    (TypeAttributesInstance::Boolean(_), serde_json::Value::Bool(v)) => {
+                        if build {
+                            built.push(ValueImpl::Boolean(*v));
+                        }
+
+                        Ok(())
+                    }
+                    (
+                        attributes @ (TypeAttributesInstance::Int32(_)
+                        | TypeAttributesInstance::Int64(_)
+                        | TypeAttributesInstance::Uint32(_)
+                        | TypeAttributesInstance::Uint64(_)
+                        | TypeAttributesInstance::Float32(_)
+                        | TypeAttributesInstance::Float64(_)),
+                        serde_json::Value::Number(v),
+                    ) => ValueImpl::parse_number(context.options, attributes, v)
+                        .map(|v| build_value(&mut built, build, v)),
+                    (attributes, serde_json::Value::String(v)) if build => {
+                        seed::parse_str(context, attributes, Cow::Borrowed(v), |v| {
+                            value_string(v.into_owned())
+                        })
+                        .map(|v| built.push(v))
+                    }
+                    (attributes, serde_json::Value::String(v)) => {
+                        validate_str(context, attributes, v)
+                    }
+                    (_, serde_json::Value::Null) => ValueImpl::parse_null(context, instance)
+                        .map(|v| build_value(&mut built, build, v)),
+                    (attributes, value) => Err(ParseErrorKind::UnexpectedValue {
+                        expected: attributes.kind(),
+                        found: json_kind(value),
+                    }),
+                }
+            }
+            Task::Check(instance, violations_before) => {
+                building -= 1;
+
+                // Validators are only invoked on structurally valid values, which were built.
+                if violations.len() == violations_before {
+                    let value = built.pop().expect("a valid node is built");
+
+                    run_validator(instance, value).map(|v| build_value(&mut built, building > 0, v))
+                } else {
+                    Ok(())
+                }
+            }
+            Task::Entry(a, k, v) => {
+                context.path.push(PathSegment::DictionaryKey(k.clone()));
+                context.enter_node()?;
+
+                let keys = keys.last_mut().expect("a dictionary is being validated");
+                let skipped = skip_nulls && v.is_null();
+
+                match parse_key(context, a, k)
+                    .and_then(|key| insert_key(keys, Some(k), &key, !skipped).map(|()| key))
+                {
+                    Ok(key) => build_value(&mut built, building > 0 && !skipped, key.into_owned()),
+                    Err(err) => violations.push(Violation {
+                        path: context.path.to_path(),
+                        err,
+                    }),
+                }
+
+                tasks.push(Task::Pop);
+
+                if !skipped {
+                    tasks.push(Task::Value(a.values_type_id(), v));
+                }
+
+                Ok(())
+            }
+            Task::Pair(a, pair) => {
+                context.enter_node()?;
+
+                match pair {
+                    serde_json::Value::Array(pair) if pair.len() == 2 => {
+                        let (key, value) = (&pair[0], &pair[1]);
+                        let skipped = skip_nulls && value.is_null();
+
+                        if !skipped {
+                            tasks.extend([
+                                Task::Pop,
+                                Task::Value(a.values_type_id(), value),
+                                Task::Push(PathSegment::ArrayIndex(1)),
+                            ]);
+                        }
+
+                        // Keys are built to be compared with the other keys.
+                        tasks.extend([
+                            Task::PairKey(violations.len(), key.as_str(), !skipped),
+                            Task::Pop,
+                            Task::Value(a.keys_type_id(), key),
+                            Task::Push(PathSegment::ArrayIndex(0)),
+                        ]);
+                        building += 1;
+
+                        Ok(())
+                    }
+                    serde_json::Value::Array(pair) => {
+                        Err(ParseErrorKind::InvalidDictionaryPair(pair.len()))
+                    }
+                    pair => Err(ParseErrorKind::UnexpectedValue {
+                        expected: "[key, value] pair",
+                        found: json_kind(pair),
+                    }),
+                }
+            }
+            Task::PairKey(violations_before, raw, present) => {
+                building -= 1;
+
+                if violations.len() == violations_before {
+                    // Duplicate keys are reported at their pairs, as when parsing.
+                    let key = built.pop().expect("a valid node is built");
+                    let result = insert_key(
+                        keys.last_mut().expect("a dictionary is being validated"),
+                        raw,
+                        &key,
+                        present,
+                    );
+
+                    if result.is_ok() {
+                        build_value(&mut built, building > 0 && present, key);
+                    }
+
+                    result
+                } else {
+                    let key_violations: Vec<_> = violations
+                        .drain(violations_before..)
+                        .map(|violation| Violation {
+                            path: violation.path,
+                            err: ParseErrorKind::InvalidDictionaryKey(Box::new(violation.err)),
+                        })
+                        .collect();
+
+                    violations.extend(key_violations);
+
+                    Ok(())
+                }
+            }
+            Task::Push(segment) => {
+                context.path.push(segment);
+
+                Ok(())
+            }
+            Task::Pop => {
+                context.path.pop();
+
+                Ok(())
+            }
+            Task::Leave {
+                dictionary,
+                violations_before,
+                built_from,
+            } => {
+                context.leave_collection();
+
+                let result = match dictionary {
+                    Some((a, value)) => {
+                        keys.pop();
+                        check_entry_rules(a, value, violations.len() == violations_before)
+                    }
+                    None => Ok(()),
+                };
+
+                // The elements of a valid collection were all built.
+                if let Some(from) = built_from {
+                    let mut elements = built.split_off(from);
+
+                    if violations.len() == violations_before && result.is_ok() {
+                        let value = match dictionary {
+                            Some((a, _)) => {
+                                let mut items = Vec::with_capacity(elements.len() / 2);
+                                let mut elements = elements.drain(..);
+
+                                while let (Some(key), Some(value)) =
+                                    (elements.next(), elements.next())
+                                {
+                                    items.push((key, value));
+                                }
+
+                                if context.options.apply_defaults {
+                                    ValueImpl::apply_defaults(a, &mut items);
+                                }
+
+                                ValueImpl::Dictionary(items)
+                            }
+                            None => ValueImpl::Array(std::mem::take(&mut elements)),
+                        };
+
+                        built.push(value);
+                    }
+                }
+
+                result
+            }
+        };
+
+        if let Err(err) = result {
+            violations.push(Violation {
+                path: context.path.to_path(),
+                err,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the entry rules of a dictionary, if its entries are valid.
+///
+/// Constraints and requirements are only meaningful between valid entries, which are all pairs in
+/// the pairs wire form.
+fn check_entry_rules<Id, FieldName: Ord + Clone + Borrow<str>>(
+    attributes: &DictionaryTypeAttributesInstance<Id, FieldName>,
+    value: &serde_json::Value,
+    valid: bool,
+) -> Result<(), ParseErrorKind> {
+    if !(valid && attributes.has_entry_rules()) {
+        return Ok(());
+    }
+
+    let entries: Vec<_> = match value {
+        serde_json::Value::Object(v) => v.iter().map(|(k, v)| (k.as_str(), v)).collect(),
+        serde_json::Value::Array(v) => v
+            .iter()
+            .filter_map(|pair| match pair {
+                serde_json::Value::Array(pair) => Some((pair[0].as_str()?, &pair[1])),
+                _ => None,
+            })
+            .collect(),
+        _ => unreachable!("dictionaries are objects or arrays of pairs"),
+    };
+
+    check_json_constraints(attributes, &entries)
+}
+
+/// Record the key of a dictionary entry among the keys of its dictionary, unless the entry is
+/// absent.
+///
+/// Distinct keys in the source can be the same key once parsed, through enum aliases or string
+/// normalization: they are rejected like keys that are repeated as they are. Keys are borrowed
+/// from the validated value, unless parsing changed them.
+fn insert_key<'v, FieldName: Borrow<str>, S: Borrow<str>>(
+    keys: &mut BTreeSet<Cow<'v, str>>,
+    raw: Option<&'v str>,
+    key: &ValueImpl<FieldName, S>,
+    present: bool,
+) -> Result<(), ParseErrorKind> {
+    let text = key.key_text();
+
+    if keys.contains(text.as_ref()) {
+        return Err(ParseErrorKind::DuplicateKey(text.into_owned()));
+    }
+
+    if present {
+        keys.insert(match raw {
+            Some(raw) if raw == text => Cow::Borrowed(raw),
+            _ => Cow::Owned(text.into_owned()),
+        });
+    }
+
+    Ok(())
+}
+
+/// Keep a value that was built, if its node is built.
+fn build_value<FieldName>(
+    built: &mut Vec<ValueImpl<FieldName>>,
+    build: bool,
+    value: ValueImpl<FieldName>,
+) {
+    if build {
+        built.push(value);
+    }
+}
+
+impl<Id, FieldName: Ord + Clone + Borrow<str>> Value<Id, FieldName> {
+    /// Check the value against another type instance, such as a newer version of its type.
+    ///
+    /// All the incompatibilities are reported, as if the value was parsed for the other type
+    /// instance. This allows previewing a schema upgrade without reparsing the source JSON.
+    ///
+    /// The value is checked as it is, not as its JSON representation: wire forms and bytes
+    /// encodings do not matter, and non-finite numbers are valid floating-point numbers.
+    pub fn revalidate(
+        &self,
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+    ) -> ValidationReport {
+        self.revalidate_with_options(instance, &ParseOptions::default())
+    }
+
+    /// Check the value against another type instance, using the specified parse options.
+    pub fn revalidate_with_options(
+        &self,
+        instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+        options: &ParseOptions,
+    ) -> ValidationReport {
+        report(instance, options, |context, violations| {
+            revalidate_for(context, instance, &self.value, violations)
+        })
+    }
+}
+
+/// A step of the revalidation of a GameSON value.
+enum Revalidation<'v, 'i, Id, FieldName: Ord> {
+    /// Check a value against a type instance, at the current path.
+    Value(
+        &'i Arc<TypeDefinitionInstance<Id, FieldName>>,
+        &'v ValueImpl<FieldName>,
+    ),
+
+    /// Check the entry rules and run the validator of a type instance on the value built for it,
+    /// once checked, given the number of violations before it.
+    Check(&'i Arc<TypeDefinitionInstance<Id, FieldName>>, usize),
+
+    /// Check an entry of a dictionary, given its position.
+    Entry(
+        &'i DictionaryTypeAttributesInstance<Id, FieldName>,
+        usize,
+        &'v DictionaryEntry<FieldName, ValueString>,
+    ),
+
+    /// Check the key of an entry, once checked, given the number of violations before it and the
+    /// key if it is a string.
+    Key(usize, Option<&'v str>),
+
+    /// Append a segment to the path.
+    Push(PathSegment),
+
+    /// Remove the last segment of the path.
+    Pop,
+
+    /// Leave an array or a dictionary, given the number of violations before its elements.
+    ///
+    /// If the collection is built, it is built from the values built for its elements, from the
+    /// given position.
+    Leave {
+        /// The dictionary being left, if it is one.
+        dictionary: Option<&'i DictionaryTypeAttributesInstance<Id, FieldName>>,
+
+        /// The number of violations before the elements.
+        violations_before: usize,
+
+        /// The position of the values built for the elements, if the collection is built.
+        built_from: Option<usize>,
+    },
+}
+
+/// Check a GameSON value against a type instance.
+///
+/// This mirrors [`validate_for`], walking the value rather than its JSON representation. Values
+/// are only built for the validators, for the dictionaries with entry rules and for the keys:
+/// the values of those nodes, and of their descendants, are converted bottom-up as they are
+/// checked.
+fn revalidate_for<Id, FieldName: Ord + Clone + Borrow<str>>(
+    context: &mut ParseContext<'_>,
+    instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+    value: &ValueImpl<FieldName>,
+    violations: &mut Vec<Violation>,
+) -> Result<(), ParseErrorKind> {
+    let mut tasks = vec![Revalidation::Value(instance, value)];
+
+    // The parsed keys of the dictionaries being checked, innermost last.
+    let mut keys: Vec<BTreeSet<Cow<'_, str>>> = Vec::new();
+
+    // The values built so far, and the number of enclosing nodes that are built.
+    let mut built: Vec<ValueImpl<FieldName>> = Vec::new();
+    let mut building = 0;
+
+    while let Some(task) = tasks.pop() {
+        let result = match task {
+            Revalidation::Value(instance, value) => {
+                context.enter_node()?;
+
+                // Entry rules and validators are checked once the value is checked and built.
+                let has_entry_rules = matches!(
+                    &instance.attributes,
+                    TypeAttributesInstance::Dictionary(a) if a.has_entry_rules()
+                );
+
+                if instance.validator.is_some() || has_entry_rules {
+                    building += 1;
+                    tasks.push(Revalidation::Check(instance, violations.len()));
+                }
+
+                let build = building > 0;
+
+                match (&instance.attributes, value) {
+                    (TypeAttributesInstance::Array(a), ValueImpl::Array(v)) => {
+                        let result = context.check_array_length(v.len());
+
+                        // There is no point in inspecting the items of an array that is too long.
+                        if result.is_ok() {
+                            context.check_array_near_limit(v.len());
+                            context.enter_collection()?;
+
+                            tasks.push(Revalidation::Leave {
+                                dictionary: None,
+                                violations_before: violations.len(),
+                                built_from: build.then_some(built.len()),
+                            });
+
+                            for (i, v) in v.iter().enumerate().rev() {
+                                tasks.extend([
+                                    Revalidation::Pop,
+                                    Revalidation::Value(a.items_type_id(), v),
+                                    Revalidation::Push(PathSegment::ArrayIndex(i)),
+                                ]);
+                            }
+                        }
+
+                        result
+                    }
+                    (TypeAttributesInstance::Dictionary(a), ValueImpl::Dictionary(v)) => {
+                        let result = match a.wire_form() {
+                            DictionaryWireForm::Object => Ok(()),
+                            DictionaryWireForm::Pairs => context.check_array_length(v.len()),
+                        };
+
+                        // There is no point in inspecting the entries of a dictionary that is too
+                        // long.
+                        if result.is_ok() {
+                            if a.wire_form() == DictionaryWireForm::Pairs {
+                                context.check_array_near_limit(v.len());
+                            }
+
+                            context.enter_collection()?;
+
+                            keys.push(BTreeSet::new());
+                            tasks.push(Revalidation::Leave {
+                                dictionary: Some(a),
+                                violations_before: violations.len(),
+                                built_from: build.then_some(built.len()),
+                            });
+                            tasks.extend(
+                                v.iter()
+                                    .enumerate()
+                                    .rev()
+                                    .map(|(i, entry)| Revalidation::Entry(a, i, entry)),
+                            );
+                        }
+
+                        result
+                    }
+                    (attributes, value) => revalidate_scalar(context, attributes, value, build)
+                        .map(|value| built.extend(value)),
+                }
+            }
+            Revalidation::Check(instance, violations_before) => {
+                building -= 1;
+
+                // Entry rules and validators are only checked on otherwise valid values, which
+                // were built.
+                if violations.len() == violations_before {
+                    let value = built.pop().expect("a valid node is built");
+
+                    ValueImpl::check_for(instance, value)
+                        .map(|v| build_value(&mut built, building > 0, v))
+                } else {
+                    Ok(())
+                }
+            }
+            Revalidation::Entry(a, i, (k, v)) => {
+                let raw = match k {
+                    ValueImpl::String(k) => Some(k.borrow()),
+                    ValueImpl::Enum(k) => Some(k.borrow()),
+                    _ => None,
+                };
+
+                // Entries are located as they would be in the wire form of the dictionary.
+                match a.wire_form() {
+                    DictionaryWireForm::Object => tasks.extend([
+                        Revalidation::Pop,
+                        Revalidation::Value(a.values_type_id(), v),
+                        Revalidation::Key(violations.len(), raw),
+                        Revalidation::Value(a.keys_type_id(), k),
+                        Revalidation::Push(PathSegment::DictionaryKey(k.key_text().into_owned())),
+                    ]),
+                    DictionaryWireForm::Pairs => tasks.extend([
+                        Revalidation::Pop,
+                        Revalidation::Pop,
+                        Revalidation::Value(a.values_type_id(), v),
+                        Revalidation::Push(PathSegment::ArrayIndex(1)),
+                        Revalidation::Key(violations.len(), raw),
+                        Revalidation::Pop,
+                        Revalidation::Value(a.keys_type_id(), k),
+                        Revalidation::Push(PathSegment::ArrayIndex(0)),
+                        Revalidation::Push(PathSegment::ArrayIndex(i)),
+                    ]),
+                }
+
+                // Keys are built to be compared with the other keys.
+                building += 1;
+
+                Ok(())
+            }
+            Revalidation::Key(violations_before, raw) => {
+                building -= 1;
+
+                if violations.len() == violations_before {
+                    let key = built.pop().expect("a valid node is built");
+                    let result = insert_key(
+                        keys.last_mut().expect("a dictionary is being checked"),
+                        raw,
+                        &key,
+                        true,
+                    );
+
+                    if result.is_ok() {
+                        build_value(&mut built, building > 0, key);
+                    }
+
+                    result
+                } else {
+                    let key_violations: Vec<_> = violations
+                        .drain(violations_before..)
+                        .map(|violation| Violation {
+                            path: violation.path,
+                            err: ParseErrorKind::InvalidDictionaryKey(Box::new(violation.err)),
+                        })
+                        .collect();
+
+                    violations.extend(key_violations);
+
+                    Ok(())
+                }
+            }
+            Revalidation::Push(segment) => {
+                context.path.push(segment);
+
+                Ok(())
+            }
+            Revalidation::Pop => {
+                context.path.pop();
+
+                Ok(())
+            }
+            Revalidation::Leave {
+                dictionary,
+                violations_before,
+                built_from,
+            } => {
+                context.leave_collection();
+
+                if dictionary.is_some() {
+                    keys.pop();
+                }
+
+                // The elements of a valid collection were all built.
+                if let Some(from) = built_from {
+                    let mut elements = built.split_off(from);
+
+                    if violations.len() == violations_before {
+                        let value = match dictionary {
+                            Some(a) => {
+                                let mut items = Vec::with_capacity(elements.len() / 2);
+                                let mut elements = elements.drain(..);
+
+                                while let (Some(key), Some(value)) =
+                                    (elements.next(), elements.next())
+                                {
+                                    items.push((key, value));
+                                }
+
+                                if context.options.apply_defaults {
+                                    ValueImpl::apply_defaults(a, &mut items);
+                                }
+
+                                ValueImpl::Dictionary(items)
+                            }
+                            None => ValueImpl::Array(std::mem::take(&mut elements)),
+                        };
+
+                        built.push(value);
+                    }
+                }
+
+                Ok(())
+            }
+        };
+
+        if let Err(err) = result {
+            violations.push(Violation {
+                path: context.path.to_path(),
+                err,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Check a GameSON value against type attributes, unless it is an array or a dictionary,
+/// returning the converted value if it is built.
+fn revalidate_scalar<Id, FieldName: Ord + Clone + Borrow<str>>(
+    context: &mut ParseContext<'_>,
+    attributes: &TypeAttributesInstance<Id, FieldName>,
+    value: &ValueImpl<FieldName>,
+    build: bool,
+) -> Result<Option<ValueImpl<FieldName>>, ParseErrorKind> {
+    let unexpected = |found| ParseErrorKind::UnexpectedValue {
+        expected: attributes.kind(),
+        found,
+    };
+
+    // Strings are only converted if built, as they are then copied.
+    let text = match (value, attributes) {
+        (ValueImpl::Array(_), _) => return Err(unexpected("array")),
+        (ValueImpl::Dictionary(_), _) => return Err(unexpected("dictionary")),
+        (ValueImpl::Boolean(v), TypeAttributesInstance::Boolean(_)) => {
+            return Ok(build.then_some(ValueImpl::Boolean(*v)));
+        }
+        (ValueImpl::Boolean(_), _) => return Err(unexpected("boolean")),
+        (ValueImpl::String(v), _) => Cow::Borrowed(v.borrow()),
+        (ValueImpl::Enum(v), _) => Cow::Borrowed(v.borrow()),
+        #[cfg(feature = "uuid")]
+        (ValueImpl::Uuid(v), TypeAttributesInstance::Uuid(_)) => {
+            return Ok(build.then_some(ValueImpl::Uuid(*v)));
+        }
+        #[cfg(feature = "uuid")]
+        (ValueImpl::Uuid(v), _) => Cow::Owned(v.to_string()),
+        #[cfg(feature = "bytes")]
+        (ValueImpl::Bytes(v), TypeAttributesInstance::Bytes(_)) => {
+            return Ok(build.then(|| ValueImpl::Bytes(v.clone())));
+        }
+        #[cfg(feature = "bytes")]
+        (ValueImpl::Bytes(_), _) => return Err(unexpected("bytes")),
+        (number, _) => {
+            let value = match number.json_number().expect("the value is a number") {
+                Some(v) => ValueImpl::parse_number(context.options, attributes, &v),
+                None => ValueImpl::parse_non_finite(attributes, number.as_f64()),
+            };
+
+            return value.map(|value| build.then_some(value));
+        }
+    };
+
+    if build {
+        seed::parse_str(context, attributes, text, |v| value_string(v.into_owned())).map(Some)
+    } else {
+        validate_str(context, attributes, &text).map(|()| None)
+    }
+}
+
+/// Parse a JSON dictionary key, to compare it with the other keys of its dictionary.
+fn parse_key<'k, Id, FieldName: Ord + Clone + Borrow<str>>(
+    context: &mut ParseContext<'_>,
+    attributes: &DictionaryTypeAttributesInstance<Id, FieldName>,
+    key: &'k str,
+) -> Result<ValueImpl<FieldName, Cow<'k, str>>, ParseErrorKind> {
+    seed::parse_str(
+        context,
+        &attributes.keys_type_id().attributes,
+        Cow::Borrowed(key),
+        |v| v,
+    )
+    .map_err(|err| ParseErrorKind::InvalidDictionaryKey(Box::new(err)))
+}
+
+impl<FieldName: Borrow<str>, S: Borrow<str>> ValueImpl<FieldName, S> {
+    /// Get the JSON number representing the value, if it is a number.
+    ///
+    /// Non-finite numbers have no JSON representation and yield `Some(None)`.
+    pub(super) fn json_number(&self) -> Option<Option<serde_json::Number>> {
+        match self {
+            Self::Int32(v) => Some(Some((*v).into())),
+            Self::Int64(v) => Some(Some((*v).into())),
+            Self::Uint32(v) => Some(Some((*v).into())),
+            Self::Uint64(v) => Some(Some((*v).into())),
+            // Float32 values are written with their shortest representation.
+            Self::Float32(v) => Some(
+                v.to_string()
+                    .parse()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64),
+            ),
+            Self::Float64(v) => Some(serde_json::Number::from_f64(*v)),
+            _ => None,
+        }
+    }
+
+    /// Get the string representing a dictionary key.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the value cannot be a dictionary key.
+    pub(super) fn key_string(&self) -> Cow<'_, str> {
+        match self {
+            Self::String(v) => Cow::Borrowed(v.borrow()),
+            Self::Enum(v) => Cow::Borrowed(v.borrow()),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(v) => Cow::Owned(v.to_string()),
+            _ => panic!("not a dictionary key"),
+        }
+    }
+
+    /// Get a string identifying a dictionary key among the keys of its dictionary.
+    ///
+    /// Unlike [`Self::key_string`], this supports the keys of dictionaries in the pairs wire form,
+    /// which can be of any type: the strings nested in those are quoted, so that distinct keys
+    /// are identified by distinct strings.
+    pub(super) fn key_text(&self) -> Cow<'_, str> {
+        match self {
+            Self::String(_) | Self::Enum(_) => return self.key_string(),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(_) => return self.key_string(),
+            _ => {}
+        }
+
+        let mut text = String::new();
+        self.write_key_text(&mut text)
+            .expect("writing to a string never fails");
+
+        Cow::Owned(text)
+    }
+
+    /// Write the string identifying a dictionary key, as returned by [`Self::key_text`].
+    ///
+    /// Arrays and dictionaries are written with an explicit stack rather than recursively.
+    pub(super) fn write_key_text(&self, out: &mut impl std::fmt::Write) -> std::fmt::Result {
+        /// A part of the text left to write.
+        enum Part<'v, FieldName, S> {
+            /// A value.
+            Value(&'v ValueImpl<FieldName, S>),
+
+            /// A delimiter.
+            Delimiter(&'static str),
+        }
+
+        match self {
+            Self::String(_) | Self::Enum(_) => return out.write_str(&self.key_string()),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(v) => return write!(out, "{v}"),
+            _ => {}
+        }
+
+        let mut parts = vec![Part::Value(self)];
+
+        while let Some(part) = parts.pop() {
+            let value = match part {
+                Part::Value(value) => value,
+                Part::Delimiter(delimiter) => {
+                    out.write_str(delimiter)?;
+                    continue;
+                }
+            };
+
+            match value {
+                Self::Array(items) => {
+                    out.write_char('[')?;
+                    parts.push(Part::Delimiter("]"));
+
+                    for (i, item) in items.iter().enumerate().rev() {
+                        parts.push(Part::Value(item));
+
+                        if i > 0 {
+                            parts.push(Part::Delimiter(", "));
+                        }
+                    }
+                }
+                Self::Dictionary(entries) => {
+                    out.write_char('{')?;
+                    parts.push(Part::Delimiter("}"));
+
+                    for (i, (k, v)) in entries.iter().enumerate().rev() {
+                        parts.extend([Part::Value(v), Part::Delimiter(": "), Part::Value(k)]);
+
+                        if i > 0 {
+                            parts.push(Part::Delimiter(", "));
+                        }
+                    }
+                }
+                Self::Boolean(v) => write!(out, "{v}")?,
+                Self::Int32(v) => write!(out, "{v}")?,
+                Self::Int64(v) => write!(out, "{v}")?,
+                Self::Uint32(v) => write!(out, "{v}")?,
+                Self::Uint64(v) => write!(out, "{v}")?,
+                Self::Float32(v) => write!(out, "{v:?}")?,
+                Self::Float64(v) => write!(out, "{v:?}")?,
+                Self::String(v) => write!(out, "{:?}", v.borrow())?,
+                Self::Enum(v) => write!(out, "{:?}", v.borrow())?,
+                #[cfg(feature = "uuid")]
+                Self::Uuid(v) => write!(out, "{v:?}")?,
+                #[cfg(feature = "bytes")]
+                Self::Bytes(v) => write!(out, "{v:?}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Validate a JSON string for the specified type attributes.
+fn validate_str<Id, FieldName: Ord + Clone + Borrow<str>>(
+    context: &mut ParseContext<'_>,
+    attributes: &TypeAttributesInstance<Id, FieldName>,
+    v: &str,
+) -> Result<(), ParseErrorKind> {
+    match attributes {
+        TypeAttributesInstance::String(_) => context.check_string_length(v),
+        TypeAttributesInstance::Enum(a) => {
+            context.check_string_length(v)?;
+            context.resolve_enum(a, v)?;
+
+            Ok(())
+        }
+        #[cfg(feature = "uuid")]
+        TypeAttributesInstance::Uuid(_) => {
+            uuid::Uuid::parse_str(v)?;
+
+            Ok(())
+        }
+        #[cfg(feature = "bytes")]
+        TypeAttributesInstance::Bytes(a) => {
+            context.check_string_length(v)?;
+            a.encoding().decode(v)?;
+
+            Ok(())
+        }
+        attributes => Err(ParseErrorKind::UnexpectedValue {
+            expected: attributes.kind(),
+            found: "string",
+        }),
+    }
+}
@@ -0,0 +1,256 @@
+//! Custom validation of GameSON values.
+
+use std::{fmt::Debug, sync::Arc};
+
+use crate::TypeDefinitionInstance;
+
+use super::{ParseErrorKind, Value, ValueImpl};
+
+/// A custom validator of the values of a type, for the domain rules that type attributes cannot
+/// express, such as a start date preceding an end date.
+///
+/// Validators are attached to registered types with
+/// [`TypeDefinitionRegistry::set_validator`](crate::TypeDefinitionRegistry::set_validator). They
+/// are invoked whenever a value of their type is parsed, nested ones included, once the value is
+/// structurally valid: a rejected value fails to parse with [`ParseErrorKind::Rejected`].
+///
+/// Validation of JSON values with
+/// [`TypeDefinitionInstance::validate`](crate::TypeDefinitionInstance::validate) invokes them as
+/// well, reporting rejections as violations, so that validation and parsing agree.
+pub trait Validator<Id, FieldName: Ord>: Debug + Send + Sync {
+    /// Validate a value, returning why it is rejected if it is.
+    fn validate(&self, value: &Value<Id, FieldName>) -> Result<(), String>;
+}
+
+/// Run the validator of a type instance, if it has one, on a parsed value.
+pub(super) fn run_validator<Id, FieldName: Ord>(
+    instance: &Arc<TypeDefinitionInstance<Id, FieldName>>,
+    value: ValueImpl<FieldName>,
+) -> Result<ValueImpl<FieldName>, ParseErrorKind> {
+    let Some(validator) = &instance.validator else {
+        return Ok(value);
+    };
+
+    let value = Value {
+        instance: Arc::clone(instance),
+        value,
+    };
+
+    match validator.validate(&value) {
+        Ok(()) => Ok(value.value),
+        Err(reason) => Err(ParseErrorKind::Rejected(reason)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::Validator;
+    use crate::{
+        ParseErrorKind, PathSegment,
+        test_support::{FieldName, Id, TypeAttributes, Value, registry, td},
+        type_attributes::{ArrayTypeAttributes, DictionaryTypeAttributes, DictionaryWireForm},
+    };
+
+    /// A validator of arrays of numbers, that must be sorted.
+    #[derive(Debug)]
+    struct Sorted;
+
+    impl Validator<Id, FieldName> for Sorted {
+        fn validate(&self, value: &Value) -> Result<(), String> {
+            let items =
+                Vec::<u32>::deserialize(value.as_deserializer()).map_err(|err| err.to_string())?;
+
+            match items.is_sorted() {
+                true => Ok(()),
+                false => Err(format!("{items:?} is not sorted")),
+            }
+        }
+    }
+
+    /// A validator that accepts all values, counting them.
+    #[derive(Debug, Default)]
+    struct Counter(AtomicUsize);
+
+    impl Counter {
+        /// Get the number of values validated so far, and reset it.
+        fn take(&self) -> usize {
+            self.0.swap(0, Ordering::Relaxed)
+        }
+    }
+
+    impl Validator<Id, FieldName> for Counter {
+        fn validate(&self, _: &Value) -> Result<(), String> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_validator() {
+        let mut registry = registry([
+            td(1, "Frame", TypeAttributes::Uint32(Default::default())),
+            td(
+                2,
+                "Keyframes",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(
+                3,
+                "Tracks",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+        ]);
+
+        let instances = registry.set_validator(&2, Some(Arc::new(Sorted))).unwrap();
+        assert_eq!(instances.iter().map(|i| i.id).collect::<Vec<_>>(), [2, 3]);
+
+        let tracks = registry.get(&3).unwrap().clone();
+
+        Value::parse_for(tracks.clone(), json!([[1, 2], [3, 5]])).unwrap();
+
+        let err = Value::parse_for(tracks.clone(), json!([[1, 2], [5, 3]])).unwrap_err();
+        assert_eq!(err.path(), [PathSegment::ArrayIndex(1)]);
+        assert!(
+            matches!(err.kind(), ParseErrorKind::Rejected(reason) if reason == "[5, 3] is not sorted")
+        );
+
+        let err = Value::parse_str_for(tracks, "[[2, 1]]").unwrap_err();
+        assert_eq!(err.kind().code(), "rejected");
+
+        // Replacing a type definition keeps its validator.
+        registry
+            .replace(td(
+                2,
+                "Keyframes",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ))
+            .unwrap();
+        Value::parse_for(registry.get(&2).unwrap().clone(), json!([2, 1])).unwrap_err();
+
+        registry.set_validator(&2, None).unwrap();
+        Value::parse_for(registry.get(&2).unwrap().clone(), json!([2, 1])).unwrap();
+
+        assert!(registry.set_validator(&4, None).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut registry = registry([
+            td(1, "Frame", TypeAttributes::Uint32(Default::default())),
+            td(
+                2,
+                "Keyframes",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(
+                3,
+                "Tracks",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+        ]);
+
+        let unsorted = Value::parse_for(registry.get(&2).unwrap().clone(), json!([2, 1])).unwrap();
+
+        registry.set_validator(&2, Some(Arc::new(Sorted))).unwrap();
+        let tracks = registry.get(&3).unwrap().clone();
+
+        assert!(tracks.validate(&json!([[1, 2], [3, 5]])).is_valid());
+
+        // Validation agrees with parsing.
+        let report = tracks.validate(&json!([[1, 2], [5, 3], [4, 1]]));
+        assert_eq!(report.errors().len(), 2);
+        assert_eq!(report.errors()[0].path(), [PathSegment::ArrayIndex(1)]);
+        assert!(
+            matches!(report.errors()[0].kind(), ParseErrorKind::Rejected(reason) if reason == "[5, 3] is not sorted")
+        );
+        assert_eq!(
+            report.errors()[1].to_string(),
+            "[2]: rejected: [4, 1] is not sorted"
+        );
+
+        // Validators only run on otherwise valid values.
+        let report = tracks.validate(&json!([[5, -3]]));
+        assert_eq!(report.errors().len(), 1);
+        assert_ne!(report.errors()[0].kind().code(), "rejected");
+
+        #[cfg(feature = "rayon")]
+        {
+            let reports = tracks.validate_many(vec![json!([[2, 1]]), json!([[1, 2]])]);
+            assert!(!reports[0].is_valid());
+            assert!(reports[1].is_valid());
+        }
+
+        let report = unsorted.revalidate(registry.get(&2).unwrap());
+        assert_eq!(report.errors()[0].kind().code(), "rejected");
+    }
+
+    #[test]
+    fn test_validate_runs_validators_once() {
+        let mut registry = registry([
+            td(1, "Frame", TypeAttributes::Uint32(Default::default())),
+            td(
+                2,
+                "Keyframes",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+            td(
+                3,
+                "Tracks",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(2, 4).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+            td(
+                4,
+                "Curve",
+                TypeAttributes::Array(ArrayTypeAttributes::new(1)),
+            ),
+        ]);
+
+        let counters: Vec<_> = (1..=3)
+            .map(|id| {
+                let counter = Arc::new(Counter::default());
+                registry.set_validator(&id, Some(counter.clone())).unwrap();
+
+                counter
+            })
+            .collect();
+
+        let tracks = registry.get(&3).unwrap().clone();
+        let json = json!([[[1, 2], [3, 5, 8]], [[13], [21]]]);
+
+        // Each value, keys included, is validated once, however deeply it is nested.
+        assert!(tracks.validate(&json).is_valid());
+        assert_eq!(
+            counters.iter().map(|c| c.take()).collect::<Vec<_>>(),
+            [7, 2, 1]
+        );
+
+        // Parsing agrees.
+        Value::parse_for(tracks.clone(), json).unwrap();
+        assert_eq!(
+            counters.iter().map(|c| c.take()).collect::<Vec<_>>(),
+            [7, 2, 1]
+        );
+
+        // Values with violations are not validated, nor are their ancestors.
+        assert!(
+            !tracks
+                .validate(&json!([[[1], [-2]], [[3], [5]]]))
+                .is_valid()
+        );
+        assert_eq!(
+            counters.iter().map(|c| c.take()).collect::<Vec<_>>(),
+            [3, 2, 0]
+        );
+    }
+}
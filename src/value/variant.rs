@@ -0,0 +1,286 @@
+//! Configuration variants: values with overrides conditioned on a set of variants.
+
+use std::{borrow::Borrow, collections::BTreeMap, fmt::Display, str::FromStr};
+
+use crate::ParseOptions;
+
+use super::{PathSegment, PersistentValueError, Prefab, Value};
+
+/// A set of variants, each being the value of a dimension, like `platform=ps5, difficulty=hard`.
+///
+/// Variant sets are both the active variants a value is resolved for and the conditions of its
+/// overrides. They are formatted, and parsed, as comma-separated `dimension=value` pairs, sorted by
+/// dimension.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct VariantSet {
+    /// The values, by dimension.
+    values: BTreeMap<String, String>,
+}
+
+/// An error that can occur when parsing a [`VariantSet`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseVariantSetError {
+    /// A variant is not a `dimension=value` pair, with a non-empty dimension and value.
+    #[error("invalid variant `{0}`, expected `dimension=value`")]
+    InvalidVariant(String),
+
+    /// A dimension has more than one value.
+    #[error("duplicate dimension `{0}`")]
+    DuplicateDimension(String),
+}
+
+impl VariantSet {
+    /// Create an empty variant set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value of a dimension, replacing its previous value, if any.
+    pub fn with(mut self, dimension: impl Into<String>, value: impl Into<String>) -> Self {
+        self.values.insert(dimension.into(), value.into());
+        self
+    }
+
+    /// Get the value of a dimension.
+    pub fn get(&self, dimension: &str) -> Option<&str> {
+        self.values.get(dimension).map(String::as_str)
+    }
+
+    /// Get the number of dimensions that have a value.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Check whether no dimension has a value.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Check whether all the variants of another set are part of this one.
+    ///
+    /// This is the case of the conditions of the overrides that apply to this set.
+    pub fn satisfies(&self, condition: &Self) -> bool {
+        condition
+            .values
+            .iter()
+            .all(|(dimension, value)| self.get(dimension) == Some(value))
+    }
+}
+
+impl Display for VariantSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (dimension, value)) in self.values.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+
+            write!(f, "{dimension}={value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for VariantSet {
+    type Err = ParseVariantSetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut set = Self::new();
+
+        for variant in s.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+            let (dimension, value) = variant
+                .split_once('=')
+                .map(|(dimension, value)| (dimension.trim(), value.trim()))
+                .filter(|(dimension, value)| !dimension.is_empty() && !value.is_empty())
+                .ok_or_else(|| ParseVariantSetError::InvalidVariant(variant.to_owned()))?;
+
+            if set.values.contains_key(dimension) {
+                return Err(ParseVariantSetError::DuplicateDimension(
+                    dimension.to_owned(),
+                ));
+            }
+
+            set.values.insert(dimension.to_owned(), value.to_owned());
+        }
+
+        Ok(set)
+    }
+}
+
+/// A base value, along with overrides that only apply for some variants.
+///
+/// Resolving the value for a set of active variants applies all the overrides whose conditions
+/// the set [satisfies](VariantSet::satisfies): the least specific ones first, that is the ones
+/// with the fewest conditions, and in the order they were added for the same specificity, so that
+/// a `platform=ps5, difficulty=hard` override wins over a `platform=ps5` one. Overrides are
+/// applied like [`Prefab`] overrides, and are validated on resolution.
+#[derive(Debug, Clone)]
+pub struct VariantValue<Id, FieldName: Ord> {
+    /// The base value.
+    base: Prefab<Id, FieldName>,
+
+    /// The overrides, with their conditions, in the order they were added.
+    overrides: Vec<(VariantSet, Vec<PathSegment>, serde_json::Value)>,
+}
+
+impl<Id, FieldName: Ord> VariantValue<Id, FieldName> {
+    /// Create a variant value from its base value, without overrides.
+    pub fn new(base: Value<Id, FieldName>) -> Self {
+        Self {
+            base: Prefab::new(base),
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Override the value at the specified path for the variants that satisfy a condition.
+    pub fn with_override(
+        mut self,
+        condition: VariantSet,
+        path: impl Into<Vec<PathSegment>>,
+        value: serde_json::Value,
+    ) -> Self {
+        self.overrides.push((condition, path.into(), value));
+        self
+    }
+
+    /// Get the overrides, with their conditions, in the order they were added.
+    pub fn overrides(
+        &self,
+    ) -> impl Iterator<Item = (&VariantSet, &[PathSegment], &serde_json::Value)> {
+        self.overrides
+            .iter()
+            .map(|(condition, path, value)| (condition, path.as_slice(), value))
+    }
+}
+
+impl<Id: Clone + Display, FieldName: Ord + Display + Clone + Borrow<str>>
+    VariantValue<Id, FieldName>
+{
+    /// Get a copy of the base value.
+    pub fn base(&self) -> Value<Id, FieldName> {
+        self.base.template()
+    }
+
+    /// Resolve the value for a set of active variants.
+    pub fn resolve(
+        &self,
+        variants: &VariantSet,
+    ) -> Result<Value<Id, FieldName>, PersistentValueError<Id, FieldName>> {
+        self.resolve_with_options(variants, &ParseOptions::default())
+    }
+
+    /// Resolve the value for a set of active variants, using the specified parse options to parse
+    /// the overrides.
+    pub fn resolve_with_options(
+        &self,
+        variants: &VariantSet,
+        options: &ParseOptions,
+    ) -> Result<Value<Id, FieldName>, PersistentValueError<Id, FieldName>> {
+        let mut overrides: Vec<_> = self
+            .overrides
+            .iter()
+            .filter(|(condition, _, _)| variants.satisfies(condition))
+            .collect();
+
+        // The sort is stable, which keeps the order of the overrides of the same specificity.
+        overrides.sort_by_key(|(condition, _, _)| condition.len());
+
+        self.base.instantiate_with_options(
+            overrides
+                .into_iter()
+                .map(|(_, path, value)| (path, value.clone())),
+            options,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        PathSegment,
+        test_support::owned::{FieldName, Id, Value, registry_from_json},
+    };
+
+    use super::{ParseVariantSetError, VariantSet};
+
+    type VariantValue = super::VariantValue<Id, FieldName>;
+
+    fn key(key: &str) -> [PathSegment; 1] {
+        [PathSegment::DictionaryKey(key.to_owned())]
+    }
+
+    #[test]
+    fn test_variant_value() {
+        let registry = registry_from_json(json!([
+            { "id": 1, "name": "Setting", "type": "string", "attributes": {} },
+            { "id": 2, "name": "Amount", "type": "int32", "attributes": { "max": 100 } },
+            { "id": 3, "name": "Settings", "type": "dictionary", "attributes": { "keys_type_id": 1, "values_type_id": 2 } },
+        ]));
+
+        let ps5: VariantSet = "platform=ps5".parse().unwrap();
+        let ps5_hard: VariantSet = " difficulty = hard , platform=ps5".parse().unwrap();
+        assert_eq!(ps5_hard.to_string(), "difficulty=hard, platform=ps5");
+        assert_eq!(
+            "platform=ps5,platform=pc".parse::<VariantSet>(),
+            Err(ParseVariantSetError::DuplicateDimension(
+                "platform".to_owned()
+            ))
+        );
+        assert_eq!(
+            "platform".parse::<VariantSet>(),
+            Err(ParseVariantSetError::InvalidVariant("platform".to_owned()))
+        );
+
+        let value = VariantValue::new(
+            Value::parse_for(
+                registry.by_id[&3].clone(),
+                json!({ "enemies": 10, "fps": 60 }),
+            )
+            .unwrap(),
+        )
+        .with_override(ps5_hard.clone(), key("enemies"), json!(30))
+        .with_override(ps5.clone(), key("enemies"), json!(15))
+        .with_override(ps5.clone(), key("fps"), json!(30))
+        .with_override(
+            VariantSet::new().with("difficulty", "hard"),
+            key("enemies"),
+            json!(20),
+        );
+
+        let resolve = |variants: &str| {
+            value
+                .resolve(&variants.parse().unwrap())
+                .unwrap()
+                .to_json()
+                .unwrap()
+        };
+
+        assert_eq!(resolve(""), json!({ "enemies": 10, "fps": 60 }));
+        assert_eq!(resolve("platform=pc"), json!({ "enemies": 10, "fps": 60 }));
+        assert_eq!(resolve("platform=ps5"), json!({ "enemies": 15, "fps": 30 }));
+        assert_eq!(
+            resolve("platform=pc, difficulty=hard"),
+            json!({ "enemies": 20, "fps": 60 })
+        );
+
+        // The most specific override wins, whatever the order it was added in.
+        assert_eq!(
+            resolve("platform=ps5, difficulty=hard, region=eu"),
+            json!({ "enemies": 30, "fps": 30 })
+        );
+
+        // Overrides are validated on resolution.
+        let err = value
+            .clone()
+            .with_override(ps5.clone(), key("fps"), json!(120))
+            .resolve(&ps5)
+            .unwrap_err();
+        assert!(err.to_string().contains("fps"));
+        assert_eq!(
+            value.base().to_json().unwrap(),
+            json!({ "enemies": 10, "fps": 60 })
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Serialization of GameSON values to self-describing binary formats.
+
+use std::borrow::Borrow;
+
+use serde::{
+    Serialize, Serializer,
+    ser::{SerializeMap, SerializeSeq, SerializeTuple},
+};
+
+use crate::{
+    DictionaryWireForm, TypeDefinitionInstance, type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::ValueImpl;
+
+/// A value, serialized for its type instance with any `serde` serializer.
+///
+/// Values are serialized with the same structure as in JSON, so that they parse back through the
+/// same validation, except that floating-point numbers keep their precision and bytes and UUIDs
+/// are serialized as native bytes.
+pub(super) struct Wire<'a, Id, FieldName: Ord> {
+    /// The value.
+    pub(super) value: &'a ValueImpl<FieldName>,
+
+    /// The type instance of the value.
+    pub(super) instance: &'a TypeDefinitionInstance<Id, FieldName>,
+}
+
+impl<Id, FieldName: Ord + Borrow<str>> Serialize for Wire<'_, Id, FieldName> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self.value, &self.instance.attributes) {
+            (ValueImpl::Array(items), TypeAttributesInstance::Array(a)) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+
+                for item in items {
+                    seq.serialize_element(&Wire {
+                        value: item,
+                        instance: a.items_type_id(),
+                    })?;
+                }
+
+                seq.end()
+            }
+            (ValueImpl::Dictionary(entries), TypeAttributesInstance::Dictionary(a)) => {
+                match a.wire_form() {
+                    DictionaryWireForm::Object => {
+                        let mut map = serializer.serialize_map(Some(entries.len()))?;
+
+                        for (key, value) in entries {
+                            map.serialize_entry(
+                                &key.key_string(),
+                                &Wire {
+                                    value,
+                                    instance: a.values_type_id(),
+                                },
+                            )?;
+                        }
+
+                        map.end()
+                    }
+                    DictionaryWireForm::Pairs => {
+                        let mut seq = serializer.serialize_seq(Some(entries.len()))?;
+
+                        for (key, value) in entries {
+                            seq.serialize_element(&Pair {
+                                key: Wire {
+                                    value: key,
+                                    instance: a.keys_type_id(),
+                                },
+                                value: Wire {
+                                    value,
+                                    instance: a.values_type_id(),
+                                },
+                            })?;
+                        }
+
+                        seq.end()
+                    }
+                }
+            }
+            (ValueImpl::Array(_) | ValueImpl::Dictionary(_), _) => {
+                panic!("inconsistent value and type attributes");
+            }
+            (ValueImpl::Boolean(v), _) => serializer.serialize_bool(*v),
+            (ValueImpl::Int32(v), _) => serializer.serialize_i32(*v),
+            (ValueImpl::Int64(v), _) => serializer.serialize_i64(*v),
+            (ValueImpl::Uint32(v), _) => serializer.serialize_u32(*v),
+            (ValueImpl::Uint64(v), _) => serializer.serialize_u64(*v),
+            (ValueImpl::Float32(v), _) => serializer.serialize_f32(*v),
+            (ValueImpl::Float64(v), _) => serializer.serialize_f64(*v),
+            (ValueImpl::String(v), _) => serializer.serialize_str(v),
+            (ValueImpl::Enum(v), _) => serializer.serialize_str(v.borrow()),
+            #[cfg(feature = "uuid")]
+            (ValueImpl::Uuid(v), _) => serializer.serialize_bytes(v.as_bytes()),
+            #[cfg(feature = "bytes")]
+            (ValueImpl::Bytes(v), _) => serializer.serialize_bytes(v),
+        }
+    }
+}
+
+/// A dictionary entry in the pairs wire form.
+struct Pair<'a, Id, FieldName: Ord> {
+    /// The key.
+    key: Wire<'a, Id, FieldName>,
+
+    /// The value.
+    value: Wire<'a, Id, FieldName>,
+}
+
+impl<Id, FieldName: Ord + Borrow<str>> Serialize for Pair<'_, Id, FieldName> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&self.key)?;
+        tuple.serialize_element(&self.value)?;
+        tuple.end()
+    }
+}
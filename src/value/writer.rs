@@ -0,0 +1,439 @@
+//! Incremental serialization of GameSON arrays and dictionaries.
+
+use std::{
+    borrow::Borrow,
+    fmt::Display,
+    io::{self, Write},
+    sync::Arc,
+};
+
+use crate::{
+    DictionaryWireForm, ParseOptions, TypeDefinitionInstance,
+    type_attributes_instance::TypeAttributesInstance,
+};
+
+use super::{
+    ParseError, ParseErrorKind, ParseErrorPath, PathSegment, SerializeError, Value, ValueImpl,
+    constraints::check_rules,
+};
+
+/// A writer serializing a JSON array or dictionary to an `io::Write`, one element at a time.
+///
+/// Each element is validated against the items type, or the keys and values types, before it is
+/// written, so that arbitrarily large collections can be written without ever holding them in
+/// memory. The constraints, requirements and invariants between the entries of dictionaries are
+/// checked by [`finish`](Self::finish), from the few entries they reference.
+///
+/// Unlike parsing, writing neither applies defaults nor runs the validator of the collection
+/// type, as the collection is never held as a whole.
+pub struct ValueWriter<W, Id, FieldName: Ord> {
+    /// The underlying writer.
+    writer: W,
+
+    /// The type instance of the collection.
+    instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+
+    /// The parse options the elements are validated with.
+    options: ParseOptions,
+
+    /// The number of elements written so far.
+    len: usize,
+
+    /// The entries referenced by the entry rules of the dictionary, in the order of their keys.
+    entries: Vec<Option<ValueImpl<FieldName>>>,
+}
+
+/// An error that can occur when writing a collection with a [`ValueWriter`].
+#[derive(Debug, thiserror::Error)]
+pub enum ValueWriterError<Id: Display, FieldName: Ord + Display> {
+    /// The type is neither an array nor a dictionary type.
+    #[error("cannot write a {0} incrementally")]
+    NotACollection(&'static str),
+
+    /// The element is invalid.
+    #[error(transparent)]
+    Invalid(#[from] ParseError<Id, FieldName>),
+
+    /// The element could not be serialized.
+    #[error(transparent)]
+    Serialize(#[from] SerializeError),
+
+    /// The underlying writer failed.
+    #[error("cannot write value: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl<W: Write, Id: Display, FieldName: Ord + Display + Clone + Borrow<str>>
+    ValueWriter<W, Id, FieldName>
+{
+    /// Start writing a collection of a specified array or dictionary type instance.
+    pub fn new(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        writer: W,
+    ) -> Result<Self, ValueWriterError<Id, FieldName>> {
+        Self::with_options(instance, writer, &ParseOptions::default())
+    }
+
+    /// Start writing a collection of a specified array or dictionary type instance, validating its
+    /// elements with the specified parse options.
+    pub fn with_options(
+        instance: Arc<TypeDefinitionInstance<Id, FieldName>>,
+        mut writer: W,
+        options: &ParseOptions,
+    ) -> Result<Self, ValueWriterError<Id, FieldName>> {
+        let (start, rules) = match &instance.attributes {
+            TypeAttributesInstance::Array(_) => (b'[', 0),
+            TypeAttributesInstance::Dictionary(a) => (
+                match a.wire_form() {
+                    DictionaryWireForm::Object => b'{',
+                    DictionaryWireForm::Pairs => b'[',
+                },
+                a.compiled_rules().map_or(0, |rules| rules.keys().len()),
+            ),
+            attributes => return Err(ValueWriterError::NotACollection(attributes.kind())),
+        };
+
+        writer.write_all(&[start])?;
+
+        Ok(Self {
+            writer,
+            instance,
+            options: options.clone(),
+            len: 0,
+            entries: vec![None; rules],
+        })
+    }
+
+    /// Validate and write an item of the array.
+    ///
+    /// On error, nothing is written and the writer can still be used.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the type is not an array type.
+    pub fn write_item(
+        &mut self,
+        item: serde_json::Value,
+    ) -> Result<(), ValueWriterError<Id, FieldName>> {
+        let TypeAttributesInstance::Array(a) = &self.instance.attributes else {
+            panic!("not an array type");
+        };
+
+        self.check_len(PathSegment::ArrayIndex(self.len))?;
+
+        let item = Value::parse_for_with_options(a.items_type_id().clone(), item, &self.options)
+            .map_err(|err| self.nested(err, [PathSegment::ArrayIndex(self.len)], None))?
+            .to_json()?;
+
+        self.separator()?;
+        serde_json::to_writer(&mut self.writer, &item).map_err(io::Error::from)?;
+
+        Ok(())
+    }
+
+    /// Validate and write an entry of the dictionary.
+    ///
+    /// On error, nothing is written and the writer can still be used.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the type is not a dictionary type.
+    pub fn write_entry(
+        &mut self,
+        key: serde_json::Value,
+        value: serde_json::Value,
+    ) -> Result<(), ValueWriterError<Id, FieldName>> {
+        let TypeAttributesInstance::Dictionary(a) = &self.instance.attributes else {
+            panic!("not a dictionary type");
+        };
+
+        let (key_path, value_path) = match a.wire_form() {
+            DictionaryWireForm::Object => {
+                let key = match &key {
+                    serde_json::Value::String(key) => key.clone(),
+                    key => key.to_string(),
+                };
+
+                (
+                    vec![PathSegment::DictionaryKey(key.clone())],
+                    vec![PathSegment::DictionaryKey(key)],
+                )
+            }
+            DictionaryWireForm::Pairs => {
+                self.check_len(PathSegment::ArrayIndex(self.len))?;
+
+                (
+                    vec![
+                        PathSegment::ArrayIndex(self.len),
+                        PathSegment::ArrayIndex(0),
+                    ],
+                    vec![
+                        PathSegment::ArrayIndex(self.len),
+                        PathSegment::ArrayIndex(1),
+                    ],
+                )
+            }
+        };
+
+        let key = Value::parse_for_with_options(a.keys_type_id().clone(), key, &self.options)
+            .map_err(|err| {
+                self.nested(err, key_path, Some(ParseErrorKind::InvalidDictionaryKey))
+            })?;
+        let value = Value::parse_for_with_options(a.values_type_id().clone(), value, &self.options)
+            .map_err(|err| {
+                self.nested(
+                    err,
+                    value_path,
+                    Some(ParseErrorKind::InvalidDictionaryValue),
+                )
+            })?;
+
+        let json = value.to_json()?;
+        let pair_key = match a.wire_form() {
+            DictionaryWireForm::Object => None,
+            DictionaryWireForm::Pairs => Some(key.to_json()?),
+        };
+
+        if let (Some(rules), ValueImpl::Enum(k)) = (a.compiled_rules(), &key.value)
+            && let Some(index) = rules.index(k.borrow())
+        {
+            self.entries[index].get_or_insert(value.value);
+        }
+
+        self.separator()?;
+
+        match pair_key {
+            Some(key) => {
+                serde_json::to_writer(&mut self.writer, &[key, json]).map_err(io::Error::from)?;
+            }
+            None => {
+                serde_json::to_writer(&mut self.writer, &key.value.key_string())
+                    .map_err(io::Error::from)?;
+                self.writer.write_all(b":")?;
+                serde_json::to_writer(&mut self.writer, &json).map_err(io::Error::from)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finish writing the collection, and get the underlying writer back.
+    ///
+    /// Fails if the entries of a dictionary violate its constraints, requirements or invariants.
+    pub fn finish(mut self) -> Result<W, ValueWriterError<Id, FieldName>> {
+        if let TypeAttributesInstance::Dictionary(a) = &self.instance.attributes
+            && let Some(rules) = a.compiled_rules()
+        {
+            let entries: Vec<_> = self.entries.iter().map(Option::as_ref).collect();
+
+            check_rules(rules, &entries).map_err(|err| ParseError {
+                instance: Arc::clone(&self.instance),
+                path: ParseErrorPath::default(),
+                err,
+                location: None,
+            })?;
+        }
+
+        let end = match &self.instance.attributes {
+            TypeAttributesInstance::Dictionary(a)
+                if a.wire_form() == DictionaryWireForm::Object =>
+            {
+                b'}'
+            }
+            _ => b']',
+        };
+
+        self.writer.write_all(&[end])?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    /// Check that one more element does not exceed the maximum array length.
+    fn check_len(&self, segment: PathSegment) -> Result<(), ValueWriterError<Id, FieldName>> {
+        match self.options.limits.max_array_length {
+            Some(max) if self.len >= max => Err(ParseError {
+                instance: Arc::clone(&self.instance),
+                path: ParseErrorPath(vec![segment]),
+                err: ParseErrorKind::ArrayTooLong(max),
+                location: None,
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Write the separator before an element.
+    fn separator(&mut self) -> io::Result<()> {
+        if self.len > 0 {
+            self.writer.write_all(b",")?;
+        }
+
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Report the error of an element as an error of the collection.
+    fn nested(
+        &self,
+        err: ParseError<Id, FieldName>,
+        path: impl IntoIterator<Item = PathSegment>,
+        wrap: Option<fn(Box<ParseErrorKind>) -> ParseErrorKind>,
+    ) -> ValueWriterError<Id, FieldName> {
+        let mut path: Vec<_> = path.into_iter().collect();
+        path.extend(err.path.0);
+
+        ParseError {
+            instance: Arc::clone(&self.instance),
+            path: ParseErrorPath(path),
+            err: match wrap {
+                Some(wrap) => wrap(Box::new(err.err)),
+                None => err.err,
+            },
+            location: None,
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ValueWriter, ValueWriterError};
+    use crate::{
+        Comparison, DictionaryWireForm, EntryConstraint, ParseErrorKind, ParseLimits, ParseOptions,
+        PathSegment,
+        test_support::{TypeAttributes, TypeDefinitionRegistry, Value, registry, td},
+        type_attributes::{
+            ArrayTypeAttributes, DictionaryTypeAttributes, EnumTypeAttributes, NumberTypeAttributes,
+        },
+    };
+
+    fn levels_registry() -> TypeDefinitionRegistry {
+        registry([
+            td(
+                1,
+                "Stat",
+                TypeAttributes::Enum(
+                    EnumTypeAttributes::builder()
+                        .with_value("min")
+                        .with_value("max")
+                        .build()
+                        .unwrap(),
+                ),
+            ),
+            td(
+                2,
+                "Level",
+                TypeAttributes::Uint32(NumberTypeAttributes::builder().max(10).build().unwrap()),
+            ),
+            td(
+                3,
+                "Levels",
+                TypeAttributes::Array(ArrayTypeAttributes::new(2)),
+            ),
+            td(
+                4,
+                "Range",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(1, 2).with_constraint(EntryConstraint::new(
+                        "min",
+                        Comparison::LessOrEqual,
+                        "max",
+                    )),
+                ),
+            ),
+            td(
+                5,
+                "RangePairs",
+                TypeAttributes::Dictionary(
+                    DictionaryTypeAttributes::new(1, 2).with_wire_form(DictionaryWireForm::Pairs),
+                ),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_write_items() {
+        let levels = levels_registry().get(&3).unwrap().clone();
+        let mut writer = ValueWriter::new(levels.clone(), Vec::new()).unwrap();
+        writer.write_item(json!(1)).unwrap();
+
+        let err = writer.write_item(json!(11)).unwrap_err();
+        let ValueWriterError::Invalid(err) = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(err.path(), [PathSegment::ArrayIndex(1)]);
+        assert!(matches!(err.kind(), ParseErrorKind::InvalidUint32(_)));
+
+        writer.write_item(json!(2)).unwrap();
+        let text = writer.finish().unwrap();
+        assert_eq!(text, b"[1,2]");
+        assert!(Value::parse_str_for(levels, std::str::from_utf8(&text).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_write_items_limits() {
+        let levels = levels_registry().get(&3).unwrap().clone();
+        let options = ParseOptions::default().limits(ParseLimits::default().max_array_length(1));
+        let mut writer = ValueWriter::with_options(levels, Vec::new(), &options).unwrap();
+        writer.write_item(json!(1)).unwrap();
+        let err = writer.write_item(json!(2)).unwrap_err();
+        assert!(matches!(
+            err,
+            ValueWriterError::Invalid(err) if matches!(err.kind(), ParseErrorKind::ArrayTooLong(1))
+        ));
+    }
+
+    #[test]
+    fn test_write_entries() {
+        let range = levels_registry().get(&4).unwrap().clone();
+        let mut writer = ValueWriter::new(range, Vec::new()).unwrap();
+        writer.write_entry(json!("min"), json!(3)).unwrap();
+
+        let err = writer.write_entry(json!("avg"), json!(3)).unwrap_err();
+        let ValueWriterError::Invalid(err) = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(err.path(), [PathSegment::DictionaryKey("avg".to_owned())]);
+        assert!(matches!(
+            err.kind(),
+            ParseErrorKind::InvalidDictionaryKey(_)
+        ));
+
+        writer.write_entry(json!("max"), json!(5)).unwrap();
+        assert_eq!(writer.finish().unwrap(), br#"{"min":3,"max":5}"#);
+    }
+
+    #[test]
+    fn test_write_entries_constraints() {
+        let range = levels_registry().get(&4).unwrap().clone();
+        let mut writer = ValueWriter::new(range, Vec::new()).unwrap();
+        writer.write_entry(json!("min"), json!(5)).unwrap();
+        writer.write_entry(json!("max"), json!(3)).unwrap();
+        let err = writer.finish().unwrap_err();
+        assert!(matches!(
+            err,
+            ValueWriterError::Invalid(err)
+                if matches!(err.kind(), ParseErrorKind::ConstraintViolation(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_entries_pairs() {
+        let pairs = levels_registry().get(&5).unwrap().clone();
+        let mut writer = ValueWriter::new(pairs, Vec::new()).unwrap();
+        writer.write_entry(json!("max"), json!(7)).unwrap();
+        assert_eq!(writer.finish().unwrap(), br#"[["max",7]]"#);
+    }
+
+    #[test]
+    fn test_value_writer_not_a_collection() {
+        assert!(matches!(
+            ValueWriter::new(levels_registry().get(&2).unwrap().clone(), Vec::new()),
+            Err(ValueWriterError::NotACollection("uint32"))
+        ));
+    }
+}
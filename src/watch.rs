@@ -0,0 +1,250 @@
+//! Hot-reloading of type definitions from directory trees.
+
+use std::{
+    collections::BTreeSet,
+    fmt::Display,
+    hash::Hash,
+    path::{Path, PathBuf},
+    sync::{Arc, mpsc},
+};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    LoadError, LoadErrorKind, TypeDefinitionInstance, TypeDefinitionRegistry,
+    directory::{is_json_file, read_type_definitions},
+};
+
+/// The changes applied to a registry by a [`SchemaWatcher`].
+#[derive(Debug)]
+pub struct AppliedChanges<Id: Display, FieldName: Ord + Display + Clone> {
+    /// The new type instances.
+    pub(crate) instances: Vec<Arc<TypeDefinitionInstance<Id, FieldName>>>,
+
+    /// The errors, with the paths of the files they occurred in.
+    pub(crate) errors: Vec<LoadError<Id, FieldName>>,
+}
+
+impl<Id: Display, FieldName: Ord + Display + Clone> AppliedChanges<Id, FieldName> {
+    /// Get the new type instances, registered or replacing previous ones.
+    pub fn instances(&self) -> &[Arc<TypeDefinitionInstance<Id, FieldName>>] {
+        &self.instances
+    }
+
+    /// Get the errors, with the paths of the files they occurred in.
+    pub fn errors(&self) -> &[LoadError<Id, FieldName>] {
+        &self.errors
+    }
+
+    /// Check whether all the changed files were loaded and all their type definitions applied.
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// A watcher of the type definitions files of a directory tree.
+///
+/// The watcher records the files that change, and [`apply_changes`](Self::apply_changes) updates
+/// a registry accordingly, which notifies its [subscribers](TypeDefinitionRegistry::subscribe).
+/// The files are typically loaded first with [`load_from_dir`](TypeDefinitionRegistry::load_from_dir).
+#[derive(Debug)]
+pub struct SchemaWatcher {
+    /// The underlying file system watcher, which must be kept alive.
+    _watcher: notify::RecommendedWatcher,
+
+    /// The file system events.
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+}
+
+impl SchemaWatcher {
+    /// Start watching a directory tree.
+    pub fn new(path: impl AsRef<Path>) -> notify::Result<Self> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+
+        watcher.watch(path.as_ref(), RecursiveMode::Recursive)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Apply the changes of the files since the last call to a registry, without blocking.
+    ///
+    /// The type definitions of the changed files that are already registered are
+    /// [replaced](TypeDefinitionRegistry::replace) if they changed, and the others are
+    /// registered. The type definitions of deleted files are left registered, as other type
+    /// definitions may still reference them.
+    ///
+    /// The method returns the new type instances, and the errors with the paths of the files
+    /// they occurred in.
+    pub fn apply_changes<Id, FieldName>(
+        &self,
+        registry: &mut TypeDefinitionRegistry<Id, FieldName>,
+    ) -> AppliedChanges<Id, FieldName>
+    where
+        Id: Ord + Hash + Clone + Display + DeserializeOwned,
+        FieldName: Ord + Hash + Clone + Display + DeserializeOwned,
+    {
+        let mut paths = BTreeSet::new();
+        let mut errors = Vec::new();
+
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) => {
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                        paths.extend(event.paths.into_iter().filter(|path| is_json_file(path)));
+                    }
+                }
+                Err(err) => errors.push(LoadError {
+                    path: err.paths.first().cloned().unwrap_or_default(),
+                    kind: LoadErrorKind::Watch(err),
+                }),
+            }
+        }
+
+        let mut instances = Vec::new();
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+
+        for path in paths {
+            // The file may have been deleted or renamed since the event.
+            if !path.is_file() {
+                continue;
+            }
+
+            match read_type_definitions::<Id, FieldName>(&path) {
+                Ok(tds) => {
+                    for td in tds {
+                        match registry.by_id.get(&td.id) {
+                            Some(instance) if instance.to_definition() == td => {}
+                            Some(_) => changed.push((path.clone(), td)),
+                            None => added.push((path.clone(), td)),
+                        }
+                    }
+                }
+                Err(kind) => errors.push(LoadError { path, kind }),
+            }
+        }
+
+        // The new type definitions are registered first, as the changed ones may reference them.
+        let sources: Vec<PathBuf> = added.iter().map(|(path, _)| path.clone()).collect();
+        let ids: Vec<Id> = added.iter().map(|(_, td)| td.id.clone()).collect();
+        let outcome = registry.register(added.into_iter().map(|(_, td)| td));
+
+        for (td, err) in outcome.failed {
+            let path = ids
+                .iter()
+                .position(|id| *id == td.id)
+                .map(|index| sources[index].clone())
+                .unwrap_or_default();
+
+            errors.push(LoadError {
+                path,
+                kind: LoadErrorKind::Registration {
+                    id: td.id,
+                    name: td.name,
+                    err,
+                },
+            });
+        }
+
+        instances.extend(outcome.registered);
+
+        for (path, td) in changed {
+            match registry.replace(td) {
+                Ok(replaced) => instances.extend(replaced),
+                Err((td, err)) => errors.push(LoadError {
+                    path,
+                    kind: LoadErrorKind::Registration {
+                        id: td.id,
+                        name: td.name,
+                        err,
+                    },
+                }),
+            }
+        }
+
+        AppliedChanges { instances, errors }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use serde_json::json;
+
+    use crate::{RegistryEvent, test_support::owned::TypeDefinitionRegistry};
+
+    use super::SchemaWatcher;
+
+    #[test]
+    fn test_schema_watcher() {
+        let root = std::env::temp_dir().join(format!("gameson-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+
+        // Files are replaced atomically, so that the watcher never reads them half-written.
+        let write = |name: &str, content: serde_json::Value| {
+            let tmp = root.join(format!("{name}.tmp"));
+            std::fs::write(&tmp, content.to_string()).unwrap();
+            std::fs::rename(tmp, root.join(name)).unwrap();
+        };
+
+        write(
+            "numbers.json",
+            json!([
+                { "id": 1, "name": "MyInt", "type": "int32", "attributes": {} },
+                { "id": 2, "name": "MyIntArray", "type": "array", "attributes": { "items_type_id": 1 } },
+            ]),
+        );
+
+        let mut registry = TypeDefinitionRegistry::default();
+        assert!(registry.load_from_dir(&root).unwrap().is_success());
+
+        let watcher = SchemaWatcher::new(&root).unwrap();
+        let events = registry.subscribe();
+
+        write(
+            "numbers.json",
+            json!([
+                { "id": 1, "name": "MyInt", "type": "int32", "attributes": { "max": 10 } },
+                { "id": 2, "name": "MyIntArray", "type": "array", "attributes": { "items_type_id": 1 } },
+            ]),
+        );
+        write(
+            "strings.json",
+            json!({ "id": 3, "name": "MyString", "type": "string", "attributes": {} }),
+        );
+
+        // File system events are delivered asynchronously.
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut names = Vec::new();
+
+        while names.len() < 3 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+
+            let changes = watcher.apply_changes(&mut registry);
+            assert!(changes.is_success(), "{:?}", changes.errors());
+            names.extend(changes.instances().iter().map(|inst| inst.name.clone()));
+        }
+
+        std::fs::remove_dir_all(&root).unwrap();
+
+        names.sort();
+        assert_eq!(names, vec!["MyInt", "MyIntArray", "MyString"]);
+        assert_eq!(
+            registry.by_name["MyIntArray"].to_string(),
+            "MyIntArray(2): array(MyInt(1): int32(..10))"
+        );
+        assert_eq!(
+            events
+                .try_iter()
+                .filter(|event| matches!(event, RegistryEvent::Replaced { .. }))
+                .count(),
+            2
+        );
+    }
+}